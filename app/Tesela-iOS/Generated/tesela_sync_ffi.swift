@@ -5417,7 +5417,9 @@ public func presenceSeal(groupKey: Data, groupId: Data, inner: Data) -> Data  {
  * WebSocket — the pure-FFI mirror of `tesela-server::presence_relay::connect`.
  * The Swift transport sets them on its `URLSessionWebSocketTask` request
  * (CF rebuilds the canonical from `x-tesela-original-path`, so the signed
- * path is `/groups/{hex}/presence/ws` with empty query + empty body hash).
+ * path is `/groups/{hex}/presence/ws` with an empty body hash). The signed
+ * query is `device={device_hex}`, which the transport MUST append to the
+ * upgrade URL verbatim — the relay takes the socket's identity from it.
  *
  * CRITICAL — two distinct nonces + two distinct keys, never crossed:
  * the MAC nonce here is a FRESH 16 random bytes (NOT the 24-byte AEAD nonce
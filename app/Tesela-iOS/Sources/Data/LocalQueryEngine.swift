@@ -487,7 +487,7 @@ enum LocalQueryEngine {
         var i = 0
         func isWordByte(_ b: UInt8) -> Bool {
             (b >= 48 && b <= 57) || (b >= 65 && b <= 90) || (b >= 97 && b <= 122)
-                || b == UInt8(ascii: "_") || b == UInt8(ascii: "-")
+                || b == UInt8(ascii: "_") || b == UInt8(ascii: "-") || b == UInt8(ascii: ".")
         }
         func isSpaceByte(_ b: UInt8) -> Bool {
             b == 0x20 || (b >= 0x09 && b <= 0x0D)
@@ -769,6 +769,18 @@ enum LocalQueryEngine {
                 return nil
             }
 
+            // Dotted traversal (`project.status:x`) and `has-backlink-from:`
+            // need the relation index only the Rust side loads; here the
+            // key falls back to a literal property lookup, so flag it.
+            if key == "has-backlink-from" || key.contains(".") {
+                recordDrop(
+                    keySpanned.start,
+                    keySpanned.end,
+                    rawSlice(keySpanned.start, keySpanned.end),
+                    "unsupported predicate '\(key)': relation traversal is only evaluated by the server"
+                )
+            }
+
             // Legacy `tag-in:a,b,c` — whitespace-tolerant comma list on
             // the stripped key, mirroring `parse_comma_list_until_whitespace`.
             if key.hasSuffix("-in"), peek == .colon {
@@ -9,7 +9,8 @@ import Combine
 /// It byte-matches the desktop on the wire:
 ///   • the MAC-signed upgrade GET — five `x-tesela-*` headers computed by the
 ///     pure FFI `presenceWsHeaders` (fresh 16-byte MAC nonce + unix-seconds ts
-///     + HMAC over the canonical `GET\n/groups/{hex}/presence/ws\n\n…` request),
+///     + HMAC over the canonical `GET\n/groups/{hex}/presence/ws\ndevice={hex}\n…`
+///     request, so the upgrade URL carries the same `device=` query),
 ///     regenerated on EVERY (re)connect so the replay window never goes stale;
 ///   • outbound: a raw `b"PRES"++json` frame is AEAD-sealed via `presenceSeal`
 ///     (XChaCha20-Poly1305 + postcard `OuterPayload`) then sent as a binary
@@ -124,7 +125,12 @@ final class PresenceRelaySocket: ObservableObject {
             scheduleReconnect()
             return
         }
-        var request = URLRequest(url: url)
+        // The relay takes the socket's identity from the signed `device`
+        // query (the FFI signs exactly `device={deviceHex}`), not the header.
+        guard var components = URLComponents(url: url, resolvingAgainstBaseURL: false) else { return }
+        components.query = "device=\(headers.deviceHex)"
+        guard let signedURL = components.url else { return }
+        var request = URLRequest(url: signedURL)
         request.setValue(headers.groupHex, forHTTPHeaderField: "x-tesela-group")
         request.setValue(headers.deviceHex, forHTTPHeaderField: "x-tesela-device")
         request.setValue(headers.nonceB64, forHTTPHeaderField: "x-tesela-nonce")
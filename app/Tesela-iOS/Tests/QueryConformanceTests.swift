@@ -34,6 +34,9 @@ final class QueryConformanceTests: XCTestCase {
         let block: FixtureBlock
         let expect: Bool
         let expectDiagnostics: Bool?
+        /// Relation traversal the client can't evaluate: requires an
+        /// unsupported-predicate parser diagnostic.
+        let clientDiagnostics: Bool?
         /// L5 optional registry: property name → value_type. Absent ⇒ the
         /// registry-free heuristic; present ⇒ the typed matcher.
         let propertyTypes: [String: String]?
@@ -112,6 +115,12 @@ final class QueryConformanceTests: XCTestCase {
             if c.expectDiagnostics == true {
                 XCTAssertFalse(parsed.diagnostics.isEmpty, "case \(c.name) expected parser diagnostics")
             }
+            if c.clientDiagnostics == true {
+                XCTAssertTrue(
+                    parsed.diagnostics.contains { $0.hint.hasPrefix("unsupported predicate") },
+                    "case \(c.name) expected an unsupported-predicate diagnostic"
+                )
+            }
             // L5: build the lowercased name → value_type registry; absent ⇒ empty
             // ⇒ the registry-free heuristic (existing cases unchanged).
             var types: [String: String] = [:]
@@ -16,6 +16,7 @@
 import {
   handleRegister,
   handleGetRegistration,
+  handleRekey,
   handlePutOp,
   handleGetOps,
   handlePostAck,
@@ -172,6 +173,8 @@ export class GroupDO implements DurableObject {
           return await handleRegister(this, req);
         case "GET /registration":
           return await handleGetRegistration(this, req);
+        case "POST /rekey":
+          return await handleRekey(this, req);
         case "PUT /ops":
           return await handlePutOp(this, req);
         case "GET /ops":
@@ -242,6 +245,16 @@ export class GroupDO implements DurableObject {
       -- is a routing identifier, NOT note content — storing it keeps the
       -- relay zero-knowledge. On a PUT /ops the DO pushes a
       -- content-available wake to the group's OTHER tokens (P3c).
+      -- Auth keys from earlier group-key epochs (key rotation, CF parity
+      -- for the Rust relay's relay_retired_auth_keys). The MAC gate
+      -- accepts these for GETs only, so a member that hasn't applied the
+      -- rotation yet can still fetch the rotation record but can't
+      -- deposit under a retired epoch.
+      CREATE TABLE IF NOT EXISTS retired_auth_keys (
+        auth_key BLOB PRIMARY KEY,
+        retired_at INTEGER NOT NULL
+      );
+
       CREATE TABLE IF NOT EXISTS device_tokens (
         device_id BLOB PRIMARY KEY,
         apns_token TEXT NOT NULL,
@@ -293,6 +306,48 @@ export class GroupDO implements DurableObject {
     return { outcome: "inserted" };
   }
 
+  /** Group key rotation: swap to the new epoch's auth_key + intent and
+   *  retire the old auth_key (read-only in the MAC gate). Returns false
+   *  when the group isn't registered. Re-submitting the current key is a
+   *  no-op. Mirrors the Rust `Store::rekey_group`; like the Rust `rekey`
+   *  handler it also closes live presence sockets, which were all opened
+   *  under the retired key. */
+  rekeyRegistration(auth_key: Uint8Array, registered_at: number, intent: Uint8Array): boolean {
+    const existing = this.getRegistration();
+    if (!existing) return false;
+    if (bytesEq(existing.auth_key, auth_key)) return true;
+    this.state.storage.sql.exec(
+      "INSERT OR IGNORE INTO retired_auth_keys (auth_key, retired_at) VALUES (?, ?)",
+      existing.auth_key.buffer,
+      registered_at,
+    );
+    for (const ws of this.state.getWebSockets()) {
+      try {
+        ws.close(1008, "auth key rotated");
+      } catch {
+        // already closing/closed
+      }
+    }
+    this.wsClients.clear();
+    this.state.storage.sql.exec(
+      "UPDATE registration SET auth_key = ?, registered_at = ?, intent = ? WHERE id = 1",
+      auth_key.buffer,
+      registered_at,
+      intent.buffer,
+    );
+    return true;
+  }
+
+  /** Auth keys from earlier key epochs, newest first. */
+  retiredAuthKeys(): Uint8Array[] {
+    return this.state.storage.sql
+      .exec<{ auth_key: ArrayBuffer }>(
+        "SELECT auth_key FROM retired_auth_keys ORDER BY retired_at DESC",
+      )
+      .toArray()
+      .map((row) => new Uint8Array(row.auth_key));
+  }
+
   deleteRegistration(): void {
     // Hijack recovery must also sever in-flight presence: a revoked group must
     // not keep relaying cursors among the old members until they idle out. New
@@ -310,6 +365,7 @@ export class GroupDO implements DurableObject {
     this.state.storage.sql.exec("DELETE FROM device_seen");
     this.state.storage.sql.exec("DELETE FROM snapshots");
     this.state.storage.sql.exec("DELETE FROM group_meta");
+    this.state.storage.sql.exec("DELETE FROM retired_auth_keys");
   }
 
   insertOp(from_device: Uint8Array, ts: number, payload: Uint8Array): { seq: number; ts: number } {
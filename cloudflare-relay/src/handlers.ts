@@ -123,6 +123,13 @@ interface MacContext {
    *  was supplied. Optional because the Rust MAC gate does NOT require it
    *  — it's read opportunistically (e.g. for device-seen touch). */
   device_id?: Uint8Array;
+  /** The `device` query parameter, IF present + well-formed. Unlike the
+   *  header it is part of the signed canonical request, so presence takes
+   *  its socket identity from here. Mirrors the Rust `MacAuth::device`. */
+  signed_device_id?: Uint8Array;
+  /** The MAC verified only against a retired auth key (GETs only).
+   *  Mirrors the Rust `MacAuth::retired_key`. */
+  retired_key: boolean;
   ok: true;
 }
 
@@ -164,14 +171,80 @@ async function verifyMac(self: GroupDO, req: Request, bodyBytes: Uint8Array): Pr
 
   const expected = await hmacSha256(reg.auth_key, canonical);
   const given = fromB64(mac);
-  if (!constantTimeEq(expected, given)) {
-    return json({ error: "mac mismatch" }, 401);
+  const retired_key = !constantTimeEq(expected, given);
+  if (retired_key) {
+    // Key rotation: GETs also verify against retired auth keys, so a
+    // member still on the previous epoch can read (and pick up the
+    // rotation record) but not write. Mirrors the Rust mac_gate.
+    let readOnlyOk = false;
+    if (req.method === "GET") {
+      for (const key of self.retiredAuthKeys()) {
+        if (constantTimeEq(await hmacSha256(key, canonical), given)) {
+          readOnlyOk = true;
+          break;
+        }
+      }
+    }
+    if (!readOnlyOk) return json({ error: "mac mismatch" }, 401);
   }
 
   // Read the device id opportunistically — only if present + well-formed.
   const deviceHex = req.headers.get("x-tesela-device");
   const device_id = deviceHex && isHex(deviceHex, 16) ? fromHex(deviceHex) : undefined;
-  return { device_id, ok: true };
+  const signedHex = new URLSearchParams(originalQuery).get("device");
+  const signed_device_id = signedHex && isHex(signedHex, 16) ? fromHex(signedHex) : undefined;
+  return { device_id, signed_device_id, retired_key, ok: true };
+}
+
+// ─── /rekey ────────────────────────────────────────────────────────
+
+/** POST /groups/:id/rekey — group key rotation. MAC-gated with the
+ *  CURRENT auth key; same body as /register. The previous auth key stays
+ *  valid for GETs only. Mirrors the Rust `rekey` handler. */
+export async function handleRekey(self: GroupDO, req: Request): Promise<Response> {
+  const raw = new Uint8Array(await req.arrayBuffer());
+  const macCheck = await verifyMac(self, req, raw);
+  if (macCheck instanceof Response) return macCheck;
+
+  let body: RegisterBody | null = null;
+  try {
+    body = JSON.parse(new TextDecoder().decode(raw)) as RegisterBody;
+  } catch {
+    body = null;
+  }
+  if (!body || typeof body.auth_key_b64 !== "string" || typeof body.intent_b64 !== "string") {
+    return json({ error: "invalid body" }, 400);
+  }
+  const auth_key = fromB64(body.auth_key_b64);
+  const intent = fromB64(body.intent_b64);
+  if (auth_key.length !== 32) return json({ error: "auth_key must be 32 bytes" }, 400);
+  if (intent.length !== 32) return json({ error: "intent must be 32 bytes" }, 400);
+  let disc: Uint8Array | undefined;
+  if (typeof body.disc_b64 === "string") {
+    try {
+      disc = fromB64(body.disc_b64);
+    } catch {
+      return json({ error: "disc_b64 not base64" }, 400);
+    }
+    if (disc.length !== 32) return json({ error: "disc must be 32 bytes" }, 400);
+  }
+
+  if (!self.rekeyRegistration(auth_key, body.registered_at, intent)) {
+    return json({ error: "group not registered" }, 404);
+  }
+  if (disc) {
+    const originalPath = req.headers.get(ORIGINAL_PATH_HEADER) ?? "";
+    const groupIdHex = originalPath.match(/^\/groups\/([0-9a-f]{32})\/rekey$/)?.[1];
+    if (!groupIdHex) return json({ error: "internal: missing original path" }, 500);
+    const discoveryStub = self.env.DISCOVERY_DO.get(self.env.DISCOVERY_DO.idFromName("global"));
+    const upsertRes = await discoveryStub.fetch("https://do.internal/upsert", {
+      method: "POST",
+      headers: { "content-type": "application/json" },
+      body: JSON.stringify({ disc: toHex(disc), group_id: groupIdHex }),
+    });
+    if (!upsertRes.ok) return json({ error: "internal: discovery upsert failed" }, 500);
+  }
+  return json({ status: "ok" }, 200);
 }
 
 // ─── /ops (PUT + GET) ─────────────────────────────────────────────
@@ -380,9 +453,12 @@ export async function handleGetSnapshots(self: GroupDO, req: Request): Promise<R
  * is the wsClients key and the broadcast exclude-self identity, so a socket
  * without one is rejected rather than keyed by `undefined`.
  *
- * The MAC-verified X-Tesela-Device header IS the authoritative identity —
+ * The MAC-signed `device` query parameter IS the authoritative identity —
+ * the X-Tesela-Device header is outside the MAC and is NOT trusted here, and
  * there is NO separate plaintext first-frame handshake (an unauthenticated
- * first frame could spoof the key). The first data frame a client sends is
+ * first frame could spoof the key). Publishing frames is a write to every
+ * member, so an upgrade signed with a retired auth key is refused with 403
+ * (mirrors the Rust `presence_ws`). The first data frame a client sends is
  * simply the first sealed, opaque presence frame, which the DO broadcasts
  * verbatim (zero-knowledge) in webSocketMessage.
  */
@@ -396,10 +472,13 @@ export async function handlePresenceWs(self: GroupDO, req: Request): Promise<Res
   if ((req.headers.get("upgrade") ?? "").toLowerCase() !== "websocket") {
     return new Response("expected websocket upgrade", { status: 426 });
   }
-  if (!macCheck.device_id) {
+  if (macCheck.retired_key) {
+    return new Response("auth key retired", { status: 403 });
+  }
+  if (!macCheck.signed_device_id) {
     return new Response("device required", { status: 401 });
   }
-  const deviceHex = toHex(macCheck.device_id);
+  const deviceHex = toHex(macCheck.signed_device_id);
 
   const client = self.acceptPresenceSocket(deviceHex);
   return new Response(null, { status: 101, webSocket: client });
@@ -7,6 +7,7 @@
  *   PUT    /groups/:id/ops                  — deposit an envelope
  *   GET    /groups/:id/ops?since=N          — drain since seq N
  *   POST   /groups/:id/ack                  — record applied seq
+ *   POST   /groups/:id/rekey                — group key rotation (MAC-gated)
  *   DELETE /admin/groups/:id/register       — hijack recovery (admin token)
  *   GET    /                                — health check
  *
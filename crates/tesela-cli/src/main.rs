@@ -3,6 +3,7 @@ use clap::{Parser, Subcommand};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tesela_core::traits::plugin::PluginRegistry;
+use tesela_sync::SyncEngine;
 
 mod backfill_task;
 mod import_logseq;
@@ -12,6 +13,8 @@ mod mosaic_notes;
 mod recover_logseq_dates;
 mod repair_daily_tags;
 mod repair_garbled_blocks;
+mod sync_doctor;
+mod systemd_service;
 use tesela_core::{
     config::Config,
     daily,
@@ -102,13 +105,17 @@ enum Commands {
         /// Note ID or title
         query: String,
     },
-    /// Export a single note as html / text / markdown
+    /// Export a single note as html / text / markdown / opml
     ExportNote {
         /// Note ID or title
-        query: String,
-        /// Format: html, text, markdown
+        #[arg(required_unless_present = "dsl")]
+        query: Option<String>,
+        /// Format: html, text, markdown, opml
         #[arg(short, long, default_value = "markdown")]
         format: String,
+        /// Export a query's results instead of a note (OPML only)
+        #[arg(long, conflicts_with = "query")]
+        dsl: Option<String>,
     },
     /// Export the entire mosaic as a portable markdown directory
     Export {
@@ -123,6 +130,33 @@ enum Commands {
         #[arg(long)]
         attachments: bool,
     },
+    /// Export every page and block as JSON Lines (`-` for stdout)
+    ExportJson {
+        /// Output file
+        out: PathBuf,
+    },
+    /// Import JSON Lines produced by `export-json`, creating or updating notes
+    ImportJson {
+        /// Input file (`-` for stdin)
+        source: PathBuf,
+        /// Dry run — show what would be written without writing
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Publish a tagged or queried subset of the mosaic as a static HTML site
+    Publish {
+        /// Output directory (will be created)
+        out: PathBuf,
+        /// Publish pages carrying this tag (default: `public`)
+        #[arg(long, conflicts_with = "query")]
+        tag: Option<String>,
+        /// Publish the pages a query matches instead, e.g. "tag:blog"
+        #[arg(long)]
+        query: Option<String>,
+        /// Site title shown on every page
+        #[arg(long, default_value = "Tesela")]
+        title: String,
+    },
     /// Back up the mosaic to a timestamped, manifest-validated archive
     Backup {
         /// External output directory (defaults to <mosaic>/.tesela/backups/).
@@ -200,6 +234,21 @@ enum Commands {
         #[arg(long)]
         dry_run: bool,
     },
+    /// Import an OPML outline as a new note
+    ImportOpml {
+        /// Path to the `.opml` file
+        source: PathBuf,
+        /// Note title (defaults to the OPML head title, then the file name)
+        #[arg(long)]
+        title: Option<String>,
+        /// Tags (comma-separated)
+        #[arg(short, long)]
+        tags: Option<String>,
+        /// Keep the outline's `bid` attributes as block ids (for importing
+        /// an export into a different mosaic)
+        #[arg(long)]
+        keep_ids: bool,
+    },
     /// Add #Task to every status-bearing block that lacks it (dry-run unless --apply)
     BackfillTask {
         /// Actually write the tags. Default: dry-run — summary + per-note rollup.
@@ -236,6 +285,16 @@ enum Commands {
         #[arg(long)]
         apply: bool,
     },
+    /// Multi-device sync tools
+    Sync {
+        #[command(subcommand)]
+        command: SyncCommands,
+    },
+    /// View or restore a note as of a past moment; prune stored versions
+    History {
+        #[command(subcommand)]
+        command: HistoryCommands,
+    },
     /// Restore a mosaic from a backup
     Restore {
         /// Backup directory to restore from (e.g., .tesela/backups/backup-20260404-120000)
@@ -258,10 +317,83 @@ enum Commands {
         /// Shell (bash, zsh, fish, elvish, powershell)
         shell: clap_complete::Shell,
     },
-    /// Install tesela-server as a macOS LaunchAgent (runs on login)
-    Install,
-    /// Uninstall the tesela-server LaunchAgent
+    /// Install tesela-server as a login service (macOS LaunchAgent, Linux
+    /// systemd user unit)
+    Install {
+        /// Linux only: let a `tesela-server.socket` unit own the port and
+        /// start the server on first connection
+        #[arg(long)]
+        socket_activation: bool,
+    },
+    /// Uninstall the tesela-server login service
     Uninstall,
+    /// Show whether the tesela-server login service is installed and running
+    Status,
+}
+
+#[derive(Subcommand)]
+enum SyncCommands {
+    /// List every sync anomaly per note (twins, directory conflicts, causal
+    /// gaps, outbound strands) with its repair, and record the scan in the
+    /// incident log (dry-run unless --apply)
+    Doctor {
+        /// Run the offline repairs: collapse disjoint twins and re-arm stuck
+        /// snapshot catch-ups.
+        #[arg(long)]
+        apply: bool,
+        /// Print the anomalies as JSON.
+        #[arg(long)]
+        json: bool,
+        /// Also show this many recent incidents from the log.
+        #[arg(long, default_value_t = 10)]
+        history: usize,
+    },
+    /// Run the oplog retention sweep: drop ops every peer has acked, stale
+    /// parked ops and long-silent peers (policy from `[sync.retention]`)
+    Gc {
+        /// Report what would be reclaimed without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Override `evict_peers_after_days` (0 = never evict)
+        #[arg(long)]
+        evict_after_days: Option<u64>,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommands {
+    /// Print a note as it stood at a past moment
+    Show {
+        /// Note ID or title
+        note: String,
+        /// When: unix seconds, RFC 3339, `2026-10-13 14:00`, `last tuesday
+        /// 14:00`, `yesterday`, `3 hours ago`
+        at: String,
+    },
+    /// Revert a note (or one block and its children) to a past moment, as a
+    /// new change that syncs like any other edit
+    Restore {
+        /// Note ID or title
+        note: String,
+        /// When — same spellings as `history show`
+        at: String,
+        /// Restore only this block subtree (block UUID)
+        #[arg(long)]
+        block: Option<String>,
+    },
+    /// Thin and compress the stored version history (policy from
+    /// `[history]`) and report the space reclaimed
+    Prune {
+        /// Report what would be reclaimed without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 struct Ctx {
@@ -662,20 +794,257 @@ fn cmd_export_mosaic(mosaic: &Path, out: PathBuf, mode: String, attachments: boo
     Ok(())
 }
 
-async fn cmd_export_note(ctx: &Ctx, query: String, format: String) -> Result<()> {
-    let note = resolve_note(ctx, &query).await?;
+async fn cmd_export_json(ctx: &Ctx, out: PathBuf) -> Result<()> {
+    use tesela_core::export::json::export_mosaic_json;
+    use tesela_core::property::ValueType;
+    let types = ctx
+        .index
+        .get_all_property_defs()
+        .await
+        .context("Failed to load property definitions")?
+        .into_iter()
+        .map(|def| {
+            (
+                def.name.to_ascii_lowercase(),
+                ValueType::parse(&def.value_type),
+            )
+        })
+        .collect();
+    let outcome = if out.as_os_str() == "-" {
+        export_mosaic_json(&ctx.mosaic, &mut std::io::stdout().lock(), &types)?
+    } else {
+        let mut file = std::io::BufWriter::new(
+            std::fs::File::create(&out).with_context(|| format!("create {}", out.display()))?,
+        );
+        let outcome = export_mosaic_json(&ctx.mosaic, &mut file, &types)?;
+        std::io::Write::flush(&mut file)?;
+        outcome
+    };
+    eprintln!(
+        "Exported {} page{} and {} block{}",
+        outcome.page_count,
+        if outcome.page_count == 1 { "" } else { "s" },
+        outcome.block_count,
+        if outcome.block_count == 1 { "" } else { "s" },
+    );
+    Ok(())
+}
 
+async fn cmd_import_json(ctx: &Ctx, source: PathBuf, dry_run: bool) -> Result<()> {
+    use tesela_core::export::json::import_mosaic_json;
+    let notes = if source.as_os_str() == "-" {
+        import_mosaic_json(std::io::stdin().lock())?
+    } else {
+        let file =
+            std::fs::File::open(&source).with_context(|| format!("open {}", source.display()))?;
+        import_mosaic_json(std::io::BufReader::new(file))
+            .with_context(|| format!("parse {}", source.display()))?
+    };
+
+    let mut changed = Vec::new();
+    for note in &notes {
+        let path = ctx.mosaic.join("notes").join(format!("{}.md", note.id));
+        let existing = match std::fs::read_to_string(&path) {
+            Ok(existing) if existing == note.content => continue,
+            Ok(existing) => {
+                println!("  update {} ({} blocks)", note.id, note.block_count);
+                Some(existing)
+            }
+            Err(_) => {
+                println!("  create {} ({} blocks)", note.id, note.block_count);
+                None
+            }
+        };
+        changed.push((note, existing));
+    }
+    println!(
+        "{} of {} note{} {}",
+        changed.len(),
+        notes.len(),
+        if notes.len() == 1 { "" } else { "s" },
+        if dry_run { "would change" } else { "changed" }
+    );
+    if dry_run || changed.is_empty() {
+        return Ok(());
+    }
+
+    // Engine-only-writes: each note is recorded as the same NoteUpsert
+    // `tesela edit` uses. That upsert never deletes a block or rewrites a
+    // block's properties, so for a note that already exists the file is
+    // authoritative about absence: the diff against the current body
+    // supplies the explicit BlockDelete / BlockUpsert ops on top.
+    let (_lock, engine) = open_locked_engine(&ctx.mosaic).await?;
+    for (note, existing) in &changed {
+        let note_id = stable_uuid_from_slug(&note.id);
+        hydrate_note(&engine, note_id, &note.id, &note.content)
+            .await
+            .with_context(|| format!("Failed to import {}", note.id))?;
+        let Some(existing) = existing else {
+            continue;
+        };
+        let ops = tesela_sync::diff::diff_note_trees(
+            note_id,
+            &tesela_core::note_tree::parse_note(existing),
+            &tesela_core::note_tree::parse_note(&note.content),
+        );
+        for op in ops {
+            engine
+                .record_local(op)
+                .await
+                .map_err(|e| anyhow::anyhow!("import {}: {e}", note.id))?;
+        }
+    }
+    drop(engine);
+    for (note, _) in &changed {
+        if let Some(written) = ctx.store.get(&NoteId::new(&note.id)).await? {
+            ctx.index
+                .upsert_note(&written)
+                .await
+                .context("Failed to index note")?;
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_publish(
+    ctx: &Ctx,
+    out: PathBuf,
+    tag: Option<String>,
+    query: Option<String>,
+    title: String,
+) -> Result<()> {
+    use tesela_core::publish::{publish_site, PublishOptions, PublishSelection};
+    let selection = match (tag, query) {
+        (_, Some(dsl)) => PublishSelection::Query(dsl),
+        (Some(tag), None) => PublishSelection::Tag(tag.trim_start_matches('#').to_string()),
+        (None, None) => PublishSelection::default(),
+    };
+    let outcome = publish_site(
+        ctx.store.as_ref(),
+        ctx.index.as_ref(),
+        &out,
+        &PublishOptions {
+            selection,
+            site_title: title,
+        },
+    )
+    .await?;
+    println!(
+        "Published {} page{} and {} tag page{} → {}",
+        outcome.page_count,
+        if outcome.page_count == 1 { "" } else { "s" },
+        outcome.tag_page_count,
+        if outcome.tag_page_count == 1 { "" } else { "s" },
+        out.display()
+    );
+    println!(
+        "Attachments: {} file{}",
+        outcome.attachment_count,
+        if outcome.attachment_count == 1 {
+            ""
+        } else {
+            "s"
+        }
+    );
+    println!(
+        "Private: skipped {} page{}, dropped {} block{}, stripped {} propert{}",
+        outcome.private_pages_skipped,
+        if outcome.private_pages_skipped == 1 {
+            ""
+        } else {
+            "s"
+        },
+        outcome.private_blocks_dropped,
+        if outcome.private_blocks_dropped == 1 {
+            ""
+        } else {
+            "s"
+        },
+        outcome.stripped_property_count,
+        if outcome.stripped_property_count == 1 {
+            "y"
+        } else {
+            "ies"
+        }
+    );
+    Ok(())
+}
+
+async fn cmd_export_note(
+    ctx: &Ctx,
+    query: Option<String>,
+    format: String,
+    dsl: Option<String>,
+) -> Result<()> {
     let fmt = match format.as_str() {
         "html" => ExportFormat::Html,
         "text" | "txt" => ExportFormat::PlainText,
         "markdown" | "md" => ExportFormat::Markdown,
-        other => anyhow::bail!("Unknown format: {}. Use html, text, or markdown", other),
+        "opml" => ExportFormat::Opml,
+        other => anyhow::bail!(
+            "Unknown format: {}. Use html, text, markdown, or opml",
+            other
+        ),
     };
 
+    if let Some(dsl) = dsl {
+        if !matches!(fmt, ExportFormat::Opml) {
+            anyhow::bail!("--dsl exports query results as OPML; pass --format opml");
+        }
+        // Same page directory `POST /search/query/opml` resolves
+        // `[[Page]]` values against, read from the snapshot on disk.
+        let directory =
+            tesela_sync::LoroEngine::read_page_directory(&ctx.mosaic.join(".tesela").join("loro"))
+                .await;
+        let result = ctx
+            .index
+            .execute_query_with_context(
+                &tesela_core::query::parse_query(&dsl),
+                None,
+                None,
+                &tesela_sync::page_query_context(directory),
+            )
+            .await
+            .context("Query failed")?;
+        print!("{}", tesela_core::opml::query_result_to_opml(&dsl, &result));
+        return Ok(());
+    }
+
+    let query = query.expect("clap requires a note unless --dsl is given");
+    let note = resolve_note(ctx, &query).await?;
     print!("{}", export_note(&note, fmt));
     Ok(())
 }
 
+async fn cmd_import_opml(
+    ctx: &Ctx,
+    source: PathBuf,
+    title: Option<String>,
+    tags: Option<String>,
+    keep_ids: bool,
+) -> Result<()> {
+    use tesela_core::opml::{opml_to_note, OpmlImportOptions};
+    let raw =
+        std::fs::read_to_string(&source).with_context(|| format!("read {}", source.display()))?;
+    let imported = opml_to_note(&raw, OpmlImportOptions { keep_ids })
+        .with_context(|| format!("parse {}", source.display()))?;
+    let title = title
+        .or(imported.title)
+        .or_else(|| {
+            source
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .context("OPML has no title; pass --title")?;
+    println!(
+        "Importing {} block{} from {}",
+        imported.block_count,
+        if imported.block_count == 1 { "" } else { "s" },
+        source.display()
+    );
+    cmd_new(ctx, title, tags, Some(imported.body)).await
+}
+
 async fn cmd_backup(
     mosaic: &Path,
     output: Option<PathBuf>,
@@ -932,6 +1301,205 @@ async fn cmd_reindex(ctx: &Ctx) -> Result<()> {
     Ok(())
 }
 
+async fn cmd_sync_gc(
+    ctx: &Ctx,
+    dry_run: bool,
+    evict_after_days: Option<u64>,
+    json: bool,
+) -> Result<()> {
+    let config_path = ctx.mosaic.join(".tesela").join("config.toml");
+    let mut retention = if config_path.exists() {
+        Config::load(&config_path)
+            .context("Failed to read mosaic config")?
+            .sync
+            .retention
+    } else {
+        Default::default()
+    };
+    if let Some(days) = evict_after_days {
+        retention.evict_peers_after_days = days;
+    }
+    let policy = tesela_sync::RetentionPolicy::from_config(&retention);
+    let device = backfill_task::load_device_id(&ctx.mosaic);
+    let now = chrono::Utc::now().timestamp_millis();
+    let report = tesela_sync::run_retention_sweep(ctx.index.pool(), device, &policy, now, dry_run)
+        .await
+        .map_err(|e| anyhow::anyhow!("retention sweep: {e}"))?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    let verb = if dry_run {
+        "would reclaim"
+    } else {
+        "reclaimed"
+    };
+    println!("sync gc: {verb} {} row(s)", report.reclaimed_rows());
+    println!(
+        "  oplog:       {} deleted, {} remain",
+        report.oplog_rows_deleted, report.oplog_rows_remaining
+    );
+    println!(
+        "  parked ops:  {} deleted, {} remain",
+        report.parked_ops_deleted, report.parked_ops_remaining
+    );
+    println!("  stale peers: {} evicted", report.evicted_peers.len());
+    for peer in &report.evicted_peers {
+        println!("    {peer}");
+    }
+    if dry_run && report.reclaimed_rows() > 0 {
+        println!("\nDRY-RUN — re-run without --dry-run to delete.");
+    }
+    Ok(())
+}
+
+fn parse_history_at(at: &str) -> Result<chrono::DateTime<chrono::Utc>> {
+    tesela_core::history::parse_point_in_time(at, &chrono::Local::now())
+        .ok_or_else(|| anyhow::anyhow!("unrecognized point in time: {at:?}"))
+}
+
+async fn cmd_history_show(ctx: &Ctx, query: String, at: String) -> Result<()> {
+    let note = resolve_note(ctx, &query).await?;
+    let at = parse_history_at(&at)?;
+    let (_lock, engine) = open_locked_engine(&ctx.mosaic).await?;
+    let doc_id = engine
+        .resolve_note_doc_id(note.id.as_str())
+        .await
+        .map_err(|e| anyhow::anyhow!("resolve {}: {e}", note.id))?;
+    let past = engine
+        .note_at(doc_id, at.timestamp())
+        .await
+        .map_err(|e| anyhow::anyhow!("{e}"))?
+        .ok_or_else(|| anyhow::anyhow!("no history for '{}'", note.id))?;
+    eprintln!(
+        "{} as of {} ({} later change(s))",
+        note.id,
+        at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+        past.changes_since
+    );
+    print!("{}", past.content);
+    Ok(())
+}
+
+async fn cmd_history_restore(
+    ctx: &Ctx,
+    query: String,
+    at: String,
+    block: Option<String>,
+) -> Result<()> {
+    let note = resolve_note(ctx, &query).await?;
+    let at = parse_history_at(&at)?;
+    let block_id = block
+        .as_deref()
+        .map(|b| {
+            uuid::Uuid::parse_str(b)
+                .map(|u| *u.as_bytes())
+                .with_context(|| format!("Invalid block id: {b}"))
+        })
+        .transpose()?;
+    // Engine-only-writes: the revert is recorded as a Loro change, so it
+    // syncs instead of being reverted by the next materialize.
+    let (_lock, engine) = open_locked_engine(&ctx.mosaic).await?;
+    let doc_id = engine
+        .resolve_note_doc_id(note.id.as_str())
+        .await
+        .map_err(|e| anyhow::anyhow!("resolve {}: {e}", note.id))?;
+    let restored = engine
+        .restore_note(doc_id, at.timestamp(), block_id)
+        .await
+        .map_err(|e| anyhow::anyhow!("restore {}: {e}", note.id))?;
+    drop(engine);
+    if !restored {
+        println!(
+            "{} already matches that moment; nothing to restore.",
+            note.id
+        );
+        return Ok(());
+    }
+
+    if let Some(updated) = ctx
+        .store
+        .get(&note.id)
+        .await
+        .context("Failed to re-read note")?
+    {
+        ctx.index
+            .upsert_note(&updated)
+            .await
+            .context("Failed to reindex note")?;
+        if let Err(e) = ctx
+            .index
+            .record_version(&note.id, Some(&note.content), &updated.content, 200)
+            .await
+        {
+            tracing::warn!("Failed to record note version: {e}");
+        }
+        if let Err(e) = ctx.registry.dispatch_note_updated(&updated) {
+            tracing::warn!("Plugin hook on_note_updated failed: {}", e);
+        }
+    }
+    println!(
+        "Restored {} to {}",
+        note.id,
+        at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+    );
+    Ok(())
+}
+
+async fn cmd_history_prune(ctx: &Ctx, dry_run: bool, json: bool) -> Result<()> {
+    let config_path = ctx.mosaic.join(".tesela").join("config.toml");
+    let history = if config_path.exists() {
+        Config::load(&config_path)
+            .context("Failed to read mosaic config")?
+            .history
+    } else {
+        Default::default()
+    };
+    let policy = tesela_core::db::VersionRetention::from_config(&history);
+    let report = ctx
+        .index
+        .prune_versions(&policy, chrono::Utc::now(), dry_run)
+        .await
+        .context("Failed to prune version history")?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    let verb = if dry_run {
+        "would reclaim"
+    } else {
+        "reclaimed"
+    };
+    println!(
+        "history prune: {verb} {} across {} note(s)",
+        human_bytes(report.reclaimed_bytes()),
+        report.notes
+    );
+    println!(
+        "  versions: {} deleted, {} remain ({} stored as deltas)",
+        report.versions_deleted, report.versions_remaining, report.versions_compressed
+    );
+    println!(
+        "  text:     {} -> {}",
+        human_bytes(report.bytes_before),
+        human_bytes(report.bytes_after)
+    );
+    if dry_run && report.reclaimed_bytes() > 0 {
+        println!("\nDRY-RUN — re-run without --dry-run to prune.");
+    }
+    Ok(())
+}
+
+fn human_bytes(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1 << 20 => format!("{:.1} MiB", b as f64 / (1 << 20) as f64),
+        b if b >= 1 << 10 => format!("{:.1} KiB", b as f64 / (1 << 10) as f64),
+        b => format!("{b} B"),
+    }
+}
+
 const LAUNCHD_LABEL: &str = "com.tesela.server";
 const PLIST_TEMPLATE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
 <!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
@@ -964,7 +1532,7 @@ fn plist_path() -> Result<std::path::PathBuf> {
         .join(format!("{}.plist", LAUNCHD_LABEL)))
 }
 
-async fn cmd_install(mosaic: PathBuf) -> Result<()> {
+async fn cmd_install(mosaic: PathBuf, socket_activation: bool) -> Result<()> {
     // Find tesela-server binary next to the current executable
     let exe_dir = std::env::current_exe()
         .context("Cannot determine executable path")?
@@ -980,6 +1548,13 @@ async fn cmd_install(mosaic: PathBuf) -> Result<()> {
         );
     }
 
+    if cfg!(target_os = "linux") {
+        return systemd_service::install(&server_bin, &mosaic, socket_activation);
+    }
+    if socket_activation {
+        anyhow::bail!("--socket-activation is only supported with systemd (Linux)");
+    }
+
     let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Cannot find home directory"))?;
     let log_path = home
         .join("Library")
@@ -1019,6 +1594,9 @@ async fn cmd_install(mosaic: PathBuf) -> Result<()> {
 }
 
 async fn cmd_uninstall() -> Result<()> {
+    if cfg!(target_os = "linux") {
+        return systemd_service::uninstall();
+    }
     let plist_file = plist_path()?;
     if !plist_file.exists() {
         println!("LaunchAgent plist not found — already uninstalled.");
@@ -1041,6 +1619,28 @@ async fn cmd_uninstall() -> Result<()> {
     Ok(())
 }
 
+async fn cmd_status() -> Result<()> {
+    if cfg!(target_os = "linux") {
+        return systemd_service::status();
+    }
+    let plist_file = plist_path()?;
+    if !plist_file.exists() {
+        println!("tesela-server is not installed (run `tesela install`).");
+        return Ok(());
+    }
+    println!("Plist:   {}", plist_file.display());
+    let output = std::process::Command::new("launchctl")
+        .args(["list", LAUNCHD_LABEL])
+        .output()
+        .context("Failed to run launchctl")?;
+    if output.status.success() {
+        print!("{}", String::from_utf8_lossy(&output.stdout));
+    } else {
+        println!("  not loaded (launchctl load -w {})", plist_file.display());
+    }
+    Ok(())
+}
+
 /// Resolve a note by ID or title.
 async fn resolve_note(ctx: &Ctx, query: &str) -> Result<tesela_core::Note> {
     let id = NoteId::new(query);
@@ -1086,16 +1686,19 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    // Handle uninstall without a mosaic
+    // Handle uninstall / status without a mosaic
     if matches!(cli.command, Commands::Uninstall) {
         return cmd_uninstall().await;
     }
+    if matches!(cli.command, Commands::Status) {
+        return cmd_status().await;
+    }
 
     let mosaic = resolve_mosaic(cli.mosaic)?;
 
     // Handle install — needs mosaic path but not a full Ctx
-    if matches!(cli.command, Commands::Install) {
-        return cmd_install(mosaic).await;
+    if let Commands::Install { socket_activation } = cli.command {
+        return cmd_install(mosaic, socket_activation).await;
     }
 
     // Handle backup — needs mosaic path but not a full Ctx
@@ -1190,13 +1793,27 @@ async fn main() -> Result<()> {
         return repair_garbled_blocks::run(&mosaic, apply).await;
     }
 
+    // Sync health — opens the Loro engine directly (locks the mosaic).
+    if let Commands::Sync {
+        command:
+            SyncCommands::Doctor {
+                apply,
+                json,
+                history,
+            },
+    } = cli.command
+    {
+        return sync_doctor::run(&mosaic, apply, json, history).await;
+    }
+
     let ctx = Ctx::new(mosaic).await?;
 
     match cli.command {
         Commands::Init { .. }
         | Commands::Completions { .. }
-        | Commands::Install
+        | Commands::Install { .. }
         | Commands::Uninstall
+        | Commands::Status
         | Commands::Backup { .. }
         | Commands::BackupKeygen
         | Commands::BackupVerify { .. }
@@ -1210,7 +1827,27 @@ async fn main() -> Result<()> {
         | Commands::BackfillTask { .. }
         | Commands::RecoverLogseqDates { .. }
         | Commands::RepairDailyTags { .. }
-        | Commands::RepairGarbledBlocks { .. } => unreachable!(),
+        | Commands::RepairGarbledBlocks { .. }
+        | Commands::Sync {
+            command: SyncCommands::Doctor { .. },
+        } => unreachable!(),
+        Commands::Sync {
+            command:
+                SyncCommands::Gc {
+                    dry_run,
+                    evict_after_days,
+                    json,
+                },
+        } => cmd_sync_gc(&ctx, dry_run, evict_after_days, json).await?,
+        Commands::History {
+            command: HistoryCommands::Show { note, at },
+        } => cmd_history_show(&ctx, note, at).await?,
+        Commands::History {
+            command: HistoryCommands::Restore { note, at, block },
+        } => cmd_history_restore(&ctx, note, at, block).await?,
+        Commands::History {
+            command: HistoryCommands::Prune { dry_run, json },
+        } => cmd_history_prune(&ctx, dry_run, json).await?,
         Commands::New {
             title,
             tags,
@@ -1222,7 +1859,23 @@ async fn main() -> Result<()> {
         Commands::Search { query, limit } => cmd_search(&ctx, query, limit).await?,
         Commands::Daily { date } => cmd_daily(&ctx, date).await?,
         Commands::Links { query } => cmd_links(&ctx, query).await?,
-        Commands::ExportNote { query, format } => cmd_export_note(&ctx, query, format).await?,
+        Commands::ExportNote { query, format, dsl } => {
+            cmd_export_note(&ctx, query, format, dsl).await?
+        }
+        Commands::ImportOpml {
+            source,
+            title,
+            tags,
+            keep_ids,
+        } => cmd_import_opml(&ctx, source, title, tags, keep_ids).await?,
+        Commands::ExportJson { out } => cmd_export_json(&ctx, out).await?,
+        Commands::ImportJson { source, dry_run } => cmd_import_json(&ctx, source, dry_run).await?,
+        Commands::Publish {
+            out,
+            tag,
+            query,
+            title,
+        } => cmd_publish(&ctx, out, tag, query, title).await?,
         Commands::Reindex => cmd_reindex(&ctx).await?,
         Commands::Tui => {
             let exe_dir = std::env::current_exe()
@@ -0,0 +1,159 @@
+//! `tesela sync doctor`: the offline face of `GET /sync/health`.
+//!
+//! Opens the Loro engine over the mosaic (locking it, like the other repair
+//! commands), lists every sync anomaly per note with its severity and the
+//! repair that clears it, and records the scan in the incident log the
+//! server keeps (`.tesela/sync_incidents.json`). `--apply` runs the repairs
+//! that work without a relay: collapsing garbled blocks' twins (as
+//! `repair-garbled-blocks --apply` does), and re-arming
+//! causal gaps so the server's next relay tick retries their snapshot
+//! catch-up. Directory conflicts are listed for a manual fix.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use tesela_sync::{
+    AnomalyKind, AnomalySeverity, Hlc, IncidentLog, LoroEngine, SyncAnomaly, SyncRepair,
+};
+
+use crate::backfill_task::{acquire_mosaic_lock, load_device_id};
+
+fn severity_label(severity: AnomalySeverity) -> &'static str {
+    match severity {
+        AnomalySeverity::Error => "ERROR",
+        AnomalySeverity::Warning => "WARN ",
+        AnomalySeverity::Info => "INFO ",
+    }
+}
+
+fn action_label(anomaly: &SyncAnomaly) -> &'static str {
+    match (anomaly.repair, anomaly.kind) {
+        (Some(SyncRepair::RepairGarbledBlocks), _) => "repair_garbled_blocks (--apply)",
+        (Some(SyncRepair::SnapshotCatchup), _) => "snapshot_catchup (--apply re-arms it)",
+        (None, AnomalyKind::DirectoryConflict) => "manual: delete or rename one of the pages",
+        (None, _) => "none — heals on the next relay tick",
+    }
+}
+
+/// CLI entry.
+pub async fn run(mosaic: &Path, apply: bool, json: bool, history: usize) -> Result<()> {
+    let _lock = acquire_mosaic_lock(mosaic).context(
+        "could not lock the mosaic — is tesela-server (or the desktop app) running on it? \
+         Ask the running server instead (GET /sync/health), or stop it first (single-writer).",
+    )?;
+
+    let device = load_device_id(mosaic);
+    let snapshot_dir = mosaic.join(".tesela").join("loro");
+    let notes_dir = mosaic.join("notes");
+    let hlc = Arc::new(Hlc::new(device));
+    let engine = LoroEngine::with_dirs(device, hlc, snapshot_dir, Some(notes_dir))
+        .await
+        .map_err(|e| anyhow::anyhow!("open loro engine: {e}"))?;
+    let log = IncidentLog::for_mosaic(mosaic);
+
+    let anomalies = engine.sync_anomalies().await;
+    log.record_scan(&anomalies)
+        .await
+        .map_err(|e| anyhow::anyhow!("record incidents: {e}"))?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&anomalies)?);
+    } else if anomalies.is_empty() {
+        println!("sync doctor: no sync anomalies — this mosaic is healthy.");
+    } else {
+        println!("sync doctor: {} anomaly(ies):", anomalies.len());
+        for a in &anomalies {
+            println!(
+                "  {} {:<18} {} ({})",
+                severity_label(a.severity),
+                format!("{:?}", a.kind),
+                a.slug.as_deref().unwrap_or("-"),
+                a.note_id
+            );
+            println!("        {}", a.detail);
+            println!("        action: {}", action_label(a));
+        }
+    }
+
+    if history > 0 && !json {
+        let incidents = log.recent(history).await;
+        println!("\nrecent incidents ({}):", incidents.len());
+        for i in &incidents {
+            let at = chrono::DateTime::from_timestamp_millis(i.at_ms)
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            println!(
+                "  {at} {:<9} {:?} on {} — {}",
+                format!("{:?}", i.event),
+                i.kind,
+                i.slug.as_deref().unwrap_or(&i.note_id),
+                i.detail
+            );
+        }
+    }
+
+    let repairable = anomalies.iter().any(|a| a.repair.is_some());
+    if !apply {
+        if repairable && !json {
+            println!("\nDRY-RUN — re-run with --apply to run the repairs above.");
+        }
+        return Ok(());
+    }
+
+    if anomalies
+        .iter()
+        .any(|a| a.kind == AnomalyKind::GarbledBlocks)
+    {
+        let healed = engine.heal_disjoint_twins().await;
+        for a in anomalies
+            .iter()
+            .filter(|a| a.kind == AnomalyKind::GarbledBlocks)
+        {
+            let blocks = healed
+                .iter()
+                .filter(|(id, _)| hex::encode(id) == a.note_id)
+                .count();
+            log.record_repair(
+                a,
+                SyncRepair::RepairGarbledBlocks,
+                format!("collapsed {blocks} twin block(s) (sync doctor)"),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!("record repair: {e}"))?;
+        }
+        println!("sync doctor: collapsed {} twin block(s).", healed.len());
+    }
+    for pending in engine.pending_import_notes().await {
+        let hex_id = hex::encode(pending.note_id);
+        let Some(a) = anomalies
+            .iter()
+            .find(|a| a.kind == AnomalyKind::CausalGap && a.note_id == hex_id)
+        else {
+            continue;
+        };
+        if engine.rearm_snapshot_catchup(pending.note_id).await {
+            log.record_repair(
+                a,
+                SyncRepair::SnapshotCatchup,
+                "re-armed; the server's next relay tick retries the catch-up (sync doctor)".into(),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!("record repair: {e}"))?;
+            println!(
+                "sync doctor: re-armed the snapshot catch-up for {}.",
+                a.slug.as_deref().unwrap_or(&a.note_id)
+            );
+        }
+    }
+    let remaining = engine.sync_anomalies().await;
+    log.record_scan(&remaining)
+        .await
+        .map_err(|e| anyhow::anyhow!("record incidents: {e}"))?;
+    println!(
+        "sync doctor: {} anomaly(ies) remain (causal gaps clear once the server's relay \
+         catch-up lands).",
+        remaining.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,276 @@
+//! `tesela install` / `uninstall` / `status` on Linux: a systemd user unit
+//! (`~/.config/systemd/user/tesela-server.service`) instead of the macOS
+//! LaunchAgent.
+//!
+//! The unit is `Type=notify` with `WatchdogSec=`: tesela-server reports
+//! `READY=1` once its listener is serving and pings the watchdog from its
+//! runtime (`tesela_server::systemd`), so systemd — not the server's own
+//! detached re-exec — restarts it after a crash, a hang, or
+//! `/server/restart`. `StartLimitBurst=` stops a crash loop from spinning
+//! forever. With `--socket-activation` a companion `tesela-server.socket`
+//! owns the port and starts the service on first connection.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use tesela_core::config::Config;
+
+const SERVICE_NAME: &str = "tesela-server.service";
+const SOCKET_NAME: &str = "tesela-server.socket";
+
+const SERVICE_TEMPLATE: &str = "[Unit]
+Description=Tesela server (notes API, sync daemon, WebSocket)
+After=network-online.target
+StartLimitIntervalSec=300
+StartLimitBurst=5
+{SOCKET_DEPS}
+[Service]
+Type=notify
+NotifyAccess=main
+ExecStart={BINARY_PATH}
+WorkingDirectory={NOTES_DIR}
+Environment=TESELA_SUPERVISED=systemd
+Restart=always
+RestartSec=2
+TimeoutStartSec=300
+WatchdogSec=60
+
+[Install]
+WantedBy=default.target
+";
+
+const SOCKET_TEMPLATE: &str = "[Unit]
+Description=Tesela server socket
+
+[Socket]
+ListenStream={BIND}
+NoDelay=true
+
+[Install]
+WantedBy=sockets.target
+";
+
+fn unit_dir() -> Result<PathBuf> {
+    let config =
+        dirs::config_dir().ok_or_else(|| anyhow::anyhow!("Cannot find config directory"))?;
+    Ok(config.join("systemd").join("user"))
+}
+
+/// Escape a value for a unit file: `%` starts a specifier, and `ExecStart=`
+/// splits on whitespace unless the word is quoted.
+fn escape_unit_value(value: &str) -> String {
+    value.replace('%', "%%")
+}
+
+fn quote_exec_arg(value: &str) -> String {
+    let escaped = escape_unit_value(value)
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    format!("\"{}\"", escaped)
+}
+
+fn render_service(server_bin: &Path, mosaic: &Path, socket_activation: bool) -> String {
+    let socket_deps = if socket_activation {
+        format!("Requires={SOCKET_NAME}\nAfter={SOCKET_NAME}\n")
+    } else {
+        String::new()
+    };
+    SERVICE_TEMPLATE
+        .replace("{SOCKET_DEPS}", &socket_deps)
+        .replace(
+            "{BINARY_PATH}",
+            &quote_exec_arg(&server_bin.to_string_lossy()),
+        )
+        .replace("{NOTES_DIR}", &escape_unit_value(&mosaic.to_string_lossy()))
+}
+
+fn render_socket(bind: &str) -> String {
+    SOCKET_TEMPLATE.replace("{BIND}", &escape_unit_value(bind))
+}
+
+/// Address the socket unit listens on — the server's own default
+/// (`TESELA_SERVER_BIND`, then `[server] bind` in the global config).
+fn resolve_bind() -> String {
+    if let Ok(env) = std::env::var("TESELA_SERVER_BIND") {
+        if !env.trim().is_empty() {
+            return env.trim().to_string();
+        }
+    }
+    let global = Config::default_path();
+    Config::load(&global)
+        .map(|cfg| cfg.server.bind)
+        .unwrap_or_else(|_| "127.0.0.1:7474".to_string())
+}
+
+fn systemctl(args: &[&str]) -> Result<()> {
+    let status = Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .status()
+        .context("Failed to run systemctl")?;
+    if !status.success() {
+        anyhow::bail!(
+            "systemctl --user {} failed with status: {}",
+            args.join(" "),
+            status
+        );
+    }
+    Ok(())
+}
+
+pub fn install(server_bin: &Path, mosaic: &Path, socket_activation: bool) -> Result<()> {
+    let dir = unit_dir()?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let service_file = dir.join(SERVICE_NAME);
+    std::fs::write(
+        &service_file,
+        render_service(server_bin, mosaic, socket_activation),
+    )
+    .with_context(|| format!("Failed to write unit to {}", service_file.display()))?;
+
+    let socket_file = dir.join(SOCKET_NAME);
+    let bind = resolve_bind();
+    if socket_activation {
+        std::fs::write(&socket_file, render_socket(&bind))
+            .with_context(|| format!("Failed to write unit to {}", socket_file.display()))?;
+    } else if socket_file.exists() {
+        // Re-install without activation: drop the stale socket unit.
+        let _ = systemctl(&["disable", "--now", SOCKET_NAME]);
+        std::fs::remove_file(&socket_file)
+            .with_context(|| format!("Failed to remove {}", socket_file.display()))?;
+    }
+
+    systemctl(&["daemon-reload"])?;
+    if socket_activation {
+        systemctl(&["enable", "--now", SOCKET_NAME])?;
+        systemctl(&["enable", SERVICE_NAME])?;
+    } else {
+        systemctl(&["enable", "--now", SERVICE_NAME])?;
+    }
+
+    println!("tesela-server installed as systemd user service.");
+    println!("  Binary:  {}", server_bin.display());
+    println!("  Unit:    {}", service_file.display());
+    if socket_activation {
+        println!("  Socket:  {} ({})", socket_file.display(), bind);
+    }
+    println!("  Logs:    journalctl --user -u {}", SERVICE_NAME);
+    println!("  API:     http://{}", bind);
+    println!("To keep it running while logged out: loginctl enable-linger");
+    Ok(())
+}
+
+pub fn uninstall() -> Result<()> {
+    let dir = unit_dir()?;
+    let service_file = dir.join(SERVICE_NAME);
+    let socket_file = dir.join(SOCKET_NAME);
+    if !service_file.exists() && !socket_file.exists() {
+        println!("systemd unit not found — already uninstalled.");
+        return Ok(());
+    }
+
+    if socket_file.exists() {
+        systemctl(&["disable", "--now", SOCKET_NAME])?;
+        std::fs::remove_file(&socket_file)
+            .with_context(|| format!("Failed to remove {}", socket_file.display()))?;
+    }
+    if service_file.exists() {
+        systemctl(&["disable", "--now", SERVICE_NAME])?;
+        std::fs::remove_file(&service_file)
+            .with_context(|| format!("Failed to remove {}", service_file.display()))?;
+    }
+    systemctl(&["daemon-reload"])?;
+
+    println!("tesela-server systemd user service uninstalled.");
+    Ok(())
+}
+
+/// `KEY=value` lines from `systemctl show`, in the order asked for.
+fn show(unit: &str, properties: &[&str]) -> Result<Vec<(String, String)>> {
+    let output = Command::new("systemctl")
+        .args(["--user", "show", unit, "--property", &properties.join(",")])
+        .output()
+        .context("Failed to run systemctl")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "systemctl --user show {} failed with status: {}",
+            unit,
+            output.status
+        );
+    }
+    Ok(parse_show(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn parse_show(stdout: &str) -> Vec<(String, String)> {
+    stdout
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+pub fn status() -> Result<()> {
+    let dir = unit_dir()?;
+    let service_file = dir.join(SERVICE_NAME);
+    if !service_file.exists() {
+        println!("tesela-server is not installed (run `tesela install`).");
+        return Ok(());
+    }
+    println!("Unit:    {}", service_file.display());
+    for (key, value) in show(
+        SERVICE_NAME,
+        &[
+            "UnitFileState",
+            "ActiveState",
+            "SubState",
+            "MainPID",
+            "NRestarts",
+            "ActiveEnterTimestamp",
+        ],
+    )? {
+        println!("  {key}: {value}");
+    }
+    let socket_file = dir.join(SOCKET_NAME);
+    if socket_file.exists() {
+        println!("Socket:  {}", socket_file.display());
+        for (key, value) in show(SOCKET_NAME, &["ActiveState", "Listen"])? {
+            println!("  {key}: {value}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn service_unit_quotes_binary_and_escapes_specifiers() {
+        let unit = render_service(
+            Path::new("/opt/my apps/tesela-server"),
+            Path::new("/home/me/100% notes"),
+            false,
+        );
+        assert!(unit.contains("ExecStart=\"/opt/my apps/tesela-server\"\n"));
+        assert!(unit.contains("WorkingDirectory=/home/me/100%% notes\n"));
+        assert!(unit.contains("Type=notify\n"));
+        assert!(unit.contains("Environment=TESELA_SUPERVISED=systemd\n"));
+        assert!(!unit.contains("Requires="));
+    }
+
+    #[test]
+    fn socket_activation_ties_service_to_socket_unit() {
+        let unit = render_service(Path::new("/bin/tesela-server"), Path::new("/m"), true);
+        assert!(unit.contains("Requires=tesela-server.socket\nAfter=tesela-server.socket\n"));
+        assert!(render_socket("127.0.0.1:7474").contains("ListenStream=127.0.0.1:7474\n"));
+    }
+
+    #[test]
+    fn parse_show_splits_on_first_equals() {
+        let parsed = parse_show("ActiveState=active\nListen=127.0.0.1:7474 (Stream)\nX=a=b\n");
+        assert_eq!(parsed[0], ("ActiveState".into(), "active".into()));
+        assert_eq!(parsed[2], ("X".into(), "a=b".into()));
+    }
+}
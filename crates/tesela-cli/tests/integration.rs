@@ -215,6 +215,40 @@ fn test_completions() {
         .success();
 }
 
+#[test]
+fn test_sync_doctor_on_a_healthy_mosaic() {
+    let tmp = TempDir::new().unwrap();
+    init_mosaic(&tmp);
+
+    tesela(&tmp)
+        .args(["sync", "doctor"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("no sync anomalies"));
+    tesela(&tmp)
+        .args(["sync", "doctor", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("[]"));
+}
+
+#[test]
+fn test_sync_gc_reports_reclaimed_rows() {
+    let tmp = TempDir::new().unwrap();
+    init_mosaic(&tmp);
+
+    tesela(&tmp)
+        .args(["sync", "gc", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("would reclaim 0 row(s)"));
+    tesela(&tmp)
+        .args(["sync", "gc", "--json", "--evict-after-days", "0"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"oplog_rows_remaining\": 0"));
+}
+
 #[cfg(unix)]
 #[test]
 fn logseq_import_refuses_a_server_locked_mosaic() {
@@ -11,7 +11,7 @@ use std::collections::HashMap;
 use ts_rs::TS;
 
 /// A parsed block from a note body.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 #[cfg_attr(test, derive(TS))]
 #[cfg_attr(test, ts(export, export_to = "../../../web/src/lib/types/"))]
 pub struct ParsedBlock {
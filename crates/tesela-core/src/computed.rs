@@ -0,0 +1,728 @@
+//! Computed properties — the `formula` and `rollup` value types.
+//!
+//! A Property page declares the expression in its frontmatter; the index
+//! evaluates it per row at query time, so a computed value filters, sorts
+//! and shows in table views like a stored one, but is never written to
+//! markdown and so never conflicts in sync.
+//!
+//! ```yaml
+//! type: Property
+//! value_type: formula
+//! formula: deadline - today
+//! ```
+//!
+//! Formulas combine numbers, `today`, parentheses, `+ - * /` and other
+//! properties of the same row by name. Dates subtract to a day count and
+//! shift by a number of days; a formula with a missing or non-numeric input
+//! has no value on that row. A formula may read another formula; one
+//! caught in a reference cycle has no value anywhere.
+//!
+//! ```yaml
+//! type: Property
+//! value_type: rollup
+//! rollup: percent status:done
+//! rollup_via: project
+//! rollup_where: tag:Task
+//! ```
+//!
+//! A rollup aggregates the rows whose Node-typed `rollup_via` property
+//! points at the page, narrowed by the optional `rollup_where` sub-query:
+//! `count [query]`, `percent <query>` (0–100), or `sum|avg|min|max
+//! <property>`. Rollups apply to page rows only, and a page nothing points
+//! at has no value.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{NaiveDate, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use crate::block::ParsedBlock;
+use crate::property::ValueType;
+use crate::query::{
+    block_matches_typed, extract_iso_date, parse_query, ParsedQuery, RelationIndex,
+};
+
+/// The frontmatter form of a computed property, as cached in the index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum ComputedSpec {
+    Formula {
+        formula: String,
+    },
+    Rollup {
+        rollup: String,
+        via: String,
+        #[serde(default)]
+        filter: Option<String>,
+    },
+}
+
+impl ComputedSpec {
+    /// The spec a Property page of `value_type` declares in `custom`
+    /// frontmatter; `None` for stored types or when the expression is
+    /// missing.
+    pub fn from_frontmatter(
+        value_type: ValueType,
+        custom: &HashMap<String, serde_json::Value>,
+    ) -> Option<ComputedSpec> {
+        let field = |key: &str| {
+            custom
+                .get(key)
+                .and_then(|value| value.as_str())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(String::from)
+        };
+        match value_type {
+            ValueType::Formula => Some(ComputedSpec::Formula {
+                formula: field("formula")?,
+            }),
+            ValueType::Rollup => Some(ComputedSpec::Rollup {
+                rollup: field("rollup")?,
+                via: field("rollup_via")?,
+                filter: field("rollup_where"),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// A computed property ready to evaluate.
+#[derive(Debug, Clone)]
+pub struct ComputedProperty {
+    pub name: String,
+    kind: Computed,
+}
+
+#[derive(Debug, Clone)]
+enum Computed {
+    Formula(Expr),
+    Rollup {
+        aggregate: Box<Aggregate>,
+        via: String,
+        filter: Option<ParsedQuery>,
+    },
+}
+
+#[derive(Debug, Clone)]
+enum Aggregate {
+    Count(Option<ParsedQuery>),
+    Percent(ParsedQuery),
+    Sum(String),
+    Avg(String),
+    Min(String),
+    Max(String),
+}
+
+impl ComputedProperty {
+    /// Compile `spec` for property `name`; `Err` describes what's malformed.
+    pub fn compile(name: &str, spec: &ComputedSpec) -> Result<ComputedProperty, String> {
+        let kind = match spec {
+            ComputedSpec::Formula { formula } => Computed::Formula(parse_formula(formula)?),
+            ComputedSpec::Rollup {
+                rollup,
+                via,
+                filter,
+            } => {
+                let (function, argument) = match rollup.trim().split_once(char::is_whitespace) {
+                    Some((function, argument)) => (function, argument.trim()),
+                    None => (rollup.trim(), ""),
+                };
+                let property = || {
+                    if argument.is_empty() {
+                        Err(format!("rollup `{function}` needs a property"))
+                    } else {
+                        Ok(argument.to_string())
+                    }
+                };
+                let aggregate = match function.to_ascii_lowercase().as_str() {
+                    "count" if argument.is_empty() => Aggregate::Count(None),
+                    "count" => Aggregate::Count(Some(parse_query(argument))),
+                    "percent" if argument.is_empty() => {
+                        return Err("rollup `percent` needs a query".into())
+                    }
+                    "percent" => Aggregate::Percent(parse_query(argument)),
+                    "sum" => Aggregate::Sum(property()?),
+                    "avg" => Aggregate::Avg(property()?),
+                    "min" => Aggregate::Min(property()?),
+                    "max" => Aggregate::Max(property()?),
+                    other => return Err(format!("unknown rollup function `{other}`")),
+                };
+                Computed::Rollup {
+                    aggregate: Box::new(aggregate),
+                    via: via.clone(),
+                    filter: filter.as_deref().map(parse_query),
+                }
+            }
+        };
+        Ok(ComputedProperty {
+            name: name.to_string(),
+            kind,
+        })
+    }
+
+    pub fn is_rollup(&self) -> bool {
+        matches!(self.kind, Computed::Rollup { .. })
+    }
+
+    /// Lowercased names of the row properties a formula reads.
+    fn references(&self) -> HashSet<String> {
+        let mut names = HashSet::new();
+        if let Computed::Formula(expr) = &self.kind {
+            expr.collect_properties(&mut names);
+        }
+        names
+    }
+
+    /// This property's value on `row`, or `None` when it has none there.
+    /// Rollups read `relations`; without it they have no value.
+    pub fn evaluate(
+        &self,
+        row: &ParsedBlock,
+        relations: Option<&RelationIndex>,
+        types: &HashMap<String, ValueType>,
+        today: NaiveDate,
+    ) -> Option<String> {
+        match &self.kind {
+            Computed::Formula(expr) => expr.eval(row, today).map(Value::render),
+            Computed::Rollup {
+                aggregate,
+                via,
+                filter,
+            } => {
+                if row.id != row.note_id {
+                    return None;
+                }
+                let rows: Vec<&ParsedBlock> = relations?
+                    .backlinks(row, Some(via))
+                    .filter(|source| {
+                        filter
+                            .as_ref()
+                            .is_none_or(|filter| block_matches_typed(source, filter, types))
+                    })
+                    .collect();
+                if rows.is_empty() {
+                    return None;
+                }
+                aggregate.eval(&rows, types).map(Value::render)
+            }
+        }
+    }
+}
+
+/// Order `computed` for [`apply`]: rollups first, then every formula after
+/// the formulas it reads. Formulas in a reference cycle, or reading one,
+/// can't be placed; their names come back in the second list.
+pub fn evaluation_order(computed: Vec<ComputedProperty>) -> (Vec<ComputedProperty>, Vec<String>) {
+    let (mut ordered, mut pending): (Vec<_>, Vec<_>) =
+        computed.into_iter().partition(|c| c.is_rollup());
+    while !pending.is_empty() {
+        let unplaced: HashSet<String> = pending
+            .iter()
+            .map(|c| c.name.to_ascii_lowercase())
+            .collect();
+        let (ready, blocked): (Vec<_>, Vec<_>) = pending
+            .into_iter()
+            .partition(|c| c.references().is_disjoint(&unplaced));
+        if ready.is_empty() {
+            return (ordered, blocked.into_iter().map(|c| c.name).collect());
+        }
+        ordered.extend(ready);
+        pending = blocked;
+    }
+    (ordered, Vec::new())
+}
+
+/// Add every computed property's value on `row` to its properties, rollups
+/// first so formulas can use them, formulas in the order given (see
+/// [`evaluation_order`]). Stored values of the same name are replaced.
+pub fn apply(
+    computed: &[ComputedProperty],
+    row: &mut ParsedBlock,
+    relations: Option<&RelationIndex>,
+    types: &HashMap<String, ValueType>,
+    today: NaiveDate,
+) {
+    let (rollups, formulas): (Vec<_>, Vec<_>) = computed.iter().partition(|c| c.is_rollup());
+    for property in rollups.into_iter().chain(formulas) {
+        row.properties
+            .retain(|key, _| !key.eq_ignore_ascii_case(&property.name));
+        if let Some(value) = property.evaluate(row, relations, types, today) {
+            row.properties.insert(property.name.clone(), value);
+        }
+    }
+}
+
+impl Aggregate {
+    fn eval(&self, rows: &[&ParsedBlock], types: &HashMap<String, ValueType>) -> Option<Value> {
+        let numbers = |property: &str| {
+            rows.iter()
+                .filter_map(|row| property_value(row, property))
+                .filter_map(|value| value.trim().parse::<f64>().ok())
+                .filter(|number| number.is_finite())
+                .collect::<Vec<_>>()
+        };
+        let count = |query: &ParsedQuery| {
+            rows.iter()
+                .filter(|row| block_matches_typed(row, query, types))
+                .count()
+        };
+        let number = match self {
+            Aggregate::Count(None) => rows.len() as f64,
+            Aggregate::Count(Some(query)) => count(query) as f64,
+            Aggregate::Percent(query) => (count(query) * 100) as f64 / rows.len() as f64,
+            Aggregate::Sum(property) => numbers(property).iter().sum(),
+            Aggregate::Avg(property) => {
+                let values = numbers(property);
+                if values.is_empty() {
+                    return None;
+                }
+                values.iter().sum::<f64>() / values.len() as f64
+            }
+            Aggregate::Min(property) => numbers(property).into_iter().reduce(f64::min)?,
+            Aggregate::Max(property) => numbers(property).into_iter().reduce(f64::max)?,
+        };
+        number.is_finite().then_some(Value::Number(number))
+    }
+}
+
+fn property_value<'a>(row: &'a ParsedBlock, name: &str) -> Option<&'a str> {
+    row.properties
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    Number(f64),
+    Date(NaiveDate),
+}
+
+impl Value {
+    /// Numbers to at most two decimals, dates as ISO `YYYY-MM-DD`.
+    fn render(self) -> String {
+        match self {
+            Value::Number(number) => ((number * 100.0).round() / 100.0 + 0.0).to_string(),
+            Value::Date(date) => date.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Today,
+    Property(String),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, char, Box<Expr>),
+}
+
+impl Expr {
+    /// `None` when an operand is missing, the result isn't a finite
+    /// number, or date arithmetic leaves chrono's range.
+    fn eval(&self, row: &ParsedBlock, today: NaiveDate) -> Option<Value> {
+        let value = match self {
+            Expr::Number(number) => Value::Number(*number),
+            Expr::Today => Value::Date(today),
+            Expr::Property(name) => {
+                let raw = property_value(row, name)?.trim();
+                match raw.parse::<f64>() {
+                    Ok(number) if number.is_finite() => Value::Number(number),
+                    Ok(_) => return None,
+                    Err(_) => Value::Date(
+                        NaiveDate::parse_from_str(&extract_iso_date(raw)?, "%Y-%m-%d").ok()?,
+                    ),
+                }
+            }
+            Expr::Neg(inner) => match inner.eval(row, today)? {
+                Value::Number(number) => Value::Number(-number),
+                Value::Date(_) => return None,
+            },
+            Expr::Binary(left, op, right) => {
+                match (left.eval(row, today)?, *op, right.eval(row, today)?) {
+                    (Value::Number(a), '+', Value::Number(b)) => Value::Number(a + b),
+                    (Value::Number(a), '-', Value::Number(b)) => Value::Number(a - b),
+                    (Value::Number(a), '*', Value::Number(b)) => Value::Number(a * b),
+                    (Value::Number(_), '/', Value::Number(0.0)) => return None,
+                    (Value::Number(a), '/', Value::Number(b)) => Value::Number(a / b),
+                    (Value::Date(a), '-', Value::Date(b)) => {
+                        Value::Number((a - b).num_days() as f64)
+                    }
+                    (Value::Date(date), '+', Value::Number(days))
+                    | (Value::Number(days), '+', Value::Date(date)) => {
+                        Value::Date(date.checked_add_signed(day_offset(days)?)?)
+                    }
+                    (Value::Date(date), '-', Value::Number(days)) => {
+                        Value::Date(date.checked_sub_signed(day_offset(days)?)?)
+                    }
+                    _ => return None,
+                }
+            }
+        };
+        match value {
+            Value::Number(number) if !number.is_finite() => None,
+            value => Some(value),
+        }
+    }
+
+    fn collect_properties(&self, names: &mut HashSet<String>) {
+        match self {
+            Expr::Number(_) | Expr::Today => {}
+            Expr::Property(name) => {
+                names.insert(name.to_ascii_lowercase());
+            }
+            Expr::Neg(inner) => inner.collect_properties(names),
+            Expr::Binary(left, _, right) => {
+                left.collect_properties(names);
+                right.collect_properties(names);
+            }
+        }
+    }
+}
+
+/// Whole days for date arithmetic; `None` past chrono's range.
+fn day_offset(days: f64) -> Option<TimeDelta> {
+    // Float-to-int `as` saturates, and `try_days` rejects the extremes.
+    TimeDelta::try_days(days.round() as i64)
+}
+
+/// Deepest `(` / unary `-` nesting a formula may use. The parser and the
+/// evaluator both recurse per level, so a Property page with a formula of
+/// thousands of `(` would otherwise overflow the stack while indexing.
+const MAX_FORMULA_DEPTH: usize = 64;
+
+/// Longest formula source, in chars. Operator chains (`a + b + c …`) build a
+/// tree as deep as they are long, so length bounds evaluation depth too.
+const MAX_FORMULA_LEN: usize = 1024;
+
+/// `expr := term (('+' | '-') term)*`, `term := factor (('*' | '/') factor)*`,
+/// `factor := '-' factor | number | name | '(' expr ')'`.
+fn parse_formula(source: &str) -> Result<Expr, String> {
+    if source.chars().count() > MAX_FORMULA_LEN {
+        return Err(format!("formula longer than {MAX_FORMULA_LEN} characters"));
+    }
+    let mut parser = FormulaParser {
+        chars: source.chars().collect(),
+        pos: 0,
+        depth: 0,
+    };
+    let expr = parser.expr()?;
+    parser.skip_whitespace();
+    match parser.chars.get(parser.pos) {
+        None => Ok(expr),
+        Some(c) => Err(format!("unexpected `{c}` in formula")),
+    }
+}
+
+struct FormulaParser {
+    chars: Vec<char>,
+    pos: usize,
+    /// Current `factor` recursion depth, capped at [`MAX_FORMULA_DEPTH`].
+    depth: usize,
+}
+
+impl FormulaParser {
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, ops: &[char]) -> Option<char> {
+        self.skip_whitespace();
+        let c = *self.chars.get(self.pos)?;
+        if ops.contains(&c) {
+            self.pos += 1;
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut left = self.term()?;
+        while let Some(op) = self.eat(&['+', '-']) {
+            left = Expr::Binary(Box::new(left), op, Box::new(self.term()?));
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut left = self.factor()?;
+        while let Some(op) = self.eat(&['*', '/']) {
+            left = Expr::Binary(Box::new(left), op, Box::new(self.factor()?));
+        }
+        Ok(left)
+    }
+
+    fn factor(&mut self) -> Result<Expr, String> {
+        if self.depth >= MAX_FORMULA_DEPTH {
+            return Err("formula nested too deeply".into());
+        }
+        self.depth += 1;
+        let factor = self.nested_factor();
+        self.depth -= 1;
+        factor
+    }
+
+    fn nested_factor(&mut self) -> Result<Expr, String> {
+        if self.eat(&['-']).is_some() {
+            return Ok(Expr::Neg(Box::new(self.factor()?)));
+        }
+        if self.eat(&['(']).is_some() {
+            let inner = self.expr()?;
+            return match self.eat(&[')']) {
+                Some(_) => Ok(inner),
+                None => Err("missing `)` in formula".into()),
+            };
+        }
+        let start = self.pos;
+        let Some(&first) = self.chars.get(start) else {
+            return Err("formula ends early".into());
+        };
+        if first.is_ascii_digit() || first == '.' {
+            while self
+                .chars
+                .get(self.pos)
+                .is_some_and(|c| c.is_ascii_digit() || *c == '.')
+            {
+                self.pos += 1;
+            }
+            let literal: String = self.chars[start..self.pos].iter().collect();
+            return literal
+                .parse()
+                .map(Expr::Number)
+                .map_err(|_| format!("bad number `{literal}` in formula"));
+        }
+        if first.is_alphabetic() || first == '_' {
+            while self
+                .chars
+                .get(self.pos)
+                .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+            {
+                self.pos += 1;
+            }
+            let name: String = self.chars[start..self.pos].iter().collect();
+            return Ok(if name.eq_ignore_ascii_case("today") {
+                Expr::Today
+            } else {
+                Expr::Property(name)
+            });
+        }
+        Err(format!("unexpected `{first}` in formula"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: &str, tags: &[&str], props: &[(&str, &str)]) -> ParsedBlock {
+        ParsedBlock {
+            id: id.into(),
+            bid: Some(format!("{id}-bid")),
+            text: id.into(),
+            raw_text: format!("- {id}"),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            inline_tags: vec![],
+            trailing_tags: vec![],
+            inherited_tags: vec![],
+            properties: props
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            indent_level: 0,
+            note_id: "tasks".into(),
+            parent_note_type: None,
+        }
+    }
+
+    fn formula(source: &str) -> ComputedProperty {
+        ComputedProperty::compile(
+            "f",
+            &ComputedSpec::Formula {
+                formula: source.into(),
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn formulas_do_date_and_number_arithmetic() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let task = row(
+            "t",
+            &[],
+            &[
+                ("deadline", "[[2026-10-25]]"),
+                ("estimate", "3"),
+                ("spent", "1.5"),
+            ],
+        );
+        let eval = |source: &str| formula(source).evaluate(&task, None, &HashMap::new(), today);
+        assert_eq!(eval("deadline - today").as_deref(), Some("7"));
+        assert_eq!(eval("today - deadline").as_deref(), Some("-7"));
+        assert_eq!(eval("deadline + 2").as_deref(), Some("2026-10-27"));
+        assert_eq!(eval("(estimate - spent) * 2 / 3").as_deref(), Some("1"));
+        assert_eq!(eval("spent / estimate").as_deref(), Some("0.5"));
+        assert_eq!(eval("-estimate").as_deref(), Some("-3"));
+        // Missing inputs, dates in number-only operations, and x/0 have no value.
+        assert_eq!(eval("scheduled - today"), None);
+        assert_eq!(eval("deadline * 2"), None);
+        assert_eq!(eval("estimate / 0"), None);
+        // Out-of-range dates and non-finite numbers have no value either.
+        let extreme = row(
+            "x",
+            &[],
+            &[
+                ("deadline", "2026-10-25"),
+                ("far", "1e300"),
+                ("years", "99999999999"),
+                ("bad", "inf"),
+            ],
+        );
+        let eval = |source: &str| formula(source).evaluate(&extreme, None, &HashMap::new(), today);
+        assert_eq!(eval("deadline + far"), None);
+        assert_eq!(eval("deadline - years"), None);
+        assert_eq!(eval("far * far"), None);
+        assert_eq!(eval("bad"), None);
+        assert_eq!(eval("1".repeat(400).as_str()), None);
+
+        for bad in ["deadline -", "(estimate", "estimate $ 2", "1..2"] {
+            assert!(
+                ComputedProperty::compile(
+                    "f",
+                    &ComputedSpec::Formula {
+                        formula: bad.into()
+                    }
+                )
+                .is_err(),
+                "{bad:?} should not compile"
+            );
+        }
+    }
+
+    #[test]
+    fn deeply_nested_formulas_fail_to_compile() {
+        let compile = |source: String| {
+            ComputedProperty::compile("f", &ComputedSpec::Formula { formula: source })
+        };
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(compile(nested(MAX_FORMULA_DEPTH - 1)).is_ok());
+        let err = compile(nested(MAX_FORMULA_DEPTH + 1)).unwrap_err();
+        assert!(err.contains("nested too deeply"), "{err}");
+        assert!(compile("(".repeat(50_000)).is_err());
+        assert!(compile("-".repeat(50_000) + "1").is_err());
+        assert!(compile("1 + ".repeat(50_000) + "1").is_err());
+    }
+
+    #[test]
+    fn rollups_aggregate_the_rows_pointing_at_a_page() {
+        let project = crate::PageId::from_legacy_doc_id(&[1; 16]);
+        let mut page = row("apollo", &[], &[("deadline", "2026-11-01")]);
+        page.note_id = "apollo".into();
+        let mut relations = RelationIndex::default();
+        relations.add_page(project, page.clone());
+        for task in [
+            row("a", &["Task"], &[("status", "done"), ("estimate", "2")]),
+            row("b", &["Task"], &[("status", "todo"), ("estimate", "4")]),
+            row("c", &["Task"], &[("status", "todo")]),
+            row("m", &["Meeting"], &[("status", "done"), ("estimate", "9")]),
+        ] {
+            relations.add_edge(
+                &crate::RelationEdge {
+                    source_page_id: crate::PageId::from_legacy_doc_id(&[2; 16]),
+                    source_note_id: task.note_id.clone(),
+                    source_block_id: task.bid.clone(),
+                    property_key: "project".into(),
+                    target_page_id: project,
+                },
+                task,
+            );
+        }
+        let rollup = |rollup: &str| {
+            ComputedProperty::compile(
+                "r",
+                &ComputedSpec::Rollup {
+                    rollup: rollup.into(),
+                    via: "project".into(),
+                    filter: Some("tag:Task".into()),
+                },
+            )
+            .unwrap()
+        };
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let types = HashMap::new();
+        let eval = |r: &str| rollup(r).evaluate(&page, Some(&relations), &types, today);
+        assert_eq!(eval("count").as_deref(), Some("3"));
+        assert_eq!(eval("count status:todo").as_deref(), Some("2"));
+        assert_eq!(eval("percent status:done").as_deref(), Some("33.33"));
+        assert_eq!(eval("sum estimate").as_deref(), Some("6"));
+        assert_eq!(eval("avg estimate").as_deref(), Some("3"));
+        assert_eq!(eval("max estimate").as_deref(), Some("4"));
+        // Only page rows roll up, and only from rows that point at them.
+        let task = row("a", &["Task"], &[]);
+        assert_eq!(
+            rollup("count").evaluate(&task, Some(&relations), &types, today),
+            None
+        );
+        assert!(ComputedProperty::compile(
+            "r",
+            &ComputedSpec::Rollup {
+                rollup: "median estimate".into(),
+                via: "project".into(),
+                filter: None,
+            },
+        )
+        .is_err());
+
+        // Rollups land before formulas, so a formula can build on one.
+        let computed = [
+            formula_named("days_left", "deadline - today"),
+            formula_named("remaining", "100 - progress"),
+            ComputedProperty::compile(
+                "progress",
+                &ComputedSpec::Rollup {
+                    rollup: "percent status:done".into(),
+                    via: "project".into(),
+                    filter: Some("tag:Task".into()),
+                },
+            )
+            .unwrap(),
+        ];
+        apply(&computed, &mut page, Some(&relations), &types, today);
+        assert_eq!(page.properties["progress"], "33.33");
+        assert_eq!(page.properties["remaining"], "66.67");
+        assert_eq!(page.properties["days_left"], "14");
+    }
+
+    #[test]
+    fn formulas_evaluate_after_the_formulas_they_read() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let (ordered, mut rejected) = evaluation_order(vec![
+            formula_named("a_total", "b_subtotal * 2"),
+            formula_named("b_subtotal", "estimate + 1"),
+            formula_named("loop_x", "loop_y + 1"),
+            formula_named("loop_y", "loop_x + 1"),
+            formula_named("uses_loop", "loop_x"),
+        ]);
+        rejected.sort();
+        assert_eq!(rejected, ["loop_x", "loop_y", "uses_loop"]);
+        let mut task = row("t", &[], &[("estimate", "3"), ("loop_x", "1")]);
+        apply(&ordered, &mut task, None, &HashMap::new(), today);
+        assert_eq!(task.properties["b_subtotal"], "4");
+        assert_eq!(task.properties["a_total"], "8");
+        assert!(!task.properties.contains_key("uses_loop"));
+    }
+
+    fn formula_named(name: &str, source: &str) -> ComputedProperty {
+        ComputedProperty::compile(
+            name,
+            &ComputedSpec::Formula {
+                formula: source.into(),
+            },
+        )
+        .unwrap()
+    }
+}
@@ -35,10 +35,16 @@ pub struct Config {
     /// Sync — relay + future LAN/internet settings.
     #[serde(default)]
     pub sync: SyncConfig,
+    /// Note version history retention (`[history]`).
+    #[serde(default)]
+    pub history: HistoryConfig,
+    /// Outbound webhooks (`[[webhooks]]` tables). Empty = none.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
 }
 
-/// Sync-related configuration. Currently only the optional WAN relay;
-/// the LAN/mDNS path is enabled by default and doesn't need
+/// Sync-related configuration: the optional WAN relay and oplog
+/// retention; the LAN/mDNS path is enabled by default and doesn't need
 /// configuration. Future internet-P2P direct addressing lives here too
 /// when that lands.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -47,6 +53,10 @@ pub struct SyncConfig {
     /// section absent) means LAN-only sync.
     #[serde(default)]
     pub relay: Option<RelayConfig>,
+    /// `[sync.retention]` — how long the sync tables keep acked ops,
+    /// parked ops and silent peers.
+    #[serde(default)]
+    pub retention: RetentionConfig,
 }
 
 /// `[sync.relay]` block. Minimum field is the URL; everything else
@@ -71,6 +81,123 @@ fn default_poll_interval_ms() -> u64 {
     5_000
 }
 
+/// `[sync.retention]` block: the oplog retention sweep tesela-server runs
+/// every `interval_hours` (and `tesela sync gc` runs on demand). A zero
+/// `parked_max_age_days` / `evict_peers_after_days` disables that step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// Ops younger than this are kept even once every peer has acked
+    /// them. Defaults to 24.
+    pub safety_lag_hours: u64,
+    /// Ops parked (unappliable by this schema) longer than this are
+    /// dropped. Defaults to 90.
+    pub parked_max_age_days: u64,
+    /// A peer that hasn't acked in this long stops holding ops back.
+    /// Defaults to 180.
+    pub evict_peers_after_days: u64,
+    /// How often the server sweeps. Defaults to 6.
+    pub interval_hours: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            safety_lag_hours: 24,
+            parked_max_age_days: 90,
+            evict_peers_after_days: 180,
+            interval_hours: 6,
+        }
+    }
+}
+
+/// `[history]` block: how long the per-note version history
+/// (`note_versions`) is kept. tesela-server thins it every
+/// `interval_hours` and `tesela history prune` runs the same pass on
+/// demand. Versions younger than `keep_all_hours` are all kept; older ones
+/// are thinned to the newest per hour up to `keep_hourly_days`, then the
+/// newest per day up to `keep_daily_days`, and dropped after that. A zero
+/// disables that tier. The newest version of a note is always kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Defaults to 24.
+    pub keep_all_hours: u64,
+    /// Defaults to 7.
+    pub keep_hourly_days: u64,
+    /// Defaults to 365.
+    pub keep_daily_days: u64,
+    /// Store each kept version as a line delta against the next newer one
+    /// (the newest stays whole). Defaults to true.
+    pub compress: bool,
+    /// How often the server prunes. Defaults to 6.
+    pub interval_hours: u64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            keep_all_hours: 24,
+            keep_hourly_days: 7,
+            keep_daily_days: 365,
+            compress: true,
+            interval_hours: 6,
+        }
+    }
+}
+
+/// One `[[webhooks]]` entry: tesela-server POSTs a signed JSON payload to
+/// `url` for every server event whose type is listed in `events`.
+///
+/// ```toml
+/// [[webhooks]]
+/// name = "home-assistant"
+/// url = "http://homeassistant.local:8123/api/webhook/tesela"
+/// secret = "shared-secret"
+/// events = ["deadline_approaching", "scheduled_fires"]
+/// query = "tag:errand"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Stable name shown in the delivery log. Must be unique.
+    pub name: String,
+    /// Endpoint receiving the `POST`.
+    pub url: String,
+    /// HMAC-SHA256 key for the `X-Tesela-Signature` header. Unsigned when
+    /// absent.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Event types to deliver, each one of [`WEBHOOK_EVENTS`]. Empty =
+    /// every event.
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// Optional query DSL. When set, only events about a page or block the
+    /// query currently matches are delivered; events without a subject
+    /// (`views_changed`, `note_deleted`) are then skipped.
+    #[serde(default)]
+    pub query: Option<String>,
+    /// Delivery attempts before giving up (first try included).
+    #[serde(default = "default_webhook_max_attempts")]
+    pub max_attempts: u32,
+}
+
+fn default_webhook_max_attempts() -> u32 {
+    5
+}
+
+/// Every event type tesela-server emits, as named in a webhook's `events`
+/// list and in the payload's `event` field.
+pub const WEBHOOK_EVENTS: &[&str] = &[
+    "note_created",
+    "note_updated",
+    "note_deleted",
+    "deadline_approaching",
+    "scheduled_fires",
+    "recurring_rolled",
+    "views_changed",
+    "notification_state_changed",
+];
+
 /// General application settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeneralConfig {
@@ -194,12 +321,19 @@ pub struct ServerConfig {
     /// `/server/restart` re-execs WITH the inherited environment, so an env
     /// override survives a restart (this config is the fallback).
     pub bind: String,
+    /// Expose the Prometheus/OpenMetrics scrape endpoint at `GET /metrics`.
+    /// Off by default: the server has no auth, and route names, socket
+    /// counts and relay throughput shouldn't leak to anyone who can reach
+    /// a LAN-bound port unless the operator asks for it.
+    #[serde(default)]
+    pub metrics: bool,
 }
 
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
             bind: "127.0.0.1:7474".to_string(),
+            metrics: false,
         }
     }
 }
@@ -385,6 +519,28 @@ impl Config {
             ));
         }
 
+        // Webhook names key the delivery log, so they must be unique
+        let mut names = std::collections::HashSet::new();
+        if let Some(hook) = self.webhooks.iter().find(|h| !names.insert(&h.name)) {
+            return Err(TeselaError::validation(format!(
+                "Duplicate webhook name: {}",
+                hook.name
+            )));
+        }
+        // An unknown event name is a typo that would silently never fire
+        for hook in &self.webhooks {
+            if let Some(event) = hook
+                .events
+                .iter()
+                .find(|e| !WEBHOOK_EVENTS.contains(&e.as_str()))
+            {
+                return Err(TeselaError::validation(format!(
+                    "Unknown event '{}' in webhook {}",
+                    event, hook.name
+                )));
+            }
+        }
+
         Ok(())
     }
 
@@ -593,6 +749,76 @@ mod tests {
         assert_eq!(loaded.server.bind, "0.0.0.0:7474");
     }
 
+    #[test]
+    fn server_metrics_is_opt_in() {
+        let config: Config = toml::from_str("[server]\nbind = \"127.0.0.1:7474\"\n").unwrap();
+        assert!(!config.server.metrics);
+
+        let config: Config =
+            toml::from_str("[server]\nbind = \"127.0.0.1:7474\"\nmetrics = true\n").unwrap();
+        assert!(config.server.metrics);
+    }
+
+    #[test]
+    fn webhooks_parse_from_array_of_tables_with_defaults() {
+        let config: Config = toml::from_str(
+            r#"
+[[webhooks]]
+name = "ha"
+url = "http://ha.local/api/webhook/tesela"
+events = ["deadline_approaching"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.webhooks.len(), 1);
+        let hook = &config.webhooks[0];
+        assert_eq!(hook.events, vec!["deadline_approaching"]);
+        assert_eq!(hook.secret, None);
+        assert_eq!(hook.query, None);
+        assert_eq!(hook.max_attempts, 5);
+        assert!(!toml::to_string_pretty(&Config::default())
+            .unwrap()
+            .contains("webhooks"));
+    }
+
+    #[test]
+    fn duplicate_webhook_names_fail_validation() {
+        let mut config: Config = toml::from_str(
+            r#"
+[[webhooks]]
+name = "ha"
+url = "http://ha.local/a"
+
+[[webhooks]]
+name = "ha"
+url = "http://ha.local/b"
+"#,
+        )
+        .unwrap();
+        assert!(config.validate().is_err());
+
+        config.webhooks[1].name = "bot".to_string();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn unknown_webhook_events_fail_validation() {
+        let mut config: Config = toml::from_str(
+            r#"
+[[webhooks]]
+name = "ha"
+url = "http://ha.local/a"
+events = ["note_updated", "note.updatd"]
+"#,
+        )
+        .unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("note.updatd"), "{err}");
+
+        config.webhooks[0].events = WEBHOOK_EVENTS.iter().map(|e| e.to_string()).collect();
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_merge_env() {
         // Use unique env var names to avoid conflicts with parallel tests
@@ -1,5 +1,7 @@
 pub mod queries;
 pub mod schema;
 pub mod sqlite;
+pub mod versions;
 
 pub use sqlite::SqliteIndex;
+pub use versions::{VersionPruneReport, VersionRetention};
@@ -1,6 +1,6 @@
 //! SQLite schema definitions and migrations for Tesela
 
-pub const SCHEMA_VERSION: i64 = 7;
+pub const SCHEMA_VERSION: i64 = 8;
 
 pub const CREATE_MIGRATIONS_TABLE: &str = r#"
 CREATE TABLE IF NOT EXISTS schema_migrations (
@@ -232,4 +232,27 @@ END"#,
         "CREATE INDEX IF NOT EXISTS idx_relation_edges_target ON relation_edges(target_page_id)",
         "CREATE INDEX IF NOT EXISTS idx_relation_edges_source_note ON relation_edges(source_note_id)",
     ],
+), (
+    // Group key rotation: each member's X25519 key-agreement public key,
+    // learned from its member announcement over the relay. The rotating
+    // device wraps the next epoch key to exactly these keys, so a device
+    // removed from `group_members` never receives it. Mirrors
+    // `tesela_sync::schema::GROUP_MEMBER_KX_DDL`.
+    "008_group_member_kx",
+    &["ALTER TABLE group_members ADD COLUMN kx_pubkey BLOB"],
+), (
+    // Version history compaction (see `db::versions`): a row whose
+    // `content_delta` is set stores `content` as a line delta against the
+    // next newer version of the note; `prev_delta` stores `prev_content` as
+    // a delta against the row's own content.
+    "009_note_version_deltas",
+    &[
+        "ALTER TABLE note_versions ADD COLUMN content_delta INTEGER NOT NULL DEFAULT 0",
+        "ALTER TABLE note_versions ADD COLUMN prev_delta INTEGER NOT NULL DEFAULT 0",
+    ],
+), (
+    // Formula/rollup Property pages (see `computed`): the expression
+    // fields as a JSON `ComputedSpec`, `NULL` for stored types.
+    "010_computed_property_defs",
+    &["ALTER TABLE property_defs ADD COLUMN computed_json TEXT"],
 )];
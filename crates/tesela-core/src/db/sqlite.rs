@@ -4,6 +4,7 @@ use async_trait::async_trait;
 use sha2::{Digest, Sha256};
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use sqlx::{Pool, Row, Sqlite};
+use std::collections::HashMap;
 use std::path::Path;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
@@ -13,8 +14,10 @@ use super::queries;
 use super::schema;
 use crate::block::ParsedBlock;
 use crate::error::{Result, TeselaError};
+use crate::graph::{GraphPage, LinkGraphSnapshot};
 use crate::link::{Link, LinkType};
 use crate::note::{Note, NoteId, SearchHit};
+use crate::related::{RelatedCorpus, RelatedNotes};
 use crate::traits::link_graph::LinkGraph;
 use crate::traits::search_index::SearchIndex;
 
@@ -32,7 +35,7 @@ struct CachedBlocks {
     blocks: Arc<Vec<ParsedBlock>>,
 }
 
-fn db_err(msg: &str, e: sqlx::Error) -> TeselaError {
+pub(super) fn db_err(msg: &str, e: sqlx::Error) -> TeselaError {
     TeselaError::Database {
         message: format!("{}: {}", msg, e),
         source: None,
@@ -208,6 +211,67 @@ pub struct SqliteIndex {
     /// currently in `notes`. See `CachedBlocks` for the invalidation
     /// contract. Explicitly evicted in `remove_note`.
     blocks_cache: Mutex<std::collections::HashMap<String, CachedBlocks>>,
+    /// Views derived from `notes`, `links`, `relation_edges` and
+    /// `property_defs`. Each is built on first use; all are dropped by
+    /// every write to those tables
+    /// (see `invalidate_derived`), which also bumps the generation so a
+    /// build that raced a write is never stored.
+    derived: Mutex<DerivedCache>,
+    /// Term vectors for related-notes recommendations. Loaded on first
+    /// use, then updated per note by `upsert_note` / `remove_note`; the
+    /// generation guards a load that raced one of those. Readers score
+    /// against a cloned `Arc` outside the lock; a write while one is
+    /// scoring copies the corpus instead of waiting.
+    related: Mutex<(u64, Option<Arc<RelatedCorpus>>)>,
+    /// Link-graph nodes by note id, so rebuilding the graph after a write
+    /// doesn't reparse every note's frontmatter. Loaded and kept current
+    /// the same way as `related`.
+    graph_pages: Mutex<(u64, Option<HashMap<String, GraphPage>>)>,
+}
+
+/// Read-side views shared by every query until the next index write.
+#[derive(Default)]
+struct DerivedCache {
+    generation: u64,
+    /// Resolved page graph for analytics, `is:orphan` / `links-to:` and
+    /// search ranking.
+    link_graph: Option<Arc<LinkGraphSnapshot>>,
+    /// Relation edges with the rows at both ends, and the page directory
+    /// those pages form, for dotted traversal, `has-backlink-from:` and
+    /// rollups.
+    relations: Option<RelationSnapshot>,
+    /// Compiled formula and rollup properties, in evaluation order.
+    computed: Option<Arc<[crate::computed::ComputedProperty]>>,
+}
+
+type RelationSnapshot = (
+    Arc<crate::query::RelationIndex>,
+    Arc<Vec<crate::query::QueryPage>>,
+);
+
+/// How strongly PageRank scales full-text relevance in `search`.
+const SEARCH_GRAPH_WEIGHT: f64 = 0.25;
+/// `search` re-ranks this many times the requested page depth of FTS hits.
+const SEARCH_RERANK_WINDOW: usize = 4;
+
+/// A note as a link-graph node.
+fn graph_page(
+    slug: String,
+    title: String,
+    metadata: &crate::note::NoteMetadata,
+    note_type: Option<String>,
+) -> GraphPage {
+    GraphPage {
+        slug,
+        title,
+        page_id: metadata
+            .custom
+            .get(crate::storage::markdown::TESELA_PAGE_ID_KEY)
+            .and_then(|value| value.as_str())
+            .and_then(crate::PageId::parse),
+        aliases: metadata.aliases.clone(),
+        note_type,
+    }
 }
 
 const DEFAULT_MAX_CONNECTIONS: u32 = 5;
@@ -234,6 +298,9 @@ impl SqliteIndex {
         Ok(Self {
             pool,
             blocks_cache: Mutex::new(std::collections::HashMap::new()),
+            derived: Mutex::new(DerivedCache::default()),
+            related: Mutex::new((0, None)),
+            graph_pages: Mutex::new((0, None)),
         })
     }
 
@@ -255,9 +322,18 @@ impl SqliteIndex {
         Ok(Self {
             pool,
             blocks_cache: Mutex::new(std::collections::HashMap::new()),
+            derived: Mutex::new(DerivedCache::default()),
+            related: Mutex::new((0, None)),
+            graph_pages: Mutex::new((0, None)),
         })
     }
 
+    /// The underlying connection pool, for the sync substrate's tables
+    /// that share this database (`group_members`, see `schema.rs`).
+    pub fn pool(&self) -> &Pool<Sqlite> {
+        &self.pool
+    }
+
     /// Run schema migrations.
     async fn migrate(pool: &Pool<Sqlite>) -> Result<()> {
         // Create migrations tracking table
@@ -349,6 +425,26 @@ impl SqliteIndex {
         .execute(&self.pool)
         .await
         .map_err(|e| db_err("Failed to upsert note", e))?;
+        self.invalidate_derived();
+        self.update_related(|corpus| {
+            corpus.upsert(
+                note.id.as_str(),
+                &note.title,
+                &note.body,
+                &note.metadata.tags,
+            )
+        });
+        self.update_graph_pages(|pages| {
+            pages.insert(
+                note.id.as_str().to_string(),
+                graph_page(
+                    note.id.as_str().to_string(),
+                    note.title.clone(),
+                    &note.metadata,
+                    note.metadata.note_type.clone(),
+                ),
+            );
+        });
 
         Ok(())
     }
@@ -377,6 +473,11 @@ impl SqliteIndex {
             .execute(&self.pool)
             .await
             .map_err(|e| db_err("Failed to remove note", e))?;
+        self.invalidate_derived();
+        self.update_related(|corpus| corpus.remove(id.as_str()));
+        self.update_graph_pages(|pages| {
+            pages.remove(id.as_str());
+        });
 
         // A deleted note no longer appears in the `candidate_notes` scan
         // that `execute_block_query` builds its cache from, so a lingering
@@ -568,9 +669,15 @@ impl SqliteIndex {
                     .custom
                     .get("description")
                     .and_then(|v| v.as_str().map(String::from));
+                // Formula / rollup expression, evaluated at query time.
+                let computed_json = crate::computed::ComputedSpec::from_frontmatter(
+                    crate::property::ValueType::parse(&value_type),
+                    &note.metadata.custom,
+                )
+                .and_then(|spec| serde_json::to_string(&spec).ok());
 
                 sqlx::query(
-                    "INSERT OR REPLACE INTO property_defs (id, name, value_type, choices_json, default_value, multiple_values, hide_empty, hide_by_default, description, note_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+                    "INSERT OR REPLACE INTO property_defs (id, name, value_type, choices_json, default_value, multiple_values, hide_empty, hide_by_default, description, computed_json, note_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
                 )
                 .bind(note.id.as_str())
                 .bind(&note.title)
@@ -581,6 +688,7 @@ impl SqliteIndex {
                 .bind(hide_empty)
                 .bind(hide_by_default)
                 .bind(&description)
+                .bind(&computed_json)
                 .bind(note.id.as_str())
                 .execute(&self.pool)
                 .await
@@ -588,6 +696,8 @@ impl SqliteIndex {
             }
             _ => {}
         }
+        // Computed property definitions are cached with the derived views.
+        self.invalidate_derived();
 
         // Index block-level properties into block_properties table
         self.index_block_properties(note).await?;
@@ -683,9 +793,163 @@ impl SqliteIndex {
             .execute(&self.pool)
             .await
             .map_err(|e| db_err("Failed to clear relation edges", e))?;
+        self.invalidate_derived();
         Ok(())
     }
 
+    /// The resolved page graph over wiki-links and relation edges (see
+    /// [`crate::graph`]), served from cache until the next index write.
+    pub async fn link_graph_snapshot(&self) -> Result<Arc<LinkGraphSnapshot>> {
+        let generation = match self.cached(|cache| &mut cache.link_graph) {
+            Ok(graph) => return Ok(graph),
+            Err(generation) => generation,
+        };
+
+        let pages = self.graph_pages().await?;
+        let links: Vec<(String, String)> = sqlx::query_as(
+            "SELECT DISTINCT source_id, target FROM links WHERE link_type = 'internal'",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| db_err("Failed to fetch links for link graph", e))?;
+        let relations: Vec<(String, String)> =
+            sqlx::query_as("SELECT DISTINCT source_note_id, target_page_id FROM relation_edges")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| db_err("Failed to fetch relation edges for link graph", e))?;
+
+        let graph = Arc::new(LinkGraphSnapshot::build(
+            pages,
+            links
+                .iter()
+                .map(|(source, target)| (source.as_str(), target.as_str())),
+            relations.iter().filter_map(|(source, target)| {
+                Some((source.as_str(), crate::PageId::parse(target)?))
+            }),
+        ));
+        self.store(
+            generation,
+            |cache| &mut cache.link_graph,
+            Arc::clone(&graph),
+        );
+        Ok(graph)
+    }
+
+    /// Every indexed note as a graph node, from the per-note cache.
+    async fn graph_pages(&self) -> Result<Vec<GraphPage>> {
+        let generation = {
+            let cache = self.graph_pages.lock().expect("graph pages mutex poisoned");
+            if let Some(pages) = &cache.1 {
+                return Ok(pages.values().cloned().collect());
+            }
+            cache.0
+        };
+
+        let rows = sqlx::query("SELECT id, title, note_type, content FROM notes")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| db_err("Failed to fetch notes for link graph", e))?;
+        let pages: HashMap<String, GraphPage> = rows
+            .iter()
+            .map(|row| {
+                let content: String = row.get("content");
+                let metadata = crate::storage::markdown::parse_frontmatter(&content)
+                    .map(|(metadata, _)| metadata)
+                    .unwrap_or_default();
+                let slug: String = row.get("id");
+                let page = graph_page(
+                    slug.clone(),
+                    row.get("title"),
+                    &metadata,
+                    row.try_get("note_type").ok().flatten(),
+                );
+                (slug, page)
+            })
+            .collect();
+        let list = pages.values().cloned().collect();
+        let mut cache = self.graph_pages.lock().expect("graph pages mutex poisoned");
+        if cache.0 == generation {
+            cache.1 = Some(pages);
+        }
+        Ok(list)
+    }
+
+    /// Apply one note's change to the graph-page cache, if loaded.
+    fn update_graph_pages(&self, change: impl FnOnce(&mut HashMap<String, GraphPage>)) {
+        let mut cache = self.graph_pages.lock().expect("graph pages mutex poisoned");
+        cache.0 += 1;
+        if let Some(pages) = cache.1.as_mut() {
+            change(pages);
+        }
+    }
+
+    /// A derived view if it's cached, else the generation a fresh build
+    /// must still match to be stored (see [`store`](Self::store)).
+    fn cached<T: Clone>(
+        &self,
+        slot: fn(&mut DerivedCache) -> &mut Option<T>,
+    ) -> std::result::Result<T, u64> {
+        let mut cache = self.derived.lock().expect("derived mutex poisoned");
+        let generation = cache.generation;
+        slot(&mut cache).clone().ok_or(generation)
+    }
+
+    /// Cache a view built at `generation`, unless a write has since
+    /// invalidated it.
+    fn store<T>(&self, generation: u64, slot: fn(&mut DerivedCache) -> &mut Option<T>, value: T) {
+        let mut cache = self.derived.lock().expect("derived mutex poisoned");
+        if cache.generation == generation {
+            *slot(&mut cache) = Some(value);
+        }
+    }
+
+    fn invalidate_derived(&self) {
+        let mut cache = self.derived.lock().expect("derived mutex poisoned");
+        *cache = DerivedCache {
+            generation: cache.generation + 1,
+            ..DerivedCache::default()
+        };
+    }
+
+    /// Pages and blocks most like note `id` (see [`crate::related`]), at
+    /// most `limit` of each. `None` when the note isn't indexed.
+    pub async fn related_notes(&self, id: &NoteId, limit: usize) -> Result<Option<RelatedNotes>> {
+        let graph = self.link_graph_snapshot().await?;
+        let (generation, cached) = {
+            let cache = self.related.lock().expect("related mutex poisoned");
+            (cache.0, cache.1.clone())
+        };
+        if let Some(corpus) = cached {
+            return Ok(corpus.related(id.as_str(), &graph, limit));
+        }
+
+        let rows = sqlx::query("SELECT id, title, body, tags FROM notes")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| db_err("Failed to fetch notes for related notes", e))?;
+        let mut corpus = RelatedCorpus::default();
+        for row in &rows {
+            let tags: Vec<String> =
+                serde_json::from_str(&row.get::<String, _>("tags")).unwrap_or_default();
+            corpus.upsert(row.get("id"), row.get("title"), row.get("body"), &tags);
+        }
+        let related = corpus.related(id.as_str(), &graph, limit);
+        let mut cache = self.related.lock().expect("related mutex poisoned");
+        if cache.0 == generation {
+            cache.1 = Some(Arc::new(corpus));
+        }
+        Ok(related)
+    }
+
+    /// Apply one note's change to the related-notes corpus, if loaded.
+    fn update_related(&self, change: impl FnOnce(&mut RelatedCorpus)) {
+        let mut cache = self.related.lock().expect("related mutex poisoned");
+        cache.0 += 1;
+        if let Some(corpus) = cache.1.as_mut() {
+            change(Arc::make_mut(corpus));
+        }
+    }
+
     /// Take a consistent snapshot of the database into `target` via
     /// SQLite's `VACUUM INTO`. Unlike a raw `fs::copy` of `tesela.db`,
     /// this is safe while the database is open in WAL mode — `VACUUM
@@ -738,6 +1002,9 @@ impl SqliteIndex {
             .execute(&self.pool)
             .await
             .map_err(|e| db_err("Failed to clear notes", e))?;
+        self.invalidate_derived();
+        self.update_related(|corpus| *corpus = RelatedCorpus::default());
+        self.update_graph_pages(HashMap::clear);
 
         // Re-insert all notes. Mirror `reindex` (upsert + index_type_info)
         // rather than a bare `upsert_note`, so Tag/Property pages repopulate
@@ -860,6 +1127,45 @@ impl SqliteIndex {
             .collect())
     }
 
+    /// Formula and rollup properties, compiled and in evaluation order;
+    /// served from cache until the next index write. A malformed
+    /// expression, or a formula in a reference cycle, is skipped — the
+    /// property then has no value anywhere — rather than failing every
+    /// query.
+    async fn computed_properties(&self) -> Result<Arc<[crate::computed::ComputedProperty]>> {
+        use crate::computed::{evaluation_order, ComputedProperty, ComputedSpec};
+        let generation = match self.cached(|cache| &mut cache.computed) {
+            Ok(computed) => return Ok(computed),
+            Err(generation) => generation,
+        };
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT name, computed_json FROM property_defs WHERE computed_json IS NOT NULL ORDER BY name",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| db_err("Failed to get computed property defs", e))?;
+        let compiled = rows
+            .into_iter()
+            .filter_map(|(name, json)| {
+                let spec: ComputedSpec = serde_json::from_str(&json).ok()?;
+                ComputedProperty::compile(&name, &spec)
+                    .map_err(|e| debug!("computed property '{name}' skipped: {e}"))
+                    .ok()
+            })
+            .collect();
+        let (ordered, cyclic) = evaluation_order(compiled);
+        for name in cyclic {
+            debug!("computed property '{name}' skipped: formula reference cycle");
+        }
+        let computed: Arc<[ComputedProperty]> = ordered.into();
+        self.store(
+            generation,
+            |cache| &mut cache.computed,
+            Arc::clone(&computed),
+        );
+        Ok(computed)
+    }
+
     /// Get a single tag definition with resolved property schemas (walks extends chain).
     pub async fn get_resolved_tag_def(
         &self,
@@ -1126,6 +1432,11 @@ impl SqliteIndex {
 
 #[async_trait]
 impl SearchIndex for SqliteIndex {
+    /// FTS5 bm25 relevance, nudged by link-graph PageRank: `rank` (lower
+    /// is better) is scaled by `1 + SEARCH_GRAPH_WEIGHT · ln(1 + n·pr)`, so
+    /// a well-linked page edges out an orphan with similar text while text
+    /// relevance still dominates. The top `(offset + limit) ×
+    /// SEARCH_RERANK_WINDOW` FTS matches are re-ranked before paging.
     async fn search(&self, query: &str, limit: usize, offset: usize) -> Result<Vec<SearchHit>> {
         let fts_query = Self::prepare_fts_query(query);
 
@@ -1138,12 +1449,18 @@ impl SearchIndex for SqliteIndex {
             JOIN notes n ON notes_fts.id = n.id
             WHERE notes_fts MATCH ?
             ORDER BY rank
-            LIMIT ? OFFSET ?
+            LIMIT ?
             "#,
         )
         .bind(&fts_query)
-        .bind(limit as i64)
-        .bind(offset as i64)
+        .bind(
+            i64::try_from(
+                offset
+                    .saturating_add(limit)
+                    .saturating_mul(SEARCH_RERANK_WINDOW),
+            )
+            .unwrap_or(i64::MAX),
+        )
         .fetch_all(&self.pool)
         .await
         .map_err(|e| db_err("Failed to search notes", e))?;
@@ -1152,7 +1469,17 @@ impl SearchIndex for SqliteIndex {
         for row in &rows {
             results.push(queries::row_to_search_hit(row)?);
         }
-        Ok(results)
+
+        let graph = self.link_graph_snapshot().await?;
+        let page_rank = graph.page_rank();
+        let pages = page_rank.len() as f64;
+        for hit in &mut results {
+            if let Some(i) = graph.resolve(hit.note_id.as_str()) {
+                hit.rank *= 1.0 + SEARCH_GRAPH_WEIGHT * (pages * page_rank[i]).ln_1p();
+            }
+        }
+        results.sort_by(|a, b| a.rank.total_cmp(&b.rank));
+        Ok(results.into_iter().skip(offset).take(limit).collect())
     }
 
     async fn suggest(&self, partial: &str) -> Result<Vec<String>> {
@@ -1281,50 +1608,59 @@ impl SearchIndex for SqliteIndex {
         note_id: &NoteId,
         limit: usize,
     ) -> Result<Vec<crate::note::NoteVersion>> {
-        use crate::note::NoteVersion;
-        let rows = sqlx::query(
-            r#"SELECT id, note_id, version_number, content, prev_content, created_at
+        use super::versions::{materialize, StoredVersion};
+        // Newest first from the head, which is always stored whole, so
+        // every delta row's newer neighbour is already decoded.
+        let rows = sqlx::query(&format!(
+            r#"SELECT {}
                FROM note_versions
                WHERE note_id = ?
                ORDER BY version_number DESC
                LIMIT ?"#,
-        )
+            StoredVersion::COLUMNS
+        ))
         .bind(note_id.as_str())
         .bind(limit as i64)
         .fetch_all(&self.pool)
         .await
         .map_err(|e| db_err("Failed to list note versions", e))?;
-        Ok(rows
-            .into_iter()
-            .map(|row| NoteVersion {
-                id: row.get("id"),
-                note_id: NoteId::from(row.get::<String, _>("note_id")),
-                version_number: row.get("version_number"),
-                content: row.get("content"),
-                prev_content: row.try_get("prev_content").ok().flatten(),
-                created_at: row.get("created_at"),
-            })
-            .collect())
+        materialize(rows.iter().map(StoredVersion::from_row).collect())
     }
 
     async fn get_version(&self, version_id: i64) -> Result<Option<crate::note::NoteVersion>> {
-        use crate::note::NoteVersion;
-        let row = sqlx::query(
-            r#"SELECT id, note_id, version_number, content, prev_content, created_at
-               FROM note_versions WHERE id = ?"#,
-        )
+        use super::versions::{materialize, StoredVersion};
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM note_versions WHERE id = ?",
+            StoredVersion::COLUMNS
+        ))
         .bind(version_id)
         .fetch_optional(&self.pool)
         .await
         .map_err(|e| db_err("Failed to get note version", e))?;
-        Ok(row.map(|row| NoteVersion {
-            id: row.get("id"),
-            note_id: NoteId::from(row.get::<String, _>("note_id")),
-            version_number: row.get("version_number"),
-            content: row.get("content"),
-            prev_content: row.try_get("prev_content").ok().flatten(),
-            created_at: row.get("created_at"),
-        }))
+        let Some(row) = row.as_ref().map(StoredVersion::from_row) else {
+            return Ok(None);
+        };
+        // A delta row decodes from the head down to it.
+        let rows = if row.content_delta {
+            sqlx::query(&format!(
+                r#"SELECT {}
+                   FROM note_versions
+                   WHERE note_id = ? AND version_number >= ?
+                   ORDER BY version_number DESC"#,
+                StoredVersion::COLUMNS
+            ))
+            .bind(&row.note_id)
+            .bind(row.version_number)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| db_err("Failed to load note version chain", e))?
+            .iter()
+            .map(StoredVersion::from_row)
+            .collect()
+        } else {
+            vec![row]
+        };
+        Ok(materialize(rows)?.pop())
     }
 
     async fn calendar_marks(&self, from: &str, to: &str) -> Result<crate::query::CalendarMarks> {
@@ -1646,6 +1982,22 @@ impl SqliteIndex {
             .await
     }
 
+    /// Whether `query` matches one block of `note_id` (or, with no
+    /// `block_id`, any row of that note), evaluated like
+    /// [`Self::execute_query_with_context`] but without scanning the rest
+    /// of the corpus. Page queries test the note's page row either way.
+    pub async fn subject_matches_with_context(
+        &self,
+        query: &crate::query::ParsedQuery,
+        note_id: &str,
+        block_id: Option<&str>,
+        context: &crate::query::QueryContext,
+    ) -> Result<bool> {
+        let scope = Scope { note_id, block_id };
+        let items = self.query_items(query, Some(context), Some(&scope)).await?;
+        Ok(!items.is_empty())
+    }
+
     async fn execute_query_inner(
         &self,
         query: &crate::query::ParsedQuery,
@@ -1653,21 +2005,178 @@ impl SqliteIndex {
         sort: Option<&str>,
         context: Option<&crate::query::QueryContext>,
     ) -> Result<crate::query::QueryResult> {
-        use crate::query::{Kind, QueryResult};
-        let mut items = match query.kind {
-            Kind::Block => self.execute_block_query(query, context).await?,
-            Kind::Page => self.execute_page_query(query, context).await?,
-        };
+        use crate::query::QueryResult;
+        let mut items = self.query_items(query, context, None).await?;
         // DSL-embedded `ORDER BY` wins over the external param so a
         // saved-query note can carry its own sort spec; the external
         // `sort` arg remains the fallback for ad-hoc callers that
         // want to override without modifying the DSL.
         let effective_sort = query.sort.as_deref().or(sort);
-        apply_sort(&mut items, effective_sort);
+        if effective_sort.is_some() {
+            apply_sort(&mut items, effective_sort, &self.property_type_map().await?);
+        }
         let groups = apply_group(items, group);
         Ok(QueryResult { groups })
     }
 
+    /// The unsorted rows `query` matches, over the whole corpus or just
+    /// `scope`.
+    async fn query_items(
+        &self,
+        query: &crate::query::ParsedQuery,
+        context: Option<&crate::query::QueryContext>,
+        scope: Option<&Scope<'_>>,
+    ) -> Result<Vec<crate::query::QueryItem>> {
+        use crate::query::Kind;
+        // `is:orphan` / `links-to:` read the link graph from the context,
+        // dotted traversal and `has-backlink-from:` the relation edges;
+        // load each only for queries that use them.
+        let uses_graph = crate::query::uses_link_graph(query);
+        let uses_relations = crate::query::uses_relations(query);
+        // Formula and rollup values are filled into each row before it's
+        // matched; rollups (page rows only) read the relation edges too.
+        let computed = self.computed_properties().await?;
+        let rolls_up = query.kind == Kind::Page && computed.iter().any(|c| c.is_rollup());
+        let (relations, pages) = if uses_relations || rolls_up {
+            let (relations, pages) = self.relation_index().await?;
+            (Some(relations), Some(pages))
+        } else {
+            (None, None)
+        };
+        let extended;
+        let context = if uses_graph || uses_relations {
+            let mut context = context.cloned().unwrap_or_default();
+            if uses_graph {
+                context.graph = Some(self.link_graph_snapshot().await?);
+            }
+            if uses_relations {
+                context.relations = relations.clone();
+                // `[[Page]]` values past a hop resolve through `pages`;
+                // callers without a page directory get the index's.
+                if context.pages.is_empty() {
+                    context.pages = pages.as_deref().cloned().unwrap_or_default();
+                }
+            }
+            extended = context;
+            Some(&extended)
+        } else {
+            context
+        };
+        let computed = Computed {
+            properties: &computed,
+            relations: relations.as_deref(),
+            today: chrono::Local::now().date_naive(),
+        };
+        match query.kind {
+            Kind::Block => {
+                self.execute_block_query(query, context, &computed, scope)
+                    .await
+            }
+            Kind::Page => {
+                self.execute_page_query(query, context, &computed, scope)
+                    .await
+            }
+        }
+    }
+
+    /// Every relation edge with the rows at both ends, for dotted traversal
+    /// and `has-backlink-from:`, plus the page directory those pages form.
+    /// Served from cache until the next index write.
+    async fn relation_index(&self) -> Result<RelationSnapshot> {
+        let generation = match self.cached(|cache| &mut cache.relations) {
+            Ok(snapshot) => return Ok(snapshot),
+            Err(generation) => generation,
+        };
+        let rows = sqlx::query("SELECT id, title, tags, note_type, content, body FROM notes")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| db_err("Failed to fetch notes for relation index", e))?;
+        let edges: Vec<(String, String, Option<String>, String, String)> = sqlx::query_as(
+            "SELECT source_page_id, source_note_id, source_block_id, property_key, target_page_id
+             FROM relation_edges",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| db_err("Failed to fetch relation edges for relation index", e))?;
+
+        let mut index = crate::query::RelationIndex::default();
+        let mut pages = Vec::new();
+        let mut notes = std::collections::HashMap::new();
+        for row in &rows {
+            let id: String = row.get("id");
+            let title: String = row.get("title");
+            let note_type: Option<String> = row.try_get("note_type").ok().flatten();
+            let content: String = row.get("content");
+            let page = page_block(
+                &id,
+                &title,
+                &row.get::<String, _>("tags"),
+                note_type.as_deref(),
+                &content,
+            );
+            let metadata = crate::storage::markdown::parse_frontmatter(&content)
+                .map(|(metadata, _)| metadata)
+                .unwrap_or_default();
+            if let Some(page_id) = metadata
+                .custom
+                .get(crate::storage::markdown::TESELA_PAGE_ID_KEY)
+                .and_then(|value| value.as_str())
+                .and_then(crate::PageId::parse)
+            {
+                index.add_page(page_id, page.clone());
+                pages.push(crate::query::QueryPage {
+                    page_id,
+                    slug: id.clone(),
+                    title: title.clone(),
+                    aliases: metadata.aliases,
+                    deleted: false,
+                });
+            }
+            notes.insert(id, (page, row.get::<String, _>("body"), note_type, None));
+        }
+
+        for (source_page_id, source_note_id, source_block_id, property_key, target) in edges {
+            let (Some(source_page_id), Some(target_page_id)) = (
+                crate::PageId::parse(&source_page_id),
+                crate::PageId::parse(&target),
+            ) else {
+                continue;
+            };
+            let Some((page, body, note_type, blocks)) = notes.get_mut(&source_note_id) else {
+                continue;
+            };
+            let source = match &source_block_id {
+                None => Some(page.clone()),
+                Some(bid) => blocks
+                    .get_or_insert_with(|| {
+                        let mut blocks = self.parsed_blocks_cached(&source_note_id, body);
+                        for block in blocks.iter_mut() {
+                            block.parent_note_type = note_type.clone();
+                        }
+                        blocks
+                    })
+                    .iter()
+                    .find(|block| block.bid.as_ref() == Some(bid))
+                    .cloned(),
+            };
+            if let Some(source) = source {
+                index.add_edge(
+                    &crate::RelationEdge {
+                        source_page_id,
+                        source_note_id,
+                        source_block_id,
+                        property_key,
+                        target_page_id,
+                    },
+                    source,
+                );
+            }
+        }
+        let snapshot = (Arc::new(index), Arc::new(pages));
+        self.store(generation, |cache| &mut cache.relations, snapshot.clone());
+        Ok(snapshot)
+    }
+
     /// Execute a `kind:block` query. Strategy: pull a candidate set of notes
     /// from SQL using the most selective tag filter (or all notes if none),
     /// parse blocks, then refine in-memory with [`crate::query::block_matches`].
@@ -1675,6 +2184,8 @@ impl SqliteIndex {
         &self,
         query: &crate::query::ParsedQuery,
         context: Option<&crate::query::QueryContext>,
+        computed: &Computed<'_>,
+        scope: Option<&Scope<'_>>,
     ) -> Result<Vec<crate::query::QueryItem>> {
         use crate::query::{
             block_matches_typed, block_matches_typed_with_context, Kind, QueryItem, QueryOp,
@@ -1692,7 +2203,23 @@ impl SqliteIndex {
             .map(|f| f.value.as_str());
 
         let candidate_notes: Vec<(String, String, String, Option<String>)> =
-            if let Some(tag) = prefilter_tag {
+            if let Some(scope) = scope {
+                sqlx::query("SELECT id, title, body, note_type FROM notes WHERE id = ?")
+                    .bind(scope.note_id)
+                    .fetch_all(&self.pool)
+                    .await
+                    .map_err(|e| db_err("Failed to fetch scoped note for block query", e))?
+                    .into_iter()
+                    .map(|row| {
+                        (
+                            row.get("id"),
+                            row.get("title"),
+                            row.get("body"),
+                            row.try_get::<Option<String>, _>("note_type").ok().flatten(),
+                        )
+                    })
+                    .collect()
+            } else if let Some(tag) = prefilter_tag {
                 // Pre-filter is intentionally over-inclusive — `block_matches`
                 // refines below. `body LIKE '%<tag>%'` catches both legacy
                 // `#<tag>` inline syntax AND the `tags:: <tag>` continuation-line
@@ -1736,6 +2263,7 @@ impl SqliteIndex {
         let mut out = Vec::new();
         for (note_id, note_title, body, page_note_type) in &candidate_notes {
             let mut blocks = self.parsed_blocks_cached(note_id, body);
+            let wanted = scope.and_then(|scope| scope.block_id);
             // Enrich every block with its containing page's note_type so
             // DSL predicates that depend on parent metadata (`on:system-
             // pages`, `on:daily-page`'s fallback branch) can run inside
@@ -1743,9 +2271,13 @@ impl SqliteIndex {
             // time. Cheap (a clone per block) and keeps the matcher pure.
             for b in blocks.iter_mut() {
                 b.parent_note_type = page_note_type.clone();
+                computed.apply(b, &types);
             }
             // Refine each block in-memory.
             for (idx, block) in blocks.iter().enumerate() {
+                if wanted.is_some_and(|wanted| wanted != block.id) {
+                    continue;
+                }
                 let matched = match context {
                     Some(context) => {
                         block_matches_typed_with_context(block, query, &types, context).matched
@@ -1857,21 +2389,30 @@ impl SqliteIndex {
         &self,
         query: &crate::query::ParsedQuery,
         context: Option<&crate::query::QueryContext>,
+        computed: &Computed<'_>,
+        scope: Option<&Scope<'_>>,
     ) -> Result<Vec<crate::query::QueryItem>> {
         use crate::query::{
             block_matches_typed, block_matches_typed_with_context, Kind, QueryItem,
         };
-        use std::collections::HashMap;
 
         // L5: typed-comparison registry — built once, consulted per page-block.
         let types = self.property_type_map().await?;
 
         // SELECT id, title, tags, note_type, plus full content for property parsing.
-        let rows = sqlx::query(
-            "SELECT id, title, tags, note_type, content FROM notes ORDER BY modified_at DESC",
-        )
-        .fetch_all(&self.pool)
-        .await
+        let rows = match scope {
+            Some(scope) => {
+                sqlx::query("SELECT id, title, tags, note_type, content FROM notes WHERE id = ?")
+                    .bind(scope.note_id)
+                    .fetch_all(&self.pool)
+                    .await
+            }
+            None => sqlx::query(
+                "SELECT id, title, tags, note_type, content FROM notes ORDER BY modified_at DESC",
+            )
+            .fetch_all(&self.pool)
+            .await,
+        }
         .map_err(|e| db_err("Failed to fetch notes for page query", e))?;
 
         let mut out = Vec::new();
@@ -1882,55 +2423,8 @@ impl SqliteIndex {
             let note_type: Option<String> = row.try_get("note_type").ok().flatten();
             let content: String = row.get("content");
 
-            let tags: Vec<String> = serde_json::from_str(&tags_json).unwrap_or_default();
-            let mut props: HashMap<String, String> = HashMap::new();
-            // Pull properties from frontmatter — naive line-by-line parse looking
-            // for `key: value` between `---` fences.
-            if let Some(fm) = extract_frontmatter(&content) {
-                for line in fm.lines() {
-                    if let Some((k, v)) = line.split_once(':') {
-                        let k = k.trim();
-                        let v = v.trim().trim_matches('"');
-                        if !k.is_empty() && !v.is_empty() {
-                            // YAML uses `type:`; metadata API exposes it as
-                            // `note_type`. Alias on insert so DSL filters that
-                            // reference `note_type:` resolve correctly.
-                            let canonical = if k == "type" { "note_type" } else { k };
-                            props.insert(canonical.to_string(), v.to_string());
-                        }
-                    }
-                }
-            }
-            // Canonical page-owned values render as `key:: value` lines
-            // immediately after frontmatter. They are authoritative over a
-            // legacy YAML custom field of the same name.
-            for (key, value) in crate::note_tree::parse_note(&content).page_properties {
-                props.insert(key, value);
-            }
-            if let Some(nt) = &note_type {
-                props.insert("note_type".to_string(), nt.clone());
-            }
-
-            // Synthetic page-block for matcher. inherited_tags is empty for pages.
-            // inline/trailing tags are treated as empty here — page-level tags
-            // come from frontmatter, not from positional `#tag` tokens in body.
-            let pseudo = ParsedBlock {
-                id: id.clone(),
-                bid: None,
-                text: title.clone(),
-                raw_text: title.clone(),
-                tags: tags.clone(),
-                inline_tags: vec![],
-                trailing_tags: vec![],
-                inherited_tags: vec![],
-                properties: props.clone(),
-                indent_level: 0,
-                note_id: id.clone(),
-                // Page-kind rows don't have a "parent" — the row IS the
-                // page — so leave None. `on:*` predicates that depend
-                // on this field don't make sense for page queries.
-                parent_note_type: None,
-            };
+            let mut pseudo = page_block(&id, &title, &tags_json, note_type.as_deref(), &content);
+            computed.apply(&mut pseudo, &types);
             let matched = match context {
                 Some(context) => {
                     block_matches_typed_with_context(&pseudo, query, &types, context).matched
@@ -1947,8 +2441,8 @@ impl SqliteIndex {
                 text: title,
                 parent_breadcrumb: vec![],
                 kind: Kind::Page,
-                primary_tag: tags.first().cloned(),
-                properties: props,
+                primary_tag: pseudo.tags.first().cloned(),
+                properties: pseudo.properties,
                 page_note_type: note_type,
             });
         }
@@ -1956,6 +2450,91 @@ impl SqliteIndex {
     }
 }
 
+/// The one note (and optionally one block) a scoped query run looks at.
+struct Scope<'a> {
+    note_id: &'a str,
+    block_id: Option<&'a str>,
+}
+
+/// What a query run needs to fill in formula and rollup values.
+struct Computed<'a> {
+    properties: &'a [crate::computed::ComputedProperty],
+    relations: Option<&'a crate::query::RelationIndex>,
+    today: chrono::NaiveDate,
+}
+
+impl Computed<'_> {
+    fn apply(
+        &self,
+        row: &mut ParsedBlock,
+        types: &std::collections::HashMap<String, crate::property::ValueType>,
+    ) {
+        if !self.properties.is_empty() {
+            crate::computed::apply(self.properties, row, self.relations, types, self.today);
+        }
+    }
+}
+
+/// The synthetic page-kind block the query matcher sees for a `notes` row:
+/// frontmatter fields and page-level properties, frontmatter tags.
+fn page_block(
+    id: &str,
+    title: &str,
+    tags_json: &str,
+    note_type: Option<&str>,
+    content: &str,
+) -> ParsedBlock {
+    let tags: Vec<String> = serde_json::from_str(tags_json).unwrap_or_default();
+    let mut props: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+    // Pull properties from frontmatter — naive line-by-line parse looking
+    // for `key: value` between `---` fences.
+    if let Some(fm) = extract_frontmatter(content) {
+        for line in fm.lines() {
+            if let Some((k, v)) = line.split_once(':') {
+                let k = k.trim();
+                let v = v.trim().trim_matches('"');
+                if !k.is_empty() && !v.is_empty() {
+                    // YAML uses `type:`; metadata API exposes it as
+                    // `note_type`. Alias on insert so DSL filters that
+                    // reference `note_type:` resolve correctly.
+                    let canonical = if k == "type" { "note_type" } else { k };
+                    props.insert(canonical.to_string(), v.to_string());
+                }
+            }
+        }
+    }
+    // Canonical page-owned values render as `key:: value` lines
+    // immediately after frontmatter. They are authoritative over a
+    // legacy YAML custom field of the same name.
+    for (key, value) in crate::note_tree::parse_note(content).page_properties {
+        props.insert(key, value);
+    }
+    if let Some(nt) = note_type {
+        props.insert("note_type".to_string(), nt.to_string());
+    }
+
+    // inherited_tags is empty for pages. inline/trailing tags are treated
+    // as empty here — page-level tags come from frontmatter, not from
+    // positional `#tag` tokens in body.
+    ParsedBlock {
+        id: id.to_string(),
+        bid: None,
+        text: title.to_string(),
+        raw_text: title.to_string(),
+        tags,
+        inline_tags: vec![],
+        trailing_tags: vec![],
+        inherited_tags: vec![],
+        properties: props,
+        indent_level: 0,
+        note_id: id.to_string(),
+        // Page-kind rows don't have a "parent" — the row IS the
+        // page — so leave None. `on:*` predicates that depend
+        // on this field don't make sense for page queries.
+        parent_note_type: None,
+    }
+}
+
 /// Extract the YAML frontmatter body (between the two `---` fences) from a
 /// note's full content. Returns `None` if there is no frontmatter.
 fn extract_frontmatter(content: &str) -> Option<&str> {
@@ -1969,8 +2548,15 @@ fn extract_frontmatter(content: &str) -> Option<&str> {
 
 /// Sort `items` in place by a comma-separated `key [asc|desc]` list. Property
 /// keys map to the row's `properties` map; `title` and `text` map to the row
-/// fields directly. Unknown keys are ignored.
-fn apply_sort(items: &mut [crate::query::QueryItem], sort: Option<&str>) {
+/// fields directly. Unknown keys are ignored. Number and computed
+/// properties order numerically; other declared types through
+/// [`crate::query::compare_typed`]; everything else as text.
+fn apply_sort(
+    items: &mut [crate::query::QueryItem],
+    sort: Option<&str>,
+    types: &std::collections::HashMap<String, crate::property::ValueType>,
+) {
+    use crate::property::ValueType;
     let Some(s) = sort else {
         return;
     };
@@ -1988,7 +2574,13 @@ fn apply_sort(items: &mut [crate::query::QueryItem], sort: Option<&str>) {
         for (k, desc) in &keys {
             let av = field(a, k);
             let bv = field(b, k);
-            let ord = av.cmp(&bv);
+            let ord = match types.get(k) {
+                Some(ValueType::Number | ValueType::Formula | ValueType::Rollup) => {
+                    compare_numbers_first(&av, &bv)
+                }
+                Some(vt) => crate::query::compare_typed(&av, &bv, *vt),
+                None => av.cmp(&bv),
+            };
             if ord != std::cmp::Ordering::Equal {
                 return if *desc { ord.reverse() } else { ord };
             }
@@ -1997,6 +2589,19 @@ fn apply_sort(items: &mut [crate::query::QueryItem], sort: Option<&str>) {
     });
 }
 
+/// Numbers by value ahead of anything that doesn't parse as one, which
+/// orders as text. Unlike a mixed numeric/text comparison this is a total
+/// order, which `sort_by` relies on.
+fn compare_numbers_first(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
 fn field(item: &crate::query::QueryItem, key: &str) -> String {
     match key {
         "title" => item.title.to_ascii_lowercase(),
@@ -2165,6 +2770,7 @@ impl LinkGraph for SqliteIndex {
         tx.commit()
             .await
             .map_err(|e| db_err("Failed to commit transaction", e))?;
+        self.invalidate_derived();
 
         Ok(())
     }
@@ -2175,6 +2781,7 @@ impl LinkGraph for SqliteIndex {
             .execute(&self.pool)
             .await
             .map_err(|e| db_err("Failed to remove links", e))?;
+        self.invalidate_derived();
 
         Ok(())
     }
@@ -2193,6 +2800,7 @@ impl LinkGraph for SqliteIndex {
         .execute(&self.pool)
         .await
         .map_err(|error| db_err("Failed to upsert relation edge", error))?;
+        self.invalidate_derived();
         Ok(())
     }
 
@@ -2214,6 +2822,7 @@ impl LinkGraph for SqliteIndex {
         .execute(&self.pool)
         .await
         .map_err(|error| db_err("Failed to remove relation edge", error))?;
+        self.invalidate_derived();
         Ok(())
     }
 
@@ -2223,6 +2832,7 @@ impl LinkGraph for SqliteIndex {
             .execute(&self.pool)
             .await
             .map_err(|error| db_err("Failed to remove relation edges for note", error))?;
+        self.invalidate_derived();
         Ok(())
     }
 
@@ -2706,6 +3316,45 @@ mod tests {
         assert!(backlinks.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_related_notes_follow_upserts() {
+        let index = SqliteIndex::open_in_memory().await.unwrap();
+        let garden = "tomato seedlings need compost and sunlight before transplanting";
+        for (id, title, body) in [
+            ("tomatoes", "Tomatoes", garden),
+            ("seedlings", "Seedlings", garden),
+            (
+                "taxes",
+                "Taxes",
+                "quarterly estimated payments and receipts",
+            ),
+        ] {
+            index
+                .upsert_note(&make_test_note(id, title, body, &[]))
+                .await
+                .unwrap();
+        }
+        let tomatoes = NoteId::new("tomatoes");
+        let related = index.related_notes(&tomatoes, 5).await.unwrap().unwrap();
+        let slugs: Vec<&str> = related.pages.iter().map(|p| p.slug.as_str()).collect();
+        assert_eq!(slugs, ["seedlings"]);
+
+        // The loaded corpus follows later writes without a reload.
+        index
+            .upsert_note(&make_test_note("taxes", "Taxes", garden, &[]))
+            .await
+            .unwrap();
+        index.remove_note(&NoteId::new("seedlings")).await.unwrap();
+        let related = index.related_notes(&tomatoes, 5).await.unwrap().unwrap();
+        let slugs: Vec<&str> = related.pages.iter().map(|p| p.slug.as_str()).collect();
+        assert_eq!(slugs, ["taxes"]);
+        assert!(index
+            .related_notes(&NoteId::new("seedlings"), 5)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
     #[tokio::test]
     async fn test_rebuild_fts_index() {
         let index = SqliteIndex::open_in_memory().await.unwrap();
@@ -2995,7 +3644,7 @@ mod tests {
             make_query_item("bare-b", &[("scheduled", "2026-05-24")]),
             make_query_item("older", &[("scheduled", "2026-05-23")]),
         ];
-        apply_sort(&mut items, Some("scheduled desc"));
+        apply_sort(&mut items, Some("scheduled desc"), &Default::default());
         let order: Vec<&str> = items.iter().map(|i| i.text.as_str()).collect();
         // The three 05-24 entries tie; stable sort preserves their
         // input order. `older` (05-23) must come last under DESC.
@@ -3012,7 +3661,7 @@ mod tests {
             make_query_item("b", &[("scheduled", "2026-05-23")]),
             make_query_item("c", &[("scheduled", "2026-05-22")]),
         ];
-        apply_sort(&mut items, Some("scheduled"));
+        apply_sort(&mut items, Some("scheduled"), &Default::default());
         let order: Vec<&str> = items.iter().map(|i| i.text.as_str()).collect();
         assert_eq!(order, vec!["c", "b", "a"]);
     }
@@ -3025,7 +3674,7 @@ mod tests {
             make_query_item("b", &[("scheduled", "[[2026-05-24]]")]),
             make_query_item("c", &[("scheduled", "2026-05-23")]),
         ];
-        apply_sort(&mut items, Some("scheduled desc"));
+        apply_sort(&mut items, Some("scheduled desc"), &Default::default());
         let order: Vec<&str> = items.iter().map(|i| i.text.as_str()).collect();
         assert_eq!(order, vec!["b", "c", "a"]);
     }
@@ -3040,11 +3689,36 @@ mod tests {
             make_query_item("b", &[("status", "doing")]),
             make_query_item("c", &[("status", "blocked")]),
         ];
-        apply_sort(&mut items, Some("status"));
+        apply_sort(&mut items, Some("status"), &Default::default());
         let order: Vec<&str> = items.iter().map(|i| i.text.as_str()).collect();
         assert_eq!(order, vec!["c", "b", "a"]); // blocked, doing, todo
     }
 
+    /// Number and computed columns sort numbers by value, ahead of any
+    /// value that isn't one; undeclared columns stay textual, so `"10"`
+    /// sorts before `"9"` there.
+    #[test]
+    fn apply_sort_orders_numerically_only_for_number_typed_columns() {
+        use crate::property::ValueType;
+        let items = || {
+            vec![
+                make_query_item("a", &[("score", "10"), ("code", "10")]),
+                make_query_item("b", &[("score", "1x"), ("code", "1x")]),
+                make_query_item("c", &[("score", "9"), ("code", "9")]),
+                make_query_item("d", &[("score", "NaN"), ("code", "NaN")]),
+            ]
+        };
+        let types = [("score".to_string(), ValueType::Formula)].into();
+        let mut sorted = items();
+        apply_sort(&mut sorted, Some("score"), &types);
+        let order: Vec<&str> = sorted.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(order, vec!["c", "a", "d", "b"]);
+        let mut sorted = items();
+        apply_sort(&mut sorted, Some("code"), &types);
+        let order: Vec<&str> = sorted.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(order, vec!["a", "b", "c", "d"]);
+    }
+
     /// A property value that *contains* `[[…]]` mid-string (not the
     /// whole value) must keep its brackets — only fully-wrapped values
     /// are unwrapped, since mid-string brackets are real content.
@@ -3054,7 +3728,7 @@ mod tests {
             make_query_item("a", &[("status", "see [[Project]] notes")]),
             make_query_item("b", &[("status", "blocked")]),
         ];
-        apply_sort(&mut items, Some("status"));
+        apply_sort(&mut items, Some("status"), &Default::default());
         // "blocked" < "see [[Project]] notes" lexically — verify the
         // mid-string brackets weren't blindly stripped.
         let order: Vec<&str> = items.iter().map(|i| i.text.as_str()).collect();
@@ -3713,6 +4387,7 @@ mod tests {
                 aliases: Vec::new(),
                 deleted: false,
             }],
+            ..Default::default()
         };
         let block = index
             .execute_query_with_context(
@@ -3736,6 +4411,298 @@ mod tests {
         assert_eq!(item_texts(&page_result), vec!["Page source"]);
     }
 
+    #[tokio::test]
+    async fn graph_predicates_follow_links_and_refresh_on_link_changes() {
+        let index = SqliteIndex::open_in_memory().await.unwrap();
+        for note in [
+            make_test_note("a", "A", "- See [[B page]]\n- unrelated", &[]),
+            make_test_note("b", "B page", "- plain", &[]),
+            make_test_note("c", "C", "- alone", &[]),
+        ] {
+            index.reindex(&note).await.unwrap();
+            index
+                .update_links(&note.id, &crate::link::extract_wiki_links(&note.body))
+                .await
+                .unwrap();
+        }
+        let run = |dsl: &'static str| {
+            let index = &index;
+            async move {
+                let result = index
+                    .execute_query(&crate::query::parse_query(dsl), None, None)
+                    .await
+                    .unwrap();
+                let mut texts = item_texts(&result);
+                texts.sort();
+                texts
+            }
+        };
+        assert_eq!(run("kind:page is:orphan").await, vec!["C"]);
+        assert_eq!(run("kind:page -is:orphan").await, vec!["A", "B page"]);
+        assert_eq!(run("kind:page links-to:[[b]]").await, vec!["A"]);
+        assert_eq!(run("links-to:[[B page]]").await, vec!["See [[B page]]"]);
+        assert!(run("links-to:[[Nowhere]]").await.is_empty());
+
+        index
+            .update_links(
+                &NoteId::new("c"),
+                &crate::link::extract_wiki_links("- back to [[A]]"),
+            )
+            .await
+            .unwrap();
+        assert!(run("kind:page is:orphan").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn relation_traversal_predicates_follow_relation_edges() {
+        let index = SqliteIndex::open_in_memory().await.unwrap();
+        for key in ["project", "owner"] {
+            let mut property = make_test_note(key, key, "- definition\n", &[]);
+            property.metadata.note_type = Some("Property".into());
+            property.metadata.custom.insert(
+                "value_type".into(),
+                serde_json::Value::String("node".into()),
+            );
+            index.reindex(&property).await.unwrap();
+        }
+        let page_id = |n: u8| crate::PageId::from_legacy_doc_id(&[n; 16]);
+        let (apollo, zeus, alice) = (page_id(1), page_id(2), page_id(3));
+        let pages = [
+            ("apollo", "Apollo", apollo, format!("status:: active\nowner:: {alice}\n\n- plan\n")),
+            ("zeus", "Zeus", zeus, "status:: paused\n\n- plan\n".to_string()),
+            ("alice", "Alice", alice, "- person\n".to_string()),
+            ("tasks", "Tasks", page_id(4), format!(
+                "- Ship it #Task <!-- bid:aaaaaaaa-aaaa-aaaa-aaaa-aaaaaaaaaaaa -->\n  project:: {apollo}\n\
+                 - Wait #Task <!-- bid:bbbbbbbb-bbbb-bbbb-bbbb-bbbbbbbbbbbb -->\n  project:: {zeus}\n\
+                 - Loose #Task\n"
+            )),
+            ("standup", "Standup", page_id(5), format!(
+                "- Sync #Meeting <!-- bid:cccccccc-cccc-cccc-cccc-cccccccccccc -->\n  project:: {apollo}\n"
+            )),
+        ];
+        for (id, title, page_id, body) in pages {
+            let mut note = make_test_note(id, title, &body, &[]);
+            note.content = crate::storage::markdown::set_page_id_frontmatter(&body, page_id);
+            index.reindex(&note).await.unwrap();
+            index
+                .rebuild_relation_edges_from_authoritative_note(&note, page_id)
+                .await
+                .unwrap();
+        }
+        let run = |dsl: &'static str| {
+            let index = &index;
+            async move {
+                let result = index
+                    .execute_query(&crate::query::parse_query(dsl), None, None)
+                    .await
+                    .unwrap();
+                let mut texts = item_texts(&result);
+                texts.sort();
+                texts
+            }
+        };
+        assert_eq!(run("tag:Task project.status:active").await, vec!["Ship it"]);
+        assert_eq!(
+            run("tag:Task -project.status:active").await,
+            vec!["Loose", "Wait"]
+        );
+        assert_eq!(
+            run("project.owner:[[Alice]]").await,
+            vec!["Ship it", "Sync"]
+        );
+        assert_eq!(run("project.status:active,paused").await.len(), 3);
+        assert_eq!(
+            run("kind:page has-backlink-from:tag:Meeting").await,
+            vec!["Apollo"]
+        );
+        assert_eq!(
+            run("kind:page has-backlink-from:tag:Task").await,
+            vec!["Apollo", "Zeus"]
+        );
+        assert_eq!(
+            run(r#"kind:page has-backlink-from:"tag:Task project.owner:[[Alice]]""#).await,
+            vec!["Apollo"]
+        );
+
+        // The relation index is cached across queries; a write refreshes it.
+        let body = "status:: active\n\n- plan\n";
+        let mut zeus_note = make_test_note("zeus", "Zeus", body, &[]);
+        zeus_note.content = crate::storage::markdown::set_page_id_frontmatter(body, zeus);
+        index.reindex(&zeus_note).await.unwrap();
+        assert_eq!(
+            run("tag:Task project.status:active").await,
+            vec!["Ship it", "Wait"]
+        );
+    }
+
+    #[tokio::test]
+    async fn computed_properties_are_filled_in_at_query_time() {
+        let index = SqliteIndex::open_in_memory().await.unwrap();
+        let properties = [
+            ("project", "node", None),
+            (
+                "progress",
+                "rollup",
+                Some(("rollup", "percent status:done")),
+            ),
+            (
+                "days_left",
+                "formula",
+                Some(("formula", "deadline - today")),
+            ),
+            ("effort", "formula", Some(("formula", "estimate * 2"))),
+        ];
+        for (key, value_type, expression) in properties {
+            let mut property = make_test_note(key, key, "- definition\n", &[]);
+            property.metadata.note_type = Some("Property".into());
+            let custom = &mut property.metadata.custom;
+            custom.insert("value_type".into(), value_type.into());
+            if let Some((field, expression)) = expression {
+                custom.insert(field.into(), expression.into());
+            }
+            if value_type == "rollup" {
+                custom.insert("rollup_via".into(), "project".into());
+                custom.insert("rollup_where".into(), "tag:Task".into());
+            }
+            index.reindex(&property).await.unwrap();
+        }
+        let today = chrono::Local::now().date_naive();
+        let in_days = |days: i64| (today + chrono::Duration::days(days)).format("%Y-%m-%d");
+        let page_id = |n: u8| crate::PageId::from_legacy_doc_id(&[n; 16]);
+        let (apollo, zeus) = (page_id(1), page_id(2));
+        let pages = [
+            ("apollo", "Apollo", apollo, format!("deadline:: {}\n\n- plan\n", in_days(30))),
+            ("zeus", "Zeus", zeus, format!("deadline:: {}\n\n- plan\n", in_days(5))),
+            ("ares", "Ares", page_id(3), "- plan\n".to_string()),
+            ("tasks", "Tasks", page_id(4), format!(
+                "- Ship it #Task <!-- bid:aaaaaaaa-aaaa-aaaa-aaaa-aaaaaaaaaaaa -->\n  project:: {apollo}\n  status:: done\n  estimate:: 3\n\
+                 - Test it #Task <!-- bid:bbbbbbbb-bbbb-bbbb-bbbb-bbbbbbbbbbbb -->\n  project:: {apollo}\n  status:: todo\n  estimate:: 1\n\
+                 - Wait #Task <!-- bid:cccccccc-cccc-cccc-cccc-cccccccccccc -->\n  project:: {zeus}\n  status:: todo\n"
+            )),
+            ("standup", "Standup", page_id(5), format!(
+                "- Sync #Meeting <!-- bid:dddddddd-dddd-dddd-dddd-dddddddddddd -->\n  project:: {zeus}\n  status:: done\n"
+            )),
+        ];
+        for (id, title, page_id, body) in pages {
+            let mut note = make_test_note(id, title, &body, &[]);
+            note.content = crate::storage::markdown::set_page_id_frontmatter(&body, page_id);
+            index.reindex(&note).await.unwrap();
+            index
+                .rebuild_relation_edges_from_authoritative_note(&note, page_id)
+                .await
+                .unwrap();
+        }
+        let run = |dsl: &'static str, sort: Option<&'static str>| {
+            let index = &index;
+            async move {
+                let result = index
+                    .execute_query(&crate::query::parse_query(dsl), None, sort)
+                    .await
+                    .unwrap();
+                result
+                    .groups
+                    .into_iter()
+                    .flat_map(|group| group.items)
+                    .map(|item| (item.text, item.properties))
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        // Rollups filter and show on page rows; only `#Task` rows count.
+        let rows = run("kind:page has:progress", Some("title")).await;
+        let progress: Vec<_> = rows
+            .iter()
+            .map(|(title, props)| (title.as_str(), props["progress"].as_str()))
+            .collect();
+        assert_eq!(progress, vec![("Apollo", "50"), ("Zeus", "0")]);
+        let done: Vec<_> = run("kind:page progress:>=50", None).await;
+        assert_eq!(done.len(), 1);
+        assert_eq!(done[0].0, "Apollo");
+        assert_eq!(done[0].1["days_left"], "30");
+
+        // Formulas sort numerically ("5" before "30").
+        let by_deadline: Vec<_> = run("kind:page has:days_left", Some("days_left asc"))
+            .await
+            .into_iter()
+            .map(|(title, _)| title)
+            .collect();
+        assert_eq!(by_deadline, vec!["Zeus", "Apollo"]);
+
+        // Block rows get formulas too; a missing input leaves no value.
+        let effort: Vec<_> = run("tag:Task", Some("text"))
+            .await
+            .into_iter()
+            .map(|(text, props)| (text, props.get("effort").cloned()))
+            .collect();
+        assert_eq!(
+            effort,
+            vec![
+                ("Ship it".to_string(), Some("6".to_string())),
+                ("Test it".to_string(), Some("2".to_string())),
+                ("Wait".to_string(), None),
+            ]
+        );
+        assert_eq!(run("tag:Task effort:>4", None).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn search_ranks_well_linked_pages_above_equal_text_matches() {
+        let index = SqliteIndex::open_in_memory().await.unwrap();
+        let notes = [
+            make_test_note("alpha", "Alpha", "- widget gadget", &[]),
+            make_test_note("bravo", "Bravo", "- widget gadget", &[]),
+            make_test_note("hub-1", "Hub one", "- see [[Bravo]]", &[]),
+            make_test_note("hub-2", "Hub two", "- see [[Bravo]]", &[]),
+        ];
+        for note in &notes {
+            index.reindex(note).await.unwrap();
+            index
+                .update_links(&note.id, &crate::link::extract_wiki_links(&note.body))
+                .await
+                .unwrap();
+        }
+        let hits = index.search("widget", 10, 0).await.unwrap();
+        let ids: Vec<_> = hits.iter().map(|h| h.note_id.as_str()).collect();
+        assert_eq!(ids, vec!["bravo", "alpha"]);
+        let second = index.search("widget", 1, 1).await.unwrap();
+        assert_eq!(second[0].note_id.as_str(), "alpha");
+        assert!(index
+            .search("widget", usize::MAX, usize::MAX)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn link_graph_pages_follow_note_writes_after_first_build() {
+        let index = SqliteIndex::open_in_memory().await.unwrap();
+        index
+            .reindex(&make_test_note("a", "A", "- plain", &[]))
+            .await
+            .unwrap();
+        assert!(index
+            .link_graph_snapshot()
+            .await
+            .unwrap()
+            .resolve("nick")
+            .is_none());
+
+        let mut b = make_test_note("b", "B", "- plain", &[]);
+        b.metadata.aliases = vec!["Nick".into()];
+        index.reindex(&b).await.unwrap();
+        let graph = index.link_graph_snapshot().await.unwrap();
+        let nick = graph.resolve("nick").expect("alias from the cached page");
+        assert_eq!(graph.page(nick).slug, "b");
+
+        index.remove_note(&NoteId::new("b")).await.unwrap();
+        assert!(index
+            .link_graph_snapshot()
+            .await
+            .unwrap()
+            .resolve("nick")
+            .is_none());
+    }
+
     /// A deleted note's blocks must never resurface from the cache. Also
     /// exercises `remove_note`'s explicit `blocks_cache` eviction.
     #[tokio::test]
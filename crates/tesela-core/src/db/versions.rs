@@ -0,0 +1,467 @@
+//! Note version history: reading compacted rows, and the retention pass
+//! that thins and compresses them.
+//!
+//! `record_version` appends every edit as a whole copy. The prune pass
+//! ([`SqliteIndex::prune_versions`]) then keeps everything from the last
+//! day, the newest version per hour for a week and per day for a year (see
+//! [`VersionRetention`]), and — with compression on — rewrites what it
+//! keeps as reverse line deltas: each row's `content` becomes a delta
+//! against the next newer kept version, and its `prev_content` a delta
+//! against its own content. The newest version of a note always stays
+//! whole, so reading from the head backwards (`list_versions`, or a chain
+//! walk in `get_version`) never needs more than the rows already fetched.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde::Serialize;
+use sqlx::Row;
+
+use super::sqlite::db_err;
+use super::SqliteIndex;
+use crate::config::HistoryConfig;
+use crate::error::{Result, TeselaError};
+use crate::note::{NoteId, NoteVersion};
+
+/// The tiers of one prune pass. `None` disables a tier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionRetention {
+    /// Versions younger than this are all kept.
+    pub keep_all: Option<Duration>,
+    /// Older versions younger than this keep the newest per clock hour.
+    pub keep_hourly: Option<Duration>,
+    /// Older versions younger than this keep the newest per UTC day.
+    pub keep_daily: Option<Duration>,
+    /// Store kept versions as deltas rather than whole copies.
+    pub compress: bool,
+}
+
+impl Default for VersionRetention {
+    fn default() -> Self {
+        Self::from_config(&HistoryConfig::default())
+    }
+}
+
+impl VersionRetention {
+    /// The policy described by a mosaic's `[history]` config block.
+    pub fn from_config(cfg: &HistoryConfig) -> Self {
+        let tier = |d: Duration| (d > Duration::zero()).then_some(d);
+        VersionRetention {
+            keep_all: tier(Duration::hours(cfg.keep_all_hours as i64)),
+            keep_hourly: tier(Duration::days(cfg.keep_hourly_days as i64)),
+            keep_daily: tier(Duration::days(cfg.keep_daily_days as i64)),
+            compress: cfg.compress,
+        }
+    }
+}
+
+/// What one prune pass reclaimed (or, on a dry run, would have).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct VersionPruneReport {
+    /// True when nothing was actually written.
+    pub dry_run: bool,
+    /// Notes with at least one stored version.
+    pub notes: u64,
+    /// Version rows deleted by the retention tiers.
+    pub versions_deleted: u64,
+    /// Version rows left after the pass.
+    pub versions_remaining: u64,
+    /// Remaining rows stored as deltas.
+    pub versions_compressed: u64,
+    /// Bytes of `content` + `prev_content` before the pass.
+    pub bytes_before: u64,
+    /// Bytes of `content` + `prev_content` after the pass.
+    pub bytes_after: u64,
+}
+
+impl VersionPruneReport {
+    /// Version text bytes freed. SQLite reuses the freed pages for new
+    /// rows; the file itself only shrinks on `VACUUM`.
+    pub fn reclaimed_bytes(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+}
+
+/// One `note_versions` row as stored.
+pub(crate) struct StoredVersion {
+    pub id: i64,
+    pub note_id: String,
+    pub version_number: i64,
+    pub content: String,
+    pub prev_content: Option<String>,
+    pub content_delta: bool,
+    pub prev_delta: bool,
+    pub created_at: String,
+}
+
+impl StoredVersion {
+    pub(crate) const COLUMNS: &'static str =
+        "id, note_id, version_number, content, prev_content, content_delta, prev_delta, created_at";
+
+    pub(crate) fn from_row(row: &sqlx::sqlite::SqliteRow) -> Self {
+        StoredVersion {
+            id: row.get("id"),
+            note_id: row.get("note_id"),
+            version_number: row.get("version_number"),
+            content: row.get("content"),
+            prev_content: row.try_get("prev_content").ok().flatten(),
+            content_delta: row.get::<i64, _>("content_delta") != 0,
+            prev_delta: row.get::<i64, _>("prev_delta") != 0,
+            created_at: row.get("created_at"),
+        }
+    }
+
+    fn stored_bytes(&self) -> u64 {
+        (self.content.len() + self.prev_content.as_ref().map_or(0, String::len)) as u64
+    }
+}
+
+/// Decode rows of one note ordered newest first. The first row must be
+/// whole (the head, or any row the caller knows isn't a delta).
+pub(crate) fn materialize(rows: Vec<StoredVersion>) -> Result<Vec<NoteVersion>> {
+    let mut out: Vec<NoteVersion> = Vec::with_capacity(rows.len());
+    for row in rows {
+        let content = if row.content_delta {
+            let newer = out.last().ok_or_else(|| broken_chain(row.id))?;
+            apply_delta(&newer.content, &row.content).ok_or_else(|| broken_chain(row.id))?
+        } else {
+            row.content
+        };
+        let prev_content = match row.prev_content {
+            Some(delta) if row.prev_delta => {
+                Some(apply_delta(&content, &delta).ok_or_else(|| broken_chain(row.id))?)
+            }
+            prev => prev,
+        };
+        out.push(NoteVersion {
+            id: row.id,
+            note_id: NoteId::from(row.note_id),
+            version_number: row.version_number,
+            content,
+            prev_content,
+            created_at: row.created_at,
+        });
+    }
+    Ok(out)
+}
+
+fn broken_chain(id: i64) -> TeselaError {
+    TeselaError::Database {
+        message: format!("note version {id}: delta chain is broken"),
+        source: None,
+    }
+}
+
+/// `target` as a delta against `base`: the count of leading and trailing
+/// lines they share, then the lines in between. `None` when the delta
+/// wouldn't be smaller than `target` itself.
+pub(crate) fn encode_delta(base: &str, target: &str) -> Option<String> {
+    let old: Vec<&str> = base.split_inclusive('\n').collect();
+    let new: Vec<&str> = target.split_inclusive('\n').collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let middle = new[prefix..new.len() - suffix].concat();
+    let delta = format!("{prefix} {suffix}\n{middle}");
+    (delta.len() < target.len()).then_some(delta)
+}
+
+/// Rebuild a version from its newer neighbour and [`encode_delta`]'s
+/// output. `None` when the delta doesn't fit `base`.
+pub(crate) fn apply_delta(base: &str, delta: &str) -> Option<String> {
+    let (header, middle) = delta.split_once('\n')?;
+    let (prefix, suffix) = header.split_once(' ')?;
+    let (prefix, suffix): (usize, usize) = (prefix.parse().ok()?, suffix.parse().ok()?);
+    let old: Vec<&str> = base.split_inclusive('\n').collect();
+    if prefix + suffix > old.len() {
+        return None;
+    }
+    Some(
+        [
+            &old[..prefix].concat(),
+            middle,
+            &old[old.len() - suffix..].concat(),
+        ]
+        .concat(),
+    )
+}
+
+/// Ids (of one note's versions, newest first) the tiers keep at `now`.
+fn kept_versions(
+    versions: &[NoteVersion],
+    policy: &VersionRetention,
+    now: DateTime<Utc>,
+) -> HashSet<i64> {
+    let within = |tier: Option<Duration>, age: Duration| tier.is_some_and(|t| age < t);
+    let mut hours = HashSet::new();
+    let mut days = HashSet::new();
+    let mut kept = HashSet::new();
+    for (i, v) in versions.iter().enumerate() {
+        let keep = match parse_created_at(&v.created_at) {
+            // The head, and anything whose age can't be told.
+            _ if i == 0 => true,
+            None => true,
+            Some(at) => {
+                let age = now - at;
+                if within(policy.keep_all, age) {
+                    true
+                } else if within(policy.keep_hourly, age) {
+                    hours.insert(at.timestamp().div_euclid(3600))
+                } else if within(policy.keep_daily, age) {
+                    days.insert(at.date_naive())
+                } else {
+                    false
+                }
+            }
+        };
+        if keep {
+            kept.insert(v.id);
+        }
+    }
+    kept
+}
+
+/// `created_at` is SQLite's `datetime('now')` (UTC, `YYYY-MM-DD HH:MM:SS`).
+fn parse_created_at(created_at: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(created_at, "%Y-%m-%d %H:%M:%S")
+        .map(|t| t.and_utc())
+        .ok()
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(created_at)
+                .ok()
+                .map(|t| t.with_timezone(&Utc))
+        })
+}
+
+impl SqliteIndex {
+    /// Thin and (optionally) compress every note's version history per
+    /// `policy`. `now` is injected so callers and tests agree on it. With
+    /// `dry_run` the pass runs in a transaction that is rolled back, so the
+    /// report shows exactly what a real run would reclaim.
+    pub async fn prune_versions(
+        &self,
+        policy: &VersionRetention,
+        now: DateTime<Utc>,
+        dry_run: bool,
+    ) -> Result<VersionPruneReport> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| db_err("Failed to begin tx for prune_versions", e))?;
+        let mut report = VersionPruneReport {
+            dry_run,
+            ..VersionPruneReport::default()
+        };
+
+        let note_ids: Vec<String> =
+            sqlx::query_scalar("SELECT DISTINCT note_id FROM note_versions ORDER BY note_id")
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(|e| db_err("Failed to list versioned notes", e))?;
+        for note_id in note_ids {
+            report.notes += 1;
+            let rows: Vec<StoredVersion> = sqlx::query(&format!(
+                "SELECT {} FROM note_versions WHERE note_id = ? ORDER BY version_number DESC",
+                StoredVersion::COLUMNS
+            ))
+            .bind(&note_id)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| db_err("Failed to load note versions", e))?
+            .iter()
+            .map(StoredVersion::from_row)
+            .collect();
+            report.bytes_before += rows.iter().map(StoredVersion::stored_bytes).sum::<u64>();
+            let stored: Vec<(bool, bool)> = rows
+                .iter()
+                .map(|r| (r.content_delta, r.prev_delta))
+                .collect();
+            let versions = materialize(rows)?;
+            let kept = kept_versions(&versions, policy, now);
+
+            let mut newer: Option<&str> = None;
+            for (version, (was_delta, prev_was_delta)) in versions.iter().zip(stored) {
+                if !kept.contains(&version.id) {
+                    sqlx::query("DELETE FROM note_versions WHERE id = ?")
+                        .bind(version.id)
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(|e| db_err("Failed to delete note version", e))?;
+                    report.versions_deleted += 1;
+                    continue;
+                }
+                let content_delta = newer
+                    .filter(|_| policy.compress)
+                    .and_then(|newer| encode_delta(newer, &version.content));
+                let prev_delta = version
+                    .prev_content
+                    .as_deref()
+                    .filter(|_| policy.compress)
+                    .and_then(|prev| encode_delta(&version.content, prev));
+                let row = StoredVersion {
+                    id: version.id,
+                    note_id: note_id.clone(),
+                    version_number: version.version_number,
+                    content_delta: content_delta.is_some(),
+                    prev_delta: prev_delta.is_some(),
+                    content: content_delta.unwrap_or_else(|| version.content.clone()),
+                    prev_content: prev_delta.or_else(|| version.prev_content.clone()),
+                    created_at: version.created_at.clone(),
+                };
+                report.versions_remaining += 1;
+                report.versions_compressed += u64::from(row.content_delta);
+                report.bytes_after += row.stored_bytes();
+                newer = Some(&version.content);
+                // Rewrite whenever the row was or becomes a delta: a kept
+                // row's newer neighbour may just have been pruned.
+                if was_delta || prev_was_delta || row.content_delta || row.prev_delta {
+                    sqlx::query(
+                        "UPDATE note_versions \
+                         SET content = ?, prev_content = ?, content_delta = ?, prev_delta = ? \
+                         WHERE id = ?",
+                    )
+                    .bind(&row.content)
+                    .bind(&row.prev_content)
+                    .bind(row.content_delta as i64)
+                    .bind(row.prev_delta as i64)
+                    .bind(row.id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| db_err("Failed to rewrite note version", e))?;
+                }
+            }
+        }
+
+        if dry_run {
+            tx.rollback()
+                .await
+                .map_err(|e| db_err("Failed to roll back prune_versions", e))?;
+        } else {
+            tx.commit()
+                .await
+                .map_err(|e| db_err("Failed to commit prune_versions", e))?;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::search_index::SearchIndex;
+
+    #[test]
+    fn deltas_round_trip() {
+        let base = "---\ntitle: Trip\n---\n- plan\n- pack\n- socks\n";
+        for target in [
+            "---\ntitle: Trip\n---\n- plan trip\n- pack\n- socks\n",
+            "---\ntitle: Trip\n---\n- plan\n- pack\n- socks\n- new\n",
+            "- plan\n",
+            "",
+            base,
+        ] {
+            if let Some(delta) = encode_delta(base, target) {
+                assert!(delta.len() < target.len());
+                assert_eq!(apply_delta(base, &delta).as_deref(), Some(target));
+            }
+        }
+        assert!(encode_delta(base, "a\n").is_none(), "not worth a delta");
+        assert_eq!(apply_delta("a\n", "2 0\n"), None, "doesn't fit the base");
+    }
+
+    async fn seed(index: &SqliteIndex, ages_minutes: &[i64], now: DateTime<Utc>) -> NoteId {
+        let note = NoteId::new("trip");
+        sqlx::query(
+            "INSERT INTO notes (id, title, body, content, path, checksum, created_at, modified_at) \
+             VALUES ('trip', 'Trip', '', '', 'notes/trip.md', '', '', '')",
+        )
+        .execute(index.pool())
+        .await
+        .unwrap();
+        let mut prev = None;
+        for (n, age) in ages_minutes.iter().enumerate() {
+            let content = format!("---\ntitle: Trip\n---\n- plan\n- pack\n- item {n}\n- socks\n");
+            let id = index
+                .record_version(&note, prev.as_deref(), &content, 0)
+                .await
+                .unwrap();
+            let at = now - Duration::minutes(*age);
+            sqlx::query(
+                "UPDATE note_versions SET created_at = ? WHERE note_id = ? AND version_number = ?",
+            )
+            .bind(at.format("%Y-%m-%d %H:%M:%S").to_string())
+            .bind(note.as_str())
+            .bind(id)
+            .execute(index.pool())
+            .await
+            .unwrap();
+            prev = Some(content);
+        }
+        note
+    }
+
+    #[tokio::test]
+    async fn prune_thins_by_tier_and_keeps_history_readable() {
+        let index = SqliteIndex::open_in_memory().await.unwrap();
+        let now = "2026-10-18T12:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        // Oldest first: one two years ago, two in one day a month ago, two
+        // in one hour three days ago, and three from the last day.
+        let h = 60;
+        let note = seed(
+            &index,
+            &[
+                17_520 * h,
+                721 * h,
+                720 * h,
+                72 * h + 10,
+                72 * h,
+                5 * h,
+                2 * h,
+                h,
+            ],
+            now,
+        )
+        .await;
+        let before = index.list_versions(&note, 100).await.unwrap();
+
+        let policy = VersionRetention::default();
+        let dry = index.prune_versions(&policy, now, true).await.unwrap();
+        assert_eq!(index.list_versions(&note, 100).await.unwrap(), before);
+
+        let report = index.prune_versions(&policy, now, false).await.unwrap();
+        assert_eq!(
+            report,
+            VersionPruneReport {
+                dry_run: false,
+                ..dry
+            }
+        );
+        assert_eq!(report.versions_deleted, 3);
+        assert_eq!(report.versions_remaining, 5);
+        assert_eq!(report.versions_compressed, 4, "all but the head");
+        assert!(report.reclaimed_bytes() > 0);
+
+        let after = index.list_versions(&note, 100).await.unwrap();
+        let numbers: Vec<i64> = after.iter().map(|v| v.version_number).collect();
+        assert_eq!(numbers, vec![8, 7, 6, 5, 3]);
+        for v in &after {
+            let whole = before.iter().find(|b| b.id == v.id).unwrap();
+            assert_eq!(v, whole, "deltas decode to the original text");
+            assert_eq!(index.get_version(v.id).await.unwrap().as_ref(), Some(whole));
+        }
+
+        // Turning compression off writes the kept rows back out whole.
+        let plain = VersionRetention {
+            compress: false,
+            ..policy
+        };
+        let report = index.prune_versions(&plain, now, false).await.unwrap();
+        assert_eq!(report.versions_compressed, 0);
+        assert_eq!(index.list_versions(&note, 100).await.unwrap(), after);
+    }
+}
@@ -0,0 +1,734 @@
+//! Mosaic-level structured export — JSON Lines.
+//!
+//! One JSON object per line, for tooling that wants Tesela's page/block
+//! model without re-implementing the markdown + `key:: value` parser:
+//!
+//! - `{"kind":"page", "id", "title", "page_id", "tags", "aliases",
+//!   "frontmatter", "properties", "links", "relations"}` per note;
+//! - `{"kind":"block", "page", "bid", "parent", "order", "text", "tags",
+//!   "properties", "links", "relations"}` per block, after its page.
+//!
+//! `properties` is an ordered list of `{"key", "value", "type"}`. Values
+//! are typed through [`crate::property::parse_scalar`] with the mosaic's
+//! property definitions (`number` → JSON number, `checkbox` → bool), but
+//! only when the typed value renders back to the exact source text; anything
+//! else stays a string. `relations` are the `node`-typed properties that
+//! hold a page id, with the target's slug when it is in the export.
+//!
+//! [`import_mosaic_json`] is the inverse. The authoritative fields are a
+//! page's `frontmatter` and `properties` and a block's `bid`, `parent`,
+//! `order`, `text` and `properties`; `tags`, `links` and `relations` are
+//! derived and ignored on import. Blocks are re-assembled by parent and
+//! order, so a tool may move, add (omit `bid` to mint one) or drop
+//! records. A block whose source layout the structured fields can't
+//! reproduce (property lines between prose, non-canonical `key::value`
+//! spacing) carries its verbatim text as `raw`, used on import for as long
+//! as `text` and `properties` are unchanged. Export → import is lossless
+//! against the block model: every note comes back as
+//! `serialize_note(parse_note(file))`, the form the engine materializes.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use walkdir::WalkDir;
+
+use crate::block::parse_blocks;
+use crate::error::{Result, TeselaError};
+use crate::export::markdown::parse_property_line;
+use crate::link::extract_wiki_links;
+use crate::note::PageId;
+use crate::note_tree::{parse_note, serialize_note, FlatBlock, MarkdownFenceTracker, NoteTree};
+use crate::property::{format_scalar, parse_scalar, PropScalar, ValueType};
+use crate::storage::markdown::{generate_frontmatter, page_id_from_frontmatter, parse_frontmatter};
+
+/// One line of the export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JsonRecord {
+    Page(PageRecord),
+    Block(BlockRecord),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageRecord {
+    /// Note slug (file stem under `notes/`).
+    pub id: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_id: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Verbatim YAML frontmatter, `---` markers included. When absent on
+    /// import, one is generated from `title` and `tags`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frontmatter: Option<String>,
+    /// Page-level `key:: value` properties.
+    #[serde(default)]
+    pub properties: Vec<PropertyRecord>,
+    #[serde(default)]
+    pub links: Vec<String>,
+    #[serde(default)]
+    pub relations: Vec<RelationRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockRecord {
+    /// Slug of the containing page.
+    pub page: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bid: Option<Uuid>,
+    #[serde(default)]
+    pub parent: Option<Uuid>,
+    /// Position among the parent's children.
+    #[serde(default)]
+    pub order: u32,
+    /// The block's prose, without property lines.
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub properties: Vec<PropertyRecord>,
+    #[serde(default)]
+    pub links: Vec<String>,
+    #[serde(default)]
+    pub relations: Vec<RelationRecord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PropertyRecord {
+    pub key: String,
+    pub value: serde_json::Value,
+    /// The property's declared value type, when it has a definition.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub value_type: Option<ValueType>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelationRecord {
+    pub key: String,
+    /// Target page id.
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_slug: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct JsonExportOutcome {
+    pub page_count: usize,
+    pub block_count: usize,
+}
+
+/// A note rebuilt from JSON records, ready to be written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedNote {
+    pub id: String,
+    pub content: String,
+    pub block_count: usize,
+}
+
+/// Write every note under `mosaic_root/notes` to `out` as JSON Lines.
+/// `types` maps lowercased property names to their declared value type
+/// (`SqliteIndex::get_all_property_defs`); undeclared properties export as
+/// strings.
+pub fn export_mosaic_json(
+    mosaic_root: &Path,
+    out: &mut dyn Write,
+    types: &HashMap<String, ValueType>,
+) -> Result<JsonExportOutcome> {
+    let notes_src = mosaic_root.join("notes");
+    if !notes_src.exists() {
+        return Err(TeselaError::NoteNotFound {
+            identifier: format!("mosaic {}", mosaic_root.display()),
+        });
+    }
+    let mut notes = Vec::new();
+    for entry in WalkDir::new(&notes_src).sort_by_file_name() {
+        let entry = entry.map_err(|e| TeselaError::file_op(e.to_string()))?;
+        let path = entry.path();
+        if !entry.file_type().is_file() || path.extension().is_none_or(|e| e != "md") {
+            continue;
+        }
+        let rel = path
+            .strip_prefix(&notes_src)
+            .expect("walk under notes_src")
+            .with_extension("");
+        let slug = rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        notes.push((slug, fs::read_to_string(path)?));
+    }
+
+    let slugs: HashMap<PageId, String> = notes
+        .iter()
+        .filter_map(|(slug, content)| Some((page_id_from_frontmatter(content)?, slug.clone())))
+        .collect();
+    let exporter = Exporter { types, slugs };
+    let mut outcome = JsonExportOutcome::default();
+    for (slug, content) in &notes {
+        for record in exporter.records(slug, content) {
+            if matches!(record, JsonRecord::Block(_)) {
+                outcome.block_count += 1;
+            }
+            serde_json::to_writer(&mut *out, &record)?;
+            out.write_all(b"\n")?;
+        }
+        outcome.page_count += 1;
+    }
+    Ok(outcome)
+}
+
+struct Exporter<'a> {
+    types: &'a HashMap<String, ValueType>,
+    slugs: HashMap<PageId, String>,
+}
+
+impl Exporter<'_> {
+    fn records(&self, slug: &str, content: &str) -> Vec<JsonRecord> {
+        let tree = parse_note(content);
+        let (metadata, _) = parse_frontmatter(content).unwrap_or_default();
+        let page_props: Vec<(&str, &str)> = tree
+            .page_properties
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        let mut records = vec![JsonRecord::Page(PageRecord {
+            id: slug.to_string(),
+            title: metadata.title.clone().unwrap_or_else(|| slug.to_string()),
+            page_id: page_id_from_frontmatter(content).map(|id| id.to_string()),
+            tags: metadata.tags.clone(),
+            aliases: metadata.aliases.clone(),
+            frontmatter: tree.frontmatter.clone(),
+            properties: self.properties(&page_props),
+            links: link_targets(
+                &tree
+                    .blocks
+                    .iter()
+                    .map(|b| b.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+            relations: self.relations(&page_props),
+        })];
+
+        let block_tags: HashMap<String, Vec<String>> = parse_blocks(slug, content)
+            .into_iter()
+            .filter_map(|b| Some((b.bid?, b.tags)))
+            .collect();
+        let mut next_order: HashMap<Option<Uuid>, u32> = HashMap::new();
+        for block in &tree.blocks {
+            let order = next_order.entry(block.parent).or_default();
+            let (text, props) = split_block_text(block);
+            let raw = (render_block_text(&text, &props) != block.text).then(|| block.text.clone());
+            records.push(JsonRecord::Block(BlockRecord {
+                page: slug.to_string(),
+                bid: Some(block.id),
+                parent: block.parent,
+                order: *order,
+                tags: block_tags
+                    .get(&block.id.to_string())
+                    .cloned()
+                    .unwrap_or_default(),
+                links: link_targets(&text),
+                properties: self.properties(&props),
+                relations: self.relations(&props),
+                text,
+                raw,
+            }));
+            *order += 1;
+        }
+        records
+    }
+
+    fn value_type(&self, key: &str) -> Option<ValueType> {
+        self.types.get(&key.to_ascii_lowercase()).copied()
+    }
+
+    fn properties(&self, props: &[(&str, &str)]) -> Vec<PropertyRecord> {
+        props
+            .iter()
+            .map(|(key, raw)| {
+                let value_type = self.value_type(key);
+                PropertyRecord {
+                    key: key.to_string(),
+                    value: typed_value(value_type, raw),
+                    value_type,
+                }
+            })
+            .collect()
+    }
+
+    fn relations(&self, props: &[(&str, &str)]) -> Vec<RelationRecord> {
+        props
+            .iter()
+            .filter(|(key, _)| self.value_type(key) == Some(ValueType::Node))
+            .filter_map(|(key, value)| {
+                let target = PageId::parse(value.trim())?;
+                Some(RelationRecord {
+                    key: key.to_string(),
+                    target: target.to_string(),
+                    target_slug: self.slugs.get(&target).cloned(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// The scalar for `raw`, or the string itself when typing it would not
+/// render back to the same text.
+fn typed_value(value_type: Option<ValueType>, raw: &str) -> serde_json::Value {
+    let text = || serde_json::Value::String(raw.to_string());
+    let Some(value_type) = value_type else {
+        return text();
+    };
+    let scalar = parse_scalar(value_type, raw);
+    if format_scalar(&scalar) != raw {
+        return text();
+    }
+    match scalar {
+        PropScalar::Text(s) => serde_json::Value::String(s),
+        PropScalar::Int(i) => i.into(),
+        PropScalar::Float(f) => serde_json::Number::from_f64(f).map_or_else(text, Into::into),
+        PropScalar::Bool(b) => b.into(),
+    }
+}
+
+fn link_targets(text: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    extract_wiki_links(text)
+        .into_iter()
+        .map(|link| link.target)
+        .filter(|target| seen.insert(target.clone()))
+        .collect()
+}
+
+/// Split a block's text into prose and its `key:: value` lines. The
+/// first line is always prose; fenced lines never read as properties.
+fn split_block_text(block: &FlatBlock) -> (String, Vec<(&str, &str)>) {
+    let mut lines = block.text.split('\n');
+    let mut prose = vec![lines.next().unwrap_or_default()];
+    let mut props = Vec::new();
+    let mut fence = MarkdownFenceTracker::default();
+    fence.line_is_fenced(prose[0]);
+    for line in lines {
+        if fence.line_is_fenced(line) {
+            prose.push(line);
+            continue;
+        }
+        match parse_property_line(line) {
+            Some(prop) => props.push(prop),
+            None => prose.push(line),
+        }
+    }
+    props.extend(
+        block
+            .properties
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str())),
+    );
+    (prose.join("\n"), props)
+}
+
+fn render_block_text(text: &str, props: &[(&str, &str)]) -> String {
+    let mut out = text.to_string();
+    for (key, value) in props {
+        out.push_str(&format!("\n{key}:: {value}"));
+    }
+    out
+}
+
+/// Rebuild notes from JSON Lines produced by [`export_mosaic_json`] (or
+/// a tool transforming it). Blank lines are skipped. Returns the notes in
+/// page-record order; nothing is written.
+pub fn import_mosaic_json(input: impl BufRead) -> Result<Vec<ImportedNote>> {
+    let mut pages: Vec<PageRecord> = Vec::new();
+    let mut blocks: BTreeMap<String, Vec<BlockRecord>> = BTreeMap::new();
+    for (n, line) in input.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: JsonRecord = serde_json::from_str(&line)
+            .map_err(|e| TeselaError::validation(format!("line {}: {e}", n + 1)))?;
+        match record {
+            JsonRecord::Page(page) => {
+                validate_slug(&page.id)?;
+                if pages.iter().any(|p| p.id == page.id) {
+                    return Err(TeselaError::validation(format!(
+                        "line {}: page `{}` appears twice",
+                        n + 1,
+                        page.id
+                    )));
+                }
+                pages.push(page);
+            }
+            JsonRecord::Block(block) => blocks.entry(block.page.clone()).or_default().push(block),
+        }
+    }
+    if let Some(orphan) = blocks
+        .keys()
+        .find(|slug| !pages.iter().any(|p| &p.id == *slug))
+    {
+        return Err(TeselaError::validation(format!(
+            "blocks reference page `{orphan}`, which has no page record"
+        )));
+    }
+
+    let mut seen_bids = HashSet::new();
+    pages
+        .into_iter()
+        .map(|page| {
+            let page_blocks = blocks.remove(&page.id).unwrap_or_default();
+            build_note(page, page_blocks, &mut seen_bids)
+        })
+        .collect()
+}
+
+fn validate_slug(slug: &str) -> Result<()> {
+    let ok = !slug.is_empty()
+        && slug
+            .split('/')
+            .all(|part| !part.is_empty() && part != "." && part != "..")
+        && !slug.contains('\\');
+    if ok {
+        Ok(())
+    } else {
+        Err(TeselaError::validation(format!("invalid page id `{slug}`")))
+    }
+}
+
+fn build_note(
+    page: PageRecord,
+    records: Vec<BlockRecord>,
+    seen_bids: &mut HashSet<Uuid>,
+) -> Result<ImportedNote> {
+    let frontmatter = page.frontmatter.clone().unwrap_or_else(|| {
+        let tags: Vec<&str> = page.tags.iter().map(String::as_str).collect();
+        let title = if page.title.is_empty() {
+            &page.id
+        } else {
+            &page.title
+        };
+        generate_frontmatter(title, &tags, Utc::now(), &HashMap::new())
+    });
+    let mut tree = NoteTree {
+        frontmatter: Some(frontmatter),
+        page_properties: page
+            .properties
+            .iter()
+            .map(|p| Ok((p.key.clone(), value_text(&page.id, p)?)))
+            .collect::<Result<_>>()?,
+        blocks: Vec::new(),
+        stamped_any: false,
+    };
+
+    // Mint ids first so children can be attached by parent id.
+    let mut ids = Vec::with_capacity(records.len());
+    for record in &records {
+        let id = record.bid.unwrap_or_else(Uuid::now_v7);
+        if !seen_bids.insert(id) {
+            return Err(TeselaError::validation(format!(
+                "page `{}`: block {id} appears twice",
+                page.id
+            )));
+        }
+        ids.push(id);
+    }
+    let mut children: HashMap<Option<Uuid>, Vec<usize>> = HashMap::new();
+    for (i, record) in records.iter().enumerate() {
+        if let Some(parent) = record.parent {
+            if !ids.contains(&parent) {
+                return Err(TeselaError::validation(format!(
+                    "page `{}`: block {} has parent {parent}, which is not on the page",
+                    page.id, ids[i]
+                )));
+            }
+        }
+        children.entry(record.parent).or_default().push(i);
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(|&i| records[i].order);
+    }
+
+    // Depth-first from the roots; anything unreached sits on a cycle.
+    let mut stack: Vec<(usize, u16)> = children
+        .get(&None)
+        .map(|roots| roots.iter().rev().map(|&i| (i, 0)).collect())
+        .unwrap_or_default();
+    while let Some((i, indent)) = stack.pop() {
+        let record = &records[i];
+        let props: Vec<(String, String)> = record
+            .properties
+            .iter()
+            .map(|p| Ok((p.key.clone(), value_text(&page.id, p)?)))
+            .collect::<Result<_>>()?;
+        let as_exported: Vec<(&str, &str)> = props
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        let text = match &record.raw {
+            Some(raw) if unchanged(raw, &record.text, &as_exported) => raw.clone(),
+            _ => render_block_text(&record.text, &as_exported),
+        };
+        tree.blocks.push(FlatBlock {
+            id: ids[i],
+            parent: record.parent,
+            indent,
+            text,
+            properties: Vec::new(),
+        });
+        if let Some(kids) = children.get(&Some(ids[i])) {
+            stack.extend(kids.iter().rev().map(|&k| (k, indent + 1)));
+        }
+    }
+    if tree.blocks.len() != records.len() {
+        return Err(TeselaError::validation(format!(
+            "page `{}`: block parents form a cycle",
+            page.id
+        )));
+    }
+
+    Ok(ImportedNote {
+        block_count: tree.blocks.len(),
+        content: serialize_note(&tree),
+        id: page.id,
+    })
+}
+
+/// Whether `raw` still splits into exactly `text` and `props`.
+fn unchanged(raw: &str, text: &str, props: &[(&str, &str)]) -> bool {
+    let probe = FlatBlock {
+        id: Uuid::nil(),
+        parent: None,
+        indent: 0,
+        text: raw.to_string(),
+        properties: Vec::new(),
+    };
+    let (raw_text, raw_props) = split_block_text(&probe);
+    raw_text == text && raw_props == props
+}
+
+/// A property value back as its `key:: value` text.
+fn value_text(page: &str, prop: &PropertyRecord) -> Result<String> {
+    match &prop.value {
+        serde_json::Value::String(s) => Ok(s.clone()),
+        serde_json::Value::Bool(b) => Ok(format_scalar(&PropScalar::Bool(*b))),
+        serde_json::Value::Number(n) => Ok(match n.as_i64() {
+            Some(i) => format_scalar(&PropScalar::Int(i)),
+            None => format_scalar(&PropScalar::Float(n.as_f64().unwrap_or_default())),
+        }),
+        other => Err(TeselaError::validation(format!(
+            "page `{page}`: property `{}` has unsupported value {other}",
+            prop.key
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const A: &str = "0193a000-0000-7000-8000-00000000000a";
+    const B: &str = "0193a000-0000-7000-8000-00000000000b";
+    const C: &str = "0193a000-0000-7000-8000-00000000000c";
+    const D: &str = "0193a000-0000-7000-8000-00000000000d";
+    const OWNER: &str = "0193a000-0000-7000-8000-0000000000ff";
+
+    fn mosaic(notes: &[(&str, String)]) -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join("notes")).unwrap();
+        for (slug, content) in notes {
+            fs::write(tmp.path().join("notes").join(format!("{slug}.md")), content).unwrap();
+        }
+        tmp
+    }
+
+    fn types() -> HashMap<String, ValueType> {
+        [
+            ("estimate".to_string(), ValueType::Number),
+            ("done".to_string(), ValueType::Checkbox),
+            ("owner".to_string(), ValueType::Node),
+            ("status".to_string(), ValueType::Select),
+        ]
+        .into()
+    }
+
+    fn trip() -> String {
+        format!(
+            "---\ntitle: Trip\ntags: [travel]\n---\n\
+             type:: project\n\
+             - Pack for [[Lisbon]] #todo <!-- bid:{A} -->\n  remember socks\n  estimate:: 3\n  done:: yes\n\
+             \x20 - passport <!-- bid:{B} -->\n    owner:: {OWNER}\n\
+             - Book <!-- bid:{C} -->\n  status:: todo\n  more prose after props\n  estimate:: 2.50\n"
+        )
+    }
+
+    fn owner() -> String {
+        format!("---\ntitle: Ana\ntesela_page_id: {OWNER}\n---\n- hi <!-- bid:{D} -->\n")
+    }
+
+    fn export(tmp: &TempDir) -> String {
+        let mut out = Vec::new();
+        export_mosaic_json(tmp.path(), &mut out, &types()).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn exports_pages_and_blocks_with_typed_properties() {
+        let tmp = mosaic(&[("trip", trip()), ("ana", owner())]);
+        let jsonl = export(&tmp);
+        let records: Vec<JsonRecord> = jsonl
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let JsonRecord::Page(page) = &records[2] else {
+            panic!("{jsonl}")
+        };
+        assert_eq!(page.id, "trip");
+        assert_eq!(page.tags, vec!["travel"]);
+        assert_eq!(page.properties[0].value, serde_json::json!("project"));
+        assert_eq!(page.links, vec!["Lisbon"]);
+        let JsonRecord::Block(pack) = &records[3] else {
+            panic!()
+        };
+        assert_eq!(pack.text, "Pack for [[Lisbon]] #todo\nremember socks");
+        assert_eq!(pack.tags, vec!["todo"]);
+        assert_eq!(pack.properties[0].value, serde_json::json!(3));
+        assert_eq!(pack.properties[0].value_type, Some(ValueType::Number));
+        // `yes` isn't a checkbox value; it stays text rather than `false`.
+        assert_eq!(pack.properties[1].value, serde_json::json!("yes"));
+        assert!(pack.raw.is_none());
+        let JsonRecord::Block(passport) = &records[4] else {
+            panic!()
+        };
+        assert_eq!(passport.parent, Some(Uuid::parse_str(A).unwrap()));
+        assert_eq!(passport.order, 0);
+        assert_eq!(
+            passport.relations,
+            vec![RelationRecord {
+                key: "owner".into(),
+                target: OWNER.into(),
+                target_slug: Some("ana".into()),
+            }]
+        );
+        let JsonRecord::Block(book) = &records[5] else {
+            panic!()
+        };
+        assert_eq!(book.order, 1);
+        assert_eq!(book.properties[1].value, serde_json::json!("2.50"));
+        assert!(book.raw.is_some(), "prose after a property needs raw");
+    }
+
+    #[test]
+    fn round_trips_to_the_canonical_block_model() {
+        let tmp = mosaic(&[("trip", trip()), ("ana", owner())]);
+        let notes = import_mosaic_json(export(&tmp).as_bytes()).unwrap();
+        assert_eq!(notes.len(), 2);
+        for note in notes {
+            let original =
+                fs::read_to_string(tmp.path().join(format!("notes/{}.md", note.id))).unwrap();
+            assert_eq!(note.content, serialize_note(&parse_note(&original)));
+        }
+    }
+
+    #[test]
+    fn import_applies_transformations() {
+        let tmp = mosaic(&[("trip", trip())]);
+        let mut records: Vec<JsonRecord> = export(&tmp)
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        for record in &mut records {
+            if let JsonRecord::Block(block) = record {
+                // Promote the passport to the top level, after "Book".
+                if block.bid == Some(Uuid::parse_str(B).unwrap()) {
+                    block.parent = None;
+                    block.order = 5;
+                }
+                // Edit a typed property; `raw` no longer matches and is ignored.
+                for prop in &mut block.properties {
+                    if prop.key == "status" {
+                        prop.value = serde_json::json!("done");
+                    }
+                }
+            }
+        }
+        records.push(JsonRecord::Block(BlockRecord {
+            page: "trip".into(),
+            bid: None,
+            parent: Some(Uuid::parse_str(C).unwrap()),
+            order: 0,
+            text: "new child".into(),
+            tags: vec![],
+            properties: vec![PropertyRecord {
+                key: "done".into(),
+                value: serde_json::json!(true),
+                value_type: None,
+            }],
+            links: vec![],
+            relations: vec![],
+            raw: None,
+        }));
+        let jsonl: String = records
+            .iter()
+            .map(|r| serde_json::to_string(r).unwrap() + "\n")
+            .collect();
+        let notes = import_mosaic_json(jsonl.as_bytes()).unwrap();
+        let tree = parse_note(&notes[0].content);
+        let texts: Vec<(u16, &str)> = tree
+            .blocks
+            .iter()
+            .map(|b| (b.indent, b.text.lines().next().unwrap()))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                (0, "Pack for [[Lisbon]] #todo"),
+                (0, "Book"),
+                (1, "new child"),
+                (0, "passport"),
+            ]
+        );
+        assert_eq!(
+            tree.blocks[1].text,
+            "Book\nmore prose after props\nstatus:: done\nestimate:: 2.50"
+        );
+        assert_eq!(tree.blocks[2].text, "new child\ndone:: true");
+    }
+
+    #[test]
+    fn import_rejects_inconsistent_records() {
+        let page = r#"{"kind":"page","id":"p"}"#;
+        let block = |bid: &str, parent: &str| {
+            format!(r#"{{"kind":"block","page":"p","bid":"{bid}","parent":{parent}}}"#)
+        };
+        let cases = [
+            format!("{}\n", block(A, "null")),
+            format!("{page}\n{}\n{}\n", block(A, "null"), block(A, "null")),
+            format!("{page}\n{}\n", block(A, &format!("\"{B}\""))),
+            format!(
+                "{page}\n{}\n{}\n",
+                block(A, &format!("\"{B}\"")),
+                block(B, &format!("\"{A}\""))
+            ),
+            r#"{"kind":"page","id":"../escape"}"#.to_string(),
+            "not json".to_string(),
+        ];
+        for case in cases {
+            assert!(import_mosaic_json(case.as_bytes()).is_err(), "{case}");
+        }
+    }
+}
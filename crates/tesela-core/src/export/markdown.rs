@@ -272,7 +272,7 @@ fn strip_frontmatter(frontmatter: &str) -> (String, usize) {
 
 /// `key:: value` pattern (Logseq/Tesela inline property). Matches a
 /// line whose first non-whitespace run is `<ident> ::`.
-fn parse_property_line(line: &str) -> Option<(&str, &str)> {
+pub(crate) fn parse_property_line(line: &str) -> Option<(&str, &str)> {
     let trimmed = line.trim_start();
     let colon_pos = trimmed.find("::")?;
     let key = trimmed[..colon_pos].trim();
@@ -309,7 +309,7 @@ fn parse_yaml_line(line: &str) -> Option<(&str, &str)> {
 /// Hard-coded list of Tesela-internal property/frontmatter keys we
 /// strip in portable mode. Keep this list narrow — false positives
 /// here destroy real user data.
-fn is_tesela_internal_key(key: &str) -> bool {
+pub(crate) fn is_tesela_internal_key(key: &str) -> bool {
     if key.starts_with('_') {
         return true;
     }
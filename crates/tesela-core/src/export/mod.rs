@@ -3,12 +3,16 @@
 //! Two layers:
 //! - **Single-note export** (this file): `export_note(note, ExportFormat)`
 //!   produces a string in the requested format. Used by `tesela export-note`.
+//!   OPML rendering lives in [`crate::opml`], next to its importer.
 //! - **Mosaic export** (`markdown` submodule): walks the entire mosaic and
 //!   writes a portable directory tree. Two modes — `full` (round-trippable
 //!   back into a Tesela mosaic byte-exact) and `portable` (lossy, strips
 //!   Tesela-specific properties so the output opens cleanly in Obsidian
 //!   or Logseq).
+//! - **Structured export** (`json` submodule): every page and block as a
+//!   JSON Lines record, plus the inverse importer.
 
+pub mod json;
 pub mod markdown;
 
 use crate::note::Note;
@@ -18,6 +22,7 @@ pub enum ExportFormat {
     Html,
     PlainText,
     Markdown,
+    Opml,
 }
 
 /// Export a note to the given format
@@ -26,6 +31,7 @@ pub fn export_note(note: &Note, format: ExportFormat) -> String {
         ExportFormat::Html => export_to_html(note),
         ExportFormat::PlainText => export_to_text(note),
         ExportFormat::Markdown => note.content.clone(),
+        ExportFormat::Opml => crate::opml::note_to_opml(note),
     }
 }
 
@@ -0,0 +1,480 @@
+//! Analytics over the page graph: orphans, dead links, PageRank, clusters
+//! and shortest paths.
+//!
+//! A [`LinkGraphSnapshot`] is built from the same two edge sources the rest
+//! of the app already projects into the index — `[[wiki-links]]` from the
+//! `links` table and typed [`crate::RelationEdge`]s from `relation_edges` —
+//! with every link target resolved to a page by slug, title or alias (the
+//! resolution `[[...]]` rendering uses). Both sources collapse into one
+//! directed page→page edge; a page never links to itself.
+//!
+//! The snapshot is immutable and cheap to query; `SqliteIndex` caches one
+//! and drops it whenever a note, link or relation edge changes. The
+//! `/graph/*` routes, the `is:orphan` / `links-to:` query predicates and
+//! the search ranking signal all read from it.
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+use crate::storage::markdown::sanitize_filename;
+use crate::PageId;
+
+/// One page as a graph node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphPage {
+    pub slug: String,
+    pub title: String,
+    pub aliases: Vec<String>,
+    /// Frontmatter `tesela_page_id`; relation edges target pages by it.
+    pub page_id: Option<PageId>,
+    /// `note_type` — Tag / Property / Query / Template pages are system
+    /// pages and never reported as orphans.
+    pub note_type: Option<String>,
+}
+
+/// A page in an analytics result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphPageRef {
+    pub slug: String,
+    pub title: String,
+}
+
+/// A `[[wiki-link]]` whose target names no page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeadLink {
+    /// Slug of the linking page.
+    pub source: String,
+    /// The link target as written.
+    pub target: String,
+}
+
+/// PageRank plus degree for one page.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PageScore {
+    pub slug: String,
+    pub title: String,
+    /// PageRank; sums to 1 across the graph.
+    pub score: f64,
+    /// Distinct pages linking here.
+    pub in_degree: usize,
+    /// Distinct pages this page links to.
+    pub out_degree: usize,
+}
+
+/// A weakly connected component.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Cluster {
+    pub size: usize,
+    /// Members ordered by slug.
+    pub pages: Vec<GraphPageRef>,
+}
+
+/// PageRank damping factor.
+const DAMPING: f64 = 0.85;
+const PAGE_RANK_MAX_ITERATIONS: usize = 100;
+const PAGE_RANK_TOLERANCE: f64 = 1e-10;
+
+/// Resolved page graph. Pages are indexed by position in slug order.
+#[derive(Debug, Clone, Default)]
+pub struct LinkGraphSnapshot {
+    pages: Vec<GraphPage>,
+    /// Lowercased slug / sanitized title / title / alias → page.
+    by_name: HashMap<String, usize>,
+    out: Vec<BTreeSet<usize>>,
+    incoming: Vec<BTreeSet<usize>>,
+    dead: Vec<DeadLink>,
+    /// PageRank, computed on first use.
+    rank: OnceLock<Vec<f64>>,
+}
+
+/// Equal graphs rank equally, so the PageRank cache is left out.
+impl PartialEq for LinkGraphSnapshot {
+    fn eq(&self, other: &Self) -> bool {
+        self.pages == other.pages
+            && self.by_name == other.by_name
+            && self.out == other.out
+            && self.incoming == other.incoming
+            && self.dead == other.dead
+    }
+}
+
+impl Eq for LinkGraphSnapshot {}
+
+impl LinkGraphSnapshot {
+    /// Build from the page list, wiki-link edges (`source` slug → target as
+    /// written) and relation edges (`source` slug → target page id). Edges
+    /// from unknown sources are ignored; unresolvable wiki-link targets
+    /// become [`DeadLink`]s; relation edges to a missing page are dropped
+    /// (their property value already fails to resolve wherever it's shown).
+    pub fn build<'a>(
+        mut pages: Vec<GraphPage>,
+        links: impl IntoIterator<Item = (&'a str, &'a str)>,
+        relations: impl IntoIterator<Item = (&'a str, PageId)>,
+    ) -> Self {
+        pages.sort_by(|a, b| a.slug.cmp(&b.slug));
+        pages.dedup_by(|a, b| a.slug == b.slug);
+        let by_slug: HashMap<String, usize> = pages
+            .iter()
+            .enumerate()
+            .map(|(i, page)| (page.slug.to_lowercase(), i))
+            .collect();
+        // Slugs win over titles, titles over aliases: insert weakest first.
+        let mut by_name = HashMap::new();
+        for (i, page) in pages.iter().enumerate() {
+            for alias in &page.aliases {
+                by_name.insert(alias.trim().to_lowercase(), i);
+            }
+        }
+        for (i, page) in pages.iter().enumerate() {
+            by_name.insert(page.title.trim().to_lowercase(), i);
+            by_name.insert(sanitize_filename(&page.title), i);
+        }
+        by_name.extend(by_slug.iter().map(|(slug, i)| (slug.clone(), *i)));
+        let by_page_id: HashMap<PageId, usize> = pages
+            .iter()
+            .enumerate()
+            .filter_map(|(i, page)| Some((page.page_id?, i)))
+            .collect();
+
+        let mut graph = Self {
+            out: vec![BTreeSet::new(); pages.len()],
+            incoming: vec![BTreeSet::new(); pages.len()],
+            pages,
+            by_name,
+            dead: Vec::new(),
+            rank: OnceLock::new(),
+        };
+        let mut dead = BTreeSet::new();
+        for (source, target) in links {
+            let Some(&from) = by_slug.get(&source.to_lowercase()) else {
+                continue;
+            };
+            match graph.resolve(target) {
+                Some(to) => graph.add_edge(from, to),
+                None => {
+                    dead.insert((graph.pages[from].slug.clone(), target.trim().to_string()));
+                }
+            }
+        }
+        for (source, target) in relations {
+            if let (Some(&from), Some(&to)) =
+                (by_slug.get(&source.to_lowercase()), by_page_id.get(&target))
+            {
+                graph.add_edge(from, to);
+            }
+        }
+        graph.dead = dead
+            .into_iter()
+            .map(|(source, target)| DeadLink { source, target })
+            .collect();
+        graph
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize) {
+        if from != to {
+            self.out[from].insert(to);
+            self.incoming[to].insert(from);
+        }
+    }
+
+    /// The page a link target or page name (`[[X]]`, slug, title or alias,
+    /// case-insensitive) names.
+    pub fn resolve(&self, name: &str) -> Option<usize> {
+        let name = name.trim();
+        let name = name
+            .strip_prefix("[[")
+            .and_then(|inner| inner.strip_suffix("]]"))
+            .unwrap_or(name)
+            .trim()
+            .to_lowercase();
+        self.by_name
+            .get(&name)
+            .or_else(|| self.by_name.get(&sanitize_filename(&name)))
+            .copied()
+    }
+
+    pub fn page(&self, index: usize) -> &GraphPage {
+        &self.pages[index]
+    }
+
+    pub fn page_ref(&self, index: usize) -> GraphPageRef {
+        GraphPageRef {
+            slug: self.pages[index].slug.clone(),
+            title: self.pages[index].title.clone(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    /// Whether `from` links (or relates) directly to `to`.
+    pub fn links_to(&self, from: usize, to: usize) -> bool {
+        self.out[from].contains(&to)
+    }
+
+    /// Pages `from` links (or relates) to directly, in slug order.
+    pub fn links_from(&self, from: usize) -> impl Iterator<Item = usize> + '_ {
+        self.out[from].iter().copied()
+    }
+
+    /// No incoming and no outgoing edges. System pages are never orphans.
+    pub fn is_orphan(&self, index: usize) -> bool {
+        self.out[index].is_empty()
+            && self.incoming[index].is_empty()
+            && !self.pages[index]
+                .note_type
+                .as_deref()
+                .is_some_and(|t| matches!(t, "Tag" | "Property" | "Query" | "Template"))
+    }
+
+    /// Orphan pages, by slug.
+    pub fn orphans(&self) -> Vec<GraphPageRef> {
+        (0..self.pages.len())
+            .filter(|&i| self.is_orphan(i))
+            .map(|i| self.page_ref(i))
+            .collect()
+    }
+
+    /// Wiki-links to pages that don't exist, by source then target.
+    pub fn dead_links(&self) -> &[DeadLink] {
+        &self.dead
+    }
+
+    /// PageRank over the directed graph (dangling pages spread their rank
+    /// evenly), indexed like the pages. Empty for an empty graph. Computed
+    /// once per snapshot.
+    pub fn page_rank(&self) -> &[f64] {
+        self.rank.get_or_init(|| self.compute_page_rank())
+    }
+
+    fn compute_page_rank(&self) -> Vec<f64> {
+        let n = self.pages.len();
+        if n == 0 {
+            return Vec::new();
+        }
+        let uniform = 1.0 / n as f64;
+        let mut rank = vec![uniform; n];
+        for _ in 0..PAGE_RANK_MAX_ITERATIONS {
+            let dangling: f64 = (0..n)
+                .filter(|&i| self.out[i].is_empty())
+                .map(|i| rank[i])
+                .sum();
+            let base = (1.0 - DAMPING) * uniform + DAMPING * dangling * uniform;
+            let mut next = vec![base; n];
+            for (from, targets) in self.out.iter().enumerate() {
+                if targets.is_empty() {
+                    continue;
+                }
+                let share = DAMPING * rank[from] / targets.len() as f64;
+                for &to in targets {
+                    next[to] += share;
+                }
+            }
+            let delta: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+            rank = next;
+            if delta < PAGE_RANK_TOLERANCE {
+                break;
+            }
+        }
+        rank
+    }
+
+    /// Every page's [`PageScore`], highest PageRank first (ties by slug).
+    pub fn scores(&self) -> Vec<PageScore> {
+        let mut scores: Vec<PageScore> = self
+            .page_rank()
+            .iter()
+            .copied()
+            .enumerate()
+            .map(|(i, score)| PageScore {
+                slug: self.pages[i].slug.clone(),
+                title: self.pages[i].title.clone(),
+                score,
+                in_degree: self.incoming[i].len(),
+                out_degree: self.out[i].len(),
+            })
+            .collect();
+        scores.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.slug.cmp(&b.slug))
+        });
+        scores
+    }
+
+    /// Weakly connected components with at least `min_size` pages, largest
+    /// first (ties by first slug).
+    pub fn clusters(&self, min_size: usize) -> Vec<Cluster> {
+        let mut component = vec![usize::MAX; self.pages.len()];
+        let mut clusters = Vec::new();
+        for start in 0..self.pages.len() {
+            if component[start] != usize::MAX {
+                continue;
+            }
+            component[start] = start;
+            let mut members = vec![start];
+            let mut queue = VecDeque::from([start]);
+            while let Some(at) = queue.pop_front() {
+                for &next in self.out[at].iter().chain(&self.incoming[at]) {
+                    if component[next] == usize::MAX {
+                        component[next] = start;
+                        members.push(next);
+                        queue.push_back(next);
+                    }
+                }
+            }
+            if members.len() >= min_size.max(1) {
+                members.sort_unstable();
+                clusters.push(Cluster {
+                    size: members.len(),
+                    pages: members.into_iter().map(|i| self.page_ref(i)).collect(),
+                });
+            }
+        }
+        clusters.sort_by(|a, b| {
+            b.size
+                .cmp(&a.size)
+                .then_with(|| a.pages[0].slug.cmp(&b.pages[0].slug))
+        });
+        clusters
+    }
+
+    /// Fewest-hop path from `from` to `to`, both ends included. `directed`
+    /// follows links only forwards; otherwise a backlink is a step too.
+    /// Among equally short paths the one through lower slugs wins.
+    pub fn shortest_path(&self, from: usize, to: usize, directed: bool) -> Option<Vec<usize>> {
+        let mut previous = vec![usize::MAX; self.pages.len()];
+        previous[from] = from;
+        let mut queue = VecDeque::from([from]);
+        while let Some(at) = queue.pop_front() {
+            if at == to {
+                let mut path = vec![to];
+                let mut step = to;
+                while step != from {
+                    step = previous[step];
+                    path.push(step);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            let backward = if directed {
+                None
+            } else {
+                Some(&self.incoming[at])
+            };
+            let neighbours: BTreeSet<usize> = self.out[at]
+                .iter()
+                .chain(backward.into_iter().flatten())
+                .copied()
+                .collect();
+            for next in neighbours {
+                if previous[next] == usize::MAX {
+                    previous[next] = at;
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(slug: &str, title: &str) -> GraphPage {
+        GraphPage {
+            slug: slug.into(),
+            title: title.into(),
+            aliases: Vec::new(),
+            page_id: None,
+            note_type: None,
+        }
+    }
+
+    fn sample() -> LinkGraphSnapshot {
+        let mut alice = page("alice", "Alice");
+        alice.aliases.push("Al".into());
+        let mut project = page("apollo", "Apollo");
+        project.page_id = Some(PageId::from_legacy_doc_id(&[7; 16]));
+        let mut tag = page("task", "Task");
+        tag.note_type = Some("Tag".into());
+        LinkGraphSnapshot::build(
+            vec![
+                alice,
+                project,
+                page("meeting-notes", "Meeting notes"),
+                page("lonely", "Lonely"),
+                tag,
+            ],
+            [
+                ("meeting-notes", "Al"),
+                ("meeting-notes", "Nowhere"),
+                ("alice", "Alice"),
+            ],
+            [("alice", PageId::from_legacy_doc_id(&[7; 16]))],
+        )
+    }
+
+    #[test]
+    fn resolves_links_by_slug_title_and_alias_and_reports_the_rest() {
+        let graph = sample();
+        let alice = graph.resolve("[[al]]").unwrap();
+        let meeting = graph.resolve("Meeting Notes").unwrap();
+        let apollo = graph.resolve("apollo").unwrap();
+        assert!(graph.links_to(meeting, alice));
+        assert!(graph.links_to(alice, apollo), "relation edges count");
+        assert!(!graph.links_to(alice, alice), "no self loops");
+        assert_eq!(
+            graph.dead_links(),
+            &[DeadLink {
+                source: "meeting-notes".into(),
+                target: "Nowhere".into(),
+            }]
+        );
+        let orphans: Vec<_> = graph.orphans().into_iter().map(|p| p.slug).collect();
+        assert_eq!(orphans, vec!["lonely"], "system pages are not orphans");
+    }
+
+    #[test]
+    fn page_rank_favours_linked_pages_and_sums_to_one() {
+        let graph = sample();
+        let scores = graph.scores();
+        let total: f64 = scores.iter().map(|s| s.score).sum();
+        assert!((total - 1.0).abs() < 1e-9, "{total}");
+        assert_eq!(scores[0].slug, "apollo");
+        assert_eq!(scores[0].in_degree, 1);
+        let lonely = scores.iter().find(|s| s.slug == "lonely").unwrap();
+        assert!(lonely.score < scores[0].score);
+    }
+
+    #[test]
+    fn clusters_and_paths() {
+        let graph = sample();
+        let clusters = graph.clusters(2);
+        assert_eq!(clusters.len(), 1);
+        let slugs: Vec<_> = clusters[0].pages.iter().map(|p| p.slug.as_str()).collect();
+        assert_eq!(slugs, vec!["alice", "apollo", "meeting-notes"]);
+        assert_eq!(graph.clusters(1).len(), 3);
+
+        let meeting = graph.resolve("meeting-notes").unwrap();
+        let apollo = graph.resolve("apollo").unwrap();
+        let path = graph.shortest_path(meeting, apollo, true).unwrap();
+        let slugs: Vec<_> = path.iter().map(|&i| graph.page(i).slug.as_str()).collect();
+        assert_eq!(slugs, vec!["meeting-notes", "alice", "apollo"]);
+        assert!(graph.shortest_path(apollo, meeting, true).is_none());
+        assert_eq!(
+            graph.shortest_path(apollo, meeting, false).unwrap().len(),
+            3
+        );
+        let lonely = graph.resolve("lonely").unwrap();
+        assert!(graph.shortest_path(lonely, apollo, false).is_none());
+    }
+}
@@ -0,0 +1,190 @@
+//! Point-in-time phrases for note history ("as of last tuesday 14:00").
+//!
+//! The server's `/notes/{id}/at` and `/notes/{id}/restore` routes,
+//! `tesela history` and the MCP history tools all resolve their `at`
+//! argument here, so every surface accepts the same spellings:
+//!
+//! - unix seconds (`1760968800`) or RFC 3339 (`2026-10-13T14:00:00Z`);
+//! - a local date with an optional time (`2026-10-13`, `2026-10-13 14:00`);
+//! - `today` / `yesterday` / `last <weekday>` / `<weekday>`, optionally
+//!   followed by a time (`last tuesday 14:00`, `yesterday 9:30am`);
+//! - `N minutes|hours|days|weeks ago`.
+//!
+//! A day without a time means the END of that day — "as of Tuesday" is the
+//! page once Tuesday was over. Weekdays always look back: a bare `tuesday`
+//! is the most recent Tuesday before today, the same as `last tuesday`.
+
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc, Weekday};
+
+/// Resolve `input` against `now` (whose time zone interprets local dates
+/// and times). `None` when the phrase isn't recognized.
+pub fn parse_point_in_time<Tz: TimeZone>(input: &str, now: &DateTime<Tz>) -> Option<DateTime<Utc>> {
+    let trimmed = input.trim();
+    let raw = trimmed.to_lowercase();
+    if raw.is_empty() {
+        return None;
+    }
+    if let Ok(secs) = raw.parse::<i64>() {
+        return DateTime::from_timestamp(secs, 0);
+    }
+    if let Ok(at) = DateTime::parse_from_rfc3339(trimmed) {
+        return Some(at.with_timezone(&Utc));
+    }
+    if raw == "now" {
+        return Some(now.with_timezone(&Utc));
+    }
+    if let Some(ago) = parse_ago(&raw) {
+        return now.with_timezone(&Utc).checked_sub_signed(ago);
+    }
+    let (day, time) = split_time(&raw);
+    let date = parse_day(day, now.date_naive())?;
+    let time = match time {
+        Some(time) => parse_time(time)?,
+        None => NaiveTime::from_hms_opt(23, 59, 59)?,
+    };
+    let local = now
+        .timezone()
+        .from_local_datetime(&date.and_time(time))
+        .earliest()?;
+    Some(local.with_timezone(&Utc))
+}
+
+/// `N <unit> ago`; `None` as well when the span doesn't fit a [`TimeDelta`].
+fn parse_ago(raw: &str) -> Option<TimeDelta> {
+    let mut words = raw.split_whitespace();
+    let n: i64 = words.next()?.parse().ok()?;
+    let unit = words.next()?;
+    if words.next()? != "ago" || words.next().is_some() {
+        return None;
+    }
+    match unit.trim_end_matches('s') {
+        "min" | "minute" => TimeDelta::try_minutes(n),
+        "hr" | "hour" => TimeDelta::try_hours(n),
+        "day" => TimeDelta::try_days(n),
+        "week" => TimeDelta::try_weeks(n),
+        _ => None,
+    }
+}
+
+/// Split a trailing time off the day part: `2026-10-13t14:00`,
+/// `2026-10-13 14:00`, `last tuesday 2pm`.
+fn split_time(raw: &str) -> (&str, Option<&str>) {
+    if let Some((day, time)) = raw.split_once('t') {
+        if NaiveDate::parse_from_str(day, "%Y-%m-%d").is_ok() {
+            return (day, Some(time));
+        }
+    }
+    match raw.rsplit_once(' ') {
+        Some((day, time)) if parse_time(time).is_some() => (day.trim_end(), Some(time)),
+        _ => (raw, None),
+    }
+}
+
+fn parse_day(day: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match day {
+        "today" => return Some(today),
+        "yesterday" => return today.pred_opt(),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(day, "%Y-%m-%d") {
+        return Some(date);
+    }
+    let weekday: Weekday = day.strip_prefix("last ").unwrap_or(day).parse().ok()?;
+    (1..=7)
+        .map(|back| today - TimeDelta::days(back))
+        .find(|d| chrono::Datelike::weekday(d) == weekday)
+}
+
+/// `14:00`, `14:00:30`, `9:30am`, `2pm`.
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    let (clock, pm) = match (time.strip_suffix("am"), time.strip_suffix("pm")) {
+        (Some(clock), _) => (clock, Some(false)),
+        (_, Some(clock)) => (clock, Some(true)),
+        _ => (time, None),
+    };
+    let mut parts = clock.split(':');
+    let mut hour: u32 = parts.next()?.parse().ok()?;
+    let minute: u32 = parts.next().map_or(Some(0), |m| m.parse().ok())?;
+    let second: u32 = parts.next().map_or(Some(0), |s| s.parse().ok())?;
+    if parts.next().is_some() || (pm.is_none() && !clock.contains(':')) {
+        return None;
+    }
+    if let Some(pm) = pm {
+        if !(1..=12).contains(&hour) {
+            return None;
+        }
+        hour = hour % 12 + if pm { 12 } else { 0 };
+    }
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(input: &str) -> Option<String> {
+        // Thursday 2026-10-15 10:00 at UTC+02:00.
+        let now = chrono::FixedOffset::east_opt(2 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2026, 10, 15, 10, 0, 0)
+            .unwrap();
+        parse_point_in_time(input, &now).map(|t| t.to_rfc3339())
+    }
+
+    #[test]
+    fn absolute_spellings() {
+        assert_eq!(
+            at("1760968800").as_deref(),
+            Some("2025-10-20T14:00:00+00:00")
+        );
+        assert_eq!(
+            at("2026-10-13T14:00:00Z").as_deref(),
+            Some("2026-10-13T14:00:00+00:00")
+        );
+        assert_eq!(
+            at("2026-10-13 14:00").as_deref(),
+            Some("2026-10-13T12:00:00+00:00"),
+            "local time"
+        );
+        assert_eq!(
+            at("2026-10-13").as_deref(),
+            Some("2026-10-13T21:59:59+00:00"),
+            "a bare day is its end"
+        );
+    }
+
+    #[test]
+    fn relative_spellings_look_back() {
+        assert_eq!(
+            at("last tuesday 14:00").as_deref(),
+            Some("2026-10-13T12:00:00+00:00")
+        );
+        assert_eq!(at("tuesday 2pm"), at("last tuesday 14:00"));
+        assert_eq!(
+            at("thursday 9:30am").as_deref(),
+            Some("2026-10-08T07:30:00+00:00"),
+            "a weekday never means today"
+        );
+        assert_eq!(
+            at("yesterday").as_deref(),
+            Some("2026-10-14T21:59:59+00:00")
+        );
+        assert_eq!(
+            at("3 hours ago").as_deref(),
+            Some("2026-10-15T05:00:00+00:00")
+        );
+        assert_eq!(at("next tuesday"), None);
+        assert_eq!(at("tuesday 25:00"), None);
+    }
+
+    #[test]
+    fn out_of_range_ago_is_none() {
+        assert_eq!(at("9223372036854775807 weeks ago"), None);
+        assert_eq!(at("-9223372036854775807 minutes ago"), None);
+        assert_eq!(
+            at("20000000 weeks ago"),
+            None,
+            "before chrono's earliest date"
+        );
+    }
+}
@@ -1,9 +1,12 @@
 pub mod block;
+pub mod computed;
 pub mod config;
 pub mod daily;
 pub mod db;
 pub mod error;
 pub mod export;
+pub mod graph;
+pub mod history;
 pub mod import_logseq;
 pub mod indexer;
 pub mod lifecycle;
@@ -11,10 +14,13 @@ pub mod link;
 pub mod nlp_lift;
 pub mod note;
 pub mod note_tree;
+pub mod opml;
 pub mod property;
+pub mod publish;
 pub mod query;
 pub mod recurrence;
 pub mod regex_cache;
+pub mod related;
 pub mod storage;
 pub mod system_widgets;
 pub mod tag;
@@ -183,7 +183,7 @@ pub struct Attachment {
 }
 
 /// A historical version of a note. Created on every successful PUT (Phase 9.3).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(test, derive(TS))]
 #[cfg_attr(test, ts(export, export_to = "../../../web/src/lib/types/"))]
 pub struct NoteVersion {
@@ -0,0 +1,645 @@
+//! OPML exchange for outlines.
+//!
+//! Export turns a page (or a query result) into OPML 2.0: one
+//! `<outline>` per block, nested by indent. The block's first line is
+//! the `text` attribute, remaining prose lines go to `_note` (the
+//! attribute Workflowy / Dynalist / OmniOutliner use for notes), every
+//! `key:: value` property becomes an attribute, and the block id rides
+//! along as `bid` so a round trip keeps block identity.
+//!
+//! Import reads any OPML outline back into a [`NoteTree`] and renders it
+//! with [`serialize_note`], so the result is canonical Tesela markdown
+//! (bullets, continuation lines, property lines, bid markers). Outline
+//! attributes other than `text` / `_note` / `bid` become block
+//! properties when their name is a valid property key.
+//!
+//! Page properties and frontmatter beyond the title are not part of the
+//! exchange: OPML's `<head>` has a fixed vocabulary.
+//!
+//! The XML reader is hand-rolled for the subset OPML needs (elements,
+//! attributes, entities, comments, CDATA) — the same trade-off the org
+//! importer makes instead of pulling in a full XML crate.
+
+use std::collections::HashSet;
+
+use uuid::Uuid;
+
+use crate::error::{Result, TeselaError};
+use crate::export::markdown::parse_property_line;
+use crate::note::Note;
+use crate::note_tree::{parse_note, serialize_note, FlatBlock, MarkdownFenceTracker, NoteTree};
+use crate::query::{Kind, QueryResult};
+
+/// Outline attributes with a fixed meaning; never read as properties.
+const TEXT_ATTR: &str = "text";
+const NOTE_ATTR: &str = "_note";
+const BID_ATTR: &str = "bid";
+
+/// Deepest element nesting an imported document may use. Outlines become
+/// blocks recursively (and `Element` trees drop recursively), so a hostile
+/// document nested thousands deep would otherwise overflow the stack.
+const MAX_NESTING: usize = 256;
+
+/// Render `note`'s block outline as an OPML document.
+pub fn note_to_opml(note: &Note) -> String {
+    let tree = parse_note(&note.content);
+    let mut out = String::new();
+    let mut depth = 0usize;
+    for block in &tree.blocks {
+        // Clamp to one level below the previous block, as the parser does.
+        let level = (block.indent as usize).min(depth);
+        close_outlines(&mut out, &mut depth, level);
+        push_outline_open(&mut out, level, &block_attrs(block));
+        depth = level + 1;
+    }
+    close_outlines(&mut out, &mut depth, 0);
+    document(&note.title, &out)
+}
+
+/// Render a query result as an OPML document: one outline per row (the
+/// row's block id and properties as attributes, its page as `page`),
+/// nested under one outline per group when the result is grouped.
+pub fn query_result_to_opml(title: &str, result: &QueryResult) -> String {
+    let grouped = result.groups.len() > 1 || result.groups.iter().any(|g| !g.key.is_empty());
+    let mut out = String::new();
+    for group in &result.groups {
+        let level = usize::from(grouped);
+        if grouped {
+            push_outline_open(&mut out, 0, &[(TEXT_ATTR.to_string(), group.key.clone())]);
+        }
+        for item in &group.items {
+            let mut attrs = vec![(
+                TEXT_ATTR.to_string(),
+                match item.kind {
+                    Kind::Page => item.title.clone(),
+                    Kind::Block => item.text.clone(),
+                },
+            )];
+            // Index-local row ids (`page:line`) aren't block ids.
+            if let Some(bid) = item
+                .block_id
+                .as_deref()
+                .filter(|bid| Uuid::parse_str(bid).is_ok())
+            {
+                attrs.push((BID_ATTR.to_string(), bid.to_string()));
+            }
+            let mut props: Vec<_> = item
+                .properties
+                .iter()
+                .filter(|(k, _)| is_attr_key(k))
+                .collect();
+            props.sort();
+            if !item.properties.contains_key("page") {
+                attrs.push(("page".to_string(), item.page_id.clone()));
+            }
+            attrs.extend(props.into_iter().map(|(k, v)| (k.clone(), v.clone())));
+            push_outline_open(&mut out, level, &attrs);
+            out.truncate(out.len() - 2);
+            out.push_str("/>\n");
+        }
+        if grouped {
+            push_outline_close(&mut out, 0);
+        }
+    }
+    document(title, &out)
+}
+
+/// An OPML document read back as a Tesela page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpmlImport {
+    /// `<head><title>`, when present and non-empty.
+    pub title: Option<String>,
+    /// Canonical note body (no frontmatter), ready for note creation.
+    pub body: String,
+    pub block_count: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OpmlImportOptions {
+    /// Keep `bid` attributes as block ids. Off by default: re-importing
+    /// an export into the mosaic it came from would otherwise create a
+    /// second page claiming the same blocks. Ids that are malformed or
+    /// repeated within the document are always re-minted.
+    pub keep_ids: bool,
+}
+
+/// Parse an OPML document into a page body.
+pub fn opml_to_note(opml: &str, opts: OpmlImportOptions) -> Result<OpmlImport> {
+    let root = parse_xml(opml)?;
+    if !root.name.eq_ignore_ascii_case("opml") {
+        return Err(TeselaError::validation(format!(
+            "not an OPML document: root element is <{}>",
+            root.name
+        )));
+    }
+    let title = root
+        .child("head")
+        .and_then(|head| head.child("title"))
+        .map(|t| t.text.trim().to_string())
+        .filter(|t| !t.is_empty());
+    let body = root
+        .child("body")
+        .ok_or_else(|| TeselaError::validation("OPML document has no <body>"))?;
+
+    let mut tree = NoteTree {
+        frontmatter: None,
+        page_properties: Vec::new(),
+        blocks: Vec::new(),
+        stamped_any: false,
+    };
+    let mut seen = HashSet::new();
+    push_blocks(&mut tree.blocks, body, None, 0, opts, &mut seen);
+    let block_count = tree.blocks.len();
+    Ok(OpmlImport {
+        title,
+        body: serialize_note(&tree),
+        block_count,
+    })
+}
+
+fn push_blocks(
+    blocks: &mut Vec<FlatBlock>,
+    parent_el: &Element,
+    parent: Option<Uuid>,
+    indent: u16,
+    opts: OpmlImportOptions,
+    seen: &mut HashSet<Uuid>,
+) {
+    for el in parent_el.children.iter().filter(|c| c.name == "outline") {
+        let id = el
+            .attr(BID_ATTR)
+            .filter(|_| opts.keep_ids)
+            .and_then(|bid| Uuid::parse_str(bid.trim()).ok())
+            .filter(|id| !seen.contains(id))
+            .unwrap_or_else(Uuid::now_v7);
+        seen.insert(id);
+
+        let mut lines: Vec<String> = el
+            .attr(TEXT_ATTR)
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        if let Some(note) = el.attr(NOTE_ATTR) {
+            lines.extend(note.lines().map(str::to_string));
+        }
+        // A continuation line that reads as a bullet would re-parse as a
+        // child block.
+        for line in lines.iter_mut().skip(1) {
+            if line.trim_start().starts_with("- ") {
+                line.insert(line.len() - line.trim_start().len(), '\\');
+            }
+        }
+        let properties = el
+            .attrs
+            .iter()
+            .filter(|(k, _)| k != TEXT_ATTR && k != NOTE_ATTR && k != BID_ATTR)
+            .filter(|(k, _)| is_property_key(k))
+            .map(|(k, v)| (k.clone(), v.replace('\n', " ")))
+            .collect();
+        blocks.push(FlatBlock {
+            id,
+            parent,
+            indent,
+            text: lines.join("\n"),
+            properties,
+        });
+        push_blocks(blocks, el, Some(id), indent + 1, opts, seen);
+    }
+}
+
+/// `text`, `_note`, `bid` and property attributes for one block.
+fn block_attrs(block: &FlatBlock) -> Vec<(String, String)> {
+    let mut lines = block.text.lines();
+    let first = lines.next().unwrap_or_default().to_string();
+    let mut note = Vec::new();
+    let mut props = Vec::new();
+    let mut fence = MarkdownFenceTracker::default();
+    for line in lines {
+        if fence.line_is_fenced(line) {
+            note.push(line.to_string());
+            continue;
+        }
+        match parse_property_line(line) {
+            Some((key, value)) if is_attr_key(key) => {
+                props.push((key.to_string(), value.to_string()))
+            }
+            _ => note.push(line.to_string()),
+        }
+    }
+    props.extend(
+        block
+            .properties
+            .iter()
+            .filter(|(k, _)| is_attr_key(k))
+            .cloned(),
+    );
+    // Properties that can't be attributes stay in the note as lines.
+    note.extend(
+        block
+            .properties
+            .iter()
+            .filter(|(k, _)| !is_attr_key(k))
+            .map(|(k, v)| format!("{k}:: {v}")),
+    );
+
+    let mut attrs = vec![(TEXT_ATTR.to_string(), first)];
+    if !note.is_empty() {
+        attrs.push((NOTE_ATTR.to_string(), note.join("\n")));
+    }
+    attrs.push((BID_ATTR.to_string(), block.id.to_string()));
+    attrs.extend(props);
+    attrs
+}
+
+/// Property keys that can be written as an XML attribute and don't
+/// collide with the outline attributes.
+fn is_attr_key(key: &str) -> bool {
+    key.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key != TEXT_ATTR
+        && key != NOTE_ATTR
+        && key != BID_ATTR
+}
+
+/// Attribute names that are valid `key::` property keys.
+fn is_property_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+}
+
+fn push_outline_open(out: &mut String, level: usize, attrs: &[(String, String)]) {
+    out.push_str(&"  ".repeat(level + 2));
+    out.push_str("<outline");
+    for (key, value) in attrs {
+        out.push_str(&format!(" {key}=\"{}\"", escape_attr(value)));
+    }
+    out.push_str(">\n");
+}
+
+fn push_outline_close(out: &mut String, level: usize) {
+    out.push_str(&"  ".repeat(level + 2));
+    out.push_str("</outline>\n");
+}
+
+/// Close open outlines down to `level`. A leaf's `>` is rewritten to
+/// `/>` instead of emitting an empty element.
+fn close_outlines(out: &mut String, depth: &mut usize, level: usize) {
+    let mut leaf = true;
+    while *depth > level {
+        *depth -= 1;
+        if leaf {
+            out.truncate(out.len() - 2);
+            out.push_str("/>\n");
+            leaf = false;
+        } else {
+            push_outline_close(out, *depth);
+        }
+    }
+}
+
+fn document(title: &str, outlines: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n  <head>\n    \
+         <title>{}</title>\n  </head>\n  <body>\n{outlines}  </body>\n</opml>\n",
+        escape_text(title)
+    )
+}
+
+fn escape_text(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn escape_attr(s: &str) -> String {
+    escape_text(s)
+        .replace('"', "&quot;")
+        .replace('\n', "&#10;")
+        .replace('\t', "&#9;")
+}
+
+/// One XML element; text content is concatenated.
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attrs: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+fn xml_err(message: impl Into<String>) -> TeselaError {
+    TeselaError::validation(format!("malformed OPML: {}", message.into()))
+}
+
+/// Parse `src` into its root element.
+fn parse_xml(src: &str) -> Result<Element> {
+    let mut stack: Vec<Element> = Vec::new();
+    let mut root = None;
+    let mut rest = src.trim_start_matches('\u{feff}');
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            if let Some(top) = stack.last_mut() {
+                top.text.push_str(&unescape(rest)?);
+            }
+            break;
+        };
+        if let Some(top) = stack.last_mut() {
+            top.text.push_str(&unescape(&rest[..lt])?);
+        }
+        rest = &rest[lt..];
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = skip_past(after, "-->")?;
+        } else if let Some(after) = rest.strip_prefix("<![CDATA[") {
+            let end = after.find("]]>").ok_or_else(|| xml_err("unclosed CDATA"))?;
+            if let Some(top) = stack.last_mut() {
+                top.text.push_str(&after[..end]);
+            }
+            rest = &after[end + 3..];
+        } else if let Some(after) = rest.strip_prefix("<?") {
+            rest = skip_past(after, "?>")?;
+        } else if let Some(after) = rest.strip_prefix("<!") {
+            rest = skip_past(after, ">")?;
+        } else if let Some(after) = rest.strip_prefix("</") {
+            let end = after.find('>').ok_or_else(|| xml_err("unclosed end tag"))?;
+            let name = after[..end].trim();
+            let el = stack
+                .pop()
+                .filter(|el| el.name == name)
+                .ok_or_else(|| xml_err(format!("unexpected </{name}>")))?;
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(el),
+                None => root = Some(el),
+            }
+            rest = &after[end + 1..];
+        } else {
+            let (el, self_closing, after) = parse_start_tag(&rest[1..])?;
+            rest = after;
+            if self_closing {
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(el),
+                    None => root = Some(el),
+                }
+            } else if stack.len() >= MAX_NESTING {
+                return Err(xml_err(format!(
+                    "elements nested deeper than {MAX_NESTING} levels"
+                )));
+            } else {
+                stack.push(el);
+            }
+        }
+        if root.is_some() {
+            break;
+        }
+    }
+    if let Some(open) = stack.last() {
+        return Err(xml_err(format!("<{}> is never closed", open.name)));
+    }
+    root.ok_or_else(|| xml_err("no root element"))
+}
+
+fn skip_past<'a>(s: &'a str, end: &str) -> Result<&'a str> {
+    s.find(end)
+        .map(|i| &s[i + end.len()..])
+        .ok_or_else(|| xml_err(format!("missing `{end}`")))
+}
+
+/// Parse `name attr="v" ...>` (the `<` already consumed).
+fn parse_start_tag(s: &str) -> Result<(Element, bool, &str)> {
+    let is_name_char = |c: char| !c.is_whitespace() && !matches!(c, '>' | '/' | '=');
+    let name_end = s.find(|c| !is_name_char(c)).unwrap_or(s.len());
+    if name_end == 0 {
+        return Err(xml_err("empty tag name"));
+    }
+    let mut el = Element {
+        name: s[..name_end].to_string(),
+        ..Default::default()
+    };
+    let mut rest = &s[name_end..];
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("/>") {
+            return Ok((el, true, after));
+        }
+        if let Some(after) = rest.strip_prefix('>') {
+            return Ok((el, false, after));
+        }
+        let key_end = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+        if key_end == 0 {
+            return Err(xml_err(format!("bad attribute in <{}>", el.name)));
+        }
+        let key = rest[..key_end].to_string();
+        rest = rest[key_end..].trim_start();
+        rest = rest
+            .strip_prefix('=')
+            .ok_or_else(|| xml_err(format!("attribute `{key}` has no value")))?
+            .trim_start();
+        let quote = rest
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| xml_err(format!("attribute `{key}` is not quoted")))?;
+        let value_end = rest[1..]
+            .find(quote)
+            .ok_or_else(|| xml_err(format!("attribute `{key}` is not closed")))?;
+        el.attrs.push((key, unescape(&rest[1..1 + value_end])?));
+        rest = &rest[value_end + 2..];
+    }
+}
+
+fn unescape(s: &str) -> Result<String> {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        let after = &rest[amp + 1..];
+        let semi = after
+            .find(';')
+            .ok_or_else(|| xml_err("unterminated entity"))?;
+        let entity = &after[..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse::<u32>))
+                .and_then(|n| n.ok())
+                .and_then(char::from_u32),
+        };
+        out.push(decoded.ok_or_else(|| xml_err(format!("unknown entity &{entity};")))?);
+        rest = &after[semi + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::note::{NoteId, NoteMetadata};
+    use crate::query::{QueryGroup, QueryItem};
+    use chrono::Utc;
+    use std::path::PathBuf;
+
+    const A: &str = "0193a000-0000-7000-8000-00000000000a";
+    const B: &str = "0193a000-0000-7000-8000-00000000000b";
+    const C: &str = "0193a000-0000-7000-8000-00000000000c";
+
+    fn note(content: &str) -> Note {
+        Note {
+            id: NoteId::new("trip"),
+            title: "Trip & plans".to_string(),
+            content: content.to_string(),
+            body: String::new(),
+            metadata: NoteMetadata::default(),
+            path: PathBuf::from("notes/trip.md"),
+            checksum: String::new(),
+            created_at: Utc::now(),
+            modified_at: Utc::now(),
+            attachments: vec![],
+        }
+    }
+
+    #[test]
+    fn exports_nested_outlines_with_properties_and_bids() {
+        let opml = note_to_opml(&note(&format!(
+            "---\ntitle: Trip & plans\n---\n\
+             - Pack <b> \"light\" <!-- bid:{A} -->\n  status:: todo\n  remember socks\n\
+             \x20 - passport <!-- bid:{B} -->\n\
+             - Book hotel <!-- bid:{C} -->\n"
+        )));
+        assert!(opml.contains("<title>Trip &amp; plans</title>"), "{opml}");
+        assert!(opml.contains(&format!(
+            "<outline text=\"Pack &lt;b&gt; &quot;light&quot;\" _note=\"remember socks\" bid=\"{A}\" status=\"todo\">"
+        )));
+        assert!(opml.contains(&format!(
+            "      <outline text=\"passport\" bid=\"{B}\"/>\n    </outline>"
+        )));
+        assert!(opml.contains(&format!("<outline text=\"Book hotel\" bid=\"{C}\"/>")));
+    }
+
+    #[test]
+    fn round_trips_through_import_keeping_ids() {
+        let content = format!(
+            "---\ntitle: Trip & plans\n---\n\
+             - Pack <!-- bid:{A} -->\n  remember socks\n  status:: todo\n\
+             \x20 - passport <!-- bid:{B} -->\n\
+             - Book hotel <!-- bid:{C} -->\n"
+        );
+        let opml = note_to_opml(&note(&content));
+        let imported = opml_to_note(&opml, OpmlImportOptions { keep_ids: true }).unwrap();
+        assert_eq!(imported.title.as_deref(), Some("Trip & plans"));
+        assert_eq!(imported.block_count, 3);
+        assert_eq!(
+            parse_note(&imported.body).blocks,
+            parse_note(&content).blocks
+        );
+
+        let fresh = opml_to_note(&opml, OpmlImportOptions::default()).unwrap();
+        assert!(!fresh.body.contains(A));
+        assert_eq!(parse_note(&fresh.body).blocks.len(), 3);
+    }
+
+    #[test]
+    fn imports_foreign_opml() {
+        let opml = "<?xml version=\"1.0\"?>\n<!-- from another outliner -->\n\
+            <opml version=\"1.0\"><head><title>Groceries</title></head><body>\
+            <outline text=\"Dairy\" _complete='true' xmlns:x=\"y\">\
+              <outline text=\"milk &amp; eggs\" _note=\"- organic&#10;two dozen\"/>\
+            </outline>\
+            <outline text=\"Bread\" bid=\"not-a-uuid\"></outline>\
+            </body></opml>";
+        let imported = opml_to_note(opml, OpmlImportOptions { keep_ids: true }).unwrap();
+        let tree = parse_note(&imported.body);
+        assert_eq!(imported.title.as_deref(), Some("Groceries"));
+        let texts: Vec<_> = tree.blocks.iter().map(|b| b.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "Dairy\n_complete:: true",
+                "milk & eggs\n\\- organic\ntwo dozen",
+                "Bread"
+            ]
+        );
+        assert_eq!(tree.blocks[1].parent, Some(tree.blocks[0].id));
+        assert_eq!(tree.blocks[1].indent, 1);
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        assert!(opml_to_note("<html><body/></html>", Default::default()).is_err());
+        assert!(opml_to_note(
+            "<opml><body><outline text=\"x\"></body></opml>",
+            Default::default()
+        )
+        .is_err());
+        assert!(opml_to_note("<opml><head/></opml>", Default::default()).is_err());
+    }
+
+    #[test]
+    fn rejects_documents_nested_past_the_limit() {
+        let nested = |depth: usize| {
+            format!(
+                "<opml><body>{}{}</body></opml>",
+                "<outline text=\"x\">".repeat(depth),
+                "</outline>".repeat(depth)
+            )
+        };
+        let ok = opml_to_note(&nested(MAX_NESTING - 2), Default::default()).unwrap();
+        assert_eq!(ok.block_count, MAX_NESTING - 2);
+        assert!(opml_to_note(&nested(100_000), Default::default()).is_err());
+    }
+
+    #[test]
+    fn exports_grouped_query_results() {
+        let item = |bid: &str, text: &str| QueryItem {
+            block_id: Some(bid.to_string()),
+            page_id: "trip".to_string(),
+            title: "Trip".to_string(),
+            text: text.to_string(),
+            parent_breadcrumb: vec![],
+            kind: Kind::Block,
+            primary_tag: None,
+            properties: [("status".to_string(), "todo".to_string())].into(),
+            page_note_type: None,
+        };
+        let result = QueryResult {
+            groups: vec![QueryGroup {
+                key: "todo".to_string(),
+                count: 2,
+                items: vec![item(A, "Pack"), item(B, "Book hotel")],
+            }],
+        };
+        let opml = query_result_to_opml("status = todo", &result);
+        assert!(opml.contains("    <outline text=\"todo\">\n"), "{opml}");
+        assert!(opml.contains(&format!(
+            "      <outline text=\"Pack\" bid=\"{A}\" page=\"trip\" status=\"todo\"/>"
+        )));
+        let imported = opml_to_note(&opml, Default::default()).unwrap();
+        assert_eq!(imported.block_count, 3);
+    }
+}
@@ -34,6 +34,11 @@ pub enum ValueType {
     Email,
     Phone,
     Object,
+    /// Computed per row from a `formula:` expression; see [`crate::computed`].
+    Formula,
+    /// Computed per page by aggregating the rows related to it; see
+    /// [`crate::computed`].
+    Rollup,
 }
 
 impl ValueType {
@@ -55,6 +60,8 @@ impl ValueType {
             "email" => ValueType::Email,
             "phone" => ValueType::Phone,
             "object" => ValueType::Object,
+            "formula" => ValueType::Formula,
+            "rollup" => ValueType::Rollup,
             _ => ValueType::Text,
         }
     }
@@ -74,8 +81,16 @@ impl ValueType {
             ValueType::Email => "email",
             ValueType::Phone => "phone",
             ValueType::Object => "object",
+            ValueType::Formula => "formula",
+            ValueType::Rollup => "rollup",
         }
     }
+
+    /// Whether values of this type are computed by the index rather than
+    /// stored — such properties are never written.
+    pub fn is_computed(self) -> bool {
+        matches!(self, ValueType::Formula | ValueType::Rollup)
+    }
 }
 
 /// A single stored scalar property value, mirroring the Loro primitive forms.
@@ -132,6 +147,8 @@ mod tests {
         assert_eq!(ValueType::parse("email"), ValueType::Email);
         assert_eq!(ValueType::parse("phone"), ValueType::Phone);
         assert_eq!(ValueType::parse("object"), ValueType::Object);
+        assert_eq!(ValueType::parse("formula"), ValueType::Formula);
+        assert_eq!(ValueType::parse("rollup"), ValueType::Rollup);
     }
 
     #[test]
@@ -159,6 +176,8 @@ mod tests {
             "email",
             "phone",
             "object",
+            "formula",
+            "rollup",
         ] {
             assert_eq!(ValueType::parse(s).as_str(), s);
         }
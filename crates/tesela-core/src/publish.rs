@@ -0,0 +1,998 @@
+//! Static site publishing — `tesela publish`.
+//!
+//! Where [`crate::export`] copies the mosaic as markdown, publishing renders
+//! a selected subset of it (pages carrying a tag, `public` by default, or
+//! the pages a DSL query matches) into a self-contained, linked HTML site:
+//!
+//! - one `<slug>.html` per page, blocks as nested lists, `[[wiki-links]]`
+//!   pointing at the linked page when it is published and left as plain
+//!   text when it isn't;
+//! - a "Linked from" section per page, from [`LinkGraph::get_backlinks`]
+//!   and limited to published pages;
+//! - `query::` blocks run against the index and rendered as result lists,
+//!   again limited to published pages;
+//! - `tags/index.html` plus one `tags/<tag>.html` per tag;
+//! - the attachments published pages reference, copied to `attachments/`;
+//! - `search-index.json` (`url`, `title`, `tags`, `text` per page) for a
+//!   client-side search box.
+//!
+//! Privacy: a page marked `private: true` (frontmatter or page property)
+//! or tagged `private` is never published, even when selected. A block
+//! with `private:: true` or `#private` is dropped together with its
+//! children. The properties the portable export strips (`_`-prefixed and
+//! Tesela-internal keys, see [`crate::export::markdown`]) never reach the
+//! HTML or the search index, and `query::` results inside a private block
+//! are dropped the same way.
+//!
+//! Each run renders into a staging directory next to `out_root` and then
+//! swaps it in, so pages no longer selected disappear. An existing
+//! `out_root` is only replaced when it is empty or an earlier run made it.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use pulldown_cmark::{html, Parser};
+use serde::Serialize;
+
+use crate::block::parse_blocks;
+use crate::error::{Result, TeselaError};
+use crate::export::markdown::{is_tesela_internal_key, parse_property_line};
+use crate::note::{Note, NoteId};
+use crate::note_tree::{markdown_body_fence_mask, parse_note};
+use crate::query::{parse_query, Kind};
+use crate::regex_cache::{HASH_TAG_RE, WIKI_LINK_RE};
+use crate::storage::markdown::sanitize_filename;
+use crate::traits::{link_graph::LinkGraph, note_store::NoteStore, search_index::SearchIndex};
+
+/// Which pages to publish.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PublishSelection {
+    /// Pages carrying this frontmatter tag.
+    Tag(String),
+    /// Pages a DSL query matches; a block row selects its page.
+    Query(String),
+}
+
+impl Default for PublishSelection {
+    fn default() -> Self {
+        PublishSelection::Tag("public".to_string())
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct PublishOptions {
+    pub selection: PublishSelection,
+    /// Shown in every page's header and `<title>`.
+    pub site_title: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct PublishOutcome {
+    pub page_count: usize,
+    pub tag_page_count: usize,
+    pub attachment_count: usize,
+    /// Selected pages left out because they are private.
+    pub private_pages_skipped: usize,
+    /// Private blocks (with their children) left out of published pages.
+    pub private_blocks_dropped: usize,
+    /// Private or internal properties stripped from published pages.
+    pub stripped_property_count: usize,
+}
+
+/// File an earlier run left in `out_root`, so the next may replace it.
+const SITE_MARKER: &str = ".tesela-site";
+
+/// Block properties that only steer the editor's display.
+const DISPLAY_KEYS: &[&str] = &["collapsed", "view"];
+
+/// Render the selected pages of the mosaic behind `store` / `index` into
+/// `out_root`, replacing what an earlier run published there. The index
+/// should be current (`tesela reindex`): backlinks and query blocks are
+/// read from it.
+pub async fn publish_site<S, I>(
+    store: &S,
+    index: &I,
+    out_root: &Path,
+    opts: &PublishOptions,
+) -> Result<PublishOutcome>
+where
+    S: NoteStore + ?Sized,
+    I: SearchIndex + LinkGraph + ?Sized,
+{
+    if out_root.exists()
+        && !out_root.join(SITE_MARKER).is_file()
+        && fs::read_dir(out_root)?.next().is_some()
+    {
+        return Err(TeselaError::Validation {
+            message: format!(
+                "{} is not empty and wasn't created by publish; choose a new or empty directory",
+                out_root.display()
+            ),
+        });
+    }
+    let staging = sibling(out_root, "publishing")?;
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    let outcome = match render_site(store, index, &staging, opts).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+    };
+    fs::write(staging.join(SITE_MARKER), "")?;
+    if out_root.exists() {
+        let previous = sibling(out_root, "previous")?;
+        if previous.exists() {
+            fs::remove_dir_all(&previous)?;
+        }
+        fs::rename(out_root, &previous)?;
+        fs::rename(&staging, out_root)?;
+        fs::remove_dir_all(&previous)?;
+    } else {
+        fs::rename(&staging, out_root)?;
+    }
+    Ok(outcome)
+}
+
+/// `.<name>.<suffix>` next to `out_root`, on the same filesystem so the
+/// swap is a rename.
+fn sibling(out_root: &Path, suffix: &str) -> Result<PathBuf> {
+    let absolute = std::path::absolute(out_root)?;
+    let (Some(parent), Some(name)) = (absolute.parent(), absolute.file_name()) else {
+        return Err(TeselaError::Validation {
+            message: format!("can't publish into {}", out_root.display()),
+        });
+    };
+    fs::create_dir_all(parent)?;
+    Ok(parent.join(format!(".{}.{suffix}", name.to_string_lossy())))
+}
+
+async fn render_site<S, I>(
+    store: &S,
+    index: &I,
+    out_root: &Path,
+    opts: &PublishOptions,
+) -> Result<PublishOutcome>
+where
+    S: NoteStore + ?Sized,
+    I: SearchIndex + LinkGraph + ?Sized,
+{
+    let mosaic_root = store.mosaic_root().await.to_path_buf();
+    let mut outcome = PublishOutcome::default();
+
+    let mut pages = Vec::new();
+    for note in select_pages(store, index, &opts.selection).await? {
+        if is_private_page(&note) {
+            outcome.private_pages_skipped += 1;
+        } else {
+            pages.push(note);
+        }
+    }
+    pages.sort_by_key(|n| (n.title.to_lowercase(), n.id.as_str().to_string()));
+    let site = Site::new(&pages);
+
+    fs::create_dir_all(out_root.join("tags"))?;
+    let mut tags: BTreeMap<String, BTreeSet<usize>> = BTreeMap::new();
+    let mut search = Vec::with_capacity(pages.len());
+    let mut attachments = BTreeSet::new();
+    for (i, note) in pages.iter().enumerate() {
+        let page = render_page(note, &site, index, &mut outcome).await?;
+        let backlinks = backlinks(note, &site, index).await?;
+        let mut body = format!("<h1>{}</h1>\n", escape(&note.title));
+        body.push_str(&page.html);
+        if !page.tags.is_empty() {
+            body.push_str("<p class=\"tags\">");
+            for tag in &page.tags {
+                body.push_str(&tag_link(tag, ""));
+                body.push(' ');
+            }
+            body.push_str("</p>\n");
+        }
+        if !backlinks.is_empty() {
+            body.push_str("<section class=\"backlinks\">\n<h2>Linked from</h2>\n<ul>\n");
+            for &j in &backlinks {
+                body.push_str(&format!("<li>{}</li>\n", page_link(&site.pages[j], "")));
+            }
+            body.push_str("</ul>\n</section>\n");
+        }
+        fs::write(
+            out_root.join(&site.pages[i].file),
+            shell(&opts.site_title, Some(&note.title), &body, ""),
+        )?;
+        for tag in &page.tags {
+            tags.entry(tag.clone()).or_default().insert(i);
+        }
+        search.push(SearchEntry {
+            url: site.pages[i].file.clone(),
+            title: note.title.clone(),
+            tags: page.tags.iter().cloned().collect(),
+            text: page.text,
+        });
+        attachments.extend(page.attachments);
+        outcome.page_count += 1;
+    }
+
+    let mut tag_index = String::from("<h1>Tags</h1>\n<ul>\n");
+    for (tag, members) in &tags {
+        let mut body = format!("<h1>#{}</h1>\n<ul>\n", escape(tag));
+        for &i in members {
+            body.push_str(&format!("<li>{}</li>\n", page_link(&site.pages[i], "../")));
+        }
+        body.push_str("</ul>\n");
+        fs::write(
+            out_root.join("tags").join(tag_file(tag)),
+            shell(&opts.site_title, Some(&format!("#{tag}")), &body, "../"),
+        )?;
+        tag_index.push_str(&format!(
+            "<li>{} ({})</li>\n",
+            tag_link(tag, "../"),
+            members.len()
+        ));
+        outcome.tag_page_count += 1;
+    }
+    tag_index.push_str("</ul>\n");
+    fs::write(
+        out_root.join("tags").join("index.html"),
+        shell(&opts.site_title, Some("Tags"), &tag_index, "../"),
+    )?;
+
+    let mut home = format!("<h1>{}</h1>\n<ul>\n", escape(&opts.site_title));
+    for page in &site.pages {
+        home.push_str(&format!("<li>{}</li>\n", page_link(page, "")));
+    }
+    home.push_str("</ul>\n");
+    fs::write(
+        out_root.join("index.html"),
+        shell(&opts.site_title, None, &home, ""),
+    )?;
+    fs::write(out_root.join("style.css"), STYLE)?;
+    fs::write(
+        out_root.join("search-index.json"),
+        serde_json::to_string(&search)?,
+    )?;
+
+    for rel in attachments {
+        let src = mosaic_root.join("attachments").join(&rel);
+        if !src.is_file() {
+            continue;
+        }
+        let dst = out_root.join("attachments").join(&rel);
+        if let Some(parent) = dst.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(&src, &dst)?;
+        outcome.attachment_count += 1;
+    }
+
+    Ok(outcome)
+}
+
+async fn select_pages<S, I>(store: &S, index: &I, selection: &PublishSelection) -> Result<Vec<Note>>
+where
+    S: NoteStore + ?Sized,
+    I: SearchIndex + ?Sized,
+{
+    match selection {
+        PublishSelection::Tag(tag) => store.list(Some(tag), usize::MAX, 0).await,
+        PublishSelection::Query(dsl) => {
+            let result = index.execute_query(&parse_query(dsl), None, None).await?;
+            let ids: BTreeSet<String> = result
+                .groups
+                .into_iter()
+                .flat_map(|g| g.items)
+                .map(|item| item.page_id)
+                .collect();
+            let mut notes = Vec::with_capacity(ids.len());
+            for id in ids {
+                if let Some(note) = store.get(&NoteId::new(id)).await? {
+                    notes.push(note);
+                }
+            }
+            Ok(notes)
+        }
+    }
+}
+
+fn is_private_page(note: &Note) -> bool {
+    let flagged = note
+        .metadata
+        .custom
+        .get("private")
+        .is_some_and(|v| v == &serde_json::Value::Bool(true) || v == "true");
+    let property = parse_note(&note.content)
+        .page_properties
+        .iter()
+        .any(|(k, v)| k == "private" && is_true(v));
+    flagged || property || note.metadata.tags.iter().any(|t| is_private_tag(t))
+}
+
+fn is_true(value: &str) -> bool {
+    value.trim().eq_ignore_ascii_case("true")
+}
+
+/// A block marked `private:: true` or tagged `#private`; it is dropped
+/// together with its children.
+fn is_private_block<'a>(mut props: impl Iterator<Item = (&'a str, &'a str)>, text: &str) -> bool {
+    props.any(|(k, v)| k == "private" && is_true(v))
+        || block_tags(text).iter().any(|t| is_private_tag(t))
+}
+
+fn is_private_tag(tag: &str) -> bool {
+    tag.eq_ignore_ascii_case("private")
+}
+
+/// Private properties never published: the portable export's internal
+/// keys, plus the `private` marker itself.
+fn is_private_key(key: &str) -> bool {
+    key == "private" || is_tesela_internal_key(key)
+}
+
+/// A published page's output name and how wiki-links reach it.
+struct PageRef {
+    title: String,
+    file: String,
+}
+
+struct Site {
+    pages: Vec<PageRef>,
+    /// Lowercased slug, title and aliases → index into `pages`.
+    by_name: HashMap<String, usize>,
+    /// Index ids (`note:line`) of blocks left out as private, children
+    /// included, so query results skip them too.
+    private_blocks: HashSet<String>,
+}
+
+impl Site {
+    fn new(notes: &[Note]) -> Self {
+        let mut by_name = HashMap::new();
+        let mut private_blocks = HashSet::new();
+        for note in notes {
+            let mut skip_below = None;
+            for block in parse_blocks(note.id.as_str(), &note.body) {
+                match skip_below {
+                    Some(indent) if block.indent_level > indent => {
+                        private_blocks.insert(block.id);
+                        continue;
+                    }
+                    _ => skip_below = None,
+                }
+                let props = block
+                    .properties
+                    .iter()
+                    .map(|(k, v)| (k.as_str(), v.as_str()));
+                if is_private_block(props, &block.raw_text) {
+                    skip_below = Some(block.indent_level);
+                    private_blocks.insert(block.id);
+                }
+            }
+        }
+        let pages = notes
+            .iter()
+            .enumerate()
+            .map(|(i, note)| {
+                let names = [note.id.as_str(), note.title.as_str()]
+                    .into_iter()
+                    .chain(note.metadata.aliases.iter().map(String::as_str));
+                for name in names {
+                    by_name.entry(name.to_lowercase()).or_insert(i);
+                }
+                PageRef {
+                    title: note.title.clone(),
+                    file: format!("{}.html", note.id.as_str().replace('/', "-")),
+                }
+            })
+            .collect();
+        Site {
+            pages,
+            by_name,
+            private_blocks,
+        }
+    }
+
+    /// The published page a wiki-link target (or query row's page id)
+    /// names, if any.
+    fn resolve(&self, target: &str) -> Option<usize> {
+        let target = target.trim().to_lowercase();
+        self.by_name
+            .get(&target)
+            .or_else(|| self.by_name.get(&sanitize_filename(&target)))
+            .copied()
+    }
+}
+
+struct RenderedPage {
+    html: String,
+    /// Plain text for the search index.
+    text: String,
+    tags: BTreeSet<String>,
+    /// Paths under `attachments/` the page references.
+    attachments: BTreeSet<String>,
+}
+
+async fn render_page<I>(
+    note: &Note,
+    site: &Site,
+    index: &I,
+    outcome: &mut PublishOutcome,
+) -> Result<RenderedPage>
+where
+    I: SearchIndex + ?Sized,
+{
+    let tree = parse_note(&note.content);
+    let mut page = RenderedPage {
+        html: String::new(),
+        text: String::new(),
+        tags: note
+            .metadata
+            .tags
+            .iter()
+            .map(|t| t.to_lowercase())
+            .collect(),
+        attachments: BTreeSet::new(),
+    };
+
+    let mut page_props = Vec::new();
+    for (key, value) in &tree.page_properties {
+        if key == "query" {
+            page.html.push_str(&render_query(value, site, index).await);
+        } else if is_private_key(key) {
+            outcome.stripped_property_count += 1;
+        } else if !DISPLAY_KEYS.contains(&key.as_str()) {
+            page_props.push((key.as_str(), value.as_str()));
+        }
+    }
+    page.html
+        .push_str(&render_props(&page_props, site, &mut page.attachments));
+
+    let mut depth = 0usize;
+    let mut skip_below: Option<u16> = None;
+    for block in &tree.blocks {
+        match skip_below {
+            Some(indent) if block.indent > indent => continue,
+            _ => skip_below = None,
+        }
+        let mut prose = Vec::new();
+        let mut props = Vec::new();
+        for line in block.text.lines() {
+            match parse_property_line(line) {
+                Some((key, value)) => props.push((key, value)),
+                None => prose.push(line),
+            }
+        }
+        props.extend(
+            block
+                .properties
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str())),
+        );
+        let prose = prose.join("\n");
+        if is_private_block(props.iter().copied(), &prose) {
+            outcome.private_blocks_dropped += 1;
+            skip_below = Some(block.indent);
+            continue;
+        }
+
+        // Nested lists: `depth` open `<ul>`s, the innermost `<li>` open.
+        let target = (block.indent as usize).min(depth) + 1;
+        if target > depth {
+            page.html.push_str("<ul>\n");
+            depth += 1;
+        } else {
+            page.html.push_str("</li>\n");
+            while depth > target {
+                page.html.push_str("</ul></li>\n");
+                depth -= 1;
+            }
+        }
+        page.html.push_str("<li>");
+        page.html
+            .push_str(&render_markdown(&prose, site, &mut page.attachments));
+        page.tags.extend(block_tags(&prose));
+        page.text.push_str(&plain_text(&prose));
+        page.text.push('\n');
+
+        let mut shown = Vec::new();
+        for (key, value) in props {
+            if key == "query" {
+                page.html.push_str(&render_query(value, site, index).await);
+            } else if is_private_key(key) {
+                outcome.stripped_property_count += 1;
+            } else if !DISPLAY_KEYS.contains(&key) {
+                shown.push((key, value));
+            }
+        }
+        page.html
+            .push_str(&render_props(&shown, site, &mut page.attachments));
+    }
+    if depth > 0 {
+        page.html.push_str("</li>\n");
+        while depth > 1 {
+            page.html.push_str("</ul></li>\n");
+            depth -= 1;
+        }
+        page.html.push_str("</ul>\n");
+    }
+    page.tags.retain(|t| !is_private_tag(t));
+    Ok(page)
+}
+
+fn render_props(props: &[(&str, &str)], site: &Site, attachments: &mut BTreeSet<String>) -> String {
+    if props.is_empty() {
+        return String::new();
+    }
+    let mut out = String::from("<dl class=\"props\">");
+    for (key, value) in props {
+        out.push_str(&format!(
+            "<dt>{}</dt><dd>{}</dd>",
+            escape(key),
+            render_markdown(value, site, attachments)
+        ));
+    }
+    out.push_str("</dl>\n");
+    out
+}
+
+/// A `query::` block's results, limited to published pages.
+async fn render_query<I>(dsl: &str, site: &Site, index: &I) -> String
+where
+    I: SearchIndex + ?Sized,
+{
+    let mut out = format!(
+        "<div class=\"query\"><p class=\"query-dsl\"><code>{}</code></p>",
+        escape(dsl)
+    );
+    let items = match index.execute_query(&parse_query(dsl), None, None).await {
+        Ok(result) => result.groups.into_iter().flat_map(|g| g.items),
+        Err(e) => {
+            tracing::warn!("publish: query {dsl:?} failed: {e}");
+            out.push_str("<p class=\"empty\">Query failed.</p></div>\n");
+            return out;
+        }
+    };
+    let mut rows = String::new();
+    for item in items {
+        let Some(i) = site.resolve(&item.page_id) else {
+            continue;
+        };
+        if item
+            .block_id
+            .as_ref()
+            .is_some_and(|id| site.private_blocks.contains(id))
+        {
+            continue;
+        }
+        let page = &site.pages[i];
+        match item.kind {
+            Kind::Page => rows.push_str(&format!("<li>{}</li>\n", page_link(page, ""))),
+            _ => rows.push_str(&format!(
+                "<li><a href=\"{}\">{}</a> <span class=\"source\">— {}</span></li>\n",
+                escape(&page.file),
+                escape(&plain_text(&item.text)),
+                escape(&page.title)
+            )),
+        }
+    }
+    if rows.is_empty() {
+        out.push_str("<p class=\"empty\">No results.</p>");
+    } else {
+        out.push_str("<ul>\n");
+        out.push_str(&rows);
+        out.push_str("</ul>");
+    }
+    out.push_str("</div>\n");
+    out
+}
+
+/// Published pages (other than `note` itself) whose links reach `note`
+/// under its slug, title or an alias.
+async fn backlinks<I>(note: &Note, site: &Site, index: &I) -> Result<Vec<usize>>
+where
+    I: LinkGraph + ?Sized,
+{
+    let this = site.resolve(note.id.as_str());
+    let names = [note.id.as_str(), note.title.as_str()]
+        .into_iter()
+        .chain(note.metadata.aliases.iter().map(String::as_str));
+    let mut sources = BTreeSet::new();
+    for name in names {
+        for link in index.get_backlinks(&NoteId::new(name)).await? {
+            // `get_backlinks` reports the linking note in `target`.
+            if let Some(i) = site.resolve(&link.target).filter(|i| Some(*i) != this) {
+                sources.insert(i);
+            }
+        }
+    }
+    Ok(sources.into_iter().collect())
+}
+
+/// Block markdown to HTML: wiki-links and `#tags` become site links (a
+/// link to an unpublished page is left as its label), attachment paths
+/// point at the copied files (and are collected into `attachments`).
+fn render_markdown(text: &str, site: &Site, attachments: &mut BTreeSet<String>) -> String {
+    let linked = rewrite_unfenced(text, |segment| {
+        let segment = WIKI_LINK_RE.replace_all(segment, |cap: &regex::Captures| {
+            let label = cap.get(2).map_or(&cap[1], |m| m.as_str()).trim();
+            match site.resolve(&cap[1]) {
+                Some(i) => format!("[{label}]({})", site.pages[i].file),
+                None => label.to_string(),
+            }
+        });
+        let segment = HASH_TAG_RE.replace_all(&segment, |cap: &regex::Captures| {
+            let whole = cap.get(0).expect("tag regex has whole match");
+            let at_word_start = whole.start() == 0
+                || segment[..whole.start()]
+                    .chars()
+                    .next_back()
+                    .is_some_and(char::is_whitespace);
+            if at_word_start {
+                let tag = cap[1].to_lowercase();
+                format!("[#{}](tags/{})", &cap[1], tag_file(&tag))
+            } else {
+                whole.as_str().to_string()
+            }
+        });
+        ATTACHMENT_RE
+            .replace_all(&segment, |cap: &regex::Captures| {
+                let rel = &cap[1];
+                let safe = Path::new(rel)
+                    .components()
+                    .all(|c| matches!(c, Component::Normal(_)));
+                if safe {
+                    attachments.insert(rel.to_string());
+                }
+                format!("attachments/{rel}")
+            })
+            .into_owned()
+    });
+    let mut out = String::new();
+    html::push_html(&mut out, Parser::new(&linked));
+    // A one-paragraph block renders inline in its `<li>`.
+    match out
+        .strip_prefix("<p>")
+        .and_then(|s| s.strip_suffix("</p>\n"))
+    {
+        Some(inner) if !inner.contains("<p>") => inner.to_string(),
+        _ => out,
+    }
+}
+
+static ATTACHMENT_RE: std::sync::LazyLock<regex::Regex> = std::sync::LazyLock::new(|| {
+    regex::Regex::new(r#"(?:\.\./)?attachments/([^\s)"'<>\]]+)"#).unwrap()
+});
+
+/// Apply `f` to the parts of `text` outside fenced code.
+fn rewrite_unfenced(text: &str, mut f: impl FnMut(&str) -> String) -> String {
+    let fenced = markdown_body_fence_mask(text);
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let start = line.as_ptr() as usize - text.as_ptr() as usize;
+        if fenced.overlaps(start..start + line.len()) {
+            out.push_str(line);
+        } else {
+            out.push_str(&f(line));
+        }
+    }
+    out
+}
+
+fn block_tags(text: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    rewrite_unfenced(text, |segment| {
+        for cap in HASH_TAG_RE.captures_iter(segment) {
+            let start = cap.get(0).expect("tag regex has whole match").start();
+            if start == 0
+                || segment[..start]
+                    .chars()
+                    .next_back()
+                    .is_some_and(char::is_whitespace)
+            {
+                tags.push(cap[1].to_lowercase());
+            }
+        }
+        String::new()
+    });
+    tags
+}
+
+/// Text for the search index: wiki-links reduced to their labels.
+fn plain_text(text: &str) -> String {
+    WIKI_LINK_RE
+        .replace_all(text, |cap: &regex::Captures| {
+            cap.get(2)
+                .map_or(&cap[1], |m| m.as_str())
+                .trim()
+                .to_string()
+        })
+        .into_owned()
+}
+
+fn tag_file(tag: &str) -> String {
+    format!("{}.html", tag.replace('/', "-"))
+}
+
+fn page_link(page: &PageRef, prefix: &str) -> String {
+    format!(
+        "<a href=\"{prefix}{}\">{}</a>",
+        escape(&page.file),
+        escape(&page.title)
+    )
+}
+
+fn tag_link(tag: &str, prefix: &str) -> String {
+    format!(
+        "<a class=\"tag\" href=\"{prefix}tags/{}\">#{}</a>",
+        escape(&tag_file(tag)),
+        escape(tag)
+    )
+}
+
+#[derive(Serialize)]
+struct SearchEntry {
+    url: String,
+    title: String,
+    tags: Vec<String>,
+    text: String,
+}
+
+fn shell(site_title: &str, page_title: Option<&str>, body: &str, prefix: &str) -> String {
+    let title = match page_title {
+        Some(page) => format!("{} — {}", escape(page), escape(site_title)),
+        None => escape(site_title),
+    };
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n<link rel=\"stylesheet\" href=\"{prefix}style.css\">\n</head>\n\
+         <body>\n<header><a href=\"{prefix}index.html\">{}</a> · \
+         <a href=\"{prefix}tags/index.html\">Tags</a></header>\n<main>\n{body}</main>\n\
+         </body>\n</html>\n",
+        escape(site_title)
+    )
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+const STYLE: &str = "body { font: 16px/1.6 system-ui, sans-serif; max-width: 46rem; margin: 2rem auto; padding: 0 1rem; color: #222; }
+header { margin-bottom: 2rem; color: #666; }
+a { color: #3557c5; }
+a.tag { color: #6a5acd; text-decoration: none; }
+dl.props { display: grid; grid-template-columns: max-content 1fr; gap: 0 .75rem; margin: .25rem 0; font-size: .9em; color: #555; }
+dl.props dd { margin: 0; }
+.query { border-left: 3px solid #ddd; padding-left: .75rem; }
+.query-dsl, .source, .empty { color: #777; font-size: .9em; }
+.backlinks { margin-top: 3rem; border-top: 1px solid #eee; }
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::SqliteIndex;
+    use crate::link::extract_wiki_links;
+    use crate::storage::filesystem::FsNoteStore;
+
+    async fn mosaic(dir: &Path, notes: &[(&str, &str)]) -> (FsNoteStore, SqliteIndex) {
+        fs::create_dir_all(dir.join("notes")).unwrap();
+        fs::create_dir_all(dir.join("attachments")).unwrap();
+        fs::write(dir.join("attachments/map.png"), b"png").unwrap();
+        fs::write(dir.join("attachments/secret.png"), b"png").unwrap();
+        for (slug, content) in notes {
+            fs::write(dir.join("notes").join(format!("{slug}.md")), content).unwrap();
+        }
+        let store = FsNoteStore::open(dir.to_path_buf()).unwrap();
+        let index = SqliteIndex::open_in_memory().await.unwrap();
+        for note in store.list(None, usize::MAX, 0).await.unwrap() {
+            index.upsert_note(&note).await.unwrap();
+            index
+                .update_links(&note.id, &extract_wiki_links(&note.content))
+                .await
+                .unwrap();
+        }
+        (store, index)
+    }
+
+    #[tokio::test]
+    async fn publishes_tagged_pages_linked_and_without_private_content() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (store, index) = mosaic(
+            tmp.path(),
+            &[
+                (
+                    "trip",
+                    "---\ntitle: Trip\ntags: [public]\n---\n\
+                     - Plan with [[Packing]] and [[Diary]] #travel\n  - ![map](../attachments/map.png)\n\
+                     - budget\n  _cost:: 1200\n  status:: draft\n\
+                     - secret plans\n  private:: true\n  - ![x](../attachments/secret.png)\n",
+                ),
+                (
+                    "packing",
+                    "---\ntitle: Packing\ntags: [public]\n---\n- socks for [[Trip]]\n",
+                ),
+                ("diary", "---\ntitle: Diary\n---\n- about [[Trip]]\n"),
+                (
+                    "hidden",
+                    "---\ntitle: Hidden\ntags: [public, private]\n---\n- [[Trip]]\n",
+                ),
+            ],
+        )
+        .await;
+        let out = tmp.path().join("site");
+        let outcome = publish_site(
+            &store,
+            &index,
+            &out,
+            &PublishOptions {
+                site_title: "My notes".into(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(outcome.page_count, 2);
+        assert_eq!(outcome.private_pages_skipped, 1);
+        assert_eq!(outcome.private_blocks_dropped, 1);
+        assert_eq!(outcome.stripped_property_count, 1);
+        assert_eq!(outcome.attachment_count, 1);
+
+        let trip = fs::read_to_string(out.join("trip.html")).unwrap();
+        assert!(
+            trip.contains("<a href=\"packing.html\">Packing</a>"),
+            "{trip}"
+        );
+        assert!(trip.contains("and Diary"), "unpublished link stays text");
+        assert!(trip.contains("href=\"tags/travel.html\""));
+        assert!(trip.contains("src=\"attachments/map.png\""));
+        assert!(trip.contains("<dt>status</dt>"));
+        assert!(!trip.contains("1200") && !trip.contains("secret"), "{trip}");
+        assert!(trip.contains("Linked from") && trip.contains("packing.html\">Packing"));
+        assert!(!trip.contains("diary.html") && !trip.contains("hidden.html"));
+
+        assert!(!out.join("hidden.html").exists() && !out.join("diary.html").exists());
+        assert!(out.join("attachments/map.png").exists());
+        assert!(!out.join("attachments/secret.png").exists());
+        assert!(fs::read_to_string(out.join("tags/travel.html"))
+            .unwrap()
+            .contains("../trip.html"));
+        let search: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(out.join("search-index.json")).unwrap())
+                .unwrap();
+        assert_eq!(search.as_array().unwrap().len(), 2);
+        assert!(!search.to_string().contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn renders_query_blocks_over_published_pages_only() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (store, index) = mosaic(
+            tmp.path(),
+            &[
+                (
+                    "tasks",
+                    "---\ntitle: Tasks\ntags: [public]\n---\n- Open work\n  query:: status = todo\n  view:: table\n",
+                ),
+                (
+                    "garden",
+                    "---\ntitle: Garden\ntags: [public]\n---\n- water plants\n  status:: todo\n",
+                ),
+                ("taxes", "---\ntitle: Taxes\nprivate: true\n---\n- file taxes\n  status:: todo\n"),
+            ],
+        )
+        .await;
+        let out = tmp.path().join("site");
+        let outcome = publish_site(
+            &store,
+            &index,
+            &out,
+            &PublishOptions {
+                selection: PublishSelection::Query("status = todo".into()),
+                site_title: "Todo".into(),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(outcome.private_pages_skipped, 1);
+        assert!(out.join("garden.html").exists());
+        assert!(!out.join("tasks.html").exists() && !out.join("taxes.html").exists());
+
+        publish_site(&store, &index, &out, &PublishOptions::default())
+            .await
+            .unwrap();
+        let tasks = fs::read_to_string(out.join("tasks.html")).unwrap();
+        assert!(tasks.contains("water plants"), "{tasks}");
+        assert!(!tasks.contains("file taxes") && !tasks.contains("<dt>view</dt>"));
+    }
+
+    #[tokio::test]
+    async fn query_results_skip_blocks_under_a_private_parent() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (store, index) = mosaic(
+            tmp.path(),
+            &[
+                (
+                    "tasks",
+                    "---\ntitle: Tasks\ntags: [public]\n---\n- Open work\n  query:: status = todo\n",
+                ),
+                (
+                    "garden",
+                    "---\ntitle: Garden\ntags: [public]\n---\n- water plants\n  status:: todo\n\
+                     - Family #private\n  - call the lawyer\n    status:: todo\n\
+                     - Health\n  private:: true\n  - book checkup\n    status:: todo\n",
+                ),
+            ],
+        )
+        .await;
+        let out = tmp.path().join("site");
+        publish_site(&store, &index, &out, &PublishOptions::default())
+            .await
+            .unwrap();
+        let tasks = fs::read_to_string(out.join("tasks.html")).unwrap();
+        assert!(tasks.contains("water plants"), "{tasks}");
+        assert!(
+            !tasks.contains("lawyer") && !tasks.contains("checkup"),
+            "{tasks}"
+        );
+    }
+
+    #[tokio::test]
+    async fn republishing_replaces_the_previous_site() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (store, index) = mosaic(
+            tmp.path(),
+            &[
+                (
+                    "garden",
+                    "---\ntitle: Garden\ntags: [public]\n---\n- water plants\n",
+                ),
+                (
+                    "notes",
+                    "---\ntitle: Notes\ntags: [public, draft]\n---\n- scratch\n",
+                ),
+            ],
+        )
+        .await;
+        let out = tmp.path().join("site");
+        let publish = |tag: &str| PublishOptions {
+            selection: PublishSelection::Tag(tag.into()),
+            site_title: "Site".into(),
+        };
+        publish_site(&store, &index, &out, &publish("public"))
+            .await
+            .unwrap();
+        assert!(out.join("notes.html").exists() && out.join("tags/draft.html").exists());
+        publish_site(&store, &index, &out, &publish("draft"))
+            .await
+            .unwrap();
+        assert!(out.join("notes.html").exists());
+        assert!(!out.join("garden.html").exists());
+        let leftovers: Vec<_> = fs::read_dir(tmp.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(".site."))
+            .collect();
+        assert!(leftovers.is_empty());
+
+        // A directory publish didn't create is never replaced.
+        let foreign = tmp.path().join("foreign");
+        fs::create_dir_all(&foreign).unwrap();
+        fs::write(foreign.join("keep.txt"), "mine").unwrap();
+        assert!(publish_site(&store, &index, &foreign, &publish("public"))
+            .await
+            .is_err());
+        assert!(foreign.join("keep.txt").exists());
+    }
+}
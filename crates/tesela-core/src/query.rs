@@ -29,6 +29,18 @@
 //! - `has:foo` (op `=`) — block has property `foo` regardless of value.
 //! - `has:foo` (op `!=`) — block lacks property `foo`. Equivalently `-has:foo`.
 //! - `tag:foo` — block's resolved tag chain (direct + inherited) includes `foo`.
+//! - `is:orphan` — the page (or the block's page) has no links in or out.
+//! - `links-to:[[X]]` — the page links or relates to page `X`; for a block,
+//!   its own `[[...]]` links or node-typed properties do. Both need the
+//!   link graph, which `SqliteIndex` loads into [`QueryContext::graph`].
+//! - `project.status:active` — follow the block's Node-typed `project`
+//!   relation and test `status:active` on the page it points at. Chains
+//!   (`project.owner.team:x`) hop once per dot; no related page, no match.
+//! - `has-backlink-from:tag:Meeting` — some block or page whose Node-typed
+//!   property points at this page (or the block's page) matches the
+//!   sub-query `tag:Meeting`; quote it for more than one predicate. Both
+//!   read [`QueryContext::relations`], loaded by `SqliteIndex` from its
+//!   relation edges.
 
 use crate::block::ParsedBlock;
 use crate::property::ValueType;
@@ -209,6 +221,92 @@ pub struct QueryPage {
 pub struct QueryContext {
     #[serde(default)]
     pub pages: Vec<QueryPage>,
+    /// Resolved page graph for `is:orphan` / `links-to:`. Filled in by the
+    /// index when the query uses either; never sent over the wire.
+    #[serde(skip)]
+    pub graph: Option<std::sync::Arc<crate::graph::LinkGraphSnapshot>>,
+    /// Relation edges for dotted traversal and `has-backlink-from:`. Same
+    /// lifecycle as `graph`.
+    #[serde(skip)]
+    pub relations: Option<std::sync::Arc<RelationIndex>>,
+}
+
+/// Relation edges with the rows at both ends, for dotted traversal
+/// (`project.status:active`) and `has-backlink-from:`. Rows are blocks as
+/// the matcher sees them; a page is its synthetic page-kind block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelationIndex {
+    /// Page-kind row of every page with a `PageId`.
+    pages: HashMap<crate::PageId, ParsedBlock>,
+    /// Note id → `PageId`.
+    page_ids: HashMap<String, crate::PageId>,
+    /// (source, lowercased property) → target pages. The source is the
+    /// block's bid, or the note id for a page-level property.
+    targets: HashMap<(String, String), Vec<crate::PageId>>,
+    /// Target page → (lowercased property, row) for each relation pointing
+    /// at it.
+    sources: HashMap<crate::PageId, Vec<(String, ParsedBlock)>>,
+}
+
+impl RelationIndex {
+    /// Register a page whose page-kind row is `page`.
+    pub fn add_page(&mut self, page_id: crate::PageId, page: ParsedBlock) {
+        self.page_ids.insert(page.note_id.clone(), page_id);
+        self.pages.insert(page_id, page);
+    }
+
+    /// Register `edge`, whose source row is `source` — the block carrying
+    /// the property, or the page-kind row for a page-level one.
+    pub fn add_edge(&mut self, edge: &crate::RelationEdge, source: ParsedBlock) {
+        let from = edge
+            .source_block_id
+            .clone()
+            .unwrap_or_else(|| edge.source_note_id.clone());
+        let key = edge.property_key.to_ascii_lowercase();
+        self.targets
+            .entry((from, key.clone()))
+            .or_default()
+            .push(edge.target_page_id);
+        self.sources
+            .entry(edge.target_page_id)
+            .or_default()
+            .push((key, source));
+    }
+
+    /// Pages `row`'s `property` relation points at.
+    fn related_pages<'a>(
+        &'a self,
+        row: &ParsedBlock,
+        property: &str,
+    ) -> impl Iterator<Item = &'a ParsedBlock> {
+        let from = if row.id == row.note_id {
+            Some(row.note_id.clone())
+        } else {
+            row.bid.clone()
+        };
+        from.and_then(|from| self.targets.get(&(from, property.to_ascii_lowercase())))
+            .into_iter()
+            .flatten()
+            .filter_map(|page_id| self.pages.get(page_id))
+    }
+
+    /// Rows whose relations point at `row`'s page — only through
+    /// `property` when given.
+    pub fn backlinks<'a>(
+        &'a self,
+        row: &ParsedBlock,
+        property: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a ParsedBlock> {
+        self.page_ids
+            .get(&row.note_id)
+            .and_then(|page_id| self.sources.get(page_id))
+            .into_iter()
+            .flatten()
+            .filter(move |(key, _)| {
+                property.is_none_or(|property| key.eq_ignore_ascii_case(property))
+            })
+            .map(|(_, source)| source)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -585,7 +683,7 @@ fn tokenize(input: &str) -> Vec<Spanned> {
 }
 
 fn is_word_char(b: u8) -> bool {
-    b.is_ascii_alphanumeric() || b == b'_' || b == b'-'
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'-' || b == b'.'
 }
 
 // ────────────────────────────────────────────────────────────────────
@@ -1382,7 +1480,7 @@ fn apply_op(actual: &str, op: QueryOp, expected: &str) -> bool {
 /// parity — only `Number` / `Date|DateTime` / `Checkbox` differ from the plain
 /// string bucket. Crucially the string bucket does NOT promote numeric-looking
 /// strings, so a `select` storing `"10"` sorts lexicographically.
-fn compare_typed(a: &str, b: &str, vt: ValueType) -> std::cmp::Ordering {
+pub(crate) fn compare_typed(a: &str, b: &str, vt: ValueType) -> std::cmp::Ordering {
     use std::cmp::Ordering;
     match vt {
         // numeric: both must parse, else fall back to case-folded string
@@ -1409,6 +1507,9 @@ fn compare_typed(a: &str, b: &str, vt: ValueType) -> std::cmp::Ordering {
                 a.to_ascii_lowercase().cmp(&b.to_ascii_lowercase())
             }
         }
+        // computed: a formula yields a number or a date, a rollup a number —
+        // the untyped heuristic orders both.
+        ValueType::Formula | ValueType::Rollup => compare(a, b),
         // string bucket: Text / Url / Select / MultiSelect / Node / Email /
         // Phone / Object — case-folded string compare, NO numeric promotion.
         _ => a.to_ascii_lowercase().cmp(&b.to_ascii_lowercase()),
@@ -1593,6 +1694,18 @@ fn pred_matches_with_context(
     context: &QueryContext,
     diagnostics: &mut Vec<String>,
 ) -> ContextEvaluation {
+    if let Some(graph) = &context.graph {
+        if let Some(evaluation) = graph_pred_matches(block, pred, types, graph, diagnostics) {
+            return evaluation;
+        }
+    }
+    if let Some(relations) = &context.relations {
+        if let Some(evaluation) =
+            relation_pred_matches(block, pred, types, context, relations, diagnostics)
+        {
+            return evaluation;
+        }
+    }
     let key = match pred {
         Predicate::Cmp { key, .. } | Predicate::In { key, .. } => key,
     };
@@ -1664,6 +1777,191 @@ fn pred_matches_with_context(
     }
 }
 
+/// Whether `query` uses a predicate that needs [`QueryContext::graph`].
+pub fn uses_link_graph(query: &ParsedQuery) -> bool {
+    fn walk(expr: &BoolExpr) -> bool {
+        match expr {
+            BoolExpr::And { args } | BoolExpr::Or { args } => args.iter().any(walk),
+            BoolExpr::Not { arg } => walk(arg),
+            BoolExpr::Atom {
+                pred: Predicate::Cmp { key, value, .. },
+            } => is_graph_predicate(key, std::slice::from_ref(value)),
+            BoolExpr::Atom {
+                pred: Predicate::In { key, values, .. },
+            } => is_graph_predicate(key, values),
+        }
+    }
+    walk(&query.expr)
+}
+
+fn is_graph_predicate(key: &str, values: &[String]) -> bool {
+    key.eq_ignore_ascii_case("links-to")
+        || (key.eq_ignore_ascii_case("is")
+            && values.iter().any(|v| v.eq_ignore_ascii_case("orphan")))
+}
+
+/// `is:orphan` and `links-to:` against the link graph; `None` for every
+/// other predicate. A page-kind row is the synthetic page block, whose id
+/// is the note id itself.
+fn graph_pred_matches(
+    block: &ParsedBlock,
+    pred: &Predicate,
+    types: &HashMap<String, ValueType>,
+    graph: &crate::graph::LinkGraphSnapshot,
+    diagnostics: &mut Vec<String>,
+) -> Option<ContextEvaluation> {
+    let (key, values, negated) = match pred {
+        Predicate::Cmp {
+            key,
+            op: op @ (QueryOp::Eq | QueryOp::Ne),
+            value,
+        } => (key, std::slice::from_ref(value), *op == QueryOp::Ne),
+        Predicate::In {
+            key,
+            values,
+            negated,
+        } => (key, values.as_slice(), *negated),
+        Predicate::Cmp { .. } => return None,
+    };
+    if !is_graph_predicate(key, values) {
+        return None;
+    }
+    let page = graph.resolve(&block.note_id);
+    let matched = if key.eq_ignore_ascii_case("is") {
+        values
+            .iter()
+            .any(|value| match value.to_ascii_lowercase().as_str() {
+                "orphan" => page.is_some_and(|page| graph.is_orphan(page)),
+                "heading" => is_heading_text(&block.text),
+                _ => false,
+            })
+    } else {
+        let mut targets = Vec::with_capacity(values.len());
+        for value in values {
+            match graph.resolve(value) {
+                Some(target) => targets.push(target),
+                None => {
+                    diagnostics.push(format!("unresolved links-to page {value:?}"));
+                    return Some(ContextEvaluation {
+                        matched: false,
+                        valid: false,
+                    });
+                }
+            }
+        }
+        if block.id == block.note_id {
+            page.is_some_and(|page| targets.iter().any(|&t| graph.links_to(page, t)))
+        } else {
+            targets.iter().any(|&target| {
+                let target_id = graph.page(target).page_id;
+                crate::link::extract_wiki_links_from_body(&block.raw_text)
+                    .iter()
+                    .any(|link| graph.resolve(&link.target) == Some(target))
+                    || block.properties.iter().any(|(key, value)| {
+                        types.get(&key.to_ascii_lowercase()) == Some(&ValueType::Node)
+                            && target_id.is_some()
+                            && crate::PageId::parse(value) == target_id
+                    })
+            })
+        }
+    };
+    Some(ContextEvaluation {
+        matched: matched != negated,
+        valid: true,
+    })
+}
+
+/// Whether `query` uses a predicate that needs [`QueryContext::relations`].
+pub fn uses_relations(query: &ParsedQuery) -> bool {
+    fn walk(expr: &BoolExpr) -> bool {
+        match expr {
+            BoolExpr::And { args } | BoolExpr::Or { args } => args.iter().any(walk),
+            BoolExpr::Not { arg } => walk(arg),
+            BoolExpr::Atom {
+                pred: Predicate::Cmp { key, .. } | Predicate::In { key, .. },
+            } => is_relation_predicate(key),
+        }
+    }
+    walk(&query.expr)
+}
+
+fn is_relation_predicate(key: &str) -> bool {
+    key.eq_ignore_ascii_case("has-backlink-from") || key.contains('.')
+}
+
+/// Dotted traversal and `has-backlink-from:` against the relation index;
+/// `None` for every other predicate.
+fn relation_pred_matches(
+    block: &ParsedBlock,
+    pred: &Predicate,
+    types: &HashMap<String, ValueType>,
+    context: &QueryContext,
+    relations: &RelationIndex,
+    diagnostics: &mut Vec<String>,
+) -> Option<ContextEvaluation> {
+    let mut matched = false;
+    let mut valid = true;
+    let key = match pred {
+        Predicate::Cmp { key, .. } | Predicate::In { key, .. } => key,
+    };
+    if key.eq_ignore_ascii_case("has-backlink-from") {
+        let (values, negated) = match pred {
+            Predicate::Cmp {
+                op: op @ (QueryOp::Eq | QueryOp::Ne),
+                value,
+                ..
+            } => (std::slice::from_ref(value), *op == QueryOp::Ne),
+            Predicate::In {
+                values, negated, ..
+            } => (values.as_slice(), *negated),
+            Predicate::Cmp { .. } => return None,
+        };
+        let sources: Vec<_> = relations.backlinks(block, None).collect();
+        for value in values {
+            let sub = parse_query(value);
+            diagnostics.extend(sub.diagnostics.iter().cloned());
+            for &source in &sources {
+                let evaluation =
+                    eval_expr_with_context(source, &sub.expr, types, context, diagnostics);
+                matched |= evaluation.matched;
+                valid &= evaluation.valid;
+            }
+        }
+        return Some(ContextEvaluation {
+            matched: valid && matched != negated,
+            valid,
+        });
+    }
+
+    let (property, rest) = key.split_once('.')?;
+    if property.is_empty() || rest.is_empty() {
+        return None;
+    }
+    let hop = match pred {
+        Predicate::Cmp { op, value, .. } => Predicate::Cmp {
+            key: rest.to_string(),
+            op: *op,
+            value: value.clone(),
+        },
+        Predicate::In {
+            values, negated, ..
+        } => Predicate::In {
+            key: rest.to_string(),
+            values: values.clone(),
+            negated: *negated,
+        },
+    };
+    for page in relations.related_pages(block, property) {
+        let evaluation = pred_matches_with_context(page, &hop, types, context, diagnostics);
+        matched |= evaluation.matched;
+        valid &= evaluation.valid;
+    }
+    Some(ContextEvaluation {
+        matched: valid && matched,
+        valid,
+    })
+}
+
 /// Walk the `BoolExpr` tree, short-circuiting AND/OR. Empty `And` matches
 /// everything (the identity); empty `Or` matches nothing.
 fn eval_expr(block: &ParsedBlock, expr: &BoolExpr, types: &HashMap<String, ValueType>) -> bool {
@@ -1965,6 +2263,7 @@ mod tests {
                 aliases: vec!["My graph".into()],
                 deleted: false,
             }],
+            ..Default::default()
         };
         let types = types1("project", ValueType::Node);
         let target_string = target.to_string();
@@ -2003,6 +2302,7 @@ mod tests {
                 aliases: vec![],
                 deleted: true,
             }],
+            ..Default::default()
         };
         let raw_deleted = block_matches_typed_with_context(
             &block,
@@ -2040,6 +2340,7 @@ mod tests {
                 aliases: Vec::new(),
                 deleted: false,
             }],
+            ..Default::default()
         };
         let types = types1("project", ValueType::Node);
         let block = block_with(vec![], &[("project", &target.to_string())]);
@@ -2064,6 +2365,7 @@ mod tests {
                 aliases: Vec::new(),
                 deleted: false,
             }],
+            ..Default::default()
         };
         let types = types1("project", ValueType::Node);
         let block = block_with(vec![], &[]);
@@ -3343,4 +3645,66 @@ mod tests {
             &q
         ));
     }
+
+    #[test]
+    fn dotted_keys_and_decimals_tokenize_as_one_word() {
+        let q = parse_query("project.status:active priority:<2.5");
+        assert_eq!(
+            q.filters,
+            vec![
+                QueryFilter {
+                    key: "project.status".into(),
+                    op: QueryOp::Eq,
+                    value: "active".into(),
+                },
+                QueryFilter {
+                    key: "priority".into(),
+                    op: QueryOp::Lt,
+                    value: "2.5".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn relation_predicates_hop_forward_and_back_through_the_relation_index() {
+        let project = crate::PageId::from_legacy_doc_id(&[7; 16]);
+        let mut page = block_with(vec![], &[("status", "active")]);
+        page.id = "apollo".into();
+        page.note_id = "apollo".into();
+        let mut task = block_with(vec!["Task"], &[("project", "")]);
+        task.bid = Some("task-bid".into());
+        let mut relations = RelationIndex::default();
+        relations.add_page(project, page.clone());
+        relations.add_edge(
+            &crate::RelationEdge {
+                source_page_id: crate::PageId::from_legacy_doc_id(&[8; 16]),
+                source_note_id: task.note_id.clone(),
+                source_block_id: task.bid.clone(),
+                property_key: "project".into(),
+                target_page_id: project,
+            },
+            task.clone(),
+        );
+        let context = QueryContext {
+            relations: Some(std::sync::Arc::new(relations)),
+            ..Default::default()
+        };
+        let matches = |block: &ParsedBlock, dsl: &str| {
+            block_matches_with_context(block, &parse_query(dsl), &context).matched
+        };
+        assert!(uses_relations(&parse_query("project.status:active")));
+        assert!(matches(&task, "project.status:active"));
+        assert!(matches(&task, "project.status:paused,active"));
+        assert!(!matches(&task, "project.status:paused"));
+        assert!(matches(&task, "-project.status:paused"));
+        // No relation under that property — nothing to hop to.
+        assert!(!matches(&task, "area.status:active"));
+        assert!(!matches(&block_with(vec![], &[]), "project.status:active"));
+
+        assert!(matches(&page, "has-backlink-from:tag:Task"));
+        assert!(!matches(&page, "has-backlink-from:tag:Meeting"));
+        assert!(matches(&page, "-has-backlink-from:tag:Meeting"));
+        assert!(!matches(&task, "has-backlink-from:tag:Task"));
+    }
 }
@@ -0,0 +1,417 @@
+//! Related-notes recommendations: pages and blocks that look like a given
+//! note, without any model — BM25-weighted term vectors over the indexed
+//! corpus (title + body, the same text FTS5 sees), plus shared outgoing
+//! links and shared tags.
+//!
+//! A [`RelatedCorpus`] holds one term vector per page and per block and the
+//! document frequencies across pages. `SqliteIndex` builds it on first use
+//! and then keeps it current note-by-note from `upsert_note` /
+//! `remove_note`, so the indexer refreshes it incrementally.
+//!
+//! Page score = cosine of the BM25 vectors + [`LINK_WEIGHT`] × Jaccard of
+//! resolved outgoing links + [`TAG_WEIGHT`] × Jaccard of tags. Block score
+//! is the cosine between the block's vector and the note's.
+
+use std::collections::{BTreeSet, HashMap};
+
+use serde::Serialize;
+
+use crate::graph::LinkGraphSnapshot;
+
+/// Weight of shared outgoing links in a page score.
+pub const LINK_WEIGHT: f64 = 0.2;
+/// Weight of shared tags in a page score.
+pub const TAG_WEIGHT: f64 = 0.1;
+/// Scores below this are noise, not recommendations.
+const MIN_SCORE: f64 = 0.05;
+/// Blocks with fewer distinct terms than this are too short to compare.
+const MIN_BLOCK_TERMS: usize = 3;
+/// BM25 term-frequency saturation and length normalisation.
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+const STOPWORDS: &[&str] = &[
+    "about", "after", "all", "also", "and", "any", "are", "because", "been", "but", "can", "did",
+    "does", "for", "from", "had", "has", "have", "her", "his", "how", "into", "its", "just",
+    "more", "not", "now", "one", "only", "our", "out", "she", "should", "some", "than", "that",
+    "the", "their", "them", "then", "there", "these", "they", "this", "was", "were", "what",
+    "when", "which", "who", "will", "with", "would", "you", "your",
+];
+
+/// A page recommended for a note.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RelatedPage {
+    pub slug: String,
+    pub title: String,
+    pub score: f64,
+    /// Text-similarity part of `score`.
+    pub text_score: f64,
+    /// Pages both notes link to.
+    pub shared_links: Vec<String>,
+    pub shared_tags: Vec<String>,
+    /// Either page already links to the other.
+    pub linked: bool,
+}
+
+/// A block in another page recommended for a note.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RelatedBlock {
+    /// Containing page.
+    pub note_id: String,
+    pub title: String,
+    /// The block's `bid`, or its `<note_id>:<line>` id when unstamped.
+    pub block_id: String,
+    pub text: String,
+    pub score: f64,
+}
+
+/// [`RelatedCorpus::related`]'s answer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RelatedNotes {
+    pub pages: Vec<RelatedPage>,
+    pub blocks: Vec<RelatedBlock>,
+}
+
+#[derive(Debug, Clone)]
+struct BlockTerms {
+    block_id: String,
+    text: String,
+    terms: HashMap<String, u32>,
+}
+
+#[derive(Debug, Clone)]
+struct PageTerms {
+    title: String,
+    tags: BTreeSet<String>,
+    terms: HashMap<String, u32>,
+    length: u32,
+    blocks: Vec<BlockTerms>,
+}
+
+/// Term vectors for every indexed page and its blocks.
+#[derive(Debug, Clone, Default)]
+pub struct RelatedCorpus {
+    pages: HashMap<String, PageTerms>,
+    /// Pages containing each term.
+    document_frequency: HashMap<String, u32>,
+    total_length: u64,
+}
+
+impl RelatedCorpus {
+    /// Add or replace one page. `body` is the note body (no frontmatter).
+    pub fn upsert(&mut self, slug: &str, title: &str, body: &str, tags: &[String]) {
+        self.remove(slug);
+        let mut terms = term_counts(title);
+        for (term, count) in term_counts(body) {
+            *terms.entry(term).or_default() += count;
+        }
+        for term in terms.keys() {
+            *self.document_frequency.entry(term.clone()).or_default() += 1;
+        }
+        let length = terms.values().sum();
+        self.total_length += u64::from(length);
+        let blocks = crate::block::parse_blocks(slug, body)
+            .into_iter()
+            .map(|block| BlockTerms {
+                block_id: block.bid.unwrap_or(block.id),
+                terms: term_counts(&block.raw_text),
+                text: block.text,
+            })
+            .filter(|block| block.terms.len() >= MIN_BLOCK_TERMS)
+            .collect();
+        self.pages.insert(
+            slug.to_string(),
+            PageTerms {
+                title: title.to_string(),
+                tags: tags.iter().map(|tag| tag.to_lowercase()).collect(),
+                terms,
+                length,
+                blocks,
+            },
+        );
+    }
+
+    /// Drop one page; a no-op for an unknown slug.
+    pub fn remove(&mut self, slug: &str) {
+        let Some(page) = self.pages.remove(slug) else {
+            return;
+        };
+        self.total_length -= u64::from(page.length);
+        for term in page.terms.keys() {
+            if let Some(count) = self.document_frequency.get_mut(term) {
+                *count -= 1;
+                if *count == 0 {
+                    self.document_frequency.remove(term);
+                }
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    /// The `limit` pages and `limit` blocks (from other pages) most like
+    /// `slug`, best first. `None` when `slug` isn't in the corpus.
+    pub fn related(
+        &self,
+        slug: &str,
+        graph: &LinkGraphSnapshot,
+        limit: usize,
+    ) -> Option<RelatedNotes> {
+        let page = self.pages.get(slug)?;
+        let average_length = self.total_length as f64 / self.pages.len().max(1) as f64;
+        let query = self.weights(&page.terms, page.length as f64, average_length);
+        let node = graph.resolve(slug);
+        let targets = |node: Option<usize>| -> BTreeSet<usize> {
+            node.map(|n| graph.links_from(n).collect())
+                .unwrap_or_default()
+        };
+        let own_targets = targets(node);
+
+        let mut pages = Vec::new();
+        let mut blocks = Vec::new();
+        for (other_slug, other) in &self.pages {
+            if other_slug == slug {
+                continue;
+            }
+            let vector = self.weights(&other.terms, other.length as f64, average_length);
+            let text_score = cosine(&query, &vector);
+            let other_node = graph.resolve(other_slug);
+            let other_targets = targets(other_node);
+            let shared_links: Vec<usize> =
+                own_targets.intersection(&other_targets).copied().collect();
+            let shared_tags: Vec<String> = page.tags.intersection(&other.tags).cloned().collect();
+            let score = text_score
+                + LINK_WEIGHT
+                    * jaccard(shared_links.len(), own_targets.len() + other_targets.len())
+                + TAG_WEIGHT * jaccard(shared_tags.len(), page.tags.len() + other.tags.len());
+            if score >= MIN_SCORE {
+                let linked = match (node, other_node) {
+                    (Some(a), Some(b)) => graph.links_to(a, b) || graph.links_to(b, a),
+                    _ => false,
+                };
+                pages.push(RelatedPage {
+                    slug: other_slug.clone(),
+                    title: other.title.clone(),
+                    score,
+                    text_score,
+                    shared_links: shared_links
+                        .into_iter()
+                        .map(|t| graph.page(t).slug.clone())
+                        .collect(),
+                    shared_tags,
+                    linked,
+                });
+            }
+            for block in &other.blocks {
+                let length = block.terms.values().sum::<u32>() as f64;
+                let score = cosine(&query, &self.weights(&block.terms, length, average_length));
+                if score >= MIN_SCORE {
+                    blocks.push(RelatedBlock {
+                        note_id: other_slug.clone(),
+                        title: other.title.clone(),
+                        block_id: block.block_id.clone(),
+                        text: block.text.clone(),
+                        score,
+                    });
+                }
+            }
+        }
+        pages.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.slug.cmp(&b.slug))
+        });
+        pages.truncate(limit);
+        blocks.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.note_id.cmp(&b.note_id))
+                .then_with(|| a.block_id.cmp(&b.block_id))
+        });
+        blocks.truncate(limit);
+        Some(RelatedNotes { pages, blocks })
+    }
+
+    /// BM25 weight per term: saturated, length-normalised frequency × idf.
+    fn weights(
+        &self,
+        terms: &HashMap<String, u32>,
+        length: f64,
+        average_length: f64,
+    ) -> HashMap<String, f64> {
+        let pages = self.pages.len() as f64;
+        let norm = BM25_K1 * (1.0 - BM25_B + BM25_B * length / average_length.max(1.0));
+        terms
+            .iter()
+            .map(|(term, &count)| {
+                let df = f64::from(self.document_frequency.get(term).copied().unwrap_or(0));
+                let idf = (1.0 + (pages - df + 0.5) / (df + 0.5)).ln();
+                let tf = f64::from(count);
+                (term.clone(), tf * (BM25_K1 + 1.0) / (tf + norm) * idf)
+            })
+            .collect()
+    }
+}
+
+/// Lowercased word counts, skipping `<!-- ... -->` comments (block ids),
+/// stopwords, words under three characters and bare numbers.
+fn term_counts(text: &str) -> HashMap<String, u32> {
+    let mut counts = HashMap::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let (visible, after) = match rest.find("<!--") {
+            Some(start) => {
+                let end = rest[start..]
+                    .find("-->")
+                    .map_or(rest.len(), |end| start + end + 3);
+                (&rest[..start], &rest[end..])
+            }
+            None => (rest, ""),
+        };
+        for word in visible
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| word.chars().count() >= 3)
+            .filter(|word| !word.chars().all(|c| c.is_ascii_digit()))
+        {
+            let word = word.to_lowercase();
+            if !STOPWORDS.contains(&word.as_str()) {
+                *counts.entry(word).or_default() += 1;
+            }
+        }
+        rest = after;
+    }
+    counts
+}
+
+fn cosine(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    let (small, large) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    let dot = small
+        .iter()
+        .filter_map(|(term, weight)| Some(weight * large.get(term)?))
+        .fold(0.0, |sum, product| sum + product);
+    let norm = |v: &HashMap<String, f64>| v.values().map(|w| w * w).sum::<f64>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+/// `shared / (|a| + |b| - shared)`, given `shared` and `|a| + |b|`.
+fn jaccard(shared: usize, total: usize) -> f64 {
+    let union = total - shared;
+    if union == 0 {
+        0.0
+    } else {
+        shared as f64 / union as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphPage;
+
+    fn graph(slugs: &[&str], links: &[(&str, &str)]) -> LinkGraphSnapshot {
+        LinkGraphSnapshot::build(
+            slugs
+                .iter()
+                .map(|slug| GraphPage {
+                    slug: slug.to_string(),
+                    title: slug.to_string(),
+                    aliases: Vec::new(),
+                    page_id: None,
+                    note_type: None,
+                })
+                .collect(),
+            links.iter().copied(),
+            [],
+        )
+    }
+
+    fn corpus() -> RelatedCorpus {
+        let mut corpus = RelatedCorpus::default();
+        corpus.upsert(
+            "sourdough",
+            "Sourdough",
+            "- Feed the starter with rye flour <!-- bid:01a150d8-b62e-7110-a0da-f511705a0ac5 -->\n- Bake at 250 degrees\n",
+            &["baking".into()],
+        );
+        corpus.upsert(
+            "starter-care",
+            "Starter care",
+            "- Rye flour keeps the starter lively\n- Discard half before feeding the starter\n",
+            &[],
+        );
+        corpus.upsert(
+            "taxes",
+            "Taxes",
+            "- File the quarterly return\n- Receipts live in the shoebox\n",
+            &[],
+        );
+        corpus.upsert("pizza", "Pizza", "- Dough night\n", &["baking".into()]);
+        corpus
+    }
+
+    #[test]
+    fn ranks_similar_text_then_shared_tags_and_skips_unrelated_pages() {
+        let corpus = corpus();
+        let related = corpus
+            .related("sourdough", &graph(&["sourdough"], &[]), 5)
+            .unwrap();
+        let slugs: Vec<_> = related.pages.iter().map(|p| p.slug.as_str()).collect();
+        assert_eq!(slugs, vec!["starter-care", "pizza"]);
+        assert_eq!(related.pages[1].shared_tags, vec!["baking"]);
+        assert_eq!(related.pages[1].text_score, 0.0);
+        assert_eq!(related.blocks[0].note_id, "starter-care");
+        assert!(related.blocks.iter().all(|b| b.note_id != "sourdough"));
+        assert!(corpus.related("missing", &graph(&[], &[]), 5).is_none());
+    }
+
+    #[test]
+    fn shared_links_count_and_direct_links_are_flagged() {
+        let corpus = corpus();
+        let graph = graph(
+            &["sourdough", "taxes", "kitchen"],
+            &[
+                ("sourdough", "kitchen"),
+                ("taxes", "kitchen"),
+                ("taxes", "sourdough"),
+            ],
+        );
+        let related = corpus.related("sourdough", &graph, 5).unwrap();
+        let taxes = related.pages.iter().find(|p| p.slug == "taxes").unwrap();
+        assert_eq!(taxes.shared_links, vec!["kitchen"]);
+        assert!(taxes.linked);
+    }
+
+    #[test]
+    fn upsert_and_remove_keep_frequencies_incremental() {
+        let mut corpus = corpus();
+        let before = corpus.document_frequency.get("starter").copied();
+        corpus.upsert("taxes", "Taxes", "- Nothing about bread\n", &[]);
+        corpus.remove("starter-care");
+        corpus.remove("starter-care");
+        assert_eq!(corpus.len(), 3);
+        assert_eq!(
+            corpus.document_frequency.get("starter").copied(),
+            before.map(|n| n - 1)
+        );
+        assert_eq!(
+            corpus.total_length,
+            corpus
+                .pages
+                .values()
+                .map(|p| u64::from(p.length))
+                .sum::<u64>()
+        );
+        assert!(!corpus.document_frequency.contains_key("lively"));
+    }
+}
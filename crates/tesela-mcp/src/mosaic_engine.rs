@@ -181,3 +181,46 @@ pub(crate) async fn create_note_via_engine(
 
     Ok(slug)
 }
+
+/// A note as it stood at `at_secs`, read from its Loro history. Takes the
+/// mosaic lock like the writes do — the engine can't be opened alongside a
+/// running server.
+pub(crate) async fn note_at_via_engine(
+    mosaic: &Path,
+    slug: &str,
+    at_secs: i64,
+) -> Result<Option<tesela_sync::NoteAtTime>> {
+    use tesela_sync::SyncEngine;
+
+    let (_lock, engine) = open_locked_engine(mosaic).await?;
+    let doc_id = engine
+        .resolve_note_doc_id(slug)
+        .await
+        .map_err(|e| anyhow::anyhow!("resolve {slug}: {e}"))?;
+    engine
+        .note_at(doc_id, at_secs)
+        .await
+        .map_err(|e| anyhow::anyhow!("{e}"))
+}
+
+/// Revert a note (or the subtree rooted at `block_id`) to `at_secs` as a
+/// new engine change. `false` when it already matched. Mirrors
+/// `tesela-cli::cmd_history_restore`'s write path.
+pub(crate) async fn restore_note_via_engine(
+    mosaic: &Path,
+    slug: &str,
+    at_secs: i64,
+    block_id: Option<[u8; 16]>,
+) -> Result<bool> {
+    use tesela_sync::SyncEngine;
+
+    let (_lock, engine) = open_locked_engine(mosaic).await?;
+    let doc_id = engine
+        .resolve_note_doc_id(slug)
+        .await
+        .map_err(|e| anyhow::anyhow!("resolve {slug}: {e}"))?;
+    engine
+        .restore_note(doc_id, at_secs, block_id)
+        .await
+        .map_err(|e| anyhow::anyhow!("restore {slug}: {e}"))
+}
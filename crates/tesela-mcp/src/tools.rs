@@ -27,7 +27,7 @@ use tesela_core::{
     traits::{link_graph::LinkGraph, note_store::NoteStore, search_index::SearchIndex},
 };
 
-use crate::mosaic_engine::create_note_via_engine;
+use crate::mosaic_engine::{create_note_via_engine, note_at_via_engine, restore_note_via_engine};
 
 const COMMAND_MANIFEST_JSON: &str =
     include_str!("../../../web/src/lib/command-manifest.json");
@@ -132,7 +132,10 @@ pub const HAND_WRITTEN_TOOL_NAMES: &[&str] = &[
     "create_note",
     "list_notes",
     "get_backlinks",
+    "get_related_notes",
     "get_daily_note",
+    "get_note_at",
+    "restore_note",
 ];
 
 fn hand_written_tools() -> Vec<Value> {
@@ -196,6 +199,18 @@ fn hand_written_tools() -> Vec<Value> {
                 "required": ["id"]
             }
         }),
+        json!({
+            "name": "get_related_notes",
+            "description": "Find pages and blocks similar to a note by text, shared links and shared tags",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string", "description": "Note ID" },
+                    "limit": { "type": "integer", "description": "Max pages and max blocks (default 10)" }
+                },
+                "required": ["id"]
+            }
+        }),
         json!({
             "name": "get_daily_note",
             "description": "Get or create the daily note for a given date",
@@ -206,9 +221,41 @@ fn hand_written_tools() -> Vec<Value> {
                 }
             }
         }),
+        json!({
+            "name": "get_note_at",
+            "description": "Get a note as it stood at a past moment, rebuilt from its edit history",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string", "description": "Note ID" },
+                    "at": { "type": "string", "description": "Unix seconds, RFC 3339, '2026-10-13 14:00', 'last tuesday 14:00', 'yesterday' or '3 hours ago'" }
+                },
+                "required": ["id", "at"]
+            }
+        }),
+        json!({
+            "name": "restore_note",
+            "description": "Revert a note, or one block and its children, to a past moment as a new change",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string", "description": "Note ID" },
+                    "at": { "type": "string", "description": "Same spellings as get_note_at" },
+                    "block_id": { "type": "string", "description": "Restore only this block subtree (block UUID)" }
+                },
+                "required": ["id", "at"]
+            }
+        }),
     ]
 }
 
+/// Resolve a tool's `at` argument (see `tesela_core::history`).
+fn parse_at(params: &Value) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    let at = params["at"].as_str().ok_or("Missing required field: at")?;
+    tesela_core::history::parse_point_in_time(at, &chrono::Local::now())
+        .ok_or_else(|| format!("Unrecognized point in time: '{}'", at))
+}
+
 /// The MCP `tools/list` response: the hand-written, genuinely-MCP-only
 /// tools followed by every manifest command that auto-exposes (tesela-cmdd.3).
 pub fn list_tools() -> Value {
@@ -241,7 +288,10 @@ impl ToolRegistry {
             "create_note" => self.create_note(params).await,
             "list_notes" => self.list_notes(params).await,
             "get_backlinks" => self.get_backlinks(params).await,
+            "get_related_notes" => self.get_related_notes(params).await,
             "get_daily_note" => self.get_daily_note(params).await,
+            "get_note_at" => self.get_note_at(params).await,
+            "restore_note" => self.restore_note(params).await,
             _ if COMMAND_MANIFEST.iter().any(|c| c.id == name) => Err(format!(
                 "tool '{}' is listed via the command manifest but has no MCP execution handler yet",
                 name
@@ -347,6 +397,63 @@ impl ToolRegistry {
         }))
     }
 
+    async fn get_note_at(&self, params: Value) -> Result<Value, String> {
+        let id = params["id"].as_str().ok_or("Missing required field: id")?;
+        let at = parse_at(&params)?;
+        let past = note_at_via_engine(&self.mosaic, id, at.timestamp())
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Note not found: {}", id))?;
+
+        Ok(json!({
+            "content": [{ "type": "text", "text": past.content }]
+        }))
+    }
+
+    async fn restore_note(&self, params: Value) -> Result<Value, String> {
+        let id = params["id"].as_str().ok_or("Missing required field: id")?;
+        let at = parse_at(&params)?;
+        let block_id = params["block_id"]
+            .as_str()
+            .map(|b| {
+                uuid::Uuid::parse_str(b)
+                    .map(|u| *u.as_bytes())
+                    .map_err(|e| format!("Invalid block_id '{}': {}", b, e))
+            })
+            .transpose()?;
+        let note_id = NoteId::new(id);
+        let prev = self
+            .store
+            .get(&note_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Note not found: {}", id))?;
+
+        // Engine-only-writes: the revert is a Loro change, so it syncs.
+        let restored = restore_note_via_engine(&self.mosaic, id, at.timestamp(), block_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        if !restored {
+            return Ok(json!({
+                "content": [{ "type": "text", "text": format!("'{}' already matches that moment; nothing restored", id) }]
+            }));
+        }
+
+        if let Some(note) = self.store.get(&note_id).await.map_err(|e| e.to_string())? {
+            let _ = self.index.reindex(&note).await;
+            let _ = self
+                .index
+                .record_version(&note_id, Some(&prev.content), &note.content, 200)
+                .await;
+            if let Err(e) = self.registry.dispatch_note_updated(&note) {
+                tracing::warn!("Plugin hook on_note_updated failed: {}", e);
+            }
+        }
+        Ok(json!({
+            "content": [{ "type": "text", "text": format!("Restored '{}' to {}", id, at.to_rfc3339()) }]
+        }))
+    }
+
     async fn list_notes(&self, params: Value) -> Result<Value, String> {
         let tag = params["tag"].as_str();
         let limit = params["limit"].as_u64().unwrap_or(20) as usize;
@@ -400,6 +507,22 @@ impl ToolRegistry {
         }))
     }
 
+    async fn get_related_notes(&self, params: Value) -> Result<Value, String> {
+        let id = params["id"].as_str().ok_or("Missing required field: id")?;
+        let limit = params["limit"].as_u64().unwrap_or(10) as usize;
+
+        let related = self
+            .index
+            .related_notes(&NoteId::new(id), limit)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Note not found: {}", id))?;
+
+        Ok(json!({
+            "content": [{ "type": "text", "text": serde_json::to_string_pretty(&related).map_err(|e| e.to_string())? }]
+        }))
+    }
+
     async fn get_daily_note(&self, params: Value) -> Result<Value, String> {
         let date = params["date"]
             .as_str()
@@ -193,3 +193,93 @@ async fn test_get_note_not_found() {
         text
     );
 }
+
+#[tokio::test]
+async fn test_get_note_at_and_restore_read_the_engine_history() {
+    let tmp = TempDir::new().unwrap();
+    let registry = setup_registry(&tmp).await;
+
+    registry
+        .call(
+            "create_note",
+            Some(json!({ "title": "Trip", "content": "- plan" })),
+        )
+        .await
+        .unwrap();
+
+    let result = registry
+        .call("get_note_at", Some(json!({ "id": "trip", "at": "now" })))
+        .await
+        .unwrap();
+    let text = result["content"][0]["text"].as_str().unwrap();
+    assert!(text.contains("- plan"), "{text}");
+
+    let err = registry
+        .call(
+            "get_note_at",
+            Some(json!({ "id": "trip", "at": "2001-01-01" })),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.contains("history unavailable"), "{err}");
+
+    let result = registry
+        .call("restore_note", Some(json!({ "id": "trip", "at": "now" })))
+        .await
+        .unwrap();
+    let text = result["content"][0]["text"].as_str().unwrap();
+    assert!(text.contains("nothing restored"), "{text}");
+
+    let err = registry
+        .call(
+            "restore_note",
+            Some(json!({ "id": "trip", "at": "next week" })),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.contains("Unrecognized point in time"), "{err}");
+}
+
+#[tokio::test]
+async fn test_get_related_notes() {
+    let tmp = TempDir::new().unwrap();
+    let registry = setup_registry(&tmp).await;
+
+    let garden = "tomato seedlings need compost and sunlight before transplanting";
+    for (title, content) in [
+        ("Tomatoes", garden),
+        ("Seedlings", garden),
+        ("Taxes", "quarterly estimated payments and receipts"),
+    ] {
+        registry
+            .call(
+                "create_note",
+                Some(json!({ "title": title, "content": content })),
+            )
+            .await
+            .unwrap();
+    }
+
+    let result = registry
+        .call("get_related_notes", Some(json!({ "id": "tomatoes" })))
+        .await
+        .unwrap();
+    let text = result["content"][0]["text"].as_str().unwrap();
+    let related: serde_json::Value = serde_json::from_str(text).unwrap();
+    let slugs: Vec<&str> = related["pages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["slug"].as_str().unwrap())
+        .collect();
+    assert_eq!(slugs, ["seedlings"], "got: {}", text);
+
+    let err = registry
+        .call(
+            "get_related_notes",
+            Some(json!({ "id": "nonexistent-note" })),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.contains("not found"), "got: {}", err);
+}
@@ -5,19 +5,27 @@
 //! stubs for `/ops` + `/ack` so the MAC gate has something to wrap
 //! (real op semantics arrive in stages 3c/3d).
 
+use std::collections::VecDeque;
+use std::convert::Infallible;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use axum::extract::{Path, State};
+use axum::extract::ws::rejection::WebSocketUpgradeRejection;
+use axum::extract::ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Extension, Path, State};
 use axum::http::{header, HeaderMap, StatusCode};
 use axum::middleware::Next;
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use base64::Engine;
 use serde::{Deserialize, Serialize};
 use tesela_sync::crypto::relay_auth::{body_hash_hex, canonical_request, verify_request_mac};
+use tokio::sync::broadcast::error::RecvError;
 
-use crate::state::AppState;
-use crate::store::{RegisterOutcome, Registration};
+use crate::metrics::ConnectionGuard;
+use crate::state::{AppState, PresenceEvent, PRESENCE_RATE_MAX, PRESENCE_RATE_WINDOW};
+use crate::store::{GroupSummary, RegisterOutcome, Registration};
 
 // ─── Health ────────────────────────────────────────────────────────
 
@@ -142,6 +150,66 @@ pub async fn register(
     }
 }
 
+/// `POST /groups/{group_id}/rekey` (MAC-gated with the CURRENT auth key)
+///
+/// Group key rotation: a member that minted a new epoch re-registers
+/// the epoch's `auth_key` + signed intent. Same body as `/register`.
+/// The previous `auth_key` stays valid for reads only (see
+/// [`mac_gate`]), so lagging members can still pull the rotation
+/// record; deposits need the new key.
+pub async fn rekey(
+    State(state): State<AppState>,
+    Path(group_id_hex): Path<String>,
+    Json(req): Json<RegisterRequest>,
+) -> Response {
+    let Some(group_id) = parse_group_id(&group_id_hex) else {
+        return (StatusCode::BAD_REQUEST, "invalid group_id hex").into_response();
+    };
+    let b64 = base64::engine::general_purpose::STANDARD;
+    let Ok(auth_key_vec) = b64.decode(&req.auth_key_b64) else {
+        return (StatusCode::BAD_REQUEST, "auth_key_b64 not base64").into_response();
+    };
+    let Ok(auth_key_arr): Result<[u8; 32], _> = auth_key_vec.try_into() else {
+        return (StatusCode::BAD_REQUEST, "auth_key must be 32 bytes").into_response();
+    };
+    let Ok(intent_vec) = b64.decode(&req.intent_b64) else {
+        return (StatusCode::BAD_REQUEST, "intent_b64 not base64").into_response();
+    };
+    let disc: Option<[u8; 32]> = match &req.disc_b64 {
+        None => None,
+        Some(s) => match b64.decode(s).ok().and_then(|v| v.try_into().ok()) {
+            Some(disc) => Some(disc),
+            None => {
+                return (StatusCode::BAD_REQUEST, "disc must be 32 bytes of base64").into_response()
+            }
+        },
+    };
+
+    match state
+        .inner
+        .store
+        .rekey_group(&group_id, &auth_key_arr, req.registered_at, &intent_vec)
+        .await
+    {
+        Ok(true) => {
+            if let Some(disc) = disc {
+                if let Err(e) = state
+                    .inner
+                    .store
+                    .upsert_discovery_index(&disc, &group_id)
+                    .await
+                {
+                    return internal_err(&e.to_string());
+                }
+            }
+            state.publish_presence(group_id, PresenceEvent::Rekeyed);
+            (StatusCode::OK, Json(serde_json::json!({ "ok": true }))).into_response()
+        }
+        Ok(false) => (StatusCode::NOT_FOUND, "group not registered").into_response(),
+        Err(e) => internal_err(&e.to_string()),
+    }
+}
+
 /// `GET /discover/{disc}`
 ///
 /// Resolves a recovery-phrase discovery handle to its `group_id`
@@ -254,10 +322,25 @@ pub async fn put_op(
     match state
         .inner
         .store
-        .insert_op(&group_id, &from_device_arr, ts, &payload)
+        .insert_op(
+            &group_id,
+            &from_device_arr,
+            ts,
+            &payload,
+            state.inner.limits.quota(),
+        )
         .await
     {
-        Ok((seq, ts)) => {
+        Ok(None) => quota_exceeded(&state),
+        Ok(Some((seq, ts))) => {
+            // Wake live `/stream` subscribers before anything slower.
+            state.notify_op(group_id, from_device_arr, seq);
+            state.inner.metrics.ops_in.inc();
+            state
+                .inner
+                .metrics
+                .ops_in_bytes
+                .inc_by(payload.len() as u64);
             // Best-effort touch so PUTs count toward known-members
             // for the GC pass in stage 3d.
             let _ = state
@@ -354,6 +437,7 @@ pub async fn get_ops(
                     }
                 }
             }
+            state.inner.metrics.ops_out.inc_by(rows.len() as u64);
             let b64 = base64::engine::general_purpose::STANDARD;
             let records: Vec<OpRecord> = rows
                 .into_iter()
@@ -383,6 +467,223 @@ pub async fn get_ops(
     }
 }
 
+// ─── /stream (live op notices) ─────────────────────────────────────
+
+/// Keep-alive comment cadence on `/stream`, short enough that idle
+/// proxies don't reap the connection.
+const STREAM_KEEPALIVE: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Deserialize)]
+pub struct StreamQuery {
+    /// Only announce ops with `seq > since` — the subscriber's cursor.
+    #[serde(default)]
+    pub since: i64,
+}
+
+/// `GET /groups/{group_id}/stream?since=N`
+///
+/// Server-sent events: one `op` event (`{"seq": N}`) whenever an op
+/// newer than the subscriber's cursor lands in the group, so clients
+/// can fetch on demand instead of polling `/ops` on a timer. Only the
+/// sequence number crosses the wire — the subscriber still pulls the
+/// envelopes through `GET /ops`. Ops deposited by the requesting
+/// device (`X-Tesela-Device`) are not announced back to it. If ops
+/// newer than `since` are already retained, the first event fires
+/// immediately; consecutive notices may be coalesced, so an event's
+/// `seq` is a lower bound on the group head, not a count.
+pub async fn stream_ops(
+    State(state): State<AppState>,
+    Path(group_id_hex): Path<String>,
+    axum::extract::Query(query): axum::extract::Query<StreamQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(group_id) = parse_group_id(&group_id_hex) else {
+        return (StatusCode::BAD_REQUEST, "invalid group_id hex").into_response();
+    };
+    let device = header_str(&headers, "x-tesela-device")
+        .and_then(|h| hex::decode(h).ok())
+        .and_then(|b| <[u8; 16]>::try_from(b).ok());
+    // Subscribe BEFORE reading the head so an op committed in between
+    // is seen by one or the other.
+    let feed = state.inner.op_feed.subscribe();
+    let head = match state
+        .inner
+        .store
+        .latest_seq_since(&group_id, query.since, device.as_ref())
+        .await
+    {
+        Ok(head) => head,
+        Err(e) => return internal_err(&e.to_string()),
+    };
+
+    // Rides in the stream state, so the gauge drops when the client
+    // disconnects and axum drops the body.
+    let connection = ConnectionGuard::open(&state.inner.metrics.stream_connections);
+    let events = futures::stream::unfold(
+        (state, feed, head, query.since, connection),
+        move |(state, mut feed, pending, mut cursor, connection)| async move {
+            if let Some(seq) = pending {
+                return Some((op_event(seq), (state, feed, None, seq, connection)));
+            }
+            loop {
+                let seq = match feed.recv().await {
+                    Ok(notice) => {
+                        if notice.group_id != group_id
+                            || notice.seq <= cursor
+                            || Some(notice.from_device) == device
+                        {
+                            continue;
+                        }
+                        notice.seq
+                    }
+                    // Dropped notices: re-read the head instead.
+                    Err(RecvError::Lagged(_)) => {
+                        match state
+                            .inner
+                            .store
+                            .latest_seq_since(&group_id, cursor, device.as_ref())
+                            .await
+                        {
+                            Ok(Some(seq)) => seq,
+                            Ok(None) => continue,
+                            Err(e) => {
+                                tracing::warn!("[stream] head lookup failed: {e}");
+                                return None;
+                            }
+                        }
+                    }
+                    Err(RecvError::Closed) => return None,
+                };
+                cursor = seq;
+                return Some((op_event(seq), (state, feed, None, cursor, connection)));
+            }
+        },
+    );
+    Sse::new(events)
+        .keep_alive(KeepAlive::new().interval(STREAM_KEEPALIVE))
+        .into_response()
+}
+
+fn op_event(seq: i64) -> Result<Event, Infallible> {
+    Ok(Event::default()
+        .event("op")
+        .data(serde_json::json!({ "seq": seq }).to_string()))
+}
+
+// ─── /presence/ws (live cursors) ───────────────────────────────────
+
+/// `GET /groups/{group_id}/presence/ws` — WebSocket upgrade.
+///
+/// Same contract as the Cloudflare Worker's presence socket: the MAC
+/// gate has already verified the upgrade GET (empty body, like a `/ops`
+/// poll), and the signed `device` query parameter is required because
+/// it is the socket's identity — the unsigned `X-Tesela-Device` header
+/// is not trusted here. Every binary (or text) frame a socket sends is
+/// relayed verbatim to the group's sockets on OTHER devices — the
+/// frames are `presence_seal` output, so the relay never opens,
+/// inspects, or stores them. Frames above the body cap are refused by
+/// the socket, and frames past `PRESENCE_RATE_MAX` per
+/// `PRESENCE_RATE_WINDOW` are dropped.
+///
+/// Publishing a frame is a write to every member, so an upgrade signed
+/// with a retired auth key is refused with 403 even though the MAC gate
+/// lets other retired-key `GET`s through. Rotating the key or deleting
+/// the registration closes live sockets with 1008.
+pub async fn presence_ws(
+    State(state): State<AppState>,
+    Path(group_id_hex): Path<String>,
+    Extension(auth): Extension<MacAuth>,
+    upgrade: Result<WebSocketUpgrade, WebSocketUpgradeRejection>,
+) -> Response {
+    let Some(group_id) = parse_group_id(&group_id_hex) else {
+        return (StatusCode::BAD_REQUEST, "invalid group_id hex").into_response();
+    };
+    if auth.retired_key {
+        return (StatusCode::FORBIDDEN, "auth key retired").into_response();
+    }
+    let Ok(upgrade) = upgrade else {
+        return (StatusCode::UPGRADE_REQUIRED, "expected websocket upgrade").into_response();
+    };
+    let Some(device) = auth.device else {
+        return (StatusCode::UNAUTHORIZED, "device required").into_response();
+    };
+    let max_frame = state.inner.max_body;
+    upgrade
+        .max_message_size(max_frame)
+        .max_frame_size(max_frame)
+        .on_upgrade(move |socket| presence_session(socket, state, group_id, device))
+}
+
+async fn presence_session(
+    mut socket: WebSocket,
+    state: AppState,
+    group_id: [u8; 16],
+    device: [u8; 16],
+) {
+    let _connection = ConnectionGuard::open(&state.inner.metrics.presence_connections);
+    let mut feed = state.join_presence(group_id);
+    let mut sent = VecDeque::new();
+    loop {
+        tokio::select! {
+            inbound = socket.recv() => match inbound {
+                Some(Ok(message @ (Message::Binary(_) | Message::Text(_)))) => {
+                    if within_presence_rate(&mut sent, Instant::now()) {
+                        state.publish_presence(
+                            group_id,
+                            PresenceEvent::Frame {
+                                from_device: device,
+                                message,
+                            },
+                        );
+                    }
+                }
+                // Pings are answered by the socket itself.
+                Some(Ok(Message::Ping(_) | Message::Pong(_))) => {}
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+            },
+            event = feed.recv() => match event {
+                Ok(PresenceEvent::Frame { from_device, message }) if from_device != device => {
+                    if socket.send(message).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(event @ (PresenceEvent::Revoked | PresenceEvent::Rekeyed)) => {
+                    let reason = match event {
+                        PresenceEvent::Rekeyed => "auth key rotated",
+                        _ => "registration revoked",
+                    };
+                    let _ = socket
+                        .send(Message::Close(Some(CloseFrame {
+                            code: 1008,
+                            reason: reason.into(),
+                        })))
+                        .await;
+                    break;
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+    state.leave_presence(group_id, feed);
+}
+
+/// Sliding-window check for one socket's outbound frames (`sent` holds
+/// the recent relay times); records `now` when the frame may go out.
+fn within_presence_rate(sent: &mut VecDeque<Instant>, now: Instant) -> bool {
+    while sent
+        .front()
+        .is_some_and(|at| now.duration_since(*at) >= PRESENCE_RATE_WINDOW)
+    {
+        sent.pop_front();
+    }
+    if sent.len() >= PRESENCE_RATE_MAX {
+        return false;
+    }
+    sent.push_back(now);
+    true
+}
+
 // ─── /snapshot (spine Phase 1b-i) ──────────────────────────────────
 
 #[derive(Debug, Deserialize)]
@@ -472,18 +773,31 @@ pub async fn put_snapshot(
         ));
     }
 
+    // Judged on the net effect: a deposit that compacts more than it
+    // adds is always allowed, so an over-quota group can dig itself out.
     let now = wall_clock_secs_f64() as i64;
     match state
         .inner
         .store
-        .deposit_snapshot_batch(&group_id, effective_covers_seq, &decoded, now)
+        .deposit_snapshot_batch(
+            &group_id,
+            effective_covers_seq,
+            &decoded,
+            now,
+            state.inner.limits.quota(),
+        )
         .await
     {
-        Ok(gc) => (
-            StatusCode::OK,
-            Json(serde_json::json!({ "ok": true, "gc": gc })),
-        )
-            .into_response(),
+        Ok(None) => quota_exceeded(&state),
+        Ok(Some(gc)) => {
+            state.inner.metrics.snapshot_deposits.inc();
+            state.inner.metrics.snapshot_gc_ops.inc_by(gc);
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({ "ok": true, "gc": gc })),
+            )
+                .into_response()
+        }
         Err(e) => internal_err(&e.to_string()),
     }
 }
@@ -683,26 +997,191 @@ pub async fn admin_delete_registration(
     Path(group_id_hex): Path<String>,
     headers: HeaderMap,
 ) -> Response {
+    if let Some(refused) = admin_refusal(&state, &headers) {
+        return refused;
+    }
+    let Some(group_id) = parse_group_id(&group_id_hex) else {
+        return (StatusCode::BAD_REQUEST, "invalid group_id hex").into_response();
+    };
+    match state.inner.store.delete_registration(&group_id).await {
+        Ok(true) => {
+            // A revoked group must not keep relaying cursors among the
+            // old members; new upgrades already 401.
+            state.publish_presence(group_id, PresenceEvent::Revoked);
+            (StatusCode::NO_CONTENT, "").into_response()
+        }
+        Ok(false) => (StatusCode::NOT_FOUND, "group not registered").into_response(),
+        Err(e) => internal_err(&e.to_string()),
+    }
+}
+
+/// Bearer-token check shared by every `/admin/*` handler. `Some` is
+/// the refusal to return: `404` when admin endpoints are disabled,
+/// `401` on a missing or wrong token.
+fn admin_refusal(state: &AppState, headers: &HeaderMap) -> Option<Response> {
     let Some(expected_token) = state.inner.admin_token.as_deref() else {
-        return (StatusCode::NOT_FOUND, "admin endpoints disabled").into_response();
+        return Some((StatusCode::NOT_FOUND, "admin endpoints disabled").into_response());
     };
-    let auth = header_str(&headers, "authorization").unwrap_or("");
+    let auth = header_str(headers, "authorization").unwrap_or("");
     let Some(supplied) = auth.strip_prefix("Bearer ") else {
-        return (StatusCode::UNAUTHORIZED, "missing bearer token").into_response();
+        return Some((StatusCode::UNAUTHORIZED, "missing bearer token").into_response());
     };
     if !constant_time_eq(supplied.as_bytes(), expected_token.as_bytes()) {
-        return (StatusCode::UNAUTHORIZED, "bad admin token").into_response();
+        return Some((StatusCode::UNAUTHORIZED, "bad admin token").into_response());
+    }
+    None
+}
+
+// ─── Admin usage + expiry ──────────────────────────────────────────
+
+/// One group in the admin listing. Sizes are ciphertext bytes.
+#[derive(Debug, Serialize)]
+pub struct GroupUsageRecord {
+    pub group_id: String,
+    pub registered_at: i64,
+    pub last_seen_at: i64,
+    pub idle_secs: i64,
+    pub devices: i64,
+    pub op_count: i64,
+    pub op_bytes: i64,
+    pub snapshot_count: i64,
+    pub snapshot_bytes: i64,
+    pub total_bytes: i64,
+}
+
+impl GroupUsageRecord {
+    fn new(summary: &GroupSummary, now: i64) -> Self {
+        Self {
+            group_id: hex::encode(summary.group_id),
+            registered_at: summary.registered_at,
+            last_seen_at: summary.last_seen_at,
+            idle_secs: (now - summary.last_seen_at).max(0),
+            devices: summary.device_count,
+            op_count: summary.usage.op_count,
+            op_bytes: summary.usage.op_bytes,
+            snapshot_count: summary.usage.snapshot_count,
+            snapshot_bytes: summary.usage.snapshot_bytes,
+            total_bytes: summary.usage.total_bytes(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListGroupsQuery {
+    /// Only list groups idle for at least this many seconds.
+    #[serde(default)]
+    pub idle_for: Option<i64>,
+}
+
+/// `GET /admin/groups?idle_for=SECS`
+///
+/// Every registered group with its storage usage and last check-in,
+/// largest first, plus the configured limits. `idle_for` narrows the
+/// list to inactive groups — e.g. to preview what idle expiry would
+/// reap. Admin-token gated like `admin_delete_registration`.
+pub async fn admin_list_groups(
+    State(state): State<AppState>,
+    axum::extract::Query(query): axum::extract::Query<ListGroupsQuery>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(refused) = admin_refusal(&state, &headers) {
+        return refused;
+    }
+    let groups = match state.inner.store.list_groups().await {
+        Ok(groups) => groups,
+        Err(e) => return internal_err(&e.to_string()),
+    };
+    let now = wall_clock_secs_f64() as i64;
+    let groups: Vec<GroupUsageRecord> = groups
+        .iter()
+        .map(|g| GroupUsageRecord::new(g, now))
+        .filter(|g| query.idle_for.is_none_or(|idle| g.idle_secs >= idle))
+        .collect();
+    let limits = state.inner.limits;
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "groups": groups,
+            "limits": {
+                "max_group_bytes": limits.max_group_bytes,
+                "max_group_ops": limits.max_group_ops,
+                "idle_expiry_secs": limits.idle_expiry.map(|d| d.as_secs()),
+            },
+        })),
+    )
+        .into_response()
+}
+
+/// `GET /admin/groups/{group_id}` — one group's usage record; `404`
+/// if it isn't registered.
+pub async fn admin_group_usage(
+    State(state): State<AppState>,
+    Path(group_id_hex): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(refused) = admin_refusal(&state, &headers) {
+        return refused;
     }
     let Some(group_id) = parse_group_id(&group_id_hex) else {
         return (StatusCode::BAD_REQUEST, "invalid group_id hex").into_response();
     };
-    match state.inner.store.delete_registration(&group_id).await {
-        Ok(true) => (StatusCode::NO_CONTENT, "").into_response(),
-        Ok(false) => (StatusCode::NOT_FOUND, "group not registered").into_response(),
+    match state.inner.store.group_summary(&group_id).await {
+        Ok(Some(summary)) => {
+            let now = wall_clock_secs_f64() as i64;
+            (StatusCode::OK, Json(GroupUsageRecord::new(&summary, now))).into_response()
+        }
+        Ok(None) => (StatusCode::NOT_FOUND, "group not registered").into_response(),
+        Err(e) => internal_err(&e.to_string()),
+    }
+}
+
+/// `POST /admin/expire` — run the idle-expiry sweep now instead of
+/// waiting for the background timer. Responds `{ "expired": [hex…] }`;
+/// `409` when no expiry threshold is configured.
+pub async fn admin_expire(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(refused) = admin_refusal(&state, &headers) {
+        return refused;
+    }
+    if state.inner.limits.idle_expiry.is_none() {
+        return (StatusCode::CONFLICT, "idle expiry not configured").into_response();
+    }
+    match state.expire_idle_groups(wall_clock_secs_f64() as i64).await {
+        Ok(expired) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "expired": expired.iter().map(hex::encode).collect::<Vec<_>>(),
+            })),
+        )
+            .into_response(),
         Err(e) => internal_err(&e.to_string()),
     }
 }
 
+// ─── /metrics ──────────────────────────────────────────────────────
+
+/// `GET /metrics` — Prometheus/OpenMetrics text exposition. Gated on
+/// the admin token like `/admin/*` (scrapers send it as a bearer
+/// token), because the per-group backlog series name every group the
+/// relay holds. The backlog gauges are re-read from the store on each
+/// scrape.
+pub async fn metrics(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(refused) = admin_refusal(&state, &headers) {
+        return refused;
+    }
+    match state.inner.store.list_groups().await {
+        Ok(groups) => state.inner.metrics.set_group_backlog(&groups),
+        Err(e) => return internal_err(&e.to_string()),
+    }
+    (
+        [(
+            header::CONTENT_TYPE,
+            "application/openmetrics-text; version=1.0.0; charset=utf-8",
+        )],
+        state.inner.metrics.render(),
+    )
+        .into_response()
+}
+
 /// Constant-time byte-slice comparison so admin-token comparison
 /// doesn't leak via wall-clock timing. Implements its own loop
 /// instead of pulling in the `subtle` crate for one call site.
@@ -723,11 +1202,29 @@ fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
 /// this many seconds in the past or future are rejected.
 const REPLAY_WINDOW_SECS: i64 = 300;
 
+/// What [`mac_gate`] proved about a request, attached as a request
+/// extension for handlers that need more than "the MAC verified".
+#[derive(Debug, Clone, Copy)]
+pub struct MacAuth {
+    /// The MAC verified only against a retired auth key. The gate
+    /// admits such requests for `GET`s alone; handlers whose `GET` acts
+    /// on other members (presence) refuse them.
+    pub retired_key: bool,
+    /// The `device` query parameter, when present and well-formed. It
+    /// is part of the signed canonical request, unlike the
+    /// `X-Tesela-Device` header, so it can't be swapped in transit.
+    pub device: Option<[u8; 16]>,
+}
+
 /// Verify the per-request MAC for any endpoint that requires it
 /// (everything in `/groups/{id}/*` except `/register` and
 /// `/registration`). Failure modes: missing headers (401), invalid
 /// timestamp / outside replay window (400), replayed nonce (400),
-/// group not registered (401), MAC mismatch (401).
+/// group not registered (401), MAC mismatch (401). `GET`s also verify
+/// against the group's retired auth keys (key rotation), so a member
+/// still on the previous epoch can read — but not write — until it
+/// applies the rotation. Verified requests carry a [`MacAuth`]
+/// extension recording which epoch signed them.
 pub async fn mac_gate(
     State(state): State<AppState>,
     request: axum::extract::Request,
@@ -779,7 +1276,7 @@ pub async fn mac_gate(
     // Buffer the body so we can hash it for the MAC + replay it to
     // the downstream handler. Cheap because spec caps PUT bodies at
     // 1 MiB.
-    let (parts, body) = request.into_parts();
+    let (mut parts, body) = request.into_parts();
     let body_bytes = match axum::body::to_bytes(body, state.inner.max_body).await {
         Ok(b) => b,
         Err(_) => return (StatusCode::PAYLOAD_TOO_LARGE, "body too large").into_response(),
@@ -802,10 +1299,31 @@ pub async fn mac_gate(
         return (StatusCode::UNAUTHORIZED, "X-Tesela-Mac not base64").into_response();
     };
 
-    if !verify_request_mac(&auth_key_arr, &canonical, &mac_bytes) {
-        return (StatusCode::UNAUTHORIZED, "MAC mismatch").into_response();
+    let retired_key = !verify_request_mac(&auth_key_arr, &canonical, &mac_bytes);
+    if retired_key {
+        let retired = if parts.method == axum::http::Method::GET {
+            match state.inner.store.retired_auth_keys(&group_id).await {
+                Ok(keys) => keys,
+                Err(e) => return internal_err(&e.to_string()),
+            }
+        } else {
+            Vec::new()
+        };
+        let read_only_ok = retired.iter().any(|key| {
+            <[u8; 32]>::try_from(key.as_slice())
+                .is_ok_and(|key| verify_request_mac(&key, &canonical, &mac_bytes))
+        });
+        if !read_only_ok {
+            return (StatusCode::UNAUTHORIZED, "MAC mismatch").into_response();
+        }
     }
 
+    let device = signed_device(query);
+    parts.extensions.insert(MacAuth {
+        retired_key,
+        device,
+    });
+
     // Rebuild the request with the buffered body for the handler.
     let new_req = axum::extract::Request::from_parts(parts, axum::body::Body::from(body_bytes));
     let _ = (mac_b64, header::HeaderMap::new());
@@ -829,6 +1347,14 @@ pub async fn rate_gate(
 
 // ─── Helpers ───────────────────────────────────────────────────────
 
+/// The `device=<32 hex>` parameter of a signed query string, if any.
+fn signed_device(query: &str) -> Option<[u8; 16]> {
+    let hex_str = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("device="))?;
+    hex::decode(hex_str).ok()?.try_into().ok()
+}
+
 fn parse_group_id(hex_str: &str) -> Option<[u8; 16]> {
     let bytes = hex::decode(hex_str).ok()?;
     bytes.try_into().ok()
@@ -851,6 +1377,18 @@ fn header_str<'a>(h: &'a HeaderMap, name: &str) -> Option<&'a str> {
     h.get(name).and_then(|v| v.to_str().ok())
 }
 
+/// `507` for a write that would push a group past its quota. Clients
+/// treat it like any failed PUT and retry later — typically after a
+/// snapshot deposit has compacted the group back under the cap.
+fn quota_exceeded(state: &AppState) -> Response {
+    state.inner.metrics.quota_refusals.inc();
+    (
+        StatusCode::INSUFFICIENT_STORAGE,
+        "group storage quota exceeded",
+    )
+        .into_response()
+}
+
 fn internal_err(msg: &str) -> Response {
     tracing::error!("internal error: {}", msg);
     (StatusCode::INTERNAL_SERVER_ERROR, "internal error").into_response()
@@ -11,10 +11,11 @@ use axum::Router;
 
 pub mod apns;
 pub mod handlers;
+pub mod metrics;
 pub mod state;
 pub mod store;
 
-pub use state::AppState;
+pub use state::{AppState, RelayLimits};
 
 /// Tesela-sync's `tesela_sync` is a transitive dep through the
 /// integration tests; this re-export keeps a single place to point
@@ -36,11 +37,18 @@ pub use tesela_sync::crypto::relay_auth;
 ///                                             cannot be MAC-gated)
 /// - `PUT  /groups/{id}/ops`                — MAC-gated
 /// - `GET  /groups/{id}/ops`                — MAC-gated
+/// - `GET  /groups/{id}/stream`             — MAC-gated (SSE: live `op` seq notices)
+/// - `GET  /groups/{id}/presence/ws`        — MAC-gated (WebSocket: sealed presence fan-out)
 /// - `POST /groups/{id}/ack`                — MAC-gated
 /// - `POST /groups/{id}/devices`            — MAC-gated (APNs token registry, P3b)
 /// - `PUT  /groups/{id}/snapshot`           — MAC-gated (snapshot deposit + compaction)
 /// - `GET  /groups/{id}/snapshots`          — MAC-gated (bootstrap source)
+/// - `POST /groups/{id}/rekey`              — MAC-gated (key rotation; old auth key → GET-only)
 /// - `DELETE /admin/groups/{id}/register`   — admin-token-gated (handler checks)
+/// - `GET  /admin/groups`                   — admin-token-gated (usage + last-seen listing)
+/// - `GET  /admin/groups/{id}`              — admin-token-gated (one group's usage)
+/// - `POST /admin/expire`                   — admin-token-gated (run the idle-expiry sweep)
+/// - `GET  /metrics`                        — admin-token-gated (Prometheus exposition)
 pub fn router(state: AppState) -> Router {
     // Routes that the MAC middleware gates. Separate sub-router so we
     // can layer the middleware only over endpoints that require it —
@@ -50,6 +58,8 @@ pub fn router(state: AppState) -> Router {
             "/groups/{group_id}/ops",
             put(handlers::put_op).get(handlers::get_ops),
         )
+        .route("/groups/{group_id}/stream", get(handlers::stream_ops))
+        .route("/groups/{group_id}/presence/ws", get(handlers::presence_ws))
         .route("/groups/{group_id}/ack", post(handlers::post_ack))
         .route(
             "/groups/{group_id}/devices",
@@ -57,6 +67,7 @@ pub fn router(state: AppState) -> Router {
         )
         .route("/groups/{group_id}/snapshot", put(handlers::put_snapshot))
         .route("/groups/{group_id}/snapshots", get(handlers::get_snapshots))
+        .route("/groups/{group_id}/rekey", post(handlers::rekey))
         .layer(from_fn_with_state(state.clone(), handlers::mac_gate));
 
     Router::new()
@@ -71,11 +82,18 @@ pub fn router(state: AppState) -> Router {
             "/admin/groups/{group_id}/register",
             delete(handlers::admin_delete_registration),
         )
+        .route("/admin/groups", get(handlers::admin_list_groups))
+        .route("/admin/groups/{group_id}", get(handlers::admin_group_usage))
+        .route("/admin/expire", post(handlers::admin_expire))
+        .route("/metrics", get(handlers::metrics))
         .merge(mac_gated)
         // Per-IP rate limit runs first so even pre-auth scan traffic
         // gets throttled. Stacked over everything so /register,
         // /registration, /ops, /ack, and /admin all count toward the
         // window cap.
         .layer(from_fn_with_state(state.clone(), handlers::rate_gate))
+        // Outermost, so rate-limited and MAC-refused requests are
+        // counted and timed too.
+        .layer(from_fn_with_state(state.clone(), metrics::track_http))
         .with_state(state)
 }
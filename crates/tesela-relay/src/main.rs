@@ -13,7 +13,12 @@
 //!   stored `auth_key` (derived deterministically on every client from
 //!   `group_key` via HKDF; see spec for the derivation).
 //! - Lets the operator nuke a hijacked group registration via
-//!   `DELETE /admin/groups/{id}/register` gated by `--admin-token`.
+//!   `DELETE /admin/groups/{id}/register` gated by `--admin-token`,
+//!   inspect per-group storage via `GET /admin/groups`, and scrape
+//!   Prometheus metrics from `GET /metrics`.
+//! - Optionally caps each group's storage (`--group-max-bytes`,
+//!   `--group-max-ops`) and deletes groups idle past
+//!   `--group-idle-expiry-days` (swept hourly).
 //!
 //! ## What this binary does NOT do
 //!
@@ -23,18 +28,24 @@
 //! - Account systems, billing, multi-tenancy beyond namespacing by
 //!   randomly-generated `group_id`. One deployment = one trust
 //!   surface (operator).
-//! - WebSocket. Devices poll. (APNs silent-push is now OPTIONAL — set
-//!   `APNS_*` to nudge suspended iOS devices to pull on each deposit;
-//!   unset → poll-only, exactly as before. The push carries no content.)
+//! - Push content. Devices fetch over `GET /ops`, woken by the
+//!   `/stream` notice feed or their poll timer. (APNs silent-push is
+//!   OPTIONAL — set `APNS_*` to nudge suspended iOS devices to pull on
+//!   each deposit. The push carries no content.)
 
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use anyhow::Result;
 use clap::Parser;
 use tracing::info;
 
-use tesela_relay::{router, AppState};
+use tesela_relay::{router, AppState, RelayLimits};
+
+/// How often the idle-expiry sweep runs when `--group-idle-expiry-days`
+/// is set. Expiry is measured in days, so hourly is plenty.
+const EXPIRY_SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
 
 #[derive(Parser, Debug)]
 #[command(name = "tesela-relay", about, long_about = None)]
@@ -63,6 +74,23 @@ pub struct Args {
     #[arg(long, env = "TESELA_RELAY_MAX_BODY", default_value_t = 16 * 1024 * 1024)]
     pub max_body: usize,
 
+    /// Per-group cap on stored ciphertext (ops + snapshots), in bytes.
+    /// A PUT that would exceed it gets `507`; snapshot deposits that
+    /// shrink the group are always accepted. Unset = unlimited.
+    #[arg(long, env = "TESELA_RELAY_GROUP_MAX_BYTES")]
+    pub group_max_bytes: Option<i64>,
+
+    /// Per-group cap on retained op rows (compaction frees them).
+    /// Unset = unlimited.
+    #[arg(long, env = "TESELA_RELAY_GROUP_MAX_OPS")]
+    pub group_max_ops: Option<i64>,
+
+    /// Delete groups none of whose devices has checked in for this many
+    /// days. Irreversible for the relay copy — devices keep their data
+    /// and can re-register. Unset = groups never expire.
+    #[arg(long, env = "TESELA_RELAY_GROUP_IDLE_EXPIRY_DAYS")]
+    pub group_idle_expiry_days: Option<u64>,
+
     // ── APNs silent-push (sync durability P3c). All four required to
     //    enable; any unset → push disabled, relay runs poll-only as
     //    before. Same env-var names as the Cloudflare Worker so the two
@@ -114,8 +142,39 @@ async fn main() -> Result<()> {
         ),
         None => info!("APNs silent-push disabled (APNS_* not fully configured)"),
     }
-    let state =
-        AppState::open_with_apns(&args.db, args.max_body, args.admin_token.clone(), apns).await?;
+    let limits = RelayLimits {
+        max_group_bytes: args.group_max_bytes,
+        max_group_ops: args.group_max_ops,
+        idle_expiry: args
+            .group_idle_expiry_days
+            .map(|days| Duration::from_secs(days * 24 * 60 * 60)),
+    };
+    let state = AppState::open_with_limits(
+        &args.db,
+        args.max_body,
+        args.admin_token.clone(),
+        apns,
+        limits,
+    )
+    .await?;
+    if let Some(days) = args.group_idle_expiry_days {
+        info!("idle groups expire after {days} days");
+        let sweeper = state.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(EXPIRY_SWEEP_INTERVAL);
+            loop {
+                ticker.tick().await;
+                let now = chrono::Utc::now().timestamp();
+                match sweeper.expire_idle_groups(now).await {
+                    Ok(expired) if !expired.is_empty() => {
+                        info!("expired {} idle group(s)", expired.len())
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("idle-group sweep failed: {e}"),
+                }
+            }
+        });
+    }
     let app = router(state);
 
     let listener = tokio::net::TcpListener::bind(args.bind).await?;
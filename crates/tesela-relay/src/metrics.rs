@@ -0,0 +1,222 @@
+//! Prometheus metrics for `GET /metrics`. One [`Metrics`] lives in each
+//! `AppState`, so relays spawned side by side in a test process never
+//! share counters. Handlers bump the counters inline; the per-group
+//! backlog gauges are refreshed from the store at scrape time, since
+//! the usage triggers already keep those numbers current.
+
+use std::time::Instant;
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use prometheus_client::encoding::text::encode;
+use prometheus_client::encoding::EncodeLabelSet;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::Family;
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::metrics::histogram::{exponential_buckets, Histogram};
+use prometheus_client::registry::Registry;
+
+use crate::state::AppState;
+use crate::store::GroupSummary;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+pub(crate) struct RequestLabels {
+    pub(crate) method: String,
+    /// The matched route template (`/groups/{group_id}/ops`), never the
+    /// raw path — one series per route, not per group.
+    pub(crate) route: String,
+    pub(crate) status: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+pub(crate) struct RouteLabels {
+    pub(crate) method: String,
+    pub(crate) route: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+pub(crate) struct GroupLabels {
+    pub(crate) group: String,
+}
+
+type LatencyFamily = Family<RouteLabels, Histogram, fn() -> Histogram>;
+
+/// 1 ms … ~33 s, doubling.
+fn latency_histogram() -> Histogram {
+    Histogram::new(exponential_buckets(0.001, 2.0, 16))
+}
+
+pub(crate) struct Metrics {
+    registry: Registry,
+    pub(crate) http_requests: Family<RequestLabels, Counter>,
+    pub(crate) http_latency: LatencyFamily,
+    pub(crate) ops_in: Counter,
+    pub(crate) ops_in_bytes: Counter,
+    pub(crate) ops_out: Counter,
+    pub(crate) snapshot_deposits: Counter,
+    pub(crate) snapshot_gc_ops: Counter,
+    pub(crate) quota_refusals: Counter,
+    pub(crate) stream_connections: Gauge,
+    pub(crate) presence_connections: Gauge,
+    group_ops: Family<GroupLabels, Gauge>,
+    group_bytes: Family<GroupLabels, Gauge>,
+}
+
+impl Metrics {
+    pub(crate) fn new() -> Self {
+        let mut registry = Registry::with_prefix("tesela_relay");
+        let metrics = Self {
+            http_requests: Family::default(),
+            http_latency: Family::new_with_constructor(latency_histogram as fn() -> Histogram),
+            ops_in: Counter::default(),
+            ops_in_bytes: Counter::default(),
+            ops_out: Counter::default(),
+            snapshot_deposits: Counter::default(),
+            snapshot_gc_ops: Counter::default(),
+            quota_refusals: Counter::default(),
+            stream_connections: Gauge::default(),
+            presence_connections: Gauge::default(),
+            group_ops: Family::default(),
+            group_bytes: Family::default(),
+            registry: Registry::default(),
+        };
+        registry.register(
+            "http_requests",
+            "HTTP requests by route template, method and status",
+            metrics.http_requests.clone(),
+        );
+        registry.register(
+            "http_request_duration_seconds",
+            "HTTP request latency by route template and method",
+            metrics.http_latency.clone(),
+        );
+        registry.register(
+            "ops_in",
+            "Op envelopes accepted by PUT /ops",
+            metrics.ops_in.clone(),
+        );
+        registry.register(
+            "ops_in_bytes",
+            "Ciphertext bytes accepted by PUT /ops",
+            metrics.ops_in_bytes.clone(),
+        );
+        registry.register(
+            "ops_out",
+            "Op envelopes served by GET /ops",
+            metrics.ops_out.clone(),
+        );
+        registry.register(
+            "snapshot_deposits",
+            "Snapshot batches deposited by PUT /snapshot",
+            metrics.snapshot_deposits.clone(),
+        );
+        registry.register(
+            "snapshot_gc_ops",
+            "Op rows compacted away by snapshot deposits",
+            metrics.snapshot_gc_ops.clone(),
+        );
+        registry.register(
+            "quota_refusals",
+            "Deposits refused with 507 by a per-group quota",
+            metrics.quota_refusals.clone(),
+        );
+        registry.register(
+            "stream_connections",
+            "Open /stream (SSE) subscriptions",
+            metrics.stream_connections.clone(),
+        );
+        registry.register(
+            "presence_connections",
+            "Open /presence/ws sockets",
+            metrics.presence_connections.clone(),
+        );
+        registry.register(
+            "group_backlog_ops",
+            "Retained (not yet compacted) op rows per group",
+            metrics.group_ops.clone(),
+        );
+        registry.register(
+            "group_backlog_bytes",
+            "Stored ciphertext bytes (ops + snapshots) per group",
+            metrics.group_bytes.clone(),
+        );
+        Self {
+            registry,
+            ..metrics
+        }
+    }
+
+    /// Replace the per-group gauges with `groups`, so deleted or
+    /// expired groups drop out of the next scrape.
+    pub(crate) fn set_group_backlog(&self, groups: &[GroupSummary]) {
+        self.group_ops.clear();
+        self.group_bytes.clear();
+        for summary in groups {
+            let labels = GroupLabels {
+                group: hex::encode(summary.group_id),
+            };
+            self.group_ops
+                .get_or_create(&labels)
+                .set(summary.usage.op_count);
+            self.group_bytes
+                .get_or_create(&labels)
+                .set(summary.usage.total_bytes());
+        }
+    }
+
+    /// Prometheus text exposition of every registered metric.
+    pub(crate) fn render(&self) -> String {
+        let mut out = String::new();
+        // Writing into a String can't fail.
+        let _ = encode(&mut out, &self.registry);
+        out
+    }
+}
+
+/// Holds a connection gauge up for as long as the connection lives.
+pub(crate) struct ConnectionGuard(Gauge);
+
+impl ConnectionGuard {
+    pub(crate) fn open(gauge: &Gauge) -> Self {
+        gauge.inc();
+        Self(gauge.clone())
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+/// Outermost middleware: request count + latency per matched route.
+/// Requests that match no route are labelled `unmatched`. Streaming
+/// responses (`/stream`, `/presence/ws`) record time-to-headers.
+pub async fn track_http(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let method = request.method().as_str().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".into());
+    let started = Instant::now();
+    let response = next.run(request).await;
+    let metrics = &state.inner.metrics;
+    metrics
+        .http_latency
+        .get_or_create(&RouteLabels {
+            method: method.clone(),
+            route: route.clone(),
+        })
+        .observe(started.elapsed().as_secs_f64());
+    metrics
+        .http_requests
+        .get_or_create(&RequestLabels {
+            method,
+            route,
+            status: response.status().as_u16(),
+        })
+        .inc();
+    response
+}
@@ -7,9 +7,12 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
+use axum::extract::ws::Message;
+use tokio::sync::broadcast;
 
 use crate::apns::Apns;
-use crate::store::Store;
+use crate::metrics::Metrics;
+use crate::store::{Quota, Store};
 
 /// Window of nonces seen recently per `(group_id, nonce)`. Anything
 /// older than `NONCE_TTL` is pruned on lookup; max in-memory
@@ -30,6 +33,76 @@ pub(crate) type NonceCache = Arc<Mutex<HashMap<(Vec<u8>, String), Instant>>>;
 /// timestamps, prune older-than-window on each check.
 pub(crate) type IpRateCache = Arc<Mutex<HashMap<IpAddr, VecDeque<Instant>>>>;
 
+/// Buffered op notices before a slow `/stream` subscriber lags. A lagged
+/// subscriber re-reads the group's head from the store, so this only
+/// bounds memory — nothing is lost by overflowing it.
+pub(crate) const OP_FEED_CAPACITY: usize = 1_024;
+
+/// One freshly committed op, fanned out to live `/stream` subscribers.
+/// Carries only routing metadata — never the payload.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct OpNotice {
+    pub(crate) group_id: [u8; 16],
+    pub(crate) from_device: [u8; 16],
+    pub(crate) seq: i64,
+}
+
+/// Buffered presence events before a slow socket lags. Presence is
+/// ephemeral — a lagged socket just misses some cursor moves, which
+/// peers re-publish on their next move.
+pub(crate) const PRESENCE_FEED_CAPACITY: usize = 256;
+
+/// Per-socket presence rate limit: at most `PRESENCE_RATE_MAX` frames
+/// relayed per `PRESENCE_RATE_WINDOW`; the excess is dropped. Cursor
+/// updates are throttled client-side well below this, so only a
+/// misbehaving socket loses frames.
+pub(crate) const PRESENCE_RATE_WINDOW: Duration = Duration::from_secs(1);
+pub(crate) const PRESENCE_RATE_MAX: usize = 50;
+
+/// Fan-out unit for one group's `/presence/ws` sockets.
+#[derive(Debug, Clone)]
+pub(crate) enum PresenceEvent {
+    /// An opaque sealed frame from `from_device`, relayed verbatim to
+    /// every other device's socket in the group.
+    Frame {
+        from_device: [u8; 16],
+        message: Message,
+    },
+    /// The group's registration was deleted; live sockets must close.
+    Revoked,
+    /// The group rotated its auth key. Every live socket was opened
+    /// under the previous epoch, so all of them close; members that
+    /// applied the rotation reconnect with the new key.
+    Rekeyed,
+}
+
+/// Per-group presence channels, created by the group's first socket and
+/// dropped with its last.
+pub(crate) type PresenceChannels = Mutex<HashMap<[u8; 16], broadcast::Sender<PresenceEvent>>>;
+
+/// Operator-configured per-group limits. `Default` is unlimited with
+/// no expiry — the relay's behaviour before quotas existed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RelayLimits {
+    /// Cap on a group's stored ciphertext (ops + snapshots), in bytes.
+    pub max_group_bytes: Option<i64>,
+    /// Cap on a group's retained op rows.
+    pub max_group_ops: Option<i64>,
+    /// Groups with no device check-in for this long are deleted by
+    /// [`AppState::expire_idle_groups`].
+    pub idle_expiry: Option<Duration>,
+}
+
+impl RelayLimits {
+    /// The storage caps a write is checked against.
+    pub fn quota(&self) -> Quota {
+        Quota {
+            max_bytes: self.max_group_bytes,
+            max_ops: self.max_group_ops,
+        }
+    }
+}
+
 /// Cloneable handle holding everything the request handlers need.
 /// Wrapped in `Arc` so handlers can share without per-request locking
 /// on the inner state.
@@ -55,6 +128,16 @@ pub(crate) struct Inner {
     /// APNs push sender, iff all `APNS_*` config was supplied + the `.p8`
     /// parsed. `None` = the relay sends no silent pushes (default).
     pub(crate) apns: Option<Arc<Apns>>,
+    /// Per-group quotas + idle expiry.
+    pub(crate) limits: RelayLimits,
+    /// Process-wide feed of committed ops; each `/stream` connection
+    /// subscribes and filters to its own group.
+    pub(crate) op_feed: broadcast::Sender<OpNotice>,
+    /// Presence fan-out, one channel per group with live sockets, so a
+    /// busy group never lags or wakes another group's sockets.
+    pub(crate) presence: PresenceChannels,
+    /// Counters + gauges served on `GET /metrics`.
+    pub(crate) metrics: Metrics,
 }
 
 impl AppState {
@@ -79,6 +162,19 @@ impl AppState {
         max_body: usize,
         admin_token: Option<String>,
         apns: Option<Arc<Apns>>,
+    ) -> Result<Self> {
+        Self::open_with_limits(db_path, max_body, admin_token, apns, RelayLimits::default()).await
+    }
+
+    /// Like [`open_with_apns`](Self::open_with_apns) plus per-group
+    /// quotas and idle expiry (`main.rs` builds them from the
+    /// `--group-*` flags).
+    pub async fn open_with_limits(
+        db_path: &Path,
+        max_body: usize,
+        admin_token: Option<String>,
+        apns: Option<Arc<Apns>>,
+        limits: RelayLimits,
     ) -> Result<Self> {
         let store = Store::open(db_path).await?;
         Ok(Self {
@@ -89,6 +185,10 @@ impl AppState {
                 nonces: Arc::new(Mutex::new(HashMap::new())),
                 ip_rates: Arc::new(Mutex::new(HashMap::new())),
                 apns,
+                limits,
+                op_feed: broadcast::channel(OP_FEED_CAPACITY).0,
+                presence: Mutex::new(HashMap::new()),
+                metrics: Metrics::new(),
             }),
         })
     }
@@ -112,6 +212,72 @@ impl AppState {
         }
     }
 
+    /// Announce a committed op to live `/stream` subscribers. No
+    /// subscribers is the common case and not an error.
+    pub(crate) fn notify_op(&self, group_id: [u8; 16], from_device: [u8; 16], seq: i64) {
+        let _ = self.inner.op_feed.send(OpNotice {
+            group_id,
+            from_device,
+            seq,
+        });
+    }
+
+    /// Publish a presence event to `group_id`'s live `/presence/ws`
+    /// sockets. No sockets is the common case and not an error.
+    pub(crate) fn publish_presence(&self, group_id: [u8; 16], event: PresenceEvent) {
+        let channels = self.inner.presence.lock().expect("presence mutex poisoned");
+        if let Some(channel) = channels.get(&group_id) {
+            let _ = channel.send(event);
+        }
+    }
+
+    /// Subscribe a new socket to `group_id`'s presence channel, opening
+    /// the channel if it's the group's first socket.
+    pub(crate) fn join_presence(&self, group_id: [u8; 16]) -> broadcast::Receiver<PresenceEvent> {
+        self.inner
+            .presence
+            .lock()
+            .expect("presence mutex poisoned")
+            .entry(group_id)
+            .or_insert_with(|| broadcast::channel(PRESENCE_FEED_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Drop a closed socket's subscription, and the group's channel with
+    /// its last socket.
+    pub(crate) fn leave_presence(
+        &self,
+        group_id: [u8; 16],
+        feed: broadcast::Receiver<PresenceEvent>,
+    ) {
+        let mut channels = self.inner.presence.lock().expect("presence mutex poisoned");
+        drop(feed);
+        if channels
+            .get(&group_id)
+            .is_some_and(|channel| channel.receiver_count() == 0)
+        {
+            channels.remove(&group_id);
+        }
+    }
+
+    /// Delete every group idle past the configured expiry, closing its
+    /// presence sockets. Returns the expired group ids; empty (and no
+    /// I/O) when expiry is disabled. `main.rs` runs this on a timer.
+    pub async fn expire_idle_groups(&self, now: i64) -> Result<Vec<[u8; 16]>> {
+        let Some(idle) = self.inner.limits.idle_expiry else {
+            return Ok(Vec::new());
+        };
+        let cutoff = now - idle.as_secs() as i64;
+        let mut expired = Vec::new();
+        for group_id in self.inner.store.idle_groups(cutoff).await? {
+            if self.inner.store.delete_registration(&group_id).await? {
+                self.publish_presence(group_id, PresenceEvent::Revoked);
+                expired.push(group_id);
+            }
+        }
+        Ok(expired)
+    }
+
     /// Per-IP rate gate — `false` means this IP has exceeded the
     /// window cap and the request should be refused (429). Prunes
     /// timestamps outside the window on each check.
@@ -1,6 +1,8 @@
 //! SQLite-backed store for relay state. Three tables — registrations,
 //! ops, and device-seen — all per-group-id. Schema mirrors the spec's
-//! storage section.
+//! storage section. Per-group storage accounting (`relay_group_usage`)
+//! is maintained by triggers, so every write path — PUT, compaction,
+//! cascade delete — keeps it exact without the handlers' help.
 //!
 //! All concurrency is handled by SQLite's single-writer model: every
 //! method that mutates wraps its read-then-write in a transaction so
@@ -12,7 +14,7 @@ use std::str::FromStr;
 
 use anyhow::{Context, Result};
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
-use sqlx::{Row, SqlitePool};
+use sqlx::{Row, SqliteConnection, SqlitePool};
 
 /// Per-spec storage. Cloneable wrapper around the connection pool;
 /// every method takes `&self` so handlers share without contention.
@@ -63,6 +65,47 @@ pub struct SnapshotRow {
     pub payload: Vec<u8>,
 }
 
+/// Bytes + row counts a group currently holds. Only ciphertext is
+/// counted; registration rows and bookkeeping are noise.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GroupUsage {
+    pub op_count: i64,
+    pub op_bytes: i64,
+    pub snapshot_count: i64,
+    pub snapshot_bytes: i64,
+}
+
+impl GroupUsage {
+    /// Ops + snapshots — what a byte quota is measured against.
+    pub fn total_bytes(&self) -> i64 {
+        self.op_bytes + self.snapshot_bytes
+    }
+}
+
+/// Per-group caps a write must stay within. Checked inside the write's
+/// own transaction, so concurrent writers can't each pass the check and
+/// together overshoot it. `Default` is unlimited.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Quota {
+    /// Cap on stored ciphertext (ops + snapshots), in bytes.
+    pub max_bytes: Option<i64>,
+    /// Cap on retained op rows.
+    pub max_ops: Option<i64>,
+}
+
+/// One group as the operator sees it: when it registered, when any of
+/// its devices last talked to the relay, and what it stores.
+#[derive(Debug, Clone)]
+pub struct GroupSummary {
+    pub group_id: [u8; 16],
+    pub registered_at: i64,
+    /// Latest device check-in, falling back to `registered_at` for a
+    /// group no device has touched since registering.
+    pub last_seen_at: i64,
+    pub device_count: i64,
+    pub usage: GroupUsage,
+}
+
 impl Store {
     pub async fn open(path: &Path) -> Result<Self> {
         let opts = SqliteConnectOptions::from_str(&format!("sqlite://{}", path.display()))
@@ -147,6 +190,70 @@ impl Store {
         }))
     }
 
+    /// Group key rotation: swap the registration to the new epoch's
+    /// `auth_key` + intent and keep the old `auth_key` as retired (the
+    /// MAC gate still accepts it for reads, so members that haven't
+    /// applied the rotation yet can fetch it). Returns `false` if the
+    /// group isn't registered. Re-submitting the current key is a no-op.
+    pub async fn rekey_group(
+        &self,
+        group_id: &[u8; 16],
+        auth_key: &[u8; 32],
+        registered_at: i64,
+        intent: &[u8],
+    ) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let Some(row) = sqlx::query("SELECT auth_key FROM relay_registrations WHERE group_id = ?")
+            .bind(&group_id[..])
+            .fetch_optional(&mut *tx)
+            .await
+            .context("read registration for rekey")?
+        else {
+            return Ok(false);
+        };
+        let old_key: Vec<u8> = row.get("auth_key");
+        if old_key == auth_key {
+            tx.commit().await?;
+            return Ok(true);
+        }
+        sqlx::query(
+            "INSERT OR IGNORE INTO relay_retired_auth_keys(group_id, auth_key, retired_at) \
+             VALUES (?, ?, ?)",
+        )
+        .bind(&group_id[..])
+        .bind(&old_key)
+        .bind(registered_at)
+        .execute(&mut *tx)
+        .await
+        .context("retire auth key")?;
+        sqlx::query(
+            "UPDATE relay_registrations SET auth_key = ?, registered_at = ?, intent = ? \
+             WHERE group_id = ?",
+        )
+        .bind(&auth_key[..])
+        .bind(registered_at)
+        .bind(intent)
+        .bind(&group_id[..])
+        .execute(&mut *tx)
+        .await
+        .context("update registration for rekey")?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// `auth_key`s from earlier key epochs, newest first.
+    pub async fn retired_auth_keys(&self, group_id: &[u8; 16]) -> Result<Vec<Vec<u8>>> {
+        let rows = sqlx::query(
+            "SELECT auth_key FROM relay_retired_auth_keys WHERE group_id = ? \
+             ORDER BY retired_at DESC",
+        )
+        .bind(&group_id[..])
+        .fetch_all(&self.pool)
+        .await
+        .context("read retired auth keys")?;
+        Ok(rows.into_iter().map(|r| r.get("auth_key")).collect())
+    }
+
     /// Upsert the `disc -> group_id` discovery index (ra7 P0 step 2).
     /// Idempotent by construction: `disc` is a one-way PRF of the
     /// group key, so it always maps to the same `group_id` for a
@@ -188,6 +295,62 @@ impl Store {
         Ok(res.rows_affected() > 0)
     }
 
+    // ── Storage accounting ────────────────────────────────────────
+
+    /// Current footprint of one group (all zero if it stores nothing).
+    pub async fn group_usage(&self, group_id: &[u8; 16]) -> Result<GroupUsage> {
+        let mut conn = self.pool.acquire().await?;
+        read_usage(&mut conn, group_id).await
+    }
+
+    /// Every registered group with its usage + last-seen time, largest
+    /// first.
+    pub async fn list_groups(&self) -> Result<Vec<GroupSummary>> {
+        let rows = sqlx::query(&format!("{GROUP_SUMMARY_SELECT} ORDER BY total_bytes DESC"))
+            .fetch_all(&self.pool)
+            .await
+            .context("list groups")?;
+        rows.iter().map(summary_from_row).collect()
+    }
+
+    /// [`list_groups`](Self::list_groups) for one group; `None` if it
+    /// isn't registered.
+    pub async fn group_summary(&self, group_id: &[u8; 16]) -> Result<Option<GroupSummary>> {
+        let row = sqlx::query(&format!("{GROUP_SUMMARY_SELECT} WHERE r.group_id = ?"))
+            .bind(&group_id[..])
+            .fetch_optional(&self.pool)
+            .await
+            .context("group summary")?;
+        row.as_ref().map(summary_from_row).transpose()
+    }
+
+    /// Net change in a group's stored bytes if this snapshot batch were
+    /// deposited: new payloads, minus the snapshots they replace, minus
+    /// the ops `covers_seq` compacts away. Usually negative — which is
+    /// why a group over its byte quota can still deposit snapshots to
+    /// get back under it.
+    pub async fn snapshot_deposit_delta(
+        &self,
+        group_id: &[u8; 16],
+        covers_seq: i64,
+        snapshots: &[(Vec<u8>, i64, Vec<u8>)],
+    ) -> Result<i64> {
+        let mut conn = self.pool.acquire().await?;
+        deposit_delta(&mut conn, group_id, covers_seq, snapshots).await
+    }
+
+    /// Groups whose last check-in is older than `cutoff` (wall-clock
+    /// seconds) — the idle-expiry candidates.
+    pub async fn idle_groups(&self, cutoff: i64) -> Result<Vec<[u8; 16]>> {
+        Ok(self
+            .list_groups()
+            .await?
+            .into_iter()
+            .filter(|g| g.last_seen_at < cutoff)
+            .map(|g| g.group_id)
+            .collect())
+    }
+
     // ── Ops ────────────────────────────────────────────────────────
 
     /// Append one op to a group's FIFO. Assigns a monotonic seq
@@ -199,14 +362,16 @@ impl Store {
     /// every caught-up consumer's cursor, making the op permanently
     /// undeliverable (the #195 black hole). Mirrors the CF Worker's
     /// AUTOINCREMENT, which never reuses seqs.
-    /// Returns `(seq, ts)` the relay assigned.
+    /// Returns `(seq, ts)` the relay assigned, or `None` (nothing
+    /// written) when the op would take the group past `quota`.
     pub async fn insert_op(
         &self,
         group_id: &[u8; 16],
         from_device: &[u8; 16],
         ts: f64,
         payload: &[u8],
-    ) -> Result<(i64, f64)> {
+        quota: Quota,
+    ) -> Result<Option<(i64, f64)>> {
         // BEGIN IMMEDIATE (not the default deferred BEGIN) takes the
         // write lock up front, so the SELECT MAX(seq)+1 below and the
         // subsequent INSERT are atomic w.r.t. every other writer on
@@ -217,8 +382,19 @@ impl Store {
         // conflict or a duplicate-seq PRIMARY KEY violation on the
         // loser. Concurrent callers now serialize on the IMMEDIATE
         // lock (waiting up to the pool's 5s busy_timeout) instead of
-        // racing.
+        // racing. The same lock covers the quota check, so two PUTs
+        // can't both fit under the cap and together overshoot it.
         let mut tx = self.pool.begin_with("BEGIN IMMEDIATE").await?;
+        if quota.max_bytes.is_some() || quota.max_ops.is_some() {
+            let usage = read_usage(&mut tx, group_id).await?;
+            let over_bytes = quota
+                .max_bytes
+                .is_some_and(|max| usage.total_bytes() + payload.len() as i64 > max);
+            let over_ops = quota.max_ops.is_some_and(|max| usage.op_count >= max);
+            if over_bytes || over_ops {
+                return Ok(None);
+            }
+        }
         let next_seq: i64 = sqlx::query(
             "SELECT MAX( \
                COALESCE((SELECT MAX(seq) FROM relay_ops WHERE group_id = ?), 0), \
@@ -245,7 +421,7 @@ impl Store {
         .await
         .context("insert op")?;
         tx.commit().await?;
-        Ok((next_seq, ts))
+        Ok(Some((next_seq, ts)))
     }
 
     /// Return ops in this group with `seq > since`, ordered ascending.
@@ -271,6 +447,29 @@ impl Store {
             .collect())
     }
 
+    /// Highest retained `seq > since` in this group, skipping ops
+    /// deposited by `exclude_device` (a stream subscriber doesn't need
+    /// waking for its own PUTs). `None` when there is nothing newer.
+    pub async fn latest_seq_since(
+        &self,
+        group_id: &[u8; 16],
+        since: i64,
+        exclude_device: Option<&[u8; 16]>,
+    ) -> Result<Option<i64>> {
+        let exclude: &[u8] = exclude_device.map(|d| &d[..]).unwrap_or(&[]);
+        let row = sqlx::query(
+            "SELECT MAX(seq) AS latest FROM relay_ops \
+             WHERE group_id = ? AND seq > ? AND from_device != ?",
+        )
+        .bind(&group_id[..])
+        .bind(since)
+        .bind(exclude)
+        .fetch_one(&self.pool)
+        .await
+        .context("latest seq")?;
+        Ok(row.get::<Option<i64>, _>("latest"))
+    }
+
     // ── Acks + GC ──────────────────────────────────────────────────
 
     /// Record that `device_id` has applied every op up to and including
@@ -495,14 +694,29 @@ impl Store {
     /// number of `relay_ops` rows deleted. Consistency matters here —
     /// a half-applied deposit could GC ops without a snapshot to
     /// restore them, so all three steps share a transaction.
+    ///
+    /// A deposit whose net effect ([`snapshot_deposit_delta`]) grows the
+    /// group past `quota.max_bytes` writes nothing and returns `None`;
+    /// one that shrinks it is always allowed, so an over-quota group can
+    /// dig itself out. The check runs under the transaction's write lock.
+    ///
+    /// [`snapshot_deposit_delta`]: Self::snapshot_deposit_delta
     pub async fn deposit_snapshot_batch(
         &self,
         group_id: &[u8; 16],
         covers_seq: i64,
         snapshots: &[(Vec<u8>, i64, Vec<u8>)],
         now: i64,
-    ) -> Result<u64> {
-        let mut tx = self.pool.begin().await?;
+        quota: Quota,
+    ) -> Result<Option<u64>> {
+        let mut tx = self.pool.begin_with("BEGIN IMMEDIATE").await?;
+        if let Some(max) = quota.max_bytes {
+            let usage = read_usage(&mut tx, group_id).await?;
+            let delta = deposit_delta(&mut tx, group_id, covers_seq, snapshots).await?;
+            if delta > 0 && usage.total_bytes() + delta > max {
+                return Ok(None);
+            }
+        }
         for (stream_id, snapshot_seq, payload) in snapshots {
             sqlx::query(
                 "INSERT INTO relay_snapshots(group_id, stream_id, snapshot_seq, payload, created_at) \
@@ -546,7 +760,7 @@ impl Store {
             .rows_affected();
 
         tx.commit().await?;
-        Ok(gc)
+        Ok(Some(gc))
     }
 
     /// Latest snapshot per opaque stream for a group. Empty when the
@@ -581,6 +795,95 @@ impl Store {
     }
 }
 
+/// Registration joined with usage + device check-ins. Callers append
+/// their `WHERE` / `ORDER BY`.
+const GROUP_SUMMARY_SELECT: &str = "\
+    SELECT r.group_id, r.registered_at, \
+      COALESCE((SELECT MAX(d.last_seen_ts) FROM relay_device_seen d \
+                WHERE d.group_id = r.group_id), r.registered_at) AS last_seen_at, \
+      (SELECT COUNT(*) FROM relay_device_seen d WHERE d.group_id = r.group_id) AS device_count, \
+      COALESCE(u.op_count, 0) AS op_count, COALESCE(u.op_bytes, 0) AS op_bytes, \
+      COALESCE(u.snapshot_count, 0) AS snapshot_count, \
+      COALESCE(u.snapshot_bytes, 0) AS snapshot_bytes, \
+      COALESCE(u.op_bytes, 0) + COALESCE(u.snapshot_bytes, 0) AS total_bytes \
+    FROM relay_registrations r \
+    LEFT JOIN relay_group_usage u ON u.group_id = r.group_id";
+
+fn usage_from_row(r: &sqlx::sqlite::SqliteRow) -> GroupUsage {
+    GroupUsage {
+        op_count: r.get("op_count"),
+        op_bytes: r.get("op_bytes"),
+        snapshot_count: r.get("snapshot_count"),
+        snapshot_bytes: r.get("snapshot_bytes"),
+    }
+}
+
+fn summary_from_row(r: &sqlx::sqlite::SqliteRow) -> Result<GroupSummary> {
+    let group_id: Vec<u8> = r.get("group_id");
+    let group_id = group_id
+        .try_into()
+        .map_err(|id: Vec<u8>| anyhow::anyhow!("corrupt group_id ({} bytes)", id.len()))?;
+    Ok(GroupSummary {
+        group_id,
+        registered_at: r.get("registered_at"),
+        last_seen_at: r.get("last_seen_at"),
+        device_count: r.get("device_count"),
+        usage: usage_from_row(r),
+    })
+}
+
+/// [`Store::snapshot_deposit_delta`] on `conn`, so a deposit can judge
+/// its quota inside its own transaction.
+async fn deposit_delta(
+    conn: &mut SqliteConnection,
+    group_id: &[u8; 16],
+    covers_seq: i64,
+    snapshots: &[(Vec<u8>, i64, Vec<u8>)],
+) -> Result<i64> {
+    let mut delta = 0i64;
+    for (stream_id, snapshot_seq, payload) in snapshots {
+        let replaced: Option<(i64, i64)> = sqlx::query(
+            "SELECT snapshot_seq, length(payload) AS bytes FROM relay_snapshots \
+             WHERE group_id = ? AND stream_id = ?",
+        )
+        .bind(&group_id[..])
+        .bind(&stream_id[..])
+        .fetch_optional(&mut *conn)
+        .await
+        .context("read replaced snapshot")?
+        .map(|r| (r.get("snapshot_seq"), r.get("bytes")));
+        delta += match replaced {
+            // A stale entry is skipped by the upsert, so it costs nothing.
+            Some((stored_seq, _)) if *snapshot_seq < stored_seq => 0,
+            Some((_, bytes)) => payload.len() as i64 - bytes,
+            None => payload.len() as i64,
+        };
+    }
+    let compacted: i64 = sqlx::query(
+        "SELECT COALESCE(SUM(length(payload)), 0) AS bytes FROM relay_ops \
+         WHERE group_id = ? AND seq <= ?",
+    )
+    .bind(&group_id[..])
+    .bind(covers_seq)
+    .fetch_one(&mut *conn)
+    .await
+    .context("sum compactable ops")?
+    .get("bytes");
+    Ok(delta - compacted)
+}
+
+async fn read_usage(conn: &mut SqliteConnection, group_id: &[u8; 16]) -> Result<GroupUsage> {
+    let row = sqlx::query(
+        "SELECT op_count, op_bytes, snapshot_count, snapshot_bytes \
+         FROM relay_group_usage WHERE group_id = ?",
+    )
+    .bind(&group_id[..])
+    .fetch_optional(&mut *conn)
+    .await
+    .context("read group usage")?;
+    Ok(row.map(|r| usage_from_row(&r)).unwrap_or_default())
+}
+
 async fn migrate(pool: &SqlitePool) -> Result<()> {
     // Inline migration for now — small + stable enough that we don't
     // need sqlx::migrate! file plumbing yet. Add proper migration
@@ -678,6 +981,84 @@ async fn migrate(pool: &SqlitePool) -> Result<()> {
             PRIMARY KEY (group_id, device_id),
             FOREIGN KEY (group_id) REFERENCES relay_registrations(group_id) ON DELETE CASCADE
         );
+
+        -- Auth keys from earlier group-key epochs (key rotation). The
+        -- MAC gate accepts these for reads only: a member that hasn't
+        -- applied the rotation yet can still fetch the rotation record,
+        -- but nothing can be deposited under a retired epoch.
+        CREATE TABLE IF NOT EXISTS relay_retired_auth_keys (
+            group_id   BLOB NOT NULL,
+            auth_key   BLOB NOT NULL,
+            retired_at INTEGER NOT NULL,
+            PRIMARY KEY (group_id, auth_key),
+            FOREIGN KEY (group_id) REFERENCES relay_registrations(group_id) ON DELETE CASCADE
+        );
+
+        -- Per-group storage accounting, read by quotas + the operator
+        -- admin API. Triggers keep it exact on every write path (PUT,
+        -- snapshot upsert, compaction GC, cascade delete). Insert
+        -- triggers upsert the row; delete triggers only UPDATE, so a
+        -- cascade that drops this row first can't resurrect it.
+        CREATE TABLE IF NOT EXISTS relay_group_usage (
+            group_id       BLOB NOT NULL PRIMARY KEY,
+            op_count       INTEGER NOT NULL DEFAULT 0,
+            op_bytes       INTEGER NOT NULL DEFAULT 0,
+            snapshot_count INTEGER NOT NULL DEFAULT 0,
+            snapshot_bytes INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY (group_id) REFERENCES relay_registrations(group_id) ON DELETE CASCADE
+        );
+
+        CREATE TRIGGER IF NOT EXISTS relay_ops_usage_insert AFTER INSERT ON relay_ops
+        BEGIN
+            INSERT INTO relay_group_usage(group_id, op_count, op_bytes)
+            VALUES (NEW.group_id, 1, length(NEW.payload))
+            ON CONFLICT(group_id) DO UPDATE SET
+                op_count = op_count + 1,
+                op_bytes = op_bytes + length(NEW.payload);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS relay_ops_usage_delete AFTER DELETE ON relay_ops
+        BEGIN
+            UPDATE relay_group_usage SET
+                op_count = op_count - 1,
+                op_bytes = op_bytes - length(OLD.payload)
+            WHERE group_id = OLD.group_id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS relay_snapshots_usage_insert AFTER INSERT ON relay_snapshots
+        BEGIN
+            INSERT INTO relay_group_usage(group_id, snapshot_count, snapshot_bytes)
+            VALUES (NEW.group_id, 1, length(NEW.payload))
+            ON CONFLICT(group_id) DO UPDATE SET
+                snapshot_count = snapshot_count + 1,
+                snapshot_bytes = snapshot_bytes + length(NEW.payload);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS relay_snapshots_usage_update AFTER UPDATE OF payload ON relay_snapshots
+        BEGIN
+            UPDATE relay_group_usage SET
+                snapshot_bytes = snapshot_bytes - length(OLD.payload) + length(NEW.payload)
+            WHERE group_id = NEW.group_id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS relay_snapshots_usage_delete AFTER DELETE ON relay_snapshots
+        BEGIN
+            UPDATE relay_group_usage SET
+                snapshot_count = snapshot_count - 1,
+                snapshot_bytes = snapshot_bytes - length(OLD.payload)
+            WHERE group_id = OLD.group_id;
+        END;
+
+        -- Backfill groups that predate the accounting table (a no-op
+        -- once every group has a row).
+        INSERT INTO relay_group_usage(group_id, op_count, op_bytes, snapshot_count, snapshot_bytes)
+        SELECT r.group_id,
+            (SELECT COUNT(*) FROM relay_ops o WHERE o.group_id = r.group_id),
+            (SELECT COALESCE(SUM(length(o.payload)), 0) FROM relay_ops o WHERE o.group_id = r.group_id),
+            (SELECT COUNT(*) FROM relay_snapshots s WHERE s.group_id = r.group_id),
+            (SELECT COALESCE(SUM(length(s.payload)), 0) FROM relay_snapshots s WHERE s.group_id = r.group_id)
+        FROM relay_registrations r
+        WHERE r.group_id NOT IN (SELECT group_id FROM relay_group_usage);
         "#,
     )
     .execute(pool)
@@ -754,4 +1135,75 @@ mod tests {
             vec!["aatoken".to_string()]
         );
     }
+
+    /// Usage accounting follows every write path: op inserts, snapshot
+    /// upsert (insert + replace), compaction GC, and the cascade on
+    /// registration delete.
+    #[tokio::test]
+    async fn group_usage_tracks_ops_snapshots_and_compaction() {
+        let (store, _dir) = temp_store().await;
+        let group = [9u8; 16];
+        let dev = [0xaau8; 16];
+        store
+            .register_group(&group, &[1u8; 32], 0, &[2u8; 32])
+            .await
+            .unwrap();
+        assert_eq!(
+            store.group_usage(&group).await.unwrap(),
+            GroupUsage::default()
+        );
+
+        store
+            .insert_op(&group, &dev, 1.0, &[0u8; 100], Quota::default())
+            .await
+            .unwrap();
+        store
+            .insert_op(&group, &dev, 2.0, &[0u8; 50], Quota::default())
+            .await
+            .unwrap();
+        let usage = store.group_usage(&group).await.unwrap();
+        assert_eq!((usage.op_count, usage.op_bytes), (2, 150));
+
+        // A snapshot covering seq 1 adds 30 bytes and compacts the first op.
+        let batch = vec![(b"note-a".to_vec(), 1, vec![0u8; 30])];
+        assert_eq!(
+            store
+                .snapshot_deposit_delta(&group, 1, &batch)
+                .await
+                .unwrap(),
+            30 - 100
+        );
+        store
+            .deposit_snapshot_batch(&group, 1, &batch, 10, Quota::default())
+            .await
+            .unwrap();
+        let usage = store.group_usage(&group).await.unwrap();
+        assert_eq!(usage.op_count, 1);
+        assert_eq!(usage.op_bytes, 50);
+        assert_eq!((usage.snapshot_count, usage.snapshot_bytes), (1, 30));
+
+        // Replacing the stream's snapshot swaps its bytes, not its count.
+        let replacement = vec![(b"note-a".to_vec(), 2, vec![0u8; 80])];
+        store
+            .deposit_snapshot_batch(&group, 0, &replacement, 11, Quota::default())
+            .await
+            .unwrap();
+        let usage = store.group_usage(&group).await.unwrap();
+        assert_eq!((usage.snapshot_count, usage.snapshot_bytes), (1, 80));
+        assert_eq!(usage.total_bytes(), 130);
+
+        let summary = store.group_summary(&group).await.unwrap().unwrap();
+        assert_eq!(summary.usage, usage);
+        assert_eq!(
+            summary.last_seen_at, 0,
+            "no check-ins: falls back to registered_at"
+        );
+
+        assert!(store.delete_registration(&group).await.unwrap());
+        assert_eq!(
+            store.group_usage(&group).await.unwrap(),
+            GroupUsage::default()
+        );
+        assert!(store.list_groups().await.unwrap().is_empty());
+    }
 }
@@ -331,3 +331,239 @@ async fn transient_register_failure_is_not_misdiagnosed_as_conflict() {
         "transient failure must not be conflict/hijack-shaped, got: {msg}"
     );
 }
+
+/// Group key rotation end to end: the issuer deposits the rotation record
+/// under the old epoch and re-registers the new auth key; the remaining
+/// member reads the record (old auth key, GET only), advances, and then
+/// reads both epochs. The revoked device keeps read access to history but
+/// can neither deposit nor open anything sealed under the new epoch.
+#[tokio::test]
+async fn key_rotation_excludes_the_revoked_device_from_the_new_epoch() {
+    use tesela_sync::crypto::keys::GroupIdentity;
+    use tesela_sync::crypto::rotation::{
+        accept_rotation, decode_group_record, encode_group_record, rotate, DeviceKx, GroupKeyring,
+        GroupRecord, MemberAnnouncement, RotationOutcome,
+    };
+
+    let ctx = spawn().await;
+    let (group, key) = fresh_group();
+    let ident = GroupIdentity {
+        group_id: group,
+        group_key: key.clone(),
+    };
+    let (alice, bob, carol) = (fresh_device(), fresh_device(), fresh_device());
+    let (alice_kx, bob_kx) = (DeviceKx::random(), DeviceKx::random());
+    let members: Vec<_> = [(alice, &alice_kx), (bob, &bob_kx)]
+        .into_iter()
+        .map(|(device, kx)| {
+            MemberAnnouncement {
+                group_id: group,
+                device,
+                kx_pubkey: kx.public_key(),
+                display_name: None,
+            }
+            .to_member(0)
+        })
+        .collect();
+
+    let alice_old = RelayClient::new(ctx.base_url.clone(), group, alice, key.clone());
+    let bob_old = RelayClient::new(ctx.base_url.clone(), group, bob, key.clone());
+    let carol_client = RelayClient::new(ctx.base_url.clone(), group, carol, key);
+    alice_old.register_or_recover().await.expect("register");
+    alice_old
+        .put_envelope(fixture_envelope(alice, group))
+        .await
+        .expect("epoch-0 put");
+
+    // Alice rotates carol out and publishes the record under epoch 0.
+    let mut alice_ring = GroupKeyring::genesis(&ident);
+    let rotation = rotate(&alice_ring, alice, &alice_kx, &members, &[carol], 1).expect("rotate");
+    let mut record_env = fixture_envelope(alice, group);
+    record_env.ciphertext =
+        encode_group_record(&GroupRecord::Rotation(rotation.record.clone())).unwrap();
+    let (record_seq, _) = alice_old
+        .put_envelope(record_env)
+        .await
+        .expect("record put");
+    alice_old.rekey(&rotation.key).await.expect("rekey");
+    alice_ring.advance(1, rotation.key).unwrap();
+    let alice_new = RelayClient::with_keyring(ctx.base_url.clone(), alice, &alice_ring);
+    assert_eq!(alice_new.epoch(), 1);
+
+    // The retired auth key still reads but can no longer write.
+    assert!(
+        carol_client
+            .put_envelope(fixture_envelope(carol, group))
+            .await
+            .is_err(),
+        "revoked device must not deposit under the retired auth key"
+    );
+    let rows = bob_old.poll(0).await.expect("lagging member reads").rows;
+    let (_, env) = rows.iter().find(|(seq, _)| *seq == record_seq).unwrap();
+    let Some(GroupRecord::Rotation(record)) = decode_group_record(&env.ciphertext).unwrap() else {
+        panic!("expected a rotation record");
+    };
+    let mut bob_ring = GroupKeyring::genesis(&ident);
+    let outcome = accept_rotation(&mut bob_ring, &record, bob, &bob_kx, &members).unwrap();
+    assert!(matches!(
+        outcome,
+        RotationOutcome::Advanced { epoch: 1, .. }
+    ));
+    let bob_new = RelayClient::with_keyring(ctx.base_url.clone(), bob, &bob_ring);
+
+    // New-epoch traffic: bob opens it alongside the epoch-0 history.
+    let (new_seq, _) = alice_new
+        .put_envelope(fixture_envelope(alice, group))
+        .await
+        .expect("epoch-1 put");
+    let batch = bob_new.poll(0).await.expect("bob poll");
+    assert!(batch.skipped.is_empty());
+    assert_eq!(batch.rows.len(), 3);
+
+    // Carol still reads history, but the epoch-1 row is opaque to her.
+    let batch = carol_client.poll(0).await.expect("carol reads history");
+    assert_eq!(batch.rows.len(), 2);
+    assert_eq!(batch.skipped, vec![new_seq]);
+}
+
+#[tokio::test]
+async fn stream_wakes_a_waiting_peer_as_soon_as_an_op_lands() {
+    let ctx = spawn().await;
+    let (group, key) = fresh_group();
+    let alice = fresh_device();
+    let bob = fresh_device();
+    let alice_client = RelayClient::new(ctx.base_url.clone(), group, alice, key.clone());
+    let bob_client = std::sync::Arc::new(RelayClient::new(ctx.base_url.clone(), group, bob, key));
+    alice_client
+        .register_or_recover()
+        .await
+        .expect("alice register");
+    bob_client
+        .register_or_recover()
+        .await
+        .expect("bob register");
+
+    // Bob parks on the stream with a window far longer than the test
+    // should take; Alice's deposit must wake him well before it closes.
+    let waiter = {
+        let bob_client = std::sync::Arc::clone(&bob_client);
+        tokio::spawn(async move {
+            let started = std::time::Instant::now();
+            let woke = bob_client
+                .wait_for_ops(0, std::time::Duration::from_secs(30))
+                .await;
+            (woke, started.elapsed())
+        })
+    };
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    let (seq, _) = alice_client
+        .put_envelope(fixture_envelope(alice, group))
+        .await
+        .expect("alice put");
+    let (woke, elapsed) = waiter.await.expect("waiter");
+    assert_eq!(woke, Some(seq));
+    assert!(
+        elapsed < std::time::Duration::from_secs(10),
+        "notice arrived via the stream, not the timeout ({elapsed:?})"
+    );
+
+    // A subscriber behind the head is told immediately on connect.
+    assert_eq!(
+        bob_client
+            .wait_for_ops(0, std::time::Duration::from_secs(30))
+            .await,
+        Some(seq)
+    );
+    // The depositor is never woken by its own op.
+    assert_eq!(
+        alice_client
+            .wait_for_ops(0, std::time::Duration::from_millis(300))
+            .await,
+        None
+    );
+}
+
+#[tokio::test]
+async fn wait_for_ops_falls_back_to_a_timer_without_a_stream_endpoint() {
+    // A relay predating `/stream` 404s every unknown route.
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, axum::Router::new()).await;
+    });
+    let (group, key) = fresh_group();
+    let client = RelayClient::new(
+        Url::parse(&format!("http://{addr}")).unwrap(),
+        group,
+        fresh_device(),
+        key,
+    );
+    let window = std::time::Duration::from_millis(300);
+    let started = std::time::Instant::now();
+    assert_eq!(client.wait_for_ops(0, window).await, None);
+    assert!(
+        started.elapsed() >= window,
+        "fallback still paces the caller like the poll timer"
+    );
+}
+
+#[tokio::test]
+async fn wait_for_ops_keeps_one_stream_open_and_reconnects_after_a_drop() {
+    use axum::response::sse::{Event, Sse};
+    use futures::StreamExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Connection 0 announces seqs 1 and 2 a beat apart, then closes;
+    // every later connection announces seq 3.
+    let connections = std::sync::Arc::new(AtomicUsize::new(0));
+    let app = axum::Router::new().route(
+        "/groups/{group_id}/stream",
+        axum::routing::get({
+            let connections = std::sync::Arc::clone(&connections);
+            move || {
+                let seqs = match connections.fetch_add(1, Ordering::SeqCst) {
+                    0 => vec![1, 2],
+                    _ => vec![3],
+                };
+                async move {
+                    Sse::new(futures::stream::iter(seqs).then(|seq| async move {
+                        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                        Ok::<_, std::convert::Infallible>(
+                            Event::default()
+                                .event("op")
+                                .data(format!("{{\"seq\":{seq}}}")),
+                        )
+                    }))
+                }
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app).await;
+    });
+    let (group, key) = fresh_group();
+    let client = RelayClient::new(
+        Url::parse(&format!("http://{addr}")).unwrap(),
+        group,
+        fresh_device(),
+        key,
+    );
+    let window = std::time::Duration::from_secs(10);
+
+    assert_eq!(client.wait_for_ops(0, window).await, Some(1));
+    assert_eq!(client.wait_for_ops(1, window).await, Some(2));
+    assert_eq!(
+        connections.load(Ordering::SeqCst),
+        1,
+        "consecutive waits share one stream connection"
+    );
+    // The relay closed the stream; the next wait reconnects after backoff.
+    assert_eq!(client.wait_for_ops(2, window).await, Some(3));
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+}
@@ -2167,3 +2167,356 @@ async fn test_register_device_contract() {
         "non-hex apns_token must be 400"
     );
 }
+
+// ─── /presence/ws — sealed presence fan-out ────────────────────────
+
+/// Postcard outer wire form of a presence frame — the same
+/// `OuterPayload{nonce, ciphertext}` `presence_relay` and the FFI
+/// `presence_seal` emit. The relay only ever forwards these bytes.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PresenceOuter {
+    nonce: [u8; 24],
+    ciphertext: Vec<u8>,
+}
+
+fn presence_seal(group: &Group, inner: &[u8]) -> Vec<u8> {
+    use tesela_sync::crypto::aead::{presence_aad, seal};
+    let sealed = seal(&group.key, inner, &presence_aad(group.id.as_bytes())).unwrap();
+    postcard::to_allocvec(&PresenceOuter {
+        nonce: sealed.nonce,
+        ciphertext: sealed.ciphertext,
+    })
+    .unwrap()
+}
+
+fn presence_open(group: &Group, outer: &[u8]) -> Vec<u8> {
+    use tesela_sync::crypto::aead::{open, presence_aad};
+    let o: PresenceOuter = postcard::from_bytes(outer).unwrap();
+    open(
+        &group.key,
+        &o.nonce,
+        &o.ciphertext,
+        &presence_aad(group.id.as_bytes()),
+    )
+    .unwrap()
+}
+
+type PresenceSocket =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Open a MAC-signed presence upgrade for `device_hex` (carried in the
+/// signed `device` query parameter). `Err` carries the HTTP status the
+/// relay refused the upgrade with.
+async fn presence_connect(
+    relay: &TestRelay,
+    group: &Group,
+    device_hex: &str,
+) -> Result<PresenceSocket, u16> {
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+    let path = format!("/groups/{}/presence/ws", hex::encode(group.id.as_bytes()));
+    let query = format!("device={device_hex}");
+    let url = format!(
+        "{}{}?{}",
+        relay.base_url.replacen("http", "ws", 1),
+        path,
+        query
+    );
+    let mut request = url.into_client_request().unwrap();
+    for (name, value) in auth_headers(group, device_hex, "GET", &path, &query, b"").iter() {
+        request.headers_mut().insert(name.clone(), value.clone());
+    }
+    match tokio_tungstenite::connect_async(request).await {
+        Ok((socket, _)) => Ok(socket),
+        Err(tokio_tungstenite::tungstenite::Error::Http(resp)) => Err(resp.status().as_u16()),
+        Err(e) => panic!("presence connect: {e}"),
+    }
+}
+
+async fn register_group(relay: &TestRelay, group: &Group) {
+    let r = reqwest::Client::new()
+        .post(format!(
+            "{}/groups/{}/register",
+            relay.base_url,
+            hex::encode(group.id.as_bytes())
+        ))
+        .json(&register_body(group, now_secs()))
+        .send()
+        .await
+        .unwrap();
+    assert!(r.status().is_success(), "register: {}", r.status());
+}
+
+/// Next data frame within `wait`, skipping control frames. `None` on
+/// timeout or close.
+async fn next_presence_frame(socket: &mut PresenceSocket, wait: Duration) -> Option<Vec<u8>> {
+    use futures::StreamExt;
+    use tokio_tungstenite::tungstenite::Message;
+    tokio::time::timeout(wait, async {
+        while let Some(msg) = socket.next().await {
+            match msg.ok()? {
+                Message::Binary(bytes) => return Some(bytes.to_vec()),
+                Message::Close(_) => return None,
+                _ => continue,
+            }
+        }
+        None
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+#[tokio::test]
+async fn test_presence_ws_fans_sealed_frames_out_to_other_devices() {
+    use futures::SinkExt;
+    use tokio_tungstenite::tungstenite::Message;
+    let relay = spawn_relay().await;
+    let group = fresh_group();
+    register_group(&relay, &group).await;
+    let alice = random_device_id_hex();
+    let bob = random_device_id_hex();
+    let mut alice_ws = presence_connect(&relay, &group, &alice).await.unwrap();
+    let mut alice_second_tab = presence_connect(&relay, &group, &alice).await.unwrap();
+    let mut bob_ws = presence_connect(&relay, &group, &bob).await.unwrap();
+    // A socket in another group must never see this group's frames.
+    let other = fresh_group();
+    register_group(&relay, &other).await;
+    let mut outsider = presence_connect(&relay, &other, &random_device_id_hex())
+        .await
+        .unwrap();
+
+    let inner = br#"PRES{"note":"n1","cursor":42}"#;
+    let outer = presence_seal(&group, inner);
+    alice_ws
+        .send(Message::Binary(outer.clone().into()))
+        .await
+        .unwrap();
+
+    let got = next_presence_frame(&mut bob_ws, Duration::from_secs(5))
+        .await
+        .expect("bob receives alice's frame");
+    assert_eq!(got, outer, "relay forwards the sealed bytes verbatim");
+    assert_eq!(presence_open(&group, &got), inner);
+
+    let quiet = Duration::from_millis(300);
+    assert!(
+        next_presence_frame(&mut alice_ws, quiet).await.is_none(),
+        "the sender never hears its own frame"
+    );
+    assert!(
+        next_presence_frame(&mut alice_second_tab, quiet)
+            .await
+            .is_none(),
+        "nor does another socket on the sender's device"
+    );
+    assert!(
+        next_presence_frame(&mut outsider, quiet).await.is_none(),
+        "presence is scoped to the group"
+    );
+}
+
+#[tokio::test]
+async fn test_presence_ws_drops_frames_past_the_per_socket_rate() {
+    use futures::SinkExt;
+    use tokio_tungstenite::tungstenite::Message;
+    // Mirrors `PRESENCE_RATE_MAX` frames per one-second window.
+    const RATE_MAX: usize = 50;
+    let relay = spawn_relay().await;
+    let group = fresh_group();
+    register_group(&relay, &group).await;
+    let mut alice_ws = presence_connect(&relay, &group, &random_device_id_hex())
+        .await
+        .unwrap();
+    let mut bob_ws = presence_connect(&relay, &group, &random_device_id_hex())
+        .await
+        .unwrap();
+
+    for i in 0..RATE_MAX + 20 {
+        let outer = presence_seal(&group, format!("PRES{i}").as_bytes());
+        alice_ws.send(Message::Binary(outer.into())).await.unwrap();
+    }
+
+    let mut received = 0;
+    while next_presence_frame(&mut bob_ws, Duration::from_millis(300))
+        .await
+        .is_some()
+    {
+        received += 1;
+    }
+    assert_eq!(received, RATE_MAX, "the burst is cut at the rate limit");
+}
+
+#[tokio::test]
+async fn test_presence_ws_requires_mac_device_and_upgrade() {
+    let relay = spawn_relay().await;
+    let group = fresh_group();
+    register_group(&relay, &group).await;
+    let path = format!("/groups/{}/presence/ws", hex::encode(group.id.as_bytes()));
+    let client = reqwest::Client::new();
+
+    // No MAC envelope at all → 401 before any upgrade.
+    let url = format!("{}{}", relay.base_url.replacen("http", "ws", 1), path);
+    {
+        use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+        let request = url.into_client_request().unwrap();
+        match tokio_tungstenite::connect_async(request).await {
+            Err(tokio_tungstenite::tungstenite::Error::Http(resp)) => {
+                assert_eq!(resp.status().as_u16(), 401)
+            }
+            other => panic!("unauthenticated upgrade must be refused: {:?}", other.err()),
+        }
+    }
+
+    // MAC-valid but without the signed device id → 401 (the device is
+    // the socket's fan-out identity). The unsigned header alone doesn't
+    // count: anyone on the path could rewrite it.
+    let headers = auth_headers(&group, &random_device_id_hex(), "GET", &path, "", b"");
+    let r = client
+        .get(format!("{}{}", relay.base_url, path))
+        .headers(headers)
+        .header("Connection", "Upgrade")
+        .header("Upgrade", "websocket")
+        .header("Sec-WebSocket-Version", "13")
+        .header("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ==")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(r.status().as_u16(), 401, "device-less upgrade must 401");
+
+    // MAC-valid plain GET without the upgrade → 426.
+    let device = random_device_id_hex();
+    let query = format!("device={device}");
+    let r = client
+        .get(format!("{}{}?{}", relay.base_url, path, query))
+        .headers(auth_headers(&group, &device, "GET", &path, &query, b""))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(r.status().as_u16(), 426, "non-upgrade GET must 426");
+}
+
+#[tokio::test]
+async fn test_presence_ws_refuses_a_rotated_out_key() {
+    use futures::StreamExt;
+    use tokio_tungstenite::tungstenite::Message;
+    let relay = spawn_relay().await;
+    let old = fresh_group();
+    register_group(&relay, &old).await;
+    let mut socket = presence_connect(&relay, &old, &random_device_id_hex())
+        .await
+        .unwrap();
+
+    // Rotate to a new key epoch, signed with the current (old) key.
+    let mut gk_bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut gk_bytes);
+    let key = GroupKey::from_bytes(gk_bytes);
+    let auth = derive_relay_auth_key(&key, &old.id);
+    let next = Group {
+        id: old.id,
+        key,
+        auth,
+    };
+    let path = format!("/groups/{}/rekey", hex::encode(old.id.as_bytes()));
+    let body = serde_json::to_vec(&register_body(&next, now_secs())).unwrap();
+    let r = reqwest::Client::new()
+        .post(format!("{}{}", relay.base_url, path))
+        .headers(auth_headers(
+            &old,
+            &random_device_id_hex(),
+            "POST",
+            &path,
+            "",
+            &body,
+        ))
+        .body(body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(r.status().as_u16(), 200, "rekey");
+
+    let close = tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(msg) = socket.next().await {
+            if let Ok(Message::Close(frame)) = msg {
+                return frame.map(|f| u16::from(f.code));
+            }
+        }
+        None
+    })
+    .await
+    .expect("socket closes promptly");
+    assert_eq!(
+        close,
+        Some(1008),
+        "rotation closes sockets opened on the old key"
+    );
+
+    // The retired key still reads /ops, but can't rejoin presence.
+    let ops_path = format!("/groups/{}/ops", hex::encode(old.id.as_bytes()));
+    let r = reqwest::Client::new()
+        .get(format!("{}{}?since=0", relay.base_url, ops_path))
+        .headers(auth_headers(
+            &old,
+            &random_device_id_hex(),
+            "GET",
+            &ops_path,
+            "since=0",
+            b"",
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(r.status().as_u16(), 200, "retired key keeps read access");
+    assert_eq!(
+        presence_connect(&relay, &old, &random_device_id_hex())
+            .await
+            .err(),
+        Some(403)
+    );
+    presence_connect(&relay, &next, &random_device_id_hex())
+        .await
+        .expect("the current key joins presence");
+}
+
+#[tokio::test]
+async fn test_presence_ws_closes_when_registration_is_revoked() {
+    let relay = spawn_relay().await;
+    let group = fresh_group();
+    register_group(&relay, &group).await;
+    let mut socket = presence_connect(&relay, &group, &random_device_id_hex())
+        .await
+        .unwrap();
+
+    let r = reqwest::Client::new()
+        .delete(format!(
+            "{}/admin/groups/{}/register",
+            relay.base_url,
+            hex::encode(group.id.as_bytes())
+        ))
+        .bearer_auth(&relay.admin_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(r.status().as_u16(), 204);
+
+    use futures::StreamExt;
+    use tokio_tungstenite::tungstenite::Message;
+    let close = tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(msg) = socket.next().await {
+            if let Ok(Message::Close(frame)) = msg {
+                return frame.map(|f| u16::from(f.code));
+            }
+        }
+        None
+    })
+    .await
+    .expect("socket closes promptly");
+    assert_eq!(close, Some(1008), "revocation closes with policy violation");
+
+    // And a fresh upgrade is refused outright.
+    assert_eq!(
+        presence_connect(&relay, &group, &random_device_id_hex())
+            .await
+            .err(),
+        Some(401)
+    );
+}
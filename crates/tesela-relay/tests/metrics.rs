@@ -0,0 +1,147 @@
+//! `GET /metrics` on the self-hosted relay: admin gating, the op /
+//! snapshot counters, per-route HTTP series, and the per-group backlog
+//! gauges. Operational surface, not wire protocol — the Cloudflare
+//! Worker reports through its own analytics — so it lives outside the
+//! `conformance` suite.
+
+use std::net::SocketAddr;
+
+use rand::RngCore;
+use reqwest::Url;
+use tempfile::TempDir;
+
+use tesela_relay::{router, AppState};
+use tesela_sync::crypto::keys::GroupKey;
+use tesela_sync::device::DeviceId;
+use tesela_sync::group::GroupId;
+use tesela_sync::transport::relay::RelayClient;
+use tesela_sync::wire::envelope::SyncEnvelope;
+
+const ADMIN: &str = "metrics-admin-token";
+
+struct Ctx {
+    base_url: Url,
+    _tmp: TempDir,
+    _server: tokio::task::JoinHandle<()>,
+}
+
+async fn spawn() -> Ctx {
+    let tmp = tempfile::tempdir().expect("tmp dir");
+    let db = tmp.path().join("relay.sqlite");
+    let state = AppState::open(&db, 1_048_576, Some(ADMIN.into()))
+        .await
+        .expect("relay state");
+    let app = router(state);
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    let server = tokio::spawn(async move {
+        let _ = axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await;
+    });
+    Ctx {
+        base_url: Url::parse(&format!("http://{addr}")).unwrap(),
+        _tmp: tmp,
+        _server: server,
+    }
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut out = [0u8; N];
+    rand::thread_rng().fill_bytes(&mut out);
+    out
+}
+
+async fn scrape(ctx: &Ctx) -> String {
+    let r = reqwest::Client::new()
+        .get(ctx.base_url.join("/metrics").unwrap())
+        .bearer_auth(ADMIN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(r.status().as_u16(), 200);
+    r.text().await.unwrap()
+}
+
+#[tokio::test]
+async fn metrics_require_the_admin_token() {
+    let ctx = spawn().await;
+    let r = reqwest::get(ctx.base_url.join("/metrics").unwrap())
+        .await
+        .unwrap();
+    assert_eq!(r.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn metrics_count_ops_snapshots_routes_and_group_backlog() {
+    let ctx = spawn().await;
+    let group = GroupId::from_bytes(random_bytes());
+    let device = DeviceId::from_bytes(random_bytes());
+    let client = RelayClient::new(
+        ctx.base_url.clone(),
+        group,
+        device,
+        GroupKey::from_bytes(random_bytes()),
+    );
+    client.register_or_recover().await.expect("register");
+    for _ in 0..3 {
+        client
+            .put_envelope(SyncEnvelope {
+                from_device: device,
+                to_group: group,
+                nonce: [0u8; 24],
+                ciphertext: vec![7u8; 100],
+            })
+            .await
+            .expect("put");
+    }
+    client.poll(0).await.expect("poll");
+
+    let text = scrape(&ctx).await;
+    assert!(text.contains("tesela_relay_ops_in_total 3\n"), "{text}");
+    assert!(text.contains("tesela_relay_ops_out_total 3\n"), "{text}");
+    assert!(
+        text.contains(
+            "tesela_relay_http_requests_total{method=\"PUT\",route=\"/groups/{group_id}/ops\",status=\"200\"} 3\n"
+        ),
+        "{text}"
+    );
+    assert!(
+        text.contains("tesela_relay_http_request_duration_seconds_count{method=\"PUT\",route=\"/groups/{group_id}/ops\"} 3\n"),
+        "{text}"
+    );
+    let group_hex = hex::encode(group.as_bytes());
+    assert!(
+        text.contains(&format!(
+            "tesela_relay_group_backlog_ops{{group=\"{group_hex}\"}} 3\n"
+        )),
+        "{text}"
+    );
+
+    // Compacting every op moves the snapshot counters and drains the
+    // group's backlog gauge.
+    client
+        .put_snapshots(3, vec![(vec![1u8; 16], b"compacted".to_vec())])
+        .await
+        .expect("deposit");
+    let text = scrape(&ctx).await;
+    assert!(
+        text.contains("tesela_relay_snapshot_deposits_total 1\n"),
+        "{text}"
+    );
+    assert!(
+        text.contains("tesela_relay_snapshot_gc_ops_total 3\n"),
+        "{text}"
+    );
+    assert!(
+        text.contains(&format!(
+            "tesela_relay_group_backlog_ops{{group=\"{group_hex}\"}} 0\n"
+        )),
+        "{text}"
+    );
+    assert!(text.ends_with("# EOF\n"));
+}
@@ -0,0 +1,260 @@
+//! Operator-side limits on the self-hosted relay: per-group byte / op
+//! quotas, the admin usage listing, and idle-group expiry. These are
+//! deployment policy rather than wire protocol, so they live outside
+//! the black-box `conformance` suite (the Cloudflare Worker meters
+//! storage through its own platform).
+
+use std::net::SocketAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rand::RngCore;
+use reqwest::Url;
+use tempfile::TempDir;
+
+use tesela_relay::{router, AppState, RelayLimits};
+use tesela_sync::crypto::keys::GroupKey;
+use tesela_sync::device::DeviceId;
+use tesela_sync::group::GroupId;
+use tesela_sync::transport::relay::RelayClient;
+use tesela_sync::wire::envelope::SyncEnvelope;
+
+const ADMIN: &str = "quota-admin-token";
+
+struct Ctx {
+    base_url: Url,
+    _tmp: TempDir,
+    _server: tokio::task::JoinHandle<()>,
+}
+
+async fn spawn(limits: RelayLimits) -> Ctx {
+    let tmp = tempfile::tempdir().expect("tmp dir");
+    let db = tmp.path().join("relay.sqlite");
+    let state = AppState::open_with_limits(&db, 1_048_576, Some(ADMIN.into()), None, limits)
+        .await
+        .expect("relay state");
+    let app = router(state);
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    let server = tokio::spawn(async move {
+        let _ = axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await;
+    });
+    Ctx {
+        base_url: Url::parse(&format!("http://{addr}")).unwrap(),
+        _tmp: tmp,
+        _server: server,
+    }
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut out = [0u8; N];
+    rand::thread_rng().fill_bytes(&mut out);
+    out
+}
+
+fn client(ctx: &Ctx) -> (RelayClient, GroupId, DeviceId) {
+    let group = GroupId::from_bytes(random_bytes());
+    let device = DeviceId::from_bytes(random_bytes());
+    let client = RelayClient::new(
+        ctx.base_url.clone(),
+        group,
+        device,
+        GroupKey::from_bytes(random_bytes()),
+    );
+    (client, group, device)
+}
+
+fn envelope(from: DeviceId, group: GroupId, len: usize) -> SyncEnvelope {
+    SyncEnvelope {
+        from_device: from,
+        to_group: group,
+        nonce: [0u8; 24],
+        ciphertext: vec![7u8; len],
+    }
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+async fn admin_get(ctx: &Ctx, path: &str) -> (u16, serde_json::Value) {
+    let r = reqwest::Client::new()
+        .get(ctx.base_url.join(path).unwrap())
+        .bearer_auth(ADMIN)
+        .send()
+        .await
+        .unwrap();
+    let status = r.status().as_u16();
+    (status, r.json().await.unwrap_or(serde_json::Value::Null))
+}
+
+#[tokio::test]
+async fn byte_quota_refuses_puts_until_a_snapshot_compacts_the_group() {
+    let ctx = spawn(RelayLimits {
+        max_group_bytes: Some(2_000),
+        ..RelayLimits::default()
+    })
+    .await;
+    let (client, group, device) = client(&ctx);
+    client.register_or_recover().await.expect("register");
+
+    let mut last_seq = 0;
+    let refused = loop {
+        match client.put_envelope(envelope(device, group, 500)).await {
+            Ok((seq, _)) => last_seq = seq,
+            Err(e) => break e.to_string(),
+        }
+        assert!(last_seq < 10, "quota never kicked in");
+    };
+    assert!(
+        refused.contains("507"),
+        "over-quota PUT is a 507: {refused}"
+    );
+    assert!(last_seq >= 2, "puts under the cap were accepted");
+
+    let (status, usage) = admin_get(
+        &ctx,
+        &format!("/admin/groups/{}", hex::encode(group.as_bytes())),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(usage["op_count"], last_seq);
+    assert!(usage["total_bytes"].as_i64().unwrap() <= 2_000);
+    assert_eq!(usage["devices"], 1);
+
+    // A small snapshot covering every op shrinks the group, so it is
+    // accepted even though the group sits at its cap…
+    client
+        .put_snapshots(last_seq, vec![(vec![1u8; 16], b"compacted".to_vec())])
+        .await
+        .expect("compacting deposit is allowed at the cap");
+    // …and frees room for new ops.
+    client
+        .put_envelope(envelope(device, group, 500))
+        .await
+        .expect("put after compaction");
+}
+
+#[tokio::test]
+async fn op_quota_caps_retained_rows() {
+    let ctx = spawn(RelayLimits {
+        max_group_ops: Some(2),
+        ..RelayLimits::default()
+    })
+    .await;
+    let (client, group, device) = client(&ctx);
+    client.register_or_recover().await.expect("register");
+    for _ in 0..2 {
+        client
+            .put_envelope(envelope(device, group, 10))
+            .await
+            .expect("under the op cap");
+    }
+    let err = client
+        .put_envelope(envelope(device, group, 10))
+        .await
+        .expect_err("third op exceeds the cap");
+    assert!(err.to_string().contains("507"), "{err}");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_puts_cannot_overshoot_the_op_quota() {
+    let ctx = spawn(RelayLimits {
+        max_group_ops: Some(5),
+        ..RelayLimits::default()
+    })
+    .await;
+    let (client, group, device) = client(&ctx);
+    client.register_or_recover().await.expect("register");
+
+    let results = futures::future::join_all(
+        (0..20).map(|_| client.put_envelope(envelope(device, group, 10))),
+    )
+    .await;
+    let accepted = results.iter().filter(|r| r.is_ok()).count();
+    assert_eq!(accepted, 5, "exactly the cap is accepted: {results:?}");
+    for err in results.iter().filter_map(|r| r.as_ref().err()) {
+        assert!(err.to_string().contains("507"), "{err}");
+    }
+    let (_, usage) = admin_get(
+        &ctx,
+        &format!("/admin/groups/{}", hex::encode(group.as_bytes())),
+    )
+    .await;
+    assert_eq!(usage["op_count"], 5);
+}
+
+#[tokio::test]
+async fn admin_listing_reports_usage_and_idle_expiry_reaps_inactive_groups() {
+    let ctx = spawn(RelayLimits {
+        idle_expiry: Some(Duration::from_secs(500)),
+        ..RelayLimits::default()
+    })
+    .await;
+    // `idle`: registered long ago, never checked in since.
+    let (idle, idle_group, _) = client(&ctx);
+    idle.register(now_secs() - 1_000)
+        .await
+        .expect("register idle");
+    // `active`: deposits now, which counts as a check-in.
+    let (active, active_group, active_device) = client(&ctx);
+    active.register_or_recover().await.expect("register active");
+    active
+        .put_envelope(envelope(active_device, active_group, 64))
+        .await
+        .expect("active put");
+
+    let r = reqwest::Client::new()
+        .get(ctx.base_url.join("/admin/groups").unwrap())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(r.status().as_u16(), 401, "listing requires the admin token");
+
+    let (status, listing) = admin_get(&ctx, "/admin/groups").await;
+    assert_eq!(status, 200);
+    let groups = listing["groups"].as_array().unwrap();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(
+        groups[0]["group_id"],
+        hex::encode(active_group.as_bytes()),
+        "largest group first"
+    );
+    assert!(groups[0]["op_bytes"].as_i64().unwrap() > 64);
+    assert_eq!(listing["limits"]["idle_expiry_secs"], 500);
+
+    let (_, inactive) = admin_get(&ctx, "/admin/groups?idle_for=500").await;
+    let inactive = inactive["groups"].as_array().unwrap();
+    assert_eq!(inactive.len(), 1);
+    assert_eq!(inactive[0]["group_id"], hex::encode(idle_group.as_bytes()));
+
+    let r = reqwest::Client::new()
+        .post(ctx.base_url.join("/admin/expire").unwrap())
+        .bearer_auth(ADMIN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(r.status().as_u16(), 200);
+    let body: serde_json::Value = r.json().await.unwrap();
+    assert_eq!(
+        body["expired"],
+        serde_json::json!([hex::encode(idle_group.as_bytes())])
+    );
+
+    assert!(idle.fetch_registration().await.unwrap().is_none());
+    assert!(active.fetch_registration().await.unwrap().is_some());
+    let (status, _) = admin_get(
+        &ctx,
+        &format!("/admin/groups/{}", hex::encode(idle_group.as_bytes())),
+    )
+    .await;
+    assert_eq!(status, 404);
+}
@@ -0,0 +1,515 @@
+//! Group key rotation + device revocation on the desktop node.
+//!
+//! The crypto lives in `tesela_sync::crypto::rotation`; this module is
+//! the glue between it, the relay daemon and the `group_members` table:
+//!
+//! - On relay bring-up the node loads its [`GroupKeyring`] and X25519
+//!   key-agreement secret, pins itself in `group_members`, and deposits a
+//!   [`MemberAnnouncement`] so other members can wrap future epochs to it.
+//! - Every relay tick hands inbound rows to [`GroupMembership::apply_records`]
+//!   before the Loro apply. Announcements pin the announcing device's key;
+//!   a rotation record that grants this device the next epoch advances
+//!   and persists the keyring. Its revocations apply only then — a record
+//!   whose grant this device can't open proves nothing about its issuer,
+//!   so it is dropped whole.
+//! - [`rotate_group_key`] backs `POST /sync/relay/rotate-key`.
+//!
+//! Either way the running relay client adopts the new epoch on the spot
+//! ([`RelayClient::adopt_keyring`]). The tick that advanced stops its
+//! batch at the rotation record — rows after it were sealed under the new
+//! key, which the client didn't hold when it polled — and the next tick
+//! re-polls them.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio::sync::Mutex;
+
+use tesela_core::db::SqliteIndex;
+use tesela_sync::crypto::rotation::{
+    accept_rotation, decode_group_record, encode_group_record, load_keyring, rotate, store_keyring,
+    DeviceKx, GroupKeyring, GroupRecord, MemberAnnouncement, RotationOutcome,
+};
+use tesela_sync::group::{list_members, remove_member, upsert_member};
+use tesela_sync::transport::relay::RelayClient;
+use tesela_sync::{DeviceId, GroupIdentity, SyncEnvelope, SyncError, SyncResult};
+
+/// Per-node rotation state shared by the relay daemon and the rotate
+/// route. Cheap to clone.
+#[derive(Clone)]
+pub struct GroupMembership {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    mosaic_root: PathBuf,
+    index: Arc<SqliteIndex>,
+    device: DeviceId,
+    display_name: String,
+    kx: DeviceKx,
+    keyring: Mutex<GroupKeyring>,
+}
+
+impl GroupMembership {
+    /// Load the keyring + key-agreement secret for the current group.
+    pub async fn load(
+        mosaic_root: &Path,
+        index: Arc<SqliteIndex>,
+        ident: &GroupIdentity,
+        device: DeviceId,
+        display_name: String,
+    ) -> SyncResult<Self> {
+        let mut keyring = load_keyring(mosaic_root).await?;
+        if keyring.group_id() != ident.group_id {
+            keyring = GroupKeyring::genesis(ident);
+        }
+        let kx = DeviceKx::load_or_create(mosaic_root).await?;
+        Ok(Self {
+            inner: Arc::new(Inner {
+                mosaic_root: mosaic_root.to_path_buf(),
+                index,
+                device,
+                display_name,
+                kx,
+                keyring: Mutex::new(keyring),
+            }),
+        })
+    }
+
+    /// The keyring as of load (or the last accepted rotation).
+    pub async fn keyring(&self) -> GroupKeyring {
+        self.inner.keyring.lock().await.clone()
+    }
+
+    fn announcement(&self, group_id: tesela_sync::GroupId) -> MemberAnnouncement {
+        MemberAnnouncement {
+            group_id,
+            device: self.inner.device,
+            kx_pubkey: self.inner.kx.public_key(),
+            display_name: Some(self.inner.display_name.clone()),
+        }
+    }
+
+    /// Pin this device in `group_members` and publish its key-agreement
+    /// key through the relay. Re-announcing on every boot is harmless —
+    /// receivers upsert.
+    pub async fn announce(&self, client: &RelayClient) -> SyncResult<()> {
+        let group_id = self.inner.keyring.lock().await.group_id();
+        let announcement = self.announcement(group_id);
+        upsert_member(
+            self.inner.index.pool(),
+            &announcement.to_member(now_millis()),
+        )
+        .await?;
+        put_record(
+            client,
+            self.inner.device,
+            group_id,
+            &GroupRecord::Member(announcement),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Apply the group-control records among `rows` (inbound, already
+    /// AEAD-opened by `client`). Returns the seq of a rotation record this
+    /// device advanced on, if any: `client` now holds the new epoch, and
+    /// the caller must not advance its cursor past that seq, since later
+    /// rows in this batch were opened (or skipped) under the old key.
+    /// Records from this device and non-record payloads are ignored.
+    pub async fn apply_records(
+        &self,
+        client: &RelayClient,
+        rows: &[(i64, SyncEnvelope)],
+    ) -> Option<i64> {
+        for (seq, env) in rows {
+            if env.from_device == self.inner.device {
+                continue;
+            }
+            let record = match decode_group_record(&env.ciphertext) {
+                Ok(Some(record)) => record,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::warn!("relay: undecodable group record seq={seq}: {e} (skipping)");
+                    continue;
+                }
+            };
+            match self.apply_record(client, env, record).await {
+                Ok(true) => return Some(*seq),
+                Ok(false) => {}
+                Err(e) => tracing::warn!("relay: group record seq={seq} rejected: {e}"),
+            }
+        }
+        None
+    }
+
+    /// Returns `true` when the keyring advanced.
+    async fn apply_record(
+        &self,
+        client: &RelayClient,
+        env: &SyncEnvelope,
+        record: GroupRecord,
+    ) -> SyncResult<bool> {
+        let pool = self.inner.index.pool();
+        let mut keyring = self.inner.keyring.lock().await;
+        match record {
+            GroupRecord::Member(announcement) => {
+                if announcement.device != env.from_device
+                    || announcement.group_id != keyring.group_id()
+                {
+                    return Err(SyncError::Protocol(
+                        "member announcement does not match its envelope".into(),
+                    ));
+                }
+                if !upsert_member(pool, &announcement.to_member(now_millis())).await? {
+                    tracing::warn!(
+                        "relay: device {} announced a different key-agreement key than the \
+                         one pinned; keeping the pinned key",
+                        announcement.device
+                    );
+                }
+                Ok(false)
+            }
+            GroupRecord::Rotation(record) => {
+                if record.issuer != env.from_device {
+                    return Err(SyncError::Protocol(
+                        "rotation record was relayed by a device other than its issuer".into(),
+                    ));
+                }
+                let members = list_members(pool, keyring.group_id()).await?;
+                let mut next = keyring.clone();
+                match accept_rotation(
+                    &mut next,
+                    &record,
+                    self.inner.device,
+                    &self.inner.kx,
+                    &members,
+                )? {
+                    RotationOutcome::Advanced { epoch, revoked } => {
+                        for device in &revoked {
+                            remove_member(pool, next.group_id(), *device).await?;
+                        }
+                        store_keyring(&self.inner.mosaic_root, &next).await?;
+                        client.adopt_keyring(&next);
+                        *keyring = next;
+                        tracing::info!(
+                            "relay: group key rotated to epoch {epoch} by {} ({} device(s) \
+                             revoked)",
+                            record.issuer,
+                            revoked.len()
+                        );
+                        Ok(true)
+                    }
+                    // Unverified: any holder of the group key — a revoked
+                    // or lost device included — can deposit a record that
+                    // names itself issuer. Only a grant this device opens
+                    // proves the issuer minted the epoch, so a record
+                    // without one (or an old one) changes nothing.
+                    RotationOutcome::Stale | RotationOutcome::NotGranted => {
+                        tracing::debug!(
+                            "relay: key epoch {} carries nothing new for this device; dropped",
+                            record.epoch
+                        );
+                        Ok(false)
+                    }
+                    RotationOutcome::Revoked => {
+                        tracing::error!(
+                            "relay: this device was removed from the sync group at key epoch {}; \
+                             it keeps read access to history only",
+                            record.epoch
+                        );
+                        Ok(false)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Result of [`rotate_group_key`].
+#[derive(Debug)]
+pub struct RotatedGroup {
+    /// The new current epoch.
+    pub epoch: u32,
+    /// Devices excluded from it.
+    pub revoked: Vec<DeviceId>,
+    /// The new epoch's identity, already persisted.
+    pub identity: GroupIdentity,
+}
+
+/// Mint the next epoch, excluding `revoke`: drop the revoked devices from
+/// `group_members`, deposit the rotation record under the outgoing epoch,
+/// re-register the new auth key with the relay, then persist the keyring.
+///
+/// The relay re-registration is the commit point for the keyring: if it
+/// fails, the keyring stays put and a retry mints a newer epoch, which
+/// supersedes the orphaned record for any member that already accepted
+/// it. The revoked devices stay out of `group_members` either way.
+pub async fn rotate_group_key(
+    membership: &GroupMembership,
+    client: &RelayClient,
+    revoke: &[DeviceId],
+) -> SyncResult<RotatedGroup> {
+    let inner = &membership.inner;
+    if revoke.contains(&inner.device) {
+        return Err(SyncError::Protocol("a device cannot revoke itself".into()));
+    }
+    let pool = inner.index.pool();
+    let mut keyring = inner.keyring.lock().await;
+    for device in revoke {
+        remove_member(pool, keyring.group_id(), *device).await?;
+    }
+    let members = list_members(pool, keyring.group_id()).await?;
+    let rotation = rotate(
+        &keyring,
+        inner.device,
+        &inner.kx,
+        &members,
+        revoke,
+        now_millis(),
+    )?;
+    let record = rotation.record;
+    put_record(
+        client,
+        inner.device,
+        keyring.group_id(),
+        &GroupRecord::Rotation(record.clone()),
+    )
+    .await?;
+    client.rekey(&rotation.key).await?;
+    let mut next = keyring.clone();
+    next.advance(record.epoch, rotation.key)?;
+    store_keyring(&inner.mosaic_root, &next).await?;
+    client.adopt_keyring(&next);
+    let identity = next.identity();
+    *keyring = next;
+    tracing::info!(
+        "group key rotated to epoch {} ({} device(s) revoked)",
+        record.epoch,
+        record.revoked.len()
+    );
+    Ok(RotatedGroup {
+        epoch: record.epoch,
+        revoked: record.revoked,
+        identity,
+    })
+}
+
+async fn put_record(
+    client: &RelayClient,
+    device: DeviceId,
+    group_id: tesela_sync::GroupId,
+    record: &GroupRecord,
+) -> SyncResult<i64> {
+    let envelope = SyncEnvelope {
+        from_device: device,
+        to_group: group_id,
+        nonce: [0u8; 24],
+        ciphertext: encode_group_record(record)?,
+    };
+    client.put_envelope(envelope).await.map(|(seq, _ts)| seq)
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+    use std::net::SocketAddr;
+    use tesela_relay::{router, AppState as RelayAppState};
+    use tesela_sync::crypto::keys::GroupKey;
+    use tesela_sync::GroupId;
+
+    async fn spawn_relay() -> (reqwest::Url, tempfile::TempDir) {
+        let tmp = tempfile::tempdir().expect("tmp");
+        let state = RelayAppState::open(&tmp.path().join("relay.sqlite"), 1_048_576, None)
+            .await
+            .expect("relay state");
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        tokio::spawn(async move {
+            let _ = axum::serve(
+                listener,
+                router(state).into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await;
+        });
+        (reqwest::Url::parse(&format!("http://{addr}")).unwrap(), tmp)
+    }
+
+    struct Node {
+        mosaic: tempfile::TempDir,
+        device: DeviceId,
+        membership: GroupMembership,
+        client: RelayClient,
+    }
+
+    async fn node(url: &reqwest::Url, ident: &GroupIdentity, name: &str) -> Node {
+        let mosaic = tempfile::tempdir().unwrap();
+        tesela_sync::adopt_group_identity(mosaic.path(), ident)
+            .await
+            .unwrap();
+        let index = Arc::new(SqliteIndex::open_in_memory().await.unwrap());
+        let device = DeviceId::new_random();
+        let membership = GroupMembership::load(mosaic.path(), index, ident, device, name.into())
+            .await
+            .unwrap();
+        let client = RelayClient::with_keyring(url.clone(), device, &membership.keyring().await);
+        client.register_or_recover().await.unwrap();
+        Node {
+            mosaic,
+            device,
+            membership,
+            client,
+        }
+    }
+
+    /// Announce → rotate with one device revoked → the remaining member
+    /// advances from the relayed record and persists the new epoch; the
+    /// revoked device is dropped from both members' `group_members`.
+    #[tokio::test]
+    async fn rotation_reaches_remaining_member_and_drops_revoked_one() {
+        let (url, _relay) = spawn_relay().await;
+        let mut gid = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut gid);
+        let ident = GroupIdentity {
+            group_id: GroupId::from_bytes(gid),
+            group_key: GroupKey::random(),
+        };
+        let desk = node(&url, &ident, "desk").await;
+        let laptop = node(&url, &ident, "laptop").await;
+        let phone = node(&url, &ident, "phone").await;
+        for n in [&desk, &laptop, &phone] {
+            n.membership.announce(&n.client).await.unwrap();
+        }
+        for n in [&desk, &laptop] {
+            let rows = n.client.poll(0).await.unwrap().rows;
+            assert_eq!(n.membership.apply_records(&n.client, &rows).await, None);
+            let members = list_members(n.membership.inner.index.pool(), ident.group_id)
+                .await
+                .unwrap();
+            assert_eq!(members.len(), 3);
+        }
+
+        let rotated = rotate_group_key(&desk.membership, &desk.client, &[phone.device])
+            .await
+            .unwrap();
+        assert_eq!(rotated.epoch, 1);
+        assert_eq!(rotated.revoked, vec![phone.device]);
+        assert_eq!(desk.client.epoch(), 1, "the issuer's client follows live");
+
+        let rows = laptop.client.poll(0).await.unwrap().rows;
+        let rotated_at = laptop.membership.apply_records(&laptop.client, &rows).await;
+        assert!(rotated_at.is_some());
+        assert_eq!(laptop.client.epoch(), 1);
+        let ring = load_keyring(laptop.mosaic.path()).await.unwrap();
+        assert_eq!(ring.epoch(), 1);
+        assert_eq!(
+            ring.current_key().as_bytes(),
+            rotated.identity.group_key.as_bytes()
+        );
+        assert!(ring.key_for(0).is_some(), "epoch 0 stays readable");
+        for n in [&desk, &laptop] {
+            let members = list_members(n.membership.inner.index.pool(), ident.group_id)
+                .await
+                .unwrap();
+            assert!(members.iter().all(|m| m.device_id != phone.device));
+        }
+
+        // Both remaining members deposit under the new epoch without a
+        // restart, and read each other's rows.
+        laptop.membership.announce(&laptop.client).await.unwrap();
+        desk.membership.announce(&desk.client).await.unwrap();
+        let batch = desk.client.poll(rotated_at.unwrap()).await.unwrap();
+        assert!(batch.skipped.is_empty());
+        assert!(batch
+            .rows
+            .iter()
+            .any(|(_, env)| env.from_device == laptop.device));
+
+        // The revoked device can't deposit any more, and its own copy of
+        // the record leaves it on the old epoch.
+        assert!(phone.membership.announce(&phone.client).await.is_err());
+        let rows = phone.client.poll(0).await.unwrap().rows;
+        assert_eq!(
+            phone.membership.apply_records(&phone.client, &rows).await,
+            None
+        );
+        assert_eq!(phone.membership.keyring().await.epoch(), 0);
+    }
+
+    /// A member that still holds the group key can deposit a rotation
+    /// record naming itself issuer and revoking everyone else. With no
+    /// grant the receiver can open, the record changes nothing: no member
+    /// is dropped and the keyring stays put.
+    #[tokio::test]
+    async fn forged_rotation_record_changes_nothing() {
+        let (url, _relay) = spawn_relay().await;
+        let mut gid = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut gid);
+        let ident = GroupIdentity {
+            group_id: GroupId::from_bytes(gid),
+            group_key: GroupKey::random(),
+        };
+        let desk = node(&url, &ident, "desk").await;
+        let laptop = node(&url, &ident, "laptop").await;
+        let rogue = node(&url, &ident, "rogue").await;
+        for n in [&desk, &laptop, &rogue] {
+            n.membership.announce(&n.client).await.unwrap();
+        }
+        for n in [&desk, &rogue] {
+            let rows = n.client.poll(0).await.unwrap().rows;
+            n.membership.apply_records(&n.client, &rows).await;
+        }
+
+        // Grant only the rogue itself, revoke the laptop, and deposit the
+        // record without re-keying the relay.
+        let pool = rogue.membership.inner.index.pool();
+        let members: Vec<_> = list_members(pool, ident.group_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|m| m.device_id == rogue.device || m.device_id == laptop.device)
+            .collect();
+        let forged = rotate(
+            &rogue.membership.keyring().await,
+            rogue.device,
+            &rogue.membership.inner.kx,
+            &members,
+            &[laptop.device],
+            now_millis(),
+        )
+        .unwrap();
+        put_record(
+            &rogue.client,
+            rogue.device,
+            ident.group_id,
+            &GroupRecord::Rotation(forged.record),
+        )
+        .await
+        .unwrap();
+
+        let rows = desk.client.poll(0).await.unwrap().rows;
+        assert_eq!(
+            desk.membership.apply_records(&desk.client, &rows).await,
+            None
+        );
+        let members = list_members(desk.membership.inner.index.pool(), ident.group_id)
+            .await
+            .unwrap();
+        assert_eq!(members.len(), 3, "a forged record must not drop anyone");
+        assert!(members.iter().any(|m| m.device_id == laptop.device));
+        assert_eq!(desk.membership.keyring().await.epoch(), 0);
+        assert_eq!(load_keyring(desk.mosaic.path()).await.unwrap().epoch(), 0);
+        assert_eq!(desk.client.epoch(), 0);
+    }
+}
@@ -0,0 +1,505 @@
+//! LAN peer sync over `tesela_sync::transport::lan`.
+//!
+//! With mDNS enabled, the server binds a [`LanTransport`] listener,
+//! advertises its port in the mDNS TXT record, and every
+//! [`LAN_SYNC_INTERVAL`] opens a pinned TLS session to each discovered
+//! member of its group. A session swaps Loro updates directly, so two
+//! devices on one network converge without a round trip through the
+//! relay. The relay stays the path of record: a session only ever imports
+//! what the relay would also deliver.
+//!
+//! ## Session
+//!
+//! One session is half-duplex, so neither side writes while the other is
+//! also writing:
+//!
+//! 1. dialer → [`LanMessage::Versions`]: every tracked note's version vector;
+//! 2. listener → its own `Versions`, the updates the dialer lacks, `Done`;
+//! 3. dialer → the updates the listener lacks, `Done`.
+//!
+//! Updates travel as `TLR2` payloads (the relay's format) in the envelope
+//! body; TLS seals the session, so the envelope nonce stays zero. Each
+//! side imports through `apply_relay_updates` and fans the result out the
+//! way the relay tick does.
+//!
+//! ## Key epochs
+//!
+//! The transport's pins derive from the group's current rotation epoch.
+//! Each round re-reads the keyring (the relay membership's when the relay
+//! is up), follows a rotation through the [`GroupRuntimeFence`] and rekeys
+//! the transport, so a device removed by a rotation can no longer complete
+//! a handshake. A group replaced by pairing stops the loop.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tesela_sync::crypto::rotation::load_keyring;
+use tesela_sync::{
+    decode_loro_relay_payload, encode_loro_relay_payload, pack_loro_relay_batches, DeviceId,
+    GroupId, GroupIdentity, LanTransport, LoroDocUpdate, SyncEngine, SyncEnvelope, SyncError,
+    SyncResult, Transport, TransportSession, TransportTarget, MAX_RELAY_PLAINTEXT_BYTES,
+};
+use tokio::sync::Mutex;
+
+use crate::group_rotation::GroupMembership;
+use crate::shared_spaces::ShareFanOut;
+use crate::state::{AppState, GroupRuntimeFence, GroupScope, WsDelta};
+
+/// Cadence of the dial loop.
+pub const LAN_SYNC_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Upper bound on one session's exchange, so a stalled peer can't hold
+/// the group lease.
+const SESSION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// One frame of a LAN sync session, postcard-encoded into the envelope
+/// body.
+#[derive(Debug, Serialize, Deserialize)]
+enum LanMessage {
+    /// Every note the sender tracks, with its encoded version vector.
+    Versions(Vec<([u8; 16], Vec<u8>)>),
+    /// One `TLR2` batch (`encode_loro_relay_payload`).
+    Updates(Vec<u8>),
+    /// The sender has no more updates.
+    Done,
+}
+
+/// What one session imported from the peer.
+#[derive(Debug, Default)]
+struct SessionOutcome {
+    applied_note_ids: Vec<[u8; 16]>,
+    applied_updates: Vec<LoroDocUpdate>,
+}
+
+/// The group's current key epoch: the relay membership's keyring when the
+/// relay is up (it follows rotations live), else the persisted keyring.
+async fn current_epoch(
+    mosaic_root: &Path,
+    membership: Option<&GroupMembership>,
+) -> SyncResult<GroupIdentity> {
+    match membership {
+        Some(membership) => Ok(membership.keyring().await.identity()),
+        None => Ok(load_keyring(mosaic_root).await?.identity()),
+    }
+}
+
+/// Bind the LAN listener on every interface, pinned to the current epoch.
+pub async fn bind(device: DeviceId, mosaic_root: &Path) -> SyncResult<LanTransport> {
+    let identity = current_epoch(mosaic_root, None).await?;
+    LanTransport::bind(device, &identity, SocketAddr::from(([0, 0, 0, 0], 0))).await
+}
+
+/// Running LAN sync: the transport plus what a session needs to import
+/// and publish a peer's edits.
+pub struct LanSync {
+    transport: LanTransport,
+    fan_out: ShareFanOut,
+    mosaic_root: PathBuf,
+    membership: Option<GroupMembership>,
+    /// The epoch sessions run under. Locked only to read or move it.
+    fence: Mutex<GroupRuntimeFence>,
+}
+
+/// Spawn the listener and the dial loop. Call after relay bring-up so the
+/// loop follows the relay membership's keyring.
+pub async fn start(state: &AppState, transport: LanTransport) -> Arc<LanSync> {
+    let captured = state.group_identity.read().await.clone();
+    let lan = Arc::new(LanSync {
+        transport,
+        fan_out: ShareFanOut::from_state(state),
+        mosaic_root: state.mosaic_root.clone(),
+        membership: state.relay.as_ref().and_then(|r| r.membership.clone()),
+        fence: Mutex::new(GroupRuntimeFence::capture(
+            Arc::clone(&state.group_identity),
+            &captured,
+        )),
+    });
+
+    let listener = Arc::clone(&lan);
+    let mut inbound = lan.transport.incoming();
+    tokio::spawn(async move {
+        while let Some(session) = inbound.next().await {
+            tokio::spawn(Arc::clone(&listener).answer(session));
+        }
+    });
+
+    let daemon = Arc::clone(&lan);
+    tokio::spawn(async move {
+        loop {
+            if !daemon.refresh_epoch().await {
+                tracing::info!("lan sync: captured group was replaced; stopping");
+                break;
+            }
+            daemon.round(false).await;
+            tokio::time::sleep(LAN_SYNC_INTERVAL).await;
+        }
+    });
+    lan
+}
+
+impl LanSync {
+    /// Dial every known peer now; per-peer count of notes imported, or the
+    /// error that ended the session.
+    pub async fn sync_now(&self) -> HashMap<DeviceId, Result<usize, String>> {
+        self.refresh_epoch().await;
+        self.round(true).await
+    }
+
+    /// Follow a key rotation. `false` once the group itself was replaced.
+    async fn refresh_epoch(&self) -> bool {
+        let rotated = match current_epoch(&self.mosaic_root, self.membership.as_ref()).await {
+            Ok(identity) => identity,
+            Err(e) => {
+                tracing::debug!("lan sync: read key epochs: {e}");
+                return true;
+            }
+        };
+        let mut fence = self.fence.lock().await;
+        if fence.scope().matches(&rotated) {
+            return true;
+        }
+        if !fence.follow_rotation(&rotated).await {
+            return false;
+        }
+        if let Err(e) = self.transport.rekey(&rotated) {
+            tracing::warn!("lan sync: rekey to the new epoch: {e}");
+        }
+        true
+    }
+
+    /// Refresh discovered addresses, then dial. On the cadence only the
+    /// lower device id of a pair dials — one session syncs both ways.
+    async fn round(&self, all: bool) -> HashMap<DeviceId, Result<usize, String>> {
+        if let Err(e) = self.transport.tick().await {
+            tracing::debug!("lan sync: discovery refresh: {e}");
+        }
+        let own = self.fan_out.engine.device();
+        let mut results = HashMap::new();
+        for peer in self.transport.peers() {
+            if peer == own || (!all && peer.as_bytes() < own.as_bytes()) {
+                continue;
+            }
+            let result = self.dial(peer).await;
+            if let Err(e) = &result {
+                tracing::debug!("lan sync with {peer}: {e}");
+            }
+            results.insert(peer, result);
+        }
+        results
+    }
+
+    async fn dial(&self, peer: DeviceId) -> Result<usize, String> {
+        let fence = self.fence.lock().await.clone();
+        let scope = fence.scope();
+        fence
+            .run_if_current(async {
+                let mut session = self
+                    .transport
+                    .open(TransportTarget::Peer(peer))
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let exchange = tokio::time::timeout(
+                    SESSION_TIMEOUT,
+                    dial_exchange(&mut *session, &*self.fan_out.engine, scope.group_id()),
+                )
+                .await;
+                let _ = session.close().await;
+                let outcome = exchange
+                    .map_err(|_| "session timed out".to_string())?
+                    .map_err(|e| e.to_string())?;
+                let applied = outcome.applied_note_ids.len();
+                self.publish(outcome, scope).await;
+                Ok(applied)
+            })
+            .await
+            .unwrap_or_else(|| Err("group was replaced".to_string()))
+    }
+
+    async fn answer(self: Arc<Self>, mut session: Box<dyn TransportSession>) {
+        let peer = session.peer();
+        let fence = self.fence.lock().await.clone();
+        let scope = fence.scope();
+        let ran = fence
+            .run_if_current(async {
+                let exchange = tokio::time::timeout(
+                    SESSION_TIMEOUT,
+                    answer_exchange(&mut *session, &*self.fan_out.engine, scope.group_id()),
+                )
+                .await;
+                let _ = session.close().await;
+                match exchange {
+                    Ok(Ok(outcome)) => self.publish(outcome, scope).await,
+                    Ok(Err(e)) => tracing::debug!("lan sync answering {peer}: {e}"),
+                    Err(_) => tracing::debug!("lan sync answering {peer}: session timed out"),
+                }
+            })
+            .await;
+        if ran.is_none() {
+            tracing::debug!("lan sync: refused {peer}; captured group was replaced");
+        }
+    }
+
+    /// Surface imported edits: reindex + notify web, then re-broadcast the
+    /// exact applied bytes to live device sockets (as the relay tick does).
+    async fn publish(&self, outcome: SessionOutcome, scope: GroupScope) {
+        let f = &self.fan_out;
+        for note_id in &outcome.applied_note_ids {
+            crate::routes::ws::emit_note_updated(
+                &*f.engine, &f.store, &f.index, &f.ws_tx, *note_id, true, None,
+            )
+            .await;
+        }
+        crate::routes::ws::emit_scope_evictions(
+            &*f.engine,
+            &f.store,
+            &f.index,
+            &f.ws_tx,
+            f.engine.take_scope_evictions().await,
+        )
+        .await;
+        if !outcome.applied_updates.is_empty() {
+            if let Ok(frame) = encode_loro_relay_payload(&outcome.applied_updates) {
+                let _ = f.ws_delta_tx.send(WsDelta {
+                    origin: None,
+                    source_group: Some(scope),
+                    frame,
+                });
+            }
+        }
+    }
+}
+
+/// Dialer side of a session (steps 1 and 3).
+async fn dial_exchange(
+    session: &mut dyn TransportSession,
+    engine: &dyn SyncEngine,
+    group: GroupId,
+) -> SyncResult<SessionOutcome> {
+    let ours = local_versions(engine).await;
+    send(session, engine, group, LanMessage::Versions(ours.clone())).await?;
+    let LanMessage::Versions(theirs) = recv(session).await? else {
+        return Err(unexpected("versions"));
+    };
+    let outcome = receive_updates(session, engine).await?;
+    send_updates(session, engine, group, &ours, theirs).await?;
+    Ok(outcome)
+}
+
+/// Listener side of a session (step 2, then the dialer's updates).
+async fn answer_exchange(
+    session: &mut dyn TransportSession,
+    engine: &dyn SyncEngine,
+    group: GroupId,
+) -> SyncResult<SessionOutcome> {
+    let LanMessage::Versions(theirs) = recv(session).await? else {
+        return Err(unexpected("versions"));
+    };
+    let ours = local_versions(engine).await;
+    send(session, engine, group, LanMessage::Versions(ours.clone())).await?;
+    send_updates(session, engine, group, &ours, theirs).await?;
+    receive_updates(session, engine).await
+}
+
+async fn local_versions(engine: &dyn SyncEngine) -> Vec<([u8; 16], Vec<u8>)> {
+    let mut versions = Vec::new();
+    for note_id in engine.tracked_note_ids().await {
+        if let Some(version) = engine.doc_version(note_id).await {
+            versions.push((note_id, version));
+        }
+    }
+    versions
+}
+
+/// Send every note whose version differs from the peer's — since the
+/// peer's version, or in full when it lacks the note — then `Done`.
+async fn send_updates(
+    session: &mut dyn TransportSession,
+    engine: &dyn SyncEngine,
+    group: GroupId,
+    ours: &[([u8; 16], Vec<u8>)],
+    theirs: Vec<([u8; 16], Vec<u8>)>,
+) -> SyncResult<()> {
+    let theirs: HashMap<[u8; 16], Vec<u8>> = theirs.into_iter().collect();
+    let requests: Vec<([u8; 16], Option<Vec<u8>>)> = ours
+        .iter()
+        .filter(|(note_id, version)| theirs.get(note_id) != Some(version))
+        .map(|(note_id, _)| (*note_id, theirs.get(note_id).cloned()))
+        .collect();
+    if !requests.is_empty() {
+        let updates = engine
+            .export_doc_updates_with_versions(&requests)
+            .await
+            .into_iter()
+            .map(|e| (e.note_id, e.update_bytes, e.version))
+            .collect();
+        for (payload, _) in pack_loro_relay_batches(updates, MAX_RELAY_PLAINTEXT_BYTES) {
+            let body = encode_loro_relay_payload(&payload)?;
+            send(session, engine, group, LanMessage::Updates(body)).await?;
+        }
+    }
+    send(session, engine, group, LanMessage::Done).await
+}
+
+/// Import the peer's `Updates` until its `Done`.
+async fn receive_updates(
+    session: &mut dyn TransportSession,
+    engine: &dyn SyncEngine,
+) -> SyncResult<SessionOutcome> {
+    let mut outcome = SessionOutcome::default();
+    loop {
+        let body = match recv(session).await? {
+            LanMessage::Updates(body) => body,
+            LanMessage::Done => return Ok(outcome),
+            LanMessage::Versions(_) => return Err(unexpected("updates")),
+        };
+        let Some(updates) = decode_loro_relay_payload(&body)? else {
+            return Err(SyncError::Protocol(
+                "LAN updates frame is not a TLR2 payload".to_string(),
+            ));
+        };
+        let pairs: Vec<([u8; 16], Vec<u8>)> = updates
+            .into_iter()
+            .map(|u| (u.doc, u.update_bytes))
+            .collect();
+        let report = engine.apply_relay_updates(&pairs).await;
+        if !report.failed.is_empty() || !report.pending.is_empty() {
+            tracing::debug!(
+                "lan sync: {} failed, {} pending a causal gap (the relay heals both)",
+                report.failed.len(),
+                report.pending.len()
+            );
+        }
+        for (doc, update_bytes) in pairs {
+            if !report.applied.contains(&doc) {
+                continue;
+            }
+            if !outcome.applied_note_ids.contains(&doc) {
+                outcome.applied_note_ids.push(doc);
+            }
+            outcome
+                .applied_updates
+                .push(LoroDocUpdate { doc, update_bytes });
+        }
+    }
+}
+
+async fn send(
+    session: &mut dyn TransportSession,
+    engine: &dyn SyncEngine,
+    group: GroupId,
+    message: LanMessage,
+) -> SyncResult<()> {
+    session
+        .send(SyncEnvelope {
+            from_device: engine.device(),
+            to_group: group,
+            nonce: [0u8; 24],
+            ciphertext: postcard::to_allocvec(&message)?,
+        })
+        .await
+}
+
+async fn recv(session: &mut dyn TransportSession) -> SyncResult<LanMessage> {
+    match session.recv().await? {
+        Some(envelope) => Ok(postcard::from_bytes(&envelope.ciphertext)?),
+        None => Err(SyncError::Transport(
+            "peer closed the LAN session mid-exchange".to_string(),
+        )),
+    }
+}
+
+fn unexpected(wanted: &str) -> SyncError {
+    SyncError::Protocol(format!("LAN sync expected {wanted} from the peer"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tesela_sync::{GroupKey, Hlc, LoroEngine, OpPayload};
+
+    fn group() -> GroupIdentity {
+        GroupIdentity {
+            group_id: GroupId::from_bytes([0x71; 16]),
+            group_key: GroupKey::from_bytes([0x72; 32]),
+        }
+    }
+
+    async fn engine_with_note(device: DeviceId, slug: &str, body: &str) -> LoroEngine {
+        let engine = LoroEngine::new(device, Arc::new(Hlc::new(device)));
+        engine
+            .record_local(OpPayload::NoteUpsert {
+                note_id: tesela_core::stable_uuid_from_slug(slug),
+                display_alias: Some(slug.into()),
+                title: slug.into(),
+                content: body.into(),
+                created_at_millis: 1,
+            })
+            .await
+            .unwrap();
+        engine
+    }
+
+    #[tokio::test]
+    async fn one_session_converges_both_devices() {
+        let g = group();
+        let (a, b) = (
+            DeviceId::from_bytes([0xa7; 16]),
+            DeviceId::from_bytes([0xb7; 16]),
+        );
+        let dialer = engine_with_note(
+            a,
+            "from-a",
+            "- dialed <!-- bid:07070707-0707-0707-0707-070707070707 -->\n",
+        )
+        .await;
+        let listener = engine_with_note(
+            b,
+            "from-b",
+            "- answered <!-- bid:08080808-0808-0808-0808-080808080808 -->\n",
+        )
+        .await;
+        let loopback = SocketAddr::from(([127, 0, 0, 1], 0));
+        let ta = LanTransport::bind(a, &g, loopback).await.unwrap();
+        let tb = LanTransport::bind(b, &g, loopback).await.unwrap();
+        ta.add_peer(b, tb.local_addr());
+
+        let mut inbound = tb.incoming();
+        let mut out = ta.open(TransportTarget::Peer(b)).await.unwrap();
+        let mut answering = inbound.next().await.unwrap();
+        let (dialed, answered) = tokio::join!(
+            dial_exchange(&mut *out, &dialer, g.group_id),
+            answer_exchange(&mut *answering, &listener, g.group_id),
+        );
+
+        let (from_a, from_b) = (
+            tesela_core::stable_uuid_from_slug("from-a"),
+            tesela_core::stable_uuid_from_slug("from-b"),
+        );
+        assert!(dialed.unwrap().applied_note_ids.contains(&from_b));
+        assert!(answered.unwrap().applied_note_ids.contains(&from_a));
+        for note in [from_a, from_b] {
+            assert_eq!(
+                dialer.render_note_full(note).await,
+                listener.render_note_full(note).await
+            );
+        }
+
+        // Converged: a second session resends neither note.
+        let mut out = ta.open(TransportTarget::Peer(b)).await.unwrap();
+        let mut answering = inbound.next().await.unwrap();
+        let (dialed, answered) = tokio::join!(
+            dial_exchange(&mut *out, &dialer, g.group_id),
+            answer_exchange(&mut *answering, &listener, g.group_id),
+        );
+        let (dialed, answered) = (dialed.unwrap(), answered.unwrap());
+        for note in [from_a, from_b] {
+            assert!(!dialed.applied_note_ids.contains(&note));
+            assert!(!answered.applied_note_ids.contains(&note));
+        }
+    }
+}
@@ -7,12 +7,21 @@
 pub mod asr_engine;
 pub mod backup_scheduler;
 pub mod error;
+pub mod group_rotation;
+pub mod lan_sync;
+mod metrics;
 pub mod notifications;
+pub mod oplog_retention;
 pub mod presence_relay;
 pub mod reminders;
 pub mod routes;
+pub mod shared_spaces;
 pub mod state;
+pub mod sync_health;
 pub mod sync_relay;
+pub mod systemd;
+pub mod version_retention;
+pub mod webhooks;
 
 use anyhow::Result;
 use std::{
@@ -47,6 +56,10 @@ use state::{AppState, WsEvent};
 /// from `--mosaic` or the cwd-walk — needs to be passed explicitly.
 pub struct ServeConfig {
     pub mosaic: PathBuf,
+    /// Listener inherited through systemd socket activation. The bin claims
+    /// it in `main` before any runtime thread exists (see
+    /// [`systemd::take_listener`]); `None` binds [`resolve_bind_addr`].
+    pub listener: Option<std::net::TcpListener>,
 }
 
 impl ServeConfig {
@@ -66,7 +79,16 @@ impl ServeConfig {
             }
             None => find_mosaic()?,
         };
-        Ok(Self { mosaic })
+        Ok(Self {
+            mosaic,
+            listener: None,
+        })
+    }
+
+    /// Serve on an already-bound listener instead of binding one.
+    pub fn with_listener(mut self, listener: Option<std::net::TcpListener>) -> Self {
+        self.listener = listener;
+        self
     }
 
     /// `resolve(None)` — the env/cwd-driven default.
@@ -108,6 +130,7 @@ pub async fn serve(
     }
 
     let mosaic = config.mosaic;
+    let activated = config.listener;
 
     // Single-writer guard: only ONE tesela-server may write a mosaic at a time.
     // Held for the whole process lifetime (dropped on return / released by the
@@ -285,12 +308,6 @@ pub async fn serve(
         note_event_tx.clone(),
     );
 
-    // Phase 12.3 — periodic deadline/scheduled scanner. Fires WS events
-    // that the web client converts to desktop notifications.
-    let notifier = Arc::new(notifications::Notifier::new());
-    let store_for_notify: Arc<dyn NoteStore> = Arc::clone(&store) as Arc<dyn NoteStore>;
-    notifications::start(Arc::clone(&notifier), store_for_notify, ws_tx.clone());
-
     let mosaic_for_shutdown = mosaic.clone();
     let index_for_shutdown = Arc::clone(&index);
     let backup_cfg_for_shutdown = config.backup.clone();
@@ -347,8 +364,41 @@ pub async fn serve(
         warn!("relations: startup projection rebuild failed: {error}");
     }
 
-    let addr = resolve_bind_addr();
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    // Phase 12.3 — periodic deadline/scheduled scanner. Fires WS events
+    // that the web client converts to desktop notifications. Delivery state
+    // lives in the engine's synced notification doc, so the scanner starts
+    // once the engine is open.
+    let notifier = Arc::new(notifications::Notifier::new());
+    let store_for_notify: Arc<dyn NoteStore> = Arc::clone(&store) as Arc<dyn NoteStore>;
+    notifications::start(
+        Arc::clone(&notifier),
+        Arc::clone(&sync_engine),
+        store_for_notify,
+        ws_tx.clone(),
+    );
+
+    // Outbound webhooks ride the same event bus as WebSocket clients.
+    let webhooks = Arc::new(webhooks::Webhooks::new(config.webhooks.clone()));
+    webhooks::start(
+        Arc::clone(&webhooks),
+        &ws_tx,
+        Arc::clone(&index),
+        Arc::clone(&sync_engine),
+    );
+
+    // Under `tesela-server.socket` (Linux `tesela install
+    // --socket-activation`) systemd already holds the port; adopt it.
+    let listener = match activated {
+        Some(activated) => {
+            activated.set_nonblocking(true)?;
+            tokio::net::TcpListener::from_std(activated)?
+        }
+        None => tokio::net::TcpListener::bind(resolve_bind_addr()).await?,
+    };
+    let addr = listener
+        .local_addr()
+        .map(|a| a.to_string())
+        .unwrap_or_else(|_| resolve_bind_addr());
     let bound_port = listener.local_addr().map(|a| a.port()).unwrap_or(7474);
     // Hand the embedder the real bound address (the port is freshly allocated
     // when binding `:0`), so it can build its webview URL while serve runs.
@@ -374,24 +424,41 @@ pub async fn serve(
 
     // Phase 2.1 — mDNS-based LAN discovery. Each tesela-server instance
     // advertises itself and listens for siblings, surfacing them through
-    // `GET /sync/peer/discovered`. Failure here is non-fatal: manually
-    // configured peers still work.
-    let lan_discovery = if std::env::var("TESELA_DISABLE_MDNS").is_ok() {
+    // `GET /sync/peer/discovered`. The LAN sync listener is bound first so
+    // its port rides in the TXT record; `lan_sync` starts after relay
+    // bring-up. Failure here is non-fatal: the relay still syncs.
+    let (lan_discovery, lan_transport) = if std::env::var("TESELA_DISABLE_MDNS").is_ok() {
         info!("tesela-sync: mDNS discovery disabled via TESELA_DISABLE_MDNS");
-        None
+        (None, None)
     } else {
         let device = sync_engine.device();
-        match LanDiscovery::start(device, &display_name, bound_port) {
+        // The desktop embed shares this device id with the server, so like
+        // the relay it stays out of LAN sync (see `bring_up_relay_if_configured`).
+        let lan_transport = if std::env::var_os("TESELA_DISABLE_RELAY").is_some() {
+            None
+        } else {
+            match lan_sync::bind(device, &mosaic_for_shutdown).await {
+                Ok(t) => Some(t),
+                Err(e) => {
+                    warn!("tesela-sync: LAN sync listener failed to bind: {e}");
+                    None
+                }
+            }
+        };
+        let lan_port = lan_transport.as_ref().map(|t| t.local_addr().port());
+        match LanDiscovery::start_with_lan_port(device, &display_name, bound_port, lan_port) {
             Ok(d) => {
                 info!(
-                    "tesela-sync: mDNS advertising as {} on port {}",
-                    display_name, bound_port
+                    "tesela-sync: mDNS advertising as {} on port {} (LAN sync port {:?})",
+                    display_name, bound_port, lan_port
                 );
-                Some(Arc::new(d))
+                let d = Arc::new(d);
+                let lan_transport = lan_transport.map(|t| t.with_discovery(Arc::clone(&d)));
+                (Some(d), lan_transport)
             }
             Err(e) => {
                 warn!("tesela-sync: mDNS discovery failed to start: {e}");
-                None
+                (None, None)
             }
         }
     };
@@ -413,6 +480,8 @@ pub async fn serve(
         auto_sync,
         sync_engine,
         lan_discovery,
+        // Started below, once the relay membership is known.
+        lan_sync: None,
         group_identity,
         group_transition_pending_restart: std::sync::atomic::AtomicBool::new(false),
         display_name,
@@ -421,8 +490,30 @@ pub async fn serve(
         // Brought up below if config has `[sync.relay] url`.
         relay: None,
         backup_status: backup_status.clone(),
+        notifier,
+        webhooks,
+        shared_spaces: Arc::new(shared_spaces::SharedSpaces::default()),
+        sync_incidents: Arc::new(tesela_sync::IncidentLog::for_mosaic(&mosaic_for_shutdown)),
     };
-    let app_state = bring_up_relay_if_configured(app_state, &mosaic).await;
+    let mut app_state = bring_up_relay_if_configured(app_state, &mosaic).await;
+    if let Some(transport) = lan_transport {
+        app_state.lan_sync = Some(lan_sync::start(&app_state, transport).await);
+    }
+    // Shared spaces run their own relay loops; the desktop embed stays out
+    // for the same reason it skips the group relay.
+    if std::env::var_os("TESELA_DISABLE_RELAY").is_none() {
+        shared_spaces::start_all(&app_state, relay_poll_interval(&mosaic)).await;
+    }
+    sync_health::start(
+        Arc::clone(&app_state.sync_engine),
+        Arc::clone(&app_state.sync_incidents),
+    );
+    oplog_retention::start(
+        Arc::clone(&app_state.index),
+        app_state.sync_engine.device(),
+        &load_config(&mosaic).sync.retention,
+    );
+    version_retention::start(Arc::clone(&app_state.index), &load_config(&mosaic).history);
 
     // Saved-views registry (spec 2026-06-10; adversarial-review fix):
     // idempotently seed the built-in views (the Inbox) AFTER relay
@@ -452,13 +543,17 @@ pub async fn serve(
         backup_cfg_for_shutdown.clone(),
     );
 
-    let router = routes::build(app_state);
+    let router = routes::build(app_state, config.server.metrics);
 
     info!("tesela-server listening on http://{}", addr);
+    // `Type=notify` unit: report ready only once requests can be served.
+    systemd::notify("READY=1");
+    systemd::spawn_watchdog();
 
     axum::serve(listener, router)
         .with_graceful_shutdown(shutdown)
         .await?;
+    systemd::notify("STOPPING=1");
 
     indexer_handle.stop().await;
     // Phase 13.A.4 — auto-backup on clean shutdown. Runs after axum has
@@ -808,12 +903,37 @@ async fn bring_up_relay_if_configured(
     };
     let ident = state.group_identity.read().await.clone();
     let device = state.sync_engine.device();
-    let client = std::sync::Arc::new(tesela_sync::transport::relay::RelayClient::new(
-        url.clone(),
-        ident.group_id,
+    // Key epochs (group key rotation): the client seals under the current
+    // epoch and still opens rows + snapshots from retired ones. A mosaic
+    // that never rotated is epoch 0 — byte-identical to the plain client.
+    let membership = match group_rotation::GroupMembership::load(
+        mosaic,
+        std::sync::Arc::clone(&state.index),
+        &ident,
         device,
-        ident.group_key.clone(),
-    ));
+        state.display_name.clone(),
+    )
+    .await
+    {
+        Ok(m) => Some(m),
+        Err(e) => {
+            tracing::warn!("relay: group key epochs unavailable, sealing at epoch 0: {e}");
+            None
+        }
+    };
+    let client = std::sync::Arc::new(match membership.as_ref() {
+        Some(m) => tesela_sync::transport::relay::RelayClient::with_keyring(
+            url.clone(),
+            device,
+            &m.keyring().await,
+        ),
+        None => tesela_sync::transport::relay::RelayClient::new(
+            url.clone(),
+            ident.group_id,
+            device,
+            ident.group_key.clone(),
+        ),
+    });
     let mut persisted = sync_relay::RelayState::load(mosaic).await;
     // Scope the persisted cursors to the CURRENT (relay, group) identity:
     // a relay migration or a group re-pair means a fresh seq namespace, so
@@ -835,6 +955,7 @@ async fn bring_up_relay_if_configured(
         client: client.clone(),
         state: std::sync::Arc::new(tokio::sync::RwLock::new(persisted)),
         mosaic_root: mosaic.to_path_buf(),
+        membership,
     };
 
     // Attempt one-shot bring-up; failure is recoverable on the next tick.
@@ -846,6 +967,11 @@ async fn bring_up_relay_if_configured(
         drop(s);
     } else {
         tracing::info!("relay: registered + verified at {}", url);
+        if let Some(membership) = handle.membership.as_ref() {
+            if let Err(e) = membership.announce(&handle.client).await {
+                tracing::warn!("relay: member announcement: {e}");
+            }
+        }
         // Fresh / long-offline restore: import the relay's compacted snapshots
         // before the first poll, so a device whose ops the relay already GC'd
         // still converges (the subsequent `?since=` poll collects the tail).
@@ -865,22 +991,21 @@ async fn bring_up_relay_if_configured(
         }
     }
 
-    // Spawn the periodic tick. Single task; runs alongside the LAN
-    // peer-sync daemon. We get the poll interval from config or fall
-    // back to the module default.
-    let poll_interval = load_config(mosaic)
-        .sync
-        .relay
-        .map(|r| std::time::Duration::from_millis(r.poll_interval_ms))
-        .unwrap_or(sync_relay::DEFAULT_POLL_INTERVAL);
+    // Spawn the tick loop. Single task; runs alongside the LAN
+    // peer-sync daemon. Between ticks it parks on the relay's live op
+    // stream, so a remote edit triggers a tick right away; the poll
+    // interval (config or module default) bounds each wait, which keeps
+    // outbound flushing on cadence and is the whole schedule against a
+    // relay without `/stream`.
+    let poll_interval = relay_poll_interval(mosaic);
     let tick_handle = handle.clone();
     let tick_engine = state.sync_engine.clone();
-    let tick_ident = ident.clone();
-    let tick_fence = state::GroupRuntimeFence::capture(
+    let mut tick_ident = ident.clone();
+    let mut tick_fence = state::GroupRuntimeFence::capture(
         std::sync::Arc::clone(&state.group_identity),
         &tick_ident,
     );
-    let tick_scope = tick_fence.scope();
+    let mut tick_scope = tick_fence.scope();
     // Instant-multidevice (Phase A, spec finding #4): give the relay loop
     // the WS fan-out handles so a relay-originated edit notifies web
     // (`ws_tx`) and reaches live device sockets (`ws_delta_tx`). Cloning
@@ -892,11 +1017,53 @@ async fn bring_up_relay_if_configured(
     let tick_ws_delta_tx = state.ws_delta_tx.clone();
     let tick_store = std::sync::Arc::clone(&state.store);
     let tick_index = std::sync::Arc::clone(&state.index);
+    let tick_url = url.clone();
+    let tick_current = std::sync::Arc::clone(&state.group_identity);
     tokio::spawn(async move {
-        let mut ticker = tokio::time::interval(poll_interval);
-        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // Highest seq the stream has announced. Waiting from here rather
+        // than from the cursor keeps a held cursor (failed apply) from
+        // re-waking the loop on the same notice.
+        let mut announced = 0i64;
+        let mut first = true;
         loop {
-            ticker.tick().await;
+            // A key rotation accepted by the last tick (or issued through
+            // the rotate route) moves the group to a new epoch: follow it
+            // instead of stopping, and restart the presence bridge, whose
+            // frames are sealed under the group key.
+            if let Some(membership) = tick_handle.membership.as_ref() {
+                let rotated = membership.keyring().await.identity();
+                if !tick_scope.matches(&rotated) {
+                    if !tick_fence.follow_rotation(&rotated).await {
+                        tracing::info!(
+                            "relay tick: captured group was replaced; stopping old daemon"
+                        );
+                        break;
+                    }
+                    tick_scope = tick_fence.scope();
+                    // The tick stopped at the rotation record; re-poll
+                    // the rest under the new key without waiting.
+                    first = true;
+                    tick_ident = rotated;
+                    presence_relay::spawn(
+                        &tick_url,
+                        tick_ident.group_id,
+                        device,
+                        tick_ident.group_key.clone(),
+                        tick_ws_delta_tx.clone(),
+                        std::sync::Arc::clone(&tick_current),
+                    );
+                }
+            }
+            if !std::mem::take(&mut first) {
+                let cursor = tick_handle.state.read().await.inbound_cursor;
+                if let Some(seq) = tick_handle
+                    .client
+                    .wait_for_ops(cursor.max(announced), poll_interval)
+                    .await
+                {
+                    announced = seq;
+                }
+            }
             let ran = tick_fence
                 .run_if_current(async {
                     match sync_relay::tick(&*tick_engine, &tick_ident, &tick_handle).await {
@@ -927,6 +1094,14 @@ async fn bring_up_relay_if_configured(
                                 )
                                 .await;
                             }
+                            routes::ws::emit_scope_evictions(
+                                &*tick_engine,
+                                &tick_store,
+                                &tick_index,
+                                &tick_ws_tx,
+                                outcome.scope_evictions,
+                            )
+                            .await;
                             // Re-broadcast the EXACT applied delta bytes to live
                             // device sockets so their Loro docs converge without
                             // waiting on their own poll. `origin: None` — fan out to
@@ -958,10 +1133,11 @@ async fn bring_up_relay_if_configured(
         }
     });
 
-    // Presence bridge (Phase 3b, Stage 2): a separate long-lived WS to the CF
-    // relay's /presence/ws. It observes locally-originated PRES frames on
-    // ws_delta_tx (origin = Some) → seals → relay; opens relay-broadcast frames
-    // → fans out on ws_delta_tx (origin = None). Same (group, device, key, url)
+    // Presence bridge (Phase 3b, Stage 2): a separate long-lived WS to the
+    // relay's /presence/ws (CF Worker or self-hosted tesela-relay). It
+    // observes locally-originated PRES frames on ws_delta_tx (origin = Some)
+    // → seals → relay; opens relay-broadcast frames → fans out on
+    // ws_delta_tx (origin = None). Same (group, device, key, url)
     // identity the tick uses. Independent of the poll/produce tick — presence is
     // ephemeral and never touches the engine.
     presence_relay::spawn(
@@ -977,6 +1153,16 @@ async fn bring_up_relay_if_configured(
     state
 }
 
+/// `[sync.relay] poll_interval_ms`, shared by the group relay loop and the
+/// shared-space loops.
+pub(crate) fn relay_poll_interval(mosaic: &std::path::Path) -> std::time::Duration {
+    load_config(mosaic)
+        .sync
+        .relay
+        .map(|r| std::time::Duration::from_millis(r.poll_interval_ms))
+        .unwrap_or(sync_relay::DEFAULT_POLL_INTERVAL)
+}
+
 fn load_config(mosaic: &std::path::Path) -> Config {
     let path = mosaic.join(".tesela").join("config.toml");
     if !path.exists() {
@@ -1156,6 +1342,7 @@ mod tests {
             auto_sync: Arc::new(reminders::auto::AutoSync::new()),
             sync_engine: Arc::new(engine) as Arc<dyn tesela_sync::SyncEngine>,
             lan_discovery: None,
+            lan_sync: None,
             group_identity: Arc::new(RwLock::new(tesela_sync::GroupIdentity {
                 group_id,
                 group_key: tesela_sync::GroupKey::from_bytes([0x71; 32]),
@@ -1168,11 +1355,15 @@ mod tests {
             backup_status: crate::backup_scheduler::BackupStatusHandle::new(
                 crate::backup_scheduler::SchedulerConfig::from_env(),
             ),
+            notifier: Arc::new(crate::notifications::Notifier::new()),
+            webhooks: Arc::new(crate::webhooks::Webhooks::new(Vec::new())),
+            shared_spaces: Arc::new(crate::shared_spaces::SharedSpaces::default()),
+            sync_incidents: Arc::new(tesela_sync::IncidentLog::for_mosaic(&mosaic)),
         };
         app_state
             .group_transition_pending_restart
             .store(true, std::sync::atomic::Ordering::Release);
-        let router = routes::build(app_state);
+        let router = routes::build(app_state, false);
         let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
         let addr = listener.local_addr().unwrap();
         tokio::spawn(async move {
@@ -1398,6 +1589,7 @@ mod tests {
             auto_sync: Arc::new(reminders::auto::AutoSync::new()),
             sync_engine: Arc::new(server_engine) as Arc<dyn tesela_sync::SyncEngine>,
             lan_discovery: None,
+            lan_sync: None,
             group_identity,
             group_transition_pending_restart: std::sync::atomic::AtomicBool::new(false),
             display_name: "test".into(),
@@ -1407,9 +1599,13 @@ mod tests {
             backup_status: crate::backup_scheduler::BackupStatusHandle::new(
                 crate::backup_scheduler::SchedulerConfig::from_env(),
             ),
+            notifier: Arc::new(crate::notifications::Notifier::new()),
+            webhooks: Arc::new(crate::webhooks::Webhooks::new(Vec::new())),
+            shared_spaces: Arc::new(crate::shared_spaces::SharedSpaces::default()),
+            sync_incidents: Arc::new(tesela_sync::IncidentLog::for_mosaic(&mosaic)),
         };
         let server_engine_handle = Arc::clone(&app_state.sync_engine);
-        let router = routes::build(app_state);
+        let router = routes::build(app_state, false);
 
         // ── Serve on an ephemeral port ────────────────────────────────────
         let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -2101,6 +2297,7 @@ mod tests {
             auto_sync: Arc::new(reminders::auto::AutoSync::new()),
             sync_engine: Arc::new(server_engine) as Arc<dyn tesela_sync::SyncEngine>,
             lan_discovery: None,
+            lan_sync: None,
             group_identity,
             group_transition_pending_restart: std::sync::atomic::AtomicBool::new(false),
             display_name: "test".into(),
@@ -2110,9 +2307,13 @@ mod tests {
             backup_status: crate::backup_scheduler::BackupStatusHandle::new(
                 crate::backup_scheduler::SchedulerConfig::from_env(),
             ),
+            notifier: Arc::new(crate::notifications::Notifier::new()),
+            webhooks: Arc::new(crate::webhooks::Webhooks::new(Vec::new())),
+            shared_spaces: Arc::new(crate::shared_spaces::SharedSpaces::default()),
+            sync_incidents: Arc::new(tesela_sync::IncidentLog::for_mosaic(&mosaic)),
         };
         let server_engine_handle = Arc::clone(&app_state.sync_engine);
-        let router = routes::build(app_state);
+        let router = routes::build(app_state, false);
 
         // Sanity: the server's authoritative doc holds the HTTP edit BEFORE the
         // device's stale push — proves A is genuinely at risk of reversion.
@@ -2318,6 +2519,7 @@ mod tests {
             auto_sync: Arc::new(reminders::auto::AutoSync::new()),
             sync_engine: Arc::new(server_engine) as Arc<dyn tesela_sync::SyncEngine>,
             lan_discovery: None,
+            lan_sync: None,
             group_identity,
             group_transition_pending_restart: std::sync::atomic::AtomicBool::new(false),
             display_name: "test".into(),
@@ -2327,9 +2529,13 @@ mod tests {
             backup_status: crate::backup_scheduler::BackupStatusHandle::new(
                 crate::backup_scheduler::SchedulerConfig::from_env(),
             ),
+            notifier: Arc::new(crate::notifications::Notifier::new()),
+            webhooks: Arc::new(crate::webhooks::Webhooks::new(Vec::new())),
+            shared_spaces: Arc::new(crate::shared_spaces::SharedSpaces::default()),
+            sync_incidents: Arc::new(tesela_sync::IncidentLog::for_mosaic(&mosaic)),
         };
         let server_engine_handle = Arc::clone(&app_state.sync_engine);
-        let router = routes::build(app_state);
+        let router = routes::build(app_state, false);
 
         let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
         let addr = listener.local_addr().unwrap();
@@ -2460,6 +2666,7 @@ mod tests {
             auto_sync: Arc::new(reminders::auto::AutoSync::new()),
             sync_engine: Arc::new(engine) as Arc<dyn tesela_sync::SyncEngine>,
             lan_discovery: None,
+            lan_sync: None,
             group_identity,
             group_transition_pending_restart: std::sync::atomic::AtomicBool::new(false),
             display_name: "test".into(),
@@ -2469,8 +2676,12 @@ mod tests {
             backup_status: crate::backup_scheduler::BackupStatusHandle::new(
                 crate::backup_scheduler::SchedulerConfig::from_env(),
             ),
+            notifier: Arc::new(crate::notifications::Notifier::new()),
+            webhooks: Arc::new(crate::webhooks::Webhooks::new(Vec::new())),
+            shared_spaces: Arc::new(crate::shared_spaces::SharedSpaces::default()),
+            sync_incidents: Arc::new(tesela_sync::IncidentLog::for_mosaic(&mosaic)),
         };
-        let router = routes::build(app_state);
+        let router = routes::build(app_state, false);
         let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
         let addr = listener.local_addr().unwrap();
         tokio::spawn(async move {
@@ -8,7 +8,9 @@ use anyhow::Result;
 use clap::Parser;
 use std::path::PathBuf;
 
-use tesela_server::{serve, spawn_parent_death_watchdog, wait_for_shutdown_signal, ServeConfig};
+use tesela_server::{
+    serve, spawn_parent_death_watchdog, systemd, wait_for_shutdown_signal, ServeConfig,
+};
 
 #[derive(Debug, Parser)]
 #[command(
@@ -23,18 +25,25 @@ struct Args {
     mosaic: Option<PathBuf>,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
     tracing_subscriber::fmt()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
         .init();
 
+    // Claim a socket-activated listener (and clear `LISTEN_*`) while the
+    // process is still single-threaded: mutating the environment races
+    // with any other thread reading it.
+    let listener = systemd::take_listener();
+
     // Desktop child-spawn embed (legacy path): exit if our parent disappears,
     // so an orphaned server never becomes a second writer. No-op unless
     // TESELA_EXIT_WITH_PARENT is set (the in-process embed never sets it).
     spawn_parent_death_watchdog();
 
     let args = Args::parse();
-    let config = ServeConfig::resolve(args.mosaic)?;
-    serve(config, wait_for_shutdown_signal(), |_| {}).await
+    let config = ServeConfig::resolve(args.mosaic)?.with_listener(listener);
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(serve(config, wait_for_shutdown_signal(), |_| {}))
 }
@@ -0,0 +1,192 @@
+//! Prometheus metrics for `GET /metrics`. Process-wide rather than on
+//! `AppState`: the relay tick loop runs as a detached task that only
+//! holds engine + handle clones, and a server process serves exactly
+//! one mosaic at a time.
+
+use std::sync::LazyLock;
+use std::time::Instant;
+
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use prometheus_client::encoding::text::encode;
+use prometheus_client::encoding::EncodeLabelSet;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::Family;
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::metrics::histogram::{exponential_buckets, Histogram};
+use prometheus_client::registry::Registry;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+pub(crate) struct RequestLabels {
+    pub(crate) method: String,
+    /// The matched route template (`/notes/{id}`), never the raw path.
+    pub(crate) route: String,
+    pub(crate) status: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+pub(crate) struct RouteLabels {
+    pub(crate) method: String,
+    pub(crate) route: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+pub(crate) struct DepositLabels {
+    /// `heal` (inert per-note deposit after a broadcast) or
+    /// `compaction` (the periodic watermark-advancing deposit).
+    pub(crate) kind: &'static str,
+}
+
+type LatencyFamily = Family<RouteLabels, Histogram, fn() -> Histogram>;
+
+/// 1 ms … ~33 s, doubling.
+fn latency_histogram() -> Histogram {
+    Histogram::new(exponential_buckets(0.001, 2.0, 16))
+}
+
+pub(crate) struct Metrics {
+    registry: Registry,
+    pub(crate) http_requests: Family<RequestLabels, Counter>,
+    pub(crate) http_latency: LatencyFamily,
+    pub(crate) relay_ops_in: Counter,
+    pub(crate) relay_ops_out: Counter,
+    pub(crate) relay_apply_latency: Histogram,
+    pub(crate) outbound_strand_alarms: Counter,
+    pub(crate) snapshot_deposits: Family<DepositLabels, Counter>,
+    pub(crate) ws_connections: Gauge,
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// The process-wide metric set.
+pub(crate) fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let mut registry = Registry::with_prefix("tesela");
+        let http_requests = Family::<RequestLabels, Counter>::default();
+        let http_latency: LatencyFamily =
+            Family::new_with_constructor(latency_histogram as fn() -> Histogram);
+        let relay_ops_in = Counter::default();
+        let relay_ops_out = Counter::default();
+        let relay_apply_latency = latency_histogram();
+        let outbound_strand_alarms = Counter::default();
+        let snapshot_deposits = Family::<DepositLabels, Counter>::default();
+        let ws_connections = Gauge::default();
+        registry.register(
+            "http_requests",
+            "HTTP requests by route template, method and status",
+            http_requests.clone(),
+        );
+        registry.register(
+            "http_request_duration_seconds",
+            "HTTP request latency by route template and method",
+            http_latency.clone(),
+        );
+        registry.register(
+            "relay_ops_in",
+            "Peer op envelopes pulled from the relay and applied",
+            relay_ops_in.clone(),
+        );
+        registry.register(
+            "relay_ops_out",
+            "Op envelopes deposited on the relay",
+            relay_ops_out.clone(),
+        );
+        registry.register(
+            "relay_apply_duration_seconds",
+            "Engine apply time per inbound relay envelope",
+            relay_apply_latency.clone(),
+        );
+        registry.register(
+            "outbound_strand_alarms",
+            "Dirty notes that shipped a snapshot fallback instead of an incremental \
+             delta (engine outbound_strand_alarm_count)",
+            outbound_strand_alarms.clone(),
+        );
+        registry.register(
+            "snapshot_deposits",
+            "Snapshot deposits accepted by the relay",
+            snapshot_deposits.clone(),
+        );
+        registry.register(
+            "ws_connections",
+            "Open /ws client sockets",
+            ws_connections.clone(),
+        );
+        Self {
+            registry,
+            http_requests,
+            http_latency,
+            relay_ops_in,
+            relay_ops_out,
+            relay_apply_latency,
+            outbound_strand_alarms,
+            snapshot_deposits,
+            ws_connections,
+        }
+    }
+
+    pub(crate) fn snapshot_deposit(&self, kind: &'static str) {
+        self.snapshot_deposits
+            .get_or_create(&DepositLabels { kind })
+            .inc();
+    }
+
+    /// Prometheus text exposition of every registered metric.
+    pub(crate) fn render(&self) -> String {
+        let mut out = String::new();
+        // Writing into a String can't fail.
+        let _ = encode(&mut out, &self.registry);
+        out
+    }
+}
+
+/// Holds a connection gauge up for as long as the connection lives.
+pub(crate) struct ConnectionGuard(Gauge);
+
+impl ConnectionGuard {
+    pub(crate) fn open(gauge: &Gauge) -> Self {
+        gauge.inc();
+        Self(gauge.clone())
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+/// Request count + latency per matched route. Static-bundle and other
+/// fallback requests are labelled `unmatched`.
+pub(crate) async fn track_http(request: Request, next: Next) -> Response {
+    let method = request.method().as_str().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".into());
+    let started = Instant::now();
+    let response = next.run(request).await;
+    let metrics = metrics();
+    metrics
+        .http_latency
+        .get_or_create(&RouteLabels {
+            method: method.clone(),
+            route: route.clone(),
+        })
+        .observe(started.elapsed().as_secs_f64());
+    metrics
+        .http_requests
+        .get_or_create(&RequestLabels {
+            method,
+            route,
+            status: response.status().as_u16(),
+        })
+        .inc();
+    response
+}
@@ -6,37 +6,110 @@
 //! when the page hasn't been refreshed (the WS reconnect resends nothing,
 //! but the next scan will pick up anything still in-window).
 //!
-//! Dedupe: every fire is keyed by `(block_id, kind, deadline_iso)` and
-//! recorded in an in-memory set so the same window doesn't re-trigger
-//! every minute. Restarting the server resets the set; that's fine —
-//! a duplicate notification right after restart is acceptable, but
-//! flooding the user every minute for 60 minutes isn't.
+//! Dedupe: every fire is keyed by a stable notification id
+//! (`<kind>-<block_id>-<fire_target_unix_secs>`) whose delivery state —
+//! fired, acknowledged, or snoozed until a time — lives in the engine's
+//! synced notification-state doc (`tesela_sync::NOTIFICATIONS_DOC_ID`).
+//! A restart therefore doesn't re-fire anything, and acknowledging a
+//! reminder on one device silences it on every device once the doc syncs.
+//! A snoozed reminder fires again on the first scan after its snooze ends.
+//!
+//! Lead time: `deadline::` reminders default to [`DEADLINE_LEAD_MIN`]
+//! and `scheduled::` reminders to none; a block's `remind::` property
+//! (`15m`, `2h`, `1d`, or bare minutes) overrides both.
 
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
-use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc};
 use tokio::sync::{broadcast, Mutex};
 use tracing::{debug, warn};
 
 use tesela_core::{
     block::parse_blocks, storage::markdown::parse_frontmatter, traits::note_store::NoteStore,
 };
+use tesela_sync::{NotificationStateRecord, SyncEngine};
 
 use crate::state::WsEvent;
 
-/// In-memory dedupe set. Each entry is `block_id|kind|deadline_iso`.
+/// Delivery states stored in [`NotificationStateRecord::status`].
+pub const STATUS_FIRED: &str = "fired";
+pub const STATUS_ACKNOWLEDGED: &str = "acknowledged";
+pub const STATUS_SNOOZED: &str = "snoozed";
+
+/// Settled records (fired or acknowledged, never snoozed into the future)
+/// older than this are dropped from the synced doc on the next scan. Long
+/// past any fire window, so pruning can't cause a re-fire.
+const PRUNE_AFTER_DAYS: i64 = 30;
+
+/// Default snooze when the client doesn't say how long.
+pub const DEFAULT_SNOOZE_MIN: i64 = 10;
+
+/// Serializes reminder delivery-state writes. The state itself lives in
+/// the engine; the mutex keeps a scan that listed state before an
+/// acknowledge from overwriting it with `fired`.
 pub struct Notifier {
-    fired: Mutex<HashSet<String>>,
+    lock: Mutex<()>,
 }
 
 impl Notifier {
     pub fn new() -> Self {
         Self {
-            fired: Mutex::new(HashSet::new()),
+            lock: Mutex::new(()),
         }
     }
+
+    /// Mark a fired reminder as dismissed. `Ok(None)` when no reminder with
+    /// that id has fired on any device.
+    pub async fn acknowledge(
+        &self,
+        engine: &dyn SyncEngine,
+        id: &str,
+    ) -> anyhow::Result<Option<NotificationStateRecord>> {
+        self.transition(engine, id, STATUS_ACKNOWLEDGED, None).await
+    }
+
+    /// Silence a fired reminder until `until`; the scanner fires it again on
+    /// its first tick at or after that moment. `Ok(None)` for an unknown id.
+    pub async fn snooze(
+        &self,
+        engine: &dyn SyncEngine,
+        id: &str,
+        until: DateTime<Utc>,
+    ) -> anyhow::Result<Option<NotificationStateRecord>> {
+        self.transition(engine, id, STATUS_SNOOZED, Some(until.timestamp_millis()))
+            .await
+    }
+
+    async fn transition(
+        &self,
+        engine: &dyn SyncEngine,
+        id: &str,
+        status: &str,
+        snoozed_until_ms: Option<i64>,
+    ) -> anyhow::Result<Option<NotificationStateRecord>> {
+        let _guard = self.lock.lock().await;
+        let known = engine
+            .notification_states_list()
+            .await
+            .into_iter()
+            .any(|r| r.id == id);
+        if !known {
+            return Ok(None);
+        }
+        let record = NotificationStateRecord {
+            id: id.to_string(),
+            status: status.to_string(),
+            snoozed_until_ms,
+            updated_at_ms: Utc::now().timestamp_millis(),
+        };
+        engine
+            .notification_state_upsert(record.clone())
+            .await
+            .map_err(|e| anyhow::anyhow!("notification state write: {e}"))?;
+        Ok(Some(record))
+    }
 }
 
 impl Default for Notifier {
@@ -48,39 +121,62 @@ impl Default for Notifier {
 /// Default lead time in minutes for `deadline::` notifications.
 const DEADLINE_LEAD_MIN: i64 = 60;
 
+/// Longest `remind::` lead accepted: a year. Anything past it is a typo, and
+/// unbounded leads overflow the date arithmetic.
+const MAX_LEAD_MIN: i64 = 366 * 24 * 60;
+
 /// Spawn the periodic scanner. Walks notes every 60 seconds, computes
 /// fire times for any open Task block with a `deadline::` or
 /// `scheduled::` value, and emits WS events for any that crossed
 /// their threshold this tick.
 pub fn start(
     notifier: Arc<Notifier>,
+    engine: Arc<dyn SyncEngine>,
     store: Arc<dyn NoteStore>,
     ws_tx: broadcast::Sender<WsEvent>,
 ) {
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(Duration::from_secs(60));
         interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
-        // Skip the first immediate tick so startup doesn't immediately fire
-        // for tasks that should have notified during the previous run.
+        // Skip the first immediate tick so startup doesn't race the relay
+        // bootstrap that may still be delivering another device's state.
         interval.tick().await;
         loop {
             interval.tick().await;
-            if let Err(e) = scan_once(&notifier, &store, &ws_tx).await {
+            if let Err(e) = scan_once(&notifier, &*engine, &store, &ws_tx, Utc::now()).await {
                 warn!("notification scan failed: {}", e);
             }
         }
     });
 }
 
-/// One pass over all notes. Public so tests can drive it deterministically.
+/// One pass over all notes at `now`. Public so tests can drive it
+/// deterministically.
 pub async fn scan_once(
     notifier: &Notifier,
+    engine: &dyn SyncEngine,
     store: &Arc<dyn NoteStore>,
     ws_tx: &broadcast::Sender<WsEvent>,
+    now: DateTime<Utc>,
 ) -> anyhow::Result<()> {
-    let now = Utc::now();
     let notes = store.list(None, usize::MAX, 0).await?;
-    let mut fired = notifier.fired.lock().await;
+    let _guard = notifier.lock.lock().await;
+    let states: HashMap<String, NotificationStateRecord> = engine
+        .notification_states_list()
+        .await
+        .into_iter()
+        .map(|r| (r.id.clone(), r))
+        .collect();
+    let now_ms = now.timestamp_millis();
+    // A reminder with no record fires when its window is open; a snoozed
+    // one fires once its snooze has elapsed, window or not (the user asked
+    // to be reminded again). Fired/acknowledged records never re-fire.
+    let should_fire = |id: &str, in_window: bool| match states.get(id) {
+        None => in_window,
+        Some(r) if r.status == STATUS_SNOOZED => r.snoozed_until_ms.is_none_or(|t| now_ms >= t),
+        Some(_) => false,
+    };
+    let mut fired = Vec::new();
     for note in notes {
         let body = match parse_frontmatter(&note.content) {
             Ok((_, body)) => body,
@@ -95,53 +191,120 @@ pub async fn scan_once(
                 continue;
             }
             let title = task_title(&block.text);
+            let remind = block.properties.get("remind").and_then(|raw| {
+                let lead = parse_lead(raw);
+                if lead.is_none() {
+                    warn!("notify: ignoring invalid remind:: {raw:?} on {}", block.id);
+                }
+                lead
+            });
 
             if let Some(raw) = block.properties.get("deadline") {
                 if let Some(deadline_dt) = parse_deadline_local(raw) {
-                    let lead = chrono::Duration::minutes(DEADLINE_LEAD_MIN);
-                    let fire_at = deadline_dt - lead;
-                    let key = format!("{}|deadline|{}", block.id, deadline_dt.to_rfc3339());
+                    let lead_minutes = remind.unwrap_or(DEADLINE_LEAD_MIN);
+                    let fire_at = lead_start(deadline_dt, lead_minutes);
+                    let id = notification_id("deadline", &block.id, deadline_dt);
                     // Fire when the notification window opens AND the deadline
                     // hasn't passed yet (a deadline 2h in the past with a 1h
                     // lead is past — surfacing it now is just noise).
-                    if now >= fire_at && now < deadline_dt && !fired.contains(&key) {
-                        fired.insert(key);
+                    let opened = fire_at.is_some_and(|fire_at| now >= fire_at);
+                    if should_fire(&id, opened && now < deadline_dt) {
                         let _ = ws_tx.send(WsEvent::DeadlineApproaching {
+                            notification_id: id.clone(),
                             block_id: block.id.clone(),
                             title: title.clone(),
                             note_id: note.id.as_str().to_string(),
                             deadline_iso: deadline_dt.to_rfc3339(),
-                            lead_minutes: DEADLINE_LEAD_MIN,
+                            lead_minutes,
                         });
                         debug!("notify: deadline approaching {}", block.id);
+                        fired.push(id);
                     }
                 }
             }
 
             if let Some(raw) = block.properties.get("scheduled") {
                 if let Some(scheduled_dt) = parse_deadline_local(raw) {
-                    let key = format!("{}|scheduled|{}", block.id, scheduled_dt.to_rfc3339());
-                    // Scheduled fires at the exact time (no lead). One-minute
-                    // scan granularity means the fire window is "any tick
-                    // after the scheduled moment but within ~10 minutes."
-                    let window_end = scheduled_dt + chrono::Duration::minutes(10);
-                    if now >= scheduled_dt && now < window_end && !fired.contains(&key) {
-                        fired.insert(key);
+                    let fire_at = lead_start(scheduled_dt, remind.unwrap_or(0));
+                    let id = notification_id("scheduled", &block.id, scheduled_dt);
+                    // Scheduled fires at the exact time unless `remind::` asks
+                    // for a lead. One-minute scan granularity means the fire
+                    // window is "any tick after the fire moment but within
+                    // ~10 minutes of the scheduled moment."
+                    let window_end = scheduled_dt
+                        .checked_add_signed(TimeDelta::minutes(10))
+                        .unwrap_or(DateTime::<Utc>::MAX_UTC);
+                    let opened = fire_at.is_some_and(|fire_at| now >= fire_at);
+                    if should_fire(&id, opened && now < window_end) {
                         let _ = ws_tx.send(WsEvent::ScheduledFires {
+                            notification_id: id.clone(),
                             block_id: block.id.clone(),
                             title: title.clone(),
                             note_id: note.id.as_str().to_string(),
                             scheduled_iso: scheduled_dt.to_rfc3339(),
                         });
                         debug!("notify: scheduled fires {}", block.id);
+                        fired.push(id);
                     }
                 }
             }
         }
     }
+
+    for id in &fired {
+        let record = NotificationStateRecord {
+            id: id.clone(),
+            status: STATUS_FIRED.to_string(),
+            snoozed_until_ms: None,
+            updated_at_ms: now_ms,
+        };
+        if let Err(e) = engine.notification_state_upsert(record).await {
+            warn!("notification state write failed: {e}");
+        }
+    }
+
+    let prune_before = now_ms - chrono::Duration::days(PRUNE_AFTER_DAYS).num_milliseconds();
+    for record in states.values() {
+        let snoozed_ahead = record.snoozed_until_ms.is_some_and(|t| t > now_ms);
+        if record.updated_at_ms < prune_before && !snoozed_ahead && !fired.contains(&record.id) {
+            let _ = engine.notification_state_delete(&record.id).await;
+        }
+    }
     Ok(())
 }
 
+/// Stable id for one reminder: the same block firing for the same target
+/// moment gets the same id on every device, which is what lets delivery
+/// state sync. Moving the deadline mints a new id (and a fresh reminder).
+pub fn notification_id(kind: &str, block_id: &str, target: DateTime<Utc>) -> String {
+    format!("{kind}-{block_id}-{}", target.timestamp())
+}
+
+/// `target - lead_minutes`, or `None` when that falls outside chrono's range
+/// (the reminder window then never opens).
+fn lead_start(target: DateTime<Utc>, lead_minutes: i64) -> Option<DateTime<Utc>> {
+    target.checked_sub_signed(TimeDelta::try_minutes(lead_minutes)?)
+}
+
+/// Parse a `remind::` lead time: `15m`, `2h`, `1d`, or bare minutes.
+/// Returns minutes; `None` for anything unparseable, negative or longer
+/// than [`MAX_LEAD_MIN`].
+fn parse_lead(raw: &str) -> Option<i64> {
+    let trimmed = raw.trim().to_ascii_lowercase();
+    let (digits, unit) = match trimmed.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((idx, _)) => trimmed.split_at(idx),
+        None => (trimmed.as_str(), ""),
+    };
+    let n: i64 = digits.parse().ok()?;
+    let factor = match unit.trim() {
+        "" | "m" | "min" | "mins" | "minutes" => 1,
+        "h" | "hr" | "hrs" | "hours" => 60,
+        "d" | "day" | "days" => 60 * 24,
+        _ => return None,
+    };
+    n.checked_mul(factor).filter(|&lead| lead <= MAX_LEAD_MIN)
+}
+
 /// Parse `deadline::` / `scheduled::` value into a UTC datetime. Bare dates
 /// are interpreted as "end of day in the user's local timezone" so a
 /// deadline of `[[2026-05-09]]` notifies at 8 AM the same day (with the
@@ -199,3 +362,152 @@ fn task_title(text: &str) -> String {
         .trim()
         .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tesela_core::storage::filesystem::FsNoteStore;
+    use tesela_sync::{DeviceId, Hlc, LoroEngine};
+
+    #[test]
+    fn remind_lead_accepts_units_and_bare_minutes() {
+        assert_eq!(parse_lead("15m"), Some(15));
+        assert_eq!(parse_lead("2h"), Some(120));
+        assert_eq!(parse_lead(" 1d "), Some(1440));
+        assert_eq!(parse_lead("45"), Some(45));
+        assert_eq!(parse_lead("0"), Some(0));
+        assert_eq!(parse_lead("soon"), None);
+        assert_eq!(parse_lead("-5m"), None);
+        assert_eq!(parse_lead("366d"), Some(MAX_LEAD_MIN));
+        assert_eq!(parse_lead("367d"), None);
+        assert_eq!(parse_lead("999999999999999m"), None);
+        assert_eq!(parse_lead("99999999999999999999d"), None);
+    }
+
+    async fn fixture(body: &str) -> (tempfile::TempDir, Arc<dyn NoteStore>, LoroEngine) {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = FsNoteStore::new(tmp.path().to_path_buf(), Default::default());
+        store.create("Tasks", body, &[]).await.unwrap();
+        let device = DeviceId::from_bytes([0x42; 16]);
+        let engine = LoroEngine::new(device, Arc::new(Hlc::new(device)));
+        (tmp, Arc::new(store), engine)
+    }
+
+    fn at(raw: &str) -> DateTime<Utc> {
+        parse_deadline_local(raw).unwrap()
+    }
+
+    fn drain(rx: &mut broadcast::Receiver<WsEvent>) -> Vec<String> {
+        let mut ids = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                WsEvent::DeadlineApproaching {
+                    notification_id, ..
+                }
+                | WsEvent::ScheduledFires {
+                    notification_id, ..
+                } => ids.push(notification_id),
+                _ => {}
+            }
+        }
+        ids
+    }
+
+    #[tokio::test]
+    async fn fired_state_persists_and_ack_silences_refire() {
+        let (_tmp, store, engine) =
+            fixture("- Ship it #Task\n  status:: todo\n  deadline:: [[2030-01-15]] 10:00\n").await;
+        let (ws_tx, mut rx) = broadcast::channel(16);
+        let notifier = Notifier::new();
+
+        scan_once(&notifier, &engine, &store, &ws_tx, at("2030-01-15 09:30"))
+            .await
+            .unwrap();
+        let fired = drain(&mut rx);
+        assert_eq!(fired.len(), 1, "deadline window open → one fire");
+
+        // A fresh notifier (a restarted server) reads the synced state and
+        // does not fire the same reminder again.
+        let restarted = Notifier::new();
+        scan_once(&restarted, &engine, &store, &ws_tx, at("2030-01-15 09:31"))
+            .await
+            .unwrap();
+        assert!(drain(&mut rx).is_empty(), "no re-fire after restart");
+
+        let acked = restarted.acknowledge(&engine, &fired[0]).await.unwrap();
+        assert_eq!(acked.unwrap().status, STATUS_ACKNOWLEDGED);
+        assert!(restarted
+            .acknowledge(&engine, "deadline-missing-0")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn snoozed_reminder_fires_again_after_snooze_even_past_window() {
+        let (_tmp, store, engine) =
+            fixture("- Ship it #Task\n  status:: todo\n  deadline:: [[2030-01-15]] 10:00\n").await;
+        let (ws_tx, mut rx) = broadcast::channel(16);
+        let notifier = Notifier::new();
+        scan_once(&notifier, &engine, &store, &ws_tx, at("2030-01-15 09:30"))
+            .await
+            .unwrap();
+        let id = drain(&mut rx).remove(0);
+
+        notifier
+            .snooze(&engine, &id, at("2030-01-15 10:15"))
+            .await
+            .unwrap()
+            .unwrap();
+        scan_once(&notifier, &engine, &store, &ws_tx, at("2030-01-15 10:00"))
+            .await
+            .unwrap();
+        assert!(drain(&mut rx).is_empty(), "still snoozed");
+
+        scan_once(&notifier, &engine, &store, &ws_tx, at("2030-01-15 10:15"))
+            .await
+            .unwrap();
+        assert_eq!(drain(&mut rx), vec![id.clone()], "snooze elapsed → re-fire");
+        let state = engine.notification_states_list().await;
+        assert_eq!(state[0].status, STATUS_FIRED);
+    }
+
+    #[tokio::test]
+    async fn remind_property_overrides_lead_time() {
+        let (_tmp, store, engine) = fixture(
+            "- Ship it #Task\n  status:: todo\n  deadline:: [[2030-01-15]] 10:00\n  remind:: 15m\n",
+        )
+        .await;
+        let (ws_tx, mut rx) = broadcast::channel(16);
+        let notifier = Notifier::new();
+
+        // Inside the default 60-minute lead but outside the 15-minute override.
+        scan_once(&notifier, &engine, &store, &ws_tx, at("2030-01-15 09:30"))
+            .await
+            .unwrap();
+        assert!(drain(&mut rx).is_empty());
+
+        scan_once(&notifier, &engine, &store, &ws_tx, at("2030-01-15 09:45"))
+            .await
+            .unwrap();
+        assert_eq!(drain(&mut rx).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn oversized_remind_falls_back_to_the_default_lead() {
+        let (_tmp, store, engine) = fixture(
+            "- Ship it #Task\n  status:: todo\n  deadline:: [[2030-01-15]] 10:00\n  \
+             scheduled:: [[2030-01-15]] 10:00\n  remind:: 999999999999999m\n",
+        )
+        .await;
+        let (ws_tx, mut rx) = broadcast::channel(16);
+        let notifier = Notifier::new();
+
+        scan_once(&notifier, &engine, &store, &ws_tx, at("2030-01-15 09:30"))
+            .await
+            .unwrap();
+        let fired = drain(&mut rx);
+        assert_eq!(fired.len(), 1, "default 60m deadline lead only: {fired:?}");
+        assert!(fired[0].starts_with("deadline-"));
+    }
+}
@@ -0,0 +1,41 @@
+//! Periodic oplog retention sweep (see [`tesela_sync::oplog::retention`]).
+//! Policy and cadence come from `[sync.retention]` in the mosaic config;
+//! each sweep that reclaims anything is logged with its counts.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tesela_core::config::RetentionConfig;
+use tesela_core::db::SqliteIndex;
+use tesela_sync::{run_retention_sweep, DeviceId, RetentionPolicy};
+use tracing::{info, warn};
+
+/// Spawn the periodic sweep. The first sweep runs one interval after
+/// startup, off the bring-up path.
+pub fn start(index: Arc<SqliteIndex>, device: DeviceId, cfg: &RetentionConfig) {
+    let policy = RetentionPolicy::from_config(cfg);
+    let period = Duration::from_secs(cfg.interval_hours.max(1) * 60 * 60);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let now = chrono::Utc::now().timestamp_millis();
+            match run_retention_sweep(index.pool(), device, &policy, now, false).await {
+                Ok(report) if report.reclaimed_rows() > 0 => info!(
+                    "oplog retention: reclaimed {} row(s) — {} op(s), {} parked op(s), {} \
+                     evicted peer(s); {} op(s) / {} parked remain",
+                    report.reclaimed_rows(),
+                    report.oplog_rows_deleted,
+                    report.parked_ops_deleted,
+                    report.evicted_peers.len(),
+                    report.oplog_rows_remaining,
+                    report.parked_ops_remaining
+                ),
+                Ok(_) => {}
+                Err(e) => warn!("oplog retention: sweep failed: {e}"),
+            }
+        }
+    });
+}
@@ -1,7 +1,8 @@
-//! Desktop ↔ Cloudflare-relay presence bridge (Phase 3b, Stage 2).
+//! Desktop ↔ relay presence bridge (Phase 3b, Stage 2).
 //!
-//! Maintains a long-lived WebSocket to the CF relay's
-//! `GET /groups/{id}/presence/ws` endpoint and bridges it with this
+//! Maintains a long-lived WebSocket to the relay's
+//! `GET /groups/{id}/presence/ws` endpoint (the CF Worker's Durable
+//! Object, or the self-hosted `tesela-relay`) and bridges it with this
 //! server's local `/ws` PRES fan-out:
 //!
 //! - **OUTBOUND** — locally-originated `PRES` frames (cursor/selection from
@@ -170,7 +171,9 @@ type PresenceSocket =
 /// Open the presence WebSocket, authenticating the upgrade GET with the same
 /// MAC scheme as the other relay calls. The signed canonical path MUST be
 /// `/groups/{hex}/presence/ws` (CF rebuilds the canonical from
-/// `x-tesela-original-path`).
+/// `x-tesela-original-path`), and the query is `device={hex}`: the relay
+/// takes the socket's identity from the signed query, never from the
+/// unsigned `x-tesela-device` header.
 async fn connect(
     ws_url: &str,
     group_id: &GroupId,
@@ -182,12 +185,13 @@ async fn connect(
     let nonce_b64 = base64::engine::general_purpose::STANDARD.encode(nonce);
     let ts = now_secs_i64();
     let path = format!("/groups/{}/presence/ws", hex::encode(group_id.as_bytes()));
-    // Empty query, empty body hash — mirror `GET /ops`.
-    let canonical = canonical_request("GET", &path, "", &nonce_b64, ts, "");
+    let query = format!("device={}", hex::encode(device_id.as_bytes()));
+    // Empty body hash — mirror `GET /ops`.
+    let canonical = canonical_request("GET", &path, &query, &nonce_b64, ts, "");
     let mac = compute_request_mac(auth_key, &canonical);
     let mac_b64 = base64::engine::general_purpose::STANDARD.encode(mac);
 
-    let mut request = ws_url
+    let mut request = format!("{ws_url}?{query}")
         .into_client_request()
         .map_err(|e| format!("build presence request: {e}"))?;
     let headers = request.headers_mut();
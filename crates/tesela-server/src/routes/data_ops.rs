@@ -1498,9 +1498,10 @@ pub async fn restart_server() -> Result<Json<serde_json::Value>, (StatusCode, St
 
 #[cfg(unix)]
 fn maybe_respawn_detached(pinned_mosaic: Option<&str>) -> anyhow::Result<bool> {
-    // If launchd is managing us via the LaunchAgent (`tesela install`),
-    // it'll restart automatically — don't double-spawn.
-    if launchd_managing_us() {
+    // If launchd (macOS LaunchAgent) or systemd (Linux user unit) is
+    // managing us via `tesela install`, it'll restart automatically —
+    // don't double-spawn.
+    if launchd_managing_us() || crate::systemd::is_managed() {
         return Ok(false);
     }
     let exe = std::env::current_exe()?;
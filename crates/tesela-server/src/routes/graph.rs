@@ -0,0 +1,96 @@
+//! Link-graph analytics over wiki-links and relation edges. The graph and
+//! its algorithms live in `tesela_core::graph`; these handlers read the
+//! index's cached snapshot.
+
+use std::sync::Arc;
+
+use axum::extract::{Query, State};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use tesela_core::graph::{Cluster, DeadLink, GraphPageRef, PageScore};
+
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+/// GET /graph/orphans — pages with no links in or out (system pages excluded).
+pub async fn orphans(State(s): State<Arc<AppState>>) -> AppResult<Json<Vec<GraphPageRef>>> {
+    Ok(Json(s.index.link_graph_snapshot().await?.orphans()))
+}
+
+/// GET /graph/dead-links — wiki-links whose target names no page.
+pub async fn dead_links(State(s): State<Arc<AppState>>) -> AppResult<Json<Vec<DeadLink>>> {
+    Ok(Json(
+        s.index.link_graph_snapshot().await?.dead_links().to_vec(),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct RankQuery {
+    pub limit: Option<usize>,
+}
+
+/// GET /graph/rank?limit= — pages by PageRank, highest first (default 50).
+pub async fn rank(
+    Query(q): Query<RankQuery>,
+    State(s): State<Arc<AppState>>,
+) -> AppResult<Json<Vec<PageScore>>> {
+    let mut scores = s.index.link_graph_snapshot().await?.scores();
+    scores.truncate(q.limit.unwrap_or(50));
+    Ok(Json(scores))
+}
+
+#[derive(Deserialize)]
+pub struct ClustersQuery {
+    /// Smallest cluster to report (default 2 — skip isolated pages).
+    pub min_size: Option<usize>,
+}
+
+/// GET /graph/clusters?min_size= — connected groups of pages, largest first.
+pub async fn clusters(
+    Query(q): Query<ClustersQuery>,
+    State(s): State<Arc<AppState>>,
+) -> AppResult<Json<Vec<Cluster>>> {
+    let graph = s.index.link_graph_snapshot().await?;
+    Ok(Json(graph.clusters(q.min_size.unwrap_or(2))))
+}
+
+#[derive(Deserialize)]
+pub struct PathQuery {
+    pub from: String,
+    pub to: String,
+    /// Follow links forwards only; by default a backlink is a step too.
+    #[serde(default)]
+    pub directed: bool,
+}
+
+#[derive(Serialize)]
+pub struct PathResp {
+    /// `from` … `to`; empty when no path exists.
+    pub pages: Vec<GraphPageRef>,
+    /// Links followed — `pages.len() - 1`, or `None` when unreachable.
+    pub hops: Option<usize>,
+}
+
+/// GET /graph/path?from=&to=&directed= — fewest-hop path between two pages
+/// named by slug, title or alias.
+pub async fn path(
+    Query(q): Query<PathQuery>,
+    State(s): State<Arc<AppState>>,
+) -> AppResult<Json<PathResp>> {
+    let graph = s.index.link_graph_snapshot().await?;
+    let resolve = |name: &str| {
+        graph
+            .resolve(name)
+            .ok_or_else(|| AppError::NotFound(format!("Note not found: {}", name)))
+    };
+    let (from, to) = (resolve(&q.from)?, resolve(&q.to)?);
+    let steps = graph.shortest_path(from, to, q.directed);
+    Ok(Json(PathResp {
+        hops: steps.as_ref().map(|steps| steps.len() - 1),
+        pages: steps
+            .unwrap_or_default()
+            .into_iter()
+            .map(|i| graph.page_ref(i))
+            .collect(),
+    }))
+}
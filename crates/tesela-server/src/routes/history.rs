@@ -2,15 +2,22 @@ use std::sync::Arc;
 
 use axum::extract::{Path, Query, State};
 use axum::Json;
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use tesela_core::{
-    note::{NoteId, NoteVersion},
-    traits::search_index::SearchIndex,
+    history::parse_point_in_time,
+    link::extract_wiki_links,
+    note::{Note, NoteId, NoteVersion},
+    note_tree::parse_note,
+    traits::{link_graph::LinkGraph, note_store::NoteStore, search_index::SearchIndex},
 };
+use tesela_sync::diff::{block_changes, BlockChange, BlockChangeKind};
+use tesela_sync::group::list_members;
+use tesela_sync::SyncError;
 
 use crate::{
     error::{AppError, AppResult},
-    state::AppState,
+    state::{AppState, WsDelta},
 };
 
 #[derive(Deserialize)]
@@ -37,7 +44,11 @@ pub async fn get_version(
     Path((id, version_id)): Path<(String, i64)>,
     State(s): State<Arc<AppState>>,
 ) -> AppResult<Json<NoteVersion>> {
-    let note_id = NoteId::new(&id);
+    Ok(Json(load_version(&s, &id, version_id).await?))
+}
+
+async fn load_version(s: &AppState, id: &str, version_id: i64) -> AppResult<NoteVersion> {
+    let note_id = NoteId::new(id);
     let v = s
         .index
         .get_version(version_id)
@@ -49,5 +60,329 @@ pub async fn get_version(
             version_id, id
         )));
     }
-    Ok(Json(v))
+    Ok(v)
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionRef {
+    pub id: i64,
+    pub version_number: i64,
+    pub created_at: String,
+}
+
+impl From<&NoteVersion> for VersionRef {
+    fn from(v: &NoteVersion) -> Self {
+        VersionRef {
+            id: v.id,
+            version_number: v.version_number,
+            created_at: v.created_at.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct DiffSummary {
+    pub added: usize,
+    pub removed: usize,
+    pub edited: usize,
+    pub moved: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionDiff {
+    pub from: VersionRef,
+    pub to: VersionRef,
+    pub summary: DiffSummary,
+    /// Keyed by block id; new-version document order, removed blocks last.
+    pub changes: Vec<BlockChange>,
+}
+
+/// GET /notes/:id/versions/:a/diff/:b
+/// Block-aware structural diff from version `a` to version `b` (either
+/// order; `a` is always the "from" side).
+pub async fn diff_versions(
+    Path((id, a, b)): Path<(String, i64, i64)>,
+    State(s): State<Arc<AppState>>,
+) -> AppResult<Json<VersionDiff>> {
+    let from = load_version(&s, &id, a).await?;
+    let to = load_version(&s, &id, b).await?;
+    let changes = block_changes(&parse_note(&from.content), &parse_note(&to.content));
+    let mut summary = DiffSummary::default();
+    for c in &changes {
+        match c.kind {
+            BlockChangeKind::Added => summary.added += 1,
+            BlockChangeKind::Removed => summary.removed += 1,
+            BlockChangeKind::Edited => summary.edited += 1,
+            BlockChangeKind::Moved => summary.moved += 1,
+        }
+    }
+    Ok(Json(VersionDiff {
+        from: VersionRef::from(&from),
+        to: VersionRef::from(&to),
+        summary,
+        changes,
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct BlameEntry {
+    pub block_id: String,
+    pub text: String,
+    /// Loro PeerID of the last author, hex.
+    pub peer: String,
+    /// The paired device behind `peer`, when it is this device or a known
+    /// group member.
+    pub device_id: Option<String>,
+    pub device_name: Option<String>,
+    pub is_local: bool,
+    pub lamport: u32,
+    /// RFC 3339, when the author recorded change timestamps.
+    pub modified_at: Option<String>,
+}
+
+/// GET /notes/:id/blame
+/// Each live block (in document order) with the Loro change — device and
+/// time — that last modified it.
+pub async fn get_blame(
+    Path(id): Path<String>,
+    State(s): State<Arc<AppState>>,
+) -> AppResult<Json<Vec<BlameEntry>>> {
+    let doc_id = s.sync_engine.resolve_note_doc_id(&id).await?;
+    let not_found = || AppError::NotFound(format!("Note not found: {id}"));
+    let blame = s
+        .sync_engine
+        .block_blame(doc_id)
+        .await
+        .ok_or_else(not_found)?;
+    let rendered = s
+        .sync_engine
+        .render_note(doc_id)
+        .await
+        .ok_or_else(not_found)?;
+
+    // peer -> (device id, display name, is_local)
+    let local = s.sync_engine.device();
+    let mut devices = vec![(
+        tesela_sync::loro_peer_id(&local),
+        local.to_hex(),
+        Some(s.display_name.clone()),
+        true,
+    )];
+    let group_id = s.group_identity.read().await.group_id;
+    for m in list_members(s.index.pool(), group_id).await? {
+        if m.device_id != local {
+            devices.push((
+                tesela_sync::loro_peer_id(&m.device_id),
+                m.device_id.to_hex(),
+                m.display_name,
+                false,
+            ));
+        }
+    }
+
+    let entries = parse_note(&rendered)
+        .blocks
+        .into_iter()
+        .filter_map(|block| {
+            let hex = block.id.simple().to_string();
+            let b = blame.iter().find(|b| b.block_id == hex)?;
+            let device = devices.iter().find(|d| d.0 == b.peer);
+            Some(BlameEntry {
+                block_id: block.id.to_string(),
+                text: block.text,
+                peer: format!("{:016x}", b.peer),
+                device_id: device.map(|d| d.1.clone()),
+                device_name: device.and_then(|d| d.2.clone()),
+                is_local: device.is_some_and(|d| d.3),
+                lamport: b.lamport,
+                modified_at: b
+                    .timestamp
+                    .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+                    .map(|t| t.to_rfc3339()),
+            })
+        })
+        .collect();
+    Ok(Json(entries))
+}
+
+#[derive(Deserialize)]
+pub struct AtParams {
+    /// Unix seconds, RFC 3339, or a phrase like `last tuesday 14:00`
+    /// (see `tesela_core::history`).
+    pub at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NoteAt {
+    /// The moment `at` resolved to, RFC 3339.
+    pub at: String,
+    /// The full `.md` as it stood then.
+    pub content: String,
+    /// RFC 3339 time of the newest change included.
+    pub last_change_at: Option<String>,
+    /// Changes recorded after `at`.
+    pub changes_since: usize,
+    /// What changed from then to now, block by block.
+    pub changes: Vec<BlockChange>,
+}
+
+/// GET /notes/:id/at?at=last%20tuesday%2014:00
+/// The note as it stood at a past moment, rebuilt from its Loro history
+/// rather than the periodic `note_versions` snapshots.
+pub async fn get_note_at(
+    Path(id): Path<String>,
+    Query(p): Query<AtParams>,
+    State(s): State<Arc<AppState>>,
+) -> AppResult<Json<NoteAt>> {
+    let at = resolve_at(&p.at)?;
+    let doc_id = s.sync_engine.resolve_note_doc_id(&id).await?;
+    let past = s
+        .sync_engine
+        .note_at(doc_id, at.timestamp())
+        .await
+        .map_err(history_error)?
+        .ok_or_else(|| AppError::NotFound(format!("Note not found: {id}")))?;
+    let current = s
+        .sync_engine
+        .render_note_full(doc_id)
+        .await
+        .unwrap_or_default();
+    Ok(Json(NoteAt {
+        at: at.to_rfc3339(),
+        changes: block_changes(&parse_note(&past.content), &parse_note(&current)),
+        content: past.content,
+        last_change_at: past.last_change_at.and_then(rfc3339_secs),
+        changes_since: past.changes_since,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RestoreBody {
+    /// Same spellings as [`AtParams::at`].
+    pub at: String,
+    /// Restore only this block and its children; the whole note otherwise.
+    pub block_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoreResponse {
+    pub at: String,
+    /// `false` when the note (or subtree) already matched that moment.
+    pub restored: bool,
+    /// What the restore changed, block by block.
+    pub changes: Vec<BlockChange>,
+    pub note: Note,
+}
+
+/// POST /notes/:id/restore
+/// Revert the note — or one block subtree — to a past moment. The revert
+/// is a new forward change, so it syncs to other devices and lands in the
+/// version history like any other edit.
+pub async fn restore_note(
+    Path(id): Path<String>,
+    State(s): State<Arc<AppState>>,
+    Json(body): Json<RestoreBody>,
+) -> AppResult<Json<RestoreResponse>> {
+    let at = resolve_at(&body.at)?;
+    let block_id = body
+        .block_id
+        .as_deref()
+        .map(|bid| {
+            uuid::Uuid::parse_str(bid)
+                .map(|u| *u.as_bytes())
+                .map_err(|e| AppError::Validation(format!("invalid block_id {bid:?}: {e}")))
+        })
+        .transpose()?;
+    let note_id = NoteId::new(&id);
+    let prev = s
+        .store
+        .get(&note_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Note not found: {id}")))?;
+    let doc_id = s.sync_engine.resolve_note_doc_id(&id).await?;
+    let pre_vv = s.sync_engine.doc_version(doc_id).await;
+
+    let restored = s
+        .sync_engine
+        .restore_note(doc_id, at.timestamp(), block_id)
+        .await
+        .map_err(history_error)?;
+    if restored {
+        crate::routes::ws::emit_note_updated(
+            &*s.sync_engine,
+            &s.store,
+            &s.index,
+            &s.ws_tx,
+            doc_id,
+            false,
+            Some(id.as_str()),
+        )
+        .await;
+    }
+    let note = s
+        .store
+        .get(&note_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Note not found after restore: {id}")))?;
+    if restored {
+        if let Err(e) = s
+            .index
+            .update_links(&note_id, &extract_wiki_links(&note.content))
+            .await
+        {
+            tracing::warn!("Failed to update links after restore of {id}: {e}");
+        }
+        if note.content != prev.content {
+            if let Err(e) = s
+                .index
+                .record_version(&note_id, Some(&prev.content), &note.content, 200)
+                .await
+            {
+                tracing::warn!("Failed to record note version after restore: {e}");
+            }
+        }
+        // Push the revert to live WS clients, as the block-write path does.
+        if let Some(delta) = s
+            .sync_engine
+            .export_doc_update(doc_id, pre_vv.as_deref())
+            .await
+        {
+            match tesela_sync::encode_loro_relay_payload(&[tesela_sync::LoroDocUpdate {
+                doc: doc_id,
+                update_bytes: delta,
+            }]) {
+                Ok(frame) => {
+                    let _ = s.ws_delta_tx.send(WsDelta {
+                        origin: None,
+                        source_group: None,
+                        frame,
+                    });
+                }
+                Err(e) => tracing::warn!("ws: encode restore delta for {id} failed: {e}"),
+            }
+        }
+    }
+    Ok(Json(RestoreResponse {
+        at: at.to_rfc3339(),
+        restored,
+        changes: block_changes(&parse_note(&prev.content), &parse_note(&note.content)),
+        note,
+    }))
+}
+
+fn resolve_at(at: &str) -> AppResult<DateTime<Utc>> {
+    parse_point_in_time(at, &chrono::Local::now())
+        .ok_or_else(|| AppError::Validation(format!("unrecognized point in time: {at:?}")))
+}
+
+fn rfc3339_secs(secs: i64) -> Option<String> {
+    DateTime::from_timestamp(secs, 0).map(|t| t.to_rfc3339())
+}
+
+fn history_error(error: SyncError) -> AppError {
+    match error {
+        SyncError::HistoryUnavailable(message) => AppError::NotFound(message),
+        SyncError::Protocol(message) => AppError::Conflict(message),
+        error => AppError::Internal(anyhow::anyhow!("note history: {error}")),
+    }
 }
@@ -3,18 +3,25 @@ mod attachments;
 mod calendar;
 mod commands;
 mod data_ops;
+mod graph;
 mod history;
 mod keymap;
 mod notes;
+mod notifications;
+mod opml;
 pub mod peer_sync;
 mod relay;
 mod search;
 mod search_query;
+mod shares;
 mod sync;
+mod sync_health;
+mod sync_scope;
 mod tags;
 mod transcription;
 mod types;
 mod views;
+mod webhooks;
 pub mod ws;
 
 use std::sync::Arc;
@@ -34,9 +41,14 @@ use tracing::Level;
 
 use crate::state::AppState;
 
+pub(crate) use search_query::page_query_context;
+
 const EXPECTED_GROUP_HEADER: &str = "x-tesela-expected-group";
 
-pub fn build(state: AppState) -> Router {
+/// Build the API router. `/metrics` is only mounted when `expose_metrics`
+/// is set (`[server] metrics = true`); otherwise it 404s like any unknown
+/// path.
+pub fn build(state: AppState, expose_metrics: bool) -> Router {
     let state = Arc::new(state);
     let app = Router::new()
         .route("/health", get(health))
@@ -66,6 +78,7 @@ pub fn build(state: AppState) -> Router {
         )
         .route("/notes/{id}/links", get(notes::get_forward_links))
         .route("/notes/{id}/unlinked", get(notes::get_unlinked))
+        .route("/notes/{id}/related", get(notes::get_related))
         .route("/tags/rename", post(notes::rename_tag))
         .route("/tags/resolve", post(notes::resolve_tag))
         .route("/tags/{slug}/usage", get(notes::get_tag_usage))
@@ -78,7 +91,20 @@ pub fn build(state: AppState) -> Router {
             "/notes/{id}/versions/{version_id}",
             get(history::get_version),
         )
+        .route(
+            "/notes/{id}/versions/{a}/diff/{b}",
+            get(history::diff_versions),
+        )
+        .route("/notes/{id}/blame", get(history::get_blame))
+        .route("/notes/{id}/at", get(history::get_note_at))
+        .route("/notes/{id}/restore", post(history::restore_note))
+        .route("/notes/{id}/opml", get(opml::export_note))
         .route("/links", get(notes::get_all_edges))
+        .route("/graph/orphans", get(graph::orphans))
+        .route("/graph/dead-links", get(graph::dead_links))
+        .route("/graph/rank", get(graph::rank))
+        .route("/graph/clusters", get(graph::clusters))
+        .route("/graph/path", get(graph::path))
         .route("/blocks/move-subtree", post(notes::move_block_subtree))
         .route("/blocks/recur-bump", post(notes::recur_bump))
         .route("/blocks/set-property", post(notes::set_block_property))
@@ -102,8 +128,6 @@ pub fn build(state: AppState) -> Router {
             "/sync/peer/peers/{device_id_hex}",
             axum::routing::delete(peer_sync::remove_peer),
         )
-        .route("/sync/peer/produce", post(peer_sync::produce))
-        .route("/sync/peer/envelope", post(peer_sync::receive_envelope))
         .route("/sync/peer/now", post(peer_sync::sync_now))
         .route("/sync/peer/status", get(peer_sync::status))
         // WAN relay status — drives the web settings page.
@@ -114,6 +138,7 @@ pub fn build(state: AppState) -> Router {
                 .put(relay::put_config)
                 .delete(relay::delete_config),
         )
+        .route("/sync/relay/rotate-key", post(relay::rotate_key))
         // Phase 2.1 mDNS LAN discovery
         .route("/sync/peer/discovered", get(peer_sync::discovered))
         // Phase 2.2 pairing-code key exchange
@@ -129,9 +154,47 @@ pub fn build(state: AppState) -> Router {
         )
         // tesela-ra7 P0.3c — show-side recovery phrase for the web/desktop UI.
         .route("/sync/recovery-phrase", get(peer_sync::get_recovery_phrase))
+        // Selective sync — per-device exclusion rules (synced registry doc).
+        .route("/sync/scope", get(sync_scope::get_scope))
+        .route(
+            "/sync/scope/{device}",
+            axum::routing::put(sync_scope::put_scope).delete(sync_scope::delete_scope),
+        )
+        // Shared sub-spaces — pages shared with another mosaic through a
+        // second group identity.
+        .route(
+            "/sync/shares",
+            get(shares::list_shares).post(shares::create_share),
+        )
+        .route("/sync/shares/join", post(shares::join_share))
+        .route(
+            "/sync/shares/{share_id}",
+            axum::routing::delete(shares::leave_share),
+        )
+        .route("/sync/shares/{share_id}/code", get(shares::get_share_code))
+        .route(
+            "/sync/shares/{share_id}/notes",
+            post(shares::add_share_note),
+        )
+        .route(
+            "/sync/shares/{share_id}/notes/{note}",
+            axum::routing::delete(shares::remove_share_note),
+        )
+        // Sync-health inspector — anomalies per note, repairs, incident log.
+        .route("/sync/health", get(sync_health::get_health))
+        .route("/sync/health/scan", post(sync_health::scan))
+        .route("/sync/health/incidents", get(sync_health::list_incidents))
+        .route("/sync/health/repair", post(sync_health::repair))
+        // Reminder delivery state — synced via the engine's notification doc.
+        .route("/notifications", get(notifications::list_notifications))
+        .route("/notifications/{id}/ack", post(notifications::acknowledge))
+        .route("/notifications/{id}/snooze", post(notifications::snooze))
+        .route("/webhooks", get(webhooks::list_webhooks))
+        .route("/webhooks/deliveries", get(webhooks::list_deliveries))
         .route("/search", get(search::search_notes))
         .route("/agenda", post(agenda::post_agenda))
         .route("/search/query", post(search_query::execute))
+        .route("/search/query/opml", post(opml::export_query))
         .route("/calendar/marks", get(calendar::marks))
         .route("/tags", get(tags::list_tags))
         // Saved-views registry (spec 2026-06-10) — thin wrappers over the
@@ -182,6 +245,7 @@ pub fn build(state: AppState) -> Router {
         .route("/imports/logseq/plan", post(data_ops::plan_logseq))
         .route("/imports/logseq/apply", post(data_ops::apply_logseq))
         .route("/imports/org", post(data_ops::import_org))
+        .route("/imports/opml", post(opml::import))
         .route("/pick-folder", post(data_ops::pick_folder))
         .route("/mosaics/current", get(data_ops::get_current_mosaic))
         .route(
@@ -213,6 +277,11 @@ pub fn build(state: AppState) -> Router {
         // PCM in, committed/tentative partial frames out). Registered
         // after the body-limit layer like /ws; WS frames aren't bodies.
         .route("/transcription/stream", get(transcription::stream_ws));
+    let app = if expose_metrics {
+        app.route("/metrics", get(metrics))
+    } else {
+        app
+    };
 
     // Optional static-file serving for the desktop (Tauri) shell: when
     // TESELA_STATIC_DIR points at a built SvelteKit `/g` bundle, serve it
@@ -260,6 +329,7 @@ pub fn build(state: AppState) -> Router {
                 .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
                 .on_response(DefaultOnResponse::new().level(Level::INFO)),
         )
+        .layer(axum::middleware::from_fn(crate::metrics::track_http))
         .with_state(state)
 }
 
@@ -279,9 +349,11 @@ async fn expected_group_gate(
 
     // A request that replaces the group cannot also hold the current group's
     // read lease through its handler: that would self-deadlock when the route
-    // acquires the write lock. Group selection/pairing is intentionally an
-    // unbound control-plane operation; attached data-plane calls are bound.
-    let replaces_group = request.method() == Method::POST && matches!(path, "/sync/peer/pair-code");
+    // acquires the write lock. Group selection/pairing and key rotation are
+    // intentionally unbound control-plane operations; attached data-plane
+    // calls are bound.
+    let replaces_group = request.method() == Method::POST
+        && matches!(path, "/sync/peer/pair-code" | "/sync/relay/rotate-key");
     if replaces_group {
         if request.headers().contains_key(EXPECTED_GROUP_HEADER) {
             return (
@@ -344,7 +416,10 @@ async fn expected_group_gate(
 }
 
 fn restart_pending_blocks(path: &str) -> bool {
-    !matches!(path, "/health" | "/mosaics/current" | "/server/restart")
+    !matches!(
+        path,
+        "/health" | "/metrics" | "/mosaics/current" | "/server/restart"
+    )
 }
 
 fn restart_pending_response() -> Response {
@@ -384,13 +459,28 @@ async fn info() -> (StatusCode, Json<serde_json::Value>) {
     )
 }
 
+/// GET /metrics — Prometheus/OpenMetrics exposition: per-route HTTP
+/// latency, relay ops in/out + apply latency, outbound strand alarms,
+/// snapshot deposits and open `/ws` sockets. Opt-in via
+/// `[server] metrics = true`; there is no auth on it once enabled.
+async fn metrics() -> Response {
+    (
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "application/openmetrics-text; version=1.0.0; charset=utf-8",
+        )],
+        crate::metrics::metrics().render(),
+    )
+        .into_response()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn pending_restart_gate_allows_only_observation_and_restart_controls() {
-        for path in ["/health", "/mosaics/current", "/server/restart"] {
+        for path in ["/health", "/metrics", "/mosaics/current", "/server/restart"] {
             assert!(
                 !restart_pending_blocks(path),
                 "control path blocked: {path}"
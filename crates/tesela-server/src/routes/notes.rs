@@ -20,6 +20,7 @@ use tesela_core::{
     note::{NoteId, PageId},
     note_tree::{parse_note, serialize_note},
     property::{parse_scalar, ValueType},
+    related::RelatedNotes,
     stable_uuid_from_slug,
     storage::markdown::{
         page_id_from_frontmatter_checked, parse_frontmatter, sanitize_filename,
@@ -2528,6 +2529,27 @@ pub async fn get_unlinked(
     Ok(Json(out))
 }
 
+#[derive(Deserialize)]
+pub struct RelatedQuery {
+    pub limit: Option<usize>,
+}
+
+/// GET /notes/{id}/related?limit= — pages and blocks most similar to this
+/// note by text (BM25), shared links and shared tags (default 10 of each).
+/// Unlike `get_unlinked`, no exact title mention is needed.
+pub async fn get_related(
+    Path(id): Path<String>,
+    Query(q): Query<RelatedQuery>,
+    State(s): State<Arc<AppState>>,
+) -> AppResult<Json<RelatedNotes>> {
+    let related = s
+        .index
+        .related_notes(&NoteId::new(&id), q.limit.unwrap_or(10))
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Note not found: {}", id)))?;
+    Ok(Json(related))
+}
+
 pub async fn get_all_edges(State(s): State<Arc<AppState>>) -> AppResult<Json<Vec<GraphEdge>>> {
     let edges = s.index.get_all_edges().await?;
     Ok(Json(edges))
@@ -2719,6 +2741,7 @@ pub async fn set_block_property(
             req.key
         )));
     }
+    reject_computed_property(&s, &key).await?;
 
     let note_id = NoteId::new(note_id_str);
     let note = s
@@ -2908,6 +2931,7 @@ pub async fn set_page_property(
             req.key
         )));
     }
+    reject_computed_property(&s, &key).await?;
     let note_id = NoteId::new(&req.note_id);
     let note = s
         .store
@@ -3445,6 +3469,7 @@ mod tests {
                 client: relay_client,
                 state: Arc::new(RwLock::new(RelayState::default())),
                 mosaic_root: mosaic.path().to_path_buf(),
+                membership: None,
             };
 
             let state = Arc::new(AppState {
@@ -3458,6 +3483,7 @@ mod tests {
                 auto_sync: Arc::new(crate::reminders::auto::AutoSync::new()),
                 sync_engine: Arc::clone(&engine),
                 lan_discovery: None,
+                lan_sync: None,
                 group_identity,
                 group_transition_pending_restart: std::sync::atomic::AtomicBool::new(false),
                 display_name: "test".into(),
@@ -3467,6 +3493,10 @@ mod tests {
                 backup_status: crate::backup_scheduler::BackupStatusHandle::new(
                     crate::backup_scheduler::SchedulerConfig::from_env(),
                 ),
+                notifier: Arc::new(crate::notifications::Notifier::new()),
+                webhooks: Arc::new(crate::webhooks::Webhooks::new(Vec::new())),
+                shared_spaces: Arc::new(crate::shared_spaces::SharedSpaces::default()),
+                sync_incidents: Arc::new(tesela_sync::IncidentLog::for_mosaic(mosaic.path())),
             });
 
             // ── Author content Y via upsert_blocks (adds a NEW block gamma) ──
@@ -3606,6 +3636,7 @@ mod tests {
                 client: relay_client,
                 state: Arc::new(RwLock::new(RelayState::default())),
                 mosaic_root: mosaic.path().to_path_buf(),
+                membership: None,
             };
 
             let state = Arc::new(AppState {
@@ -3619,6 +3650,7 @@ mod tests {
                 auto_sync: Arc::new(crate::reminders::auto::AutoSync::new()),
                 sync_engine: Arc::clone(&engine),
                 lan_discovery: None,
+                lan_sync: None,
                 group_identity,
                 group_transition_pending_restart: std::sync::atomic::AtomicBool::new(false),
                 display_name: "test".into(),
@@ -3628,6 +3660,10 @@ mod tests {
                 backup_status: crate::backup_scheduler::BackupStatusHandle::new(
                     crate::backup_scheduler::SchedulerConfig::from_env(),
                 ),
+                notifier: Arc::new(crate::notifications::Notifier::new()),
+                webhooks: Arc::new(crate::webhooks::Webhooks::new(Vec::new())),
+                shared_spaces: Arc::new(crate::shared_spaces::SharedSpaces::default()),
+                sync_incidents: Arc::new(tesela_sync::IncidentLog::for_mosaic(mosaic.path())),
             });
 
             // ── Create the already-relayed slug with the product's empty body ──
@@ -3808,6 +3844,7 @@ mod tests {
                 client: relay_client,
                 state: Arc::new(RwLock::new(RelayState::default())),
                 mosaic_root: mosaic.path().to_path_buf(),
+                membership: None,
             };
             let state = Arc::new(AppState {
                 mosaic_root: mosaic.path().to_path_buf(),
@@ -3820,6 +3857,7 @@ mod tests {
                 auto_sync: Arc::new(crate::reminders::auto::AutoSync::new()),
                 sync_engine: Arc::clone(&engine),
                 lan_discovery: None,
+                lan_sync: None,
                 group_identity,
                 group_transition_pending_restart: std::sync::atomic::AtomicBool::new(false),
                 display_name: "test".into(),
@@ -3829,6 +3867,10 @@ mod tests {
                 backup_status: crate::backup_scheduler::BackupStatusHandle::new(
                     crate::backup_scheduler::SchedulerConfig::from_env(),
                 ),
+                notifier: Arc::new(crate::notifications::Notifier::new()),
+                webhooks: Arc::new(crate::webhooks::Webhooks::new(Vec::new())),
+                shared_spaces: Arc::new(crate::shared_spaces::SharedSpaces::default()),
+                sync_incidents: Arc::new(tesela_sync::IncidentLog::for_mosaic(mosaic.path())),
             });
 
             let result = move_block_subtree(
@@ -3972,6 +4014,7 @@ mod tests {
                 client: relay_client,
                 state: Arc::new(RwLock::new(RelayState::default())),
                 mosaic_root: mosaic.path().to_path_buf(),
+                membership: None,
             };
 
             let state = Arc::new(AppState {
@@ -3985,6 +4028,7 @@ mod tests {
                 auto_sync: Arc::new(crate::reminders::auto::AutoSync::new()),
                 sync_engine: Arc::clone(&engine),
                 lan_discovery: None,
+                lan_sync: None,
                 group_identity,
                 group_transition_pending_restart: std::sync::atomic::AtomicBool::new(false),
                 display_name: "test".into(),
@@ -3994,6 +4038,10 @@ mod tests {
                 backup_status: crate::backup_scheduler::BackupStatusHandle::new(
                     crate::backup_scheduler::SchedulerConfig::from_env(),
                 ),
+                notifier: Arc::new(crate::notifications::Notifier::new()),
+                webhooks: Arc::new(crate::webhooks::Webhooks::new(Vec::new())),
+                shared_spaces: Arc::new(crate::shared_spaces::SharedSpaces::default()),
+                sync_incidents: Arc::new(tesela_sync::IncidentLog::for_mosaic(mosaic.path())),
             });
 
             // ── Rename old-tag -> new-tag, rewriting the corpus ──
@@ -4121,6 +4169,7 @@ mod tests {
                 auto_sync: Arc::new(crate::reminders::auto::AutoSync::new()),
                 sync_engine: Arc::new(FailingRecordEngine) as Arc<dyn SyncEngine>,
                 lan_discovery: None,
+                lan_sync: None,
                 group_identity,
                 group_transition_pending_restart: std::sync::atomic::AtomicBool::new(false),
                 display_name: "test".into(),
@@ -4130,6 +4179,10 @@ mod tests {
                 backup_status: crate::backup_scheduler::BackupStatusHandle::new(
                     crate::backup_scheduler::SchedulerConfig::from_env(),
                 ),
+                notifier: Arc::new(crate::notifications::Notifier::new()),
+                webhooks: Arc::new(crate::webhooks::Webhooks::new(Vec::new())),
+                shared_spaces: Arc::new(crate::shared_spaces::SharedSpaces::default()),
+                sync_incidents: Arc::new(tesela_sync::IncidentLog::for_mosaic(mosaic)),
             })
         }
 
@@ -4290,6 +4343,7 @@ mod tests {
                 auto_sync: Arc::new(crate::reminders::auto::AutoSync::new()),
                 sync_engine,
                 lan_discovery: None,
+                lan_sync: None,
                 group_identity,
                 group_transition_pending_restart: std::sync::atomic::AtomicBool::new(false),
                 display_name: "test".into(),
@@ -4299,6 +4353,10 @@ mod tests {
                 backup_status: crate::backup_scheduler::BackupStatusHandle::new(
                     crate::backup_scheduler::SchedulerConfig::from_env(),
                 ),
+                notifier: Arc::new(crate::notifications::Notifier::new()),
+                webhooks: Arc::new(crate::webhooks::Webhooks::new(Vec::new())),
+                shared_spaces: Arc::new(crate::shared_spaces::SharedSpaces::default()),
+                sync_incidents: Arc::new(tesela_sync::IncidentLog::for_mosaic(mosaic)),
             })
         }
 
@@ -4462,6 +4520,7 @@ mod tests {
                 auto_sync: Arc::new(crate::reminders::auto::AutoSync::new()),
                 sync_engine: engine,
                 lan_discovery: None,
+                lan_sync: None,
                 group_identity,
                 group_transition_pending_restart: std::sync::atomic::AtomicBool::new(false),
                 display_name: "test".into(),
@@ -4471,6 +4530,10 @@ mod tests {
                 backup_status: crate::backup_scheduler::BackupStatusHandle::new(
                     crate::backup_scheduler::SchedulerConfig::from_env(),
                 ),
+                notifier: Arc::new(crate::notifications::Notifier::new()),
+                webhooks: Arc::new(crate::webhooks::Webhooks::new(Vec::new())),
+                shared_spaces: Arc::new(crate::shared_spaces::SharedSpaces::default()),
+                sync_incidents: Arc::new(tesela_sync::IncidentLog::for_mosaic(tmp)),
             })
         }
 
@@ -5074,6 +5137,17 @@ async fn lookup_value_type(s: &Arc<AppState>, key: &str) -> ValueType {
     }
 }
 
+/// Formula and rollup values are computed by the index at query time and
+/// never stored, so a write to one is refused rather than persisted.
+async fn reject_computed_property(s: &Arc<AppState>, key: &str) -> AppResult<()> {
+    if lookup_value_type(s, key).await.is_computed() {
+        return Err(AppError::Validation(format!(
+            "property '{key}' is computed and can't be set"
+        )));
+    }
+    Ok(())
+}
+
 /// Remove a property from a block and persist. Block-granular counterpart of
 /// `set_block_property` for the *clear* case (TagTable / KanbanBoard "unset").
 ///
@@ -0,0 +1,180 @@
+//! Reminder delivery-state routes: acknowledge or snooze a fired
+//! `deadline::` / `scheduled::` reminder.
+//!
+//! Thin wrappers over [`crate::notifications::Notifier`], which writes the
+//! engine's synced notification-state doc (`tesela_sync::NOTIFICATIONS_DOC_ID`).
+//! The doc rides the same relay/delta streams as note docs, so dismissing a
+//! reminder on the phone silences it on the desktop with no extra plumbing;
+//! these routes add HTTP shape plus the `NotificationStateChanged` WS fan-out
+//! and the doc's binary delta for live device sockets (mirroring
+//! `views::notify_views_changed`).
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Deserialize;
+use tesela_sync::NotificationStateRecord;
+
+use crate::{
+    error::{AppError, AppResult},
+    notifications::DEFAULT_SNOOZE_MIN,
+    state::{AppState, WsEvent},
+};
+
+/// `GET /notifications` — every reminder's delivery state, sorted by id.
+pub async fn list_notifications(
+    State(s): State<Arc<AppState>>,
+) -> Json<Vec<NotificationStateRecord>> {
+    Json(s.sync_engine.notification_states_list().await)
+}
+
+/// `POST /notifications/{id}/ack` — dismiss a fired reminder on every
+/// device. 404 when no reminder with that id has fired.
+pub async fn acknowledge(
+    Path(id): Path<String>,
+    State(s): State<Arc<AppState>>,
+) -> AppResult<Json<NotificationStateRecord>> {
+    let pre_vv = s
+        .sync_engine
+        .doc_version(tesela_sync::NOTIFICATIONS_DOC_ID)
+        .await;
+    let record = s
+        .notifier
+        .acknowledge(&*s.sync_engine, &id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Notification not found: {id}")))?;
+    notify_state_changed(&s, &record, pre_vv.as_deref()).await;
+    Ok(Json(record))
+}
+
+/// Longest snooze `minutes` accepted: a year.
+const MAX_SNOOZE_MIN: i64 = 366 * 24 * 60;
+
+#[derive(Deserialize, Default)]
+pub struct SnoozeBody {
+    /// Snooze for this many minutes from now.
+    pub minutes: Option<i64>,
+    /// Snooze until this RFC 3339 instant. Wins over `minutes`.
+    pub until: Option<String>,
+}
+
+/// `POST /notifications/{id}/snooze` — silence a fired reminder until
+/// `until` (or `minutes` from now, default 10); the scanner fires it again
+/// on its first tick after that. Body is optional.
+pub async fn snooze(
+    Path(id): Path<String>,
+    State(s): State<Arc<AppState>>,
+    body: Option<Json<SnoozeBody>>,
+) -> AppResult<Json<NotificationStateRecord>> {
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+    let until = snooze_until(&body, Utc::now())?;
+    let pre_vv = s
+        .sync_engine
+        .doc_version(tesela_sync::NOTIFICATIONS_DOC_ID)
+        .await;
+    let record = s
+        .notifier
+        .snooze(&*s.sync_engine, &id, until)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Notification not found: {id}")))?;
+    notify_state_changed(&s, &record, pre_vv.as_deref()).await;
+    Ok(Json(record))
+}
+
+/// Resolve a snooze body to the instant the reminder may fire again:
+/// `until` verbatim, else `minutes` (default [`DEFAULT_SNOOZE_MIN`]) from
+/// `now`. `minutes` must be in `1..=MAX_SNOOZE_MIN`.
+fn snooze_until(body: &SnoozeBody, now: DateTime<Utc>) -> AppResult<DateTime<Utc>> {
+    if let Some(raw) = body.until.as_deref() {
+        return Ok(DateTime::parse_from_rfc3339(raw)
+            .map_err(|e| AppError::Validation(format!("invalid snooze until '{raw}': {e}")))?
+            .with_timezone(&Utc));
+    }
+    let minutes = body.minutes.unwrap_or(DEFAULT_SNOOZE_MIN);
+    if !(1..=MAX_SNOOZE_MIN).contains(&minutes) {
+        return Err(AppError::Validation(format!(
+            "snooze minutes must be between 1 and {MAX_SNOOZE_MIN}, got {minutes}"
+        )));
+    }
+    TimeDelta::try_minutes(minutes)
+        .and_then(|delta| now.checked_add_signed(delta))
+        .ok_or_else(|| AppError::Validation(format!("snooze of {minutes} minutes is out of range")))
+}
+
+/// Post-write fan-out: a text `NotificationStateChanged` so open web tabs
+/// drop the toast, plus the write's delta since `pre_vv` (the doc version
+/// captured before it; `None` = the doc didn't exist yet, so the whole doc)
+/// as a binary frame for live device sockets. Best-effort — the relay tick
+/// carries it otherwise.
+async fn notify_state_changed(
+    s: &AppState,
+    record: &NotificationStateRecord,
+    pre_vv: Option<&[u8]>,
+) {
+    let snoozed_until = record
+        .snoozed_until_ms
+        .and_then(DateTime::<Utc>::from_timestamp_millis)
+        .map(|dt| dt.to_rfc3339());
+    let _ = s.ws_tx.send(WsEvent::NotificationStateChanged {
+        notification_id: record.id.clone(),
+        status: record.status.clone(),
+        snoozed_until,
+    });
+    if let Some(delta) = s
+        .sync_engine
+        .export_doc_update(tesela_sync::NOTIFICATIONS_DOC_ID, pre_vv)
+        .await
+    {
+        match tesela_sync::encode_loro_relay_payload(&[tesela_sync::LoroDocUpdate {
+            doc: tesela_sync::NOTIFICATIONS_DOC_ID,
+            update_bytes: delta,
+        }]) {
+            Ok(frame) => {
+                let _ = s.ws_delta_tx.send(crate::state::WsDelta {
+                    origin: None,
+                    source_group: None,
+                    frame,
+                });
+            }
+            Err(e) => tracing::warn!("ws: encode notification-doc delta failed: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(minutes: i64) -> SnoozeBody {
+        SnoozeBody {
+            minutes: Some(minutes),
+            until: None,
+        }
+    }
+
+    #[test]
+    fn snooze_minutes_must_be_in_range() {
+        let now = Utc::now();
+        assert_eq!(
+            snooze_until(&SnoozeBody::default(), now).unwrap(),
+            now + TimeDelta::minutes(DEFAULT_SNOOZE_MIN)
+        );
+        assert_eq!(
+            snooze_until(&minutes(MAX_SNOOZE_MIN), now).unwrap(),
+            now + TimeDelta::minutes(MAX_SNOOZE_MIN)
+        );
+        for bad in [0, -5, MAX_SNOOZE_MIN + 1, i64::MAX, i64::MIN] {
+            assert!(
+                matches!(
+                    snooze_until(&minutes(bad), now),
+                    Err(AppError::Validation(_))
+                ),
+                "{bad} minutes should be rejected"
+            );
+        }
+    }
+}
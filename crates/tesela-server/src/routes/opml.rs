@@ -0,0 +1,105 @@
+//! OPML exchange — a page or a query result out as OPML, an OPML outline
+//! in as a new page. Conversion lives in `tesela_core::opml`; import goes
+//! through the regular note-create path.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::response::IntoResponse;
+use axum::Json;
+use serde::Deserialize;
+use tesela_core::note::{Note, NoteId};
+use tesela_core::opml::{note_to_opml, opml_to_note, query_result_to_opml, OpmlImportOptions};
+use tesela_core::query::parse_query;
+use tesela_core::storage::markdown::sanitize_filename;
+use tesela_core::traits::note_store::NoteStore;
+
+use super::notes::{create_note, CreateNoteReq};
+use super::search_query::{page_query_context, ExecuteQueryBody};
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+
+const OPML_CONTENT_TYPE: &str = "text/x-opml; charset=utf-8";
+
+/// GET /notes/{id}/opml — the page's block outline as OPML.
+pub async fn export_note(
+    Path(id): Path<String>,
+    State(s): State<Arc<AppState>>,
+) -> AppResult<impl IntoResponse> {
+    let note = s
+        .store
+        .get(&NoteId::new(&id))
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Note not found: {}", id)))?;
+    Ok((
+        [(axum::http::header::CONTENT_TYPE, OPML_CONTENT_TYPE)],
+        note_to_opml(&note),
+    ))
+}
+
+/// POST /search/query/opml — same body as `/search/query`, rows as OPML.
+pub async fn export_query(
+    State(s): State<Arc<AppState>>,
+    Json(body): Json<ExecuteQueryBody>,
+) -> AppResult<impl IntoResponse> {
+    let context = page_query_context(&*s.sync_engine).await;
+    let result = s
+        .index
+        .execute_query_with_context(
+            &parse_query(&body.dsl),
+            body.group.as_deref(),
+            body.sort.as_deref(),
+            &context,
+        )
+        .await?;
+    Ok((
+        [(axum::http::header::CONTENT_TYPE, OPML_CONTENT_TYPE)],
+        query_result_to_opml(&body.dsl, &result),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct ImportOpmlReq {
+    /// The OPML document.
+    pub opml: String,
+    /// Page title; defaults to the OPML `<head><title>`.
+    pub title: Option<String>,
+    pub tags: Option<Vec<String>>,
+    /// Keep `bid` attributes as block ids (see `OpmlImportOptions`).
+    #[serde(default)]
+    pub keep_ids: bool,
+}
+
+/// POST /imports/opml — create a page from an OPML outline.
+pub async fn import(
+    State(s): State<Arc<AppState>>,
+    Json(req): Json<ImportOpmlReq>,
+) -> AppResult<Json<Note>> {
+    let imported = opml_to_note(
+        &req.opml,
+        OpmlImportOptions {
+            keep_ids: req.keep_ids,
+        },
+    )
+    .map_err(|e| AppError::Validation(e.to_string()))?;
+    let title = req
+        .title
+        .or(imported.title)
+        .ok_or_else(|| AppError::Validation("OPML has no title; pass `title`".into()))?;
+    let slug = sanitize_filename(&title);
+    if s.store.get(&NoteId::new(&slug)).await?.is_some() {
+        return Err(AppError::Conflict(format!(
+            "Note '{}' already exists",
+            title
+        )));
+    }
+    create_note(
+        State(s),
+        Json(CreateNoteReq {
+            title,
+            content: imported.body,
+            tags: req.tags,
+        }),
+    )
+    .await
+}
@@ -10,11 +10,13 @@
 //! - `GET  /sync/peer/peers`      list of paired peers (JSON)
 //! - `POST /sync/peer/peers`      add a peer (JSON in / JSON out)
 //! - `DELETE /sync/peer/peers/{device_id_hex}`  remove a peer
-//! - `POST /sync/peer/produce`    "what's new since cursor X?" (JSON in / postcard out)
-//! - `POST /sync/peer/envelope`   receive a SyncEnvelope (postcard in / 204 out)
-//! - `POST /sync/peer/now`        trigger immediate sync with all peers (JSON out)
+//! - `POST /sync/peer/now`        sync with every LAN peer right away (JSON out)
 //! - `GET  /sync/peer/status`     per-peer last sync info (JSON out)
 //! - `GET  /sync/peer/discovered` (Phase 2.1) mDNS-discovered LAN peers (JSON out)
+//!
+//! These routes are plain HTTP and carry no note data. Note data between
+//! LAN peers moves only over the pinned TLS sessions `crate::lan_sync`
+//! drives; the old plaintext `produce` / `envelope` exchange is gone.
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -22,21 +24,17 @@ use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{Duration, Instant};
 
 use axum::{
-    body::Bytes,
     extract::{Path as AxPath, State},
     http::StatusCode,
-    response::Response,
     Json,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use tesela_core::{db::SqliteIndex, storage::filesystem::FsNoteStore};
 use tesela_sync::{
     decode_pairing_code, encode_pairing_code, DeviceId, GroupIdentity, PairingCode, PeerCursor,
 };
-use tokio::sync::broadcast;
 
-use crate::state::{AppState, WsEvent};
+use crate::state::AppState;
 
 const PEERS_FILE: &str = "sync_peers.json";
 const GROUP_TRANSITION_PENDING_RESTART: &str = "group transition pending restart";
@@ -54,15 +52,6 @@ pub struct DeviceInfo {
     pub device_id_hex: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ProduceRequest {
-    /// Hex device id of the requester ("give me your ops not from me").
-    pub peer_device: String,
-    /// Last NTP64 the requester has seen from this device. None means
-    /// "from the beginning."
-    pub since_hlc_ntp: Option<i64>,
-}
-
 #[derive(Debug, Serialize)]
 pub struct PeerStatus {
     pub device_id_hex: String,
@@ -124,44 +113,29 @@ pub async fn remove_peer(
     }
 }
 
-// ─── LAN peer-to-peer data plane — RETIRED at the Loro flag-day ──────────
-//
-// 2026-05-29: the op-replay pull model below (peer asks "give me your ops
-// since cursor X", applies them locally) is fundamentally incompatible with
-// the Loro engine — Loro has no per-device op log to replay from an HLC
-// cursor; its unit of sync is a per-note version-vector update. Devices
-// already converge through the relay spine (the proven web↔iOS path), which
-// makes LAN P2P a pure latency optimization that is fully redundant with the
-// relay for correctness. So the data-plane endpoints below return 501 and the
-// daemon path is a no-op. Pairing + discovery (get_device / add_peer /
-// pairing-code / discovered / status) stay live so a future LAN P2P built on
-// the Loro relay-update protocol can reuse them.
-
-const PEER_DATA_PLANE_RETIRED: &str =
-    "LAN peer op-pull was retired at the Loro cutover; devices sync via the relay";
-
-pub async fn produce(
-    State(_s): State<Arc<AppState>>,
-    Json(_req): Json<ProduceRequest>,
-) -> Result<Response, (StatusCode, String)> {
-    Err((
-        StatusCode::NOT_IMPLEMENTED,
-        PEER_DATA_PLANE_RETIRED.to_string(),
-    ))
-}
-
-pub async fn receive_envelope(
-    State(_s): State<Arc<AppState>>,
-    _body: Bytes,
-) -> Result<StatusCode, (StatusCode, String)> {
-    Err((
-        StatusCode::NOT_IMPLEMENTED,
-        PEER_DATA_PLANE_RETIRED.to_string(),
-    ))
-}
-
-pub async fn sync_now(State(_s): State<Arc<AppState>>) -> Json<Value> {
-    Json(json!({ "peers": {}, "note": PEER_DATA_PLANE_RETIRED }))
+/// `POST /sync/peer/now` — run a LAN sync round with every known peer
+/// instead of waiting for the next [`crate::lan_sync::LAN_SYNC_INTERVAL`].
+/// Per-peer `applied` (notes imported) or `error`.
+pub async fn sync_now(State(s): State<Arc<AppState>>) -> Json<Value> {
+    let Some(lan) = s.lan_sync.as_ref() else {
+        return Json(json!({
+            "peers": {},
+            "note": "LAN sync is off (mDNS disabled or unavailable); devices sync via the relay"
+        }));
+    };
+    let peers: serde_json::Map<String, Value> = lan
+        .sync_now()
+        .await
+        .into_iter()
+        .map(|(device, result)| {
+            let outcome = match result {
+                Ok(applied) => json!({ "applied": applied }),
+                Err(error) => json!({ "error": error }),
+            };
+            (device.to_hex(), outcome)
+        })
+        .collect();
+    Json(json!({ "peers": peers }))
 }
 
 /// Phase 2.1 — mDNS-discovered LAN peers. These are candidates only;
@@ -545,28 +519,6 @@ async fn read_peers(mosaic_root: &Path) -> Vec<Peer> {
     }
 }
 
-/// Daemon-friendly variant that does not require an `AppState`. Used by
-/// the background sync loop in `main.rs`. Takes the group identity by
-/// reference so the daemon can hold its own `RwLock` and snapshot once
-/// per tick. The store / index / ws_tx are used to reindex touched notes
-/// and broadcast `WsEvent` so the web UI live-updates without a hard
-/// refresh.
-pub async fn sync_with_peer_minimal(
-    _engine: &dyn tesela_sync::SyncEngine,
-    _mosaic_root: &Path,
-    _store: &FsNoteStore,
-    _index: &SqliteIndex,
-    _ws_tx: &broadcast::Sender<WsEvent>,
-    _peer: &Peer,
-    _ident: &GroupIdentity,
-) -> Result<u32, String> {
-    // Retired at the Loro flag-day — see the data-plane note above. The
-    // background daemon still calls this each tick; it's a no-op (devices sync
-    // via the relay). Kept with its signature so the daemon wiring in main.rs
-    // and a future Loro-based LAN sync slot in without further plumbing.
-    Ok(0)
-}
-
 /// After `apply_changes` materializes ops onto disk, walk the set of
 /// touched `note_id`s and (a) re-fetch the note via the store so the
 /// derived SQL projections (tasks view, search index, link graph) are
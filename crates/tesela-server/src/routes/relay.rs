@@ -8,6 +8,9 @@
 //!   a one-click `/server/restart` after save.
 //! - `DELETE /sync/relay/config` — remove the block (returns to
 //!   LAN-only sync on next boot).
+//! - `POST /sync/relay/rotate-key` — rotate the group key, revoking the
+//!   listed devices (see `group_rotation`). The new epoch takes effect
+//!   immediately; no restart is needed.
 
 use std::sync::Arc;
 
@@ -18,6 +21,7 @@ use serde::{Deserialize, Serialize};
 
 use tesela_core::config::{Config, RelayConfig};
 
+use crate::group_rotation::rotate_group_key;
 use crate::state::AppState;
 use crate::sync_relay::RelayStatus;
 
@@ -149,6 +153,97 @@ pub async fn delete_config(
     }))
 }
 
+/// Body for `POST /sync/relay/rotate-key`.
+#[derive(Debug, Deserialize)]
+pub struct RotateKeyReq {
+    /// Hex device ids to exclude from the new epoch (a lost phone). May be
+    /// empty for a routine rotation.
+    #[serde(default)]
+    pub revoke: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotateKeyResp {
+    /// The new key epoch.
+    pub epoch: u32,
+    /// Hex ids of the devices excluded from it.
+    pub revoked: Vec<String>,
+    /// Always `false` — the relay client and the relay tick loop adopt
+    /// the new epoch live. Kept so the response matches the config routes.
+    pub restart_required: bool,
+}
+
+/// `POST /sync/relay/rotate-key`.
+///
+/// Mints the next group key epoch and distributes it to every other
+/// announced member through the relay; devices in `revoke` are dropped
+/// from `group_members` and get no grant. Holds the group identity's
+/// write lease (like pairing) so no data-plane request runs against a
+/// half-swapped key, then publishes the new identity; the relay tick
+/// loop follows it on its next iteration.
+pub async fn rotate_key(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<RotateKeyReq>,
+) -> Result<Json<RotateKeyResp>, (StatusCode, String)> {
+    let Some(handle) = state.relay.as_ref() else {
+        return Err((
+            StatusCode::CONFLICT,
+            "key rotation needs a configured relay".to_string(),
+        ));
+    };
+    let Some(membership) = handle.membership.as_ref() else {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "group key epochs unavailable on this node".to_string(),
+        ));
+    };
+    let mut revoke = Vec::with_capacity(req.revoke.len());
+    for raw in &req.revoke {
+        let bytes: [u8; 16] = hex::decode(raw.trim())
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("`{raw}` is not a 32-char hex device id"),
+                )
+            })?;
+        revoke.push(tesela_sync::DeviceId::from_bytes(bytes));
+    }
+
+    let mut current = state.group_identity.write().await;
+    if state
+        .group_transition_pending_restart
+        .load(std::sync::atomic::Ordering::Acquire)
+    {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "group_transition_pending_restart".to_string(),
+        ));
+    }
+    let rotated = rotate_group_key(membership, &handle.client, &revoke)
+        .await
+        .map_err(|e| {
+            // Protocol/Crypto: a member without an announced key or
+            // self-revocation. Storage is ours; the rest is the relay
+            // round trip.
+            let status = match e {
+                tesela_sync::SyncError::Protocol(_) | tesela_sync::SyncError::Crypto(_) => {
+                    StatusCode::CONFLICT
+                }
+                tesela_sync::SyncError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                _ => StatusCode::BAD_GATEWAY,
+            };
+            (status, format!("rotate: {e}"))
+        })?;
+    *current = rotated.identity;
+    Ok(Json(RotateKeyResp {
+        epoch: rotated.epoch,
+        revoked: rotated.revoked.iter().map(|d| d.to_hex()).collect(),
+        restart_required: false,
+    }))
+}
+
 fn load_or_default(path: &std::path::Path) -> Result<Config, (StatusCode, String)> {
     if path.exists() {
         Config::load(path).map_err(server_error)
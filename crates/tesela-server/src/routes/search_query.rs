@@ -3,7 +3,9 @@ use std::sync::Arc;
 use axum::extract::State;
 use axum::Json;
 use serde::Deserialize;
-use tesela_core::query::{parse_query, QueryContext, QueryPage, QueryResult};
+use tesela_core::query::{parse_query, QueryContext, QueryResult};
+
+use tesela_sync::SyncEngine;
 
 use crate::{error::AppResult, state::AppState};
 
@@ -23,24 +25,7 @@ pub async fn execute(
     Json(body): Json<ExecuteQueryBody>,
 ) -> AppResult<Json<QueryResult>> {
     let parsed = parse_query(&body.dsl);
-    // The synced directory is the sole authority for Node RHS resolution;
-    // SQLite remains a rebuildable query projection and receives it only as
-    // additive matcher context.
-    let context = QueryContext {
-        pages: s
-            .sync_engine
-            .page_directory_list()
-            .await
-            .into_iter()
-            .map(|entry| QueryPage {
-                page_id: entry.page_id,
-                slug: entry.slug,
-                title: entry.title,
-                aliases: entry.aliases,
-                deleted: entry.deleted || entry.conflict,
-            })
-            .collect(),
-    };
+    let context = page_query_context(&*s.sync_engine).await;
     let result = s
         .index
         .execute_query_with_context(
@@ -52,3 +37,9 @@ pub async fn execute(
         .await?;
     Ok(Json(result))
 }
+
+/// Matcher context for DSL queries: the synced page directory, see
+/// [`tesela_sync::page_query_context`].
+pub(crate) async fn page_query_context(engine: &dyn SyncEngine) -> QueryContext {
+    tesela_sync::page_query_context(engine.page_directory_list().await)
+}
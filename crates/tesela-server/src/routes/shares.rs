@@ -0,0 +1,300 @@
+//! Shared-space routes: share a handful of pages with a partner's mosaic
+//! through a second group identity (see `crate::shared_spaces`).
+//!
+//! `POST /sync/shares` creates a space over some pages and returns its
+//! `tesela-share:` code; the partner passes that code to
+//! `POST /sync/shares/join`. Pages can be added or dropped later on either
+//! side — a page the partner adds simply arrives. Leaving a space stops
+//! its sync; the pages stay in both mosaics.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tesela_sync::{GroupId, GroupKey, ShareCode};
+
+use crate::{
+    error::{AppError, AppResult},
+    shared_spaces::{self, ShareFanOut, ShareHandle, SharedSpaceState},
+    state::AppState,
+};
+
+/// A page in a space, as this mosaic's page directory knows it.
+#[derive(Debug, Serialize)]
+pub struct SharedPage {
+    pub note_id: String,
+    pub slug: Option<String>,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareSummary {
+    pub share_id: String,
+    pub name: String,
+    pub relay_url: String,
+    pub notes: Vec<SharedPage>,
+    /// Pages the partner sent that this mosaic already holds outside the
+    /// space, and so refused.
+    pub conflicts: Vec<String>,
+    pub created_at: i64,
+    pub registered_at: Option<i64>,
+    pub inbound_cursor: i64,
+    pub last_poll_at: Option<i64>,
+    pub last_put_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+async fn summarize(s: &AppState, handle: &ShareHandle) -> ShareSummary {
+    let state = handle.state.read().await.clone();
+    let directory = s.sync_engine.page_directory_list().await;
+    let notes = state
+        .docs
+        .iter()
+        .map(|note_id| {
+            let entry = directory
+                .iter()
+                .find(|e| e.loro_doc_id == *note_id && !e.deleted);
+            SharedPage {
+                note_id: note_id.clone(),
+                slug: entry.map(|e| e.slug.clone()),
+                title: entry.map(|e| e.title.clone()),
+            }
+        })
+        .collect();
+    ShareSummary {
+        share_id: state.share_id,
+        name: state.name,
+        relay_url: state.relay_url,
+        notes,
+        conflicts: state.conflicts,
+        created_at: state.created_at,
+        registered_at: state.registered_at,
+        inbound_cursor: state.inbound_cursor,
+        last_poll_at: state.last_poll_at,
+        last_put_at: state.last_put_at,
+        last_error: state.last_error,
+    }
+}
+
+/// A page by slug (or 32-hex doc id) → its live doc id.
+async fn resolve_page(s: &AppState, note: &str) -> AppResult<[u8; 16]> {
+    let note = note.trim();
+    let directory = s.sync_engine.page_directory_list().await;
+    let entry = directory
+        .iter()
+        .filter(|e| !e.deleted)
+        .find(|e| e.slug.eq_ignore_ascii_case(note) || e.loro_doc_id.eq_ignore_ascii_case(note))
+        .ok_or_else(|| AppError::NotFound(format!("page not found: {note}")))?;
+    if entry.conflict {
+        return Err(AppError::Conflict(format!(
+            "page {note} has conflicting directory bindings"
+        )));
+    }
+    crate::sync_relay::parse_hex_note_id(&entry.loro_doc_id)
+        .ok_or_else(|| AppError::Internal(anyhow::anyhow!("malformed directory doc id")))
+}
+
+async fn space(s: &AppState, share_id: &str) -> AppResult<ShareHandle> {
+    let id = crate::sync_relay::parse_hex_note_id(&share_id.trim().to_ascii_lowercase())
+        .ok_or_else(|| AppError::Validation(format!("invalid share id: {share_id}")))?;
+    s.shared_spaces
+        .get(id)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("no shared space {share_id}")))
+}
+
+/// Persist a new space, start its loop and track it.
+async fn start_space(
+    s: &AppState,
+    state: SharedSpaceState,
+    key: GroupKey,
+) -> AppResult<ShareHandle> {
+    shared_spaces::persist_new(&s.mosaic_root, &state, &key).await?;
+    let handle = ShareHandle::new(state, key, s.sync_engine.device(), s.mosaic_root.clone())?;
+    let task = shared_spaces::spawn(
+        ShareFanOut::from_state(s),
+        handle.clone(),
+        crate::relay_poll_interval(&s.mosaic_root),
+    );
+    s.shared_spaces.insert(handle.clone(), Some(task)).await;
+    Ok(handle)
+}
+
+fn share_code(s: &AppState, handle: &ShareHandle, state: &SharedSpaceState) -> AppResult<String> {
+    tesela_sync::encode_share_code(&ShareCode {
+        share_id: handle.share_id,
+        share_key_bytes: *handle.key.as_bytes(),
+        name: state.name.clone(),
+        relay_url: state.relay_url.clone(),
+        display_name: s.display_name.clone(),
+        version: tesela_sync::SHARE_CODE_VERSION,
+    })
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("encode share code: {e}")))
+}
+
+/// `GET /sync/shares` — every shared space on this device.
+pub async fn list_shares(State(s): State<Arc<AppState>>) -> Json<Vec<ShareSummary>> {
+    let mut out = Vec::new();
+    for handle in s.shared_spaces.list().await {
+        out.push(summarize(&s, &handle).await);
+    }
+    Json(out)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateShareBody {
+    pub name: String,
+    /// Slugs (or doc ids) of the pages to share.
+    #[serde(default)]
+    pub notes: Vec<String>,
+    /// Relay for the space; defaults to the mosaic's configured relay.
+    pub relay_url: Option<String>,
+}
+
+/// `POST /sync/shares` — create a space over some pages; returns it with
+/// the share code to hand to the partner.
+pub async fn create_share(
+    State(s): State<Arc<AppState>>,
+    Json(body): Json<CreateShareBody>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let name = body.name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::Validation("name must not be empty".into()));
+    }
+    let relay_url = body
+        .relay_url
+        .or_else(|| s.relay_url.clone())
+        .ok_or_else(|| {
+            AppError::Validation(
+                "shared spaces sync through a relay: pass relay_url or configure [sync.relay]"
+                    .into(),
+            )
+        })?;
+    reqwest::Url::parse(&relay_url)
+        .map_err(|e| AppError::Validation(format!("invalid relay_url: {e}")))?;
+    let mut docs = Vec::with_capacity(body.notes.len());
+    for note in &body.notes {
+        docs.push(resolve_page(&s, note).await?);
+    }
+    let share_id = GroupId::new_random();
+    let state = SharedSpaceState::new(share_id, name, relay_url, &docs);
+    let handle = start_space(&s, state.clone(), GroupKey::random()).await?;
+    let code = share_code(&s, &handle, &state)?;
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({ "space": summarize(&s, &handle).await, "code": code })),
+    ))
+}
+
+/// `GET /sync/shares/{id}/code` — the space's share code.
+pub async fn get_share_code(
+    Path(share_id): Path<String>,
+    State(s): State<Arc<AppState>>,
+) -> AppResult<Json<serde_json::Value>> {
+    let handle = space(&s, &share_id).await?;
+    let state = handle.state.read().await.clone();
+    let code = share_code(&s, &handle, &state)?;
+    Ok(Json(json!({ "code": code, "name": state.name })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JoinShareBody {
+    pub code: String,
+}
+
+/// `POST /sync/shares/join` — join a partner's space from its share code.
+pub async fn join_share(
+    State(s): State<Arc<AppState>>,
+    Json(body): Json<JoinShareBody>,
+) -> AppResult<(StatusCode, Json<ShareSummary>)> {
+    let code = tesela_sync::decode_share_code(&body.code)
+        .map_err(|e| AppError::Validation(format!("share code: {e}")))?;
+    if code.share_id == s.group_identity.read().await.group_id {
+        return Err(AppError::Validation(
+            "that code names this mosaic's own sync group".into(),
+        ));
+    }
+    if s.shared_spaces
+        .get(*code.share_id.as_bytes())
+        .await
+        .is_some()
+    {
+        return Err(AppError::Conflict(format!(
+            "already in shared space {}",
+            hex::encode(code.share_id.as_bytes())
+        )));
+    }
+    reqwest::Url::parse(&code.relay_url)
+        .map_err(|e| AppError::Validation(format!("share code relay URL: {e}")))?;
+    let state = SharedSpaceState::new(
+        code.share_id,
+        code.name.clone(),
+        code.relay_url.clone(),
+        &[],
+    );
+    let handle = start_space(&s, state, code.identity().group_key).await?;
+    Ok((StatusCode::CREATED, Json(summarize(&s, &handle).await)))
+}
+
+/// `DELETE /sync/shares/{id}` — leave a space. Its pages stay here.
+pub async fn leave_share(
+    Path(share_id): Path<String>,
+    State(s): State<Arc<AppState>>,
+) -> AppResult<Json<serde_json::Value>> {
+    let handle = space(&s, &share_id).await?;
+    s.shared_spaces.remove(*handle.share_id.as_bytes()).await;
+    let share_id = hex::encode(handle.share_id.as_bytes());
+    shared_spaces::remove_files(&s.mosaic_root, &share_id).await;
+    Ok(Json(json!({ "deleted": true, "share_id": share_id })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SharePageBody {
+    /// Slug (or doc id) of the page.
+    pub note: String,
+}
+
+/// `POST /sync/shares/{id}/notes` — add a page to a space.
+pub async fn add_share_note(
+    Path(share_id): Path<String>,
+    State(s): State<Arc<AppState>>,
+    Json(body): Json<SharePageBody>,
+) -> AppResult<Json<ShareSummary>> {
+    let handle = space(&s, &share_id).await?;
+    let doc = resolve_page(&s, &body.note).await?;
+    {
+        let mut state = handle.state.write().await;
+        if state.add_doc(doc) {
+            state.save(&s.mosaic_root).await?;
+        }
+    }
+    Ok(Json(summarize(&s, &handle).await))
+}
+
+/// `DELETE /sync/shares/{id}/notes/{note}` — stop sharing a page. Both
+/// sides keep their copy.
+pub async fn remove_share_note(
+    Path((share_id, note)): Path<(String, String)>,
+    State(s): State<Arc<AppState>>,
+) -> AppResult<Json<ShareSummary>> {
+    let handle = space(&s, &share_id).await?;
+    let doc = match crate::sync_relay::parse_hex_note_id(&note.trim().to_ascii_lowercase()) {
+        Some(doc) => doc,
+        None => resolve_page(&s, &note).await?,
+    };
+    {
+        let mut state = handle.state.write().await;
+        if !state.remove_doc(doc) {
+            return Err(AppError::NotFound(format!(
+                "{note} is not in shared space {share_id}"
+            )));
+        }
+        state.save(&s.mosaic_root).await?;
+    }
+    Ok(Json(summarize(&s, &handle).await))
+}
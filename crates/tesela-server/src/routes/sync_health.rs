@@ -0,0 +1,235 @@
+//! Sync-health inspector: every anomaly the engine knows about, per note,
+//! with the deterministic repair that clears it (see
+//! [`tesela_sync::SyncAnomaly`]).
+//!
+//! - `GET /sync/health` — scan now; anomalies most severe first, plus the
+//!   relay daemon's catch-up queue and last error. Read-only.
+//! - `POST /sync/health/scan` — the same scan, with changes since the last
+//!   one recorded in the incident log (as the periodic monitor does).
+//! - `GET /sync/health/incidents` — the incident history, newest first.
+//! - `POST /sync/health/repair` — run one repair: `repair_garbled_blocks`
+//!   (every note's twin blocks) or `snapshot_catchup` (one note's causal
+//!   gap, healed by the relay tick). The follow-up scan is recorded.
+//!
+//! Directory conflicts and outbound strands carry no action: the first
+//! needs a person to pick the page's document, the second heals itself on
+//! the next relay tick.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use tesela_sync::{AnomalyKind, AnomalySeverity, SyncAnomaly, SyncIncident, SyncRepair};
+
+use crate::{
+    error::{AppError, AppResult},
+    state::AppState,
+};
+
+#[derive(Debug, Default, Serialize)]
+pub struct SeverityCounts {
+    pub error: usize,
+    pub warning: usize,
+    pub info: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncHealthReport {
+    /// No warnings or errors (info-level strands don't count).
+    pub healthy: bool,
+    pub counts: SeverityCounts,
+    pub anomalies: Vec<SyncAnomaly>,
+    /// Outbound strand alarms raised since the process started.
+    pub strand_alarms: u64,
+    /// Notes the relay daemon has queued for a snapshot catch-up.
+    pub catchup_queue: Vec<String>,
+    pub relay_last_error: Option<String>,
+}
+
+/// Scan the engine; `record` folds the result into the incident log.
+async fn report(s: &AppState, record: bool) -> SyncHealthReport {
+    let anomalies = if record {
+        crate::sync_health::scan(&*s.sync_engine, &s.sync_incidents).await
+    } else {
+        s.sync_engine.sync_anomalies().await
+    };
+    let mut counts = SeverityCounts::default();
+    for a in &anomalies {
+        match a.severity {
+            AnomalySeverity::Error => counts.error += 1,
+            AnomalySeverity::Warning => counts.warning += 1,
+            AnomalySeverity::Info => counts.info += 1,
+        }
+    }
+    let (catchup_queue, relay_last_error) = match s.relay.as_ref() {
+        Some(handle) => {
+            let state = handle.state.read().await;
+            (state.catchup_notes.clone(), state.last_error.clone())
+        }
+        None => (Vec::new(), None),
+    };
+    SyncHealthReport {
+        healthy: counts.error == 0 && counts.warning == 0,
+        counts,
+        anomalies,
+        strand_alarms: s.sync_engine.outbound_strand_alarm_count().await,
+        catchup_queue,
+        relay_last_error,
+    }
+}
+
+/// `GET /sync/health`.
+pub async fn get_health(State(s): State<Arc<AppState>>) -> Json<SyncHealthReport> {
+    Json(report(&s, false).await)
+}
+
+/// `POST /sync/health/scan`.
+pub async fn scan(State(s): State<Arc<AppState>>) -> Json<SyncHealthReport> {
+    Json(report(&s, true).await)
+}
+
+#[derive(Deserialize)]
+pub struct IncidentsQuery {
+    /// Max entries (default 50). The log keeps the most recent 500.
+    pub limit: Option<usize>,
+}
+
+/// `GET /sync/health/incidents` — newest first.
+pub async fn list_incidents(
+    State(s): State<Arc<AppState>>,
+    Query(q): Query<IncidentsQuery>,
+) -> Json<Vec<SyncIncident>> {
+    Json(s.sync_incidents.recent(q.limit.unwrap_or(50)).await)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RepairBody {
+    pub action: SyncRepair,
+    /// Slug or 32-hex doc id. Required for `snapshot_catchup`.
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepairResponse {
+    pub action: SyncRepair,
+    /// Notes the repair touched (hex).
+    pub notes: Vec<String>,
+    pub incidents: Vec<SyncIncident>,
+    /// Health after the repair.
+    pub health: SyncHealthReport,
+}
+
+/// `POST /sync/health/repair`.
+pub async fn repair(
+    State(s): State<Arc<AppState>>,
+    Json(body): Json<RepairBody>,
+) -> AppResult<Json<RepairResponse>> {
+    let before = s.sync_engine.sync_anomalies().await;
+    let mut incidents = Vec::new();
+    let notes = match body.action {
+        SyncRepair::RepairGarbledBlocks => {
+            let healed = s.sync_engine.heal_disjoint_twins().await;
+            let mut notes: Vec<[u8; 16]> = Vec::new();
+            for (note_id, _) in &healed {
+                if !notes.contains(note_id) {
+                    notes.push(*note_id);
+                }
+            }
+            for note_id in &notes {
+                crate::routes::ws::emit_note_updated(
+                    &*s.sync_engine,
+                    &s.store,
+                    &s.index,
+                    &s.ws_tx,
+                    *note_id,
+                    true,
+                    None,
+                )
+                .await;
+                let hex_id = hex::encode(note_id);
+                let blocks = healed.iter().filter(|(id, _)| id == note_id).count();
+                if let Some(anomaly) = before
+                    .iter()
+                    .find(|a| a.note_id == hex_id && a.kind == AnomalyKind::GarbledBlocks)
+                {
+                    incidents.push(
+                        s.sync_incidents
+                            .record_repair(
+                                anomaly,
+                                SyncRepair::RepairGarbledBlocks,
+                                format!("collapsed {blocks} twin block(s)"),
+                            )
+                            .await?,
+                    );
+                }
+            }
+            notes
+        }
+        SyncRepair::SnapshotCatchup => {
+            let note = body
+                .note
+                .as_deref()
+                .ok_or_else(|| AppError::Validation("snapshot_catchup needs a `note`".into()))?;
+            let note_id = resolve_note(&s, note).await?;
+            let hex_id = hex::encode(note_id);
+            let anomaly = before
+                .iter()
+                .find(|a| a.note_id == hex_id && a.kind == AnomalyKind::CausalGap)
+                .ok_or_else(|| AppError::NotFound(format!("{note} has no causal gap")))?;
+            let handle = s.relay.as_ref().ok_or_else(|| {
+                AppError::Conflict("snapshot catch-up needs a configured relay".into())
+            })?;
+            s.sync_engine.rearm_snapshot_catchup(note_id).await;
+            {
+                // Same queueing as the tick's ledger drain: bounded at the
+                // current inbound cursor, healed from the relay snapshot.
+                let mut state = handle.state.write().await;
+                let floor = state.inbound_cursor.max(1);
+                state
+                    .catchup_since_seq
+                    .entry(hex_id.clone())
+                    .or_insert(floor);
+                if !state.catchup_notes.contains(&hex_id) {
+                    state.catchup_notes.push(hex_id.clone());
+                }
+                state.save(&handle.mosaic_root).await?;
+            }
+            incidents.push(
+                s.sync_incidents
+                    .record_repair(
+                        anomaly,
+                        SyncRepair::SnapshotCatchup,
+                        "queued a snapshot catch-up on the relay tick".into(),
+                    )
+                    .await?,
+            );
+            vec![note_id]
+        }
+    };
+    Ok(Json(RepairResponse {
+        action: body.action,
+        notes: notes.iter().map(hex::encode).collect(),
+        incidents,
+        health: report(&s, true).await,
+    }))
+}
+
+/// A slug (or 32-hex doc id) → its doc id.
+async fn resolve_note(s: &AppState, note: &str) -> AppResult<[u8; 16]> {
+    let note = note.trim();
+    if let Some(id) = crate::sync_relay::parse_hex_note_id(&note.to_ascii_lowercase()) {
+        return Ok(id);
+    }
+    s.sync_engine
+        .page_directory_list()
+        .await
+        .iter()
+        .filter(|e| !e.deleted)
+        .find(|e| e.slug.eq_ignore_ascii_case(note))
+        .and_then(|e| crate::sync_relay::parse_hex_note_id(&e.loro_doc_id))
+        .ok_or_else(|| AppError::NotFound(format!("page not found: {note}")))
+}
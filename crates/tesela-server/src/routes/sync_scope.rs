@@ -0,0 +1,182 @@
+//! Selective-sync routes: per-device rules that keep notes (by tag, page
+//! type or slug prefix) off a device.
+//!
+//! The rules live in the engine's synced scope registry
+//! (`tesela_sync::SYNC_SCOPES_DOC_ID`), so a device's scope can be edited
+//! from any device in the group; each device enforces only its own entry.
+//! Editing THIS device's entry reconciles immediately — evicting newly
+//! excluded notes and queueing readmitted ones for a relay snapshot
+//! catch-up. Excluded pages keep their page-directory binding, so links to
+//! them resolve and `GET /sync/scope` lists them as stubs.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tesela_sync::DeviceSyncScope;
+
+use crate::{
+    error::{AppError, AppResult},
+    state::AppState,
+};
+
+/// An excluded page as this device still knows it: its directory binding,
+/// without content.
+#[derive(Debug, Serialize)]
+pub struct ExcludedPageStub {
+    pub note_id: String,
+    pub slug: Option<String>,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncScopeResponse {
+    /// This device's id, so callers can tell which entry applies here.
+    pub device: String,
+    /// Every device's rules, sorted by device.
+    pub scopes: Vec<DeviceSyncScope>,
+    /// Pages this device currently keeps out.
+    pub excluded: Vec<ExcludedPageStub>,
+}
+
+/// `GET /sync/scope` — every device's rules plus this device's excluded
+/// pages as directory stubs.
+pub async fn get_scope(State(s): State<Arc<AppState>>) -> Json<SyncScopeResponse> {
+    let engine = &s.sync_engine;
+    let directory = engine.page_directory_list().await;
+    let excluded = engine
+        .scope_excluded_notes()
+        .await
+        .into_iter()
+        .map(|id| {
+            let note_id = hex::encode(id);
+            let entry = directory
+                .iter()
+                .find(|e| e.loro_doc_id == note_id && !e.deleted);
+            ExcludedPageStub {
+                slug: entry.map(|e| e.slug.clone()),
+                title: entry.map(|e| e.title.clone()),
+                note_id,
+            }
+        })
+        .collect();
+    Json(SyncScopeResponse {
+        device: engine.device().to_hex(),
+        scopes: engine.sync_scopes_list().await,
+        excluded,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScopeBody {
+    #[serde(default)]
+    pub exclude_tags: Vec<String>,
+    #[serde(default)]
+    pub exclude_types: Vec<String>,
+    #[serde(default)]
+    pub exclude_slug_prefixes: Vec<String>,
+}
+
+/// `PUT /sync/scope/{device}` — replace one device's rules.
+pub async fn put_scope(
+    Path(device): Path<String>,
+    State(s): State<Arc<AppState>>,
+    Json(body): Json<ScopeBody>,
+) -> AppResult<Json<DeviceSyncScope>> {
+    let device = device.trim().to_ascii_lowercase();
+    s.sync_engine
+        .sync_scope_upsert(DeviceSyncScope {
+            device: device.clone(),
+            exclude_tags: body.exclude_tags,
+            exclude_types: body.exclude_types,
+            exclude_slug_prefixes: body.exclude_slug_prefixes,
+            updated_at_ms: chrono::Utc::now().timestamp_millis(),
+        })
+        .await
+        .map_err(|e| match e {
+            tesela_sync::SyncError::Protocol(msg) => AppError::Validation(msg),
+            e => AppError::Internal(anyhow::anyhow!("sync_scope_upsert: {e}")),
+        })?;
+    after_scope_change(&s, &device).await;
+    let scope = s
+        .sync_engine
+        .sync_scopes_list()
+        .await
+        .into_iter()
+        .find(|scope| scope.device == device)
+        .ok_or_else(|| AppError::Internal(anyhow::anyhow!("sync scope vanished after write")))?;
+    Ok(Json(scope))
+}
+
+/// `DELETE /sync/scope/{device}` — drop one device's rules; it syncs
+/// everything again.
+pub async fn delete_scope(
+    Path(device): Path<String>,
+    State(s): State<Arc<AppState>>,
+) -> AppResult<Json<serde_json::Value>> {
+    let device = device.trim().to_ascii_lowercase();
+    let removed = s
+        .sync_engine
+        .sync_scope_delete(&device)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("sync_scope_delete: {e}")))?;
+    if !removed {
+        return Err(AppError::NotFound(format!(
+            "no sync scope for device {device}"
+        )));
+    }
+    after_scope_change(&s, &device).await;
+    Ok(Json(json!({ "deleted": true, "device": device })))
+}
+
+/// Post-write: apply our own rules right away, then send the registry's
+/// delta to live device sockets (the relay tick carries it otherwise).
+async fn after_scope_change(s: &AppState, device: &str) {
+    let engine = &*s.sync_engine;
+    if device == engine.device().to_hex() {
+        let readmitted = engine.reconcile_sync_scope().await;
+        if !readmitted.is_empty() {
+            match s.relay.as_ref() {
+                Some(relay) => {
+                    let mut state = relay.state.write().await;
+                    crate::sync_relay::queue_scope_readmissions(&mut state, &readmitted);
+                }
+                None => tracing::warn!(
+                    "sync scope: {} readmitted note(s) need a relay snapshot catch-up \
+                     but no relay is configured",
+                    readmitted.len()
+                ),
+            }
+        }
+        crate::routes::ws::emit_scope_evictions(
+            engine,
+            &s.store,
+            &s.index,
+            &s.ws_tx,
+            engine.take_scope_evictions().await,
+        )
+        .await;
+    }
+    if let Some(delta) = engine
+        .export_doc_update(tesela_sync::SYNC_SCOPES_DOC_ID, None)
+        .await
+    {
+        match tesela_sync::encode_loro_relay_payload(&[tesela_sync::LoroDocUpdate {
+            doc: tesela_sync::SYNC_SCOPES_DOC_ID,
+            update_bytes: delta,
+        }]) {
+            Ok(frame) => {
+                let _ = s.ws_delta_tx.send(crate::state::WsDelta {
+                    origin: None,
+                    source_group: None,
+                    frame,
+                });
+            }
+            Err(e) => tracing::warn!("ws: encode sync-scope delta failed: {e}"),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! Outbound webhook introspection: which `[[webhooks]]` are configured and
+//! how recent deliveries went. Delivery itself lives in
+//! [`crate::webhooks`]; webhooks are configured in `.tesela/config.toml`,
+//! not over HTTP, so these routes are read-only.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+
+use crate::{
+    state::AppState,
+    webhooks::{WebhookDelivery, WebhookSummary},
+};
+
+/// `GET /webhooks` — configured webhooks, secrets redacted.
+pub async fn list_webhooks(State(s): State<Arc<AppState>>) -> Json<Vec<WebhookSummary>> {
+    Json(s.webhooks.summaries())
+}
+
+#[derive(Deserialize)]
+pub struct DeliveriesQuery {
+    /// Only deliveries for the webhook with this `name`.
+    pub webhook: Option<String>,
+    /// Max entries (default 50). The log keeps the most recent 200.
+    pub limit: Option<usize>,
+}
+
+/// `GET /webhooks/deliveries` — recent deliveries, newest first.
+pub async fn list_deliveries(
+    State(s): State<Arc<AppState>>,
+    Query(q): Query<DeliveriesQuery>,
+) -> Json<Vec<WebhookDelivery>> {
+    Json(
+        s.webhooks
+            .deliveries(q.webhook.as_deref(), q.limit.unwrap_or(50))
+            .await,
+    )
+}
@@ -18,6 +18,7 @@ use tesela_core::{
 };
 use tokio::sync::{broadcast, mpsc};
 
+use crate::metrics::{metrics, ConnectionGuard};
 use crate::routes::notes::{is_property_definition, rebuild_relation_edges_for_materialized_note};
 use crate::state::{AppState, ConnId, GroupScope, WsDelta, WsEvent};
 
@@ -49,6 +50,7 @@ pub async fn ws_handler(ws: WebSocketUpgrade, State(s): State<Arc<AppState>>) ->
 }
 
 async fn handle_socket(mut socket: WebSocket, s: Arc<AppState>, conn_id: ConnId) {
+    let _connection = ConnectionGuard::open(&metrics().ws_connections);
     let canonical_mosaic = match std::fs::canonicalize(&s.mosaic_root) {
         Ok(path) => path.to_string_lossy().into_owned(),
         Err(error) => {
@@ -506,6 +508,12 @@ pub async fn apply_inbound_delta(
             need_catchup.push(hex_id);
         }
     }
+    if !report.readmitted.is_empty() {
+        if let Some(relay) = relay {
+            let mut state = relay.state.write().await;
+            crate::sync_relay::queue_scope_readmissions(&mut state, &report.readmitted);
+        }
+    }
     let mut healed: Vec<[u8; 16]> = Vec::new();
     if !need_catchup.is_empty() {
         if let Some(relay) = relay {
@@ -534,6 +542,14 @@ pub async fn apply_inbound_delta(
         }
     }
 
+    emit_scope_evictions(
+        engine,
+        store,
+        index,
+        ws_tx,
+        engine.take_scope_evictions().await,
+    )
+    .await;
     if report.applied_count() == 0 && healed.is_empty() {
         return outcome;
     }
@@ -576,7 +592,9 @@ fn outcome_for_apply_report(
     update_count: usize,
     report: &tesela_sync::RelayApplyReport,
 ) -> InboundFrameOutcome {
-    if report.pending.is_empty() && report.failed.is_empty() && report.applied.len() == update_count
+    if report.pending.is_empty()
+        && report.failed.is_empty()
+        && report.applied.len() + report.excluded.len() == update_count
     {
         InboundFrameOutcome::AppliedAll
     } else {
@@ -665,6 +683,31 @@ pub async fn emit_note_updated(
     }
 }
 
+/// Drop notes the engine evicted for this device's sync scope from the
+/// search index and tell web they're gone. The engine already removed the
+/// note's doc, index entry and materialized file; its page-directory
+/// binding stays, so links to it still resolve.
+pub async fn emit_scope_evictions(
+    engine: &dyn tesela_sync::SyncEngine,
+    store: &FsNoteStore,
+    index: &SqliteIndex,
+    ws_tx: &broadcast::Sender<WsEvent>,
+    evictions: Vec<tesela_sync::ScopeEviction>,
+) {
+    for eviction in evictions {
+        emit_note_updated(
+            engine,
+            store,
+            index,
+            ws_tx,
+            eviction.note_id,
+            false,
+            eviction.slug.as_deref(),
+        )
+        .await;
+    }
+}
+
 /// Map a 16-byte note id back to its filename slug by scanning the engine's
 /// Loro index (`note_id` hex → `slug`). The index is self-healing and small;
 /// a linear scan per applied note is acceptable on the live-sync path.
@@ -0,0 +1,1175 @@
+//! Shared sub-spaces: a second sync group, scoped to selected note docs,
+//! shared with a partner who has their own mosaic.
+//!
+//! Each space is its own group identity (id + key, handed out as a
+//! `tesela-share:` code) with its own relay registration and inbound
+//! cursor, ticked by its own loop next to the mosaic's relay daemon. The
+//! loop ships the space's docs through the same per-note Loro update
+//! stream the group relay uses and imports the partner's updates through
+//! `apply_relay_updates`, so edits from either side merge into both
+//! mosaics — and from there reach each side's own devices over their
+//! ordinary group sync.
+//!
+//! What crosses the boundary is deliberately narrow:
+//!
+//! - Outbound, only docs on the space's list are exported; the per-doc
+//!   version last shipped (`sent_versions`) is the space's own cursor and
+//!   never touches the engine's group broadcast cursors.
+//! - Inbound, a doc is admitted if it's on the list, or if this mosaic has
+//!   never heard of it (a page the partner added to the space) — it then
+//!   joins the list. Special docs (directory, views, …) and pages this
+//!   mosaic already holds outside the space are refused and reported as
+//!   `conflicts`, so a partner can never write into an unshared page.
+//!   So is an unseen doc whose slug names a page this mosaic already
+//!   holds: materializing it would overwrite that page's file.
+//!
+//! Each side deposits a snapshot of the space's docs on the group relay's
+//! cadence, covering what it has applied, so the relay can trim the
+//! space's op log; a late joiner (or a side that fell behind the trim)
+//! bootstraps from those snapshots before polling the tail.
+//!
+//! On disk: `.tesela/shares/<share_id>.json` (state + cursors). The space
+//! key goes through the group-key store under `.tesela/shares/<share_id>/`
+//! — the Keychain on macOS, a `group_key.bin` file elsewhere — and a
+//! plaintext `<share_id>.key` from an older install is moved there on
+//! load.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::AbortHandle;
+
+use tesela_core::{db::SqliteIndex, storage::filesystem::FsNoteStore};
+use tesela_sync::crypto::keys;
+use tesela_sync::transport::relay::RelayClient;
+use tesela_sync::{DeviceId, GroupId, GroupKey, SyncEngine, SyncEnvelope};
+
+use crate::state::{AppState, WsDelta, WsEvent};
+use crate::sync_relay::MAX_APPLY_RETRIES;
+
+/// Persisted state of one shared space.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SharedSpaceState {
+    /// The space's group id, hex.
+    pub share_id: String,
+    pub name: String,
+    pub relay_url: String,
+    /// Note docs in the space, hex, sorted.
+    pub docs: Vec<String>,
+    pub created_at: i64,
+    /// Highest relay seq applied + acked for this space.
+    pub inbound_cursor: i64,
+    pub registered_at: Option<i64>,
+    pub last_poll_at: Option<i64>,
+    pub last_put_at: Option<i64>,
+    pub last_error: Option<String>,
+    /// Docs the partner sent that this mosaic already holds outside the
+    /// space — refused, listed so the user can see why a page didn't
+    /// arrive.
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+    /// Encoded version vector last shipped to the space, per doc.
+    #[serde(default)]
+    pub sent_versions: HashMap<String, String>,
+    /// Failed-apply attempts per inbound seq (bounded retry, as the group
+    /// relay tick does).
+    #[serde(default)]
+    pub apply_retries: HashMap<i64, u32>,
+    /// Wall-clock seconds of the last snapshot deposit.
+    #[serde(default)]
+    pub last_snapshot_at: Option<i64>,
+}
+
+fn shares_dir(mosaic_root: &Path) -> PathBuf {
+    mosaic_root.join(".tesela").join("shares")
+}
+
+/// The directory the group-key store keys a space's key by.
+fn key_dir(mosaic_root: &Path, share_id_hex: &str) -> PathBuf {
+    shares_dir(mosaic_root).join(share_id_hex)
+}
+
+impl SharedSpaceState {
+    /// A fresh space over `docs`.
+    pub fn new(share_id: GroupId, name: String, relay_url: String, docs: &[[u8; 16]]) -> Self {
+        let mut state = SharedSpaceState {
+            share_id: hex::encode(share_id.as_bytes()),
+            name,
+            relay_url,
+            created_at: now_secs_i64(),
+            ..Default::default()
+        };
+        for doc in docs {
+            state.add_doc(*doc);
+        }
+        state
+    }
+
+    pub fn contains(&self, doc: [u8; 16]) -> bool {
+        self.docs.contains(&hex::encode(doc))
+    }
+
+    /// Add a doc to the space. Returns `false` if it was already there.
+    pub fn add_doc(&mut self, doc: [u8; 16]) -> bool {
+        let hex_id = hex::encode(doc);
+        match self.docs.binary_search(&hex_id) {
+            Ok(_) => false,
+            Err(at) => {
+                self.conflicts.retain(|c| *c != hex_id);
+                self.docs.insert(at, hex_id);
+                true
+            }
+        }
+    }
+
+    /// Drop a doc from the space (the local page stays). Returns `false`
+    /// if it wasn't there.
+    pub fn remove_doc(&mut self, doc: [u8; 16]) -> bool {
+        let hex_id = hex::encode(doc);
+        let before = self.docs.len();
+        self.docs.retain(|d| *d != hex_id);
+        self.sent_versions.remove(&hex_id);
+        self.docs.len() != before
+    }
+
+    /// Same tmp-then-rename write as `RelayState::save`.
+    pub async fn save(&self, mosaic_root: &Path) -> Result<()> {
+        let dir = shares_dir(mosaic_root);
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .with_context(|| format!("create dir {}", dir.display()))?;
+        let path = dir.join(format!("{}.json", self.share_id));
+        let bytes = serde_json::to_vec_pretty(self).context("serialize shared space")?;
+        let tmp = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, &bytes)
+            .await
+            .with_context(|| format!("write tmp {}", tmp.display()))?;
+        tokio::fs::rename(&tmp, &path)
+            .await
+            .with_context(|| format!("rename {}", path.display()))?;
+        Ok(())
+    }
+}
+
+/// Persist a new space: key first, so a state file never exists without
+/// its key.
+pub async fn persist_new(
+    mosaic_root: &Path,
+    state: &SharedSpaceState,
+    key: &GroupKey,
+) -> Result<()> {
+    store_key(&key_dir(mosaic_root, &state.share_id), key).await?;
+    state.save(mosaic_root).await
+}
+
+/// Put a space key in the active group-key store. The directory is
+/// owner-only so the file store's copy isn't readable by other users.
+async fn store_key(dir: &Path, key: &GroupKey) -> Result<()> {
+    tokio::fs::create_dir_all(dir)
+        .await
+        .with_context(|| format!("create dir {}", dir.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+            .await
+            .with_context(|| format!("chmod {}", dir.display()))?;
+    }
+    keys::store_group_key_active(dir, key)
+        .await
+        .with_context(|| format!("store space key under {}", dir.display()))
+}
+
+/// A space's key from the group-key store, moving a legacy plaintext
+/// `<share_id>.key` into the store (and shredding it) on first load.
+async fn load_key(mosaic_root: &Path, share_id_hex: &str) -> Result<GroupKey> {
+    let dir = key_dir(mosaic_root, share_id_hex);
+    if let Some(key) = keys::load_group_key_active(&dir).await? {
+        return Ok(key);
+    }
+    let legacy = shares_dir(mosaic_root).join(format!("{share_id_hex}.key"));
+    let bytes = tokio::fs::read(&legacy)
+        .await
+        .with_context(|| format!("no key stored (read {})", legacy.display()))?;
+    let raw =
+        <[u8; 32]>::try_from(bytes.as_slice()).map_err(|_| anyhow::anyhow!("malformed key"))?;
+    let key = GroupKey::from_bytes(raw);
+    store_key(&dir, &key).await?;
+    shred(&legacy).await;
+    Ok(key)
+}
+
+/// Zero a file, then remove it. Best-effort; missing is fine.
+async fn shred(path: &Path) {
+    let Ok(meta) = tokio::fs::metadata(path).await else {
+        return;
+    };
+    if let Err(e) = tokio::fs::write(path, vec![0u8; meta.len() as usize]).await {
+        tracing::warn!("shared space: zero {}: {e}", path.display());
+    }
+    if let Err(e) = tokio::fs::remove_file(path).await {
+        tracing::warn!("shared space: remove {}: {e}", path.display());
+    }
+}
+
+/// Every persisted space with its key. Unreadable entries are skipped
+/// with a warning.
+pub async fn load_all(mosaic_root: &Path) -> Vec<(SharedSpaceState, GroupKey)> {
+    let dir = shares_dir(mosaic_root);
+    let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let state: SharedSpaceState = match tokio::fs::read(&path)
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| serde_json::from_slice(&bytes).map_err(anyhow::Error::from))
+        {
+            Ok(state) => state,
+            Err(e) => {
+                tracing::warn!(
+                    "shared space {}: unreadable ({e}); skipping",
+                    path.display()
+                );
+                continue;
+            }
+        };
+        let key = match load_key(mosaic_root, &state.share_id).await {
+            Ok(key) => key,
+            Err(e) => {
+                tracing::warn!("shared space {}: key: {e:#}; skipping", state.share_id);
+                continue;
+            }
+        };
+        out.push((state, key));
+    }
+    out.sort_by(|a, b| {
+        a.0.name
+            .cmp(&b.0.name)
+            .then(a.0.share_id.cmp(&b.0.share_id))
+    });
+    out
+}
+
+/// Remove a space's files and drop its key from the group-key store. The
+/// pages it carried stay in the mosaic.
+pub async fn remove_files(mosaic_root: &Path, share_id_hex: &str) {
+    let dir = shares_dir(mosaic_root);
+    let path = dir.join(format!("{share_id_hex}.json"));
+    if let Err(e) = tokio::fs::remove_file(&path).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            tracing::warn!("shared space: remove {}: {e}", path.display());
+        }
+    }
+    shred(&dir.join(format!("{share_id_hex}.key"))).await;
+    let key_dir = key_dir(mosaic_root, share_id_hex);
+    if let Err(e) = keys::forget_group_key(&key_dir).await {
+        tracing::warn!("shared space {share_id_hex}: forget key: {e}");
+    }
+    if let Err(e) = tokio::fs::remove_dir(&key_dir).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            tracing::warn!("shared space: remove {}: {e}", key_dir.display());
+        }
+    }
+}
+
+/// Runtime handle for one space. Cloneable so routes and the space's loop
+/// see the same state.
+#[derive(Clone)]
+pub struct ShareHandle {
+    pub share_id: GroupId,
+    pub key: GroupKey,
+    pub client: Arc<RelayClient>,
+    pub state: Arc<RwLock<SharedSpaceState>>,
+    pub mosaic_root: PathBuf,
+}
+
+impl ShareHandle {
+    pub fn new(
+        state: SharedSpaceState,
+        key: GroupKey,
+        device: DeviceId,
+        mosaic_root: PathBuf,
+    ) -> Result<Self> {
+        let share_id = crate::sync_relay::parse_hex_note_id(&state.share_id)
+            .map(GroupId::from_bytes)
+            .context("shared space id is not 32 hex chars")?;
+        let url = reqwest::Url::parse(&state.relay_url)
+            .with_context(|| format!("shared space relay URL `{}`", state.relay_url))?;
+        Ok(ShareHandle {
+            share_id,
+            client: Arc::new(RelayClient::new(url, share_id, device, key.clone())),
+            key,
+            state: Arc::new(RwLock::new(state)),
+            mosaic_root,
+        })
+    }
+}
+
+/// A live space and its loop (`None` where no loop was started).
+type LiveSpace = (ShareHandle, Option<AbortHandle>);
+
+/// Live spaces and their loops, held by `AppState`.
+#[derive(Default)]
+pub struct SharedSpaces {
+    spaces: RwLock<HashMap<[u8; 16], LiveSpace>>,
+}
+
+impl SharedSpaces {
+    /// Every live space, by name.
+    pub async fn list(&self) -> Vec<ShareHandle> {
+        let mut handles: Vec<ShareHandle> = self
+            .spaces
+            .read()
+            .await
+            .values()
+            .map(|(h, _)| h.clone())
+            .collect();
+        let mut named = Vec::with_capacity(handles.len());
+        for h in handles.drain(..) {
+            let name = h.state.read().await.name.clone();
+            named.push((name, h));
+        }
+        named.sort_by(|a, b| a.0.cmp(&b.0));
+        named.into_iter().map(|(_, h)| h).collect()
+    }
+
+    pub async fn get(&self, share_id: [u8; 16]) -> Option<ShareHandle> {
+        self.spaces
+            .read()
+            .await
+            .get(&share_id)
+            .map(|(h, _)| h.clone())
+    }
+
+    /// Track a space (and its loop). Replaces — and stops — a previous
+    /// entry for the same id.
+    pub async fn insert(&self, handle: ShareHandle, task: Option<AbortHandle>) {
+        let previous = self
+            .spaces
+            .write()
+            .await
+            .insert(*handle.share_id.as_bytes(), (handle, task));
+        if let Some((_, Some(task))) = previous {
+            task.abort();
+        }
+    }
+
+    /// Stop and forget a space.
+    pub async fn remove(&self, share_id: [u8; 16]) -> Option<ShareHandle> {
+        let (handle, task) = self.spaces.write().await.remove(&share_id)?;
+        if let Some(task) = task {
+            task.abort();
+        }
+        Some(handle)
+    }
+}
+
+/// Register on the relay under the space's identity and verify the stored
+/// intent — the same handshake as the group relay's bring-up.
+pub async fn bring_up(handle: &ShareHandle) -> Result<(), String> {
+    let registered_at = handle
+        .client
+        .register_or_recover()
+        .await
+        .map_err(|e| format!("register: {e}"))?;
+    handle
+        .client
+        .verify_registration()
+        .await
+        .map_err(|e| format!("verify: {e}"))?;
+    let mut state = handle.state.write().await;
+    state.registered_at = Some(registered_at);
+    state.last_error = None;
+    if let Err(e) = state.save(&handle.mosaic_root).await {
+        tracing::warn!("shared space save (post-bringup): {e}");
+    }
+    Ok(())
+}
+
+/// Outcome of one space tick, for the caller's WS fan-out.
+#[derive(Debug, Default)]
+pub struct ShareTickOutcome {
+    pub applied_note_ids: Vec<[u8; 16]>,
+    /// Exact bytes that applied, re-broadcast to live device sockets.
+    pub applied_updates: Vec<tesela_sync::LoroDocUpdate>,
+    pub sent: u32,
+    pub scope_evictions: Vec<tesela_sync::ScopeEviction>,
+}
+
+/// Whether an inbound doc may enter this mosaic through the space; admits
+/// (and lists) docs this mosaic has never seen, unless their slug is taken
+/// by a local page.
+async fn admit_doc(
+    engine: &dyn SyncEngine,
+    state: &mut SharedSpaceState,
+    update: &tesela_sync::LoroDocUpdate,
+) -> bool {
+    let doc = update.doc;
+    if state.contains(doc) {
+        return true;
+    }
+    let hex_id = hex::encode(doc);
+    let known = tesela_sync::is_special_doc(&doc)
+        || engine.doc_version(doc).await.is_some()
+        || engine
+            .page_directory_list()
+            .await
+            .iter()
+            .any(|e| e.loro_doc_id == hex_id);
+    let slug_taken = match update.note_slug() {
+        Some(slug) if !known => engine
+            .index_entries()
+            .await
+            .iter()
+            .any(|e| e.note_id != hex_id && e.slug.eq_ignore_ascii_case(&slug)),
+        _ => false,
+    };
+    if known || slug_taken {
+        if !state.conflicts.contains(&hex_id) {
+            let why = if known {
+                "already held here outside the space"
+            } else {
+                "its slug names a page held here"
+            };
+            tracing::warn!("shared space {}: refusing {hex_id} — {why}", state.share_id);
+            state.conflicts.push(hex_id);
+        }
+        return false;
+    }
+    state.add_doc(doc);
+    true
+}
+
+/// Admit and apply one batch of partner updates, recording what applied in
+/// `outcome`. Shared by the poll path and the snapshot bootstrap.
+async fn apply_partner_updates(
+    engine: &dyn SyncEngine,
+    state: &mut SharedSpaceState,
+    updates: Vec<tesela_sync::LoroDocUpdate>,
+    outcome: &mut ShareTickOutcome,
+) -> tesela_sync::RelayApplyReport {
+    let mut pairs: Vec<([u8; 16], Vec<u8>)> = Vec::new();
+    // Docs with nothing unsent before this apply (including ones the
+    // partner just introduced): their partner-only change needn't be
+    // echoed back, so their sent version follows the import.
+    let mut in_sync: Vec<[u8; 16]> = Vec::new();
+    for update in updates {
+        let listed = state.contains(update.doc);
+        if !admit_doc(engine, state, &update).await {
+            continue;
+        }
+        let sent = state.sent_versions.get(&hex::encode(update.doc));
+        if !listed
+            || (sent.is_some()
+                && engine
+                    .doc_version(update.doc)
+                    .await
+                    .map(hex::encode)
+                    .as_ref()
+                    == sent)
+        {
+            in_sync.push(update.doc);
+        }
+        pairs.push((update.doc, update.update_bytes));
+    }
+    if pairs.is_empty() {
+        return Default::default();
+    }
+    let report = engine.apply_relay_updates(&pairs).await;
+    for (doc, bytes) in pairs {
+        if !report.applied.contains(&doc) {
+            continue;
+        }
+        if !outcome.applied_note_ids.contains(&doc) {
+            outcome.applied_note_ids.push(doc);
+        }
+        if in_sync.contains(&doc) {
+            if let Some(version) = engine.doc_version(doc).await {
+                state
+                    .sent_versions
+                    .insert(hex::encode(doc), hex::encode(version));
+            }
+        }
+        outcome.applied_updates.push(tesela_sync::LoroDocUpdate {
+            doc,
+            update_bytes: bytes,
+        });
+    }
+    report
+}
+
+/// Catch up from the space's deposited snapshots when the relay has trimmed
+/// ops this side hasn't applied. The cursor jumps to the watermark only if
+/// every admitted snapshot applied; otherwise it holds and the next poll
+/// retries.
+async fn bootstrap_from_snapshots(
+    engine: &dyn SyncEngine,
+    handle: &ShareHandle,
+    state: &mut SharedSpaceState,
+    outcome: &mut ShareTickOutcome,
+) {
+    let (compaction_seq, snaps) = match handle.client.fetch_snapshots().await {
+        Ok(v) => v,
+        Err(e) => {
+            tracing::warn!("shared space {} snapshot fetch: {e}", state.share_id);
+            return;
+        }
+    };
+    if compaction_seq <= state.inbound_cursor {
+        return;
+    }
+    let updates = snaps
+        .into_iter()
+        .filter_map(|(stream_id, _seq, update_bytes)| {
+            let doc = <[u8; 16]>::try_from(stream_id.as_slice()).ok()?;
+            Some(tesela_sync::LoroDocUpdate { doc, update_bytes })
+        })
+        .collect();
+    let report = apply_partner_updates(engine, state, updates, outcome).await;
+    if report.failed.is_empty() && report.pending.is_empty() {
+        state.inbound_cursor = compaction_seq;
+        if let Err(e) = handle.client.ack(compaction_seq).await {
+            tracing::debug!("shared space ack({compaction_seq}): {e}");
+        }
+    } else {
+        let msg = format!(
+            "shared space {}: snapshot bootstrap incomplete ({} failed, {} pending); \
+             cursor held at {}",
+            state.share_id,
+            report.failed.len(),
+            report.pending.len(),
+            state.inbound_cursor
+        );
+        tracing::warn!("{msg}");
+        state.last_error = Some(msg);
+    }
+}
+
+/// Deposit a snapshot of every space doc this side holds, covering the
+/// inbound cursor, so the relay can trim the space's op log. Skipped (and
+/// retried next tick) while an apply is retrying or a space doc sits
+/// outside this device's sync scope — neither is safely held yet.
+async fn deposit_snapshots(
+    engine: &dyn SyncEngine,
+    handle: &ShareHandle,
+    state: &mut SharedSpaceState,
+) {
+    let covers_seq = state.inbound_cursor;
+    if covers_seq <= 0 || !state.apply_retries.is_empty() {
+        return;
+    }
+    let excluded = engine.scope_excluded_notes().await;
+    let mut snapshots: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+    for hex_id in &state.docs {
+        let Some(doc) = crate::sync_relay::parse_hex_note_id(hex_id) else {
+            continue;
+        };
+        if excluded.contains(&doc) {
+            return;
+        }
+        if let Some(bytes) = engine.export_doc_update(doc, None).await {
+            snapshots.push((doc.to_vec(), bytes));
+        }
+    }
+    match handle
+        .client
+        .put_snapshots_chunked(
+            covers_seq,
+            snapshots,
+            crate::sync_relay::deposit_chunk_budget_bytes(),
+        )
+        .await
+    {
+        Ok(_) => state.last_snapshot_at = Some(now_secs_i64()),
+        Err(e) => {
+            let msg = format!("shared space snapshot deposit: {e}");
+            tracing::warn!("{msg}");
+            state.last_error = Some(msg);
+        }
+    }
+}
+
+/// One poll + produce pass for a space: import the partner's updates for
+/// admitted docs, then ship whatever changed locally in the space's docs
+/// since they were last sent.
+pub async fn tick(engine: &dyn SyncEngine, handle: &ShareHandle) -> Result<ShareTickOutcome> {
+    let mut state = handle.state.write().await;
+    let mut outcome = ShareTickOutcome::default();
+    let our_device = engine.device();
+
+    // ─── Inbound ─────────────────────────────────────────────────────
+    match handle.client.poll(state.inbound_cursor).await {
+        Ok(mut batch) => {
+            // The relay trimmed ops this side hasn't applied: catch up from
+            // the deposited snapshots first, and leave the tail for the next
+            // poll until that lands.
+            if batch
+                .compaction_seq
+                .is_some_and(|cs| cs > state.inbound_cursor)
+            {
+                bootstrap_from_snapshots(engine, handle, &mut state, &mut outcome).await;
+                if batch
+                    .compaction_seq
+                    .is_some_and(|cs| cs > state.inbound_cursor)
+                {
+                    batch.rows.clear();
+                    batch.skipped.clear();
+                }
+            }
+            let mut max_seq = batch
+                .skipped
+                .iter()
+                .copied()
+                .fold(state.inbound_cursor, i64::max);
+            let mut blocked_at: Option<i64> = None;
+            for (seq, env) in batch.rows {
+                if env.from_device == our_device {
+                    max_seq = max_seq.max(seq);
+                    continue;
+                }
+                let updates = match tesela_sync::decode_loro_relay_payload(&env.ciphertext) {
+                    Ok(Some(updates)) => updates,
+                    Ok(None) => {
+                        max_seq = max_seq.max(seq);
+                        continue;
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "shared space {} loro decode seq={seq}: {e} (skipping)",
+                            state.share_id
+                        );
+                        max_seq = max_seq.max(seq);
+                        continue;
+                    }
+                };
+                let report = apply_partner_updates(engine, &mut state, updates, &mut outcome).await;
+                if !report.pending.is_empty() {
+                    tracing::warn!(
+                        "shared space {}: {} doc(s) pending a causal gap after seq={seq}",
+                        state.share_id,
+                        report.pending.len()
+                    );
+                }
+                if report.failed.is_empty() {
+                    state.apply_retries.remove(&seq);
+                    max_seq = max_seq.max(seq);
+                    continue;
+                }
+                let attempts = {
+                    let a = state.apply_retries.entry(seq).or_insert(0);
+                    *a += 1;
+                    *a
+                };
+                if attempts >= MAX_APPLY_RETRIES {
+                    let msg = format!(
+                        "shared space {}: giving up on seq={seq} after {attempts} failed apply \
+                         attempts: {:?}",
+                        state.share_id,
+                        report
+                            .failed
+                            .iter()
+                            .map(|(id, e)| format!("{}: {e}", hex::encode(id)))
+                            .collect::<Vec<_>>()
+                    );
+                    tracing::error!("{msg}");
+                    state.last_error = Some(msg);
+                    state.apply_retries.remove(&seq);
+                    max_seq = max_seq.max(seq);
+                } else if blocked_at.is_none_or(|b| seq < b) {
+                    blocked_at = Some(seq);
+                }
+            }
+            if let Some(b) = blocked_at {
+                max_seq = max_seq.min(b - 1);
+            }
+            if max_seq > state.inbound_cursor {
+                state.inbound_cursor = max_seq;
+                if let Err(e) = handle.client.ack(max_seq).await {
+                    tracing::debug!("shared space ack({max_seq}): {e}");
+                }
+            }
+            state.last_poll_at = Some(now_secs_i64());
+        }
+        Err(e) => {
+            let msg = format!("shared space poll: {e}");
+            tracing::warn!("{msg}");
+            state.last_error = Some(msg);
+        }
+    }
+
+    // ─── Outbound ────────────────────────────────────────────────────
+    let mut requests: Vec<([u8; 16], Option<Vec<u8>>)> = Vec::new();
+    for hex_id in &state.docs {
+        let Some(doc) = crate::sync_relay::parse_hex_note_id(hex_id) else {
+            continue;
+        };
+        let Some(current) = engine.doc_version(doc).await else {
+            continue;
+        };
+        let since = state
+            .sent_versions
+            .get(hex_id)
+            .and_then(|v| hex::decode(v).ok());
+        if since.as_deref() == Some(current.as_slice()) {
+            continue;
+        }
+        requests.push((doc, since));
+    }
+    if !requests.is_empty() {
+        let updates = engine
+            .export_doc_updates_with_versions(&requests)
+            .await
+            .into_iter()
+            .map(|e| (e.note_id, e.update_bytes, e.version))
+            .collect();
+        for (payload, committed) in
+            tesela_sync::pack_loro_relay_batches(updates, tesela_sync::MAX_RELAY_PLAINTEXT_BYTES)
+        {
+            let ciphertext = match tesela_sync::encode_loro_relay_payload(&payload) {
+                Ok(c) => c,
+                Err(e) => {
+                    state.last_error = Some(format!("encode loro payload: {e}"));
+                    continue;
+                }
+            };
+            let envelope = SyncEnvelope {
+                from_device: our_device,
+                to_group: handle.share_id,
+                nonce: [0u8; 24],
+                ciphertext,
+            };
+            match handle.client.put_envelope(envelope).await {
+                Ok(_) => {
+                    for (doc, version) in committed {
+                        state
+                            .sent_versions
+                            .insert(hex::encode(doc), hex::encode(version));
+                    }
+                    outcome.sent += 1;
+                    state.last_put_at = Some(now_secs_i64());
+                }
+                Err(e) => {
+                    let msg = format!("shared space put: {e}");
+                    tracing::warn!("{msg}");
+                    state.last_error = Some(msg);
+                }
+            }
+        }
+    }
+
+    // ─── Snapshot deposit ────────────────────────────────────────────
+    let due = state
+        .last_snapshot_at
+        .is_none_or(|t| now_secs_i64() - t >= crate::sync_relay::snapshot_interval_secs());
+    if due {
+        deposit_snapshots(engine, handle, &mut state).await;
+    }
+
+    if let Err(e) = state.save(&handle.mosaic_root).await {
+        tracing::warn!("shared space save: {e}");
+    }
+    outcome.scope_evictions = engine.take_scope_evictions().await;
+    Ok(outcome)
+}
+
+/// Handles a space's loop needs to surface partner edits: the same
+/// index/WS fan-out the group relay loop does.
+#[derive(Clone)]
+pub struct ShareFanOut {
+    pub engine: Arc<dyn SyncEngine>,
+    pub store: Arc<FsNoteStore>,
+    pub index: Arc<SqliteIndex>,
+    pub ws_tx: broadcast::Sender<WsEvent>,
+    pub ws_delta_tx: broadcast::Sender<WsDelta>,
+}
+
+impl ShareFanOut {
+    pub fn from_state(state: &AppState) -> Self {
+        ShareFanOut {
+            engine: Arc::clone(&state.sync_engine),
+            store: Arc::clone(&state.store),
+            index: Arc::clone(&state.index),
+            ws_tx: state.ws_tx.clone(),
+            ws_delta_tx: state.ws_delta_tx.clone(),
+        }
+    }
+}
+
+/// Run a space's loop: bring-up (retried each pass until it succeeds),
+/// then tick, parking on the relay's op stream in between.
+pub fn spawn(fan_out: ShareFanOut, handle: ShareHandle, poll_interval: Duration) -> AbortHandle {
+    tokio::spawn(async move {
+        let mut announced = 0i64;
+        let mut first = true;
+        loop {
+            if !std::mem::take(&mut first) {
+                let cursor = handle.state.read().await.inbound_cursor;
+                if let Some(seq) = handle
+                    .client
+                    .wait_for_ops(cursor.max(announced), poll_interval)
+                    .await
+                {
+                    announced = seq;
+                }
+            }
+            if handle.state.read().await.registered_at.is_none() {
+                if let Err(e) = bring_up(&handle).await {
+                    tracing::warn!("shared space bring-up: {e} (will retry)");
+                    handle.state.write().await.last_error = Some(e);
+                    continue;
+                }
+            }
+            let outcome = match tick(&*fan_out.engine, &handle).await {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    tracing::debug!("shared space tick: {e}");
+                    continue;
+                }
+            };
+            for note_id in &outcome.applied_note_ids {
+                crate::routes::ws::emit_note_updated(
+                    &*fan_out.engine,
+                    &fan_out.store,
+                    &fan_out.index,
+                    &fan_out.ws_tx,
+                    *note_id,
+                    true,
+                    None,
+                )
+                .await;
+            }
+            crate::routes::ws::emit_scope_evictions(
+                &*fan_out.engine,
+                &fan_out.store,
+                &fan_out.index,
+                &fan_out.ws_tx,
+                outcome.scope_evictions,
+            )
+            .await;
+            // Partner edits are local news to every socket of this mosaic,
+            // whichever group it's bound to — `source_group: None`.
+            if !outcome.applied_updates.is_empty() {
+                if let Ok(frame) = tesela_sync::encode_loro_relay_payload(&outcome.applied_updates)
+                {
+                    let _ = fan_out.ws_delta_tx.send(WsDelta {
+                        origin: None,
+                        source_group: None,
+                        frame,
+                    });
+                }
+            }
+        }
+    })
+    .abort_handle()
+}
+
+/// Load every persisted space and start its loop. Called once at startup.
+pub async fn start_all(state: &AppState, poll_interval: Duration) {
+    let device = state.sync_engine.device();
+    for (space, key) in load_all(&state.mosaic_root).await {
+        let share_id = space.share_id.clone();
+        match ShareHandle::new(space, key, device, state.mosaic_root.clone()) {
+            Ok(handle) => {
+                let task = spawn(
+                    ShareFanOut::from_state(state),
+                    handle.clone(),
+                    poll_interval,
+                );
+                state.shared_spaces.insert(handle, Some(task)).await;
+            }
+            Err(e) => tracing::warn!("shared space {share_id}: {e:#}; not started"),
+        }
+    }
+}
+
+fn now_secs_i64() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+    use tesela_relay::{router, AppState as RelayAppState};
+    use tesela_sync::{Hlc, LoroEngine, OpPayload};
+
+    async fn spawn_relay() -> (String, tempfile::TempDir) {
+        let tmp = tempfile::tempdir().expect("tmp");
+        let state = RelayAppState::open(&tmp.path().join("relay.sqlite"), 4_194_304, None)
+            .await
+            .expect("relay state");
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        tokio::spawn(async move {
+            let _ = axum::serve(
+                listener,
+                router(state).into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await;
+        });
+        (format!("http://{addr}"), tmp)
+    }
+
+    async fn mosaic(device: [u8; 16]) -> (LoroEngine, tempfile::TempDir) {
+        let tmp = tempfile::tempdir().unwrap();
+        let device = DeviceId::from_bytes(device);
+        let engine = LoroEngine::with_dirs(
+            device,
+            Arc::new(Hlc::new(device)),
+            tmp.path().join("loro"),
+            Some(tmp.path().join("notes")),
+        )
+        .await
+        .unwrap();
+        (engine, tmp)
+    }
+
+    async fn note(engine: &LoroEngine, note_id: [u8; 16], slug: &str, content: &str) {
+        engine
+            .record_local(OpPayload::NoteUpsert {
+                note_id,
+                display_alias: Some(slug.into()),
+                title: slug.into(),
+                content: content.into(),
+                created_at_millis: 1,
+            })
+            .await
+            .unwrap();
+    }
+
+    async fn add_block(engine: &LoroEngine, note_id: [u8; 16], text: &str) {
+        engine
+            .record_local(OpPayload::BlockUpsert {
+                block_id: [0x7e; 16],
+                note_id,
+                parent_block_id: None,
+                order_key: "00000000".into(),
+                indent_level: 0,
+                text: text.into(),
+                after_block_id: None,
+            })
+            .await
+            .unwrap();
+    }
+
+    async fn body(engine: &LoroEngine, note_id: [u8; 16]) -> String {
+        engine.render_note(note_id).await.unwrap_or_default()
+    }
+
+    const GROCERIES: [u8; 16] = [0x61; 16];
+    const DIARY: [u8; 16] = [0x62; 16];
+    const TODO: [u8; 16] = [0x63; 16];
+    const PARTNER_PAGE: [u8; 16] = [0x64; 16];
+
+    #[tokio::test]
+    async fn space_converges_only_its_pages_between_two_mosaics() {
+        let (relay_url, _relay_tmp) = spawn_relay().await;
+        let (a, a_tmp) = mosaic([0xa1; 16]).await;
+        let (b, b_tmp) = mosaic([0xb2; 16]).await;
+        note(&a, GROCERIES, "groceries", "- oat milk\n").await;
+        note(&a, DIARY, "diary", "- private thoughts\n").await;
+        // Both mosaics hold their own `todo` under the same doc id.
+        note(&a, TODO, "todo", "- A's todo\n").await;
+        note(&b, TODO, "todo", "- B's todo\n").await;
+
+        let key = GroupKey::random();
+        let state = SharedSpaceState::new(
+            GroupId::new_random(),
+            "Home".into(),
+            relay_url,
+            &[GROCERIES, TODO],
+        );
+        persist_new(a_tmp.path(), &state, &key).await.unwrap();
+        let space_a =
+            ShareHandle::new(state, key.clone(), a.device(), a_tmp.path().to_path_buf()).unwrap();
+        bring_up(&space_a).await.unwrap();
+        assert_eq!(tick(&a, &space_a).await.unwrap().sent, 1);
+        assert_eq!(tick(&a, &space_a).await.unwrap().sent, 0, "nothing new");
+
+        // B joins from the code's identity with an empty page list.
+        let code = code_for(&space_a).await;
+        let space_b = ShareHandle::new(
+            SharedSpaceState::new(
+                code.share_id,
+                code.name.clone(),
+                code.relay_url.clone(),
+                &[],
+            ),
+            code.identity().group_key,
+            b.device(),
+            b_tmp.path().to_path_buf(),
+        )
+        .unwrap();
+        bring_up(&space_b).await.unwrap();
+        let outcome = tick(&b, &space_b).await.unwrap();
+        assert_eq!(outcome.applied_note_ids, vec![GROCERIES]);
+        assert_eq!(outcome.sent, 0, "a partner-only import is not echoed");
+        assert!(body(&b, GROCERIES).await.contains("oat milk"));
+        assert!(
+            b.doc_version(DIARY).await.is_none(),
+            "unshared page stays home"
+        );
+        {
+            let state = space_b.state.read().await;
+            assert_eq!(state.docs, vec![hex::encode(GROCERIES)]);
+            assert_eq!(state.conflicts, vec![hex::encode(TODO)]);
+        }
+        assert!(
+            !body(&b, TODO).await.contains("A's todo"),
+            "a page B holds outside the space is never written"
+        );
+
+        // Edits flow both ways, and a page B adds arrives at A.
+        add_block(&b, GROCERIES, "coffee beans").await;
+        note(&b, PARTNER_PAGE, "recipes", "- dal\n").await;
+        space_b.state.write().await.add_doc(PARTNER_PAGE);
+        assert_eq!(tick(&b, &space_b).await.unwrap().sent, 1);
+        let outcome = tick(&a, &space_a).await.unwrap();
+        assert!(outcome.applied_note_ids.contains(&GROCERIES));
+        assert!(outcome.applied_note_ids.contains(&PARTNER_PAGE));
+        assert!(body(&a, GROCERIES).await.contains("coffee beans"));
+        assert!(body(&a, PARTNER_PAGE).await.contains("dal"));
+        assert!(space_a.state.read().await.contains(PARTNER_PAGE));
+
+        // The page list and cursors persist for the next start.
+        let loaded = load_all(a_tmp.path()).await;
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].0.docs.len(), 3);
+        assert_eq!(loaded[0].1.as_bytes(), key.as_bytes());
+    }
+
+    async fn join(code: &tesela_sync::ShareCode, engine: &LoroEngine, root: &Path) -> ShareHandle {
+        let handle = ShareHandle::new(
+            SharedSpaceState::new(
+                code.share_id,
+                code.name.clone(),
+                code.relay_url.clone(),
+                &[],
+            ),
+            code.identity().group_key,
+            engine.device(),
+            root.to_path_buf(),
+        )
+        .unwrap();
+        bring_up(&handle).await.unwrap();
+        handle
+    }
+
+    #[tokio::test]
+    async fn unseen_doc_whose_slug_names_a_local_page_is_refused() {
+        let (relay_url, _relay_tmp) = spawn_relay().await;
+        let (a, a_tmp) = mosaic([0xa1; 16]).await;
+        let (b, b_tmp) = mosaic([0xb2; 16]).await;
+        note(&a, GROCERIES, "groceries", "- A's list\n").await;
+        // B's `groceries` lives under a different doc id.
+        note(&b, DIARY, "groceries", "- B's list\n").await;
+
+        let key = GroupKey::random();
+        let state = SharedSpaceState::new(
+            GroupId::new_random(),
+            "Home".into(),
+            relay_url,
+            &[GROCERIES],
+        );
+        let space_a = ShareHandle::new(state, key, a.device(), a_tmp.path().to_path_buf()).unwrap();
+        bring_up(&space_a).await.unwrap();
+        tick(&a, &space_a).await.unwrap();
+
+        let space_b = join(&code_for(&space_a).await, &b, b_tmp.path()).await;
+        let outcome = tick(&b, &space_b).await.unwrap();
+        assert!(outcome.applied_note_ids.is_empty());
+        assert!(b.doc_version(GROCERIES).await.is_none());
+        let state = space_b.state.read().await;
+        assert!(state.docs.is_empty());
+        assert_eq!(state.conflicts, vec![hex::encode(GROCERIES)]);
+        drop(state);
+        assert!(body(&b, DIARY).await.contains("B's list"));
+    }
+
+    #[tokio::test]
+    async fn late_joiner_bootstraps_from_deposited_snapshots() {
+        let (relay_url, _relay_tmp) = spawn_relay().await;
+        let (a, a_tmp) = mosaic([0xa1; 16]).await;
+        let (c, c_tmp) = mosaic([0xc3; 16]).await;
+        note(&a, GROCERIES, "groceries", "- oat milk\n").await;
+
+        let key = GroupKey::random();
+        let state = SharedSpaceState::new(
+            GroupId::new_random(),
+            "Home".into(),
+            relay_url,
+            &[GROCERIES],
+        );
+        let space_a = ShareHandle::new(state, key, a.device(), a_tmp.path().to_path_buf()).unwrap();
+        bring_up(&space_a).await.unwrap();
+        tick(&a, &space_a).await.unwrap();
+        // The next poll sees A's own put, so the deposit can cover it.
+        tick(&a, &space_a).await.unwrap();
+        assert!(space_a.state.read().await.last_snapshot_at.is_some());
+        let (compaction_seq, snaps) = space_a.client.fetch_snapshots().await.unwrap();
+        assert!(compaction_seq > 0);
+        assert_eq!(snaps.len(), 1);
+
+        let space_c = join(&code_for(&space_a).await, &c, c_tmp.path()).await;
+        let outcome = tick(&c, &space_c).await.unwrap();
+        assert_eq!(outcome.applied_note_ids, vec![GROCERIES]);
+        assert!(body(&c, GROCERIES).await.contains("oat milk"));
+        assert!(space_c.state.read().await.inbound_cursor >= compaction_seq);
+    }
+
+    #[tokio::test]
+    async fn legacy_plaintext_key_moves_into_the_key_store() {
+        let tmp = tempfile::tempdir().unwrap();
+        let key = GroupKey::random();
+        let state = SharedSpaceState::new(
+            GroupId::new_random(),
+            "Home".into(),
+            "http://127.0.0.1:1".into(),
+            &[],
+        );
+        state.save(tmp.path()).await.unwrap();
+        let legacy = shares_dir(tmp.path()).join(format!("{}.key", state.share_id));
+        tokio::fs::write(&legacy, key.as_bytes()).await.unwrap();
+
+        let loaded = load_all(tmp.path()).await;
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].1.as_bytes(), key.as_bytes());
+        assert!(!legacy.exists(), "plaintext key shredded");
+        let loaded = load_all(tmp.path()).await;
+        assert_eq!(loaded[0].1.as_bytes(), key.as_bytes());
+
+        remove_files(tmp.path(), &state.share_id).await;
+        assert!(load_all(tmp.path()).await.is_empty());
+        assert!(!key_dir(tmp.path(), &state.share_id).exists());
+    }
+
+    async fn code_for(handle: &ShareHandle) -> tesela_sync::ShareCode {
+        let state = handle.state.read().await;
+        let encoded = tesela_sync::encode_share_code(&tesela_sync::ShareCode {
+            share_id: handle.share_id,
+            share_key_bytes: *handle.key.as_bytes(),
+            name: state.name.clone(),
+            relay_url: state.relay_url.clone(),
+            display_name: "A".into(),
+            version: tesela_sync::SHARE_CODE_VERSION,
+        })
+        .unwrap();
+        tesela_sync::decode_share_code(&encoded).unwrap()
+    }
+}
@@ -46,6 +46,9 @@ pub struct AppState {
     /// disabled or failed to start (we log and continue, since sync over
     /// manually-configured peers still works).
     pub lan_discovery: Option<Arc<LanDiscovery>>,
+    /// Direct LAN sync with discovered group members (`lan_sync`). `None`
+    /// when mDNS is off or the listener failed to bind.
+    pub lan_sync: Option<Arc<crate::lan_sync::LanSync>>,
     /// Phase 2.2 — the symmetric group identity (id + key) used by the
     /// pairing flow. Wrapped in RwLock so `POST /sync/peer/pair-code`
     /// can swap it after a successful pair without restarting the
@@ -76,6 +79,17 @@ pub struct AppState {
     /// the scheduler task. `GET /backup/status` reads through this and
     /// combines it with the on-disk backup listing.
     pub backup_status: crate::backup_scheduler::BackupStatusHandle,
+    /// Reminder delivery state (fired / acknowledged / snoozed), shared
+    /// with the notification scanner so ack/snooze requests serialize
+    /// against a scan in progress.
+    pub notifier: Arc<crate::notifications::Notifier>,
+    /// Configured outbound webhooks and their recent delivery log.
+    pub webhooks: Arc<crate::webhooks::Webhooks>,
+    /// Shared sub-spaces with other mosaics and their relay loops.
+    pub shared_spaces: Arc<crate::shared_spaces::SharedSpaces>,
+    /// History of sync anomalies opening, escalating, being repaired and
+    /// resolving (`.tesela/sync_incidents.json`).
+    pub sync_incidents: Arc<tesela_sync::IncidentLog>,
 }
 
 /// Unique id assigned to each upgraded `/ws` socket, used to suppress
@@ -144,6 +158,26 @@ impl GroupRuntimeFence {
         self.captured
     }
 
+    /// Move this fence to `rotated`, the same group under a newer key
+    /// epoch (`group_rotation`). Publishes it as the current identity when
+    /// the captured one is still current; a rotation this device issued
+    /// has already published it. Returns `false` when the group was
+    /// replaced by something else, in which case the daemon should stop.
+    pub(crate) async fn follow_rotation(&mut self, rotated: &GroupIdentity) -> bool {
+        if rotated.group_id != self.captured.group_id {
+            return false;
+        }
+        let next = GroupScope::capture(rotated);
+        let mut current = self.current.write().await;
+        if self.captured.matches(&current) {
+            *current = rotated.clone();
+        } else if !next.matches(&current) {
+            return false;
+        }
+        self.captured = next;
+        true
+    }
+
     pub(crate) async fn run_if_current<F>(&self, operation: F) -> Option<F::Output>
     where
         F: Future,
@@ -175,7 +209,9 @@ pub struct WsDelta {
     pub frame: Vec<u8>,
 }
 
-/// Events broadcast to WebSocket clients when notes change.
+/// Events broadcast to WebSocket clients when notes change. Webhooks
+/// subscribe by the `event` tag, so a new variant must also be listed in
+/// `tesela_core::config::WEBHOOK_EVENTS`.
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "event", rename_all = "snake_case")]
 pub enum WsEvent {
@@ -192,6 +228,8 @@ pub enum WsEvent {
     /// lead time is reached and the task is still open. Client decides
     /// whether to surface a desktop notification.
     DeadlineApproaching {
+        /// Stable id for `POST /notifications/{id}/ack` and `/snooze`.
+        notification_id: String,
         block_id: String,
         title: String,
         note_id: String,
@@ -200,6 +238,7 @@ pub enum WsEvent {
     },
     /// Phase 12.3 — fired when `scheduled::` time-of-day is reached.
     ScheduledFires {
+        notification_id: String,
         block_id: String,
         title: String,
         note_id: String,
@@ -220,6 +259,14 @@ pub enum WsEvent {
     ViewsChanged {
         views: Vec<ViewRecord>,
     },
+    /// A reminder was acknowledged or snoozed through this server, so
+    /// every connected client can dismiss the toast it is still showing.
+    /// `snoozed_until` is RFC 3339 and present only for a snooze.
+    NotificationStateChanged {
+        notification_id: String,
+        status: String,
+        snoozed_until: Option<String>,
+    },
 }
 
 #[cfg(test)]
@@ -0,0 +1,53 @@
+//! Sync-health monitor: periodically folds the engine's anomaly scan
+//! ([`SyncEngine::sync_anomalies`]) into the incident log, so the history
+//! behind `GET /sync/health/incidents` fills in even when nobody is
+//! looking. `POST /sync/health/scan` and the repair route run the same
+//! [`scan`] on demand; `GET /sync/health` only reads.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tesela_sync::{IncidentEvent, IncidentLog, SyncAnomaly, SyncEngine};
+use tracing::warn;
+
+/// How often the monitor scans. The twin scan walks every resident doc,
+/// so this stays well above the relay cadence.
+pub const MONITOR_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// One scan: the engine's current anomalies, with any change since the
+/// last scan recorded in `log`. A log write failure is logged, not
+/// returned — the scan itself is still good.
+pub async fn scan(engine: &dyn SyncEngine, log: &IncidentLog) -> Vec<SyncAnomaly> {
+    let anomalies = engine.sync_anomalies().await;
+    match log.record_scan(&anomalies).await {
+        Ok(events) => {
+            for e in events.iter().filter(|e| e.event != IncidentEvent::Resolved) {
+                warn!(
+                    "sync health: {:?} {:?} ({:?}) on {} — {}",
+                    e.event,
+                    e.kind,
+                    e.severity,
+                    e.slug.as_deref().unwrap_or(&e.note_id),
+                    e.detail
+                );
+            }
+        }
+        Err(e) => warn!("sync health: record incidents: {e}"),
+    }
+    anomalies
+}
+
+/// Spawn the periodic monitor.
+pub fn start(engine: Arc<dyn SyncEngine>, log: Arc<IncidentLog>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(MONITOR_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // Skip the immediate tick: startup bring-up may still be healing
+        // exactly the gaps a scan would report.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            scan(&*engine, &log).await;
+        }
+    });
+}
@@ -19,6 +19,8 @@ use tokio::sync::RwLock;
 use tesela_sync::transport::relay::RelayClient;
 use tesela_sync::{GroupIdentity, SyncEnvelope};
 
+use crate::group_rotation::GroupMembership;
+
 /// Per-mosaic relay sync state, persisted to `.tesela/relay_state.json`
 /// so cursors survive restart. Schema is intentionally tiny — the
 /// real state-of-record is the relay itself (server-side ops table)
@@ -69,6 +71,7 @@ pub struct RelayState {
     ///     compaction watermark is ALREADY ahead of our cursor, i.e. its
     ///     raw ops are already gone; there is nothing left to protect, and
     ///     bounding covers_seq for it would only block GC pointlessly.
+    ///   - Sync-scope readmissions: `0` — see `queue_scope_readmissions`.
     /// A catch-up note with NO entry here (state predates this field, or a
     /// hand-edited state file) defaults to the maximally conservative
     /// bound of seq 0 — i.e. fully blocks compaction, same as before this
@@ -205,6 +208,10 @@ pub struct RelayHandle {
     pub client: Arc<RelayClient>,
     pub state: Arc<RwLock<RelayState>>,
     pub mosaic_root: PathBuf,
+    /// Key-epoch bookkeeping (`group_rotation`). `None` only where a
+    /// handle is built without an index (tests); the tick then ignores
+    /// group-control records.
+    pub membership: Option<GroupMembership>,
 }
 
 /// JSON shape returned by `GET /sync/relay/status`. Surfaced verbatim
@@ -269,6 +276,10 @@ pub struct TickOutcome {
     /// approach the WS inbound handler uses. (A post-apply `export_doc_update`
     /// can't recover them: the engine's export cursor already consumed them.)
     pub applied_updates: Vec<tesela_sync::LoroDocUpdate>,
+    /// Notes the engine evicted this tick because they fall outside this
+    /// device's sync scope. The caller drops them from the search index and
+    /// tells web they're gone.
+    pub scope_evictions: Vec<tesela_sync::ScopeEviction>,
 }
 
 /// One iteration of the relay sync loop: inbound (poll + apply +
@@ -304,7 +315,20 @@ pub async fn tick(
 
     // ─── Inbound ─────────────────────────────────────────────────────
     match handle.client.poll(state.inbound_cursor).await {
-        Ok(batch) => {
+        Ok(mut batch) => {
+            // Group-control records (member announcements, key rotations)
+            // first. When this device advances to a new key epoch the
+            // client switches keys, but this batch was opened under the
+            // old one: stop at the rotation record and let the next tick
+            // re-poll what follows it under the new key.
+            if let Some(membership) = handle.membership.as_ref() {
+                if let Some(rotated_at) =
+                    membership.apply_records(&handle.client, &batch.rows).await
+                {
+                    batch.rows.retain(|(seq, _)| *seq <= rotated_at);
+                    batch.skipped.retain(|seq| *seq <= rotated_at);
+                }
+            }
             let batch_compaction = batch.compaction_seq;
             let mut max_seq = state.inbound_cursor;
             // Earliest seq in this batch whose apply FAILED and is still
@@ -344,7 +368,13 @@ pub async fn tick(
                             .into_iter()
                             .map(|u| (u.doc, u.update_bytes))
                             .collect();
+                        let apply_started = std::time::Instant::now();
                         let report = engine.apply_relay_updates(&pairs).await;
+                        let metrics = crate::metrics::metrics();
+                        metrics
+                            .relay_apply_latency
+                            .observe(apply_started.elapsed().as_secs_f64());
+                        metrics.relay_ops_in.inc();
                         applied_total += report.applied_count() as u32;
                         // Record notes we do NOT genuinely hold this tick (failed
                         // or pending apply) so the heal-deposit skips them.
@@ -380,6 +410,7 @@ pub async fn tick(
                         // snapshot catch-up below: the buffered bytes live
                         // in-memory only and the note is frozen until its
                         // base arrives (audit A4).
+                        queue_scope_readmissions(&mut state, &report.readmitted);
                         for doc in &report.pending {
                             let hex_id = hex::encode(doc);
                             // This envelope's raw op may be the note's ONLY
@@ -555,6 +586,13 @@ pub async fn tick(
         }
     }
 
+    // ─── Selective sync ──────────────────────────────────────────────
+    // A scope-registry change can arrive through any import path
+    // (bootstrap, catch-up, a live WS delta), not just the apply above —
+    // re-check here. A no-op while this device's rules are unchanged.
+    let readmitted = engine.reconcile_sync_scope().await;
+    queue_scope_readmissions(&mut state, &readmitted);
+
     // ─── Targeted snapshot catch-up ──────────────────────────────────
     // Heal notes whose inbound apply failed or landed PENDING (and notes a
     // partially-failed bootstrap queued). First re-read the exact retained op
@@ -564,6 +602,7 @@ pub async fn tick(
     // permanent poison, or bootstrap rows whose raw ops were already GC'd).
     if !state.catchup_notes.is_empty() {
         let raw = catchup_from_retained_ops(engine, &handle.client, &state.catchup_since_seq).await;
+        queue_scope_readmissions(&mut state, &raw.readmitted);
         if !raw.healed.is_empty() {
             applied_total += raw.healed.len() as u32;
             state.catchup_notes.retain(|h| !raw.healed.contains(h));
@@ -615,6 +654,9 @@ pub async fn tick(
     // snapshot deposit above then re-anchors the cursor (item 4).
     let strand_alarms_after = engine.outbound_strand_alarm_count().await;
     if strand_alarms_after > strand_alarms_before {
+        crate::metrics::metrics()
+            .outbound_strand_alarms
+            .inc_by(strand_alarms_after - strand_alarms_before);
         tracing::warn!(
             "relay: {} outbound strand alarm(s) this tick — dirty note(s) shipped a \
              snapshot fallback instead of an incremental delta (deposit-strand class, \
@@ -654,6 +696,7 @@ pub async fn tick(
                     broadcast_note_seqs.insert(*nid, seq);
                 }
                 sent_total += 1;
+                crate::metrics::metrics().relay_ops_out.inc();
                 state.last_put_at = Some(now_secs_i64());
                 state.last_error = None;
             }
@@ -737,6 +780,7 @@ pub async fn tick(
                 .await
             {
                 Ok(_) => {
+                    crate::metrics::metrics().snapshot_deposit("heal");
                     // Record only on success — a failed deposit must re-try.
                     for (id, h) in hashes_by_seq.remove(&snapshot_seq).unwrap_or_default() {
                         state.deposit_hashes.insert(id, h);
@@ -767,11 +811,27 @@ pub async fn tick(
     // that boundary (e.g. another note's earlier, already-healthy edits),
     // instead of the previous all-or-nothing gate that froze compaction for
     // the WHOLE group the moment any one note got stuck.
+    //
+    // Selective sync: a device whose scope keeps notes out holds neither
+    // their state nor a snapshot of it, and their ops can sit anywhere in
+    // the log — no `covers_seq` both advances and protects them. Such a
+    // device never compacts; an unscoped member's deposit GCs for the group.
     let now = now_secs_i64();
     let due = state
         .last_snapshot_at
         .is_none_or(|t| now - t >= snapshot_interval_secs());
-    if due {
+    let scope_excluded = if due {
+        engine.scope_excluded_notes().await.len()
+    } else {
+        0
+    };
+    if due && scope_excluded > 0 {
+        tracing::debug!(
+            "relay: snapshot deposit SKIPPED — {scope_excluded} note(s) outside this \
+             device's sync scope keep their ops on the relay"
+        );
+        state.last_snapshot_at = Some(now);
+    } else if due {
         let earliest_stuck_seq: Option<i64> = if state.catchup_notes.is_empty() {
             None
         } else {
@@ -835,6 +895,7 @@ pub async fn tick(
             .await
             {
                 Ok(report) => {
+                    crate::metrics::metrics().snapshot_deposit("compaction");
                     // Stamp the cadence even on a partial (skipped-notes)
                     // deposit: retrying every tick can't shrink an oversize
                     // snapshot, it would just re-upload the mosaic in a loop.
@@ -879,9 +940,25 @@ pub async fn tick(
         sent: sent_total,
         applied_note_ids,
         applied_updates,
+        scope_evictions: engine.take_scope_evictions().await,
     })
 }
 
+/// Queue notes readmitted by a sync-scope change for snapshot catch-up. The
+/// engine dropped their history when it excluded them, so no single retained
+/// op recovers them — seq `0`, which blocks compaction until the snapshot
+/// lands: until then the relay's op log may hold the only copy of their
+/// later edits.
+pub(crate) fn queue_scope_readmissions(state: &mut RelayState, readmitted: &[[u8; 16]]) {
+    for doc in readmitted {
+        let hex_id = hex::encode(doc);
+        state.catchup_since_seq.entry(hex_id.clone()).or_insert(0);
+        if !state.catchup_notes.contains(&hex_id) {
+            state.catchup_notes.push(hex_id);
+        }
+    }
+}
+
 /// Deposit a per-note snapshot set covering relay-seq `covers_seq`, so the
 /// relay can GC the encrypted op log it retains. Idempotent.
 ///
@@ -1044,6 +1121,10 @@ pub async fn bootstrap_from_snapshots(engine: &dyn tesela_sync::SyncEngine, hand
         let Ok(note_id) = <[u8; 16]>::try_from(stream_id.as_slice()) else {
             continue; // v1 stream_id is the 16-byte note_id; skip anything else
         };
+        // Outside this device's sync scope: nothing to import.
+        if engine.enforce_sync_scope(note_id).await {
+            continue;
+        }
         if let Err(e) = engine.import_doc_update(note_id, &plaintext).await {
             tracing::warn!(
                 "relay snapshot bootstrap import {}: {e}",
@@ -1052,6 +1133,7 @@ pub async fn bootstrap_from_snapshots(engine: &dyn tesela_sync::SyncEngine, hand
             failed.push(hex::encode(note_id));
             continue;
         }
+        engine.enforce_sync_scope(note_id).await;
         imported += 1;
     }
     if failed.is_empty() {
@@ -1118,11 +1200,17 @@ pub(crate) async fn catchup_from_snapshots(
         if !targets_hex.contains(&hex_id) {
             continue;
         }
+        // Excluded by this device's sync scope: nothing left to heal.
+        if engine.enforce_sync_scope(note_id).await {
+            healed.push(hex_id);
+            continue;
+        }
         match engine
             .import_authoritative_snapshot(note_id, &plaintext)
             .await
         {
             Ok(forwarded_targets) => {
+                engine.enforce_sync_scope(note_id).await;
                 tracing::info!("relay snapshot catch-up healed note {hex_id}");
                 healed.push(hex_id);
                 for target in forwarded_targets {
@@ -1147,6 +1235,7 @@ pub(crate) async fn catchup_from_snapshots(
 struct RetainedOpCatchup {
     healed: Vec<String>,
     applied_updates: Vec<tesela_sync::LoroDocUpdate>,
+    readmitted: Vec<[u8; 16]>,
 }
 
 /// Retry the exact note+seq updates protected by `catchup_since_seq` directly
@@ -1199,6 +1288,13 @@ async fn catchup_from_retained_ops(
             continue;
         }
         let report = engine.apply_relay_updates(&pairs).await;
+        outcome.readmitted.extend(report.readmitted.iter().copied());
+        for doc in &report.excluded {
+            let hex_id = hex::encode(doc);
+            if !outcome.healed.contains(&hex_id) {
+                outcome.healed.push(hex_id);
+            }
+        }
         for (doc, bytes) in pairs {
             let clean = report.applied.contains(&doc)
                 && !report.pending.contains(&doc)
@@ -1247,7 +1343,7 @@ pub(crate) fn parse_hex_note_id(hex_id: &str) -> Option<[u8; 16]> {
 /// Snapshot-deposit cadence in seconds. Env-tunable
 /// (`TESELA_RELAY_SNAPSHOT_INTERVAL_SECS`) so tests can force every-tick
 /// deposits; defaults to 5 minutes in production.
-fn snapshot_interval_secs() -> i64 {
+pub(crate) fn snapshot_interval_secs() -> i64 {
     std::env::var("TESELA_RELAY_SNAPSHOT_INTERVAL_SECS")
         .ok()
         .and_then(|s| s.parse::<i64>().ok())
@@ -1259,7 +1355,7 @@ fn snapshot_interval_secs() -> i64 {
 /// payload — comfortable headroom under the HA relay's 16 MiB cap while
 /// the 413-adaptive halving in `put_snapshots_chunked` degrades to fit
 /// tighter caps (e.g. the CF Worker's 1 MiB default) automatically.
-fn deposit_chunk_budget_bytes() -> usize {
+pub(crate) fn deposit_chunk_budget_bytes() -> usize {
     std::env::var("TESELA_RELAY_DEPOSIT_CHUNK_BYTES")
         .ok()
         .and_then(|s| s.parse::<usize>().ok())
@@ -1371,6 +1467,7 @@ mod tests {
             client: Arc::new(RelayClient::new(base_url.clone(), group, device, key)),
             state: Arc::new(RwLock::new(RelayState::default())),
             mosaic_root,
+            membership: None,
         }
     }
 
@@ -2137,6 +2234,113 @@ mod tests {
         );
     }
 
+    /// Selective sync: a device whose scope keeps a note out must not
+    /// compact the op log, or the relay GCs the only copy of that note's
+    /// ops. A second, unscoped device still receives them.
+    #[tokio::test]
+    async fn scoped_device_never_compacts_away_excluded_notes() {
+        std::env::set_var("TESELA_RELAY_SNAPSHOT_INTERVAL_SECS", "0");
+
+        let (base_url, _relay_tmp, _relay_srv) = spawn_relay().await;
+        let (group, key) = fresh_group();
+        let ident = GroupIdentity {
+            group_id: group,
+            group_key: key.clone(),
+        };
+        const NID_PRIVATE: [u8; 16] = [0x5a; 16];
+        const NID_SHARED: [u8; 16] = [0x5b; 16];
+
+        // A peer that only deposits ops (no snapshots) authors both notes.
+        let p_tmp = tempfile::tempdir().unwrap();
+        let dev_p = DeviceId::from_bytes([0x5c; 16]);
+        let engine_p = engine_in(&p_tmp, dev_p).await;
+        let mut updates = Vec::new();
+        for (nid, slug, body) in [
+            (NID_PRIVATE, "diary", "- secret #private\n"),
+            (NID_SHARED, "roadmap", "- ship it\n"),
+        ] {
+            engine_p
+                .record_local(OpPayload::NoteUpsert {
+                    note_id: nid,
+                    display_alias: Some(slug.into()),
+                    title: slug.into(),
+                    content: body.into(),
+                    created_at_millis: 1,
+                })
+                .await
+                .unwrap();
+            updates.push((nid, engine_p.export_doc_update(nid, None).await.unwrap()));
+        }
+        let client_p = RelayClient::new(base_url.clone(), group, dev_p, key.clone());
+        client_p.register_or_recover().await.expect("peer register");
+        let private_seq = put_loro_envelope(&client_p, dev_p, group, &updates).await;
+
+        // B keeps #private notes out, and ticks with the deposit due.
+        let b_tmp = tempfile::tempdir().unwrap();
+        let dev_b = DeviceId::from_bytes([0xb9; 16]);
+        let engine_b = engine_in(&b_tmp, dev_b).await;
+        engine_b
+            .sync_scope_upsert(tesela_sync::DeviceSyncScope {
+                device: dev_b.to_hex(),
+                exclude_tags: vec!["private".into()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        engine_b.reconcile_sync_scope().await;
+        let handle_b = handle_for(
+            &base_url,
+            group,
+            dev_b,
+            key.clone(),
+            b_tmp.path().to_path_buf(),
+        );
+        bring_up(&handle_b).await.expect("relay bring-up");
+        tick(&engine_b, &ident, &handle_b).await.unwrap();
+        tick(&engine_b, &ident, &handle_b).await.unwrap();
+        assert_eq!(engine_b.scope_excluded_notes().await, vec![NID_PRIVATE]);
+        assert!(handle_b.state.read().await.inbound_cursor >= private_seq);
+
+        let probe = RelayClient::new(
+            base_url.clone(),
+            group,
+            DeviceId::from_bytes([0xcf; 16]),
+            key.clone(),
+        );
+        let (comp_seq, _) = probe.fetch_snapshots().await.unwrap();
+        assert_eq!(comp_seq, 0, "the scoped device never compacted");
+        assert!(
+            probe
+                .poll(0)
+                .await
+                .unwrap()
+                .rows
+                .iter()
+                .any(|(seq, _)| *seq == private_seq),
+            "the excluded note's ops stay on the relay"
+        );
+
+        // A fresh unscoped device still gets the excluded note.
+        let c_tmp = tempfile::tempdir().unwrap();
+        let dev_c = DeviceId::from_bytes([0xc9; 16]);
+        let engine_c = engine_in(&c_tmp, dev_c).await;
+        let handle_c = handle_for(
+            &base_url,
+            group,
+            dev_c,
+            key.clone(),
+            c_tmp.path().to_path_buf(),
+        );
+        bring_up(&handle_c).await.expect("relay bring-up");
+        bootstrap_from_snapshots(&engine_c, &handle_c).await;
+        tick(&engine_c, &ident, &handle_c).await.unwrap();
+        assert!(engine_c
+            .render_note(NID_PRIVATE)
+            .await
+            .unwrap_or_default()
+            .contains("secret"));
+    }
+
     /// A4: a delta that Loro leaves PENDING (causal gap — its base was
     /// compacted away / never delivered) must trigger a targeted snapshot
     /// catch-up in the same tick, not silently freeze the note.
@@ -0,0 +1,190 @@
+//! systemd integration for the Linux user service written by
+//! `tesela install` (`~/.config/systemd/user/tesela-server.service`).
+//!
+//! Three pieces, all no-ops outside systemd so the desktop embed and a
+//! hand-run server are unaffected:
+//!
+//! - [`notify`] — the `sd_notify` datagram protocol (`READY=1`,
+//!   `STOPPING=1`, `WATCHDOG=1`) over `$NOTIFY_SOCKET`, so a `Type=notify`
+//!   unit only counts as started once the HTTP listener is serving.
+//! - [`spawn_watchdog`] — pings at half of `$WATCHDOG_USEC`; a wedged
+//!   runtime stops pinging and systemd restarts the unit.
+//! - [`take_listener`] — adopts a socket-activated listener
+//!   (`$LISTEN_FDS`, fd 3) from `tesela-server.socket`. The bin calls it
+//!   before starting its runtime.
+//!
+//! Implemented directly on std (the protocol is one datagram) rather than
+//! pulling in a libsystemd binding.
+
+use std::time::Duration;
+
+use tracing::{debug, warn};
+
+/// Set by the unit `tesela install` writes. `/server/restart` checks it to
+/// leave the restart to systemd (`Restart=always`) instead of re-exec'ing.
+pub const SUPERVISED_ENV: &str = "TESELA_SUPERVISED";
+
+/// First fd passed by socket activation (`SD_LISTEN_FDS_START`).
+const LISTEN_FDS_START: i32 = 3;
+
+/// Whether this process runs under the `tesela install` systemd unit.
+pub fn is_managed() -> bool {
+    std::env::var(SUPERVISED_ENV).is_ok_and(|v| v == "systemd")
+}
+
+/// Send one `sd_notify` state string. Returns false when there is no
+/// `$NOTIFY_SOCKET` (not under systemd) or the send failed.
+pub fn notify(state: &str) -> bool {
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else {
+        return false;
+    };
+    match send_notify(&path, state) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("systemd: sd_notify {state:?} failed: {e}");
+            false
+        }
+    }
+}
+
+#[cfg(unix)]
+fn send_notify(path: &std::ffi::OsStr, state: &str) -> std::io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::net::UnixDatagram;
+
+    let socket = UnixDatagram::unbound()?;
+    let bytes = path.as_bytes();
+    // A leading '@' names a socket in the Linux abstract namespace.
+    if let Some(name) = bytes.strip_prefix(b"@") {
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::linux::net::SocketAddrExt;
+            let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+            socket.send_to_addr(state.as_bytes(), &addr)?;
+            return Ok(());
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = name;
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "abstract notify socket",
+            ));
+        }
+    }
+    socket.send_to(state.as_bytes(), path)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send_notify(_path: &std::ffi::OsStr, _state: &str) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "sd_notify is Unix-only",
+    ))
+}
+
+/// The watchdog ping interval systemd asked for (`WatchdogSec=` in the
+/// unit), halved as `sd_watchdog_enabled(3)` recommends. `None` when the
+/// watchdog is off or addressed to another pid.
+fn watchdog_interval() -> Option<Duration> {
+    if let Ok(pid) = std::env::var("WATCHDOG_PID") {
+        if pid.trim().parse::<u32>().ok() != Some(std::process::id()) {
+            return None;
+        }
+    }
+    let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.trim().parse().ok()?;
+    (usec > 0).then(|| Duration::from_micros(usec / 2))
+}
+
+/// Start pinging `WATCHDOG=1` if the unit enables the watchdog. The pings
+/// run on the tokio runtime on purpose: if the runtime stalls, they stop,
+/// and systemd restarts the service.
+pub fn spawn_watchdog() {
+    let Some(interval) = watchdog_interval() else {
+        return;
+    };
+    debug!("systemd: watchdog enabled, pinging every {interval:?}");
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if !notify("WATCHDOG=1") {
+                break;
+            }
+        }
+    });
+}
+
+/// Adopt the listener passed by `tesela-server.socket`, if any. The
+/// `LISTEN_*` variables are cleared so a later `/server/restart` re-exec
+/// never mistakes them for its own.
+///
+/// Clearing the environment is only sound while the process is still
+/// single-threaded, so call this from `main` before building the tokio
+/// runtime and hand the result to [`crate::ServeConfig::with_listener`].
+#[cfg(unix)]
+pub fn take_listener() -> Option<std::net::TcpListener> {
+    use std::os::fd::FromRawFd;
+
+    let for_us = std::env::var("LISTEN_PID")
+        .ok()
+        .and_then(|p| p.trim().parse::<u32>().ok())
+        == Some(std::process::id());
+    let count: i32 = std::env::var("LISTEN_FDS")
+        .ok()
+        .and_then(|n| n.trim().parse().ok())
+        .unwrap_or(0);
+    std::env::remove_var("LISTEN_PID");
+    std::env::remove_var("LISTEN_FDS");
+    std::env::remove_var("LISTEN_FDNAMES");
+    if !for_us || count < 1 {
+        return None;
+    }
+    if count > 1 {
+        warn!("systemd: {count} activated sockets passed; using the first");
+    }
+    // SAFETY: systemd passed fd 3 to this pid (LISTEN_PID matched) and
+    // nothing else in the process has claimed it.
+    Some(unsafe { std::net::TcpListener::from_raw_fd(LISTEN_FDS_START) })
+}
+
+#[cfg(not(unix))]
+pub fn take_listener() -> Option<std::net::TcpListener> {
+    None
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixDatagram;
+
+    #[test]
+    fn notify_datagram_reaches_a_path_socket() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("notify.sock");
+        let receiver = UnixDatagram::bind(&path).unwrap();
+
+        send_notify(path.as_os_str(), "READY=1").unwrap();
+
+        let mut buf = [0u8; 64];
+        let n = receiver.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"READY=1");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn notify_datagram_reaches_an_abstract_socket() {
+        use std::os::linux::net::SocketAddrExt;
+
+        let name = format!("tesela-notify-test-{}", std::process::id());
+        let addr = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes()).unwrap();
+        let receiver = UnixDatagram::bind_addr(&addr).unwrap();
+
+        send_notify(std::ffi::OsStr::new(&format!("@{name}")), "STOPPING=1").unwrap();
+
+        let mut buf = [0u8; 64];
+        let n = receiver.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"STOPPING=1");
+    }
+}
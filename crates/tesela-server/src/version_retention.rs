@@ -0,0 +1,40 @@
+//! Periodic note version history prune (see [`tesela_core::db::versions`]).
+//! Tiers, compression and cadence come from `[history]` in the mosaic
+//! config; each pass that reclaims anything is logged with its counts.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tesela_core::config::HistoryConfig;
+use tesela_core::db::{SqliteIndex, VersionRetention};
+use tracing::{info, warn};
+
+/// Spawn the periodic prune. The first pass runs one interval after
+/// startup, off the bring-up path.
+pub fn start(index: Arc<SqliteIndex>, cfg: &HistoryConfig) {
+    let policy = VersionRetention::from_config(cfg);
+    let period = Duration::from_secs(cfg.interval_hours.max(1) * 60 * 60);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            match index
+                .prune_versions(&policy, chrono::Utc::now(), false)
+                .await
+            {
+                Ok(report) if report.reclaimed_bytes() > 0 => info!(
+                    "version history: pruned {} version(s), reclaimed {} byte(s); {} version(s) \
+                     ({} as deltas) remain",
+                    report.versions_deleted,
+                    report.reclaimed_bytes(),
+                    report.versions_remaining,
+                    report.versions_compressed
+                ),
+                Ok(_) => {}
+                Err(e) => warn!("version history: prune failed: {e}"),
+            }
+        }
+    });
+}
@@ -0,0 +1,584 @@
+//! Outbound webhooks: POST every server event (`WsEvent`) a configured
+//! `[[webhooks]]` entry subscribes to, so home automation and chat bots can
+//! react to deadlines without holding a WebSocket open.
+//!
+//! The dispatcher is one more subscriber on the `ws_tx` bus — the same
+//! events, serialized the same way, that WebSocket clients see. Each
+//! matching (event, webhook) pair becomes one delivery, run on its own task
+//! with exponential backoff; outcomes land in a bounded in-memory log served
+//! by `GET /webhooks/deliveries`.
+//!
+//! Payload: `{"id", "webhook", "event", "timestamp", "data"}` where `data`
+//! is the event's WS JSON. When the webhook has a `secret`, the request
+//! carries `X-Tesela-Signature: sha256=<hex>` — HMAC-SHA256 over
+//! `"<timestamp>.<body>"` with `X-Tesela-Timestamp` holding the same
+//! timestamp, so receivers can reject replays.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use tokio::sync::{broadcast, Mutex};
+use tracing::{debug, warn};
+
+use tesela_core::{config::WebhookConfig, db::SqliteIndex, query::parse_query};
+use tesela_sync::SyncEngine;
+
+use crate::state::WsEvent;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Deliveries kept in the in-memory log (oldest evicted first).
+const DELIVERY_LOG_CAPACITY: usize = 200;
+
+/// Ceiling for the exponential retry delay.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+
+/// Per-request timeout; a hung receiver counts as a retryable failure.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// One delivery's outcome as shown by `GET /webhooks/deliveries`. Updated
+/// in place as attempts progress.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookDelivery {
+    /// Delivery id, also sent as the payload's `id`.
+    pub id: String,
+    pub webhook: String,
+    pub event: String,
+    /// "pending" | "delivered" | "failed".
+    pub status: String,
+    pub attempts: u32,
+    pub last_http_status: Option<u16>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A configured webhook as listed by `GET /webhooks` — the secret is never
+/// echoed back.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookSummary {
+    pub name: String,
+    pub url: String,
+    pub events: Vec<String>,
+    pub query: Option<String>,
+    pub signed: bool,
+    pub max_attempts: u32,
+}
+
+/// Configured webhooks plus the shared delivery log.
+pub struct Webhooks {
+    hooks: Vec<WebhookConfig>,
+    log: Mutex<VecDeque<WebhookDelivery>>,
+    client: reqwest::Client,
+    retry_base: Duration,
+}
+
+impl Webhooks {
+    pub fn new(hooks: Vec<WebhookConfig>) -> Self {
+        Self {
+            hooks,
+            log: Mutex::new(VecDeque::new()),
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            retry_base: Duration::from_secs(1),
+        }
+    }
+
+    /// Override the first retry delay (doubling from there). Tests use a
+    /// few milliseconds so the backoff path runs quickly.
+    pub fn with_retry_base(mut self, retry_base: Duration) -> Self {
+        self.retry_base = retry_base;
+        self
+    }
+
+    pub fn summaries(&self) -> Vec<WebhookSummary> {
+        self.hooks
+            .iter()
+            .map(|h| WebhookSummary {
+                name: h.name.clone(),
+                url: h.url.clone(),
+                events: h.events.clone(),
+                query: h.query.clone(),
+                signed: h.secret.is_some(),
+                max_attempts: h.max_attempts,
+            })
+            .collect()
+    }
+
+    /// Most recent deliveries first, optionally for one webhook.
+    pub async fn deliveries(&self, webhook: Option<&str>, limit: usize) -> Vec<WebhookDelivery> {
+        self.log
+            .lock()
+            .await
+            .iter()
+            .rev()
+            .filter(|d| webhook.is_none_or(|w| d.webhook == w))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    async fn record(&self, delivery: WebhookDelivery) {
+        let mut log = self.log.lock().await;
+        match log.iter_mut().find(|d| d.id == delivery.id) {
+            Some(existing) => *existing = delivery,
+            None => {
+                if log.len() == DELIVERY_LOG_CAPACITY {
+                    log.pop_front();
+                }
+                log.push_back(delivery);
+            }
+        }
+    }
+}
+
+/// Spawn the dispatcher. A no-op when no webhooks are configured, so
+/// mosaics without `[[webhooks]]` pay nothing.
+pub fn start(
+    webhooks: Arc<Webhooks>,
+    ws_tx: &broadcast::Sender<WsEvent>,
+    index: Arc<SqliteIndex>,
+    engine: Arc<dyn SyncEngine>,
+) {
+    if webhooks.hooks.is_empty() {
+        return;
+    }
+    let mut rx = ws_tx.subscribe();
+    tokio::spawn(async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("webhooks: dispatcher lagged, {skipped} event(s) not delivered");
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            dispatch(&webhooks, &event, &index, &*engine).await;
+        }
+    });
+}
+
+/// Fan one event out to every webhook that wants it. Deliveries run on
+/// their own tasks so a slow receiver never stalls the bus.
+pub async fn dispatch(
+    webhooks: &Arc<Webhooks>,
+    event: &WsEvent,
+    index: &SqliteIndex,
+    engine: &dyn SyncEngine,
+) {
+    let Ok(data) = serde_json::to_value(event) else {
+        return;
+    };
+    let Some(event_type) = data
+        .get("event")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+    else {
+        return;
+    };
+    for hook in &webhooks.hooks {
+        if !hook.events.is_empty() && !hook.events.contains(&event_type) {
+            continue;
+        }
+        if let Some(dsl) = hook.query.as_deref() {
+            match query_matches(dsl, event, index, engine).await {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    warn!("webhooks: query filter for '{}' failed: {e}", hook.name);
+                    continue;
+                }
+            }
+        }
+        let webhooks = Arc::clone(webhooks);
+        let hook = hook.clone();
+        let event_type = event_type.clone();
+        let data = data.clone();
+        tokio::spawn(async move { deliver(&webhooks, &hook, &event_type, data).await });
+    }
+}
+
+/// The page (and block) an event is about, for the query filter.
+fn event_subject(event: &WsEvent) -> Option<(String, Option<String>)> {
+    match event {
+        WsEvent::NoteCreated { note } | WsEvent::NoteUpdated { note } => {
+            Some((note.id.as_str().to_string(), None))
+        }
+        WsEvent::DeadlineApproaching {
+            note_id, block_id, ..
+        }
+        | WsEvent::ScheduledFires {
+            note_id, block_id, ..
+        }
+        | WsEvent::RecurringRolled {
+            note_id, block_id, ..
+        } => Some((note_id.clone(), Some(block_id.clone()))),
+        _ => None,
+    }
+}
+
+/// Whether the query currently matches the event's block, or its page for
+/// page-level events. Evaluated at delivery time against the live index.
+async fn query_matches(
+    dsl: &str,
+    event: &WsEvent,
+    index: &SqliteIndex,
+    engine: &dyn SyncEngine,
+) -> anyhow::Result<bool> {
+    let Some((note_id, block_id)) = event_subject(event) else {
+        return Ok(false);
+    };
+    let context = crate::routes::page_query_context(engine).await;
+    Ok(index
+        .subject_matches_with_context(&parse_query(dsl), &note_id, block_id.as_deref(), &context)
+        .await?)
+}
+
+async fn deliver(
+    webhooks: &Webhooks,
+    hook: &WebhookConfig,
+    event_type: &str,
+    data: serde_json::Value,
+) {
+    let created_at = Utc::now();
+    let id = uuid::Uuid::now_v7().to_string();
+    let body = serde_json::json!({
+        "id": id,
+        "webhook": hook.name,
+        "event": event_type,
+        "timestamp": created_at.to_rfc3339(),
+        "data": data,
+    })
+    .to_string();
+    let mut delivery = WebhookDelivery {
+        id: id.clone(),
+        webhook: hook.name.clone(),
+        event: event_type.to_string(),
+        status: "pending".to_string(),
+        attempts: 0,
+        last_http_status: None,
+        last_error: None,
+        created_at,
+        updated_at: created_at,
+    };
+    webhooks.record(delivery.clone()).await;
+
+    let max_attempts = hook.max_attempts.max(1);
+    loop {
+        delivery.attempts += 1;
+        let timestamp = Utc::now().timestamp().to_string();
+        let mut request = webhooks
+            .client
+            .post(&hook.url)
+            .header("content-type", "application/json")
+            .header("x-tesela-event", event_type)
+            .header("x-tesela-delivery", &id)
+            .header("x-tesela-timestamp", &timestamp);
+        if let Some(secret) = hook.secret.as_deref() {
+            request = request.header(
+                "x-tesela-signature",
+                format!("sha256={}", sign(secret, &timestamp, &body)),
+            );
+        }
+        let retryable = match request.body(body.clone()).send().await {
+            Ok(response) if response.status().is_success() => {
+                delivery.status = "delivered".to_string();
+                delivery.last_http_status = Some(response.status().as_u16());
+                delivery.last_error = None;
+                delivery.updated_at = Utc::now();
+                webhooks.record(delivery).await;
+                debug!("webhooks: delivered {event_type} to '{}'", hook.name);
+                return;
+            }
+            Ok(response) => {
+                let status = response.status();
+                delivery.last_http_status = Some(status.as_u16());
+                delivery.last_error = Some(format!("HTTP {status}"));
+                // A 4xx other than timeout/rate-limit means the receiver
+                // rejected this payload; resending it won't help.
+                status.is_server_error()
+                    || status == reqwest::StatusCode::REQUEST_TIMEOUT
+                    || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            Err(e) => {
+                delivery.last_http_status = None;
+                delivery.last_error = Some(e.to_string());
+                true
+            }
+        };
+        delivery.updated_at = Utc::now();
+        if !retryable || delivery.attempts >= max_attempts {
+            delivery.status = "failed".to_string();
+            warn!(
+                "webhooks: giving up on {event_type} to '{}' after {} attempt(s): {}",
+                hook.name,
+                delivery.attempts,
+                delivery.last_error.as_deref().unwrap_or("unknown error")
+            );
+            webhooks.record(delivery).await;
+            return;
+        }
+        webhooks.record(delivery.clone()).await;
+        tokio::time::sleep(retry_delay(webhooks.retry_base, delivery.attempts)).await;
+    }
+}
+
+/// Delay before retry number `attempt` (1-based): `base * 2^(attempt-1)`,
+/// capped at [`MAX_RETRY_DELAY`].
+fn retry_delay(base: Duration, attempt: u32) -> Duration {
+    base.saturating_mul(1u32 << attempt.saturating_sub(1).min(16))
+        .min(MAX_RETRY_DELAY)
+}
+
+/// Hex HMAC-SHA256 of `"<timestamp>.<body>"`.
+pub fn sign(secret: &str, timestamp: &str, body: &str) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::HeaderMap, http::StatusCode, routing::post, Router};
+    use tesela_sync::{DeviceId, Hlc, LoroEngine};
+
+    type Captured = Arc<std::sync::Mutex<Vec<(HeaderMap, String)>>>;
+
+    /// Local receiver answering each POST with the next status in
+    /// `statuses` (the last one repeats), recording headers and body.
+    async fn receiver(statuses: Vec<u16>) -> (String, Captured) {
+        let captured: Captured = Arc::default();
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(
+                    |State((statuses, captured)): State<(Arc<Vec<u16>>, Captured)>,
+                     headers: HeaderMap,
+                     body: String| async move {
+                        let mut seen = captured.lock().unwrap();
+                        seen.push((headers, body));
+                        let status = statuses[(seen.len() - 1).min(statuses.len() - 1)];
+                        StatusCode::from_u16(status).unwrap()
+                    },
+                ),
+            )
+            .with_state((Arc::new(statuses), Arc::clone(&captured)));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        (format!("http://{addr}/hook"), captured)
+    }
+
+    fn hook(url: &str, events: &[&str]) -> WebhookConfig {
+        WebhookConfig {
+            name: "test".to_string(),
+            url: url.to_string(),
+            secret: Some("s3cret".to_string()),
+            events: events.iter().map(|e| e.to_string()).collect(),
+            query: None,
+            max_attempts: 3,
+        }
+    }
+
+    async fn fixture() -> (tempfile::TempDir, SqliteIndex, LoroEngine) {
+        let tmp = tempfile::tempdir().unwrap();
+        let index = SqliteIndex::open(&tmp.path().join("tesela.db"))
+            .await
+            .unwrap();
+        let device = DeviceId::from_bytes([0x42; 16]);
+        let engine = LoroEngine::new(device, Arc::new(Hlc::new(device)));
+        (tmp, index, engine)
+    }
+
+    /// Wait for the spawned delivery task to settle.
+    async fn settled(webhooks: &Webhooks) -> WebhookDelivery {
+        for _ in 0..200 {
+            if let Some(d) = webhooks.deliveries(None, 1).await.into_iter().next() {
+                if d.status != "pending" {
+                    return d;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("delivery never settled");
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors_and_signs_each_attempt() {
+        let (url, captured) = receiver(vec![500, 200]).await;
+        let (_tmp, index, engine) = fixture().await;
+        let webhooks = Arc::new(
+            Webhooks::new(vec![hook(&url, &["note_deleted"])])
+                .with_retry_base(Duration::from_millis(5)),
+        );
+        let event = WsEvent::NoteDeleted {
+            id: "groceries".to_string(),
+        };
+        dispatch(&webhooks, &event, &index, &engine).await;
+
+        let delivery = settled(&webhooks).await;
+        assert_eq!(delivery.status, "delivered");
+        assert_eq!(delivery.attempts, 2);
+        assert_eq!(delivery.last_http_status, Some(200));
+
+        let seen = captured.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        let (headers, body) = &seen[1];
+        let header = |name: &str| headers.get(name).unwrap().to_str().unwrap().to_string();
+        assert_eq!(header("x-tesela-event"), "note_deleted");
+        assert_eq!(header("x-tesela-delivery"), delivery.id);
+        let expected = sign("s3cret", &header("x-tesela-timestamp"), body);
+        assert_eq!(header("x-tesela-signature"), format!("sha256={expected}"));
+        let payload: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(payload["event"], "note_deleted");
+        assert_eq!(payload["data"]["id"], "groceries");
+    }
+
+    #[tokio::test]
+    async fn client_errors_fail_without_retry_and_unsubscribed_events_are_skipped() {
+        let (url, captured) = receiver(vec![400]).await;
+        let (_tmp, index, engine) = fixture().await;
+        let webhooks = Arc::new(
+            Webhooks::new(vec![hook(&url, &["note_deleted"])])
+                .with_retry_base(Duration::from_millis(5)),
+        );
+        let skipped = WsEvent::NotificationStateChanged {
+            notification_id: "deadline-b1-0".to_string(),
+            status: "acknowledged".to_string(),
+            snoozed_until: None,
+        };
+        dispatch(&webhooks, &skipped, &index, &engine).await;
+        let event = WsEvent::NoteDeleted {
+            id: "groceries".to_string(),
+        };
+        dispatch(&webhooks, &event, &index, &engine).await;
+
+        let delivery = settled(&webhooks).await;
+        assert_eq!(delivery.status, "failed");
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(delivery.last_http_status, Some(400));
+        assert_eq!(webhooks.deliveries(None, 10).await.len(), 1);
+        assert_eq!(captured.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn query_filter_only_delivers_events_about_matching_blocks() {
+        let (url, captured) = receiver(vec![200]).await;
+        let (_tmp, index, engine) = fixture().await;
+        let body = "- buy milk #errand\n- call mum\n";
+        let note = tesela_core::note::Note {
+            id: tesela_core::note::NoteId::new("groceries"),
+            title: "Groceries".to_string(),
+            content: body.to_string(),
+            body: body.to_string(),
+            metadata: Default::default(),
+            path: "notes/groceries.md".into(),
+            checksum: "groceries".to_string(),
+            created_at: Utc::now(),
+            modified_at: Utc::now(),
+            attachments: vec![],
+        };
+        index.upsert_note(&note).await.unwrap();
+        let blocks = tesela_core::block::parse_blocks("groceries", body);
+        let mut config = hook(&url, &["deadline_approaching"]);
+        config.query = Some("tag:errand".to_string());
+        let webhooks = Arc::new(Webhooks::new(vec![config]));
+        let deadline = |block_id: &str| WsEvent::DeadlineApproaching {
+            notification_id: format!("deadline-{block_id}-0"),
+            block_id: block_id.to_string(),
+            title: "Groceries".to_string(),
+            note_id: "groceries".to_string(),
+            deadline_iso: "2026-10-18T12:00:00Z".to_string(),
+            lead_minutes: 15,
+        };
+
+        dispatch(&webhooks, &deadline(&blocks[1].id), &index, &engine).await;
+        dispatch(&webhooks, &deadline(&blocks[0].id), &index, &engine).await;
+
+        let delivery = settled(&webhooks).await;
+        assert_eq!(delivery.status, "delivered");
+        assert_eq!(webhooks.deliveries(None, 10).await.len(), 1);
+        let seen = captured.lock().unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&seen[0].1).unwrap();
+        assert_eq!(payload["data"]["block_id"], blocks[0].id);
+    }
+
+    #[test]
+    fn retry_delay_doubles_and_caps() {
+        let base = Duration::from_secs(1);
+        assert_eq!(retry_delay(base, 1), Duration::from_secs(1));
+        assert_eq!(retry_delay(base, 3), Duration::from_secs(4));
+        assert_eq!(retry_delay(base, 40), MAX_RETRY_DELAY);
+    }
+
+    #[tokio::test]
+    async fn every_event_type_is_a_known_webhook_event() {
+        use tesela_core::{
+            config::WEBHOOK_EVENTS, storage::filesystem::FsNoteStore, traits::note_store::NoteStore,
+        };
+
+        let tmp = tempfile::tempdir().unwrap();
+        let store = FsNoteStore::new(tmp.path().to_path_buf(), Default::default());
+        let note = store.create("Hooked", "- a\n", &[]).await.unwrap();
+        let text = || String::new();
+        let events = [
+            WsEvent::NoteCreated { note: note.clone() },
+            WsEvent::NoteUpdated { note },
+            WsEvent::NoteDeleted { id: text() },
+            WsEvent::DeadlineApproaching {
+                notification_id: text(),
+                block_id: text(),
+                title: text(),
+                note_id: text(),
+                deadline_iso: text(),
+                lead_minutes: 0,
+            },
+            WsEvent::ScheduledFires {
+                notification_id: text(),
+                block_id: text(),
+                title: text(),
+                note_id: text(),
+                scheduled_iso: text(),
+            },
+            WsEvent::RecurringRolled {
+                block_id: text(),
+                title: text(),
+                note_id: text(),
+                next_deadline: text(),
+            },
+            WsEvent::ViewsChanged { views: Vec::new() },
+            WsEvent::NotificationStateChanged {
+                notification_id: text(),
+                status: text(),
+                snoozed_until: None,
+            },
+        ];
+        let names: Vec<String> = events
+            .iter()
+            .map(|event| {
+                serde_json::to_value(event).unwrap()["event"]
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            names, WEBHOOK_EVENTS,
+            "WsEvent tags and WEBHOOK_EVENTS agree"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! HTTP-level coverage for link-graph analytics: `/graph/*` over pages
+//! created through `POST /notes`, and the `is:orphan` / `links-to:` DSL
+//! predicates through `POST /search/query`.
+//!
+//! Skipped on non-Unix (spawns the server binary, SIGTERMs to shut down).
+
+#![cfg(unix)]
+
+use std::fs;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::ServerGuard;
+
+fn make_fixture_mosaic(root: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(root.join("notes"))?;
+    fs::create_dir_all(root.join("attachments"))?;
+    fs::create_dir_all(root.join(".tesela"))?;
+    fs::write(
+        root.join(".tesela/config.toml"),
+        "[backup]\nauto_on_quit = false\n",
+    )?;
+    Ok(())
+}
+
+fn spawn_server_child(mosaic: &Path, addr: &str) -> Child {
+    Command::new(common::binary_path())
+        .current_dir(mosaic)
+        .env("TESELA_SERVER_BIND", addr)
+        .env("RUST_LOG", "warn")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn tesela-server")
+}
+
+async fn get(client: &reqwest::Client, url: String) -> Value {
+    client
+        .get(url)
+        .send()
+        .await
+        .expect("GET")
+        .error_for_status()
+        .expect("GET ok")
+        .json()
+        .await
+        .expect("json")
+}
+
+fn slugs(rows: &Value) -> Vec<&str> {
+    rows.as_array()
+        .unwrap()
+        .iter()
+        .map(|row| row["slug"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn graph_routes_and_predicates_see_created_links() {
+    let temp = TempDir::new().unwrap();
+    let mosaic = temp.path().join("mosaic");
+    make_fixture_mosaic(&mosaic).unwrap();
+    let (child, _addr, base) = common::spawn_with_retry(Duration::from_secs(15), |addr| {
+        spawn_server_child(&mosaic, addr)
+    });
+    let _server = ServerGuard(Some(child));
+    let client = reqwest::Client::new();
+
+    for (title, content) in [
+        ("Alice", "- Runs the [[Apollo]] launch"),
+        ("Apollo", "- The project"),
+        ("Standup", "- Talked to [[Alice]] and [[Ghost]]"),
+        ("Lonely", "- Nobody links here"),
+    ] {
+        client
+            .post(format!("{base}/notes"))
+            .json(&json!({ "title": title, "content": content }))
+            .send()
+            .await
+            .expect("POST /notes")
+            .error_for_status()
+            .expect("create ok");
+    }
+
+    let orphans = get(&client, format!("{base}/graph/orphans")).await;
+    assert!(slugs(&orphans).contains(&"lonely"), "{orphans}");
+    assert!(!slugs(&orphans).contains(&"alice"), "{orphans}");
+
+    let dead = get(&client, format!("{base}/graph/dead-links")).await;
+    assert_eq!(dead, json!([{ "source": "standup", "target": "Ghost" }]));
+
+    let rank = get(&client, format!("{base}/graph/rank?limit=1")).await;
+    assert_eq!(slugs(&rank), vec!["apollo"]);
+    assert_eq!(rank[0]["in_degree"], json!(1));
+
+    let clusters = get(&client, format!("{base}/graph/clusters")).await;
+    assert_eq!(clusters[0]["size"], json!(3), "{clusters}");
+
+    let path = get(&client, format!("{base}/graph/path?from=standup&to=Apollo")).await;
+    assert_eq!(path["hops"], json!(2));
+    assert_eq!(slugs(&path["pages"]), vec!["standup", "alice", "apollo"]);
+    let back = get(
+        &client,
+        format!("{base}/graph/path?from=apollo&to=standup&directed=true"),
+    )
+    .await;
+    assert_eq!(back["hops"], Value::Null);
+    let missing = client
+        .get(format!("{base}/graph/path?from=apollo&to=nope"))
+        .send()
+        .await
+        .expect("GET /graph/path");
+    assert_eq!(missing.status().as_u16(), 404);
+
+    let result: Value = client
+        .post(format!("{base}/search/query"))
+        .json(&json!({ "dsl": "links-to:[[Alice]]" }))
+        .send()
+        .await
+        .expect("POST /search/query")
+        .error_for_status()
+        .expect("query ok")
+        .json()
+        .await
+        .expect("json");
+    let items = result["groups"][0]["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1, "{result}");
+    assert_eq!(items[0]["page_id"], json!("standup"));
+}
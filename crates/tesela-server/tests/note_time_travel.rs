@@ -0,0 +1,135 @@
+//! HTTP-level coverage for point-in-time note views and restore: `at`
+//! accepts natural phrases, "now" is the live note, a moment before the
+//! note existed is a 404, and restoring the state a note is already in
+//! changes nothing.
+//!
+//! Edits inside one server run fold into a single Loro change (the change
+//! merge interval), so reverting a real edit is covered by the engine tests
+//! in `tesela-sync` rather than here.
+//!
+//! Skipped on non-Unix (spawns the server binary, SIGTERMs to shut down).
+
+#![cfg(unix)]
+
+use std::fs;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::ServerGuard;
+
+fn make_fixture_mosaic(root: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(root.join("notes"))?;
+    fs::create_dir_all(root.join("attachments"))?;
+    fs::create_dir_all(root.join(".tesela"))?;
+    fs::write(
+        root.join(".tesela/config.toml"),
+        "[backup]\nauto_on_quit = false\n",
+    )?;
+    Ok(())
+}
+
+fn spawn_server_child(mosaic: &Path, addr: &str) -> Child {
+    Command::new(common::binary_path())
+        .current_dir(mosaic)
+        .env("TESELA_SERVER_BIND", addr)
+        .env("RUST_LOG", "warn")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn tesela-server")
+}
+
+const PLAN: &str = "0193a000-0000-7000-8000-00000000001a";
+
+#[tokio::test]
+async fn note_at_resolves_phrases_and_restore_is_a_no_op_at_now() {
+    let temp = TempDir::new().unwrap();
+    let mosaic = temp.path().join("mosaic");
+    make_fixture_mosaic(&mosaic).unwrap();
+    let (child, _addr, base) = common::spawn_with_retry(Duration::from_secs(15), |addr| {
+        spawn_server_child(&mosaic, addr)
+    });
+    let _server = ServerGuard(Some(child));
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{base}/notes"))
+        .json(&json!({ "title": "Trip", "content": "- draft\n" }))
+        .send()
+        .await
+        .expect("POST /notes")
+        .error_for_status()
+        .expect("note created");
+    client
+        .put(format!("{base}/notes/trip"))
+        .json(&json!({ "content": format!("- plan <!-- bid:{PLAN} -->\n") }))
+        .send()
+        .await
+        .expect("PUT /notes")
+        .error_for_status()
+        .expect("PUT ok");
+
+    let at: Value = client
+        .get(format!("{base}/notes/trip/at"))
+        .query(&[("at", "now")])
+        .send()
+        .await
+        .expect("GET at")
+        .error_for_status()
+        .expect("GET at ok")
+        .json()
+        .await
+        .expect("json");
+    assert!(at["content"].as_str().unwrap().contains("- plan"), "{at}");
+    assert_eq!(at["changes_since"], json!(0));
+    assert_eq!(at["changes"], json!([]), "now is the live note");
+    assert!(at["last_change_at"].is_string());
+
+    let before = client
+        .get(format!("{base}/notes/trip/at"))
+        .query(&[("at", "2001-01-01")])
+        .send()
+        .await
+        .expect("GET at before the note existed");
+    assert_eq!(before.status().as_u16(), 404);
+
+    let garbled = client
+        .get(format!("{base}/notes/trip/at"))
+        .query(&[("at", "next week")])
+        .send()
+        .await
+        .expect("GET at with an unknown phrase");
+    assert_eq!(garbled.status().as_u16(), 400);
+
+    let bad_block = client
+        .post(format!("{base}/notes/trip/restore"))
+        .json(&json!({ "at": "now", "block_id": "not-a-uuid" }))
+        .send()
+        .await
+        .expect("POST restore with a bad block id");
+    assert_eq!(bad_block.status().as_u16(), 400);
+
+    let restore: Value = client
+        .post(format!("{base}/notes/trip/restore"))
+        .json(&json!({ "at": "now" }))
+        .send()
+        .await
+        .expect("POST restore")
+        .error_for_status()
+        .expect("restore ok")
+        .json()
+        .await
+        .expect("json");
+    assert_eq!(restore["restored"], json!(false), "{restore}");
+    assert_eq!(restore["changes"], json!([]));
+    assert!(restore["note"]["content"]
+        .as_str()
+        .unwrap()
+        .contains("- plan"));
+}
@@ -0,0 +1,137 @@
+//! HTTP-level coverage for OPML exchange: an outline imported through
+//! `POST /imports/opml` becomes a page with the same hierarchy and
+//! properties, and exports back out through `GET /notes/{id}/opml` and
+//! `POST /search/query/opml`.
+//!
+//! Skipped on non-Unix (spawns the server binary, SIGTERMs to shut down).
+
+#![cfg(unix)]
+
+use std::fs;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::ServerGuard;
+
+fn make_fixture_mosaic(root: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(root.join("notes"))?;
+    fs::create_dir_all(root.join("attachments"))?;
+    fs::create_dir_all(root.join(".tesela"))?;
+    fs::write(
+        root.join(".tesela/config.toml"),
+        "[backup]\nauto_on_quit = false\n",
+    )?;
+    Ok(())
+}
+
+fn spawn_server_child(mosaic: &Path, addr: &str) -> Child {
+    Command::new(common::binary_path())
+        .current_dir(mosaic)
+        .env("TESELA_SERVER_BIND", addr)
+        .env("RUST_LOG", "warn")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn tesela-server")
+}
+
+const OUTLINE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<opml version="2.0">
+  <head><title>Garden plan</title></head>
+  <body>
+    <outline text="Beds">
+      <outline text="Plant tomatoes" status="todo"/>
+    </outline>
+    <outline text="Compost" _note="turn weekly"/>
+  </body>
+</opml>
+"#;
+
+#[tokio::test]
+async fn opml_imports_as_a_page_and_exports_back() {
+    let temp = TempDir::new().unwrap();
+    let mosaic = temp.path().join("mosaic");
+    make_fixture_mosaic(&mosaic).unwrap();
+    let (child, _addr, base) = common::spawn_with_retry(Duration::from_secs(15), |addr| {
+        spawn_server_child(&mosaic, addr)
+    });
+    let _server = ServerGuard(Some(child));
+    let client = reqwest::Client::new();
+
+    let note: Value = client
+        .post(format!("{base}/imports/opml"))
+        .json(&json!({ "opml": OUTLINE }))
+        .send()
+        .await
+        .expect("POST /imports/opml")
+        .error_for_status()
+        .expect("import ok")
+        .json()
+        .await
+        .expect("json");
+    assert_eq!(note["title"], json!("Garden plan"));
+    let content = note["content"].as_str().unwrap();
+    assert!(content.contains("- Beds <!-- bid:"), "{content}");
+    assert!(
+        content.contains("  - Plant tomatoes <!-- bid:"),
+        "{content}"
+    );
+    assert!(content.contains("    status:: todo\n"), "{content}");
+    assert!(content.contains("  turn weekly\n"), "{content}");
+
+    let again = client
+        .post(format!("{base}/imports/opml"))
+        .json(&json!({ "opml": OUTLINE }))
+        .send()
+        .await
+        .expect("POST /imports/opml again");
+    assert_eq!(again.status().as_u16(), 409);
+    let bad = client
+        .post(format!("{base}/imports/opml"))
+        .json(&json!({ "opml": "<html/>", "title": "Nope" }))
+        .send()
+        .await
+        .expect("POST /imports/opml with HTML");
+    assert_eq!(bad.status().as_u16(), 400);
+
+    let id = note["id"].as_str().unwrap();
+    let page = client
+        .get(format!("{base}/notes/{id}/opml"))
+        .send()
+        .await
+        .expect("GET /notes/{id}/opml")
+        .error_for_status()
+        .expect("export ok");
+    assert!(page.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .starts_with("text/x-opml"));
+    let page = page.text().await.unwrap();
+    assert!(page.contains("<title>Garden plan</title>"), "{page}");
+    assert!(
+        page.contains("<outline text=\"Plant tomatoes\" bid=\""),
+        "{page}"
+    );
+    assert!(page.contains("status=\"todo\"/>"), "{page}");
+    assert!(page.contains("_note=\"turn weekly\""), "{page}");
+
+    let rows = client
+        .post(format!("{base}/search/query/opml"))
+        .json(&json!({ "dsl": "status:todo" }))
+        .send()
+        .await
+        .expect("POST /search/query/opml")
+        .error_for_status()
+        .expect("query export ok")
+        .text()
+        .await
+        .unwrap();
+    assert!(rows.contains("<outline text=\"Plant tomatoes\""), "{rows}");
+    assert!(!rows.contains("Compost"), "{rows}");
+}
@@ -0,0 +1,104 @@
+//! HTTP-level coverage for `GET /notes/{id}/related`: recommendations over
+//! pages created through `POST /notes`, including one created after the
+//! first lookup loaded the corpus.
+//!
+//! Skipped on non-Unix (spawns the server binary, SIGTERMs to shut down).
+
+#![cfg(unix)]
+
+use std::fs;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::ServerGuard;
+
+fn make_fixture_mosaic(root: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(root.join("notes"))?;
+    fs::create_dir_all(root.join("attachments"))?;
+    fs::create_dir_all(root.join(".tesela"))?;
+    fs::write(
+        root.join(".tesela/config.toml"),
+        "[backup]\nauto_on_quit = false\n",
+    )?;
+    Ok(())
+}
+
+fn spawn_server_child(mosaic: &Path, addr: &str) -> Child {
+    Command::new(common::binary_path())
+        .current_dir(mosaic)
+        .env("TESELA_SERVER_BIND", addr)
+        .env("RUST_LOG", "warn")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn tesela-server")
+}
+
+async fn create(client: &reqwest::Client, base: &str, title: &str, content: &str) {
+    client
+        .post(format!("{base}/notes"))
+        .json(&json!({ "title": title, "content": content }))
+        .send()
+        .await
+        .expect("POST /notes")
+        .error_for_status()
+        .expect("create ok");
+}
+
+async fn related_slugs(client: &reqwest::Client, base: &str, id: &str) -> Vec<String> {
+    let related: Value = client
+        .get(format!("{base}/notes/{id}/related?limit=5"))
+        .send()
+        .await
+        .expect("GET /notes/{id}/related")
+        .error_for_status()
+        .expect("related ok")
+        .json()
+        .await
+        .expect("json");
+    related["pages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|page| page["slug"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn related_route_ranks_similar_pages_and_follows_new_ones() {
+    let temp = TempDir::new().unwrap();
+    let mosaic = temp.path().join("mosaic");
+    make_fixture_mosaic(&mosaic).unwrap();
+    let (child, _addr, base) = common::spawn_with_retry(Duration::from_secs(15), |addr| {
+        spawn_server_child(&mosaic, addr)
+    });
+    let _server = ServerGuard(Some(child));
+    let client = reqwest::Client::new();
+
+    let garden = "- Tomato seedlings need compost and sunlight before transplanting";
+    create(&client, &base, "Tomatoes", garden).await;
+    create(&client, &base, "Seedlings", garden).await;
+    create(&client, &base, "Taxes", "- Quarterly estimated payments").await;
+    assert_eq!(
+        related_slugs(&client, &base, "tomatoes").await,
+        ["seedlings"]
+    );
+
+    create(&client, &base, "Compost", garden).await;
+    let slugs = related_slugs(&client, &base, "tomatoes").await;
+    assert!(slugs.contains(&"compost".to_string()), "{slugs:?}");
+    assert!(!slugs.contains(&"taxes".to_string()), "{slugs:?}");
+
+    let missing = client
+        .get(format!("{base}/notes/nope/related"))
+        .send()
+        .await
+        .expect("GET /notes/nope/related");
+    assert_eq!(missing.status().as_u16(), 404);
+}
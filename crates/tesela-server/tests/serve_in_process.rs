@@ -74,6 +74,11 @@ async fn boot_health_shutdown(mosaic: &Path) -> u16 {
         .error_for_status()
         .expect("/health is 200");
     drop(body);
+    // `/metrics` stays unmounted unless `[server] metrics = true`.
+    let metrics = reqwest::get(format!("http://{addr}/metrics"))
+        .await
+        .expect("GET /metrics connects");
+    assert_eq!(metrics.status(), reqwest::StatusCode::NOT_FOUND);
 
     // Caller-driven graceful shutdown — serve must drain and return Ok.
     shutdown_tx.send(()).expect("shutdown receiver still alive");
@@ -260,3 +265,86 @@ async fn serve_boots_in_process_serves_health_and_shuts_down_releasing_the_flock
     // serve would error, failing this call.
     let _port2 = boot_health_shutdown(dir.path()).await;
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn metrics_route_exposes_route_latency_and_live_ws_sockets() {
+    use futures::StreamExt;
+
+    let dir = TempDir::new().expect("temp mosaic");
+    make_fixture_mosaic(dir.path()).expect("fixture mosaic");
+    // `/metrics` is opt-in; turn it on for this mosaic.
+    fs::write(
+        dir.path().join(".tesela/config.toml"),
+        "[backup]\nauto_on_quit = false\n\n[server]\nbind = \"127.0.0.1:0\"\nmetrics = true\n",
+    )
+    .expect("enable metrics");
+
+    std::env::set_var("TESELA_SERVER_BIND", "127.0.0.1:0");
+    std::env::set_var("TESELA_DISABLE_MDNS", "1");
+    std::env::set_var("TESELA_DISABLE_PEER_SYNC", "1");
+    std::env::set_var("TESELA_GROUP_KEY_FILE_STORE", "1");
+
+    let config = ServeConfig::resolve(Some(dir.path().to_path_buf())).expect("resolve mosaic");
+    let (bound_tx, bound_rx) = tokio::sync::oneshot::channel::<SocketAddr>();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let handle = tokio::spawn(async move {
+        serve(
+            config,
+            async move {
+                let _ = shutdown_rx.await;
+            },
+            move |addr| {
+                let _ = bound_tx.send(addr);
+            },
+        )
+        .await
+    });
+    let addr = tokio::time::timeout(Duration::from_secs(20), bound_rx)
+        .await
+        .expect("serve bound within 20s")
+        .expect("on_bound fired with the address");
+
+    reqwest::get(format!("http://{addr}/health"))
+        .await
+        .expect("GET /health")
+        .error_for_status()
+        .expect("/health is 200");
+    // The session hello proves the socket is fully set up server-side.
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/ws"))
+        .await
+        .expect("ws connects");
+    socket
+        .next()
+        .await
+        .expect("session hello")
+        .expect("hello frame");
+
+    let scraped = reqwest::get(format!("http://{addr}/metrics"))
+        .await
+        .expect("GET /metrics");
+    let status = scraped.status();
+    let text = scraped.text().await.expect("metrics body");
+    drop(socket);
+
+    shutdown_tx.send(()).expect("shutdown receiver still alive");
+    tokio::time::timeout(Duration::from_secs(20), handle)
+        .await
+        .expect("serve returns within 20s")
+        .expect("serve task did not panic")
+        .expect("serve returned Ok");
+
+    assert_eq!(status, reqwest::StatusCode::OK);
+    assert!(
+        text.contains(
+            "tesela_http_requests_total{method=\"GET\",route=\"/health\",status=\"200\"}"
+        ),
+        "{text}"
+    );
+    assert!(
+        text.contains("tesela_http_request_duration_seconds_bucket{le="),
+        "{text}"
+    );
+    assert!(text.contains("tesela_ws_connections 1\n"), "{text}");
+    assert!(text.contains("# TYPE tesela_relay_apply_duration_seconds histogram"));
+    assert!(text.contains("# TYPE tesela_outbound_strand_alarms counter"));
+}
@@ -482,3 +482,60 @@ async fn set_property_seeds_a_markdown_only_note_before_first_edit() {
     assert!(file.contains("pinned:: true"), "got:\n{file}");
     assert_eq!(file.matches("pinned::").count(), 1, "got:\n{file}");
 }
+
+/// A formula property is computed by the index at query time: it shows up in
+/// query results, and a write to it is refused rather than landing in the
+/// markdown (where it could conflict in sync).
+#[tokio::test(flavor = "current_thread")]
+async fn computed_property_is_queryable_but_never_written() {
+    let temp = TempDir::new().unwrap();
+    let mosaic = temp.path().join("mosaic");
+    make_fixture_mosaic(&mosaic).unwrap();
+    fs::write(
+        mosaic.join("notes/effort.md"),
+        "---\ntitle: \"effort\"\ntype: \"Property\"\nvalue_type: \"formula\"\nformula: \"estimate * 2\"\ntags: []\n---\n- Effort property.\n",
+    )
+    .unwrap();
+    fs::write(
+        mosaic.join("notes/sized.md"),
+        format!(
+            "---\ntitle: \"Sized\"\ntags: []\n---\n- a sized task <!-- bid:{TASK_BID} -->\n  estimate:: 3\n"
+        ),
+    )
+    .unwrap();
+
+    let (child, _addr, base) = common::spawn_with_retry(Duration::from_secs(15), |addr| {
+        spawn_server_child(&mosaic, addr)
+    });
+    let _server = ServerGuard(Some(child));
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(format!("{base}/blocks/set-property"))
+        .json(&serde_json::json!({
+            "block_id": format!("sized:{TASK_BID}"),
+            "key": "effort",
+            "value": "10",
+        }))
+        .send()
+        .await
+        .expect("POST /blocks/set-property");
+    assert_eq!(resp.status().as_u16(), 400);
+    let file = fs::read_to_string(mosaic.join("notes/sized.md")).unwrap();
+    assert!(!file.contains("effort::"), "got:\n{file}");
+
+    let result: serde_json::Value = client
+        .post(format!("{base}/search/query"))
+        .json(&serde_json::json!({ "dsl": "effort:>5" }))
+        .send()
+        .await
+        .expect("POST /search/query")
+        .error_for_status()
+        .expect("query ok")
+        .json()
+        .await
+        .expect("query json");
+    let items = result["groups"][0]["items"].as_array().expect("items");
+    assert_eq!(items.len(), 1, "{result}");
+    assert_eq!(items[0]["properties"]["effort"], "6", "{result}");
+}
@@ -0,0 +1,184 @@
+//! HTTP-level coverage for the shared-space routes: creating a space needs
+//! a relay, returns a `tesela-share:` code and persists the space; the code
+//! can't be joined twice on the same mosaic; pages are added and dropped by
+//! slug; leaving removes the space's files. The relay URL points nowhere —
+//! the space's loop just keeps retrying bring-up.
+//!
+//! Skipped on non-Unix (spawns the server binary, SIGTERMs to shut down).
+
+#![cfg(unix)]
+
+use std::fs;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::ServerGuard;
+
+fn make_fixture_mosaic(root: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(root.join("notes"))?;
+    fs::create_dir_all(root.join("attachments"))?;
+    fs::create_dir_all(root.join(".tesela"))?;
+    fs::write(
+        root.join(".tesela/config.toml"),
+        "[backup]\nauto_on_quit = false\n",
+    )?;
+    Ok(())
+}
+
+fn spawn_server_child(mosaic: &Path, addr: &str) -> Child {
+    Command::new(common::binary_path())
+        .current_dir(mosaic)
+        .env("TESELA_SERVER_BIND", addr)
+        .env("RUST_LOG", "warn")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn tesela-server")
+}
+
+#[tokio::test]
+async fn share_routes_create_join_edit_and_leave() {
+    let temp = TempDir::new().unwrap();
+    let mosaic = temp.path().join("mosaic");
+    make_fixture_mosaic(&mosaic).unwrap();
+    let (child, _addr, base) = common::spawn_with_retry(Duration::from_secs(15), |addr| {
+        spawn_server_child(&mosaic, addr)
+    });
+    let _server = ServerGuard(Some(child));
+    let client = reqwest::Client::new();
+
+    for title in ["Groceries", "Recipes"] {
+        client
+            .post(format!("{base}/notes"))
+            .json(&json!({ "title": title, "content": "- item\n" }))
+            .send()
+            .await
+            .expect("POST /notes")
+            .error_for_status()
+            .expect("note created");
+    }
+
+    let no_relay = client
+        .post(format!("{base}/sync/shares"))
+        .json(&json!({ "name": "Home", "notes": ["groceries"] }))
+        .send()
+        .await
+        .expect("POST /sync/shares");
+    assert_eq!(no_relay.status().as_u16(), 400, "a space needs a relay");
+
+    let created = client
+        .post(format!("{base}/sync/shares"))
+        .json(&json!({
+            "name": "Home",
+            "notes": ["groceries"],
+            "relay_url": "http://127.0.0.1:9",
+        }))
+        .send()
+        .await
+        .expect("POST /sync/shares");
+    assert_eq!(created.status().as_u16(), 201);
+    let created: Value = created.json().await.expect("share json");
+    let code = created["code"].as_str().expect("code").to_string();
+    assert!(code.starts_with("tesela-share:"));
+    let share_id = created["space"]["share_id"].as_str().unwrap().to_string();
+    assert_eq!(created["space"]["notes"][0]["slug"], json!("groceries"));
+    assert!(mosaic
+        .join(format!(".tesela/shares/{share_id}.key"))
+        .exists());
+
+    let fetched: Value = client
+        .get(format!("{base}/sync/shares/{share_id}/code"))
+        .send()
+        .await
+        .expect("GET code")
+        .json()
+        .await
+        .expect("code json");
+    assert_eq!(fetched["code"], json!(code));
+
+    let rejoin = client
+        .post(format!("{base}/sync/shares/join"))
+        .json(&json!({ "code": code }))
+        .send()
+        .await
+        .expect("POST join");
+    assert_eq!(rejoin.status().as_u16(), 409, "already in this space");
+    let garbage = client
+        .post(format!("{base}/sync/shares/join"))
+        .json(&json!({ "code": "not-a-code" }))
+        .send()
+        .await
+        .expect("POST join garbage");
+    assert_eq!(garbage.status().as_u16(), 400);
+
+    let added: Value = client
+        .post(format!("{base}/sync/shares/{share_id}/notes"))
+        .json(&json!({ "note": "Recipes" }))
+        .send()
+        .await
+        .expect("POST notes")
+        .error_for_status()
+        .expect("page added")
+        .json()
+        .await
+        .expect("summary json");
+    assert_eq!(added["notes"].as_array().unwrap().len(), 2);
+    let removed: Value = client
+        .delete(format!("{base}/sync/shares/{share_id}/notes/groceries"))
+        .send()
+        .await
+        .expect("DELETE note")
+        .error_for_status()
+        .expect("page dropped")
+        .json()
+        .await
+        .expect("summary json");
+    assert_eq!(removed["notes"][0]["slug"], json!("recipes"));
+    let missing = client
+        .post(format!("{base}/sync/shares/{share_id}/notes"))
+        .json(&json!({ "note": "no-such-page" }))
+        .send()
+        .await
+        .expect("POST missing page");
+    assert_eq!(missing.status().as_u16(), 404);
+
+    let listed: Value = client
+        .get(format!("{base}/sync/shares"))
+        .send()
+        .await
+        .expect("GET /sync/shares")
+        .json()
+        .await
+        .expect("list json");
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+
+    client
+        .delete(format!("{base}/sync/shares/{share_id}"))
+        .send()
+        .await
+        .expect("DELETE share")
+        .error_for_status()
+        .expect("left");
+    assert!(!mosaic
+        .join(format!(".tesela/shares/{share_id}.json"))
+        .exists());
+    let gone = client
+        .get(format!("{base}/sync/shares/{share_id}/code"))
+        .send()
+        .await
+        .expect("GET code after leave");
+    assert_eq!(gone.status().as_u16(), 404);
+    client
+        .get(format!("{base}/notes/groceries"))
+        .send()
+        .await
+        .expect("GET page")
+        .error_for_status()
+        .expect("pages stay after leaving");
+}
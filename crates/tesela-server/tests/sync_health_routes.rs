@@ -0,0 +1,144 @@
+//! HTTP-level coverage for the sync-health routes: a fresh mosaic reports
+//! healthy with an empty incident log; `repair_garbled_blocks` (and its old
+//! name `heal_twins`) runs as a no-op; an explicit scan records nothing new;
+//! `snapshot_catchup` needs a note that actually has a causal gap.
+//!
+//! Skipped on non-Unix (spawns the server binary, SIGTERMs to shut down).
+
+#![cfg(unix)]
+
+use std::fs;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::ServerGuard;
+
+fn make_fixture_mosaic(root: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(root.join("notes"))?;
+    fs::create_dir_all(root.join("attachments"))?;
+    fs::create_dir_all(root.join(".tesela"))?;
+    fs::write(
+        root.join(".tesela/config.toml"),
+        "[backup]\nauto_on_quit = false\n",
+    )?;
+    Ok(())
+}
+
+fn spawn_server_child(mosaic: &Path, addr: &str) -> Child {
+    Command::new(common::binary_path())
+        .current_dir(mosaic)
+        .env("TESELA_SERVER_BIND", addr)
+        .env("RUST_LOG", "warn")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn tesela-server")
+}
+
+#[tokio::test]
+async fn health_routes_report_repair_and_list_incidents() {
+    let temp = TempDir::new().unwrap();
+    let mosaic = temp.path().join("mosaic");
+    make_fixture_mosaic(&mosaic).unwrap();
+    let (child, _addr, base) = common::spawn_with_retry(Duration::from_secs(15), |addr| {
+        spawn_server_child(&mosaic, addr)
+    });
+    let _server = ServerGuard(Some(child));
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{base}/notes"))
+        .json(&json!({ "title": "Groceries", "content": "- oat milk\n" }))
+        .send()
+        .await
+        .expect("POST /notes")
+        .error_for_status()
+        .expect("note created");
+
+    let health: Value = client
+        .get(format!("{base}/sync/health"))
+        .send()
+        .await
+        .expect("GET /sync/health")
+        .error_for_status()
+        .expect("health ok")
+        .json()
+        .await
+        .expect("health json");
+    assert_eq!(health["healthy"], json!(true));
+    assert_eq!(health["anomalies"], json!([]));
+    assert_eq!(health["catchup_queue"], json!([]));
+
+    let healed: Value = client
+        .post(format!("{base}/sync/health/repair"))
+        .json(&json!({ "action": "repair_garbled_blocks" }))
+        .send()
+        .await
+        .expect("POST repair")
+        .error_for_status()
+        .expect("repair_garbled_blocks ok")
+        .json()
+        .await
+        .expect("repair json");
+    assert_eq!(healed["notes"], json!([]));
+    assert_eq!(healed["health"]["healthy"], json!(true));
+    let legacy = client
+        .post(format!("{base}/sync/health/repair"))
+        .json(&json!({ "action": "heal_twins" }))
+        .send()
+        .await
+        .expect("POST repair under the old action name");
+    assert!(legacy.status().is_success());
+
+    let scanned: Value = client
+        .post(format!("{base}/sync/health/scan"))
+        .send()
+        .await
+        .expect("POST /sync/health/scan")
+        .error_for_status()
+        .expect("scan ok")
+        .json()
+        .await
+        .expect("scan json");
+    assert_eq!(scanned["healthy"], json!(true));
+
+    let no_note = client
+        .post(format!("{base}/sync/health/repair"))
+        .json(&json!({ "action": "snapshot_catchup" }))
+        .send()
+        .await
+        .expect("POST repair without note");
+    assert_eq!(no_note.status().as_u16(), 400);
+    let no_gap = client
+        .post(format!("{base}/sync/health/repair"))
+        .json(&json!({ "action": "snapshot_catchup", "note": "groceries" }))
+        .send()
+        .await
+        .expect("POST repair on a healthy note");
+    assert_eq!(no_gap.status().as_u16(), 404);
+    let unknown = client
+        .post(format!("{base}/sync/health/repair"))
+        .json(&json!({ "action": "rewrite_history" }))
+        .send()
+        .await
+        .expect("POST unknown action");
+    assert_eq!(unknown.status().as_u16(), 422);
+
+    let incidents: Value = client
+        .get(format!("{base}/sync/health/incidents"))
+        .send()
+        .await
+        .expect("GET incidents")
+        .error_for_status()
+        .expect("incidents ok")
+        .json()
+        .await
+        .expect("incidents json");
+    assert_eq!(incidents, json!([]));
+}
@@ -0,0 +1,139 @@
+//! HTTP-level coverage for the selective-sync routes: `PUT`/`DELETE
+//! /sync/scope/{device}` round-trip through the synced scope registry,
+//! `GET /sync/scope` reports this device's id, device-id validation, and
+//! a note written on this device is never evicted while its edits haven't
+//! left it (no relay here, so it stays put).
+//!
+//! Skipped on non-Unix (spawns the server binary, SIGTERMs to shut down).
+
+#![cfg(unix)]
+
+use std::fs;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::ServerGuard;
+
+fn make_fixture_mosaic(root: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(root.join("notes"))?;
+    fs::create_dir_all(root.join("attachments"))?;
+    fs::create_dir_all(root.join(".tesela"))?;
+    fs::write(
+        root.join(".tesela/config.toml"),
+        "[backup]\nauto_on_quit = false\n",
+    )?;
+    Ok(())
+}
+
+fn spawn_server_child(mosaic: &Path, addr: &str) -> Child {
+    Command::new(common::binary_path())
+        .current_dir(mosaic)
+        .env("TESELA_SERVER_BIND", addr)
+        .env("RUST_LOG", "warn")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn tesela-server")
+}
+
+async fn get_scope(client: &reqwest::Client, base: &str) -> Value {
+    client
+        .get(format!("{base}/sync/scope"))
+        .send()
+        .await
+        .expect("GET /sync/scope")
+        .error_for_status()
+        .expect("GET /sync/scope ok")
+        .json()
+        .await
+        .expect("scope json")
+}
+
+#[tokio::test]
+async fn scope_routes_round_trip_and_keep_unsent_local_notes() {
+    let temp = TempDir::new().unwrap();
+    let mosaic = temp.path().join("mosaic");
+    make_fixture_mosaic(&mosaic).unwrap();
+    let (child, _addr, base) = common::spawn_with_retry(Duration::from_secs(15), |addr| {
+        spawn_server_child(&mosaic, addr)
+    });
+    let _server = ServerGuard(Some(child));
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{base}/notes"))
+        .json(&json!({ "title": "Diary", "content": "- dinner #personal\n" }))
+        .send()
+        .await
+        .expect("POST /notes")
+        .error_for_status()
+        .expect("note created");
+
+    let scope = get_scope(&client, &base).await;
+    let device = scope["device"].as_str().expect("device id").to_string();
+    assert_eq!(device.len(), 32);
+    assert_eq!(scope["scopes"], json!([]));
+    assert_eq!(scope["excluded"], json!([]));
+
+    let stored: Value = client
+        .put(format!("{base}/sync/scope/{}", device.to_uppercase()))
+        .json(&json!({ "exclude_tags": ["personal", " "], "exclude_slug_prefixes": ["archive-"] }))
+        .send()
+        .await
+        .expect("PUT /sync/scope")
+        .error_for_status()
+        .expect("PUT ok")
+        .json()
+        .await
+        .expect("scope json");
+    assert_eq!(stored["device"], json!(device));
+    assert_eq!(
+        stored["exclude_tags"],
+        json!(["personal"]),
+        "blank rules dropped"
+    );
+    assert_eq!(stored["exclude_slug_prefixes"], json!(["archive-"]));
+
+    let scope = get_scope(&client, &base).await;
+    assert_eq!(scope["scopes"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        scope["excluded"],
+        json!([]),
+        "a note authored here is kept until its edits are broadcast"
+    );
+    client
+        .get(format!("{base}/notes/diary"))
+        .send()
+        .await
+        .expect("GET /notes/diary")
+        .error_for_status()
+        .expect("diary still served");
+
+    let bad = client
+        .put(format!("{base}/sync/scope/not-a-device"))
+        .json(&json!({ "exclude_tags": ["x"] }))
+        .send()
+        .await
+        .expect("PUT bad device");
+    assert_eq!(bad.status().as_u16(), 400);
+
+    let deleted = client
+        .delete(format!("{base}/sync/scope/{device}"))
+        .send()
+        .await
+        .expect("DELETE /sync/scope");
+    assert_eq!(deleted.status().as_u16(), 200);
+    let again = client
+        .delete(format!("{base}/sync/scope/{device}"))
+        .send()
+        .await
+        .expect("DELETE /sync/scope again");
+    assert_eq!(again.status().as_u16(), 404);
+    assert_eq!(get_scope(&client, &base).await["scopes"], json!([]));
+}
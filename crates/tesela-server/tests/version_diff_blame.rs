@@ -0,0 +1,189 @@
+//! HTTP-level coverage for note-history diff and blame: two PUTs make two
+//! versions whose block-level diff reads "edited, added" one way and
+//! "edited, removed" the other, and blame attributes every live block to
+//! this device.
+//!
+//! Skipped on non-Unix (spawns the server binary, SIGTERMs to shut down).
+
+#![cfg(unix)]
+
+use std::fs;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::ServerGuard;
+
+fn make_fixture_mosaic(root: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(root.join("notes"))?;
+    fs::create_dir_all(root.join("attachments"))?;
+    fs::create_dir_all(root.join(".tesela"))?;
+    fs::write(
+        root.join(".tesela/config.toml"),
+        "[backup]\nauto_on_quit = false\n",
+    )?;
+    Ok(())
+}
+
+fn spawn_server_child(mosaic: &Path, addr: &str) -> Child {
+    Command::new(common::binary_path())
+        .current_dir(mosaic)
+        .env("TESELA_SERVER_BIND", addr)
+        .env("RUST_LOG", "warn")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn tesela-server")
+}
+
+const MILK: &str = "0193a000-0000-7000-8000-00000000000a";
+const EGGS: &str = "0193a000-0000-7000-8000-00000000000b";
+const BREAD: &str = "0193a000-0000-7000-8000-00000000000c";
+
+async fn put(client: &reqwest::Client, base: &str, content: String) {
+    client
+        .put(format!("{base}/notes/groceries"))
+        .json(&json!({ "content": content }))
+        .send()
+        .await
+        .expect("PUT /notes")
+        .error_for_status()
+        .expect("PUT ok");
+}
+
+async fn get_json(client: &reqwest::Client, url: String) -> Value {
+    client
+        .get(url)
+        .send()
+        .await
+        .expect("GET")
+        .error_for_status()
+        .expect("GET ok")
+        .json()
+        .await
+        .expect("json")
+}
+
+#[tokio::test]
+async fn version_diff_is_block_aware_and_blame_names_the_device() {
+    let temp = TempDir::new().unwrap();
+    let mosaic = temp.path().join("mosaic");
+    make_fixture_mosaic(&mosaic).unwrap();
+    let (child, _addr, base) = common::spawn_with_retry(Duration::from_secs(15), |addr| {
+        spawn_server_child(&mosaic, addr)
+    });
+    let _server = ServerGuard(Some(child));
+    let client = reqwest::Client::new();
+
+    client
+        .post(format!("{base}/notes"))
+        .json(&json!({ "title": "Groceries", "content": "- draft\n" }))
+        .send()
+        .await
+        .expect("POST /notes")
+        .error_for_status()
+        .expect("note created");
+    put(
+        &client,
+        &base,
+        format!("- oat milk <!-- bid:{MILK} -->\n- eggs <!-- bid:{EGGS} -->\n"),
+    )
+    .await;
+    put(
+        &client,
+        &base,
+        format!("- oat milk x2 <!-- bid:{MILK} -->\n- bread <!-- bid:{BREAD} -->\n"),
+    )
+    .await;
+
+    let versions = get_json(&client, format!("{base}/notes/groceries/versions")).await;
+    let ids: Vec<i64> = versions
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["id"].as_i64().unwrap())
+        .collect();
+    assert!(ids.len() >= 2, "each PUT records a version: {versions}");
+    let (newest, previous) = (ids[0], ids[1]);
+
+    let diff = get_json(
+        &client,
+        format!("{base}/notes/groceries/versions/{previous}/diff/{newest}"),
+    )
+    .await;
+    // A PUT never infers deletes (see `DiffOptions::emit_deletes`), so
+    // "eggs" survives the second PUT.
+    assert_eq!(
+        diff["summary"],
+        json!({ "added": 1, "removed": 0, "edited": 1, "moved": 0 })
+    );
+    let kinds = |diff: &Value| -> Vec<(String, String)> {
+        diff["changes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| {
+                (
+                    c["block_id"].as_str().unwrap().to_string(),
+                    c["kind"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    };
+    assert_eq!(
+        kinds(&diff),
+        vec![
+            (MILK.to_string(), "edited".to_string()),
+            (BREAD.to_string(), "added".to_string()),
+        ]
+    );
+    assert_eq!(diff["changes"][0]["old_text"], json!("oat milk"));
+    assert_eq!(diff["changes"][0]["new_text"], json!("oat milk x2"));
+
+    let back = get_json(
+        &client,
+        format!("{base}/notes/groceries/versions/{newest}/diff/{previous}"),
+    )
+    .await;
+    assert_eq!(
+        kinds(&back),
+        vec![
+            (MILK.to_string(), "edited".to_string()),
+            (BREAD.to_string(), "removed".to_string()),
+        ]
+    );
+
+    let missing = client
+        .get(format!(
+            "{base}/notes/groceries/versions/{previous}/diff/999999"
+        ))
+        .send()
+        .await
+        .expect("GET diff against a missing version");
+    assert_eq!(missing.status().as_u16(), 404);
+
+    let blame = get_json(&client, format!("{base}/notes/groceries/blame")).await;
+    let blame = blame.as_array().unwrap();
+    let texts: Vec<&str> = blame.iter().map(|e| e["text"].as_str().unwrap()).collect();
+    assert!(
+        texts.contains(&"oat milk x2") && texts.contains(&"bread"),
+        "{texts:?}"
+    );
+    for entry in blame {
+        assert_eq!(entry["is_local"], json!(true), "{entry}");
+        assert!(entry["device_id"].is_string());
+        assert!(entry["modified_at"].is_string());
+    }
+
+    let unknown = client
+        .get(format!("{base}/notes/no-such-page/blame"))
+        .send()
+        .await
+        .expect("GET blame for a missing page");
+    assert_eq!(unknown.status().as_u16(), 404);
+}
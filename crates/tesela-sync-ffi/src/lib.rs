@@ -619,7 +619,9 @@ pub fn presence_open(group_key: Vec<u8>, group_id: Vec<u8>, outer: Vec<u8>) -> O
 /// WebSocket — the pure-FFI mirror of `tesela-server::presence_relay::connect`.
 /// The Swift transport sets them on its `URLSessionWebSocketTask` request
 /// (CF rebuilds the canonical from `x-tesela-original-path`, so the signed
-/// path is `/groups/{hex}/presence/ws` with empty query + empty body hash).
+/// path is `/groups/{hex}/presence/ws` with an empty body hash). The signed
+/// query is `device={device_hex}`, which the transport MUST append to the
+/// upgrade URL verbatim — the relay takes the socket's identity from it.
 ///
 /// CRITICAL — two distinct nonces + two distinct keys, never crossed:
 /// the MAC nonce here is a FRESH 16 random bytes (NOT the 24-byte AEAD nonce
@@ -662,8 +664,9 @@ pub fn presence_ws_headers(
         .map(|d| d.as_secs() as i64)
         .unwrap_or(0);
     let path = format!("/groups/{}/presence/ws", hex_encode(&gid));
-    // Empty query, empty body hash — mirror `GET /ops`.
-    let canonical = canonical_request("GET", &path, "", &nonce_b64, ts, "");
+    let query = format!("device={}", hex_encode(&did));
+    // Empty body hash — mirror `GET /ops`.
+    let canonical = canonical_request("GET", &path, &query, &nonce_b64, ts, "");
     let mac_b64 =
         base64::engine::general_purpose::STANDARD.encode(compute_request_mac(&auth, &canonical));
     PresenceWsHeaders {
@@ -1432,6 +1435,7 @@ impl SyncEngineHandle {
     ) -> Result<u32, FfiSyncError> {
         let key = normalize_prop_key(&key)?;
         let value_type = ValueType::parse(value_type.trim());
+        reject_computed(&key, value_type)?;
         if value_type == ValueType::Node && tesela_core::PageId::parse(&value).is_none() {
             return Err(FfiSyncError::Other {
                 message: "node properties require a canonical PageId".into(),
@@ -1457,6 +1461,7 @@ impl SyncEngineHandle {
     ) -> Result<(), FfiSyncError> {
         let key = normalize_prop_key(&key)?;
         let value_type = ValueType::parse(value_type.trim());
+        reject_computed(&key, value_type)?;
         if value_type == ValueType::Node
             && value
                 .as_deref()
@@ -1796,6 +1801,18 @@ fn normalize_prop_key(key: &str) -> Result<String, FfiSyncError> {
     Ok(key)
 }
 
+/// Refuse a typed write to a `formula` / `rollup` property — those values are
+/// computed by the index and never stored (the server's set-property routes
+/// refuse them the same way).
+fn reject_computed(key: &str, value_type: ValueType) -> Result<(), FfiSyncError> {
+    if value_type.is_computed() {
+        return Err(FfiSyncError::Other {
+            message: format!("property '{key}' is computed and can't be set"),
+        });
+    }
+    Ok(())
+}
+
 /// The [`PropOp`]s a property set maps to — the server's `prop_ops_for_set`
 /// with no registry in reach, i.e. its unknown-key degrade for every key:
 /// free-text `SetText` (coerce-and-keep), except the registry-less `tags`
@@ -2690,7 +2707,8 @@ mod tests {
         let key = GroupKey::from_bytes(gk_v.as_slice().try_into().unwrap());
         let auth = derive_relay_auth_key(&key, &GroupId::from_bytes(gid));
         let path = format!("/groups/{}/presence/ws", hex_encode(&gid));
-        let canonical = canonical_request("GET", &path, "", &h.nonce_b64, h.ts, "");
+        let query = format!("device={}", hex_encode(&[0x11u8; 16]));
+        let canonical = canonical_request("GET", &path, &query, &h.nonce_b64, h.ts, "");
         let expected = base64::engine::general_purpose::STANDARD
             .encode(compute_request_mac(&auth, &canonical));
 
@@ -124,8 +124,9 @@ fn file_store_forced() -> bool {
 
 /// Persist `key` into whichever store is active for this platform/env
 /// (Keychain on macOS unless [`FILE_STORE_ENV`] forces the file store).
-/// Used by [`adopt`] (pairing-code receiver overwrite) and by the
-/// migrate-on-first-run path in [`load_or_create_group_key`].
+/// Used by [`adopt`] (pairing-code receiver overwrite), by the
+/// migrate-on-first-run path in [`load_or_create_group_key`], and by the
+/// server's shared spaces (one key dir per space).
 ///
 /// `not(test)`-gated on the Keychain branch: `tesela-sync`'s OWN unit
 /// tests must never touch the real OS keychain (mirrors `tesela-backup`'s
@@ -134,7 +135,7 @@ fn file_store_forced() -> bool {
 /// (non-test) dependency — e.g. `tesela-server`'s integration tests —
 /// don't get this `cfg(test)` for free and must opt into
 /// [`FILE_STORE_ENV`] themselves for hermetic test mosaics.
-async fn store_group_key_active(tesela_dir: &Path, key: &GroupKey) -> SyncResult<()> {
+pub async fn store_group_key_active(tesela_dir: &Path, key: &GroupKey) -> SyncResult<()> {
     #[cfg(all(target_os = "macos", not(test)))]
     {
         if !file_store_forced() {
@@ -151,6 +152,45 @@ async fn store_group_key_active(tesela_dir: &Path, key: &GroupKey) -> SyncResult
     FileGroupKeyStore::new(tesela_dir).store_key(key).await
 }
 
+/// Load the key held by whichever store is active for `tesela_dir`,
+/// without minting one — for keys that arrive from elsewhere (a shared
+/// space's key comes in a share code). A plaintext `group_key.bin` left
+/// from before the Keychain cutover is migrated in and shredded, as in
+/// [`load_or_create_group_key`].
+pub async fn load_group_key_active(tesela_dir: &Path) -> SyncResult<Option<GroupKey>> {
+    let legacy = FileGroupKeyStore::new(tesela_dir);
+    #[cfg(all(target_os = "macos", not(test)))]
+    {
+        if !file_store_forced() {
+            let keychain = KeychainGroupKeyStore::new(tesela_dir);
+            if let Some(k) = keychain.load_key().await? {
+                return Ok(Some(k));
+            }
+            let Some(k) = legacy.load_key().await? else {
+                return Ok(None);
+            };
+            keychain.store_key(&k).await?;
+            legacy.shred().await?;
+            return Ok(Some(k));
+        }
+    }
+    legacy.load_key().await
+}
+
+/// Drop the key for `tesela_dir` from every store: the Keychain entry
+/// (macOS) and any plaintext file, shredded. No-op where nothing was
+/// stored.
+pub async fn forget_group_key(tesela_dir: &Path) -> SyncResult<()> {
+    #[cfg(all(target_os = "macos", not(test)))]
+    {
+        let account = tesela_dir.to_string_lossy().into_owned();
+        tokio::task::spawn_blocking(move || keychain_delete(&account))
+            .await
+            .map_err(|e| SyncError::Other(format!("keychain delete task: {e}")))??;
+    }
+    FileGroupKeyStore::new(tesela_dir).shred().await
+}
+
 fn group_id_path(tesela_dir: &Path) -> PathBuf {
     tesela_dir.join("group_id.hex")
 }
@@ -308,6 +348,16 @@ fn keychain_store(account: &str, bytes: &[u8; 32]) -> SyncResult<()> {
         .map_err(|e| SyncError::Other(format!("keychain store: {e}")))
 }
 
+#[cfg(all(target_os = "macos", not(test)))]
+fn keychain_delete(account: &str) -> SyncResult<()> {
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, account)
+        .map_err(|e| SyncError::Other(format!("keychain entry: {e}")))?;
+    match entry.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(SyncError::Other(format!("keychain delete: {e}"))),
+    }
+}
+
 async fn load_or_create_group_id(tesela_dir: &Path) -> SyncResult<GroupId> {
     let path = group_id_path(tesela_dir);
     match tokio::fs::read_to_string(&path).await {
@@ -10,3 +10,5 @@ pub mod keys;
 pub mod pairing;
 pub mod recovery;
 pub mod relay_auth;
+pub mod rotation;
+pub mod share;
@@ -0,0 +1,779 @@
+//! Group key rotation and device revocation.
+//!
+//! A group's symmetric key lives in numbered **epochs**. Genesis (and
+//! every install that never rotated) is epoch 0 — the key pairing hands
+//! out today. Rotating mints epoch `N + 1` and distributes it as a
+//! [`RotationRecord`] through the ordinary envelope/relay path:
+//!
+//! - Each remaining member gets an [`EpochKeyGrant`]: the new key sealed
+//!   under `HKDF(X25519(issuer, member))`. Static-static key agreement
+//!   makes the grant both confidential to that member and authenticated
+//!   as coming from the issuer — nobody else (the relay, other members,
+//!   the revoked device) can produce or open it. The AEAD binds the
+//!   record header, so stripping a `revoked` entry or a grant breaks
+//!   every grant.
+//! - Devices removed from `group_members` (see
+//!   [`crate::group::remove_member`]) simply get no grant. They still
+//!   hold the old epoch keys, so they can read history, but nothing
+//!   sealed from the new epoch on.
+//! - Old epochs stay in the [`GroupKeyring`] read-only: relay rows and
+//!   snapshots sealed under them still open; nothing new is sealed with
+//!   them. Epoch numbers are client-side bookkeeping — sealed payloads
+//!   carry no epoch tag, so openers try the current key, then retired
+//!   ones newest first.
+//!
+//! Members learn each other's X25519 keys from [`MemberAnnouncement`]s,
+//! also carried as envelopes. Keys are trust-on-first-use per device
+//! (`group::upsert_member`).
+//!
+//! Known limit: until the legitimate rotation reaches a member, a revoked
+//! device that is still online holds the current key and could race its
+//! own rotation record. Rotate from a trusted device as soon as a device
+//! is lost; members reject rotations issued by a device they already saw
+//! revoked, or by one they never saw announce.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use hkdf::Hkdf;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::crypto::aead;
+use crate::crypto::keys::{self, GroupIdentity, GroupKey};
+use crate::device::DeviceId;
+use crate::error::{SyncError, SyncResult};
+use crate::group::{GroupId, GroupMember};
+
+/// HKDF info for the per-(issuer, member) grant wrapping key. The epoch
+/// number is appended so a grant can't be replayed into another epoch.
+pub const EPOCH_GRANT_INFO_V1: &[u8] = b"tesela-epoch-grant-v1";
+
+/// Magic prefix for group-control payloads riding inside an envelope.
+/// Distinct from [`crate::wire::LORO_RELAY_MAGIC`], so a peer that only
+/// understands Loro payloads skips these instead of misreading them.
+pub const GROUP_RECORD_MAGIC: [u8; 4] = *b"TGR1";
+
+const KEYRING_FILE_VERSION: u8 = 1;
+
+// ── Device key agreement ──────────────────────────────────────────
+
+/// This device's X25519 key-agreement secret. Persisted as 32 raw bytes
+/// at `<mosaic>/.tesela/device_kx.bin` (same threat model as
+/// [`keys::FileGroupKeyStore`]); the public half is what
+/// [`MemberAnnouncement`] publishes.
+pub struct DeviceKx(StaticSecret);
+
+impl DeviceKx {
+    /// Load this device's key-agreement secret, minting one on first use.
+    pub async fn load_or_create(mosaic_root: &Path) -> SyncResult<Self> {
+        let dir = mosaic_root.join(".tesela");
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| SyncError::Other(format!("create .tesela dir: {e}")))?;
+        let path = dir.join("device_kx.bin");
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => {
+                let arr: [u8; 32] = bytes.try_into().map_err(|_| {
+                    SyncError::Other(format!("device_kx at {} has wrong length", path.display()))
+                })?;
+                Ok(Self::from_bytes(arr))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let kx = Self::random();
+                tokio::fs::write(&path, kx.0.to_bytes())
+                    .await
+                    .map_err(|e| SyncError::Other(format!("write device_kx: {e}")))?;
+                Ok(kx)
+            }
+            Err(e) => Err(SyncError::Other(format!("read device_kx: {e}"))),
+        }
+    }
+
+    /// Wrap raw secret bytes (clamped by X25519 on use).
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(StaticSecret::from(bytes))
+    }
+
+    /// Fresh secret from the OS CSPRNG.
+    pub fn random() -> Self {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        Self::from_bytes(bytes)
+    }
+
+    /// The public key other members wrap epoch keys to.
+    pub fn public_key(&self) -> [u8; 32] {
+        PublicKey::from(&self.0).to_bytes()
+    }
+
+    /// Grant wrapping key shared with `peer` for `epoch`. Symmetric:
+    /// issuer and member derive the same key from opposite halves.
+    fn grant_key(&self, peer: &[u8; 32], group_id: &GroupId, epoch: u32) -> SyncResult<GroupKey> {
+        let shared = self.0.diffie_hellman(&PublicKey::from(*peer));
+        if !shared.was_contributory() {
+            return Err(SyncError::Crypto(
+                "member key-agreement key is a low-order point".into(),
+            ));
+        }
+        let hk = Hkdf::<Sha256>::new(Some(group_id.as_bytes()), shared.as_bytes());
+        let mut info = EPOCH_GRANT_INFO_V1.to_vec();
+        info.extend_from_slice(&epoch.to_be_bytes());
+        let mut out = [0u8; 32];
+        hk.expand(&info, &mut out)
+            .expect("32-byte HKDF output is well below the max");
+        Ok(GroupKey::from_bytes(out))
+    }
+}
+
+impl std::fmt::Debug for DeviceKx {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeviceKx")
+            .field("public_key", &hex::encode(self.public_key()))
+            .finish()
+    }
+}
+
+// ── Keyring ───────────────────────────────────────────────────────
+
+/// Every epoch key this device holds for one group. The current epoch
+/// seals; retired epochs only open.
+#[derive(Debug, Clone)]
+pub struct GroupKeyring {
+    group_id: GroupId,
+    epoch: u32,
+    current: GroupKey,
+    retired: BTreeMap<u32, GroupKey>,
+}
+
+impl GroupKeyring {
+    /// A keyring holding only `ident`'s key as epoch 0 — every group
+    /// that has never rotated.
+    pub fn genesis(ident: &GroupIdentity) -> Self {
+        Self::at_epoch(ident, 0)
+    }
+
+    /// A keyring holding only `ident`'s key at `epoch`. Earlier epochs
+    /// are history this device never had.
+    pub fn at_epoch(ident: &GroupIdentity, epoch: u32) -> Self {
+        Self {
+            group_id: ident.group_id,
+            epoch,
+            current: ident.group_key.clone(),
+            retired: BTreeMap::new(),
+        }
+    }
+
+    /// The group these keys belong to.
+    pub fn group_id(&self) -> GroupId {
+        self.group_id
+    }
+
+    /// The current (sealing) epoch.
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+
+    /// The current epoch's key.
+    pub fn current_key(&self) -> &GroupKey {
+        &self.current
+    }
+
+    /// The current epoch as a [`GroupIdentity`] — what pairing, LAN and
+    /// the relay auth key are derived from.
+    pub fn identity(&self) -> GroupIdentity {
+        GroupIdentity {
+            group_id: self.group_id,
+            group_key: self.current.clone(),
+        }
+    }
+
+    /// Key for `epoch`, current or retired.
+    pub fn key_for(&self, epoch: u32) -> Option<&GroupKey> {
+        if epoch == self.epoch {
+            Some(&self.current)
+        } else {
+            self.retired.get(&epoch)
+        }
+    }
+
+    /// Retired epochs, newest first.
+    pub fn retired(&self) -> impl Iterator<Item = (u32, &GroupKey)> {
+        self.retired.iter().rev().map(|(e, k)| (*e, k))
+    }
+
+    /// Make `key` the current epoch, retiring the previous one to
+    /// read-only. Epochs only move forward.
+    pub fn advance(&mut self, epoch: u32, key: GroupKey) -> SyncResult<()> {
+        if epoch <= self.epoch {
+            return Err(SyncError::Protocol(format!(
+                "key epoch {epoch} is not after current epoch {}",
+                self.epoch
+            )));
+        }
+        let previous = std::mem::replace(&mut self.current, key);
+        self.retired.insert(self.epoch, previous);
+        self.epoch = epoch;
+        Ok(())
+    }
+}
+
+/// On-disk form of the epoch bookkeeping. The current key itself stays
+/// in the active [`keys::GroupKeyStore`] (Keychain on macOS); this file
+/// holds only its fingerprint plus the retired, history-only keys.
+#[derive(Serialize, Deserialize)]
+struct KeyringFile {
+    version: u8,
+    group_id: GroupId,
+    epoch: u32,
+    current_fingerprint: [u8; 16],
+    retired: Vec<(u32, [u8; 32])>,
+}
+
+fn keyring_path(mosaic_root: &Path) -> PathBuf {
+    mosaic_root.join(".tesela").join("group_key_epochs.bin")
+}
+
+fn key_fingerprint(key: &GroupKey) -> [u8; 16] {
+    let mut h = Sha256::new();
+    h.update(b"tesela-epoch-fingerprint-v1");
+    h.update(key.as_bytes());
+    let mut out = [0u8; 16];
+    out.copy_from_slice(&h.finalize()[..16]);
+    out
+}
+
+/// Load the group identity plus its epoch history. A mosaic without an
+/// epoch file is at epoch 0.
+///
+/// [`store_keyring`] writes the epoch file before swapping the active key,
+/// so a crash in between leaves a file that is one epoch ahead of the key
+/// store. That is detected by fingerprint and rolled back to the epoch the
+/// stored key belongs to; the rotation record is still on the relay and
+/// is accepted again on the next poll.
+pub async fn load_keyring(mosaic_root: &Path) -> SyncResult<GroupKeyring> {
+    let ident = keys::load_or_create(mosaic_root).await?;
+    let path = keyring_path(mosaic_root);
+    let bytes = match tokio::fs::read(&path).await {
+        Ok(b) => b,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(GroupKeyring::genesis(&ident));
+        }
+        Err(e) => return Err(SyncError::Other(format!("read group key epochs: {e}"))),
+    };
+    let file: KeyringFile = postcard::from_bytes(&bytes)?;
+    if file.group_id != ident.group_id {
+        // Re-paired into another group since the last rotation.
+        tracing::warn!("group key epoch file belongs to another group; starting at epoch 0");
+        return Ok(GroupKeyring::genesis(&ident));
+    }
+    let mut retired: BTreeMap<u32, GroupKey> = file
+        .retired
+        .into_iter()
+        .map(|(e, k)| (e, GroupKey::from_bytes(k)))
+        .collect();
+    let mut epoch = file.epoch;
+    if file.current_fingerprint != key_fingerprint(&ident.group_key) {
+        match retired
+            .iter()
+            .rev()
+            .find(|(_, k)| k.as_bytes() == ident.group_key.as_bytes())
+            .map(|(e, _)| *e)
+        {
+            Some(stored_epoch) => {
+                tracing::warn!(
+                    "group key rotation to epoch {epoch} did not finish; \
+                     rolling back to epoch {stored_epoch}"
+                );
+                retired.retain(|e, _| *e < stored_epoch);
+                epoch = stored_epoch;
+            }
+            None => tracing::warn!(
+                "group key does not match the epoch {epoch} fingerprint; keeping it as epoch {epoch}"
+            ),
+        }
+    }
+    Ok(GroupKeyring {
+        group_id: ident.group_id,
+        epoch,
+        current: ident.group_key,
+        retired,
+    })
+}
+
+/// Persist `keyring`: epoch file first (atomic rename), then the current
+/// key into the active key store via [`keys::adopt`].
+pub async fn store_keyring(mosaic_root: &Path, keyring: &GroupKeyring) -> SyncResult<()> {
+    let file = KeyringFile {
+        version: KEYRING_FILE_VERSION,
+        group_id: keyring.group_id,
+        epoch: keyring.epoch,
+        current_fingerprint: key_fingerprint(&keyring.current),
+        retired: keyring
+            .retired
+            .iter()
+            .map(|(e, k)| (*e, *k.as_bytes()))
+            .collect(),
+    };
+    let bytes = postcard::to_allocvec(&file)?;
+    let path = keyring_path(mosaic_root);
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| SyncError::Other(format!("create .tesela dir: {e}")))?;
+    }
+    let tmp = path.with_extension("bin.tmp");
+    tokio::fs::write(&tmp, &bytes)
+        .await
+        .map_err(|e| SyncError::Other(format!("write group key epochs: {e}")))?;
+    tokio::fs::rename(&tmp, &path)
+        .await
+        .map_err(|e| SyncError::Other(format!("rename group key epochs: {e}")))?;
+    keys::adopt(mosaic_root, &keyring.identity()).await
+}
+
+// ── Records ───────────────────────────────────────────────────────
+
+/// Group-control payload carried as envelope plaintext, prefixed with
+/// [`GROUP_RECORD_MAGIC`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GroupRecord {
+    /// A new key epoch.
+    Rotation(RotationRecord),
+    /// A device publishing its key-agreement key.
+    Member(MemberAnnouncement),
+}
+
+/// Encode a group record as envelope plaintext.
+pub fn encode_group_record(record: &GroupRecord) -> SyncResult<Vec<u8>> {
+    let body = postcard::to_allocvec(record)?;
+    let mut out = Vec::with_capacity(GROUP_RECORD_MAGIC.len() + body.len());
+    out.extend_from_slice(&GROUP_RECORD_MAGIC);
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+/// Decode envelope plaintext produced by [`encode_group_record`].
+/// `Ok(None)` when the bytes are some other payload (no magic).
+pub fn decode_group_record(bytes: &[u8]) -> SyncResult<Option<GroupRecord>> {
+    match bytes.strip_prefix(&GROUP_RECORD_MAGIC[..]) {
+        Some(body) => Ok(Some(postcard::from_bytes(body)?)),
+        None => Ok(None),
+    }
+}
+
+/// A device announcing the X25519 key it wants epoch grants wrapped to.
+/// Rides an envelope, so the sealing AEAD already binds it to the
+/// depositing device; receivers additionally require
+/// `device == envelope.from_device`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemberAnnouncement {
+    /// Group the device is announcing into.
+    pub group_id: GroupId,
+    /// The announcing device.
+    pub device: DeviceId,
+    /// Its [`DeviceKx::public_key`].
+    pub kx_pubkey: [u8; 32],
+    /// Optional user-visible name.
+    pub display_name: Option<String>,
+}
+
+impl MemberAnnouncement {
+    /// The `group_members` row this announcement describes.
+    pub fn to_member(&self, added_at_millis: i64) -> GroupMember {
+        GroupMember {
+            group_id: self.group_id,
+            device_id: self.device,
+            ed25519_pubkey: Vec::new(),
+            display_name: self.display_name.clone(),
+            added_at_millis,
+            kx_pubkey: self.kx_pubkey.to_vec(),
+        }
+    }
+}
+
+/// Authenticated announcement of a new key epoch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationRecord {
+    /// Group being rotated.
+    pub group_id: GroupId,
+    /// The new epoch.
+    pub epoch: u32,
+    /// Device that minted the key; grants are keyed to its X25519 key.
+    pub issuer: DeviceId,
+    /// Wall-clock millis at issue. Informational, but AEAD-bound.
+    pub issued_at_millis: i64,
+    /// Devices excluded from this epoch. Receivers drop them from
+    /// `group_members`.
+    pub revoked: Vec<DeviceId>,
+    /// One sealed copy of the new key per remaining member.
+    pub grants: Vec<EpochKeyGrant>,
+}
+
+/// The new epoch key sealed to one member.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpochKeyGrant {
+    /// Recipient device.
+    pub device: DeviceId,
+    /// XChaCha20-Poly1305 nonce.
+    pub nonce: [u8; 24],
+    /// Sealed 32-byte key + tag.
+    pub sealed_key: Vec<u8>,
+}
+
+impl RotationRecord {
+    /// AAD for `device`'s grant: the whole header, every grantee and the
+    /// recipient, hashed. Any edit to the record invalidates all grants.
+    fn grant_aad(&self, device: DeviceId) -> [u8; 32] {
+        let mut h = Sha256::new();
+        h.update(EPOCH_GRANT_INFO_V1);
+        h.update(self.group_id.as_bytes());
+        h.update(self.epoch.to_be_bytes());
+        h.update(self.issuer.as_bytes());
+        h.update(self.issued_at_millis.to_be_bytes());
+        h.update((self.revoked.len() as u64).to_be_bytes());
+        for d in &self.revoked {
+            h.update(d.as_bytes());
+        }
+        h.update((self.grants.len() as u64).to_be_bytes());
+        for g in &self.grants {
+            h.update(g.device.as_bytes());
+        }
+        h.update(device.as_bytes());
+        h.finalize().into()
+    }
+}
+
+/// A freshly minted epoch: the record to distribute and the key to
+/// advance the issuer's own keyring with once it is out.
+#[derive(Debug)]
+pub struct KeyRotation {
+    /// Record to deposit (sealed under the outgoing epoch).
+    pub record: RotationRecord,
+    /// The new epoch's key.
+    pub key: GroupKey,
+}
+
+/// Mint the next epoch for `keyring`'s group, granting it to every entry
+/// of `members` except the issuer and `revoke`. Every grantee must have
+/// announced a key-agreement key — a silent skip would strand a
+/// legitimate device on the old epoch.
+pub fn rotate(
+    keyring: &GroupKeyring,
+    issuer: DeviceId,
+    kx: &DeviceKx,
+    members: &[GroupMember],
+    revoke: &[DeviceId],
+    issued_at_millis: i64,
+) -> SyncResult<KeyRotation> {
+    let epoch = keyring
+        .epoch
+        .checked_add(1)
+        .ok_or_else(|| SyncError::Protocol("key epoch counter exhausted".into()))?;
+    let mut grantees: Vec<(DeviceId, [u8; 32])> = Vec::new();
+    for m in members {
+        if m.group_id != keyring.group_id
+            || m.device_id == issuer
+            || revoke.contains(&m.device_id)
+            || grantees.iter().any(|(d, _)| *d == m.device_id)
+        {
+            continue;
+        }
+        let pk: [u8; 32] = m.kx_pubkey.as_slice().try_into().map_err(|_| {
+            SyncError::Crypto(format!(
+                "member {} has not announced a key-agreement key; revoke it or let it \
+                 come online before rotating",
+                m.device_id
+            ))
+        })?;
+        grantees.push((m.device_id, pk));
+    }
+    let key = GroupKey::random();
+    let mut revoked: Vec<DeviceId> = revoke.to_vec();
+    revoked.sort_by_key(|d| *d.as_bytes());
+    revoked.dedup();
+    let mut record = RotationRecord {
+        group_id: keyring.group_id,
+        epoch,
+        issuer,
+        issued_at_millis,
+        revoked,
+        grants: grantees
+            .iter()
+            .map(|(device, _)| EpochKeyGrant {
+                device: *device,
+                nonce: [0u8; 24],
+                sealed_key: Vec::new(),
+            })
+            .collect(),
+    };
+    for (i, (device, pk)) in grantees.iter().enumerate() {
+        let wrap = kx.grant_key(pk, &keyring.group_id, epoch)?;
+        let sealed = aead::seal(&wrap, key.as_bytes(), &record.grant_aad(*device))?;
+        record.grants[i].nonce = sealed.nonce;
+        record.grants[i].sealed_key = sealed.ciphertext;
+    }
+    Ok(KeyRotation { record, key })
+}
+
+/// What [`accept_rotation`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RotationOutcome {
+    /// The keyring moved to `epoch`; `revoked` should leave
+    /// `group_members`.
+    Advanced {
+        /// The epoch now current.
+        epoch: u32,
+        /// Devices the record excluded.
+        revoked: Vec<DeviceId>,
+    },
+    /// The record is not newer than the keyring (replay, own echo, or an
+    /// already-applied rotation). Nothing changed.
+    Stale,
+    /// This device is excluded from the new epoch. Nothing changed; it can
+    /// still read history under the epochs it holds.
+    Revoked,
+    /// The record neither revokes nor grants this device — it wasn't a
+    /// known member when the issuer rotated (paired later, or never
+    /// announced). Nothing changed; a later rotation or re-pair fixes it.
+    NotGranted,
+}
+
+/// Verify a rotation record against the local member list and, if it
+/// grants this device the next epoch, advance `keyring`.
+///
+/// The issuer must be a known member with an announced key-agreement
+/// key: opening the grant with `X25519(me, issuer)` is what proves the
+/// issuer minted it. A record from an unknown device, or one whose header
+/// was altered in transit, fails with [`SyncError::Crypto`].
+pub fn accept_rotation(
+    keyring: &mut GroupKeyring,
+    record: &RotationRecord,
+    me: DeviceId,
+    kx: &DeviceKx,
+    members: &[GroupMember],
+) -> SyncResult<RotationOutcome> {
+    if record.group_id != keyring.group_id {
+        return Err(SyncError::Protocol(
+            "rotation record is for another group".into(),
+        ));
+    }
+    if record.epoch <= keyring.epoch || record.issuer == me {
+        return Ok(RotationOutcome::Stale);
+    }
+    if record.revoked.contains(&me) {
+        return Ok(RotationOutcome::Revoked);
+    }
+    let issuer_pk: [u8; 32] = members
+        .iter()
+        .find(|m| m.group_id == record.group_id && m.device_id == record.issuer)
+        .and_then(|m| m.kx_pubkey.as_slice().try_into().ok())
+        .ok_or_else(|| {
+            SyncError::Crypto(format!(
+                "rotation issued by {} which is not a known group member",
+                record.issuer
+            ))
+        })?;
+    let Some(grant) = record.grants.iter().find(|g| g.device == me) else {
+        return Ok(RotationOutcome::NotGranted);
+    };
+    let wrap = kx.grant_key(&issuer_pk, &record.group_id, record.epoch)?;
+    let key_bytes = aead::open(
+        &wrap,
+        &grant.nonce,
+        &grant.sealed_key,
+        &record.grant_aad(me),
+    )?;
+    let key: [u8; 32] = key_bytes
+        .try_into()
+        .map_err(|_| SyncError::Crypto("granted epoch key has wrong length".into()))?;
+    keyring.advance(record.epoch, GroupKey::from_bytes(key))?;
+    Ok(RotationOutcome::Advanced {
+        epoch: record.epoch,
+        revoked: record.revoked.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ident() -> GroupIdentity {
+        GroupIdentity {
+            group_id: GroupId::from_bytes([0x42; 16]),
+            group_key: GroupKey::from_bytes([0x07; 32]),
+        }
+    }
+
+    fn member(group: GroupId, device: DeviceId, kx: &DeviceKx) -> GroupMember {
+        MemberAnnouncement {
+            group_id: group,
+            device,
+            kx_pubkey: kx.public_key(),
+            display_name: None,
+        }
+        .to_member(0)
+    }
+
+    struct Fixture {
+        devices: Vec<(DeviceId, DeviceKx)>,
+        members: Vec<GroupMember>,
+    }
+
+    fn fixture(n: u8) -> Fixture {
+        let group = ident().group_id;
+        let devices: Vec<(DeviceId, DeviceKx)> = (1..=n)
+            .map(|i| (DeviceId::from_bytes([i; 16]), DeviceKx::random()))
+            .collect();
+        let members = devices
+            .iter()
+            .map(|(d, kx)| member(group, *d, kx))
+            .collect();
+        Fixture { devices, members }
+    }
+
+    #[test]
+    fn remaining_members_advance_and_the_revoked_device_does_not() {
+        let f = fixture(3);
+        let (issuer, issuer_kx) = &f.devices[0];
+        let (keeper, keeper_kx) = &f.devices[1];
+        let (lost, lost_kx) = &f.devices[2];
+        let base = GroupKeyring::genesis(&ident());
+
+        let rotation = rotate(&base, *issuer, issuer_kx, &f.members, &[*lost], 1).unwrap();
+        assert_eq!(rotation.record.epoch, 1);
+        assert_eq!(
+            rotation.record.grants.len(),
+            1,
+            "only the keeper is granted"
+        );
+
+        let mut kept = base.clone();
+        let outcome =
+            accept_rotation(&mut kept, &rotation.record, *keeper, keeper_kx, &f.members).unwrap();
+        assert_eq!(
+            outcome,
+            RotationOutcome::Advanced {
+                epoch: 1,
+                revoked: vec![*lost]
+            }
+        );
+        assert_eq!(kept.current_key().as_bytes(), rotation.key.as_bytes());
+        assert_eq!(
+            kept.key_for(0).unwrap().as_bytes(),
+            ident().group_key.as_bytes(),
+            "epoch 0 stays readable"
+        );
+
+        let mut lost_ring = base.clone();
+        let outcome =
+            accept_rotation(&mut lost_ring, &rotation.record, *lost, lost_kx, &f.members).unwrap();
+        assert_eq!(outcome, RotationOutcome::Revoked);
+        assert_eq!(lost_ring.epoch(), 0);
+
+        // Replaying the same record is a no-op.
+        assert_eq!(
+            accept_rotation(&mut kept, &rotation.record, *keeper, keeper_kx, &f.members).unwrap(),
+            RotationOutcome::Stale
+        );
+    }
+
+    #[test]
+    fn tampering_with_the_record_breaks_the_grant() {
+        let f = fixture(3);
+        let (issuer, issuer_kx) = &f.devices[0];
+        let (keeper, keeper_kx) = &f.devices[1];
+        let lost = f.devices[2].0;
+        let base = GroupKeyring::genesis(&ident());
+        let mut record = rotate(&base, *issuer, issuer_kx, &f.members, &[lost], 1)
+            .unwrap()
+            .record;
+        // A relay (or the revoked device) quietly un-revoking itself.
+        record.revoked.clear();
+        let mut ring = base.clone();
+        let err = accept_rotation(&mut ring, &record, *keeper, keeper_kx, &f.members).unwrap_err();
+        assert!(matches!(err, SyncError::Crypto(_)), "{err}");
+        assert_eq!(ring.epoch(), 0);
+    }
+
+    #[test]
+    fn rotation_from_an_unannounced_device_is_rejected() {
+        let f = fixture(2);
+        let (keeper, keeper_kx) = &f.devices[1];
+        let outsider = DeviceId::from_bytes([0xee; 16]);
+        let outsider_kx = DeviceKx::random();
+        // The outsider holds the group key and knows our announced keys,
+        // but isn't in our member list.
+        let base = GroupKeyring::genesis(&ident());
+        let record = rotate(&base, outsider, &outsider_kx, &f.members, &[], 1)
+            .unwrap()
+            .record;
+        let mut ring = base.clone();
+        let err = accept_rotation(&mut ring, &record, *keeper, keeper_kx, &f.members).unwrap_err();
+        assert!(
+            err.to_string().contains("not a known group member"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn rotating_requires_every_grantee_to_have_announced() {
+        let mut f = fixture(2);
+        f.members[1].kx_pubkey.clear();
+        let (issuer, issuer_kx) = &f.devices[0];
+        let base = GroupKeyring::genesis(&ident());
+        assert!(rotate(&base, *issuer, issuer_kx, &f.members, &[], 1).is_err());
+        let lost = f.devices[1].0;
+        assert!(rotate(&base, *issuer, issuer_kx, &f.members, &[lost], 1).is_ok());
+    }
+
+    #[test]
+    fn group_records_round_trip() {
+        let record = GroupRecord::Member(MemberAnnouncement {
+            group_id: ident().group_id,
+            device: DeviceId::from_bytes([1; 16]),
+            kx_pubkey: [9; 32],
+            display_name: Some("phone".into()),
+        });
+        let bytes = encode_group_record(&record).unwrap();
+        assert_eq!(decode_group_record(&bytes).unwrap(), Some(record));
+        assert_eq!(decode_group_record(b"TLR2....").unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn keyring_persists_and_rolls_back_an_unfinished_rotation() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let mut ring = load_keyring(root).await.unwrap();
+        assert_eq!(ring.epoch(), 0);
+        let genesis_key = ring.current_key().clone();
+
+        ring.advance(1, GroupKey::from_bytes([0x11; 32])).unwrap();
+        store_keyring(root, &ring).await.unwrap();
+        let back = load_keyring(root).await.unwrap();
+        assert_eq!(back.epoch(), 1);
+        assert_eq!(back.current_key().as_bytes(), &[0x11; 32]);
+        assert_eq!(back.key_for(0).unwrap().as_bytes(), genesis_key.as_bytes());
+
+        // Crash after the epoch file landed but before the key swap: the
+        // key store still holds epoch 1's key.
+        let mut torn = back.clone();
+        torn.advance(2, GroupKey::from_bytes([0x22; 32])).unwrap();
+        let file = KeyringFile {
+            version: KEYRING_FILE_VERSION,
+            group_id: torn.group_id(),
+            epoch: 2,
+            current_fingerprint: key_fingerprint(torn.current_key()),
+            retired: torn.retired().map(|(e, k)| (e, *k.as_bytes())).collect(),
+        };
+        tokio::fs::write(keyring_path(root), postcard::to_allocvec(&file).unwrap())
+            .await
+            .unwrap();
+        let rolled = load_keyring(root).await.unwrap();
+        assert_eq!(rolled.epoch(), 1);
+        assert_eq!(rolled.current_key().as_bytes(), &[0x11; 32]);
+        assert!(rolled.key_for(0).is_some());
+    }
+}
@@ -0,0 +1,140 @@
+//! Share-code format for shared sub-spaces.
+//!
+//! A shared space is a second group identity — its own id + symmetric key
+//! and its own relay registration — scoped to a handful of note docs that
+//! two otherwise separate mosaics both sync. The share code is how the
+//! partner joins: like a [`PairingCode`](super::pairing::PairingCode) it
+//! carries the group id + key, but it never carries the mosaic's own
+//! group, and a relay URL is mandatory (shared spaces only travel over a
+//! relay).
+//!
+//! The string form is `tesela-share:` + base64url(postcard), so pasting a
+//! share code into the device-pairing box (or vice versa) fails loudly
+//! instead of adopting the wrong group.
+//!
+//! Same threat model as the pairing code: the code is the secret.
+
+use crate::crypto::keys::{GroupIdentity, GroupKey};
+use crate::error::{SyncError, SyncResult};
+use crate::group::GroupId;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+
+/// Prefix of an encoded [`ShareCode`].
+pub const SHARE_CODE_PREFIX: &str = "tesela-share:";
+
+/// Current share-code format version.
+pub const SHARE_CODE_VERSION: u8 = 1;
+
+/// Wire-form share code. Encodes to a string via [`encode`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ShareCode {
+    /// The shared space's group id. The joiner registers under it.
+    pub share_id: GroupId,
+    /// The shared space's symmetric key.
+    pub share_key_bytes: [u8; 32],
+    /// Human-readable name of the space ("Groceries").
+    pub name: String,
+    /// Relay both mosaics exchange the space's ops through.
+    pub relay_url: String,
+    /// Display name of the inviting device, for the join prompt.
+    pub display_name: String,
+    /// Protocol version. Bumps when the payload schema changes.
+    pub version: u8,
+}
+
+impl ShareCode {
+    /// Group identity of the shared space.
+    pub fn identity(&self) -> GroupIdentity {
+        GroupIdentity {
+            group_id: self.share_id,
+            group_key: GroupKey::from_bytes(self.share_key_bytes),
+        }
+    }
+}
+
+/// Encode a share code as `tesela-share:<base64url-no-pad>`.
+pub fn encode(code: &ShareCode) -> SyncResult<String> {
+    let bytes = postcard::to_allocvec(code)?;
+    Ok(format!(
+        "{SHARE_CODE_PREFIX}{}",
+        URL_SAFE_NO_PAD.encode(&bytes)
+    ))
+}
+
+/// Decode a share code. Tolerates surrounding whitespace; rejects strings
+/// without the prefix and versions newer than [`SHARE_CODE_VERSION`].
+pub fn decode(s: &str) -> SyncResult<ShareCode> {
+    let Some(body) = s.trim().strip_prefix(SHARE_CODE_PREFIX) else {
+        return Err(SyncError::Other(format!(
+            "not a share code (expected the `{SHARE_CODE_PREFIX}` prefix)"
+        )));
+    };
+    let bytes = URL_SAFE_NO_PAD
+        .decode(body)
+        .map_err(|e| SyncError::Other(format!("share code base64 decode: {e}")))?;
+    let code: ShareCode = postcard::from_bytes(&bytes)?;
+    if code.version > SHARE_CODE_VERSION {
+        return Err(SyncError::Other(format!(
+            "share code version {} is newer than local v{}",
+            code.version, SHARE_CODE_VERSION
+        )));
+    }
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_code() -> ShareCode {
+        ShareCode {
+            share_id: GroupId::from_bytes([0x5a; 16]),
+            share_key_bytes: [0x42; 32],
+            name: "Groceries".into(),
+            relay_url: "https://relay.example.com".into(),
+            display_name: "Tay's Laptop".into(),
+            version: SHARE_CODE_VERSION,
+        }
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let code = fixture_code();
+        let s = encode(&code).unwrap();
+        assert!(s.starts_with(SHARE_CODE_PREFIX));
+        assert_eq!(decode(&format!(" {s}\n")).unwrap(), code);
+        assert_eq!(
+            code.identity().group_key.as_bytes(),
+            &code.share_key_bytes,
+            "identity carries the space's own key"
+        );
+    }
+
+    #[test]
+    fn decode_rejects_a_device_pairing_code() {
+        let pairing = crate::crypto::pairing::encode(&crate::crypto::pairing::PairingCode {
+            group_id: GroupId::from_bytes([0xa1; 16]),
+            group_key_bytes: [0x55; 32],
+            device_id: crate::device::DeviceId::from_bytes([0xc3; 16]),
+            url: "http://192.168.1.10:7474".into(),
+            display_name: "Laptop".into(),
+            relay_url: None,
+            version: crate::crypto::pairing::PAIRING_CODE_VERSION,
+        })
+        .unwrap();
+        assert!(decode(&pairing)
+            .unwrap_err()
+            .to_string()
+            .contains("not a share code"));
+    }
+
+    #[test]
+    fn decode_rejects_future_version() {
+        let mut code = fixture_code();
+        code.version = SHARE_CODE_VERSION + 1;
+        let err = decode(&encode(&code).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("newer than local"));
+    }
+}
@@ -24,6 +24,7 @@
 //! the chatty form is correct and easier to reason about.
 
 use crate::oplog::op::OpPayload;
+use serde::Serialize;
 use std::collections::HashMap;
 use tesela_core::note_tree::{FlatBlock, NoteTree};
 use uuid::Uuid;
@@ -128,6 +129,132 @@ pub fn diff_note_trees_with_options(
     ops
 }
 
+/// How one block changed between two versions of a note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockChangeKind {
+    /// Only in the newer version.
+    Added,
+    /// Only in the older version.
+    Removed,
+    /// In both, with different text (position may also differ).
+    Edited,
+    /// In both with the same text, under a different parent or out of
+    /// order relative to its surviving siblings.
+    Moved,
+}
+
+/// One entry of [`block_changes`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlockChange {
+    /// The block's stable id (its `bid`).
+    pub block_id: Uuid,
+    /// What happened to it.
+    pub kind: BlockChangeKind,
+    /// Text in the older version (`None` when added).
+    pub old_text: Option<String>,
+    /// Text in the newer version (`None` when removed).
+    pub new_text: Option<String>,
+    /// Parent in the older version.
+    pub old_parent: Option<Uuid>,
+    /// Parent in the newer version.
+    pub new_parent: Option<Uuid>,
+}
+
+/// Human-facing structural diff of two versions of one note, keyed by
+/// block id. Built on [`diff_note_trees`], with one refinement: the op
+/// diff re-keys every later sibling when a block is inserted or removed,
+/// so a `BlockMove` only counts as [`BlockChangeKind::Moved`] when the
+/// block changed parent or its order relative to the siblings it shares
+/// with the old version changed. Entries follow the new version's
+/// document order; removed blocks come last, in the old version's order.
+pub fn block_changes(old: &NoteTree, new: &NoteTree) -> Vec<BlockChange> {
+    let old_by_id: HashMap<Uuid, &FlatBlock> = old.blocks.iter().map(|b| (b.id, b)).collect();
+    let new_by_id: HashMap<Uuid, &FlatBlock> = new.blocks.iter().map(|b| (b.id, b)).collect();
+
+    let mut kinds: HashMap<Uuid, BlockChangeKind> = HashMap::new();
+    for op in diff_note_trees([0; 16], old, new) {
+        let (id, kind) = match op {
+            OpPayload::BlockDelete { block_id } => {
+                (Uuid::from_bytes(block_id), BlockChangeKind::Removed)
+            }
+            OpPayload::BlockUpsert { block_id, .. } => {
+                let id = Uuid::from_bytes(block_id);
+                if old_by_id.contains_key(&id) {
+                    (id, BlockChangeKind::Edited)
+                } else {
+                    (id, BlockChangeKind::Added)
+                }
+            }
+            OpPayload::BlockMove { block_id, .. } => {
+                let id = Uuid::from_bytes(block_id);
+                if !really_moved(id, old, new, &old_by_id, &new_by_id) {
+                    continue;
+                }
+                (id, BlockChangeKind::Moved)
+            }
+            _ => continue,
+        };
+        kinds.insert(id, kind);
+    }
+
+    let change = |id: Uuid, kind: BlockChangeKind| {
+        let before = old_by_id.get(&id);
+        let after = new_by_id.get(&id);
+        BlockChange {
+            block_id: id,
+            kind,
+            old_text: before.map(|b| b.text.clone()),
+            new_text: after.map(|b| b.text.clone()),
+            old_parent: before.and_then(|b| b.parent),
+            new_parent: after.and_then(|b| b.parent),
+        }
+    };
+    let mut out: Vec<BlockChange> = new
+        .blocks
+        .iter()
+        .filter_map(|b| kinds.get(&b.id).map(|kind| change(b.id, *kind)))
+        .collect();
+    out.extend(
+        old.blocks
+            .iter()
+            .filter(|b| kinds.get(&b.id) == Some(&BlockChangeKind::Removed))
+            .map(|b| change(b.id, BlockChangeKind::Removed)),
+    );
+    out
+}
+
+/// Whether a same-text block really moved: new parent, or a different
+/// position among the siblings that sit under that parent in both trees.
+fn really_moved(
+    id: Uuid,
+    old: &NoteTree,
+    new: &NoteTree,
+    old_by_id: &HashMap<Uuid, &FlatBlock>,
+    new_by_id: &HashMap<Uuid, &FlatBlock>,
+) -> bool {
+    let (Some(before), Some(after)) = (old_by_id.get(&id), new_by_id.get(&id)) else {
+        return false;
+    };
+    if before.parent != after.parent {
+        return true;
+    }
+    let parent = after.parent;
+    let shared = |tree: &NoteTree| -> Vec<Uuid> {
+        tree.blocks
+            .iter()
+            .filter(|b| b.parent == parent)
+            .filter(|b| {
+                old_by_id.get(&b.id).is_some_and(|o| o.parent == parent)
+                    && new_by_id.get(&b.id).is_some_and(|n| n.parent == parent)
+            })
+            .map(|b| b.id)
+            .collect()
+    };
+    let position = |siblings: Vec<Uuid>| siblings.iter().position(|s| *s == id);
+    position(shared(old)) != position(shared(new))
+}
+
 fn make_block_upsert(
     note_id: [u8; 16],
     block_id: Uuid,
@@ -491,4 +618,44 @@ mod tests {
         assert!(saw_b_move);
         assert!(saw_c_move);
     }
+
+    #[test]
+    fn block_changes_ignore_sibling_rekeys_from_inserts() {
+        let old = parse("- A\n- B\n- C\n- D\n");
+        let [a, b, c, d] = [0, 1, 2, 3].map(|i| old.blocks[i].clone());
+        let x = FlatBlock {
+            id: Uuid::now_v7(),
+            parent: None,
+            indent: 0,
+            text: "X".into(),
+            properties: vec![],
+        };
+        // X inserted at the top, B edited, C removed, D nested under A.
+        let mut b2 = b.clone();
+        b2.text = "B!".into();
+        let mut d2 = d.clone();
+        d2.parent = Some(a.id);
+        d2.indent = 1;
+        let new = NoteTree {
+            blocks: vec![x.clone(), a.clone(), d2, b2],
+            ..old.clone()
+        };
+
+        let changes = block_changes(&old, &new);
+        let summary: Vec<(Uuid, BlockChangeKind)> =
+            changes.iter().map(|c| (c.block_id, c.kind)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (x.id, BlockChangeKind::Added),
+                (d.id, BlockChangeKind::Moved),
+                (b.id, BlockChangeKind::Edited),
+                (c.id, BlockChangeKind::Removed),
+            ],
+            "A only shifted behind X, so it is not reported"
+        );
+        assert_eq!(changes[1].new_parent, Some(a.id));
+        assert_eq!(changes[2].old_text.as_deref(), Some("B"));
+        assert_eq!(changes[3].new_text, None);
+    }
 }
@@ -13,7 +13,7 @@ use crate::device::DeviceId;
 use crate::error::{SyncError, SyncResult};
 use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
 use std::collections::HashMap;
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
@@ -26,6 +26,9 @@ pub const TXT_DEVICE_ID: &str = "did";
 pub const TXT_DISPLAY_NAME: &str = "name";
 /// TXT property key carrying the wire-protocol major version.
 pub const TXT_API_VERSION: &str = "v";
+/// TXT property key carrying the `transport::lan` listener port. Absent
+/// when the device doesn't accept direct LAN sessions.
+pub const TXT_LAN_PORT: &str = "lan";
 
 /// A peer surfaced by mDNS browse. Not necessarily paired or trusted.
 #[derive(Debug, Clone)]
@@ -38,6 +41,8 @@ pub struct DiscoveredPeer {
     pub host: IpAddr,
     /// HTTP API port from the SRV record.
     pub port: u16,
+    /// `transport::lan` listener port from the TXT record, if advertised.
+    pub lan_port: Option<u16>,
     /// Last time we received an mDNS update for this peer.
     pub last_seen: Instant,
 }
@@ -50,6 +55,11 @@ impl DiscoveredPeer {
             IpAddr::V6(addr) => format!("http://[{}]:{}", addr, self.port),
         }
     }
+
+    /// Address of this peer's LAN transport listener, if it advertises one.
+    pub fn lan_addr(&self) -> Option<SocketAddr> {
+        self.lan_port.map(|port| SocketAddr::new(self.host, port))
+    }
 }
 
 /// Live mDNS state. Owning instance keeps the daemon alive; dropping
@@ -66,6 +76,17 @@ pub struct LanDiscovery {
 impl LanDiscovery {
     /// Advertise this server and start browsing for siblings.
     pub fn start(device_id: DeviceId, display_name: &str, port: u16) -> SyncResult<Self> {
+        Self::start_with_lan_port(device_id, display_name, port, None)
+    }
+
+    /// [`start`](Self::start), also advertising the `transport::lan`
+    /// listener port so peers can dial direct sessions.
+    pub fn start_with_lan_port(
+        device_id: DeviceId,
+        display_name: &str,
+        port: u16,
+        lan_port: Option<u16>,
+    ) -> SyncResult<Self> {
         let daemon = ServiceDaemon::new().map_err(map_mdns_err)?;
 
         let device_hex = device_id.to_hex();
@@ -74,11 +95,14 @@ impl LanDiscovery {
         // before name probing finishes.
         let instance_name = device_hex.clone();
         let host_name = format!("{device_hex}.local.");
-        let properties: Vec<(String, String)> = vec![
+        let mut properties: Vec<(String, String)> = vec![
             (TXT_DEVICE_ID.to_string(), device_hex.clone()),
             (TXT_DISPLAY_NAME.to_string(), display_name.to_string()),
             (TXT_API_VERSION.to_string(), "1".to_string()),
         ];
+        if let Some(lan_port) = lan_port {
+            properties.push((TXT_LAN_PORT.to_string(), lan_port.to_string()));
+        }
 
         // Empty address set + enable_addr_auto: let the library fill in the
         // host's reachable interfaces and update them when the network
@@ -185,6 +209,9 @@ fn handle_event(
                 .unwrap_or("Tesela device")
                 .to_string();
             let port = info.get_port();
+            let lan_port = info
+                .get_property_val_str(TXT_LAN_PORT)
+                .and_then(|p| p.parse().ok());
             let Some(host) = pick_host(&info) else {
                 tracing::debug!(
                     fullname = info.get_fullname(),
@@ -197,6 +224,7 @@ fn handle_event(
                 display_name,
                 host,
                 port,
+                lan_port,
                 last_seen: Instant::now(),
             };
             tracing::info!(
@@ -301,8 +329,10 @@ mod tests {
             display_name: "Test".into(),
             host: "192.168.1.10".parse().unwrap(),
             port: 7474,
+            lan_port: Some(7475),
             last_seen: Instant::now(),
         };
         assert_eq!(p.http_url(), "http://192.168.1.10:7474");
+        assert_eq!(p.lan_addr(), Some("192.168.1.10:7475".parse().unwrap()));
     }
 }
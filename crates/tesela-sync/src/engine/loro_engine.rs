@@ -31,9 +31,10 @@
 
 use crate::device::DeviceId;
 use crate::engine::{
-    cursor::PeerCursor, BlockRelocationOutcome, BlockRelocationRequest, BlockRelocationStatus,
-    ExportedDocUpdate, LocalCursor, MovePlacement, PendingImport, RelayApplyReport,
-    RelocatedNoteVersion, SyncEngine, CATCHUP_BACKOFF_SHIFT_CAP, MAX_CATCHUP_ATTEMPTS,
+    cursor::PeerCursor, BlockBlame, BlockRelocationOutcome, BlockRelocationRequest,
+    BlockRelocationStatus, ExportedDocUpdate, LocalCursor, MovePlacement, NoteAtTime,
+    PendingImport, RelayApplyReport, RelocatedNoteVersion, SyncAnomaly, SyncEngine,
+    CATCHUP_BACKOFF_SHIFT_CAP, MAX_CATCHUP_ATTEMPTS,
 };
 use crate::error::{SyncError, SyncResult};
 use crate::hlc::Hlc;
@@ -58,6 +59,10 @@ static SNAPSHOT_TMP_SEQ: AtomicU64 = AtomicU64::new(0);
 
 const IMPORT_BATCH_CONCURRENCY: usize = 64;
 
+/// Seconds within which a device's consecutive local commits merge into one
+/// Loro change (see `set_doc_peer`).
+const CHANGE_MERGE_INTERVAL_SECS: i64 = 60;
+
 /// Build a unique temp path next to `path` for atomic write+rename.
 fn unique_tmp(path: &Path) -> PathBuf {
     let n = SNAPSHOT_TMP_SEQ.fetch_add(1, Ordering::Relaxed);
@@ -163,8 +168,24 @@ pub const VIEWS_DOC_ID: [u8; 16] = *b"tesela.views.reg";
 /// paths as note documents, but is excluded from every note-shaped projection.
 pub const PAGE_DIRECTORY_DOC_ID: [u8; 16] = *b"tesela.page.dir!";
 
+/// Well-known doc id of the synced reminder delivery-state doc: the 16 ASCII
+/// bytes `tesela.notif.reg`. Same special-doc treatment as the views
+/// registry — rides the relay, never materializes.
+pub const NOTIFICATIONS_DOC_ID: [u8; 16] = *b"tesela.notif.reg";
+
+/// Well-known doc id of the synced selective-sync registry: the 16 ASCII
+/// bytes `tesela.scope.reg`. Same special-doc treatment as the views
+/// registry — rides the relay to every device (it must, since it decides
+/// what each device holds), never materializes.
+pub const SYNC_SCOPES_DOC_ID: [u8; 16] = *b"tesela.scope.reg";
+
 /// Reserved documents that sync as ordinary streams but are not notes.
-pub const SPECIAL_DOC_IDS: [[u8; 16]; 2] = [VIEWS_DOC_ID, PAGE_DIRECTORY_DOC_ID];
+pub const SPECIAL_DOC_IDS: [[u8; 16]; 4] = [
+    VIEWS_DOC_ID,
+    PAGE_DIRECTORY_DOC_ID,
+    NOTIFICATIONS_DOC_ID,
+    SYNC_SCOPES_DOC_ID,
+];
 
 /// Whether an id addresses a synced registry rather than a user note.
 pub fn is_special_doc(note_id: &[u8; 16]) -> bool {
@@ -198,6 +219,22 @@ const VIEWS_SCHEMA_VERSION: i64 = 1;
 /// container that may carry user edits wins).
 const BUILTIN_VIEWS_SEED_PEER: u64 = 0;
 
+/// The Loro PeerID a device authors under: the first 8 bytes of its
+/// DeviceId, top bit cleared to stay in Loro's valid range, with a masked 0
+/// mapped to 1 (peer 0 is [`BUILTIN_VIEWS_SEED_PEER`]). Public so callers
+/// can map a [`BlockBlame`](crate::engine::BlockBlame)'s peer back to a
+/// known device.
+pub fn loro_peer_id(device: &DeviceId) -> u64 {
+    let b = device.as_bytes();
+    let raw = u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]);
+    let masked = raw & 0x7FFF_FFFF_FFFF_FFFF;
+    if masked == 0 {
+        1
+    } else {
+        masked
+    }
+}
+
 /// Build a deterministic seed for one immutable page-to-legacy-document
 /// binding. Engine peers clear the high bit, so this high-bit namespace is
 /// reserved for seeds and cannot collide with an ordinary device author.
@@ -442,6 +479,13 @@ struct Inner {
     /// content-less/empty frame. A rising count is the live signature of the
     /// deposit-strand class; surfaced for the server/FFI tick to log.
     outbound_strand_alarms: AtomicU64,
+    /// This replica's selective-sync state: the scope it last reconciled to
+    /// and the notes that scope keeps off this device. Persisted to
+    /// `<snapshot_dir>/_scope.bin` so an excluded note stays excluded (and
+    /// its stale `.md` is never re-hydrated) across restarts.
+    sync_scope: RwLock<scope::ScopeState>,
+    /// Evictions not yet drained by `take_scope_evictions`.
+    scope_evictions: tokio::sync::Mutex<Vec<crate::engine::ScopeEviction>>,
 }
 
 /// Resolve the migrate-on-apply (P1.6) flag from the environment ONCE — mirrors
@@ -485,6 +529,8 @@ impl LoroEngine {
                 pending_imports: RwLock::new(HashMap::new()),
                 import_pass: AtomicU64::new(0),
                 outbound_strand_alarms: AtomicU64::new(0),
+                sync_scope: RwLock::new(Default::default()),
+                scope_evictions: tokio::sync::Mutex::new(Vec::new()),
             }),
         }
     }
@@ -522,6 +568,8 @@ impl LoroEngine {
                 pending_imports: RwLock::new(HashMap::new()),
                 import_pass: AtomicU64::new(0),
                 outbound_strand_alarms: AtomicU64::new(0),
+                sync_scope: RwLock::new(Default::default()),
+                scope_evictions: tokio::sync::Mutex::new(Vec::new()),
             }),
         }
     }
@@ -603,6 +651,7 @@ impl LoroEngine {
         // Restore the causal-gap ledger so a restart doesn't forget a note
         // that was still pending a snapshot catch-up (tesela-c7s item 2).
         let pending_imports = load_pending_imports(&snapshot_dir).await;
+        let sync_scope = scope::load_scope_state(&snapshot_dir).await;
         let engine = Self {
             inner: Arc::new(Inner {
                 docs: RwLock::new(docs),
@@ -630,6 +679,8 @@ impl LoroEngine {
                 pending_imports: RwLock::new(pending_imports),
                 import_pass: AtomicU64::new(0),
                 outbound_strand_alarms: AtomicU64::new(0),
+                sync_scope: RwLock::new(sync_scope),
+                scope_evictions: tokio::sync::Mutex::new(Vec::new()),
             }),
         };
         let needs_rebuild =
@@ -954,6 +1005,11 @@ impl LoroEngine {
                 note_ids.insert(id);
             }
         }
+        // Selective sync: a note this device's scope keeps out is never
+        // re-broadcast from here, even if a stray copy lingers.
+        for id in &self.inner.sync_scope.read().await.excluded {
+            note_ids.remove(id);
+        }
         let mut out = Vec::new();
         for note_id in note_ids {
             let current = match self.doc_version(note_id).await {
@@ -1021,6 +1077,8 @@ impl LoroEngine {
         // Persist so a restart doesn't re-broadcast every note's full
         // state (best-effort).
         self.save_broadcast_cursors().await;
+        // Out-of-scope notes held back only by unsent local edits can go now.
+        self.retry_deferred_scope_evictions().await;
     }
 
     /// HEAL a stranded outbound cursor after a note's full snapshot was
@@ -1090,7 +1148,22 @@ impl LoroEngine {
         // item 2). Bump once per batch, before any per-note record.
         let pass = self.inner.import_pass.fetch_add(1, Ordering::Relaxed) + 1;
         let mut report = RelayApplyReport::default();
+        let scope = self.inner.sync_scope.read().await.applied.clone();
         for (note_id, bytes) in updates {
+            // Selective sync: a note this device's scope keeps out is skipped
+            // — it is not held, so there is nothing to merge into — unless
+            // only its content kept it out. Then this update may have
+            // changed that content, so it is readmitted and applied like
+            // any note missing its base: it lands pending, the caller's
+            // snapshot catch-up completes it, and the arrival check below
+            // decides again.
+            if self.is_scope_excluded(*note_id).await
+                && !self.readmit_for_content_recheck(*note_id).await
+            {
+                self.clear_pending_import(*note_id).await;
+                report.excluded.push(*note_id);
+                continue;
+            }
             // Fully-qualified call: `apply_doc_update_status` also exists on
             // the `SyncEngine` trait, so the unqualified call would be
             // ambiguous-by-convention here (and a recursion trap if this body
@@ -1102,10 +1175,18 @@ impl LoroEngine {
             match self.apply_doc_update_outcome(*note_id, bytes).await {
                 Ok(outcome) if !outcome.pending => {
                     report.forwarded_targets.extend(outcome.forwarded_targets);
-                    report.applied.push(*note_id);
                     // A clean apply HEALED any prior causal gap for this note
                     // (the missing base arrived) — drop it from the ledger.
                     self.clear_pending_import(*note_id).await;
+                    // The update may have made the note match a scope rule
+                    // (first arrival, or a newly added excluded tag).
+                    if let Some(scope) = scope.as_ref() {
+                        if self.evict_if_out_of_scope(scope, *note_id).await {
+                            report.excluded.push(*note_id);
+                            continue;
+                        }
+                    }
+                    report.applied.push(*note_id);
                 }
                 Ok(outcome) => {
                     report.forwarded_targets.extend(outcome.forwarded_targets);
@@ -1130,6 +1211,10 @@ impl LoroEngine {
         }
         report.forwarded_targets.sort_unstable();
         report.forwarded_targets.dedup();
+        // A scope-registry change may have edited THIS device's rules.
+        if updates.iter().any(|(id, _)| *id == SYNC_SCOPES_DOC_ID) {
+            report.readmitted = self.reconcile_sync_scope().await;
+        }
         report
     }
 
@@ -1202,14 +1287,7 @@ impl LoroEngine {
     /// ops are always attributed to it — the prerequisite for two
     /// engines' per-note docs merging cleanly (Phase 4).
     fn peer_id(&self) -> u64 {
-        let b = self.inner.device.as_bytes();
-        let raw = u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]);
-        let masked = raw & 0x7FFF_FFFF_FFFF_FFFF;
-        if masked == 0 {
-            1
-        } else {
-            masked
-        }
+        loro_peer_id(&self.inner.device)
     }
 
     /// Stamp this engine's PeerID on a doc so its subsequent local ops
@@ -1230,9 +1308,16 @@ impl LoroEngine {
     /// docs: "not serialized into updates or snapshots"; must be reapplied per
     /// doc) and do NOT feed Loro's map/text LWW (that is `(lamport, peer)`),
     /// so enabling this changes observability, never merge/convergence.
+    ///
+    /// The change-merge interval drops from Loro's 1000 s default to
+    /// [`CHANGE_MERGE_INTERVAL_SECS`]: consecutive local commits inside the
+    /// interval fold into one change stamped with the FIRST commit's time, so
+    /// the interval is the resolution of point-in-time views and restores
+    /// (`time_travel`). Storage layout only — state is unaffected.
     fn set_doc_peer(&self, doc: &LoroDoc) {
         let _ = doc.set_peer_id(self.peer_id());
         doc.set_record_timestamp(true);
+        doc.set_change_merge_interval(CHANGE_MERGE_INTERVAL_SECS);
     }
 
     /// Get-or-create this note's apply-serialization lock (tesela-4ju). See
@@ -1890,6 +1975,18 @@ impl LoroEngine {
                     note_id = bound_doc_id;
                 }
             }
+            // A leftover file of a page this device's sync scope keeps out
+            // must not resurrect the note.
+            if self
+                .inner
+                .sync_scope
+                .read()
+                .await
+                .excluded
+                .contains(&note_id)
+            {
+                continue;
+            }
             if self.doc_version(note_id).await.is_some() {
                 continue;
             }
@@ -2131,213 +2228,29 @@ impl LoroEngine {
         let Some(doc) = self.lazy_load_doc(PAGE_DIRECTORY_DOC_ID).await else {
             return Vec::new();
         };
-        let loro::LoroValue::Map(values) = doc.get_map("directory").get_deep_value() else {
+        page_directory_entries(&doc)
+    }
+
+    /// [`Self::page_directory_list`] read straight from the directory's
+    /// snapshot under `snapshot_dir`, without opening an engine. For
+    /// processes that query a mosaic another process owns (the CLI next to
+    /// a running server); empty when the snapshot is missing or unreadable.
+    pub async fn read_page_directory(
+        snapshot_dir: &std::path::Path,
+    ) -> Vec<crate::engine::PageDirectoryEntry> {
+        let path = snapshot_dir.join(format!("{}.bin", hex_id(&PAGE_DIRECTORY_DOC_ID)));
+        let Ok(bytes) = tokio::fs::read(&path).await else {
             return Vec::new();
         };
-        let mut records: std::collections::BTreeMap<
-            (tesela_core::PageId, String),
-            std::collections::HashMap<String, loro::LoroValue>,
-        > = std::collections::BTreeMap::new();
-        let mut tombstones = std::collections::HashSet::<(tesela_core::PageId, String)>::new();
-        let mut forwards = std::collections::HashMap::<
-            (tesela_core::PageId, String),
-            std::collections::BTreeSet<String>,
-        >::new();
-        let mut aliases = std::collections::HashMap::<
-            (tesela_core::PageId, String),
-            std::collections::BTreeSet<String>,
-        >::new();
-        for (key, value) in values.iter() {
-            let mut parts = key.split('|');
-            match parts.next() {
-                Some("record") => {
-                    let (Some(page), Some(doc_id), Some(field), None) =
-                        (parts.next(), parts.next(), parts.next(), parts.next())
-                    else {
-                        continue;
-                    };
-                    let Some(page_id) = tesela_core::PageId::parse(page) else {
-                        continue;
-                    };
-                    records
-                        .entry((page_id, doc_id.to_string()))
-                        .or_default()
-                        .insert(field.to_string(), value.clone());
-                }
-                Some("tombstone") => {
-                    let (Some(page), Some(doc_id), None) =
-                        (parts.next(), parts.next(), parts.next())
-                    else {
-                        continue;
-                    };
-                    let Some(page_id) = tesela_core::PageId::parse(page) else {
-                        continue;
-                    };
-                    if matches!(value, loro::LoroValue::Bool(true)) {
-                        tombstones.insert((page_id, doc_id.to_string()));
-                    }
-                }
-                Some("forward") => {
-                    let (Some(page), Some(doc_id), Some(target), None) =
-                        (parts.next(), parts.next(), parts.next(), parts.next())
-                    else {
-                        continue;
-                    };
-                    let Some(page_id) = tesela_core::PageId::parse(page) else {
-                        continue;
-                    };
-                    if matches!(value, loro::LoroValue::Bool(true)) {
-                        forwards
-                            .entry((page_id, doc_id.to_string()))
-                            .or_default()
-                            .insert(target.to_string());
-                    }
-                }
-                Some("alias") => {
-                    let (Some(page), Some(doc_id), Some(_encoded), None) =
-                        (parts.next(), parts.next(), parts.next(), parts.next())
-                    else {
-                        continue;
-                    };
-                    let Some(page_id) = tesela_core::PageId::parse(page) else {
-                        continue;
-                    };
-                    if let loro::LoroValue::String(alias) = value {
-                        aliases
-                            .entry((page_id, doc_id.to_string()))
-                            .or_default()
-                            .insert((**alias).to_string());
-                    }
-                }
-                _ => {}
-            }
+        let doc = LoroDoc::new();
+        if let Err(e) = doc.import(&bytes) {
+            tracing::warn!(
+                "tesela-sync/loro: read page directory {}: {e}",
+                path.display()
+            );
+            return Vec::new();
         }
-        let document_binding_counts = records.keys().fold(
-            std::collections::HashMap::<String, usize>::new(),
-            |mut counts, (_page_id, doc_id)| {
-                *counts.entry(doc_id.clone()).or_default() += 1;
-                counts
-            },
-        );
-        let live_page_counts = records.iter().fold(
-            std::collections::HashMap::<tesela_core::PageId, usize>::new(),
-            |mut page_counts, ((page_id, doc_id), fields)| {
-                let deleted = tombstones.contains(&(*page_id, doc_id.clone()))
-                    || matches!(fields.get("deleted"), Some(loro::LoroValue::Bool(true)));
-                if !deleted {
-                    *page_counts.entry(*page_id).or_default() += 1;
-                }
-                page_counts
-            },
-        );
-        let live_slug_counts = records.iter().fold(
-            std::collections::HashMap::<String, usize>::new(),
-            |mut counts, ((page_id, doc_id), fields)| {
-                let deleted = tombstones.contains(&(*page_id, doc_id.clone()))
-                    || matches!(fields.get("deleted"), Some(loro::LoroValue::Bool(true)));
-                if !deleted {
-                    if let Some(loro::LoroValue::String(slug)) = fields.get("slug") {
-                        if !slug.is_empty() {
-                            *counts.entry(slug.to_ascii_lowercase()).or_default() += 1;
-                        }
-                    }
-                }
-                counts
-            },
-        );
-        let document_pages = records.keys().fold(
-            std::collections::HashMap::<String, std::collections::HashSet<tesela_core::PageId>>::new(),
-            |mut pages, (page_id, doc_id)| {
-                pages.entry(doc_id.clone()).or_default().insert(*page_id);
-                pages
-            },
-        );
-        let string = |fields: &std::collections::HashMap<String, loro::LoroValue>, key: &str| {
-            fields.get(key).and_then(|value| match value {
-                loro::LoroValue::String(value) => Some((**value).to_string()),
-                _ => None,
-            })
-        };
-        let mut out = records
-            .into_iter()
-            .map(|((page_id, loro_doc_id), fields)| {
-                let binding = (page_id, loro_doc_id.clone());
-                let deleted = tombstones.contains(&binding)
-                    || matches!(fields.get("deleted"), Some(loro::LoroValue::Bool(true)));
-                let mut forward_targets = forwards.remove(&binding).unwrap_or_default();
-                if let Some(legacy_forward) =
-                    string(&fields, "forward_to").filter(|value| !value.is_empty())
-                {
-                    forward_targets.insert(legacy_forward);
-                }
-                let forward_conflict = forward_targets.len() > 1
-                    || forward_targets.iter().any(|target| {
-                        parse_note_id_from_hex(target).is_none()
-                            || parse_note_id_from_hex(target)
-                                .is_some_and(|id| Self::is_special_doc(&id))
-                            || document_pages.get(target).is_some_and(|pages| {
-                                pages.iter().any(|target_page| *target_page != page_id)
-                            })
-                    });
-                let forward_to_loro_doc_id = (forward_targets.len() == 1).then(|| {
-                    forward_targets
-                        .into_iter()
-                        .next()
-                        .expect("one forward target")
-                });
-                let explicit_conflict =
-                    matches!(fields.get("conflict"), Some(loro::LoroValue::Bool(true)));
-                let conflict = live_page_counts.get(&page_id).copied().unwrap_or(0) > 1
-                    || document_binding_counts
-                        .get(loro_doc_id.as_str())
-                        .copied()
-                        .unwrap_or(0)
-                        > 1
-                    || live_slug_counts
-                        .get(
-                            &string(&fields, "slug")
-                                .unwrap_or_default()
-                                .to_ascii_lowercase(),
-                        )
-                        .copied()
-                        .unwrap_or(0)
-                        > 1
-                    || parse_note_id_from_hex(&loro_doc_id).is_none()
-                    || parse_note_id_from_hex(&loro_doc_id)
-                        .is_some_and(|id| Self::is_special_doc(&id))
-                    || explicit_conflict
-                    || forward_conflict;
-                crate::engine::PageDirectoryEntry {
-                    page_id,
-                    loro_doc_id,
-                    slug: string(&fields, "slug").unwrap_or_default(),
-                    title: string(&fields, "title").unwrap_or_default(),
-                    aliases: {
-                        let facts = aliases.remove(&binding).unwrap_or_default();
-                        let mut values: Vec<String> = string(&fields, "aliases")
-                            .filter(|value| !value.is_empty())
-                            .map(|value| value.split('\u{1f}').map(str::to_string).collect())
-                            .unwrap_or_default();
-                        for alias in facts {
-                            if !values.iter().any(|existing| existing == &alias) {
-                                values.push(alias);
-                            }
-                        }
-                        values
-                    },
-                    deleted,
-                    forward_to_loro_doc_id,
-                    conflict,
-                }
-            })
-            .collect::<Vec<_>>();
-        out.sort_by(|a, b| {
-            a.page_id
-                .to_string()
-                .cmp(&b.page_id.to_string())
-                .then_with(|| a.loro_doc_id.cmp(&b.loro_doc_id))
-        });
-        out
+        page_directory_entries(&doc)
     }
 
     /// Resolve an inbound document stream through a converged rename
@@ -3226,6 +3139,125 @@ impl LoroEngine {
     }
 }
 
+// ============================================================================
+// Notification state (reminder fired / acknowledged / snoozed)
+// ============================================================================
+//
+// ONE dedicated Loro doc (id = `NOTIFICATIONS_DOC_ID`) holds the delivery
+// state of every reminder the server's scanner has fired. Same shape and
+// exclusions as the views registry above: a `notifications` LoroMap keyed by
+// notification id → per-record LoroMap of flat scalars ({id, status,
+// snoozed_until_ms?, updated_at_ms}), field-level LWW. Notification ids are
+// derived deterministically from the reminder itself, so two devices that
+// fire the same reminder write the same key; an acknowledge on the phone and
+// a concurrent re-fire on the desktop resolve to one LWW `status`.
+impl LoroEngine {
+    async fn persist_notifications_doc(&self) {
+        if let Some(dir) = self.inner.snapshot_dir.as_ref() {
+            self.save_snapshot(dir, NOTIFICATIONS_DOC_ID).await;
+        }
+    }
+
+    /// Every reminder delivery record, sorted by id. Empty when the doc
+    /// doesn't exist yet (nothing has fired on any device).
+    pub async fn notification_states_list(&self) -> Vec<crate::engine::NotificationStateRecord> {
+        let Some(doc) = self.lazy_load_doc(NOTIFICATIONS_DOC_ID).await else {
+            return Vec::new();
+        };
+        let value = doc.get_map("notifications").get_deep_value();
+        let mut out = Vec::new();
+        if let loro::LoroValue::Map(m) = value {
+            for (key, v) in m.iter() {
+                let loro::LoroValue::Map(entry) = v else {
+                    continue;
+                };
+                let get_str = |k: &str| -> Option<String> {
+                    entry.get(k).and_then(|x| {
+                        if let loro::LoroValue::String(s) = x {
+                            Some((**s).to_string())
+                        } else {
+                            None
+                        }
+                    })
+                };
+                let get_i64 = |k: &str| -> Option<i64> {
+                    entry.get(k).and_then(|x| {
+                        if let loro::LoroValue::I64(n) = x {
+                            Some(*n)
+                        } else {
+                            None
+                        }
+                    })
+                };
+                out.push(crate::engine::NotificationStateRecord {
+                    id: get_str("id").unwrap_or_else(|| key.to_string()),
+                    status: get_str("status").unwrap_or_else(|| "fired".to_string()),
+                    snoozed_until_ms: get_i64("snoozed_until_ms"),
+                    updated_at_ms: get_i64("updated_at_ms").unwrap_or(0),
+                });
+            }
+        }
+        out.sort_by(|a, b| a.id.cmp(&b.id));
+        out
+    }
+
+    /// Create or update one reminder's delivery state. Each record is a
+    /// mergeable map keyed by id, so every field merges independently —
+    /// even when two devices create the same record concurrently.
+    pub async fn notification_state_upsert(
+        &self,
+        record: crate::engine::NotificationStateRecord,
+    ) -> SyncResult<()> {
+        if record.id.trim().is_empty() {
+            return Err(SyncError::Protocol(
+                "notification id must be non-empty".into(),
+            ));
+        }
+        let doc = self.doc_for_note_mut(NOTIFICATIONS_DOC_ID).await;
+        let notifications = doc.get_map("notifications");
+        let entry = match notifications.get(&record.id) {
+            Some(loro::ValueOrContainer::Container(loro::Container::Map(m))) => m,
+            _ => notifications
+                .ensure_mergeable_map(&record.id)
+                .map_err(|e| SyncError::Storage(format!("notifications ensure map: {e}")))?,
+        };
+        let ins = |e: loro::LoroError| SyncError::Storage(format!("notifications insert: {e}"));
+        entry.insert("id", record.id.as_str()).map_err(ins)?;
+        entry
+            .insert("status", record.status.as_str())
+            .map_err(ins)?;
+        match record.snoozed_until_ms {
+            Some(v) => entry.insert("snoozed_until_ms", v).map_err(ins)?,
+            None => {
+                let _ = entry.delete("snoozed_until_ms");
+            }
+        }
+        entry
+            .insert("updated_at_ms", record.updated_at_ms)
+            .map_err(ins)?;
+        doc.commit();
+        self.persist_notifications_doc().await;
+        Ok(())
+    }
+
+    /// Remove one reminder's delivery record. `Ok(false)` when absent.
+    pub async fn notification_state_delete(&self, id: &str) -> SyncResult<bool> {
+        let Some(doc) = self.lazy_load_doc(NOTIFICATIONS_DOC_ID).await else {
+            return Ok(false);
+        };
+        let notifications = doc.get_map("notifications");
+        if notifications.get(id).is_none() {
+            return Ok(false);
+        }
+        notifications
+            .delete(id)
+            .map_err(|e| SyncError::Storage(format!("notifications delete: {e}")))?;
+        doc.commit();
+        self.persist_notifications_doc().await;
+        Ok(true)
+    }
+}
+
 /// Load per-note broadcast cursors persisted by
 /// `LoroEngine::save_broadcast_cursors`. Missing/corrupt → empty map
 /// (a full re-broadcast on the next tick is idempotent).
@@ -3564,9 +3596,13 @@ use index::{
     build_block_index, frontmatter_title, live_block_ids, BlockIndex, INDEX_SCHEMA_VERSION,
 };
 mod apply;
+mod blame;
+mod health;
 #[cfg(test)]
 use apply::probe_import_poison;
 use apply::ImportMode;
+mod scope;
+mod time_travel;
 mod twins;
 #[cfg(test)]
 use twins::duplicate_block_ids;
@@ -4401,6 +4437,35 @@ impl SyncEngine for LoroEngine {
         LoroEngine::outbound_strand_alarm_count(self)
     }
 
+    async fn sync_anomalies(&self) -> Vec<SyncAnomaly> {
+        LoroEngine::sync_anomalies(self).await
+    }
+
+    async fn heal_disjoint_twins(&self) -> Vec<([u8; 16], String)> {
+        LoroEngine::heal_disjoint_twins(self).await
+    }
+
+    async fn rearm_snapshot_catchup(&self, note_id: [u8; 16]) -> bool {
+        LoroEngine::rearm_snapshot_catchup(self, note_id).await
+    }
+
+    async fn block_blame(&self, note_id: [u8; 16]) -> Option<Vec<BlockBlame>> {
+        LoroEngine::block_blame(self, note_id).await
+    }
+
+    async fn note_at(&self, note_id: [u8; 16], at_secs: i64) -> SyncResult<Option<NoteAtTime>> {
+        LoroEngine::note_at(self, note_id, at_secs).await
+    }
+
+    async fn restore_note(
+        &self,
+        note_id: [u8; 16],
+        at_secs: i64,
+        block_id: Option<[u8; 16]>,
+    ) -> SyncResult<bool> {
+        LoroEngine::restore_note(self, note_id, at_secs, block_id).await
+    }
+
     async fn apply_relay_updates(&self, updates: &[([u8; 16], Vec<u8>)]) -> RelayApplyReport {
         LoroEngine::apply_relay_updates(self, updates).await
     }
@@ -4522,6 +4587,49 @@ impl SyncEngine for LoroEngine {
         LoroEngine::page_directory_list(self).await
     }
 
+    async fn notification_states_list(&self) -> Vec<crate::engine::NotificationStateRecord> {
+        LoroEngine::notification_states_list(self).await
+    }
+
+    async fn notification_state_upsert(
+        &self,
+        record: crate::engine::NotificationStateRecord,
+    ) -> SyncResult<()> {
+        LoroEngine::notification_state_upsert(self, record).await
+    }
+
+    async fn notification_state_delete(&self, id: &str) -> SyncResult<bool> {
+        LoroEngine::notification_state_delete(self, id).await
+    }
+
+    async fn sync_scopes_list(&self) -> Vec<crate::engine::DeviceSyncScope> {
+        LoroEngine::sync_scopes_list(self).await
+    }
+
+    async fn sync_scope_upsert(&self, scope: crate::engine::DeviceSyncScope) -> SyncResult<()> {
+        LoroEngine::sync_scope_upsert(self, scope).await
+    }
+
+    async fn sync_scope_delete(&self, device: &str) -> SyncResult<bool> {
+        LoroEngine::sync_scope_delete(self, device).await
+    }
+
+    async fn scope_excluded_notes(&self) -> Vec<[u8; 16]> {
+        LoroEngine::scope_excluded_notes(self).await
+    }
+
+    async fn enforce_sync_scope(&self, note_id: [u8; 16]) -> bool {
+        LoroEngine::enforce_sync_scope(self, note_id).await
+    }
+
+    async fn reconcile_sync_scope(&self) -> Vec<[u8; 16]> {
+        LoroEngine::reconcile_sync_scope(self).await
+    }
+
+    async fn take_scope_evictions(&self) -> Vec<crate::engine::ScopeEviction> {
+        LoroEngine::take_scope_evictions(self).await
+    }
+
     async fn page_directory_upsert(
         &self,
         record: crate::engine::PageDirectoryEntry,
@@ -5331,5 +5439,213 @@ fn apply_prop_op(
     }
 }
 
+/// Directory records in `doc` (the page-directory doc), sorted by PageId.
+fn page_directory_entries(doc: &LoroDoc) -> Vec<crate::engine::PageDirectoryEntry> {
+    let loro::LoroValue::Map(values) = doc.get_map("directory").get_deep_value() else {
+        return Vec::new();
+    };
+    let mut records: std::collections::BTreeMap<
+        (tesela_core::PageId, String),
+        std::collections::HashMap<String, loro::LoroValue>,
+    > = std::collections::BTreeMap::new();
+    let mut tombstones = std::collections::HashSet::<(tesela_core::PageId, String)>::new();
+    let mut forwards = std::collections::HashMap::<
+        (tesela_core::PageId, String),
+        std::collections::BTreeSet<String>,
+    >::new();
+    let mut aliases = std::collections::HashMap::<
+        (tesela_core::PageId, String),
+        std::collections::BTreeSet<String>,
+    >::new();
+    for (key, value) in values.iter() {
+        let mut parts = key.split('|');
+        match parts.next() {
+            Some("record") => {
+                let (Some(page), Some(doc_id), Some(field), None) =
+                    (parts.next(), parts.next(), parts.next(), parts.next())
+                else {
+                    continue;
+                };
+                let Some(page_id) = tesela_core::PageId::parse(page) else {
+                    continue;
+                };
+                records
+                    .entry((page_id, doc_id.to_string()))
+                    .or_default()
+                    .insert(field.to_string(), value.clone());
+            }
+            Some("tombstone") => {
+                let (Some(page), Some(doc_id), None) = (parts.next(), parts.next(), parts.next())
+                else {
+                    continue;
+                };
+                let Some(page_id) = tesela_core::PageId::parse(page) else {
+                    continue;
+                };
+                if matches!(value, loro::LoroValue::Bool(true)) {
+                    tombstones.insert((page_id, doc_id.to_string()));
+                }
+            }
+            Some("forward") => {
+                let (Some(page), Some(doc_id), Some(target), None) =
+                    (parts.next(), parts.next(), parts.next(), parts.next())
+                else {
+                    continue;
+                };
+                let Some(page_id) = tesela_core::PageId::parse(page) else {
+                    continue;
+                };
+                if matches!(value, loro::LoroValue::Bool(true)) {
+                    forwards
+                        .entry((page_id, doc_id.to_string()))
+                        .or_default()
+                        .insert(target.to_string());
+                }
+            }
+            Some("alias") => {
+                let (Some(page), Some(doc_id), Some(_encoded), None) =
+                    (parts.next(), parts.next(), parts.next(), parts.next())
+                else {
+                    continue;
+                };
+                let Some(page_id) = tesela_core::PageId::parse(page) else {
+                    continue;
+                };
+                if let loro::LoroValue::String(alias) = value {
+                    aliases
+                        .entry((page_id, doc_id.to_string()))
+                        .or_default()
+                        .insert((**alias).to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    let document_binding_counts = records.keys().fold(
+        std::collections::HashMap::<String, usize>::new(),
+        |mut counts, (_page_id, doc_id)| {
+            *counts.entry(doc_id.clone()).or_default() += 1;
+            counts
+        },
+    );
+    let live_page_counts = records.iter().fold(
+        std::collections::HashMap::<tesela_core::PageId, usize>::new(),
+        |mut page_counts, ((page_id, doc_id), fields)| {
+            let deleted = tombstones.contains(&(*page_id, doc_id.clone()))
+                || matches!(fields.get("deleted"), Some(loro::LoroValue::Bool(true)));
+            if !deleted {
+                *page_counts.entry(*page_id).or_default() += 1;
+            }
+            page_counts
+        },
+    );
+    let live_slug_counts = records.iter().fold(
+        std::collections::HashMap::<String, usize>::new(),
+        |mut counts, ((page_id, doc_id), fields)| {
+            let deleted = tombstones.contains(&(*page_id, doc_id.clone()))
+                || matches!(fields.get("deleted"), Some(loro::LoroValue::Bool(true)));
+            if !deleted {
+                if let Some(loro::LoroValue::String(slug)) = fields.get("slug") {
+                    if !slug.is_empty() {
+                        *counts.entry(slug.to_ascii_lowercase()).or_default() += 1;
+                    }
+                }
+            }
+            counts
+        },
+    );
+    let document_pages = records.keys().fold(
+        std::collections::HashMap::<String, std::collections::HashSet<tesela_core::PageId>>::new(),
+        |mut pages, (page_id, doc_id)| {
+            pages.entry(doc_id.clone()).or_default().insert(*page_id);
+            pages
+        },
+    );
+    let string = |fields: &std::collections::HashMap<String, loro::LoroValue>, key: &str| {
+        fields.get(key).and_then(|value| match value {
+            loro::LoroValue::String(value) => Some((**value).to_string()),
+            _ => None,
+        })
+    };
+    let mut out = records
+        .into_iter()
+        .map(|((page_id, loro_doc_id), fields)| {
+            let binding = (page_id, loro_doc_id.clone());
+            let deleted = tombstones.contains(&binding)
+                || matches!(fields.get("deleted"), Some(loro::LoroValue::Bool(true)));
+            let mut forward_targets = forwards.remove(&binding).unwrap_or_default();
+            if let Some(legacy_forward) =
+                string(&fields, "forward_to").filter(|value| !value.is_empty())
+            {
+                forward_targets.insert(legacy_forward);
+            }
+            let forward_conflict = forward_targets.len() > 1
+                || forward_targets.iter().any(|target| {
+                    parse_note_id_from_hex(target).is_none()
+                        || parse_note_id_from_hex(target).is_some_and(|id| is_special_doc(&id))
+                        || document_pages.get(target).is_some_and(|pages| {
+                            pages.iter().any(|target_page| *target_page != page_id)
+                        })
+                });
+            let forward_to_loro_doc_id = (forward_targets.len() == 1).then(|| {
+                forward_targets
+                    .into_iter()
+                    .next()
+                    .expect("one forward target")
+            });
+            let explicit_conflict =
+                matches!(fields.get("conflict"), Some(loro::LoroValue::Bool(true)));
+            let conflict = live_page_counts.get(&page_id).copied().unwrap_or(0) > 1
+                || document_binding_counts
+                    .get(loro_doc_id.as_str())
+                    .copied()
+                    .unwrap_or(0)
+                    > 1
+                || live_slug_counts
+                    .get(
+                        &string(&fields, "slug")
+                            .unwrap_or_default()
+                            .to_ascii_lowercase(),
+                    )
+                    .copied()
+                    .unwrap_or(0)
+                    > 1
+                || parse_note_id_from_hex(&loro_doc_id).is_none()
+                || parse_note_id_from_hex(&loro_doc_id).is_some_and(|id| is_special_doc(&id))
+                || explicit_conflict
+                || forward_conflict;
+            crate::engine::PageDirectoryEntry {
+                page_id,
+                loro_doc_id,
+                slug: string(&fields, "slug").unwrap_or_default(),
+                title: string(&fields, "title").unwrap_or_default(),
+                aliases: {
+                    let facts = aliases.remove(&binding).unwrap_or_default();
+                    let mut values: Vec<String> = string(&fields, "aliases")
+                        .filter(|value| !value.is_empty())
+                        .map(|value| value.split('\u{1f}').map(str::to_string).collect())
+                        .unwrap_or_default();
+                    for alias in facts {
+                        if !values.iter().any(|existing| existing == &alias) {
+                            values.push(alias);
+                        }
+                    }
+                    values
+                },
+                deleted,
+                forward_to_loro_doc_id,
+                conflict,
+            }
+        })
+        .collect::<Vec<_>>();
+    out.sort_by(|a, b| {
+        a.page_id
+            .to_string()
+            .cmp(&b.page_id.to_string())
+            .then_with(|| a.loro_doc_id.cmp(&b.loro_doc_id))
+    });
+    out
+}
+
 #[cfg(test)]
 mod tests;
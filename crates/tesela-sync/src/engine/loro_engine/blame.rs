@@ -0,0 +1,101 @@
+use super::*;
+use crate::engine::BlockBlame;
+use loro::json::{JsonOpContent, TreeOp};
+use loro::ContainerTrait;
+
+// ============================================================================
+// Block blame
+// ============================================================================
+//
+// Walk a note doc's whole retained history once and attribute every op to
+// the tree node it touches: tree ops (create / move / delete) name their
+// target directly; any other op lands in a container whose path from the
+// root passes through exactly one `Index::Node` — the block's meta map, its
+// `text_seq`, its typed props. The op with the greatest `(lamport, peer)`
+// per node is that block's blame. Ops in containers no longer reachable
+// (a deleted node's children) resolve to nothing and are skipped.
+
+impl LoroEngine {
+    /// The Loro change that last touched each live block of `note_id`. See
+    /// [`SyncEngine::block_blame`].
+    pub async fn block_blame(&self, note_id: [u8; 16]) -> Option<Vec<BlockBlame>> {
+        let doc = self.lazy_load_doc(note_id).await?;
+        let tree = doc.get_tree("blocks");
+        let tree_cid = tree.id();
+
+        let mut changes: Vec<loro::ChangeMeta> = Vec::new();
+        let heads: Vec<loro::ID> = doc.oplog_frontiers().iter().collect();
+        // A shallow doc stops the walk at its horizon; what was reached
+        // before that is still a valid (partial) attribution.
+        let _ = doc.travel_change_ancestors(&heads, &mut |meta| {
+            changes.push(meta);
+            std::ops::ControlFlow::Continue(())
+        });
+
+        let mut node_of: HashMap<loro::ContainerID, Option<TreeID>> = HashMap::new();
+        let mut last: HashMap<TreeID, (u32, u64, i64)> = HashMap::new();
+        for meta in &changes {
+            let span = loro::IdSpan::new(
+                meta.id.peer,
+                meta.id.counter,
+                meta.id.counter + meta.len as i32,
+            );
+            for change in doc.export_json_in_id_span(span) {
+                for op in &change.ops {
+                    let node = if op.container == tree_cid {
+                        match &op.content {
+                            JsonOpContent::Tree(
+                                TreeOp::Create { target, .. }
+                                | TreeOp::Move { target, .. }
+                                | TreeOp::Delete { target },
+                            ) => Some(*target),
+                            _ => None,
+                        }
+                    } else {
+                        *node_of.entry(op.container.clone()).or_insert_with(|| {
+                            doc.get_path_to_container(&op.container)?
+                                .into_iter()
+                                .find_map(|(_, index)| match index {
+                                    loro::Index::Node(node) => Some(node),
+                                    _ => None,
+                                })
+                        })
+                    };
+                    let Some(node) = node else { continue };
+                    let lamport = change.lamport + (op.counter - change.id.counter) as u32;
+                    let candidate = (lamport, change.id.peer, change.timestamp);
+                    let slot = last.entry(node).or_insert(candidate);
+                    if (candidate.0, candidate.1) > (slot.0, slot.1) {
+                        *slot = candidate;
+                    }
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        for node in tree.nodes() {
+            if matches!(tree.is_node_deleted(&node), Ok(true)) {
+                continue;
+            }
+            let Some(block_id) = tree
+                .get_meta(node)
+                .ok()
+                .and_then(|meta| meta.get("block_id"))
+                .and_then(|v| v.into_value().ok())
+                .and_then(|v| v.into_string().ok())
+            else {
+                continue;
+            };
+            let Some((lamport, peer, timestamp)) = last.get(&node) else {
+                continue;
+            };
+            out.push(BlockBlame {
+                block_id: block_id.to_string(),
+                peer: *peer,
+                lamport: *lamport,
+                timestamp: (*timestamp > 0).then_some(*timestamp),
+            });
+        }
+        Some(out)
+    }
+}
@@ -0,0 +1,151 @@
+use super::*;
+use crate::engine::{AnomalyKind, AnomalySeverity, SyncAnomaly, SyncRepair};
+
+// ============================================================================
+// Sync-health inspection
+// ============================================================================
+//
+// One read-only pass over the diagnostics the engine already keeps — the
+// twin scan (`scan_disjoint_twins`), the page directory's `conflict` flag,
+// the causal-gap ledger (`pending_imports`) and the outbound broadcast
+// cursors — folded into one `SyncAnomaly` list per note. Nothing here
+// mutates: repairs go through `heal_disjoint_twins` and
+// `rearm_snapshot_catchup`, which the caller runs explicitly.
+
+impl LoroEngine {
+    /// Every sync anomaly the engine knows about, most severe first (then by
+    /// note id). See [`SyncEngine::sync_anomalies`].
+    pub async fn sync_anomalies(&self) -> Vec<SyncAnomaly> {
+        let directory = self.page_directory_list().await;
+        let slug_of = |hex: &str| {
+            directory
+                .iter()
+                .find(|e| e.loro_doc_id == hex && !e.deleted)
+                .map(|e| e.slug.clone())
+        };
+        let mut out = Vec::new();
+
+        // Garbled blocks (disjoint twins): one anomaly per note, however
+        // many blocks fork.
+        let mut twins: HashMap<[u8; 16], usize> = HashMap::new();
+        for (note_id, _, _) in self.scan_disjoint_twins().await {
+            *twins.entry(note_id).or_default() += 1;
+        }
+        for (note_id, count) in twins {
+            let hex = hex_id(&note_id);
+            out.push(SyncAnomaly {
+                slug: slug_of(&hex),
+                note_id: hex,
+                kind: AnomalyKind::GarbledBlocks,
+                severity: AnomalySeverity::Warning,
+                detail: format!("{count} block id(s) live on more than one tree node"),
+                repair: Some(SyncRepair::RepairGarbledBlocks),
+            });
+        }
+
+        // Directory conflicts fail closed everywhere they're read; only a
+        // person can say which document is the page.
+        for entry in directory.iter().filter(|e| e.conflict && !e.deleted) {
+            let claims = directory
+                .iter()
+                .filter(|e| e.page_id == entry.page_id && !e.deleted)
+                .count();
+            out.push(SyncAnomaly {
+                note_id: entry.loro_doc_id.clone(),
+                slug: Some(entry.slug.clone()),
+                kind: AnomalyKind::DirectoryConflict,
+                severity: AnomalySeverity::Error,
+                detail: format!(
+                    "page `{}` is claimed by {claims} live documents",
+                    entry.slug
+                ),
+                repair: None,
+            });
+        }
+
+        // Causal gaps, from the durable ledger.
+        for pending in self.pending_import_notes().await {
+            let hex = hex_id(&pending.note_id);
+            let (severity, detail) = if pending.catchup_exhausted {
+                (
+                    AnomalySeverity::Error,
+                    format!(
+                        "permanent gap: {} snapshot catch-up(s) never healed it",
+                        pending.catchup_attempts
+                    ),
+                )
+            } else {
+                (
+                    AnomalySeverity::Warning,
+                    format!(
+                        "update pending since apply pass {} ({} catch-up attempt(s))",
+                        pending.first_seen_pass, pending.catchup_attempts
+                    ),
+                )
+            };
+            out.push(SyncAnomaly {
+                slug: slug_of(&hex),
+                note_id: hex,
+                kind: AnomalyKind::CausalGap,
+                severity,
+                detail,
+                repair: Some(SyncRepair::SnapshotCatchup),
+            });
+        }
+
+        // Outbound strands: `produce_relay_updates` already ships a full
+        // snapshot in their place, so they clear on the next tick.
+        let cursors: Vec<([u8; 16], Vec<u8>)> = self
+            .inner
+            .broadcast_cursor
+            .read()
+            .await
+            .iter()
+            .map(|(id, vv)| (*id, vv.clone()))
+            .collect();
+        for (note_id, since) in cursors {
+            let Some(current) = self.doc_version(note_id).await else {
+                continue;
+            };
+            if since == current || !outbound_cursor_stranded(Some(&since), &current) {
+                continue;
+            }
+            let hex = hex_id(&note_id);
+            out.push(SyncAnomaly {
+                slug: slug_of(&hex),
+                note_id: hex,
+                kind: AnomalyKind::OutboundStrand,
+                severity: AnomalySeverity::Info,
+                detail: "broadcast cursor is stale-ahead or undecodable; the next relay tick \
+                         ships a full snapshot"
+                    .into(),
+                repair: None,
+            });
+        }
+
+        out.sort_by(|a, b| {
+            b.severity
+                .cmp(&a.severity)
+                .then_with(|| a.note_id.cmp(&b.note_id))
+        });
+        out
+    }
+
+    /// Reset a causal-gap entry's escalation accounting so
+    /// [`notes_needing_snapshot_catchup`](Self::notes_needing_snapshot_catchup)
+    /// offers it again on the next pass. Returns `false` if the note isn't
+    /// in the ledger.
+    pub async fn rearm_snapshot_catchup(&self, note_id: [u8; 16]) -> bool {
+        {
+            let mut ledger = self.inner.pending_imports.write().await;
+            let Some(entry) = ledger.get_mut(&note_id) else {
+                return false;
+            };
+            entry.catchup_attempts = 0;
+            entry.last_catchup_pass = 0;
+            entry.catchup_exhausted = false;
+        }
+        self.save_pending_imports().await;
+        true
+    }
+}
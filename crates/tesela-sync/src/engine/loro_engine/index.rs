@@ -65,7 +65,7 @@ pub(super) fn frontmatter_title(content: &str) -> Option<String> {
 /// parsed page properties. Tags come from three sources (frontmatter
 /// `tags:`, the `tags::` page property, inline `#tags`); links are
 /// `[[wiki-link]]` targets. Both deduped + sorted.
-fn extract_index_metadata(
+pub(super) fn extract_index_metadata(
     content: &str,
     page_properties: &[(String, String)],
 ) -> (Vec<String>, Vec<String>) {
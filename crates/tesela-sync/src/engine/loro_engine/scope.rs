@@ -0,0 +1,532 @@
+use super::*;
+use crate::engine::{DeviceSyncScope, ScopeEviction};
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// Selective sync (per-device scope rules)
+// ============================================================================
+//
+// ONE dedicated Loro doc (id = `SYNC_SCOPES_DOC_ID`) holds every device's
+// exclusion rules: a `scopes` LoroMap keyed by device hex → per-device LoroMap
+// of {device, exclude_tags, exclude_types, exclude_slug_prefixes,
+// updated_at_ms}. Each rule list is one whole-value field (LWW as a unit —
+// two devices editing the same device's tag list concurrently keep one
+// list, not a merge). Every device holds the registry; each enforces only
+// its OWN entry, locally:
+//
+// - `apply_relay_updates` skips updates for notes excluded by a slug rule,
+//   readmits notes excluded by their content (tags, type) so the update's
+//   new content is checked again, and evicts a note the moment an applied
+//   update makes it match a rule;
+// - `produce_relay_updates` never ships an excluded note;
+// - `reconcile_sync_scope` re-applies the rules when they change, evicting
+//   newly-matching notes and handing back previously-excluded ones for the
+//   caller to re-fetch.
+//
+// Eviction drops the note's doc, `.bin` snapshot, index entry, broadcast
+// cursor and materialized `.md`. The page directory (a special doc) is
+// untouched, so an excluded page keeps its binding and links to it still
+// resolve. A note carrying local edits that haven't been broadcast yet is
+// never evicted — it is deferred until `commit_broadcast_cursors` confirms
+// the edits left the device.
+
+/// This replica's reconciled scope and the notes it keeps out.
+#[derive(Debug, Default)]
+pub(super) struct ScopeState {
+    /// The rules last reconciled against — `None` when this device has no
+    /// scope. Compared with the registry to detect a change.
+    pub(super) applied: Option<DeviceSyncScope>,
+    /// Notes this device does not hold because of `applied`.
+    pub(super) excluded: HashSet<[u8; 16]>,
+    /// Out-of-scope notes kept only until their local edits are broadcast.
+    /// In-memory: a restart re-derives them on the next reconcile.
+    pub(super) deferred: HashSet<[u8; 16]>,
+}
+
+/// On-disk shape of [`ScopeState`] (`_scope.bin`, postcard).
+#[derive(Serialize, Deserialize)]
+struct PersistedScopeState {
+    applied: Option<DeviceSyncScope>,
+    excluded: Vec<[u8; 16]>,
+}
+
+/// Load the scope state persisted by `LoroEngine::save_scope_state`.
+/// Missing/corrupt → no scope (the next reconcile re-derives it).
+pub(super) async fn load_scope_state(dir: &Path) -> ScopeState {
+    let path = dir.join("_scope.bin");
+    match tokio::fs::read(&path).await {
+        Ok(bytes) => match postcard::from_bytes::<PersistedScopeState>(&bytes) {
+            Ok(persisted) => ScopeState {
+                applied: persisted.applied,
+                excluded: persisted.excluded.into_iter().collect(),
+                deferred: HashSet::new(),
+            },
+            Err(e) => {
+                tracing::warn!("tesela-sync/loro: scope state decode: {e}");
+                ScopeState::default()
+            }
+        },
+        Err(_) => ScopeState::default(),
+    }
+}
+
+/// Same rules, ignoring the timestamp. Lists compare as case-folded sets.
+fn same_rules(a: &DeviceSyncScope, b: &DeviceSyncScope) -> bool {
+    covers(a, b) && covers(b, a)
+}
+
+/// Whether every rule in `old` is still present in `new` — i.e. nothing
+/// `old` excluded can be admitted under `new`.
+fn covers(new: &DeviceSyncScope, old: &DeviceSyncScope) -> bool {
+    let contains =
+        |rules: &[String], rule: &String| rules.iter().any(|r| r.eq_ignore_ascii_case(rule));
+    old.exclude_tags
+        .iter()
+        .all(|r| contains(&new.exclude_tags, r))
+        && old
+            .exclude_types
+            .iter()
+            .all(|r| contains(&new.exclude_types, r))
+        && old
+            .exclude_slug_prefixes
+            .iter()
+            .all(|r| contains(&new.exclude_slug_prefixes, r))
+}
+
+/// Whether a note doc falls outside `scope`. Tags and page type are read
+/// the same way the index derives them (frontmatter, `tags::` / `type::`
+/// page properties, inline `#tags`).
+fn doc_out_of_scope(scope: &DeviceSyncScope, doc: &LoroDoc) -> bool {
+    let slug = doc
+        .get_map("root")
+        .get("slug")
+        .and_then(|v| v.into_value().ok())
+        .and_then(|v| v.into_string().ok())
+        .map(|s| (*s).clone())
+        .unwrap_or_default();
+    let content = doc_full_markdown(doc);
+    let parsed = tesela_core::note_tree::parse_note(&content);
+    let (tags, _links) = index::extract_index_metadata(&content, &parsed.page_properties);
+    let note_type = parsed
+        .page_properties
+        .iter()
+        .find(|(k, _)| k == "type")
+        .map(|(_, v)| v.clone())
+        .or_else(|| {
+            tesela_core::storage::markdown::parse_frontmatter(&content)
+                .ok()
+                .and_then(|(meta, _)| meta.note_type)
+        });
+    scope.excludes(&slug, &tags, note_type.as_deref())
+}
+
+impl LoroEngine {
+    async fn persist_sync_scopes_doc(&self) {
+        if let Some(dir) = self.inner.snapshot_dir.as_ref() {
+            self.save_snapshot(dir, SYNC_SCOPES_DOC_ID).await;
+        }
+    }
+
+    /// Persist [`ScopeState`] to `<snapshot_dir>/_scope.bin`. Best-effort,
+    /// like the broadcast cursor: a lost file only costs re-fetching the
+    /// excluded notes once.
+    async fn save_scope_state(&self) {
+        let Some(dir) = self.inner.snapshot_dir.as_ref() else {
+            return;
+        };
+        let persisted = {
+            let state = self.inner.sync_scope.read().await;
+            PersistedScopeState {
+                applied: state.applied.clone(),
+                excluded: state.excluded.iter().copied().collect(),
+            }
+        };
+        let bytes = match postcard::to_allocvec(&persisted) {
+            Ok(b) => b,
+            Err(e) => {
+                tracing::warn!("tesela-sync/loro: scope state encode: {e}");
+                return;
+            }
+        };
+        let path = dir.join("_scope.bin");
+        let tmp = unique_tmp(&path);
+        if tokio::fs::write(&tmp, &bytes).await.is_ok() {
+            if tokio::fs::rename(&tmp, &path).await.is_err() {
+                let _ = tokio::fs::remove_file(&tmp).await;
+            }
+        } else {
+            let _ = tokio::fs::remove_file(&tmp).await;
+        }
+    }
+
+    /// Every device's selective-sync rules, sorted by device. Empty when the
+    /// registry doesn't exist yet.
+    pub async fn sync_scopes_list(&self) -> Vec<DeviceSyncScope> {
+        let Some(doc) = self.lazy_load_doc(SYNC_SCOPES_DOC_ID).await else {
+            return Vec::new();
+        };
+        let value = doc.get_map("scopes").get_deep_value();
+        let mut out = Vec::new();
+        if let loro::LoroValue::Map(m) = value {
+            for (key, v) in m.iter() {
+                let loro::LoroValue::Map(entry) = v else {
+                    continue;
+                };
+                let get_list = |k: &str| -> Vec<String> {
+                    match entry.get(k) {
+                        Some(loro::LoroValue::List(items)) => items
+                            .iter()
+                            .filter_map(|x| match x {
+                                loro::LoroValue::String(s) => Some((**s).to_string()),
+                                _ => None,
+                            })
+                            .collect(),
+                        _ => Vec::new(),
+                    }
+                };
+                out.push(DeviceSyncScope {
+                    device: match entry.get("device") {
+                        Some(loro::LoroValue::String(s)) => (**s).to_string(),
+                        _ => key.to_string(),
+                    },
+                    exclude_tags: get_list("exclude_tags"),
+                    exclude_types: get_list("exclude_types"),
+                    exclude_slug_prefixes: get_list("exclude_slug_prefixes"),
+                    updated_at_ms: match entry.get("updated_at_ms") {
+                        Some(loro::LoroValue::I64(n)) => *n,
+                        _ => 0,
+                    },
+                });
+            }
+        }
+        out.sort_by(|a, b| a.device.cmp(&b.device));
+        out
+    }
+
+    /// Create or replace one device's rules. Only records them — the
+    /// device enforces them on its next [`Self::reconcile_sync_scope`].
+    pub async fn sync_scope_upsert(&self, scope: DeviceSyncScope) -> SyncResult<()> {
+        let device = scope.device.trim().to_ascii_lowercase();
+        if device.len() != 32 || hex::decode(&device).is_err() {
+            return Err(SyncError::Protocol(
+                "sync scope device must be a 32-char hex device id".into(),
+            ));
+        }
+        let doc = self.doc_for_note_mut(SYNC_SCOPES_DOC_ID).await;
+        let scopes = doc.get_map("scopes");
+        // A mergeable child: two devices first writing the same device's
+        // entry concurrently land in one container, so no field is lost.
+        let entry = match scopes.get(&device) {
+            Some(loro::ValueOrContainer::Container(loro::Container::Map(m))) => m,
+            _ => scopes
+                .ensure_mergeable_map(&device)
+                .map_err(|e| SyncError::Storage(format!("sync scopes ensure map: {e}")))?,
+        };
+        let ins = |e: loro::LoroError| SyncError::Storage(format!("sync scopes insert: {e}"));
+        let clean = |rules: Vec<String>| -> Vec<String> {
+            rules
+                .into_iter()
+                .map(|r| r.trim().to_string())
+                .filter(|r| !r.is_empty())
+                .collect()
+        };
+        entry.insert("device", device.as_str()).map_err(ins)?;
+        entry
+            .insert("exclude_tags", clean(scope.exclude_tags))
+            .map_err(ins)?;
+        entry
+            .insert("exclude_types", clean(scope.exclude_types))
+            .map_err(ins)?;
+        entry
+            .insert("exclude_slug_prefixes", clean(scope.exclude_slug_prefixes))
+            .map_err(ins)?;
+        entry
+            .insert("updated_at_ms", scope.updated_at_ms)
+            .map_err(ins)?;
+        doc.commit();
+        self.persist_sync_scopes_doc().await;
+        Ok(())
+    }
+
+    /// Drop one device's rules. `Ok(false)` when it had none.
+    pub async fn sync_scope_delete(&self, device: &str) -> SyncResult<bool> {
+        let Some(doc) = self.lazy_load_doc(SYNC_SCOPES_DOC_ID).await else {
+            return Ok(false);
+        };
+        let device = device.trim().to_ascii_lowercase();
+        let scopes = doc.get_map("scopes");
+        if scopes.get(&device).is_none() {
+            return Ok(false);
+        }
+        scopes
+            .delete(&device)
+            .map_err(|e| SyncError::Storage(format!("sync scopes delete: {e}")))?;
+        doc.commit();
+        self.persist_sync_scopes_doc().await;
+        Ok(true)
+    }
+
+    /// This device's registry entry, when it has any rules.
+    async fn own_sync_scope(&self) -> Option<DeviceSyncScope> {
+        let own = self.inner.device.to_hex();
+        self.sync_scopes_list()
+            .await
+            .into_iter()
+            .find(|s| s.device == own && !s.is_empty())
+    }
+
+    /// Notes this device's scope currently keeps out, sorted.
+    pub async fn scope_excluded_notes(&self) -> Vec<[u8; 16]> {
+        let mut out: Vec<[u8; 16]> = self
+            .inner
+            .sync_scope
+            .read()
+            .await
+            .excluded
+            .iter()
+            .copied()
+            .collect();
+        out.sort_unstable();
+        out
+    }
+
+    pub(super) async fn is_scope_excluded(&self, note_id: [u8; 16]) -> bool {
+        self.inner
+            .sync_scope
+            .read()
+            .await
+            .excluded
+            .contains(&note_id)
+    }
+
+    /// Readmit an excluded note an incoming update just touched, unless its
+    /// slug alone keeps it out. Tag and type rules read content this device
+    /// no longer holds, and the update may have removed the tag that
+    /// excluded the note; the caller applies the update and the note is
+    /// checked again once its full state arrives. The slug comes from the
+    /// page directory, which eviction leaves in place. Returns whether the
+    /// note was readmitted.
+    pub(super) async fn readmit_for_content_recheck(&self, note_id: [u8; 16]) -> bool {
+        let Some(scope) = self.inner.sync_scope.read().await.applied.clone() else {
+            return false;
+        };
+        let key = hex_id(&note_id);
+        let slug = self
+            .page_directory_list()
+            .await
+            .into_iter()
+            .find(|entry| entry.loro_doc_id == key && !entry.deleted)
+            .map(|entry| entry.slug);
+        if slug.is_some_and(|slug| scope.excludes(&slug, &[], None)) {
+            return false;
+        }
+        if !self
+            .inner
+            .sync_scope
+            .write()
+            .await
+            .excluded
+            .remove(&note_id)
+        {
+            return false;
+        }
+        self.save_scope_state().await;
+        tracing::debug!(
+            "tesela-sync/loro: readmitted {} — an update may have brought it back into scope",
+            key
+        );
+        true
+    }
+
+    /// Whether `doc` carries ops authored here that no confirmed broadcast
+    /// has covered yet — evicting it would lose them.
+    async fn has_unbroadcast_local_ops(&self, note_id: [u8; 16], doc: &LoroDoc) -> bool {
+        let peer = self.peer_id();
+        let authored = doc.oplog_vv().get(&peer).copied().unwrap_or(0);
+        if authored == 0 {
+            return false;
+        }
+        let sent = self
+            .inner
+            .broadcast_cursor
+            .read()
+            .await
+            .get(&note_id)
+            .and_then(|enc| VersionVector::decode(enc).ok())
+            .and_then(|vv| vv.get(&peer).copied())
+            .unwrap_or(0);
+        authored > sent
+    }
+
+    /// Evict `note_id` if it is a held, live note outside `scope`. Returns
+    /// whether it was evicted; a note with unsent local edits is deferred
+    /// instead (see `retry_deferred_scope_evictions`).
+    pub(super) async fn evict_if_out_of_scope(
+        &self,
+        scope: &DeviceSyncScope,
+        note_id: [u8; 16],
+    ) -> bool {
+        if Self::is_special_doc(&note_id) {
+            return false;
+        }
+        let Some(doc) = self.lazy_load_doc(note_id).await else {
+            return false;
+        };
+        if note_doc_is_deleted(&doc) || !doc_out_of_scope(scope, &doc) {
+            self.inner
+                .sync_scope
+                .write()
+                .await
+                .deferred
+                .remove(&note_id);
+            return false;
+        }
+        if self.has_unbroadcast_local_ops(note_id, &doc).await {
+            self.inner.sync_scope.write().await.deferred.insert(note_id);
+            return false;
+        }
+        self.evict_for_scope(note_id).await;
+        true
+    }
+
+    /// Drop every local trace of a note except its page-directory binding.
+    async fn evict_for_scope(&self, note_id: [u8; 16]) {
+        let apply_lock = self.apply_lock_for_note(note_id).await;
+        let _apply_guard = apply_lock.lock().await;
+        let slug = self.slug_for_note(note_id).await;
+        {
+            let _ownership_guard = self.inner.ownership_transition.lock().await;
+            self.unregister_note_under_ownership(note_id).await;
+            self.inner.docs.write().await.remove(&note_id);
+        }
+        self.index_remove(note_id);
+        if let Some(dir) = self.inner.snapshot_dir.as_ref() {
+            // The doc is gone from the map, so this deletes its `.bin`.
+            self.save_snapshot(dir, note_id).await;
+            self.save_index_snapshot(dir).await;
+        }
+        if let Some(slug) = slug.as_deref() {
+            self.remove_materialized(slug).await;
+        }
+        if self
+            .inner
+            .broadcast_cursor
+            .write()
+            .await
+            .remove(&note_id)
+            .is_some()
+        {
+            self.save_broadcast_cursors().await;
+        }
+        self.clear_pending_import(note_id).await;
+        {
+            let mut state = self.inner.sync_scope.write().await;
+            state.deferred.remove(&note_id);
+            state.excluded.insert(note_id);
+        }
+        self.save_scope_state().await;
+        tracing::info!(
+            "tesela-sync/loro: evicted {} ({}) — outside this device's sync scope",
+            hex_id(&note_id),
+            slug.as_deref().unwrap_or("?")
+        );
+        self.inner
+            .scope_evictions
+            .lock()
+            .await
+            .push(ScopeEviction { note_id, slug });
+    }
+
+    /// Evict `note_id` if it is held and outside this device's reconciled
+    /// scope. For import paths outside `apply_relay_updates` (snapshot
+    /// bootstrap / catch-up). Returns whether the note is out of scope.
+    pub async fn enforce_sync_scope(&self, note_id: [u8; 16]) -> bool {
+        if self.is_scope_excluded(note_id).await {
+            return true;
+        }
+        let Some(scope) = self.inner.sync_scope.read().await.applied.clone() else {
+            return false;
+        };
+        self.evict_if_out_of_scope(&scope, note_id).await
+    }
+
+    /// Bring this replica in line with its registry entry. A no-op while
+    /// the rules are unchanged. Otherwise: when the new rules drop any old
+    /// one, every excluded note is readmitted (the content that decided its
+    /// exclusion is gone, so it is re-fetched and re-checked on arrival);
+    /// then every held note is checked against the new rules. Returns the
+    /// readmitted notes.
+    pub async fn reconcile_sync_scope(&self) -> Vec<[u8; 16]> {
+        let registry = self.own_sync_scope().await;
+        let previous = self.inner.sync_scope.read().await.applied.clone();
+        let unchanged = match (&registry, &previous) {
+            (None, None) => true,
+            (Some(new), Some(old)) => same_rules(new, old),
+            _ => false,
+        };
+        if unchanged {
+            return Vec::new();
+        }
+        let narrowed_only = match (&registry, &previous) {
+            (Some(new), Some(old)) => covers(new, old),
+            (Some(_), None) => true,
+            _ => false,
+        };
+        let mut readmitted = Vec::new();
+        {
+            let mut state = self.inner.sync_scope.write().await;
+            state.applied = registry.clone();
+            state.deferred.clear();
+            if !narrowed_only {
+                readmitted = state.excluded.drain().collect();
+                readmitted.sort_unstable();
+            }
+        }
+        self.save_scope_state().await;
+        if let Some(scope) = registry.as_ref() {
+            for note_id in self.scope_candidate_ids().await {
+                self.evict_if_out_of_scope(scope, note_id).await;
+            }
+        }
+        readmitted
+    }
+
+    /// Every note the engine holds: resident docs plus the index.
+    async fn scope_candidate_ids(&self) -> Vec<[u8; 16]> {
+        let mut ids: HashSet<[u8; 16]> = self.inner.docs.read().await.keys().copied().collect();
+        for entry in self.index_entries().await {
+            if let Some(id) = parse_note_id_from_hex(&entry.note_id) {
+                ids.insert(id);
+            }
+        }
+        let mut ids: Vec<[u8; 16]> = ids
+            .into_iter()
+            .filter(|id| !Self::is_special_doc(id))
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Evict deferred out-of-scope notes whose local edits have since been
+    /// confirmed broadcast.
+    pub(super) async fn retry_deferred_scope_evictions(&self) {
+        let (scope, deferred) = {
+            let state = self.inner.sync_scope.read().await;
+            if state.deferred.is_empty() {
+                return;
+            }
+            let Some(scope) = state.applied.clone() else {
+                return;
+            };
+            (scope, state.deferred.iter().copied().collect::<Vec<_>>())
+        };
+        for note_id in deferred {
+            self.evict_if_out_of_scope(&scope, note_id).await;
+        }
+    }
+
+    /// Drain the evictions recorded since the last call.
+    pub async fn take_scope_evictions(&self) -> Vec<ScopeEviction> {
+        std::mem::take(&mut *self.inner.scope_evictions.lock().await)
+    }
+}
@@ -1,9 +1,13 @@
 use super::*;
 
+mod blame;
 mod convergence;
+mod health;
 mod ops;
 mod page_directory;
 mod relocation;
+mod sync_scope;
+mod time_travel;
 mod views_and_races;
 
 fn test_device() -> DeviceId {
@@ -0,0 +1,34 @@
+use super::*;
+
+// ─── Block blame ──────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn block_blame_attributes_each_block_to_its_last_author() {
+    let note = [0x51; 16];
+    let b_bid = [0x0b; 16];
+    let (a, b) = splice_shared_base(note, "hello").await;
+    upsert_block(&a, note, b_bid, "from a", Some(A_BID_BYTES)).await;
+
+    // B edits the shared block; A learns of it.
+    b.splice_block_text(note, A_BID_BYTES, 5, 0, " world")
+        .await
+        .unwrap();
+    let update = b.export_doc_update(note, None).await.unwrap();
+    a.import_doc_update(note, &update).await.unwrap();
+
+    let mut blame = a.block_blame(note).await.expect("note is tracked");
+    blame.sort_by(|x, y| x.block_id.cmp(&y.block_id));
+    let authors: Vec<(String, u64)> = blame.iter().map(|e| (e.block_id.clone(), e.peer)).collect();
+    assert_eq!(
+        authors,
+        vec![
+            (hex_id(&A_BID_BYTES), loro_peer_id(&b.device())),
+            (hex_id(&b_bid), loro_peer_id(&a.device())),
+        ]
+    );
+    assert!(
+        blame.iter().all(|e| e.timestamp.is_some()),
+        "engine-authored changes carry timestamps"
+    );
+    assert!(a.block_blame([0x52; 16]).await.is_none());
+}
@@ -0,0 +1,79 @@
+use super::*;
+use crate::engine::{AnomalyKind, AnomalySeverity, SyncRepair};
+
+// ─── Sync-health inspection ───────────────────────────────────────────
+
+const STRANDED: [u8; 16] = [0x41; 16];
+const GAPPED: [u8; 16] = [0x42; 16];
+
+#[tokio::test]
+async fn sync_anomalies_report_strands_and_causal_gaps_without_mutating() {
+    let engine = LoroEngine::new(test_device(), Arc::new(Hlc::new(test_device())));
+    engine
+        .record_local(OpPayload::NoteUpsert {
+            note_id: STRANDED,
+            display_alias: Some("stranded".into()),
+            title: "stranded".into(),
+            content: "- body\n".into(),
+            created_at_millis: 0,
+        })
+        .await
+        .unwrap();
+    assert!(engine.sync_anomalies().await.is_empty());
+
+    // An undecodable broadcast cursor on a dirty note is a strand.
+    engine
+        .commit_broadcast_cursors(&[(STRANDED, vec![0xff, 0xee])])
+        .await;
+    engine.record_pending_import(GAPPED, 0, vec![7]).await;
+
+    let anomalies = engine.sync_anomalies().await;
+    let kinds: Vec<_> = anomalies
+        .iter()
+        .map(|a| (a.note_id.clone(), a.kind, a.severity, a.repair))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (
+                hex_id(&GAPPED),
+                AnomalyKind::CausalGap,
+                AnomalySeverity::Warning,
+                Some(SyncRepair::SnapshotCatchup)
+            ),
+            (
+                hex_id(&STRANDED),
+                AnomalyKind::OutboundStrand,
+                AnomalySeverity::Info,
+                None
+            ),
+        ],
+        "most severe first"
+    );
+    assert_eq!(anomalies[1].slug.as_deref(), Some("stranded"));
+    assert_eq!(
+        engine.pending_import_notes().await[0].catchup_attempts,
+        0,
+        "a scan never advances escalation accounting"
+    );
+
+    // A permanent gap is an error until re-armed.
+    {
+        let mut ledger = engine.inner.pending_imports.write().await;
+        let entry = ledger.get_mut(&GAPPED).unwrap();
+        entry.catchup_attempts = MAX_CATCHUP_ATTEMPTS;
+        entry.catchup_exhausted = true;
+    }
+    assert_eq!(
+        engine.sync_anomalies().await[0].severity,
+        AnomalySeverity::Error
+    );
+    assert!(engine.rearm_snapshot_catchup(GAPPED).await);
+    assert!(
+        !engine.rearm_snapshot_catchup(STRANDED).await,
+        "not pending"
+    );
+    let pending = &engine.pending_import_notes().await[0];
+    assert!(!pending.catchup_exhausted);
+    assert_eq!(pending.catchup_attempts, 0);
+}
@@ -130,6 +130,11 @@ async fn startup_does_not_hydrate_a_duplicate_for_materialized_directory_entry()
         .await
         .unwrap();
     assert!(notes.join(format!("{slug}.md")).exists());
+    // Readers that don't own the mosaic see the persisted directory too.
+    assert_eq!(
+        LoroEngine::read_page_directory(&snapshots).await,
+        engine.page_directory_list().await
+    );
     drop(engine);
 
     let reopened =
@@ -884,7 +889,15 @@ async fn delayed_concurrent_upsert_cannot_clear_tombstone_or_forwarding() {
 #[tokio::test]
 async fn every_special_document_is_excluded_from_note_operations() {
     let engine = LoroEngine::new(test_device(), Arc::new(Hlc::new(test_device())));
-    assert_eq!(SPECIAL_DOC_IDS, [VIEWS_DOC_ID, PAGE_DIRECTORY_DOC_ID]);
+    assert_eq!(
+        SPECIAL_DOC_IDS,
+        [
+            VIEWS_DOC_ID,
+            PAGE_DIRECTORY_DOC_ID,
+            NOTIFICATIONS_DOC_ID,
+            SYNC_SCOPES_DOC_ID
+        ]
+    );
 
     for (index, note_id) in SPECIAL_DOC_IDS.into_iter().enumerate() {
         assert!(is_special_doc(&note_id));
@@ -0,0 +1,371 @@
+use super::*;
+use crate::engine::DeviceSyncScope;
+
+// ─── Selective sync (per-device scope rules) ─────────────────────────
+
+fn note(note_id: [u8; 16], slug: &str, content: &str) -> OpPayload {
+    OpPayload::NoteUpsert {
+        note_id,
+        display_alias: Some(slug.to_string()),
+        title: slug.to_string(),
+        content: content.to_string(),
+        created_at_millis: 0,
+    }
+}
+
+fn scope_for(device: DeviceId) -> DeviceSyncScope {
+    DeviceSyncScope {
+        device: device.to_hex(),
+        ..Default::default()
+    }
+}
+
+const JOURNAL: [u8; 16] = [0x31; 16];
+const WORK: [u8; 16] = [0x32; 16];
+const ARCHIVE: [u8; 16] = [0x33; 16];
+
+async fn seed_notes(engine: &LoroEngine) {
+    for (id, slug, content) in [
+        (JOURNAL, "2026-10-01", "- dinner with family #Personal\n"),
+        (WORK, "roadmap", "- ship the scope API #work\n"),
+        (ARCHIVE, "archive-2019", "type:: Archive\n- old stuff\n"),
+    ] {
+        engine.record_local(note(id, slug, content)).await.unwrap();
+    }
+}
+
+#[test]
+fn scope_rules_match_case_insensitively() {
+    let scope = DeviceSyncScope {
+        device: hex_id(&[1; 16]),
+        exclude_tags: vec!["#personal".into()],
+        exclude_types: vec!["archive".into()],
+        exclude_slug_prefixes: vec!["Journal/".into()],
+        updated_at_ms: 0,
+    };
+    assert!(scope.excludes("x", &["Personal".into()], None));
+    assert!(scope.excludes("x", &[], Some(" Archive")));
+    assert!(scope.excludes("journal/2026-10-01", &[], None));
+    assert!(!scope.excludes("work", &["personality".into()], Some("Project")));
+    assert!(!scope_for(test_device()).excludes("journal/x", &["personal".into()], None));
+}
+
+#[tokio::test]
+async fn excluded_notes_are_evicted_on_apply_but_stay_in_the_page_directory() {
+    let dev_a = DeviceId::from_bytes([0xa1; 16]);
+    let dev_b = DeviceId::from_bytes([0xb2; 16]);
+    let a = LoroEngine::new(dev_a, Arc::new(Hlc::new(dev_a)));
+    let b = LoroEngine::new(dev_b, Arc::new(Hlc::new(dev_b)));
+    b.sync_scope_upsert(DeviceSyncScope {
+        exclude_tags: vec!["personal".into()],
+        exclude_types: vec!["archive".into()],
+        ..scope_for(dev_b)
+    })
+    .await
+    .unwrap();
+    assert!(b.reconcile_sync_scope().await.is_empty());
+
+    seed_notes(&a).await;
+    ship_relay(&a, &b).await;
+
+    assert!(
+        b.lazy_load_doc(WORK).await.is_some(),
+        "in-scope note applies"
+    );
+    assert!(b.lazy_load_doc(JOURNAL).await.is_none());
+    assert!(b.lazy_load_doc(ARCHIVE).await.is_none());
+    let mut expected = vec![JOURNAL, ARCHIVE];
+    expected.sort_unstable();
+    assert_eq!(b.scope_excluded_notes().await, expected);
+    let evicted: Vec<_> = b
+        .take_scope_evictions()
+        .await
+        .into_iter()
+        .map(|e| (e.note_id, e.slug))
+        .collect();
+    assert!(evicted.contains(&(JOURNAL, Some("2026-10-01".into()))));
+    assert!(b.take_scope_evictions().await.is_empty(), "drained");
+    assert!(!b
+        .index_entries()
+        .await
+        .iter()
+        .any(|e| e.note_id == hex_id(&JOURNAL)));
+    // The directory still binds the excluded pages, so links resolve.
+    let directory = b.page_directory_list().await;
+    for id in [JOURNAL, ARCHIVE] {
+        assert!(
+            directory.iter().any(|e| e.loro_doc_id == hex_id(&id)),
+            "excluded page keeps its directory stub"
+        );
+    }
+
+    // A later edit to a note its content keeps out is re-checked: it lands
+    // pending until the note's snapshot arrives, then is evicted again
+    // while it still carries the tag.
+    upsert_block(&a, JOURNAL, [0x41; 16], "more family news", None).await;
+    let updates: Vec<([u8; 16], Vec<u8>)> = a
+        .produce_relay_updates()
+        .await
+        .into_iter()
+        .map(|(id, bytes, _)| (id, bytes))
+        .collect();
+    let report = b.apply_relay_updates(&updates).await;
+    assert!(report.failed.is_empty());
+    assert!(!report.excluded.contains(&JOURNAL));
+    assert!(!report.applied.contains(&JOURNAL));
+    let snapshot = a.export_doc_update(JOURNAL, None).await.unwrap();
+    b.import_authoritative_snapshot(JOURNAL, &snapshot)
+        .await
+        .unwrap();
+    // The import leaves local ops behind, so the eviction waits for the
+    // next broadcast, as for any out-of-scope note with unsent edits.
+    b.enforce_sync_scope(JOURNAL).await;
+    ship_relay(&b, &a).await;
+    assert!(b.enforce_sync_scope(JOURNAL).await, "still tagged personal");
+    assert!(b.lazy_load_doc(JOURNAL).await.is_none());
+    assert!(b.scope_excluded_notes().await.contains(&JOURNAL));
+    assert!(
+        !b.produce_relay_updates()
+            .await
+            .iter()
+            .any(|(id, _, _)| *id == JOURNAL),
+        "an excluded note is never re-broadcast"
+    );
+}
+
+#[tokio::test]
+async fn edit_that_drops_the_excluded_tag_brings_the_note_back() {
+    let dev_a = DeviceId::from_bytes([0xa1; 16]);
+    let dev_b = DeviceId::from_bytes([0xb2; 16]);
+    let a = LoroEngine::new(dev_a, Arc::new(Hlc::new(dev_a)));
+    let b = LoroEngine::new(dev_b, Arc::new(Hlc::new(dev_b)));
+    b.sync_scope_upsert(DeviceSyncScope {
+        exclude_tags: vec!["personal".into()],
+        ..scope_for(dev_b)
+    })
+    .await
+    .unwrap();
+    b.reconcile_sync_scope().await;
+
+    a.record_local(note(JOURNAL, "2026-10-01", "- dinner\n"))
+        .await
+        .unwrap();
+    upsert_block(&a, JOURNAL, [0x42; 16], "tagged #personal", None).await;
+    ship_relay(&a, &b).await;
+    assert_eq!(b.scope_excluded_notes().await, vec![JOURNAL]);
+
+    // A drops the tag; B re-checks the new content and keeps the note.
+    upsert_block(&a, JOURNAL, [0x42; 16], "no longer private", None).await;
+    ship_relay(&a, &b).await;
+    assert!(b.scope_excluded_notes().await.is_empty());
+    let snapshot = a.export_doc_update(JOURNAL, None).await.unwrap();
+    b.import_authoritative_snapshot(JOURNAL, &snapshot)
+        .await
+        .unwrap();
+    assert!(!b.enforce_sync_scope(JOURNAL).await);
+    assert!(b
+        .render_note(JOURNAL)
+        .await
+        .unwrap()
+        .contains("no longer private"));
+}
+
+#[tokio::test]
+async fn edits_to_a_note_excluded_by_slug_are_skipped() {
+    let dev_a = DeviceId::from_bytes([0xa1; 16]);
+    let dev_b = DeviceId::from_bytes([0xb2; 16]);
+    let a = LoroEngine::new(dev_a, Arc::new(Hlc::new(dev_a)));
+    let b = LoroEngine::new(dev_b, Arc::new(Hlc::new(dev_b)));
+    b.sync_scope_upsert(DeviceSyncScope {
+        exclude_slug_prefixes: vec!["archive-".into()],
+        ..scope_for(dev_b)
+    })
+    .await
+    .unwrap();
+    b.reconcile_sync_scope().await;
+    seed_notes(&a).await;
+    ship_relay(&a, &b).await;
+    assert_eq!(b.scope_excluded_notes().await, vec![ARCHIVE]);
+
+    upsert_block(&a, ARCHIVE, [0x43; 16], "more old stuff", None).await;
+    let updates: Vec<([u8; 16], Vec<u8>)> = a
+        .produce_relay_updates()
+        .await
+        .into_iter()
+        .map(|(id, bytes, _)| (id, bytes))
+        .collect();
+    let report = b.apply_relay_updates(&updates).await;
+    assert!(report.excluded.contains(&ARCHIVE));
+    assert!(report.pending.is_empty() && report.failed.is_empty());
+    assert!(b.lazy_load_doc(ARCHIVE).await.is_none());
+}
+
+#[tokio::test]
+async fn concurrent_first_writes_of_one_scope_entry_merge() {
+    let dev_a = DeviceId::from_bytes([0xa1; 16]);
+    let dev_b = DeviceId::from_bytes([0xb2; 16]);
+    let dev_c = DeviceId::from_bytes([0xc3; 16]);
+    let a = LoroEngine::new(dev_a, Arc::new(Hlc::new(dev_a)));
+    let b = LoroEngine::new(dev_b, Arc::new(Hlc::new(dev_b)));
+    // Both devices create C's entry before either sees the other's, then
+    // each writes one field of its own. A same-key container race would
+    // hide one device's whole entry.
+    for (engine, field) in [(&a, "from_a"), (&b, "from_b")] {
+        engine
+            .sync_scope_upsert(DeviceSyncScope {
+                exclude_tags: vec!["personal".into()],
+                ..scope_for(dev_c)
+            })
+            .await
+            .unwrap();
+        let doc = engine.doc_for_note_mut(SYNC_SCOPES_DOC_ID).await;
+        let Some(loro::ValueOrContainer::Container(loro::Container::Map(entry))) =
+            doc.get_map("scopes").get(&dev_c.to_hex())
+        else {
+            panic!("scope entry is a map container");
+        };
+        entry.insert(field, true).unwrap();
+        doc.commit();
+    }
+
+    ship_relay(&a, &b).await;
+    ship_relay(&b, &a).await;
+    for engine in [&a, &b] {
+        let doc = engine.lazy_load_doc(SYNC_SCOPES_DOC_ID).await.unwrap();
+        let loro::LoroValue::Map(scopes) = doc.get_map("scopes").get_deep_value() else {
+            panic!("scopes map");
+        };
+        let Some(loro::LoroValue::Map(entry)) = scopes.get(&dev_c.to_hex()) else {
+            panic!("entry for c");
+        };
+        assert!(
+            entry.contains_key("from_a") && entry.contains_key("from_b"),
+            "both devices' writes survive: {entry:?}"
+        );
+    }
+    assert_eq!(a.sync_scopes_list().await, b.sync_scopes_list().await);
+}
+
+#[tokio::test]
+async fn scope_edited_on_another_device_evicts_then_readmits() {
+    let dev_a = DeviceId::from_bytes([0xa1; 16]);
+    let dev_b = DeviceId::from_bytes([0xb2; 16]);
+    let a = LoroEngine::new(dev_a, Arc::new(Hlc::new(dev_a)));
+    let b = LoroEngine::new(dev_b, Arc::new(Hlc::new(dev_b)));
+    seed_notes(&a).await;
+    ship_relay(&a, &b).await;
+    ship_relay(&b, &a).await;
+    assert!(b.lazy_load_doc(ARCHIVE).await.is_some());
+
+    // A narrows B's scope; B enforces it when the registry arrives.
+    a.sync_scope_upsert(DeviceSyncScope {
+        exclude_slug_prefixes: vec!["Archive-".into()],
+        ..scope_for(dev_b)
+    })
+    .await
+    .unwrap();
+    assert!(
+        a.reconcile_sync_scope().await.is_empty(),
+        "A has no scope of its own"
+    );
+    assert!(a.lazy_load_doc(ARCHIVE).await.is_some());
+    ship_relay(&a, &b).await;
+    assert_eq!(b.sync_scopes_list().await.len(), 1);
+    assert!(b.lazy_load_doc(ARCHIVE).await.is_none());
+    assert_eq!(b.scope_excluded_notes().await, vec![ARCHIVE]);
+
+    // Dropping the rule readmits the note for the caller to re-fetch.
+    assert!(a.sync_scope_delete(&dev_b.to_hex()).await.unwrap());
+    let updates: Vec<([u8; 16], Vec<u8>)> = a
+        .produce_relay_updates()
+        .await
+        .into_iter()
+        .map(|(id, bytes, _)| (id, bytes))
+        .collect();
+    let report = b.apply_relay_updates(&updates).await;
+    assert_eq!(report.readmitted, vec![ARCHIVE]);
+    assert!(b.scope_excluded_notes().await.is_empty());
+    // Its authoritative snapshot now imports normally.
+    let snapshot = a.export_doc_update(ARCHIVE, None).await.unwrap();
+    b.import_authoritative_snapshot(ARCHIVE, &snapshot)
+        .await
+        .unwrap();
+    assert!(!b.enforce_sync_scope(ARCHIVE).await);
+    assert!(b.lazy_load_doc(ARCHIVE).await.is_some());
+}
+
+#[tokio::test]
+async fn unsent_local_edits_defer_eviction_until_broadcast() {
+    let dev_a = DeviceId::from_bytes([0xa1; 16]);
+    let dev_b = DeviceId::from_bytes([0xb2; 16]);
+    let a = LoroEngine::new(dev_a, Arc::new(Hlc::new(dev_a)));
+    let b = LoroEngine::new(dev_b, Arc::new(Hlc::new(dev_b)));
+    b.record_local(note(JOURNAL, "2026-10-01", "- written on B #personal\n"))
+        .await
+        .unwrap();
+    b.sync_scope_upsert(DeviceSyncScope {
+        exclude_tags: vec!["personal".into()],
+        ..scope_for(dev_b)
+    })
+    .await
+    .unwrap();
+    b.reconcile_sync_scope().await;
+    assert!(
+        b.lazy_load_doc(JOURNAL).await.is_some(),
+        "never evict edits that haven't left the device"
+    );
+
+    ship_relay(&b, &a).await;
+    assert!(a.lazy_load_doc(JOURNAL).await.is_some(), "A got the edit");
+    assert!(
+        b.lazy_load_doc(JOURNAL).await.is_none(),
+        "evicted once sent"
+    );
+    assert_eq!(b.scope_excluded_notes().await, vec![JOURNAL]);
+}
+
+#[tokio::test]
+async fn exclusions_survive_restart_without_rehydrating_the_markdown() {
+    let tmp = tempfile::tempdir().unwrap();
+    let snap = tmp.path().join("loro");
+    let notes = tmp.path().join("notes");
+    let dev_a = DeviceId::from_bytes([0xa1; 16]);
+    let dev_b = DeviceId::from_bytes([0xb2; 16]);
+    let a = LoroEngine::new(dev_a, Arc::new(Hlc::new(dev_a)));
+    seed_notes(&a).await;
+    {
+        let b = LoroEngine::with_dirs(
+            dev_b,
+            Arc::new(Hlc::new(dev_b)),
+            snap.clone(),
+            Some(notes.clone()),
+        )
+        .await
+        .unwrap();
+        ship_relay(&a, &b).await;
+        assert!(notes.join("2026-10-01.md").exists());
+        b.sync_scope_upsert(DeviceSyncScope {
+            exclude_tags: vec!["personal".into()],
+            ..scope_for(dev_b)
+        })
+        .await
+        .unwrap();
+        assert!(b.reconcile_sync_scope().await.is_empty());
+        assert!(!notes.join("2026-10-01.md").exists());
+        assert!(!snap.join(format!("{}.bin", hex_id(&JOURNAL))).exists());
+        assert!(notes.join("roadmap.md").exists());
+    }
+    // A stray copy of the file must not resurrect the note at boot.
+    tokio::fs::write(notes.join("2026-10-01.md"), "- dinner #personal\n")
+        .await
+        .unwrap();
+    let b = LoroEngine::with_dirs(dev_b, Arc::new(Hlc::new(dev_b)), snap, Some(notes))
+        .await
+        .unwrap();
+    assert_eq!(b.scope_excluded_notes().await, vec![JOURNAL]);
+    assert!(b.lazy_load_doc(JOURNAL).await.is_none());
+    assert!(
+        b.reconcile_sync_scope().await.is_empty(),
+        "the reconciled scope persisted too"
+    );
+}
@@ -0,0 +1,153 @@
+use super::*;
+
+// ─── Point-in-time views and restore ─────────────────────────────────
+
+const C_BID_BYTES: [u8; 16] = [0x0c; 16];
+const D_BID_BYTES: [u8; 16] = [0x0d; 16];
+
+/// Stamp the next commit on `note`'s doc with `at` — later commits that
+/// land within the merge interval fold into the same change, so a whole
+/// engine write is attributed to `at`.
+async fn at_time(engine: &LoroEngine, note: [u8; 16], at: i64) {
+    engine
+        .lazy_load_doc(note)
+        .await
+        .expect("note is tracked")
+        .set_next_commit_timestamp(at);
+}
+
+/// A note with `plan` and `pack` (with child `socks`) at `t0`; at `t0 +
+/// 1h` `plan` is edited, `pack` deleted and `new` added; at `t0 + 2h`
+/// `plan` is edited again.
+async fn seed_history(note: [u8; 16]) -> (LoroEngine, i64) {
+    let engine = LoroEngine::new(test_device(), Arc::new(Hlc::new(test_device())));
+    let t0 = chrono::Utc::now().timestamp();
+    engine
+        .record_local(OpPayload::NoteUpsert {
+            note_id: note,
+            display_alias: Some("trip".into()),
+            title: "Trip".into(),
+            content: format!(
+                "- plan <!-- bid:{A_BID} -->\n- pack <!-- bid:{B_BID} -->\n  - socks <!-- bid:{} -->\n",
+                uuid::Uuid::from_bytes(C_BID_BYTES)
+            ),
+            created_at_millis: 1,
+        })
+        .await
+        .unwrap();
+
+    at_time(&engine, note, t0 + 3600).await;
+    engine
+        .splice_block_text(note, A_BID_BYTES, 4, 0, " trip")
+        .await
+        .unwrap();
+    engine
+        .record_local(OpPayload::BlockDelete {
+            block_id: B_BID_BYTES,
+        })
+        .await
+        .unwrap();
+    upsert_block(&engine, note, D_BID_BYTES, "new", Some(C_BID_BYTES)).await;
+
+    at_time(&engine, note, t0 + 7200).await;
+    engine
+        .splice_block_text(note, A_BID_BYTES, 9, 0, "!")
+        .await
+        .unwrap();
+    assert_eq!(
+        block_texts(&engine, note).await,
+        vec!["plan trip!", "socks", "new"]
+    );
+    (engine, t0)
+}
+
+#[tokio::test]
+async fn note_at_renders_the_note_as_of_a_past_moment() {
+    let note = [0x61; 16];
+    let (engine, t0) = seed_history(note).await;
+
+    let past = engine.note_at(note, t0 + 60).await.unwrap().unwrap();
+    let blocks = tesela_core::note_tree::parse_note(&past.content).blocks;
+    let texts: Vec<(&str, u16)> = blocks.iter().map(|b| (b.text.as_str(), b.indent)).collect();
+    assert_eq!(texts, vec![("plan", 0), ("pack", 0), ("socks", 1)]);
+    assert_eq!(past.changes_since, 2);
+    assert!(past.last_change_at.is_some_and(|t| t <= t0 + 60));
+
+    let middle = engine.note_at(note, t0 + 3700).await.unwrap().unwrap();
+    assert!(
+        middle.content.contains("plan trip <!--"),
+        "{}",
+        middle.content
+    );
+    assert_eq!(middle.changes_since, 1);
+    let latest = engine.note_at(note, t0 + 7300).await.unwrap().unwrap();
+    assert_eq!(latest.changes_since, 0);
+    assert_eq!(
+        Some(latest.content),
+        engine.render_note_full(note).await,
+        "now is the live note"
+    );
+
+    assert!(matches!(
+        engine.note_at(note, t0 - 3600).await,
+        Err(SyncError::HistoryUnavailable(_))
+    ));
+    assert!(engine.note_at([0x62; 16], t0).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn restore_note_reverts_as_a_forward_change_that_syncs() {
+    let note = [0x63; 16];
+    let (engine, t0) = seed_history(note).await;
+    let peer = LoroEngine::new(
+        DeviceId::from_bytes([0xb7; 16]),
+        Arc::new(Hlc::new(DeviceId::from_bytes([0xb7; 16]))),
+    );
+    let before = engine.export_doc_update(note, None).await.unwrap();
+    peer.import_doc_update(note, &before).await.unwrap();
+    let peer_version = peer.doc_version(note).await.unwrap();
+
+    let past = engine.note_at(note, t0 + 60).await.unwrap().unwrap();
+    assert!(engine.restore_note(note, t0 + 60, None).await.unwrap());
+    assert_eq!(engine.render_note_full(note).await, Some(past.content));
+    assert_eq!(engine.slug_for_note(note).await.as_deref(), Some("trip"));
+    assert!(
+        !engine.restore_note(note, t0 + 60, None).await.unwrap(),
+        "restoring the state it's already in changes nothing"
+    );
+
+    // The revert is an ordinary update on top of what the peer has.
+    let delta = engine
+        .export_doc_update(note, Some(&peer_version))
+        .await
+        .unwrap();
+    peer.import_doc_update(note, &delta).await.unwrap();
+    assert_eq!(
+        block_texts(&peer, note).await,
+        vec!["plan", "pack", "socks"]
+    );
+}
+
+#[tokio::test]
+async fn restore_note_can_revert_just_one_block_subtree() {
+    let note = [0x64; 16];
+    let (engine, t0) = seed_history(note).await;
+
+    assert!(engine
+        .restore_note(note, t0 + 60, Some(B_BID_BYTES))
+        .await
+        .unwrap());
+    let blocks =
+        tesela_core::note_tree::parse_note(&engine.render_note(note).await.unwrap()).blocks;
+    let texts: Vec<(&str, u16)> = blocks.iter().map(|b| (b.text.as_str(), b.indent)).collect();
+    assert_eq!(
+        texts,
+        vec![("plan trip!", 0), ("pack", 0), ("socks", 1), ("new", 0)],
+        "pack and its child come back; edits outside the subtree stay"
+    );
+
+    assert!(matches!(
+        engine.restore_note(note, t0 + 60, Some(D_BID_BYTES)).await,
+        Err(SyncError::HistoryUnavailable(_))
+    ));
+}
@@ -664,6 +664,147 @@ async fn views_persist_across_restart() {
     assert_eq!(e.views_list().await, expected, "registry survives restart");
 }
 
+// ─── Notification state (synced reminder fired/ack/snooze) ──────────
+
+fn notification(
+    id: &str,
+    status: &str,
+    snoozed_until_ms: Option<i64>,
+    at: i64,
+) -> crate::engine::NotificationStateRecord {
+    crate::engine::NotificationStateRecord {
+        id: id.to_string(),
+        status: status.to_string(),
+        snoozed_until_ms,
+        updated_at_ms: at,
+    }
+}
+
+#[tokio::test]
+async fn notification_state_round_trips_and_persists_across_restart() {
+    let tmp = tempfile::tempdir().unwrap();
+    let snap = tmp.path().join("loro");
+    let dev = test_device();
+    {
+        let e = LoroEngine::with_dirs(
+            dev,
+            Arc::new(Hlc::new(dev)),
+            snap.clone(),
+            Some(tmp.path().join("notes")),
+        )
+        .await
+        .unwrap();
+        e.notification_state_upsert(notification("deadline-b1-100", "snoozed", Some(5_000), 1))
+            .await
+            .unwrap();
+        e.notification_state_upsert(notification("scheduled-b2-200", "fired", None, 2))
+            .await
+            .unwrap();
+        // Un-snoozing clears the stale `snoozed_until_ms` field.
+        e.notification_state_upsert(notification("deadline-b1-100", "acknowledged", None, 3))
+            .await
+            .unwrap();
+        assert!(
+            !e.index_entries()
+                .await
+                .iter()
+                .any(|x| x.note_id == hex_id(&NOTIFICATIONS_DOC_ID)),
+            "notification doc is never indexed as a note"
+        );
+    }
+    let e = LoroEngine::with_dirs(dev, Arc::new(Hlc::new(dev)), snap, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        e.notification_states_list().await,
+        vec![
+            notification("deadline-b1-100", "acknowledged", None, 3),
+            notification("scheduled-b2-200", "fired", None, 2),
+        ],
+        "state survives restart, sorted by id"
+    );
+    assert!(e
+        .notification_state_delete("scheduled-b2-200")
+        .await
+        .unwrap());
+    assert!(!e
+        .notification_state_delete("scheduled-b2-200")
+        .await
+        .unwrap());
+    assert_eq!(e.notification_states_list().await.len(), 1);
+}
+
+#[tokio::test]
+async fn notification_ack_on_one_device_reaches_the_other_over_relay() {
+    let dev_a = DeviceId::from_bytes([0xa1; 16]);
+    let dev_b = DeviceId::from_bytes([0xb2; 16]);
+    let a = LoroEngine::new(dev_a, Arc::new(Hlc::new(dev_a)));
+    let b = LoroEngine::new(dev_b, Arc::new(Hlc::new(dev_b)));
+    a.notification_state_upsert(notification("deadline-b1-100", "fired", None, 1))
+        .await
+        .unwrap();
+    ship_relay(&a, &b).await;
+
+    b.notification_state_upsert(notification("deadline-b1-100", "acknowledged", None, 2))
+        .await
+        .unwrap();
+    ship_relay(&b, &a).await;
+
+    assert_eq!(
+        a.notification_states_list().await,
+        b.notification_states_list().await,
+        "engines converge"
+    );
+    assert_eq!(a.notification_states_list().await[0].status, "acknowledged");
+}
+
+#[tokio::test]
+async fn concurrent_first_writes_of_one_notification_both_survive() {
+    let dev_a = DeviceId::from_bytes([0xa1; 16]);
+    let dev_b = DeviceId::from_bytes([0xb2; 16]);
+    let a = LoroEngine::new(dev_a, Arc::new(Hlc::new(dev_a)));
+    let b = LoroEngine::new(dev_b, Arc::new(Hlc::new(dev_b)));
+    // Both scanners fire the same reminder before either sees the other,
+    // then each writes one field of its own. A same-key container race
+    // would drop one device's whole record.
+    for (engine, field) in [(&a, "seen_on_a"), (&b, "seen_on_b")] {
+        engine
+            .notification_state_upsert(notification("deadline-b1-100", "fired", None, 1))
+            .await
+            .unwrap();
+        let doc = engine.doc_for_note_mut(NOTIFICATIONS_DOC_ID).await;
+        let Some(loro::ValueOrContainer::Container(loro::Container::Map(entry))) =
+            doc.get_map("notifications").get("deadline-b1-100")
+        else {
+            panic!("notification entry is a map container");
+        };
+        entry.insert(field, true).unwrap();
+        doc.commit();
+    }
+
+    ship_relay(&a, &b).await;
+    ship_relay(&b, &a).await;
+
+    for engine in [&a, &b] {
+        let doc = engine.lazy_load_doc(NOTIFICATIONS_DOC_ID).await.unwrap();
+        let loro::LoroValue::Map(all) = doc.get_map("notifications").get_deep_value() else {
+            panic!("notifications map");
+        };
+        let Some(loro::LoroValue::Map(entry)) = all.get("deadline-b1-100") else {
+            panic!("merged entry");
+        };
+        assert!(
+            entry.contains_key("seen_on_a") && entry.contains_key("seen_on_b"),
+            "fields from both devices survive: {entry:?}"
+        );
+    }
+    assert_eq!(
+        a.notification_states_list().await,
+        b.notification_states_list().await,
+        "engines converge"
+    );
+}
+
 // -----------------------------------------------------------------
 // Residency audit (tesela-engc.5): lazy-load regression tests
 // (tesela-qql). The full classification table of every walk over
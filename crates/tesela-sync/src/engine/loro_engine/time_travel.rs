@@ -0,0 +1,285 @@
+use super::*;
+use crate::engine::NoteAtTime;
+use loro::event::{Diff, DiffBatch};
+use loro::{ContainerTrait, Frontiers, VersionVector};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use tesela_core::note_tree::FlatBlock;
+
+// ============================================================================
+// Point-in-time views and restore
+// ============================================================================
+//
+// A note's doc already holds its whole edit history, so "the page as of T"
+// is a checkout: every change committed at or before T, closed under its
+// causal deps, rendered from a fork at those frontiers. A restore is
+// `doc.diff(now, then)` applied back onto the live doc as a fresh local
+// change — the same thing `revert_to` does — so it syncs to peers like any
+// other edit and can itself be undone by a later restore. The diff is
+// filtered before it is applied: the root map's identity keys are never
+// reverted, and a subtree restore keeps only the containers that belong to
+// the subtree's block nodes.
+
+/// Root-map keys that name the page rather than describe it. A restore
+/// leaves them as they are: reverting a rename would move the file out from
+/// under its links, and `deleted` / `forward_base` are lifecycle state.
+const IDENTITY_KEYS: &[&str] = &["slug", "title", "page_id", "deleted", "forward_base"];
+
+/// Loro stamps a change with its commit time ROUNDED to the nearest second,
+/// so a change committed during second `t` can carry `t + 1`. A point in
+/// time takes changes stamped up to this much past it, or "now" could miss
+/// the edit that just landed.
+const TIMESTAMP_ROUNDING_SECS: i64 = 1;
+
+/// Where in a doc's history a timestamp lands.
+struct HistoryPoint {
+    frontiers: Frontiers,
+    last_change_at: Option<i64>,
+    changes_since: usize,
+}
+
+impl LoroEngine {
+    /// The note as it stood at `at_secs`. See [`SyncEngine::note_at`].
+    pub async fn note_at(&self, note_id: [u8; 16], at_secs: i64) -> SyncResult<Option<NoteAtTime>> {
+        if Self::is_special_doc(&note_id) {
+            return Ok(None);
+        }
+        let Some(doc) = self.lazy_load_doc(note_id).await else {
+            return Ok(None);
+        };
+        let point = history_point(&doc, at_secs)?;
+        let past = fork_at(&doc, &point.frontiers)?;
+        Ok(Some(NoteAtTime {
+            content: doc_full_markdown(&past),
+            last_change_at: point.last_change_at,
+            changes_since: point.changes_since,
+        }))
+    }
+
+    /// Revert the note, or just the subtree rooted at `block_id`, to its
+    /// state at `at_secs`. See [`SyncEngine::restore_note`].
+    ///
+    /// Shares the [`splice_block_text`](Self::splice_block_text) write tail:
+    /// `commit`, `refresh_note_derived`, persist the snapshot, materialize.
+    pub async fn restore_note(
+        &self,
+        note_id: [u8; 16],
+        at_secs: i64,
+        block_id: Option<[u8; 16]>,
+    ) -> SyncResult<bool> {
+        if Self::is_special_doc(&note_id) {
+            return Err(SyncError::HistoryUnavailable(format!(
+                "{} is not a note",
+                hex_id(&note_id)
+            )));
+        }
+        self.ensure_note_writable(note_id).await?;
+        let apply_lock = self.apply_lock_for_note(note_id).await;
+        let _apply_guard = apply_lock.lock().await;
+        let Some(doc) = self.lazy_load_doc(note_id).await else {
+            return Err(SyncError::HistoryUnavailable(format!(
+                "no history for note {}",
+                hex_id(&note_id)
+            )));
+        };
+        let point = history_point(&doc, at_secs)?;
+        let past = fork_at(&doc, &point.frontiers)?;
+        // Compare renders first: a node recreated by an earlier restore has
+        // a new TreeID, so the raw diff is never empty once one has run.
+        let before = doc_full_markdown(&doc);
+        let subtree = match block_id {
+            Some(bid) => {
+                let then = subtree_blocks(&past, bid).ok_or_else(|| {
+                    SyncError::HistoryUnavailable(format!(
+                        "block {} didn't exist at that time",
+                        hex_id(&bid)
+                    ))
+                })?;
+                let current = subtree_blocks(&doc, bid);
+                if current.as_ref() == Some(&then) {
+                    return Ok(false);
+                }
+                Some(subtree_nodes(
+                    &[&past, &doc],
+                    then.iter().chain(current.iter().flatten()),
+                ))
+            }
+            None if before == doc_full_markdown(&past) => return Ok(false),
+            None => None,
+        };
+
+        let now = doc.state_frontiers();
+        let full = doc
+            .diff(&now, &point.frontiers)
+            .map_err(|e| SyncError::Storage(format!("loro diff: {e}")))?;
+        let root_cid = doc.get_map("root").id();
+        let tree_cid = doc.get_tree("blocks").id();
+        let mut batch = DiffBatch::default();
+        for (cid, diff) in full.iter() {
+            let kept = if *cid == root_cid {
+                // A subtree restore never touches page-level state.
+                match (diff, &subtree) {
+                    (Diff::Map(delta), None) => {
+                        let mut delta = delta.clone();
+                        delta
+                            .updated
+                            .retain(|key, _| !IDENTITY_KEYS.contains(&key.as_ref()));
+                        (!delta.updated.is_empty()).then_some(Diff::Map(delta))
+                    }
+                    _ => None,
+                }
+            } else if *cid == tree_cid {
+                match (diff, &subtree) {
+                    (Diff::Tree(tree_diff), Some(nodes)) => {
+                        let mut tree_diff = tree_diff.clone().into_owned();
+                        tree_diff.diff.retain(|item| nodes.contains(&item.target));
+                        (!tree_diff.diff.is_empty()).then_some(Diff::Tree(Cow::Owned(tree_diff)))
+                    }
+                    _ => Some(diff.clone()),
+                }
+            } else {
+                match &subtree {
+                    Some(nodes) => container_node(&doc, &past, cid)
+                        .filter(|node| nodes.contains(node))
+                        .map(|_| diff.clone()),
+                    None => Some(diff.clone()),
+                }
+            };
+            if let Some(diff) = kept {
+                let _ = batch.push(cid.clone(), diff);
+            }
+        }
+        if batch.iter().next().is_none() {
+            return Ok(false);
+        }
+
+        doc.apply_diff(batch)
+            .map_err(|e| SyncError::Storage(format!("loro apply_diff: {e}")))?;
+        doc.commit();
+        // The past state can predate a twin heal; keep the one-node-per-bid
+        // invariant the rest of the engine relies on.
+        tombstone_duplicate_twins(&doc, note_id);
+        doc.commit();
+        if doc.state_frontiers() != now {
+            self.refresh_note_derived(note_id, &doc).await;
+            if let Some(dir) = self.inner.snapshot_dir.as_ref() {
+                self.save_snapshot_checked(dir, note_id).await?;
+            }
+            if self.inner.materialize_dir.is_some() {
+                self.materialize_note(note_id).await;
+            }
+        }
+        Ok(doc_full_markdown(&doc) != before)
+    }
+}
+
+/// Resolve `at_secs` to frontiers: walk the retained changes in causal
+/// order and take each one committed at or before `at_secs` whose deps (and
+/// same-peer predecessor) were taken too, so a peer's skewed clock can't
+/// produce a version that never existed. Changes without a timestamp
+/// (`0`) count as old. Fails when nothing qualifies — the note didn't exist
+/// yet, or a shallow doc no longer holds that stretch of history.
+fn history_point(doc: &LoroDoc, at_secs: i64) -> SyncResult<HistoryPoint> {
+    let mut changes: Vec<loro::ChangeMeta> = Vec::new();
+    let heads: Vec<loro::ID> = doc.oplog_frontiers().iter().collect();
+    let _ = doc.travel_change_ancestors(&heads, &mut |meta| {
+        changes.push(meta);
+        std::ops::ControlFlow::Continue(())
+    });
+    changes.sort_by_key(|meta| (meta.lamport, meta.id.peer));
+
+    let mut vv = if doc.is_shallow() {
+        doc.shallow_since_vv().to_vv()
+    } else {
+        VersionVector::default()
+    };
+    let mut taken = 0usize;
+    let mut last_change_at = None;
+    for meta in &changes {
+        let contiguous = vv.get(&meta.id.peer).copied().unwrap_or(0) == meta.id.counter;
+        if meta.timestamp > at_secs + TIMESTAMP_ROUNDING_SECS
+            || !contiguous
+            || !meta.deps.iter().all(|d| vv.includes_id(d))
+        {
+            continue;
+        }
+        vv.set_end(loro::ID::new(
+            meta.id.peer,
+            meta.id.counter + meta.len as i32,
+        ));
+        taken += 1;
+        if meta.timestamp > 0 {
+            last_change_at = last_change_at.max(Some(meta.timestamp));
+        }
+    }
+    if taken == 0 {
+        let why = if doc.is_shallow() {
+            "its history before then is no longer retained"
+        } else {
+            "the note didn't exist yet"
+        };
+        return Err(SyncError::HistoryUnavailable(format!(
+            "cannot show the note as of {at_secs}: {why}"
+        )));
+    }
+    Ok(HistoryPoint {
+        frontiers: doc.vv_to_frontiers(&vv),
+        last_change_at,
+        changes_since: changes.len() - taken,
+    })
+}
+
+fn fork_at(doc: &LoroDoc, frontiers: &Frontiers) -> SyncResult<LoroDoc> {
+    doc.fork_at(frontiers)
+        .map_err(|e| SyncError::Storage(format!("loro fork_at: {e}")))
+}
+
+/// The subtree rooted at `block_id` as rendered from `version`: in the flat
+/// model, the block plus the run of deeper-indented blocks that follows it.
+fn subtree_blocks(version: &LoroDoc, block_id: [u8; 16]) -> Option<Vec<FlatBlock>> {
+    let root = uuid::Uuid::from_bytes(block_id);
+    let blocks = tesela_core::note_tree::parse_note(&doc_full_markdown(version)).blocks;
+    let start = blocks.iter().position(|b| b.id == root)?;
+    let indent = blocks[start].indent;
+    let end = blocks[start + 1..]
+        .iter()
+        .position(|b| b.indent <= indent)
+        .map_or(blocks.len(), |n| start + 1 + n);
+    Some(blocks[start..end].to_vec())
+}
+
+/// Every live tree node, across `versions`, carrying one of `blocks`' ids.
+fn subtree_nodes<'a>(
+    versions: &[&LoroDoc],
+    blocks: impl Iterator<Item = &'a FlatBlock>,
+) -> HashSet<TreeID> {
+    let bids: HashSet<String> = blocks.map(|b| b.id.simple().to_string()).collect();
+    let mut nodes = HashSet::new();
+    for version in versions {
+        let tree = version.get_tree("blocks");
+        for node in tree.nodes() {
+            if matches!(tree.is_node_deleted(&node), Ok(true)) {
+                continue;
+            }
+            if read_meta_str(&tree, node, "block_id").is_some_and(|bid| bids.contains(&bid)) {
+                nodes.insert(node);
+            }
+        }
+    }
+    nodes
+}
+
+/// The tree node a container hangs off (a block's meta map, its
+/// `text_seq`, its props), resolved in the live doc or — for a node deleted
+/// since — in the past fork.
+fn container_node(doc: &LoroDoc, past: &LoroDoc, cid: &loro::ContainerID) -> Option<TreeID> {
+    [doc, past].into_iter().find_map(|version| {
+        version
+            .get_path_to_container(cid)?
+            .into_iter()
+            .find_map(|(_, index)| match index {
+                loro::Index::Node(node) => Some(node),
+                _ => None,
+            })
+    })
+}
@@ -11,7 +11,7 @@ pub use hydration::{hydrate_note, EngineImportNoteWriter};
 pub use loro_engine::LoroEngine;
 
 use crate::device::DeviceId;
-use crate::error::SyncResult;
+use crate::error::{SyncError, SyncResult};
 use crate::oplog::op::{ContentHash, OpPayload};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -139,6 +139,15 @@ pub struct RelayApplyReport {
     /// NOT ack/advance its relay cursor past the carrying envelope without
     /// a retry/catch-up policy, or the update is skipped forever.
     pub failed: Vec<([u8; 16], String)>,
+    /// Notes outside this device's sync scope ([`DeviceSyncScope`]): either
+    /// already excluded and skipped, or evicted right after this apply made
+    /// them match an exclusion rule. Not failures — the caller may advance
+    /// its cursor past them — but not held either.
+    pub excluded: Vec<[u8; 16]>,
+    /// Previously-excluded notes this batch's scope-registry change brought
+    /// back into scope. The engine no longer holds their history, so the
+    /// caller must fetch them with a snapshot catch-up.
+    pub readmitted: Vec<[u8; 16]>,
 }
 
 impl RelayApplyReport {
@@ -207,6 +216,99 @@ pub const MAX_CATCHUP_ATTEMPTS: u32 = 6;
 /// passes after the previous one (tesela-c7s F3).
 pub const CATCHUP_BACKOFF_SHIFT_CAP: u32 = 4;
 
+/// How urgently a [`SyncAnomaly`] needs attention. Orders `Info < Warning <
+/// Error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalySeverity {
+    /// Heals on its own at the next relay tick; listed for visibility.
+    Info,
+    /// Degraded but recoverable — a repair is queued or available.
+    Warning,
+    /// Sync can't make progress on the note without intervention.
+    Error,
+}
+
+/// Class of a [`SyncAnomaly`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    /// Garbled blocks: a block id lives on more than one live tree node —
+    /// the residue of disjoint-lineage authoring (tesela-49d).
+    #[serde(alias = "disjoint_twins")]
+    GarbledBlocks,
+    /// More than one live document claims the same page in the directory.
+    DirectoryConflict,
+    /// An inbound update is stuck behind a causal gap ([`PendingImport`]).
+    CausalGap,
+    /// A dirty note's broadcast cursor is stale-ahead or undecodable
+    /// (tesela-c7s item 3).
+    OutboundStrand,
+}
+
+/// A deterministic repair that clears an anomaly — the same routines the
+/// relay apply path and the CLI repairs run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncRepair {
+    /// Collapse garbled blocks' twins to the global-max `TreeID` survivor
+    /// ([`SyncEngine::heal_disjoint_twins`]) — what `tesela
+    /// repair-garbled-blocks --apply` runs.
+    #[serde(alias = "heal_twins")]
+    RepairGarbledBlocks,
+    /// Re-arm the note's causal-gap ledger entry and queue an
+    /// authoritative-snapshot catch-up on the relay tick.
+    SnapshotCatchup,
+}
+
+/// One anomaly the engine knows about for one document
+/// ([`SyncEngine::sync_anomalies`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncAnomaly {
+    /// 32-char hex id of the affected document.
+    pub note_id: String,
+    /// The page's slug, when the directory binds one.
+    pub slug: Option<String>,
+    /// What is wrong.
+    pub kind: AnomalyKind,
+    /// How urgently it needs attention.
+    pub severity: AnomalySeverity,
+    /// Human-readable specifics (twin count, stalled pass, …).
+    pub detail: String,
+    /// The repair that clears it, when one exists. `None` means it heals
+    /// on its own or needs a manual edit.
+    pub repair: Option<SyncRepair>,
+}
+
+/// The Loro change that last touched one live block
+/// ([`SyncEngine::block_blame`]): its text, properties, or its place in the
+/// tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockBlame {
+    /// 32-char hex block id.
+    pub block_id: String,
+    /// Loro PeerID of the author. Map it back to a device with
+    /// [`loro_peer_id`](crate::loro_peer_id).
+    pub peer: u64,
+    /// Lamport clock of the op — orders blames causally.
+    pub lamport: u32,
+    /// Unix seconds the change was committed, when the author recorded
+    /// timestamps (every engine-authored change does).
+    pub timestamp: Option<i64>,
+}
+
+/// A note as it stood at a past moment ([`SyncEngine::note_at`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteAtTime {
+    /// The full `.md` (frontmatter + body) at that moment.
+    pub content: String,
+    /// Unix seconds of the newest change it includes, when that change
+    /// carried a timestamp.
+    pub last_change_at: Option<i64>,
+    /// Changes recorded after it; `0` means the note hasn't changed since.
+    pub changes_since: usize,
+}
+
 /// The core sync engine trait. Post-flag-day (2026-05-29) the only
 /// implementation is [`LoroEngine`]; the trait remains as the boundary
 /// the server's `Arc<dyn SyncEngine>` and the FFI hold. The legacy
@@ -323,6 +425,62 @@ pub trait SyncEngine: Send + Sync {
         0
     }
 
+    /// Every anomaly the engine currently knows about — garbled blocks,
+    /// directory conflicts, causal gaps and outbound strands — most severe
+    /// first. Read-only: unlike
+    /// [`notes_needing_snapshot_catchup`](Self::notes_needing_snapshot_catchup)
+    /// it never advances escalation accounting. Default empty.
+    async fn sync_anomalies(&self) -> Vec<SyncAnomaly> {
+        Vec::new()
+    }
+
+    /// Collapse every note's disjoint twins to the deterministic survivor
+    /// (the [`SyncRepair::RepairGarbledBlocks`] action). Returns the `(note_id,
+    /// block_id_hex)` pairs collapsed. Default empty.
+    async fn heal_disjoint_twins(&self) -> Vec<([u8; 16], String)> {
+        Vec::new()
+    }
+
+    /// Clear a causal-gap entry's escalation backoff (including a
+    /// permanent-gap verdict) so the next relay tick escalates it to a
+    /// snapshot catch-up again. Returns `false` when the note isn't
+    /// pending. Default `false`.
+    async fn rearm_snapshot_catchup(&self, _note_id: [u8; 16]) -> bool {
+        false
+    }
+
+    /// Attribute each live block of a note to the Loro change that last
+    /// modified it. Blocks whose last change predates a shallow snapshot's
+    /// horizon are absent. `None` when the engine doesn't track the note.
+    /// Default `None`.
+    async fn block_blame(&self, _note_id: [u8; 16]) -> Option<Vec<BlockBlame>> {
+        None
+    }
+
+    /// Reconstruct a note as of `at_secs` (unix seconds) from its own edit
+    /// history. `Ok(None)` when the engine doesn't track the note;
+    /// [`SyncError::HistoryUnavailable`] when it didn't exist yet or that
+    /// stretch of history is no longer retained. Default `Ok(None)`.
+    async fn note_at(&self, _note_id: [u8; 16], _at_secs: i64) -> SyncResult<Option<NoteAtTime>> {
+        Ok(None)
+    }
+
+    /// Revert a note — or only the subtree rooted at `block_id` — to its
+    /// state as of `at_secs`, recorded as a new forward change so the
+    /// revert syncs like any other edit. The page keeps its identity
+    /// (slug, title, id). Returns whether anything changed. Default:
+    /// [`SyncError::HistoryUnavailable`].
+    async fn restore_note(
+        &self,
+        _note_id: [u8; 16],
+        _at_secs: i64,
+        _block_id: Option<[u8; 16]>,
+    ) -> SyncResult<bool> {
+        Err(SyncError::HistoryUnavailable(
+            "this engine keeps no note history".into(),
+        ))
+    }
+
     /// Apply a batch of inbound per-note Loro updates from the relay
     /// (idempotent + commutative). Returns a per-note [`RelayApplyReport`]
     /// — which notes applied cleanly, which were left PENDING by Loro
@@ -553,6 +711,68 @@ pub trait SyncEngine: Send + Sync {
     async fn page_directory_upsert(&self, _record: PageDirectoryEntry) -> SyncResult<()> {
         Ok(())
     }
+
+    /// Every reminder delivery record from the synced notification-state
+    /// doc, sorted by id. Default empty; LoroEngine overrides.
+    async fn notification_states_list(&self) -> Vec<NotificationStateRecord> {
+        Vec::new()
+    }
+
+    /// Create or update one reminder's delivery state (field-level LWW, like
+    /// the views registry). Default no-op; LoroEngine overrides.
+    async fn notification_state_upsert(&self, _record: NotificationStateRecord) -> SyncResult<()> {
+        Ok(())
+    }
+
+    /// Drop a reminder's delivery record. `Ok(false)` when no such record
+    /// exists. Default `Ok(false)`; LoroEngine overrides.
+    async fn notification_state_delete(&self, _id: &str) -> SyncResult<bool> {
+        Ok(false)
+    }
+
+    /// Every device's selective-sync rules, sorted by device. Default empty;
+    /// LoroEngine overrides.
+    async fn sync_scopes_list(&self) -> Vec<DeviceSyncScope> {
+        Vec::new()
+    }
+
+    /// Create or replace one device's selective-sync rules. Does not apply
+    /// them locally — call [`Self::reconcile_sync_scope`] afterwards. Default
+    /// no-op; LoroEngine overrides.
+    async fn sync_scope_upsert(&self, _scope: DeviceSyncScope) -> SyncResult<()> {
+        Ok(())
+    }
+
+    /// Drop one device's selective-sync rules. `Ok(false)` when it had none.
+    /// Default `Ok(false)`; LoroEngine overrides.
+    async fn sync_scope_delete(&self, _device: &str) -> SyncResult<bool> {
+        Ok(false)
+    }
+
+    /// Notes this device currently keeps out of its replica.
+    async fn scope_excluded_notes(&self) -> Vec<[u8; 16]> {
+        Vec::new()
+    }
+
+    /// Evict `note_id` if it is held and now falls outside this device's
+    /// scope. Returns whether the note is out of scope (already excluded or
+    /// just evicted). Default `false`.
+    async fn enforce_sync_scope(&self, _note_id: [u8; 16]) -> bool {
+        false
+    }
+
+    /// Re-apply this device's scope to every note: evict held notes that now
+    /// match a rule and return the previously-excluded notes that no longer
+    /// do (the caller re-fetches those). Default empty.
+    async fn reconcile_sync_scope(&self) -> Vec<[u8; 16]> {
+        Vec::new()
+    }
+
+    /// Drain the notes evicted since the last call, from any path (relay
+    /// apply, catch-up, reconcile). Default empty.
+    async fn take_scope_evictions(&self) -> Vec<ScopeEviction> {
+        Vec::new()
+    }
 }
 
 /// One immutable page binding as projected from the synced directory.
@@ -581,6 +801,26 @@ pub struct PageDirectoryEntry {
     pub conflict: bool,
 }
 
+/// Matcher context for DSL queries over `directory`. The synced directory
+/// is the sole authority for Node RHS resolution; SQLite remains a
+/// rebuildable query projection and receives it only as additive matcher
+/// context. Conflicted bindings resolve like deleted ones.
+pub fn page_query_context(directory: Vec<PageDirectoryEntry>) -> tesela_core::query::QueryContext {
+    tesela_core::query::QueryContext {
+        pages: directory
+            .into_iter()
+            .map(|entry| tesela_core::query::QueryPage {
+                page_id: entry.page_id,
+                slug: entry.slug,
+                title: entry.title,
+                aliases: entry.aliases,
+                deleted: entry.deleted || entry.conflict,
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
 /// One saved view in the synced views registry doc (saved-views spec,
 /// 2026-06-10). The registry is ONE dedicated always-resident Loro doc
 /// ([`loro_engine::VIEWS_DOC_ID`]) that syncs across devices exactly like
@@ -654,6 +894,89 @@ pub struct TableColumnConfig {
     pub sort_dir: Option<String>,
 }
 
+/// Delivery state of one reminder in the synced notification-state doc
+/// ([`loro_engine::NOTIFICATIONS_DOC_ID`]). Keyed by the scanner's stable
+/// notification id (kind + block + fire target), so every device computes
+/// the same id for the same reminder and acknowledging it on one device
+/// silences it on the others once the doc syncs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationStateRecord {
+    /// Stable notification id, e.g. `deadline-<block_id>-<unix_secs>`.
+    pub id: String,
+    /// "fired" | "acknowledged" | "snoozed". A plain string validated at the
+    /// boundary, matching `ViewRecord::display_mode`.
+    pub status: String,
+    /// When a snoozed reminder may fire again (unix millis). Only
+    /// meaningful when `status == "snoozed"`.
+    #[serde(default)]
+    pub snoozed_until_ms: Option<i64>,
+    /// Wall-clock time of the last state change (unix millis). Drives the
+    /// scanner's pruning of long-settled records.
+    pub updated_at_ms: i64,
+}
+
+/// One device's selective-sync rules in the synced scope registry
+/// ([`loro_engine::SYNC_SCOPES_DOC_ID`]). A note matching ANY rule is kept
+/// off that device: its updates are skipped on apply and any held copy is
+/// evicted. Matching is case-insensitive. The page directory is never
+/// scoped, so excluded pages still resolve as link targets.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceSyncScope {
+    /// 32-char hex [`DeviceId`] the rules apply to.
+    pub device: String,
+    /// Tags (frontmatter, `tags::` property or inline `#tag`), without `#`.
+    #[serde(default)]
+    pub exclude_tags: Vec<String>,
+    /// Page types (`type::` property or frontmatter `type:`).
+    #[serde(default)]
+    pub exclude_types: Vec<String>,
+    /// Slug prefixes, e.g. `journal-` or `work/`.
+    #[serde(default)]
+    pub exclude_slug_prefixes: Vec<String>,
+    /// Wall-clock time of the last change (unix millis).
+    #[serde(default)]
+    pub updated_at_ms: i64,
+}
+
+impl DeviceSyncScope {
+    /// Whether a note with this slug, tag set and page type falls outside
+    /// the scope.
+    pub fn excludes(&self, slug: &str, tags: &[String], note_type: Option<&str>) -> bool {
+        let slug = slug.to_lowercase();
+        self.exclude_slug_prefixes
+            .iter()
+            .any(|p| !p.is_empty() && slug.starts_with(&p.to_lowercase()))
+            || self.exclude_tags.iter().any(|rule| {
+                let rule = rule.trim_start_matches('#');
+                tags.iter()
+                    .any(|t| t.trim_start_matches('#').eq_ignore_ascii_case(rule))
+            })
+            || note_type.is_some_and(|ty| {
+                self.exclude_types
+                    .iter()
+                    .any(|rule| rule.eq_ignore_ascii_case(ty.trim()))
+            })
+    }
+
+    /// No rules at all — equivalent to having no scope record.
+    pub fn is_empty(&self) -> bool {
+        self.exclude_tags.is_empty()
+            && self.exclude_types.is_empty()
+            && self.exclude_slug_prefixes.is_empty()
+    }
+}
+
+/// A held note the engine dropped because it fell outside this device's
+/// sync scope. Carries the slug it was materialized under so the caller can
+/// drop its own projections (search index, live clients) of that page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeEviction {
+    /// The evicted note.
+    pub note_id: [u8; 16],
+    /// Its slug at eviction time, when it had one.
+    pub slug: Option<String>,
+}
+
 /// One note's entry in the Loro index doc.
 #[derive(Debug, Clone)]
 pub struct IndexEntry {
@@ -62,6 +62,11 @@ pub enum SyncError {
         message: String,
     },
 
+    /// The requested point in a note's history can't be reconstructed: the
+    /// note didn't exist yet, or the changes before it are no longer retained.
+    #[error("history unavailable: {0}")]
+    HistoryUnavailable(String),
+
     /// Transport-level error (connection refused, channel closed, etc.).
     #[error("transport error: {0}")]
     Transport(String),
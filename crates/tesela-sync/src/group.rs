@@ -3,7 +3,9 @@
 //! devices, a Tesela install has exactly one group.
 
 use crate::device::DeviceId;
+use crate::error::SyncResult;
 use serde::{Deserialize, Serialize};
+use sqlx::Row;
 use uuid::Uuid;
 
 /// 16-byte group identifier. Generated at group genesis.
@@ -42,4 +44,118 @@ pub struct GroupMember {
     pub display_name: Option<String>,
     /// Wall-clock time when this member was added, millis since epoch.
     pub added_at_millis: i64,
+    /// X25519 key-agreement public key, 32 bytes, from the member's
+    /// announcement. Empty until the member has announced; a member
+    /// without one cannot be granted a rotated group key (see
+    /// [`crate::crypto::rotation`]).
+    #[serde(default)]
+    pub kx_pubkey: Vec<u8>,
+}
+
+/// All members of `group_id`, oldest first.
+pub async fn list_members(
+    pool: &sqlx::SqlitePool,
+    group_id: GroupId,
+) -> SyncResult<Vec<GroupMember>> {
+    let rows = sqlx::query(
+        "SELECT device_id, ed25519_pubkey, display_name, added_at, kx_pubkey \
+         FROM group_members WHERE group_id = ? ORDER BY added_at, device_id",
+    )
+    .bind(&group_id.as_bytes()[..])
+    .fetch_all(pool)
+    .await?;
+    let mut out = Vec::with_capacity(rows.len());
+    for row in rows {
+        let device: Vec<u8> = row.get("device_id");
+        let Ok(device) = <[u8; 16]>::try_from(device.as_slice()) else {
+            tracing::warn!("group_members: skipping row with malformed device_id");
+            continue;
+        };
+        out.push(GroupMember {
+            group_id,
+            device_id: DeviceId::from_bytes(device),
+            ed25519_pubkey: row.get("ed25519_pubkey"),
+            display_name: row.get("display_name"),
+            added_at_millis: row.get("added_at"),
+            kx_pubkey: row
+                .get::<Option<Vec<u8>>, _>("kx_pubkey")
+                .unwrap_or_default(),
+        });
+    }
+    Ok(out)
+}
+
+/// Record a member, or refresh its display name. The key-agreement key is
+/// trust-on-first-use: once a device has a non-empty `kx_pubkey`, a later
+/// announcement carrying a different one is ignored (and reported as
+/// `false`) so a member can't be silently re-keyed by whoever else holds
+/// the group key. Remove the member first to accept a genuinely new key.
+pub async fn upsert_member(pool: &sqlx::SqlitePool, member: &GroupMember) -> SyncResult<bool> {
+    let mut tx = pool.begin().await?;
+    let existing: Option<Option<Vec<u8>>> = sqlx::query_scalar(
+        "SELECT kx_pubkey FROM group_members WHERE group_id = ? AND device_id = ?",
+    )
+    .bind(&member.group_id.as_bytes()[..])
+    .bind(&member.device_id.as_bytes()[..])
+    .fetch_optional(&mut *tx)
+    .await?;
+    let accepted = match existing {
+        None => {
+            sqlx::query(
+                "INSERT INTO group_members \
+                 (group_id, device_id, ed25519_pubkey, display_name, added_at, kx_pubkey) \
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&member.group_id.as_bytes()[..])
+            .bind(&member.device_id.as_bytes()[..])
+            .bind(&member.ed25519_pubkey)
+            .bind(&member.display_name)
+            .bind(member.added_at_millis)
+            .bind(&member.kx_pubkey)
+            .execute(&mut *tx)
+            .await?;
+            true
+        }
+        Some(stored) => {
+            let stored = stored.unwrap_or_default();
+            let pinned = !stored.is_empty();
+            if pinned && !member.kx_pubkey.is_empty() && stored != member.kx_pubkey {
+                false
+            } else {
+                let kx = if pinned {
+                    stored
+                } else {
+                    member.kx_pubkey.clone()
+                };
+                sqlx::query(
+                    "UPDATE group_members SET display_name = COALESCE(?, display_name), \
+                     kx_pubkey = ? WHERE group_id = ? AND device_id = ?",
+                )
+                .bind(&member.display_name)
+                .bind(&kx)
+                .bind(&member.group_id.as_bytes()[..])
+                .bind(&member.device_id.as_bytes()[..])
+                .execute(&mut *tx)
+                .await?;
+                true
+            }
+        }
+    };
+    tx.commit().await?;
+    Ok(accepted)
+}
+
+/// Drop a member. Returns `true` if a row was removed. Removal is what
+/// excludes a device from the next key epoch.
+pub async fn remove_member(
+    pool: &sqlx::SqlitePool,
+    group_id: GroupId,
+    device_id: DeviceId,
+) -> SyncResult<bool> {
+    let res = sqlx::query("DELETE FROM group_members WHERE group_id = ? AND device_id = ?")
+        .bind(&group_id.as_bytes()[..])
+        .bind(&device_id.as_bytes()[..])
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected() > 0)
 }
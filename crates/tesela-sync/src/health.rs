@@ -0,0 +1,310 @@
+//! Sync-incident history.
+//!
+//! [`SyncEngine::sync_anomalies`](crate::SyncEngine::sync_anomalies) is a
+//! point-in-time view; this module turns successive scans into a history.
+//! Each scan is diffed against the incidents still open in the log: a new
+//! `(note, kind)` pair opens an incident, a changed severity escalates it,
+//! and a pair that no longer shows up resolves it. Repairs are recorded as
+//! their own events, so the log reads as "what went wrong, what was done,
+//! when it cleared".
+//!
+//! The log is one JSON array at `.tesela/sync_incidents.json`, rewritten
+//! tmp-then-rename and capped at [`INCIDENT_LOG_CAPACITY`] events (oldest
+//! dropped). It is per-device and never synced. The server and the CLI's
+//! `sync doctor` write the same file; the server holds the mosaic lock
+//! whenever the CLI can't, so the two never interleave.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::engine::{AnomalyKind, AnomalySeverity, SyncAnomaly, SyncRepair};
+use crate::error::{SyncError, SyncResult};
+
+/// Events kept in the incident log.
+pub const INCIDENT_LOG_CAPACITY: usize = 500;
+
+/// What happened to an incident.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentEvent {
+    /// A scan found an anomaly that wasn't open.
+    Opened,
+    /// An open anomaly's severity changed.
+    Escalated,
+    /// A repair ran against it.
+    Repaired,
+    /// A scan no longer found it.
+    Resolved,
+}
+
+/// One entry in the incident log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncIncident {
+    /// Wall-clock time of the event (unix millis).
+    pub at_ms: i64,
+    /// What happened.
+    pub event: IncidentEvent,
+    /// 32-char hex id of the affected document.
+    pub note_id: String,
+    /// The page's slug at the time, when known.
+    pub slug: Option<String>,
+    /// The anomaly class.
+    pub kind: AnomalyKind,
+    /// Severity at the time of the event.
+    pub severity: AnomalySeverity,
+    /// The anomaly's detail, or what the repair did.
+    pub detail: String,
+    /// The repair that ran (`Repaired` events only).
+    #[serde(default)]
+    pub repair: Option<SyncRepair>,
+}
+
+/// The on-disk incident log for one mosaic.
+pub struct IncidentLog {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl IncidentLog {
+    /// The log at `<mosaic>/.tesela/sync_incidents.json`.
+    pub fn for_mosaic(mosaic_root: &Path) -> Self {
+        Self::at(mosaic_root.join(".tesela").join("sync_incidents.json"))
+    }
+
+    /// A log at an explicit path.
+    pub fn at(path: PathBuf) -> Self {
+        IncidentLog {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Most recent events first, at most `limit`.
+    pub async fn recent(&self, limit: usize) -> Vec<SyncIncident> {
+        let _guard = self.lock.lock().await;
+        let mut events = self.load().await;
+        events.reverse();
+        events.truncate(limit);
+        events
+    }
+
+    /// Diff a scan against the open incidents and append the resulting
+    /// `Opened` / `Escalated` / `Resolved` events. Returns just the
+    /// appended events (empty when nothing changed).
+    pub async fn record_scan(&self, anomalies: &[SyncAnomaly]) -> SyncResult<Vec<SyncIncident>> {
+        let _guard = self.lock.lock().await;
+        let mut events = self.load().await;
+        let open = open_incidents(&events);
+        let now = now_millis();
+        let mut appended = Vec::new();
+        for anomaly in anomalies {
+            let event = match open.get(&(anomaly.note_id.as_str(), anomaly.kind)) {
+                None => IncidentEvent::Opened,
+                Some(prev) if prev.severity != anomaly.severity => IncidentEvent::Escalated,
+                Some(_) => continue,
+            };
+            appended.push(SyncIncident {
+                at_ms: now,
+                event,
+                note_id: anomaly.note_id.clone(),
+                slug: anomaly.slug.clone(),
+                kind: anomaly.kind,
+                severity: anomaly.severity,
+                detail: anomaly.detail.clone(),
+                repair: None,
+            });
+        }
+        let mut resolved: Vec<&SyncIncident> = open
+            .values()
+            .filter(|prev| {
+                !anomalies
+                    .iter()
+                    .any(|a| a.note_id == prev.note_id && a.kind == prev.kind)
+            })
+            .copied()
+            .collect();
+        resolved.sort_by(|a, b| a.note_id.cmp(&b.note_id));
+        for prev in resolved {
+            appended.push(SyncIncident {
+                at_ms: now,
+                event: IncidentEvent::Resolved,
+                repair: None,
+                ..prev.clone()
+            });
+        }
+        if appended.is_empty() {
+            return Ok(appended);
+        }
+        events.extend(appended.iter().cloned());
+        self.save(events).await?;
+        Ok(appended)
+    }
+
+    /// Append a `Repaired` event for `anomaly`.
+    pub async fn record_repair(
+        &self,
+        anomaly: &SyncAnomaly,
+        repair: SyncRepair,
+        detail: String,
+    ) -> SyncResult<SyncIncident> {
+        let _guard = self.lock.lock().await;
+        let mut events = self.load().await;
+        let incident = SyncIncident {
+            at_ms: now_millis(),
+            event: IncidentEvent::Repaired,
+            note_id: anomaly.note_id.clone(),
+            slug: anomaly.slug.clone(),
+            kind: anomaly.kind,
+            severity: anomaly.severity,
+            detail,
+            repair: Some(repair),
+        };
+        events.push(incident.clone());
+        self.save(events).await?;
+        Ok(incident)
+    }
+
+    /// The whole log, oldest first. A missing or unreadable file reads as
+    /// empty — the history is best-effort, like the pending-import ledger.
+    async fn load(&self) -> Vec<SyncIncident> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                tracing::warn!(
+                    "tesela-sync: incident log {} unreadable ({e}); starting fresh",
+                    self.path.display()
+                );
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    async fn save(&self, mut events: Vec<SyncIncident>) -> SyncResult<()> {
+        if events.len() > INCIDENT_LOG_CAPACITY {
+            events.drain(..events.len() - INCIDENT_LOG_CAPACITY);
+        }
+        let io = |what: &str, e: std::io::Error| {
+            SyncError::Other(format!("incident log {what} {}: {e}", self.path.display()))
+        };
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| io("create dir for", e))?;
+        }
+        let bytes = serde_json::to_vec_pretty(&events)
+            .map_err(|e| SyncError::Other(format!("encode incident log: {e}")))?;
+        let tmp = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, &bytes)
+            .await
+            .map_err(|e| io("write", e))?;
+        tokio::fs::rename(&tmp, &self.path)
+            .await
+            .map_err(|e| io("rename", e))?;
+        Ok(())
+    }
+}
+
+/// The latest event per `(note, kind)` whose incident is still open.
+fn open_incidents(events: &[SyncIncident]) -> HashMap<(&str, AnomalyKind), &SyncIncident> {
+    let mut latest: HashMap<(&str, AnomalyKind), &SyncIncident> = HashMap::new();
+    for event in events {
+        match event.event {
+            // A repair doesn't close anything — the next scan decides.
+            IncidentEvent::Repaired => {}
+            IncidentEvent::Resolved => {
+                latest.remove(&(event.note_id.as_str(), event.kind));
+            }
+            IncidentEvent::Opened | IncidentEvent::Escalated => {
+                latest.insert((event.note_id.as_str(), event.kind), event);
+            }
+        }
+    }
+    latest
+}
+
+fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anomaly(note: &str, kind: AnomalyKind, severity: AnomalySeverity) -> SyncAnomaly {
+        SyncAnomaly {
+            note_id: note.into(),
+            slug: Some(format!("slug-{note}")),
+            kind,
+            severity,
+            detail: "detail".into(),
+            repair: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn scans_open_escalate_and_resolve_incidents() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log = IncidentLog::for_mosaic(tmp.path());
+        let gap = anomaly("aa", AnomalyKind::CausalGap, AnomalySeverity::Warning);
+        let twins = anomaly("bb", AnomalyKind::GarbledBlocks, AnomalySeverity::Warning);
+
+        let first = log
+            .record_scan(&[gap.clone(), twins.clone()])
+            .await
+            .unwrap();
+        assert_eq!(first.len(), 2);
+        assert!(first.iter().all(|e| e.event == IncidentEvent::Opened));
+        assert!(
+            log.record_scan(&[gap.clone(), twins.clone()])
+                .await
+                .unwrap()
+                .is_empty(),
+            "an unchanged scan records nothing"
+        );
+
+        log.record_repair(
+            &twins,
+            SyncRepair::RepairGarbledBlocks,
+            "collapsed 1".into(),
+        )
+        .await
+        .unwrap();
+        let exhausted = SyncAnomaly {
+            severity: AnomalySeverity::Error,
+            ..gap.clone()
+        };
+        let next = log.record_scan(&[exhausted]).await.unwrap();
+        assert_eq!(
+            next.iter().map(|e| (e.event, e.kind)).collect::<Vec<_>>(),
+            vec![
+                (IncidentEvent::Escalated, AnomalyKind::CausalGap),
+                (IncidentEvent::Resolved, AnomalyKind::GarbledBlocks),
+            ]
+        );
+
+        let history = log.recent(10).await;
+        assert_eq!(history.len(), 5);
+        assert_eq!(history[0].event, IncidentEvent::Resolved, "newest first");
+        assert_eq!(history[2].repair, Some(SyncRepair::RepairGarbledBlocks));
+
+        // The twins re-appearing is a fresh incident.
+        let again = log.record_scan(&[twins]).await.unwrap();
+        assert_eq!(again[0].event, IncidentEvent::Opened);
+        assert_eq!(again[1].event, IncidentEvent::Resolved);
+    }
+
+    #[test]
+    fn logs_written_under_the_twin_names_still_read() {
+        let kind: AnomalyKind = serde_json::from_str("\"disjoint_twins\"").unwrap();
+        assert_eq!(kind, AnomalyKind::GarbledBlocks);
+        let repair: SyncRepair = serde_json::from_str("\"heal_twins\"").unwrap();
+        assert_eq!(repair, SyncRepair::RepairGarbledBlocks);
+        assert_eq!(
+            serde_json::to_string(&SyncRepair::RepairGarbledBlocks).unwrap(),
+            "\"repair_garbled_blocks\""
+        );
+    }
+}
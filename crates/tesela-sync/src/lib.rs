@@ -38,6 +38,7 @@ pub mod discovery;
 pub mod engine;
 pub mod error;
 pub mod group;
+pub mod health;
 pub mod hlc;
 pub mod oplog;
 pub mod recovery;
@@ -58,23 +59,31 @@ pub use crypto::keys::{
 pub use crypto::pairing::{
     decode as decode_pairing_code, encode as encode_pairing_code, PairingCode,
 };
+pub use crypto::share::{
+    decode as decode_share_code, encode as encode_share_code, ShareCode, SHARE_CODE_VERSION,
+};
 pub use device::{DeviceId, DeviceMetadata};
 pub use discovery::{DiscoveredPeer, LanDiscovery, TESELA_SERVICE_TYPE};
 pub use engine::loro_engine::{
-    is_special_doc, LoroEngine, INBOX_DEFAULT_DSL, INBOX_VIEW_ID, PAGE_DIRECTORY_DOC_ID,
-    SPECIAL_DOC_IDS, VIEWS_DOC_ID,
+    is_special_doc, loro_peer_id, LoroEngine, INBOX_DEFAULT_DSL, INBOX_VIEW_ID,
+    NOTIFICATIONS_DOC_ID, PAGE_DIRECTORY_DOC_ID, SPECIAL_DOC_IDS, SYNC_SCOPES_DOC_ID, VIEWS_DOC_ID,
 };
 pub use engine::{
-    hydrate_note, AppliedChanges, BlockRelocationOutcome, BlockRelocationRequest,
-    BlockRelocationStatus, EngineImportNoteWriter, LocalCursor, MovePlacement, PageDirectoryEntry,
-    PeerCursor, PendingImport, RelayApplyReport, RelocatedNoteVersion, RelocationNoteSeed,
-    SyncEngine, TableColumnConfig, ViewRecord,
+    hydrate_note, page_query_context, AnomalyKind, AnomalySeverity, AppliedChanges, BlockBlame,
+    BlockRelocationOutcome, BlockRelocationRequest, BlockRelocationStatus, DeviceSyncScope,
+    EngineImportNoteWriter, LocalCursor, MovePlacement, NoteAtTime, NotificationStateRecord,
+    PageDirectoryEntry, PeerCursor, PendingImport, RelayApplyReport, RelocatedNoteVersion,
+    RelocationNoteSeed, ScopeEviction, SyncAnomaly, SyncEngine, SyncRepair, TableColumnConfig,
+    ViewRecord,
 };
 pub use error::{SyncError, SyncResult};
 pub use group::{GroupId, GroupMember};
+pub use health::{IncidentEvent, IncidentLog, SyncIncident};
 pub use hlc::{Hlc, HlcTimestamp};
 pub use oplog::op::{ContentHash, EncodedOp, OpKind, OpPayload, PropOp};
+pub use oplog::retention::{run_retention_sweep, RetentionPolicy, RetentionReport};
 pub use tesela_core::property::PropScalar;
+pub use transport::lan::LanTransport;
 pub use transport::loopback::LoopbackTransport;
 pub use transport::{Transport, TransportSession, TransportTarget, TransportTickReport};
 pub use wire::envelope::SyncEnvelope;
@@ -1,8 +1,35 @@
 //! Oplog retention: GC ops once all known peers have ack'd past them.
 //!
-//! Phase 1 stub. The retention sweep runs as a method on the engine
-//! (`SyncEngine::run_retention_sweep`) once the engine implementation
-//! exists; this module holds the policy constants.
+//! Nothing else ever deletes from the `oplog`, `parked_ops` or
+//! `peer_cursors` tables (see [`crate::schema`]), so without a sweep they
+//! grow for the life of the mosaic. [`run_retention_sweep`] trims all three
+//! in one transaction:
+//!
+//! 1. **Stale peers.** A peer whose last ack is older than
+//!    [`RetentionPolicy::evict_peers_after_millis`] is dropped from
+//!    `peer_cursors`, so a device that was retired without being unpaired
+//!    stops pinning the oplog forever. Group membership is untouched:
+//!    eviction only stops *waiting* for the device. If it comes back it
+//!    catches up from Loro snapshots, like a fresh join, and its next ack
+//!    re-registers the cursor.
+//! 2. **Acked ops.** Ops at or below the lowest remaining peer cursor *and*
+//!    older than the safety lag are deleted. With no peer on record the
+//!    safety lag alone bounds the oplog.
+//! 3. **Parked ops.** Rows that have since landed in the oplog (a schema
+//!    upgrade applied them), rows authored by an evicted peer, and rows
+//!    parked longer than [`RetentionPolicy::parked_max_age_millis`] are
+//!    dropped.
+//!
+//! The sweep works on the mosaic database directly rather than through the
+//! engine: post-flag-day the [`crate::LoroEngine`] keeps its state in Loro
+//! snapshots and never reads these tables back. The server runs it
+//! periodically; `tesela sync gc` runs it on demand.
+
+use serde::Serialize;
+use sqlx::Row;
+
+use crate::device::DeviceId;
+use crate::error::SyncResult;
 
 /// Safety lag applied to retention. Even after all peers ack, ops within
 /// this window of wall-clock past are retained so a peer that briefly
@@ -10,3 +37,328 @@
 ///
 /// Default: 24 hours of wall-clock equivalent.
 pub const DEFAULT_RETENTION_SAFETY_LAG_MILLIS: i64 = 24 * 60 * 60 * 1000;
+
+/// How long an op may sit in `parked_ops` before the sweep gives up on it.
+///
+/// Default: 90 days.
+pub const DEFAULT_PARKED_OP_MAX_AGE_MILLIS: i64 = 90 * 24 * 60 * 60 * 1000;
+
+/// How long a peer may go without acking before the sweep stops holding
+/// ops back for it.
+///
+/// Default: 180 days.
+pub const DEFAULT_PEER_EVICTION_MILLIS: i64 = 180 * 24 * 60 * 60 * 1000;
+
+/// Knobs for one retention sweep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Ops younger than this are kept even when every peer has acked them.
+    pub safety_lag_millis: i64,
+    /// Parked ops older than this are dropped. `None` keeps them forever.
+    pub parked_max_age_millis: Option<i64>,
+    /// Peers silent for longer than this are evicted from `peer_cursors`.
+    /// `None` never evicts.
+    pub evict_peers_after_millis: Option<i64>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        RetentionPolicy {
+            safety_lag_millis: DEFAULT_RETENTION_SAFETY_LAG_MILLIS,
+            parked_max_age_millis: Some(DEFAULT_PARKED_OP_MAX_AGE_MILLIS),
+            evict_peers_after_millis: Some(DEFAULT_PEER_EVICTION_MILLIS),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// The policy described by a mosaic's `[sync.retention]` config block.
+    /// A zero age means "never" for both parked ops and peer eviction.
+    pub fn from_config(cfg: &tesela_core::config::RetentionConfig) -> Self {
+        const HOUR: i64 = 60 * 60 * 1000;
+        const DAY: i64 = 24 * HOUR;
+        let days = |d: u64| (d > 0).then(|| d as i64 * DAY);
+        RetentionPolicy {
+            safety_lag_millis: cfg.safety_lag_hours as i64 * HOUR,
+            parked_max_age_millis: days(cfg.parked_max_age_days),
+            evict_peers_after_millis: days(cfg.evict_peers_after_days),
+        }
+    }
+}
+
+/// What one sweep reclaimed (or, on a dry run, would have).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RetentionReport {
+    /// True when nothing was actually deleted.
+    pub dry_run: bool,
+    /// Peers dropped from `peer_cursors`, as 32-char hex device ids.
+    pub evicted_peers: Vec<String>,
+    /// `oplog` rows deleted.
+    pub oplog_rows_deleted: u64,
+    /// `parked_ops` rows deleted.
+    pub parked_ops_deleted: u64,
+    /// `oplog` rows left after the sweep.
+    pub oplog_rows_remaining: u64,
+    /// `parked_ops` rows left after the sweep.
+    pub parked_ops_remaining: u64,
+    /// The HLC (NTP64 as i64) at or below which ops were eligible: the
+    /// lower of the slowest peer's cursor and the safety-lag cutoff.
+    pub horizon_ntp: i64,
+}
+
+impl RetentionReport {
+    /// Rows deleted across all three tables.
+    pub fn reclaimed_rows(&self) -> u64 {
+        self.oplog_rows_deleted + self.parked_ops_deleted + self.evicted_peers.len() as u64
+    }
+}
+
+/// Run one retention sweep over the sync tables in `pool`.
+///
+/// `self_device` is excluded from the peer set (a device never waits on
+/// its own acks). `now_millis` is wall-clock unix millis, injected so
+/// callers and tests agree on "now". With `dry_run` the sweep runs in a
+/// transaction that is rolled back, so the report shows exactly what a
+/// real run would reclaim.
+pub async fn run_retention_sweep(
+    pool: &sqlx::SqlitePool,
+    self_device: DeviceId,
+    policy: &RetentionPolicy,
+    now_millis: i64,
+    dry_run: bool,
+) -> SyncResult<RetentionReport> {
+    let mut tx = pool.begin().await?;
+    let mut report = RetentionReport {
+        dry_run,
+        ..RetentionReport::default()
+    };
+
+    // Stale peers.
+    if let Some(after) = policy.evict_peers_after_millis {
+        let rows = sqlx::query(
+            "DELETE FROM peer_cursors \
+             WHERE last_ack_at_wall_clock < ? AND peer_device_id != ? \
+             RETURNING peer_device_id",
+        )
+        .bind(now_millis.saturating_sub(after))
+        .bind(&self_device.as_bytes()[..])
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut evicted: Vec<Vec<u8>> = rows.iter().map(|r| r.get("peer_device_id")).collect();
+        evicted.sort();
+        for device in &evicted {
+            report.parked_ops_deleted +=
+                sqlx::query("DELETE FROM parked_ops WHERE op_device_id = ?")
+                    .bind(device)
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+        }
+        report.evicted_peers = evicted.iter().map(hex::encode).collect();
+    }
+
+    // Parked ops that have since been applied. Checked before the
+    // oplog GC below can delete the row that proves it.
+    report.parked_ops_deleted += sqlx::query(
+        "DELETE FROM parked_ops WHERE EXISTS (\
+             SELECT 1 FROM oplog \
+             WHERE oplog.hlc_ntp = parked_ops.op_hlc_ntp \
+               AND oplog.device_id = parked_ops.op_device_id)",
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    // Acked ops. MIN over zero rows is NULL: no peer holds anything back.
+    let slowest_peer: Option<i64> = sqlx::query_scalar(
+        "SELECT MIN(last_seen_hlc_ntp) FROM peer_cursors WHERE peer_device_id != ?",
+    )
+    .bind(&self_device.as_bytes()[..])
+    .fetch_one(&mut *tx)
+    .await?;
+    let lag_cutoff = millis_to_ntp64(now_millis.saturating_sub(policy.safety_lag_millis));
+    report.horizon_ntp = slowest_peer.map_or(lag_cutoff, |peer| peer.min(lag_cutoff));
+    report.oplog_rows_deleted = sqlx::query("DELETE FROM oplog WHERE hlc_ntp <= ?")
+        .bind(report.horizon_ntp)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    // Parked ops past their max age.
+    if let Some(max_age) = policy.parked_max_age_millis {
+        report.parked_ops_deleted += sqlx::query("DELETE FROM parked_ops WHERE parked_at < ?")
+            .bind(now_millis.saturating_sub(max_age))
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    }
+
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM oplog")
+        .fetch_one(&mut *tx)
+        .await?;
+    report.oplog_rows_remaining = remaining as u64;
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM parked_ops")
+        .fetch_one(&mut *tx)
+        .await?;
+    report.parked_ops_remaining = remaining as u64;
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+    Ok(report)
+}
+
+/// Unix millis → NTP64 (as stored in `hlc_ntp`), the inverse of
+/// [`crate::HlcTimestamp::physical_millis`].
+fn millis_to_ntp64(millis: i64) -> i64 {
+    let millis = millis.max(0);
+    let secs = millis / 1000;
+    // Round up so `physical_millis` (which rounds down) maps back exactly.
+    let frac = (((millis % 1000) << 32) + 999) / 1000;
+    (secs << 32) | frac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HlcTimestamp;
+
+    const DAY: i64 = 24 * 60 * 60 * 1000;
+    const NOW: i64 = 1_780_000_000_000;
+
+    async fn pool() -> sqlx::SqlitePool {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        crate::schema::apply_ddl(&pool).await.unwrap();
+        pool
+    }
+
+    async fn op(pool: &sqlx::SqlitePool, at_millis: i64, device: u8) {
+        sqlx::query(
+            "INSERT INTO oplog (hlc_ntp, device_id, schema_version, payload, content_hash) \
+             VALUES (?, ?, 1, x'00', x'00')",
+        )
+        .bind(millis_to_ntp64(at_millis))
+        .bind(&[device; 16][..])
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn peer(pool: &sqlx::SqlitePool, device: u8, seen_millis: i64, ack_millis: i64) {
+        sqlx::query("INSERT INTO peer_cursors VALUES (?, ?, ?)")
+            .bind(&[device; 16][..])
+            .bind(millis_to_ntp64(seen_millis))
+            .bind(ack_millis)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn parked(pool: &sqlx::SqlitePool, at_millis: i64, device: u8, parked_at: i64) {
+        sqlx::query("INSERT INTO parked_ops VALUES (?, ?, 99, x'00', ?, 'schema_too_new')")
+            .bind(millis_to_ntp64(at_millis))
+            .bind(&[device; 16][..])
+            .bind(parked_at)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn millis_round_trip_through_ntp64() {
+        let ts = HlcTimestamp::from_ntp64_i64(millis_to_ntp64(NOW + 123), DeviceId([0; 16]));
+        assert_eq!(ts.physical_millis(), NOW + 123);
+    }
+
+    #[tokio::test]
+    async fn sweep_respects_slowest_peer_and_safety_lag() {
+        let pool = pool().await;
+        let me = DeviceId([1; 16]);
+        for days_ago in [30, 20, 10, 2] {
+            op(&pool, NOW - days_ago * DAY, 1).await;
+        }
+        op(&pool, NOW - DAY / 2, 1).await;
+        // Peer 2 has acked up to 15 days ago; peer 3 is fully caught up.
+        peer(&pool, 2, NOW - 15 * DAY, NOW - DAY).await;
+        peer(&pool, 3, NOW, NOW).await;
+
+        let policy = RetentionPolicy::default();
+        let dry = run_retention_sweep(&pool, me, &policy, NOW, true)
+            .await
+            .unwrap();
+        assert_eq!(dry.oplog_rows_deleted, 2, "only the 30- and 20-day-old ops");
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM oplog")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 5, "a dry run deletes nothing");
+
+        let real = run_retention_sweep(&pool, me, &policy, NOW, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            real,
+            RetentionReport {
+                dry_run: false,
+                ..dry
+            }
+        );
+        assert_eq!(real.oplog_rows_remaining, 3);
+
+        // Once peer 2 catches up, the safety lag is what holds the rest.
+        sqlx::query("UPDATE peer_cursors SET last_seen_hlc_ntp = ?")
+            .bind(millis_to_ntp64(NOW))
+            .execute(&pool)
+            .await
+            .unwrap();
+        let next = run_retention_sweep(&pool, me, &policy, NOW, false)
+            .await
+            .unwrap();
+        assert_eq!(next.oplog_rows_deleted, 2);
+        assert_eq!(
+            next.oplog_rows_remaining, 1,
+            "the 12-hour-old op is inside the lag"
+        );
+    }
+
+    #[tokio::test]
+    async fn stale_peers_are_evicted_and_parked_ops_compacted() {
+        let pool = pool().await;
+        let me = DeviceId([1; 16]);
+        op(&pool, NOW - 300 * DAY, 1).await;
+        op(&pool, NOW - 100 * DAY, 2).await;
+        // Peer 4 went quiet 200 days ago and pins everything.
+        peer(&pool, 2, NOW, NOW).await;
+        peer(&pool, 4, NOW - 301 * DAY, NOW - 200 * DAY).await;
+        parked(&pool, NOW - 100 * DAY, 2, NOW - 5 * DAY).await; // applied since
+        parked(&pool, NOW - 5 * DAY, 4, NOW - 5 * DAY).await; // evicted author
+        parked(&pool, NOW - 95 * DAY, 5, NOW - 95 * DAY).await; // too old
+        parked(&pool, NOW - 5 * DAY, 5, NOW - 5 * DAY).await; // kept
+
+        let never = RetentionPolicy {
+            parked_max_age_millis: None,
+            evict_peers_after_millis: None,
+            ..RetentionPolicy::default()
+        };
+        let pinned = run_retention_sweep(&pool, me, &never, NOW, true)
+            .await
+            .unwrap();
+        assert_eq!(pinned.oplog_rows_deleted, 0);
+        assert!(pinned.evicted_peers.is_empty());
+
+        let report = run_retention_sweep(&pool, me, &RetentionPolicy::default(), NOW, false)
+            .await
+            .unwrap();
+        assert_eq!(report.evicted_peers, vec![hex::encode([4; 16])]);
+        assert_eq!(report.oplog_rows_deleted, 2);
+        assert_eq!(report.parked_ops_deleted, 3);
+        assert_eq!(report.parked_ops_remaining, 1);
+        assert_eq!(report.reclaimed_rows(), 6);
+    }
+}
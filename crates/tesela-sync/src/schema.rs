@@ -68,6 +68,13 @@ pub const SYNC_SUBSTRATE_DDL: &[&str] = &[
     )"#,
 ];
 
+/// Adds the X25519 key-agreement public key to `group_members` (group
+/// key rotation, see [`crate::crypto::rotation`]). Wired into tesela-core
+/// as migration `008_group_member_kx`; kept out of
+/// [`SYNC_SUBSTRATE_DDL`] because migration `004` is already applied on
+/// existing mosaics.
+pub const GROUP_MEMBER_KX_DDL: &str = "ALTER TABLE group_members ADD COLUMN kx_pubkey BLOB";
+
 /// Apply the sync substrate DDL to a freshly-connected SQLite handle.
 ///
 /// Idempotent (all `CREATE` statements use `IF NOT EXISTS`). Useful for
@@ -77,5 +84,15 @@ pub async fn apply_ddl(pool: &sqlx::SqlitePool) -> crate::SyncResult<()> {
     for stmt in SYNC_SUBSTRATE_DDL {
         sqlx::query(stmt).execute(pool).await?;
     }
+    // `ALTER TABLE ADD COLUMN` has no `IF NOT EXISTS`; probe first so a
+    // second call stays a no-op.
+    let has_kx: Option<i64> = sqlx::query_scalar(
+        "SELECT 1 FROM pragma_table_info('group_members') WHERE name = 'kx_pubkey'",
+    )
+    .fetch_optional(pool)
+    .await?;
+    if has_kx.is_none() {
+        sqlx::query(GROUP_MEMBER_KX_DDL).execute(pool).await?;
+    }
     Ok(())
 }
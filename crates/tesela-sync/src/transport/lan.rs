@@ -1,6 +1,861 @@
-//! LAN transport (mDNS + TLS-pinned). Phase 2 placeholder.
+//! LAN transport: direct device-to-device sessions over TCP + TLS 1.3,
+//! pinned to keys derived from the group key.
 //!
-//! The plan reserves this module for the mDNS discovery + rustls
-//! self-signed-pinned implementation that arrives in Phase 2 alongside the
-//! crypto module. Phase 1 leaves it empty so the module structure is
-//! complete from day one.
+//! ## Trust model
+//!
+//! There is no CA and no X.509. Each device's TLS identity is an Ed25519
+//! key derived deterministically from the shared group key:
+//!
+//! ```text
+//! seed = HKDF-SHA256(salt = group_id, ikm = group_key,
+//!                    info = "tesela-lan-tls-v1" ++ device_id)
+//! ```
+//!
+//! and is presented as an RFC 7250 raw public key. Every group member —
+//! and only a group member — can compute the expected key of every other
+//! member from its `DeviceId` ([`lan_peer_spki`]), so:
+//!
+//! - the dialing side pins the listener's key to the `DeviceId` it asked
+//!   for before any application byte is exchanged;
+//! - the listening side requires a client key, then checks it against
+//!   the `DeviceId` the client claims in its hello frame.
+//!
+//! A device on the network without the group key can neither impersonate
+//! a member nor read the session; a member with the key can in principle
+//! derive any member's identity, the same trust boundary the relay's AEAD
+//! already has. The group key arrives through the pairing exchange
+//! (`crypto::pairing`), so pairing is what establishes the pin.
+//!
+//! ## Framing
+//!
+//! After the handshake each side sends one postcard [`LanHello`] frame;
+//! every later frame is a postcard [`SyncEnvelope`]. Frames are a 4-byte
+//! big-endian length followed by the body, capped at [`MAX_FRAME_LEN`].
+//! The hello is read before the peer's key has been checked, so it gets
+//! its own [`MAX_HELLO_LEN`] cap, and the listener runs at most
+//! [`MAX_CONCURRENT_HANDSHAKES`] unauthenticated handshakes at a time.
+//! Envelopes travel exactly as the relay client hands them to callers —
+//! TLS provides confidentiality on the wire, so relay vs. LAN stays
+//! invisible above this layer.
+//!
+//! Peer addresses come from [`LanTransport::add_peer`] or, when attached,
+//! from [`LanDiscovery`] peers advertising a LAN port (refreshed by
+//! [`Transport::tick`]).
+//!
+//! ## Key epochs
+//!
+//! Pins are derived from the group's *current* key epoch
+//! (`crypto::rotation::GroupKeyring::identity`). When the group rotates,
+//! [`LanTransport::rekey`] swaps the identity in place: later handshakes,
+//! in both directions, use the new epoch's keys, so a device left out of
+//! the rotation can no longer open or accept a session.
+//!
+//! ## Scope
+//!
+//! This module provides the transport only. `tesela-server`'s `lan_sync`
+//! binds it, advertises its port over mDNS and drives the sync sessions.
+
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::stream::{self, Stream};
+use hkdf::Hkdf;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{
+    verify_tls13_signature_with_raw_key, CryptoProvider, WebPkiSupportedAlgorithms,
+};
+use rustls::pki_types::{
+    CertificateDer, PrivatePkcs8KeyDer, ServerName, SubjectPublicKeyInfoDer, UnixTime,
+};
+use rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
+use rustls::sign::CertifiedKey;
+use rustls::{DigitallySignedStruct, DistinguishedName, SignatureScheme};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinHandle;
+use tokio_rustls::{TlsAcceptor, TlsConnector, TlsStream};
+
+use crate::crypto::keys::{GroupIdentity, GroupKey};
+use crate::device::DeviceId;
+use crate::discovery::LanDiscovery;
+use crate::error::{SyncError, SyncResult};
+use crate::group::GroupId;
+use crate::transport::{Transport, TransportSession, TransportTarget, TransportTickReport};
+use crate::wire::envelope::SyncEnvelope;
+
+/// HKDF info prefix for the per-device LAN TLS key. Distinct from the
+/// relay-auth derivations so one derived key never doubles as another.
+pub const LAN_TLS_INFO_V1: &[u8] = b"tesela-lan-tls-v1";
+
+/// LAN wire protocol version carried in [`LanHello`].
+pub const LAN_PROTOCOL_VERSION: u8 = 1;
+
+/// Largest frame either side will read. Matches the relay's envelope
+/// ceiling with headroom; a single Loro snapshot can't be split.
+pub const MAX_FRAME_LEN: usize = 32 * 1024 * 1024;
+
+/// Largest hello frame the listener reads. A postcard [`LanHello`] is
+/// under 40 bytes; anything bigger is refused before it is buffered.
+pub const MAX_HELLO_LEN: usize = 256;
+
+/// Inbound handshakes in flight at once. Further connections wait in the
+/// kernel backlog until a slot frees up.
+pub const MAX_CONCURRENT_HANDSHAKES: usize = 32;
+
+/// First and last delay after a failed `accept` (e.g. out of file
+/// descriptors); doubles per consecutive failure, resets on success.
+const ACCEPT_BACKOFF_MIN: Duration = Duration::from_millis(10);
+const ACCEPT_BACKOFF_MAX: Duration = Duration::from_secs(1);
+
+/// Bound on TCP connect + TLS handshake + hello exchange.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a discovered peer stays dialable without a fresh mDNS record.
+const DISCOVERY_MAX_AGE: Duration = Duration::from_secs(5 * 60);
+
+/// Inbound sessions buffered before the accept loop applies backpressure.
+const INCOMING_BUFFER: usize = 16;
+
+/// DER prefix of an Ed25519 SubjectPublicKeyInfo (RFC 8410); the 32-byte
+/// public key follows.
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+/// DER prefix of an Ed25519 PKCS#8 v1 private key (RFC 8410); the 32-byte
+/// seed follows.
+const ED25519_PKCS8_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04, 0x20,
+];
+
+/// First frame on every session, in both directions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanHello {
+    /// [`LAN_PROTOCOL_VERSION`] of the sender.
+    pub version: u8,
+    /// Sender's device id. The receiver pins the TLS key against it.
+    pub device: DeviceId,
+    /// Sender's group. Sessions never cross groups.
+    pub group: GroupId,
+}
+
+fn lan_seed(group_key: &GroupKey, group_id: &GroupId, device: DeviceId) -> [u8; 32] {
+    let hk = Hkdf::<Sha256>::new(Some(group_id.as_bytes()), group_key.as_bytes());
+    let mut info = Vec::with_capacity(LAN_TLS_INFO_V1.len() + 16);
+    info.extend_from_slice(LAN_TLS_INFO_V1);
+    info.extend_from_slice(device.as_bytes());
+    let mut out = [0u8; 32];
+    hk.expand(&info, &mut out)
+        .expect("32-byte HKDF output is well below the max");
+    out
+}
+
+fn ed25519_public(seed: &[u8; 32]) -> SyncResult<[u8; 32]> {
+    let pair = ring::signature::Ed25519KeyPair::from_seed_unchecked(seed)
+        .map_err(|e| SyncError::Crypto(format!("derive LAN key: {e}")))?;
+    let mut out = [0u8; 32];
+    out.copy_from_slice(ring::signature::KeyPair::public_key(&pair).as_ref());
+    Ok(out)
+}
+
+fn spki_der(public: &[u8; 32]) -> Vec<u8> {
+    let mut out = ED25519_SPKI_PREFIX.to_vec();
+    out.extend_from_slice(public);
+    out
+}
+
+/// The DER SubjectPublicKeyInfo `device` presents on LAN sessions of this
+/// group — what a peer pins it to.
+pub fn lan_peer_spki(
+    group_key: &GroupKey,
+    group_id: &GroupId,
+    device: DeviceId,
+) -> SyncResult<Vec<u8>> {
+    Ok(spki_der(&ed25519_public(&lan_seed(
+        group_key, group_id, device,
+    ))?))
+}
+
+/// This device's LAN TLS identity plus everything needed to pin peers.
+struct LanIdentity {
+    device: DeviceId,
+    group_id: GroupId,
+    group_key: GroupKey,
+    certified: Arc<CertifiedKey>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl LanIdentity {
+    fn derive(device: DeviceId, group: &GroupIdentity) -> SyncResult<Self> {
+        let seed = lan_seed(&group.group_key, &group.group_id, device);
+        let mut pkcs8 = ED25519_PKCS8_PREFIX.to_vec();
+        pkcs8.extend_from_slice(&seed);
+        let signing = rustls::crypto::ring::sign::any_eddsa_type(&PrivatePkcs8KeyDer::from(pkcs8))
+            .map_err(|e| SyncError::Crypto(format!("load LAN key: {e}")))?;
+        let spki = spki_der(&ed25519_public(&seed)?);
+        Ok(Self {
+            device,
+            group_id: group.group_id,
+            group_key: group.group_key.clone(),
+            certified: Arc::new(CertifiedKey::new(vec![CertificateDer::from(spki)], signing)),
+            provider: Arc::new(rustls::crypto::ring::default_provider()),
+        })
+    }
+
+    fn expected_spki(&self, peer: DeviceId) -> SyncResult<Vec<u8>> {
+        lan_peer_spki(&self.group_key, &self.group_id, peer)
+    }
+
+    fn hello(&self) -> LanHello {
+        LanHello {
+            version: LAN_PROTOCOL_VERSION,
+            device: self.device,
+            group: self.group_id,
+        }
+    }
+
+    fn acceptor(&self) -> SyncResult<TlsAcceptor> {
+        let verifier = Arc::new(AnyEd25519Client {
+            algs: self.provider.signature_verification_algorithms,
+        });
+        let config = rustls::ServerConfig::builder_with_provider(Arc::clone(&self.provider))
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(tls_err)?
+            .with_client_cert_verifier(verifier)
+            .with_cert_resolver(Arc::new(
+                rustls::server::AlwaysResolvesServerRawPublicKeys::new(Arc::clone(&self.certified)),
+            ));
+        Ok(TlsAcceptor::from(Arc::new(config)))
+    }
+
+    fn connector(&self, peer: DeviceId) -> SyncResult<TlsConnector> {
+        let verifier = Arc::new(PinnedServer {
+            expected: self.expected_spki(peer)?,
+            algs: self.provider.signature_verification_algorithms,
+        });
+        let config = rustls::ClientConfig::builder_with_provider(Arc::clone(&self.provider))
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(tls_err)?
+            .dangerous()
+            .with_custom_certificate_verifier(verifier)
+            .with_client_cert_resolver(Arc::new(
+                rustls::client::AlwaysResolvesClientRawPublicKeys::new(Arc::clone(&self.certified)),
+            ));
+        Ok(TlsConnector::from(Arc::new(config)))
+    }
+}
+
+fn tls_err(e: rustls::Error) -> SyncError {
+    SyncError::Transport(format!("tls: {e}"))
+}
+
+fn io_err(e: std::io::Error) -> SyncError {
+    SyncError::Transport(e.to_string())
+}
+
+/// Dial side: accept only the raw key derived for the device we dialed.
+#[derive(Debug)]
+struct PinnedServer {
+    expected: Vec<u8>,
+    algs: WebPkiSupportedAlgorithms,
+}
+
+impl ServerCertVerifier for PinnedServer {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if end_entity.as_ref() == self.expected.as_slice() {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::InvalidCertificate(
+                rustls::CertificateError::ApplicationVerificationFailure,
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Err(rustls::Error::General(
+            "LAN sessions are TLS 1.3 only".into(),
+        ))
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature_with_raw_key(
+            message,
+            &SubjectPublicKeyInfoDer::from(cert.as_ref()),
+            dss,
+            &self.algs,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        vec![SignatureScheme::ED25519]
+    }
+
+    fn requires_raw_public_keys(&self) -> bool {
+        true
+    }
+}
+
+/// Listen side: require proof of possession of an Ed25519 key. Which
+/// device it must belong to is only known from the hello frame, so the
+/// pin check happens in [`accept_session`].
+#[derive(Debug)]
+struct AnyEd25519Client {
+    algs: WebPkiSupportedAlgorithms,
+}
+
+impl ClientCertVerifier for AnyEd25519Client {
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        let der = end_entity.as_ref();
+        if der.len() == ED25519_SPKI_PREFIX.len() + 32 && der.starts_with(&ED25519_SPKI_PREFIX) {
+            Ok(ClientCertVerified::assertion())
+        } else {
+            Err(rustls::Error::InvalidCertificate(
+                rustls::CertificateError::BadEncoding,
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Err(rustls::Error::General(
+            "LAN sessions are TLS 1.3 only".into(),
+        ))
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature_with_raw_key(
+            message,
+            &SubjectPublicKeyInfoDer::from(cert.as_ref()),
+            dss,
+            &self.algs,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        vec![SignatureScheme::ED25519]
+    }
+
+    fn requires_raw_public_keys(&self) -> bool {
+        true
+    }
+}
+
+async fn write_frame<S: AsyncWrite + Unpin>(stream: &mut S, body: &[u8]) -> SyncResult<()> {
+    if body.len() > MAX_FRAME_LEN {
+        return Err(SyncError::Transport(format!(
+            "frame of {} bytes exceeds the {MAX_FRAME_LEN}-byte limit",
+            body.len()
+        )));
+    }
+    stream
+        .write_all(&(body.len() as u32).to_be_bytes())
+        .await
+        .map_err(io_err)?;
+    stream.write_all(body).await.map_err(io_err)?;
+    stream.flush().await.map_err(io_err)
+}
+
+/// Read one frame; `None` on a clean close between frames.
+async fn read_frame<S: AsyncRead + Unpin>(stream: &mut S) -> SyncResult<Option<Vec<u8>>> {
+    read_frame_limited(stream, MAX_FRAME_LEN).await
+}
+
+/// [`read_frame`] with a caller-chosen size cap.
+async fn read_frame_limited<S: AsyncRead + Unpin>(
+    stream: &mut S,
+    limit: usize,
+) -> SyncResult<Option<Vec<u8>>> {
+    let mut len = [0u8; 4];
+    match stream.read_exact(&mut len).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(io_err(e)),
+    }
+    let len = u32::from_be_bytes(len) as usize;
+    if len > limit {
+        return Err(SyncError::Protocol(format!(
+            "peer sent a {len}-byte frame (limit {limit})"
+        )));
+    }
+    let mut body = vec![0u8; len];
+    stream.read_exact(&mut body).await.map_err(io_err)?;
+    Ok(Some(body))
+}
+
+async fn read_hello<S: AsyncRead + Unpin>(stream: &mut S) -> SyncResult<LanHello> {
+    let frame = read_frame_limited(stream, MAX_HELLO_LEN)
+        .await?
+        .ok_or_else(|| SyncError::Protocol("peer closed before hello".to_string()))?;
+    let hello: LanHello = postcard::from_bytes(&frame)?;
+    if hello.version != LAN_PROTOCOL_VERSION {
+        return Err(SyncError::Protocol(format!(
+            "LAN protocol v{} not supported (local v{LAN_PROTOCOL_VERSION})",
+            hello.version
+        )));
+    }
+    Ok(hello)
+}
+
+/// Listener half of the handshake: TLS accept, read the client's hello,
+/// pin its key to the claimed device, answer with our own hello.
+async fn accept_session(identity: &LanIdentity, tcp: TcpStream) -> SyncResult<LanSession> {
+    let mut tls = identity.acceptor()?.accept(tcp).await.map_err(io_err)?;
+    let hello = read_hello(&mut tls).await?;
+    if hello.group != identity.group_id {
+        return Err(SyncError::Protocol(
+            "peer belongs to another group".to_string(),
+        ));
+    }
+    if hello.device == identity.device {
+        return Err(SyncError::Protocol(
+            "peer claims our own device id".to_string(),
+        ));
+    }
+    let presented = tls
+        .get_ref()
+        .1
+        .peer_certificates()
+        .and_then(|certs| certs.first())
+        .map(|c| c.as_ref().to_vec())
+        .ok_or_else(|| SyncError::Crypto("peer presented no key".to_string()))?;
+    if presented != identity.expected_spki(hello.device)? {
+        return Err(SyncError::Crypto(format!(
+            "key presented for device {} is not derived from the group key",
+            hello.device
+        )));
+    }
+    write_frame(&mut tls, &postcard::to_allocvec(&identity.hello())?).await?;
+    Ok(LanSession {
+        peer: hello.device,
+        stream: TlsStream::Server(tls),
+        closed: false,
+    })
+}
+
+/// Dial half of the handshake: the TLS verifier has already pinned the
+/// listener to `peer`; the hello exchange confirms the group.
+async fn connect_session(
+    identity: &LanIdentity,
+    peer: DeviceId,
+    addr: SocketAddr,
+) -> SyncResult<LanSession> {
+    let connector = identity.connector(peer)?;
+    let tcp = TcpStream::connect(addr).await.map_err(io_err)?;
+    let _ = tcp.set_nodelay(true);
+    let mut tls = connector
+        .connect(ServerName::IpAddress(addr.ip().into()), tcp)
+        .await
+        .map_err(io_err)?;
+    write_frame(&mut tls, &postcard::to_allocvec(&identity.hello())?).await?;
+    let hello = read_hello(&mut tls).await?;
+    if hello.device != peer || hello.group != identity.group_id {
+        return Err(SyncError::Protocol(format!(
+            "dialed {peer} at {addr} but it answered as {} (or from another group)",
+            hello.device
+        )));
+    }
+    Ok(LanSession {
+        peer,
+        stream: TlsStream::Client(tls),
+        closed: false,
+    })
+}
+
+fn current_identity(identity: &RwLock<Arc<LanIdentity>>) -> Arc<LanIdentity> {
+    Arc::clone(&identity.read().expect("identity RwLock poisoned"))
+}
+
+/// Direct LAN transport. Listens for pinned inbound sessions and dials
+/// known peers by `DeviceId`.
+pub struct LanTransport {
+    /// Current key epoch's identity, shared with the accept loop and
+    /// replaced by [`LanTransport::rekey`].
+    identity: Arc<RwLock<Arc<LanIdentity>>>,
+    local_addr: SocketAddr,
+    /// Dialable peers: manual entries plus those learned from discovery.
+    peers: RwLock<HashMap<DeviceId, SocketAddr>>,
+    /// Subset of `peers` that came from discovery (and may expire).
+    discovered: Mutex<HashSet<DeviceId>>,
+    discovery: Option<Arc<LanDiscovery>>,
+    incoming: Mutex<Option<mpsc::Receiver<Box<dyn TransportSession>>>>,
+    accept_task: JoinHandle<()>,
+}
+
+impl LanTransport {
+    /// Bind the listener (`127.0.0.1:0` / `0.0.0.0:0` pick a free port)
+    /// and start accepting sessions for `group`.
+    pub async fn bind(
+        device: DeviceId,
+        group: &GroupIdentity,
+        addr: SocketAddr,
+    ) -> SyncResult<Self> {
+        let identity = Arc::new(RwLock::new(Arc::new(LanIdentity::derive(device, group)?)));
+        let listener = TcpListener::bind(addr).await.map_err(io_err)?;
+        let local_addr = listener.local_addr().map_err(io_err)?;
+        let (tx, rx) = mpsc::channel(INCOMING_BUFFER);
+
+        let accept_identity = Arc::clone(&identity);
+        let handshakes = Arc::new(Semaphore::new(MAX_CONCURRENT_HANDSHAKES));
+        let accept_task = tokio::spawn(async move {
+            let mut backoff = ACCEPT_BACKOFF_MIN;
+            loop {
+                let Ok(permit) = Arc::clone(&handshakes).acquire_owned().await else {
+                    return;
+                };
+                let (tcp, remote) = match listener.accept().await {
+                    Ok(conn) => {
+                        backoff = ACCEPT_BACKOFF_MIN;
+                        conn
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            target: "tesela_sync::lan",
+                            "accept failed, retrying in {backoff:?}: {e}"
+                        );
+                        tokio::time::sleep(backoff).await;
+                        backoff = (backoff * 2).min(ACCEPT_BACKOFF_MAX);
+                        continue;
+                    }
+                };
+                let _ = tcp.set_nodelay(true);
+                let identity = current_identity(&accept_identity);
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    let handshake =
+                        tokio::time::timeout(HANDSHAKE_TIMEOUT, accept_session(&identity, tcp))
+                            .await;
+                    drop(permit);
+                    match handshake {
+                        Ok(Ok(session)) => {
+                            tracing::debug!(
+                                target: "tesela_sync::lan",
+                                peer = %session.peer,
+                                %remote,
+                                "inbound LAN session"
+                            );
+                            let _ = tx
+                                .send(Box::new(session) as Box<dyn TransportSession>)
+                                .await;
+                        }
+                        Ok(Err(e)) => tracing::info!(
+                            target: "tesela_sync::lan",
+                            %remote,
+                            "rejected inbound LAN session: {e}"
+                        ),
+                        Err(_) => tracing::info!(
+                            target: "tesela_sync::lan",
+                            %remote,
+                            "inbound LAN handshake timed out"
+                        ),
+                    }
+                });
+            }
+        });
+
+        tracing::info!(target: "tesela_sync::lan", %local_addr, "LAN transport listening");
+        Ok(Self {
+            identity,
+            local_addr,
+            peers: RwLock::new(HashMap::new()),
+            discovered: Mutex::new(HashSet::new()),
+            discovery: None,
+            incoming: Mutex::new(Some(rx)),
+            accept_task,
+        })
+    }
+
+    /// Re-derive this device's key and every peer pin from `group` — the
+    /// group's new key epoch after a rotation. Sessions already open keep
+    /// running; every later handshake uses the new keys.
+    pub fn rekey(&self, group: &GroupIdentity) -> SyncResult<()> {
+        let device = current_identity(&self.identity).device;
+        let next = Arc::new(LanIdentity::derive(device, group)?);
+        *self.identity.write().expect("identity RwLock poisoned") = next;
+        tracing::info!(target: "tesela_sync::lan", "LAN identity moved to a new key epoch");
+        Ok(())
+    }
+
+    /// Learn peer addresses from mDNS on every [`Transport::tick`].
+    pub fn with_discovery(mut self, discovery: Arc<LanDiscovery>) -> Self {
+        self.discovery = Some(discovery);
+        self
+    }
+
+    /// Address the listener is bound to — advertise its port.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Add (or move) a peer's LAN address. Manual entries never expire.
+    pub fn add_peer(&self, device: DeviceId, addr: SocketAddr) {
+        self.peers
+            .write()
+            .expect("peers RwLock poisoned")
+            .insert(device, addr);
+        self.discovered
+            .lock()
+            .expect("discovered Mutex poisoned")
+            .remove(&device);
+    }
+
+    /// Forget a peer's address.
+    pub fn remove_peer(&self, device: DeviceId) {
+        self.peers
+            .write()
+            .expect("peers RwLock poisoned")
+            .remove(&device);
+        self.discovered
+            .lock()
+            .expect("discovered Mutex poisoned")
+            .remove(&device);
+    }
+
+    /// Every peer with a known address, manual or discovered.
+    pub fn peers(&self) -> Vec<DeviceId> {
+        self.peers
+            .read()
+            .expect("peers RwLock poisoned")
+            .keys()
+            .copied()
+            .collect()
+    }
+
+    /// Known peer address, if any.
+    pub fn peer_addr(&self, device: DeviceId) -> Option<SocketAddr> {
+        self.peers
+            .read()
+            .expect("peers RwLock poisoned")
+            .get(&device)
+            .copied()
+    }
+}
+
+impl Drop for LanTransport {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+    }
+}
+
+#[async_trait]
+impl Transport for LanTransport {
+    async fn open(&self, target: TransportTarget) -> SyncResult<Box<dyn TransportSession>> {
+        let peer = match target {
+            TransportTarget::Peer(id) => id,
+            TransportTarget::Relay { .. } => {
+                return Err(SyncError::Transport(
+                    "LanTransport does not support relay targets".to_string(),
+                ))
+            }
+        };
+        let addr = self
+            .peer_addr(peer)
+            .ok_or_else(|| SyncError::Transport(format!("no LAN address known for {peer}")))?;
+        let identity = current_identity(&self.identity);
+        let session =
+            tokio::time::timeout(HANDSHAKE_TIMEOUT, connect_session(&identity, peer, addr))
+                .await
+                .map_err(|_| {
+                    SyncError::Transport(format!("LAN handshake with {peer} timed out"))
+                })??;
+        Ok(Box::new(session))
+    }
+
+    async fn tick(&self) -> SyncResult<TransportTickReport> {
+        let Some(discovery) = &self.discovery else {
+            return Ok(TransportTickReport::default());
+        };
+        let live: HashMap<DeviceId, SocketAddr> = discovery
+            .snapshot(DISCOVERY_MAX_AGE)
+            .into_iter()
+            .filter_map(|p| p.lan_addr().map(|addr| (p.device_id, addr)))
+            .collect();
+
+        let mut report = TransportTickReport::default();
+        let mut peers = self.peers.write().expect("peers RwLock poisoned");
+        let mut discovered = self.discovered.lock().expect("discovered Mutex poisoned");
+        discovered.retain(|device| {
+            if live.contains_key(device) {
+                return true;
+            }
+            peers.remove(device);
+            report.dropped_peers += 1;
+            false
+        });
+        for (device, addr) in live {
+            let manual = peers.contains_key(&device) && !discovered.contains(&device);
+            if manual {
+                continue;
+            }
+            if peers.insert(device, addr).is_none() {
+                report.new_peers += 1;
+            }
+            discovered.insert(device);
+        }
+        Ok(report)
+    }
+
+    fn incoming(&self) -> Pin<Box<dyn Stream<Item = Box<dyn TransportSession>> + Send>> {
+        // Single consumer: the first caller takes the receiver.
+        match self
+            .incoming
+            .lock()
+            .expect("incoming Mutex poisoned")
+            .take()
+        {
+            Some(rx) => Box::pin(stream::unfold(rx, |mut rx| async move {
+                rx.recv().await.map(|session| (session, rx))
+            })),
+            None => Box::pin(stream::empty()),
+        }
+    }
+}
+
+/// One pinned TLS session with a peer.
+pub struct LanSession {
+    peer: DeviceId,
+    stream: TlsStream<TcpStream>,
+    closed: bool,
+}
+
+#[async_trait]
+impl TransportSession for LanSession {
+    fn peer(&self) -> DeviceId {
+        self.peer
+    }
+
+    async fn send(&mut self, envelope: SyncEnvelope) -> SyncResult<()> {
+        if self.closed {
+            return Err(SyncError::Transport("session closed".to_string()));
+        }
+        write_frame(&mut self.stream, &postcard::to_allocvec(&envelope)?).await
+    }
+
+    async fn recv(&mut self) -> SyncResult<Option<SyncEnvelope>> {
+        if self.closed {
+            return Ok(None);
+        }
+        match read_frame(&mut self.stream).await? {
+            Some(frame) => Ok(Some(postcard::from_bytes(&frame)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn close(&mut self) -> SyncResult<()> {
+        if !self.closed {
+            self.closed = true;
+            // close_notify; the peer's next recv sees a clean end.
+            let _ = self.stream.shutdown().await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(seed: u8) -> GroupIdentity {
+        GroupIdentity {
+            group_id: GroupId::from_bytes([seed; 16]),
+            group_key: GroupKey::from_bytes([seed; 32]),
+        }
+    }
+
+    #[test]
+    fn peer_keys_are_per_device_and_per_group() {
+        let g = group(1);
+        let a = DeviceId::from_bytes([0xa; 16]);
+        let b = DeviceId::from_bytes([0xb; 16]);
+        let spki_a = lan_peer_spki(&g.group_key, &g.group_id, a).unwrap();
+        assert_eq!(spki_a.len(), 44);
+        assert!(spki_a.starts_with(&ED25519_SPKI_PREFIX));
+        assert_eq!(spki_a, lan_peer_spki(&g.group_key, &g.group_id, a).unwrap());
+        assert_ne!(spki_a, lan_peer_spki(&g.group_key, &g.group_id, b).unwrap());
+        let other = group(2);
+        assert_ne!(
+            spki_a,
+            lan_peer_spki(&other.group_key, &other.group_id, a).unwrap()
+        );
+    }
+
+    #[test]
+    fn identity_presents_the_pinned_key() {
+        let g = group(3);
+        let device = DeviceId::from_bytes([0xc; 16]);
+        let identity = LanIdentity::derive(device, &g).unwrap();
+        assert_eq!(
+            identity.certified.cert[0].as_ref(),
+            identity.expected_spki(device).unwrap().as_slice()
+        );
+    }
+
+    #[tokio::test]
+    async fn oversized_frames_are_refused() {
+        let (mut a, mut b) = tokio::io::duplex(64);
+        tokio::spawn(async move {
+            a.write_all(&((MAX_FRAME_LEN as u32) + 1).to_be_bytes())
+                .await
+                .unwrap();
+        });
+        assert!(matches!(
+            read_frame(&mut b).await,
+            Err(SyncError::Protocol(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn hello_frames_are_capped_before_buffering() {
+        let (mut a, mut b) = tokio::io::duplex(64);
+        tokio::spawn(async move {
+            a.write_all(&((MAX_HELLO_LEN as u32) + 1).to_be_bytes())
+                .await
+                .unwrap();
+        });
+        assert!(matches!(
+            read_hello(&mut b).await,
+            Err(SyncError::Protocol(_))
+        ));
+    }
+}
@@ -1,13 +1,14 @@
 //! Transport adapter trait. Implementations:
 //!
 //! - [`loopback::LoopbackTransport`] (Phase 1, in-process, used for tests)
-//! - `lan::LanTransport` (Phase 2 placeholder)
+//! - [`lan::LanTransport`] (direct LAN sessions, TLS pinned to the group key)
 //! - `relay::RelayClient` (Phase 3 placeholder)
 
 pub mod lan;
 pub mod loopback;
 pub mod relay;
 
+pub use lan::{LanSession, LanTransport};
 pub use loopback::LoopbackTransport;
 
 use crate::device::DeviceId;
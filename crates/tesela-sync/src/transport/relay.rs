@@ -6,9 +6,11 @@
 //! Unlike the LAN [`Transport`](super::Transport) trait, the relay
 //! isn't session-oriented — it's an async deposit box. We expose a
 //! direct surface (`register`, `verify_registration`, `put_envelope`,
-//! `poll`, `ack`) and let the desktop orchestrator drive it on a
-//! poll timer. Forcing it into the streaming `Transport` shape would
-//! mask what it actually is.
+//! `poll`, `ack`) and let the desktop orchestrator drive it. Between
+//! ticks the orchestrator parks in [`RelayClient::wait_for_ops`], which
+//! holds the relay's `/stream` notice feed open and degrades to a plain
+//! poll timer against relays that don't serve it. Forcing it into the
+//! streaming `Transport` shape would mask what it actually is.
 //!
 //! ## Zero-knowledge guarantee
 //!
@@ -18,7 +20,9 @@
 //! get back the original `SyncEnvelope` they would have gotten from
 //! the LAN transport — relay vs. LAN is invisible above this layer.
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use base64::Engine;
 use hmac::{Hmac, Mac};
@@ -35,6 +39,7 @@ use crate::crypto::relay_auth::{
     body_hash_hex, canonical_request, compute_request_mac, derive_relay_auth_key, intent_msg,
     sign_intent, verify_intent,
 };
+use crate::crypto::rotation::GroupKeyring;
 use crate::device::DeviceId;
 use crate::error::{SyncError, SyncResult};
 use crate::group::GroupId;
@@ -47,11 +52,85 @@ pub struct RelayClient {
     base_url: Url,
     group_id: GroupId,
     device_id: DeviceId,
+    /// Key material for the current epoch. Swapped whole by
+    /// [`RelayClient::adopt_keyring`] when the group key rotates, so a
+    /// running client follows the new epoch without a restart.
+    keys: std::sync::RwLock<Arc<EpochKeys>>,
+    http: Client,
+    /// Cleared once the relay answers `/stream` with 404/405 (e.g. a
+    /// deployment predating it); `wait_for_ops` then just sleeps.
+    live_stream: AtomicBool,
+    /// For the long-lived `/stream` connection: no overall deadline, only
+    /// connect and between-chunk timeouts.
+    stream_http: Client,
+    /// The `/stream` connection kept open across `wait_for_ops` calls.
+    stream: tokio::sync::Mutex<OpStream>,
+}
+
+/// First delay before re-opening a failed `/stream` connection; doubles
+/// per consecutive failure up to [`STREAM_RETRY_MAX`].
+const STREAM_RETRY_BASE: Duration = Duration::from_secs(1);
+const STREAM_RETRY_MAX: Duration = Duration::from_secs(60);
+
+/// Longest silence tolerated on `/stream` before the connection counts as
+/// dead. The relay sends a keep-alive comment every 15s.
+const STREAM_READ_TIMEOUT: Duration = Duration::from_secs(45);
+
+/// Read state of the relay's `/stream` feed.
+#[derive(Default)]
+struct OpStream {
+    response: Option<reqwest::Response>,
+    /// Received bytes not yet forming a complete event.
+    buf: String,
+    /// Highest seq announced so far, on this or an earlier connection.
+    announced: i64,
+    /// Consecutive failed connections (or drops), for the backoff.
+    failures: u32,
+    /// No reconnect before this instant.
+    retry_at: Option<tokio::time::Instant>,
+}
+
+impl OpStream {
+    /// Drop the connection and schedule the next attempt.
+    fn fail(&mut self, e: &SyncError) {
+        self.response = None;
+        self.failures = self.failures.saturating_add(1);
+        let delay = STREAM_RETRY_BASE
+            .saturating_mul(1 << (self.failures - 1).min(6))
+            .min(STREAM_RETRY_MAX);
+        tracing::debug!("relay stream: {e}; reconnecting in {delay:?}");
+        self.retry_at = Some(tokio::time::Instant::now() + delay);
+    }
+}
+
+/// The keys a [`RelayClient`] seals, opens and signs with.
+struct EpochKeys {
+    /// Current key epoch; `group_key` is its key. Everything this client
+    /// seals uses it.
+    epoch: u32,
     group_key: GroupKey,
+    /// Retired epochs, newest first — only ever used to OPEN rows and
+    /// snapshots deposited before a rotation.
+    retired: Vec<(u32, GroupKey)>,
     /// Cached HKDF derivation — same on every device, but we hold
     /// it locally so request-time MAC computation is allocation-free.
     auth_key: [u8; 32],
-    http: Client,
+}
+
+impl EpochKeys {
+    fn new(
+        group_id: &GroupId,
+        epoch: u32,
+        group_key: GroupKey,
+        retired: Vec<(u32, GroupKey)>,
+    ) -> Self {
+        Self {
+            auth_key: derive_relay_auth_key(&group_key, group_id),
+            epoch,
+            group_key,
+            retired,
+        }
+    }
 }
 
 /// What the relay returned for a registration record. Carries the
@@ -77,7 +156,32 @@ impl RelayClient {
     /// `verify_registration()` explicitly so callers control error
     /// reporting + retry policy.
     pub fn new(base_url: Url, group_id: GroupId, device_id: DeviceId, group_key: GroupKey) -> Self {
-        let auth_key = derive_relay_auth_key(&group_key, &group_id);
+        Self::build(base_url, group_id, device_id, 0, group_key, Vec::new())
+    }
+
+    /// Build a client over a rotated group: seals under the keyring's
+    /// current epoch (and MACs with that epoch's auth key), opens rows
+    /// from any epoch the keyring holds.
+    pub fn with_keyring(base_url: Url, device_id: DeviceId, keyring: &GroupKeyring) -> Self {
+        Self::build(
+            base_url,
+            keyring.group_id(),
+            device_id,
+            keyring.epoch(),
+            keyring.current_key().clone(),
+            keyring.retired().map(|(e, k)| (e, k.clone())).collect(),
+        )
+    }
+
+    fn build(
+        base_url: Url,
+        group_id: GroupId,
+        device_id: DeviceId,
+        epoch: u32,
+        group_key: GroupKey,
+        retired: Vec<(u32, GroupKey)>,
+    ) -> Self {
+        let keys = EpochKeys::new(&group_id, epoch, group_key, retired);
         let http = Client::builder()
             .timeout(std::time::Duration::from_secs(15))
             .build()
@@ -86,31 +190,45 @@ impl RelayClient {
             base_url,
             group_id,
             device_id,
-            group_key,
-            auth_key,
+            keys: std::sync::RwLock::new(Arc::new(keys)),
             http,
+            live_stream: AtomicBool::new(true),
+            stream_http: Client::builder()
+                .connect_timeout(std::time::Duration::from_secs(15))
+                .read_timeout(STREAM_READ_TIMEOUT)
+                .build()
+                .expect("reqwest client construction is infallible with default config"),
+            stream: tokio::sync::Mutex::new(OpStream::default()),
         }
     }
 
+    /// The key epoch this client seals under.
+    pub fn epoch(&self) -> u32 {
+        self.keys().epoch
+    }
+
+    /// Switch to `keyring`'s current epoch after a key rotation: seal and
+    /// sign under the new key from the next request on, and keep opening
+    /// rows from every epoch the keyring holds.
+    pub fn adopt_keyring(&self, keyring: &GroupKeyring) {
+        let keys = EpochKeys::new(
+            &self.group_id,
+            keyring.epoch(),
+            keyring.current_key().clone(),
+            keyring.retired().map(|(e, k)| (e, k.clone())).collect(),
+        );
+        *self.keys.write().expect("relay keys lock poisoned") = Arc::new(keys);
+    }
+
+    fn keys(&self) -> Arc<EpochKeys> {
+        Arc::clone(&self.keys.read().expect("relay keys lock poisoned"))
+    }
+
     /// `POST /groups/{id}/register`. Idempotent on byte-identical
     /// re-register (the relay returns 200 if our `(auth_key,
     /// registered_at, intent)` tuple matches what's stored).
     pub async fn register(&self, registered_at: i64) -> SyncResult<()> {
-        let intent_text = intent_msg(&self.group_id, &self.auth_key, registered_at);
-        let intent = sign_intent(&self.group_key, &intent_text);
-        // Recovery-phrase discovery handle (ra7 P0 step 2): a one-way
-        // PRF of the group key alone, independent of `group_id`.
-        // Published on every registration so a future phrase-only
-        // device (has the key, not the group_id) can resolve this
-        // group via `GET /discover/{disc}`. NOT part of the signed
-        // intent — see `intent_msg`.
-        let disc = crate::crypto::recovery::derive_discovery_handle(&self.group_key);
-        let body = serde_json::json!({
-            "auth_key_b64": base64_std(&self.auth_key),
-            "registered_at": registered_at,
-            "intent_b64": base64_std(&intent),
-            "disc_b64": base64_std(&disc),
-        });
+        let body = self.registration_body(&self.keys().group_key, registered_at);
         let url = self.group_url("/register");
         let resp = self
             .http
@@ -141,6 +259,79 @@ impl RelayClient {
         }
     }
 
+    /// `POST /groups/{id}/rekey`: move this group's relay registration to
+    /// `next_key`'s auth key after a key rotation. Authenticated by the
+    /// ordinary request MAC under the CURRENT (outgoing) auth key — only
+    /// a device that held the old epoch can re-register, and the intent
+    /// it uploads is signed with the new key, so joiners of the new epoch
+    /// verify it exactly like a first registration. After this the relay
+    /// accepts the old auth key for reads only, so lagging members can
+    /// still fetch the rotation record but nobody can deposit under the
+    /// retired epoch. Returns the new `registered_at`.
+    pub async fn rekey(&self, next_key: &GroupKey) -> SyncResult<i64> {
+        let registered_at = now_secs_i64();
+        let body = self.registration_body(next_key, registered_at);
+        let body_bytes =
+            serde_json::to_vec(&body).map_err(|e| SyncError::Other(format!("json body: {e}")))?;
+        let path = format!("/groups/{}/rekey", hex::encode(self.group_id.as_bytes()));
+        let url = self
+            .base_url
+            .join(&path)
+            .map_err(|e| SyncError::Other(format!("url join: {e}")))?;
+        let nonce_b64 = self.fresh_nonce_b64();
+        let ts = now_secs_i64();
+        let canonical = canonical_request(
+            "POST",
+            &path,
+            "",
+            &nonce_b64,
+            ts,
+            &body_hash_hex(&body_bytes),
+        );
+        let mac = compute_request_mac(&self.keys().auth_key, &canonical);
+        let resp = self
+            .http
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header("X-Tesela-Group", hex::encode(self.group_id.as_bytes()))
+            .header("X-Tesela-Device", hex::encode(self.device_id.as_bytes()))
+            .header("X-Tesela-Nonce", &nonce_b64)
+            .header("X-Tesela-Ts", ts.to_string())
+            .header("X-Tesela-Mac", base64_std(&mac))
+            .body(body_bytes)
+            .send()
+            .await
+            .map_err(net_err("rekey"))?;
+        if !resp.status().is_success() {
+            return Err(SyncError::Crypto(format!(
+                "rekey: relay returned {}",
+                resp.status()
+            )));
+        }
+        Ok(registered_at)
+    }
+
+    /// JSON body shared by `/register` and `/rekey`: `key`'s auth key plus
+    /// the intent signed with `key`.
+    fn registration_body(&self, key: &GroupKey, registered_at: i64) -> serde_json::Value {
+        let auth_key = derive_relay_auth_key(key, &self.group_id);
+        let intent_text = intent_msg(&self.group_id, &auth_key, registered_at);
+        let intent = sign_intent(key, &intent_text);
+        // Recovery-phrase discovery handle (ra7 P0 step 2): a one-way
+        // PRF of the group key alone, independent of `group_id`.
+        // Published on every registration so a future phrase-only
+        // device (has the key, not the group_id) can resolve this
+        // group via `GET /discover/{disc}`. NOT part of the signed
+        // intent — see `intent_msg`.
+        let disc = crate::crypto::recovery::derive_discovery_handle(key);
+        serde_json::json!({
+            "auth_key_b64": base64_std(&auth_key),
+            "registered_at": registered_at,
+            "intent_b64": base64_std(&intent),
+            "disc_b64": base64_std(&disc),
+        })
+    }
+
     /// Higher-level register: try with `now()`; on conflict, fetch
     /// the stored record + verify intent + retry register with the
     /// stored timestamp so the idempotent path succeeds. Lets a
@@ -155,8 +346,9 @@ impl RelayClient {
                 let stored = self.fetch_registration().await?.ok_or_else(|| {
                     SyncError::Crypto("relay 409 but /registration returned 404".into())
                 })?;
-                let intent_text = intent_msg(&self.group_id, &self.auth_key, stored.registered_at);
-                if !verify_intent(&self.group_key, &intent_text, &stored.intent) {
+                let keys = self.keys();
+                let intent_text = intent_msg(&self.group_id, &keys.auth_key, stored.registered_at);
+                if !verify_intent(&keys.group_key, &intent_text, &stored.intent) {
                     return Err(SyncError::Crypto(
                         "relay registration is hijacked: stored intent does not verify under \
                          our group key. Use admin recovery to delete the bogus registration."
@@ -211,8 +403,9 @@ impl RelayClient {
         // registration stored under a different auth_key (wrong
         // group_key, wrong group_id, or a squatter) already fails this
         // verify — no need for the relay to echo the key back.
-        let intent_text = intent_msg(&self.group_id, &self.auth_key, stored.registered_at);
-        if !verify_intent(&self.group_key, &intent_text, &stored.intent) {
+        let keys = self.keys();
+        let intent_text = intent_msg(&self.group_id, &keys.auth_key, stored.registered_at);
+        if !verify_intent(&keys.group_key, &intent_text, &stored.intent) {
             return Err(SyncError::Crypto(
                 "relay registration intent does not verify under our group_key — HIJACKED. \
                  Use admin recovery to delete the bogus registration and re-pair."
@@ -227,7 +420,7 @@ impl RelayClient {
     /// `(seq, ts)` so callers can pin their last-deposited cursor.
     pub async fn put_envelope(&self, envelope: SyncEnvelope) -> SyncResult<(i64, f64)> {
         let aad = envelope_aad(self.device_id.as_bytes(), self.group_id.as_bytes());
-        let sealed = aead_seal(&self.group_key, &envelope.ciphertext, &aad)?;
+        let sealed = aead_seal(&self.keys().group_key, &envelope.ciphertext, &aad)?;
         let outer = OuterPayload {
             nonce: sealed.nonce,
             ciphertext: sealed.ciphertext,
@@ -255,7 +448,7 @@ impl RelayClient {
             ts,
             &body_hash_hex(&body_bytes),
         );
-        let mac = compute_request_mac(&self.auth_key, &canonical);
+        let mac = compute_request_mac(&self.keys().auth_key, &canonical);
         let resp = self
             .http
             .put(url)
@@ -306,7 +499,7 @@ impl RelayClient {
         let nonce_b64 = self.fresh_nonce_b64();
         let ts = now_secs_i64();
         let canonical = canonical_request("GET", &path, &query, &nonce_b64, ts, "");
-        let mac = compute_request_mac(&self.auth_key, &canonical);
+        let mac = compute_request_mac(&self.keys().auth_key, &canonical);
         let resp = self
             .http
             .get(url)
@@ -351,6 +544,115 @@ impl RelayClient {
         Ok(out)
     }
 
+    /// Park until the relay announces an op newer than `since`, or
+    /// `max_wait` elapses. Reads the relay's `GET /groups/{id}/stream`
+    /// feed and returns `Some(seq)` as soon as a notice past `since` has
+    /// arrived — now or on an earlier call. `seq` is the highest one
+    /// announced, a lower bound on the group head; the caller still
+    /// [`poll`](Self::poll)s for the rows. Returns `None` when the window
+    /// passed quietly.
+    ///
+    /// The stream connection outlives the call: the next wait picks up
+    /// where this one stopped reading, so a caller alternating waits and
+    /// polls holds one connection for its lifetime. A dropped or refused
+    /// connection is re-opened with exponential backoff.
+    ///
+    /// Never fails: a relay without `/stream` (404/405) flips this
+    /// client to timer-only mode for its lifetime, and while the stream
+    /// is down the call just sleeps out the window — so the loop
+    /// degrades to exactly the old poll timer.
+    pub async fn wait_for_ops(&self, since: i64, max_wait: Duration) -> Option<i64> {
+        let deadline = tokio::time::Instant::now() + max_wait;
+        if self.live_stream.load(Ordering::Relaxed) {
+            let mut stream = self.stream.lock().await;
+            let notice = self.next_stream_notice(&mut stream, since);
+            if let Ok(Some(seq)) = tokio::time::timeout_at(deadline, notice).await {
+                return Some(seq);
+            }
+        }
+        tokio::time::sleep_until(deadline).await;
+        None
+    }
+
+    /// Read `stream` until it has announced a seq past `since`,
+    /// (re)connecting as needed. `None` once the relay turns out not to
+    /// serve `/stream`. Cancel-safe: unread notices stay in `stream`.
+    async fn next_stream_notice(&self, stream: &mut OpStream, since: i64) -> Option<i64> {
+        loop {
+            if stream.announced > since {
+                return Some(stream.announced);
+            }
+            let Some(resp) = stream.response.as_mut() else {
+                if let Some(retry_at) = stream.retry_at {
+                    tokio::time::sleep_until(retry_at).await;
+                }
+                match self.open_stream(since).await {
+                    Ok(Some(resp)) => {
+                        stream.response = Some(resp);
+                        stream.buf.clear();
+                        stream.failures = 0;
+                        stream.retry_at = None;
+                    }
+                    Ok(None) => return None,
+                    Err(e) => stream.fail(&e),
+                }
+                continue;
+            };
+            match resp.chunk().await {
+                Ok(Some(chunk)) => {
+                    stream.buf.push_str(&String::from_utf8_lossy(&chunk));
+                    if let Some(seq) = drain_sse_op_seq(&mut stream.buf) {
+                        stream.announced = stream.announced.max(seq);
+                    }
+                }
+                Ok(None) => stream.fail(&SyncError::Transport("relay closed the stream".into())),
+                Err(e) => stream.fail(&net_err("stream body")(e)),
+            }
+        }
+    }
+
+    /// Open `GET /groups/{id}/stream?since=N`. `Ok(None)` (and timer-only
+    /// mode from then on) when the relay doesn't serve it.
+    async fn open_stream(&self, since: i64) -> SyncResult<Option<reqwest::Response>> {
+        let path = format!("/groups/{}/stream", hex::encode(self.group_id.as_bytes()));
+        let query = format!("since={since}");
+        let url = self
+            .base_url
+            .join(&format!("{path}?{query}"))
+            .map_err(|e| SyncError::Other(format!("url join: {e}")))?;
+        let nonce_b64 = self.fresh_nonce_b64();
+        let ts = now_secs_i64();
+        let canonical = canonical_request("GET", &path, &query, &nonce_b64, ts, "");
+        let mac = compute_request_mac(&self.keys().auth_key, &canonical);
+        let resp = self
+            .stream_http
+            .get(url)
+            .header("Accept", "text/event-stream")
+            .header("X-Tesela-Group", hex::encode(self.group_id.as_bytes()))
+            .header("X-Tesela-Device", hex::encode(self.device_id.as_bytes()))
+            .header("X-Tesela-Nonce", &nonce_b64)
+            .header("X-Tesela-Ts", ts.to_string())
+            .header("X-Tesela-Mac", base64_std(&mac))
+            .send()
+            .await
+            .map_err(|e| SyncError::Transport(format!("stream: {e}")))?;
+        if matches!(
+            resp.status(),
+            StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED
+        ) {
+            tracing::info!("relay has no /stream endpoint; falling back to timed polling");
+            self.live_stream.store(false, Ordering::Relaxed);
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            return Err(SyncError::Protocol(format!(
+                "stream: relay returned {}",
+                resp.status()
+            )));
+        }
+        Ok(Some(resp))
+    }
+
     /// Decode + AEAD-open one relay row. Failures here are
     /// deterministic per-row conditions, isolated so `poll` can skip
     /// the row instead of failing the whole batch.
@@ -366,7 +668,10 @@ impl RelayClient {
         let outer: OuterPayload = postcard::from_bytes(&outer_bytes)
             .map_err(|e| SyncError::Other(format!("postcard outer: {e}")))?;
         let aad = envelope_aad(&from_device_arr, self.group_id.as_bytes());
-        let plaintext = aead_open(&self.group_key, &outer.nonce, &outer.ciphertext, &aad)?;
+        // Rows carry no epoch tag (the relay shouldn't learn when a group
+        // rotated from its ciphertext), so rows from before a rotation are
+        // tried against the retired keys, newest first.
+        let plaintext = self.trial_open(&outer.nonce, &outer.ciphertext, &aad)?;
         Ok(SyncEnvelope {
             from_device: DeviceId::from_bytes(from_device_arr),
             to_group: self.group_id,
@@ -555,6 +860,7 @@ impl RelayClient {
         snapshot_seq: i64,
         snapshots: &[(Vec<u8>, Vec<u8>)],
     ) -> SyncResult<Vec<SealedSnapshotEntry>> {
+        let keys = self.keys();
         let mut entries = Vec::with_capacity(snapshots.len());
         for (stream_id, plaintext) in snapshots {
             // Preserve the legacy OuterPayload + group-only AEAD so old
@@ -562,7 +868,7 @@ impl RelayClient {
             // marker makes the appended routing record mandatory for new
             // clients, preventing a relay from stripping it as a downgrade.
             let sealed = aead_seal_with_nonce_prefix(
-                &self.group_key,
+                &keys.group_key,
                 plaintext,
                 &snapshot_aad(self.group_id.as_bytes()),
                 SNAPSHOT_V2_NONCE_PREFIX,
@@ -575,7 +881,7 @@ impl RelayClient {
                 .map_err(|e| SyncError::Other(format!("postcard serialize outer: {e}")))?;
             append_snapshot_route_record(
                 &mut outer_bytes,
-                &self.group_key,
+                &keys.group_key,
                 self.group_id.as_bytes(),
                 stream_id,
                 snapshot_seq,
@@ -631,7 +937,7 @@ impl RelayClient {
             ts,
             &body_hash_hex(&body_bytes),
         );
-        let mac = compute_request_mac(&self.auth_key, &canonical);
+        let mac = compute_request_mac(&self.keys().auth_key, &canonical);
         let resp = self
             .http
             .put(url)
@@ -679,7 +985,7 @@ impl RelayClient {
         let nonce_b64 = self.fresh_nonce_b64();
         let ts = now_secs_i64();
         let canonical = canonical_request("GET", &path, "", &nonce_b64, ts, "");
-        let mac = compute_request_mac(&self.auth_key, &canonical);
+        let mac = compute_request_mac(&self.keys().auth_key, &canonical);
         let resp = self
             .http
             .get(url)
@@ -710,13 +1016,7 @@ impl RelayClient {
             let outer_bytes = b64
                 .decode(&entry.payload_b64)
                 .map_err(|e| SyncError::Other(format!("payload base64: {e}")))?;
-            let plaintext = open_snapshot_payload(
-                &self.group_key,
-                self.group_id.as_bytes(),
-                &stream_id,
-                entry.snapshot_seq,
-                &outer_bytes,
-            )?;
+            let plaintext = self.open_snapshot(&stream_id, entry.snapshot_seq, &outer_bytes)?;
             out.push((stream_id, entry.snapshot_seq, plaintext));
         }
         Ok((wire.compaction_seq, out))
@@ -746,7 +1046,7 @@ impl RelayClient {
             ts,
             &body_hash_hex(&body_bytes),
         );
-        let mac = compute_request_mac(&self.auth_key, &canonical);
+        let mac = compute_request_mac(&self.keys().auth_key, &canonical);
         let resp = self
             .http
             .post(url)
@@ -796,7 +1096,7 @@ impl RelayClient {
             ts,
             &body_hash_hex(&body_bytes),
         );
-        let mac = compute_request_mac(&self.auth_key, &canonical);
+        let mac = compute_request_mac(&self.keys().auth_key, &canonical);
         let resp = self
             .http
             .post(url)
@@ -821,6 +1121,55 @@ impl RelayClient {
 
     // ── Helpers ────────────────────────────────────────────────────
 
+    /// AEAD-open under the current key, then each retired key.
+    fn trial_open(&self, nonce: &[u8; 24], ciphertext: &[u8], aad: &[u8]) -> SyncResult<Vec<u8>> {
+        let keys = self.keys();
+        let err = match aead_open(&keys.group_key, nonce, ciphertext, aad) {
+            Ok(plaintext) => return Ok(plaintext),
+            Err(e) => e,
+        };
+        keys.retired
+            .iter()
+            .find_map(|(_, key)| aead_open(key, nonce, ciphertext, aad).ok())
+            .ok_or(err)
+    }
+
+    /// Open a snapshot under the current epoch, falling back to retired
+    /// epochs newest-first (a snapshot deposited before a rotation).
+    fn open_snapshot(
+        &self,
+        stream_id: &[u8],
+        snapshot_seq: i64,
+        outer_bytes: &[u8],
+    ) -> SyncResult<Vec<u8>> {
+        let epoch_keys = self.keys();
+        let mut keys =
+            std::iter::once(&epoch_keys.group_key).chain(epoch_keys.retired.iter().map(|(_, k)| k));
+        let first = keys.next().expect("current key is always present");
+        let err = match open_snapshot_payload(
+            first,
+            self.group_id.as_bytes(),
+            stream_id,
+            snapshot_seq,
+            outer_bytes,
+        ) {
+            Ok(plaintext) => return Ok(plaintext),
+            Err(e) => e,
+        };
+        for key in keys {
+            if let Ok(plaintext) = open_snapshot_payload(
+                key,
+                self.group_id.as_bytes(),
+                stream_id,
+                snapshot_seq,
+                outer_bytes,
+            ) {
+                return Ok(plaintext);
+            }
+        }
+        Err(err)
+    }
+
     fn group_url(&self, suffix: &str) -> Url {
         let path = format!(
             "/groups/{}{}",
@@ -865,7 +1214,11 @@ pub async fn discover_group(relay_url: &str, disc: &[u8; 32]) -> SyncResult<Opti
         .timeout(std::time::Duration::from_secs(15))
         .build()
         .expect("reqwest client construction is infallible with default config");
-    let resp = http.get(url).send().await.map_err(net_err("discover_group"))?;
+    let resp = http
+        .get(url)
+        .send()
+        .await
+        .map_err(net_err("discover_group"))?;
     if resp.status() == StatusCode::NOT_FOUND {
         return Ok(None);
     }
@@ -1026,6 +1379,12 @@ struct RelayOpWire {
     payload_b64: String,
 }
 
+/// `data` of a `/stream` `op` event.
+#[derive(Debug, Deserialize)]
+struct OpNoticeWire {
+    seq: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct OuterPayload {
     nonce: [u8; 24],
@@ -1034,6 +1393,33 @@ struct OuterPayload {
 
 // ── Free helpers ──────────────────────────────────────────────────
 
+/// Consume every complete server-sent event in `buf`, returning the
+/// highest `seq` carried by an `op` event among them. A trailing
+/// partial event stays in `buf` for the next chunk; keep-alive comments
+/// and unknown events are dropped.
+fn drain_sse_op_seq(buf: &mut String) -> Option<i64> {
+    let mut latest = None;
+    while let Some(end) = buf.find("\n\n") {
+        let block: String = buf.drain(..end + 2).collect();
+        let mut event = "message";
+        let mut data = String::new();
+        for line in block.lines() {
+            if let Some(name) = line.strip_prefix("event:") {
+                event = name.trim();
+            } else if let Some(value) = line.strip_prefix("data:") {
+                data.push_str(value.trim());
+            }
+        }
+        if event != "op" {
+            continue;
+        }
+        if let Ok(notice) = serde_json::from_str::<OpNoticeWire>(&data) {
+            latest = latest.max(Some(notice.seq));
+        }
+    }
+    latest
+}
+
 fn base64_std(bytes: &[u8]) -> String {
     base64::engine::general_purpose::STANDARD.encode(bytes)
 }
@@ -1185,6 +1571,17 @@ mod tests {
         assert_eq!(back.ciphertext, outer.ciphertext);
     }
 
+    #[test]
+    fn sse_parser_keeps_partial_events_and_skips_keepalives() {
+        let mut buf = String::from(":\n\nevent: op\ndata: {\"seq\":4}\n\nevent: op\ndata: {\"se");
+        assert_eq!(drain_sse_op_seq(&mut buf), Some(4));
+        assert_eq!(buf, "event: op\ndata: {\"se");
+        buf.push_str("q\":9}\n\nevent: other\ndata: {\"seq\":99}\n\n");
+        assert_eq!(drain_sse_op_seq(&mut buf), Some(9));
+        assert!(buf.is_empty());
+        assert_eq!(drain_sse_op_seq(&mut buf), None);
+    }
+
     #[test]
     fn client_builds_with_well_formed_inputs() {
         let url = Url::parse("https://relay.example.com").unwrap();
@@ -1219,7 +1616,7 @@ mod tests {
         assert!(legacy_outer.nonce.starts_with(SNAPSHOT_V2_NONCE_PREFIX));
         assert_eq!(
             aead_open(
-                &client.group_key,
+                &client.keys().group_key,
                 &legacy_outer.nonce,
                 &legacy_outer.ciphertext,
                 &snapshot_aad(client.group_id.as_bytes()),
@@ -1229,7 +1626,7 @@ mod tests {
         );
         assert_eq!(
             open_snapshot_payload(
-                &client.group_key,
+                &client.keys().group_key,
                 client.group_id.as_bytes(),
                 &stream_id,
                 41,
@@ -1240,7 +1637,7 @@ mod tests {
         );
         assert!(
             open_snapshot_payload(
-                &client.group_key,
+                &client.keys().group_key,
                 client.group_id.as_bytes(),
                 b"note-b",
                 41,
@@ -1251,7 +1648,7 @@ mod tests {
         );
         assert_eq!(
             open_snapshot_payload(
-                &client.group_key,
+                &client.keys().group_key,
                 client.group_id.as_bytes(),
                 &stream_id,
                 0,
@@ -1266,7 +1663,7 @@ mod tests {
         let stripped_len = payload.len() - route_suffix.len();
         assert!(
             open_snapshot_payload(
-                &client.group_key,
+                &client.keys().group_key,
                 client.group_id.as_bytes(),
                 &stream_id,
                 41,
@@ -1280,7 +1677,7 @@ mod tests {
         tampered[stripped_len + 4] ^= 0x01;
         assert!(
             open_snapshot_payload(
-                &client.group_key,
+                &client.keys().group_key,
                 client.group_id.as_bytes(),
                 &stream_id,
                 41,
@@ -24,6 +24,23 @@ pub struct LoroDocUpdate {
     pub update_bytes: Vec<u8>,
 }
 
+impl LoroDocUpdate {
+    /// The note slug (`root.slug`) these bytes carry, read from a scratch
+    /// doc — lets a receiver see which page an unseen doc would land on
+    /// before importing it. `None` for a delta that doesn't carry the slug
+    /// (or bytes that don't import).
+    pub fn note_slug(&self) -> Option<String> {
+        let doc = loro::LoroDoc::new();
+        doc.import(&self.update_bytes).ok()?;
+        doc.get_map("root")
+            .get("slug")
+            .and_then(|v| v.into_value().ok())
+            .and_then(|v| v.into_string().ok())
+            .map(|s| (*s).clone())
+            .filter(|s| !s.is_empty())
+    }
+}
+
 /// Magic + version prefix for the Loro relay payload (protocol v2).
 ///
 /// The legacy v1 payload is a bare `postcard(Vec<EncodedOp>)` with NO
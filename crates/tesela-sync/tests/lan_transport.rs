@@ -0,0 +1,198 @@
+//! `transport::lan` over the loopback interface: two devices of one group
+//! open a TLS session pinned to group-key-derived keys and carry Loro
+//! updates end-to-end without a relay; a device with the wrong key, or a
+//! listener answering for the wrong device, never gets a session, and a
+//! rekey locks out devices left on the retired epoch.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use tesela_sync::wire::{decode_loro_relay_payload, encode_loro_relay_payload, LoroDocUpdate};
+use tesela_sync::{
+    DeviceId, GroupId, GroupIdentity, GroupKey, Hlc, LanTransport, LoroEngine, OpPayload,
+    SyncEngine, SyncEnvelope, Transport, TransportTarget,
+};
+
+fn group(key: u8) -> GroupIdentity {
+    GroupIdentity {
+        group_id: GroupId::from_bytes([0x61; 16]),
+        group_key: GroupKey::from_bytes([key; 32]),
+    }
+}
+
+fn device(b: u8) -> DeviceId {
+    DeviceId::from_bytes([b; 16])
+}
+
+async fn bind(dev: DeviceId, group: &GroupIdentity) -> LanTransport {
+    LanTransport::bind(dev, group, "127.0.0.1:0".parse().unwrap())
+        .await
+        .expect("bind loopback listener")
+}
+
+fn envelope(from: DeviceId, group: &GroupIdentity, body: Vec<u8>) -> SyncEnvelope {
+    SyncEnvelope {
+        from_device: from,
+        to_group: group.group_id,
+        nonce: [0u8; 24],
+        ciphertext: body,
+    }
+}
+
+#[tokio::test]
+async fn paired_devices_exchange_envelopes_both_ways() {
+    let g = group(7);
+    let (a, b) = (device(0xa1), device(0xb1));
+    let ta = bind(a, &g).await;
+    let tb = bind(b, &g).await;
+    ta.add_peer(b, tb.local_addr());
+
+    let mut inbound = tb.incoming();
+    let mut to_b = ta.open(TransportTarget::Peer(b)).await.expect("dial b");
+    let mut from_a = tokio::time::timeout(Duration::from_secs(5), inbound.next())
+        .await
+        .expect("inbound session in time")
+        .expect("inbound session");
+    assert_eq!(to_b.peer(), b);
+    assert_eq!(from_a.peer(), a, "listener learned the pinned dialer");
+
+    to_b.send(envelope(a, &g, vec![1, 2, 3])).await.unwrap();
+    let got = from_a.recv().await.unwrap().expect("envelope at b");
+    assert_eq!(got.from_device, a);
+    assert_eq!(got.ciphertext, vec![1, 2, 3]);
+
+    from_a.send(envelope(b, &g, vec![9])).await.unwrap();
+    let got = to_b.recv().await.unwrap().expect("envelope at a");
+    assert_eq!(got.ciphertext, vec![9]);
+
+    to_b.close().await.unwrap();
+    assert!(
+        from_a.recv().await.unwrap().is_none(),
+        "close is a clean end of stream"
+    );
+}
+
+#[tokio::test]
+async fn loro_updates_converge_over_a_lan_session() {
+    let g = group(8);
+    let (a, b) = (device(0xa2), device(0xb2));
+    let author = LoroEngine::new(a, Arc::new(Hlc::new(a)));
+    let reader = LoroEngine::new(b, Arc::new(Hlc::new(b)));
+    let note = tesela_core::stable_uuid_from_slug("lan-note");
+    author
+        .record_local(OpPayload::NoteUpsert {
+            note_id: note,
+            display_alias: Some("lan-note".into()),
+            title: "lan-note".into(),
+            content: "- over the wire <!-- bid:02020202-0202-0202-0202-020202020202 -->\n".into(),
+            created_at_millis: 1,
+        })
+        .await
+        .unwrap();
+
+    let ta = bind(a, &g).await;
+    let tb = bind(b, &g).await;
+    ta.add_peer(b, tb.local_addr());
+    let mut inbound = tb.incoming();
+    let mut out = ta.open(TransportTarget::Peer(b)).await.unwrap();
+    let mut session = inbound.next().await.unwrap();
+
+    let updates: Vec<LoroDocUpdate> = author
+        .produce_relay_updates()
+        .await
+        .into_iter()
+        .map(|(doc, update_bytes, _vv)| LoroDocUpdate { doc, update_bytes })
+        .collect();
+    assert!(!updates.is_empty());
+    let payload = encode_loro_relay_payload(&updates).unwrap();
+    out.send(envelope(a, &g, payload)).await.unwrap();
+
+    let received = session.recv().await.unwrap().unwrap();
+    let decoded = decode_loro_relay_payload(&received.ciphertext)
+        .unwrap()
+        .expect("v2 payload");
+    let batch: Vec<([u8; 16], Vec<u8>)> = decoded
+        .into_iter()
+        .map(|u| (u.doc, u.update_bytes))
+        .collect();
+    let report = reader.apply_relay_updates(&batch).await;
+    assert!(report.applied.contains(&note), "{report:?}");
+    assert_eq!(
+        reader.render_note_full(note).await,
+        author.render_note_full(note).await
+    );
+}
+
+#[tokio::test]
+async fn a_device_without_the_group_key_gets_no_session() {
+    let (a, b) = (device(0xa3), device(0xb3));
+    let tb = bind(b, &group(9)).await;
+    // Same group id, different key: everything it derives is wrong.
+    let intruder = bind(a, &group(10)).await;
+    intruder.add_peer(b, tb.local_addr());
+
+    let mut inbound = tb.incoming();
+    assert!(
+        intruder.open(TransportTarget::Peer(b)).await.is_err(),
+        "dialer cannot pin the listener's real key"
+    );
+    assert!(
+        tokio::time::timeout(Duration::from_millis(500), inbound.next())
+            .await
+            .is_err(),
+        "listener surfaced no session"
+    );
+}
+
+#[tokio::test]
+async fn a_listener_answering_for_another_device_is_rejected() {
+    let g = group(11);
+    let (a, b, c) = (device(0xa4), device(0xb4), device(0xc4));
+    let ta = bind(a, &g).await;
+    let tb = bind(b, &g).await;
+    // `a` believes `c` lives at `b`'s address; `b` can't present `c`'s key.
+    ta.add_peer(c, tb.local_addr());
+    let err = match ta.open(TransportTarget::Peer(c)).await {
+        Ok(_) => panic!("session opened to the wrong device"),
+        Err(e) => e,
+    };
+    assert!(err.to_string().contains("transport"), "{err}");
+}
+
+#[tokio::test]
+async fn rekeying_locks_out_a_device_left_on_the_old_epoch() {
+    let (a, b, c) = (device(0xa5), device(0xb5), device(0xc5));
+    let old = group(12);
+    let ta = bind(a, &old).await;
+    let tb = bind(b, &old).await;
+    let removed = bind(c, &old).await;
+
+    // `a` and `b` move to the next epoch; `c` was removed and never sees it.
+    let next = group(13);
+    ta.rekey(&next).unwrap();
+    tb.rekey(&next).unwrap();
+
+    removed.add_peer(b, tb.local_addr());
+    let mut inbound = tb.incoming();
+    assert!(
+        removed.open(TransportTarget::Peer(b)).await.is_err(),
+        "old-epoch dialer cannot pin the rekeyed listener"
+    );
+    assert!(
+        tokio::time::timeout(Duration::from_millis(500), inbound.next())
+            .await
+            .is_err(),
+        "listener surfaced no session for the old epoch"
+    );
+
+    ta.add_peer(b, tb.local_addr());
+    let mut to_b = ta.open(TransportTarget::Peer(b)).await.expect("dial b");
+    let mut from_a = tokio::time::timeout(Duration::from_secs(5), inbound.next())
+        .await
+        .expect("inbound session in time")
+        .expect("inbound session");
+    to_b.send(envelope(a, &next, vec![4])).await.unwrap();
+    let got = from_a.recv().await.unwrap().expect("envelope at b");
+    assert_eq!(got.ciphertext, vec![4]);
+}
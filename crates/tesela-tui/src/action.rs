@@ -56,6 +56,25 @@ pub enum Action {
     ExitEditMode { save: bool },
     EditInput(KeyEvent),
 
+    // Block outliner
+    EnterOutliner,
+    ExitOutliner { save: bool },
+    OutlineNext,
+    OutlinePrev,
+    OutlineIndent,
+    OutlineOutdent,
+    OutlineMoveUp,
+    OutlineMoveDown,
+    OutlineToggleFold,
+    OutlineToggleStatus,
+
+    // Property picker
+    TogglePropertyPicker,
+    PropertyPickerQuery(String),
+    PropertyPickerSelect,
+    PropertyPickerNext,
+    PropertyPickerPrev,
+
     // Status
     ShowMessage(String),
     ShowError(String),
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -12,12 +13,14 @@ use ratatui::{
     layout::{Constraint, Direction, Layout},
     Terminal,
 };
+use tokio::sync::broadcast;
 use tokio_stream::StreamExt;
 use tui_textarea::TextArea;
 
-use tesela_core::daily::DailyNoteConfig;
+use tesela_core::daily::{self, DailyNoteConfig};
 use tesela_core::db::SqliteIndex;
-use tesela_core::note::NoteId;
+use tesela_core::indexer::NoteEvent;
+use tesela_core::note::{Note, NoteId};
 use tesela_core::storage::filesystem::FsNoteStore;
 use tesela_core::storage::markdown::generate_frontmatter;
 use tesela_core::traits::link_graph::LinkGraph;
@@ -28,46 +31,125 @@ use crate::{
     action::Action,
     event::{self, Event},
     handler::handle,
-    state::{mode::Mode, AppState},
+    mosaic_engine,
+    state::{mode::Mode, outline::OutlineState, AppState, PropertyPickerStage},
 };
 
 const TICK_INTERVAL_MS: u64 = 250;
 
 pub struct App {
+    mosaic: PathBuf,
     store: Arc<FsNoteStore>,
     index: Arc<SqliteIndex>,
     state: AppState,
     fuzzy_matcher: SkimMatcherV2,
     editor_textarea: Option<TextArea<'static>>,
+    /// Materialized-note changes from the Indexer's watcher — engine writes
+    /// by this TUI, the CLI, MCP, or a relay-fed server all land as `.md`
+    /// rewrites — so the listing and open note refresh live.
+    note_events: broadcast::Receiver<NoteEvent>,
 }
 
 impl App {
-    pub async fn new(store: Arc<FsNoteStore>, index: Arc<SqliteIndex>) -> Self {
-        let mut state = AppState::default();
-
-        // Local-only banner (tesela-ows.3): the TUI writes via a raw
-        // `FsNoteStore` write, not through the Loro engine like the CLI
-        // (tesela-ows.2) and MCP (tesela-ows.3) now do — its edits never
-        // sync and get reverted by the engine's next materialize. Not
-        // ported yet; daily-driver is web/desktop. See README.
-        state.status_message =
-            Some("⚠ local-only: TUI writes do not sync — daily-driver is web/desktop".to_string());
-
-        // Start on today's daily note
-        let today = chrono::Utc::now().date_naive();
-        let config = DailyNoteConfig::default();
-        if let Ok(note) = store.daily_note(Some(today), &config).await {
-            state.current_note = Some(note);
-            state.mode = Mode::NoteView;
-        }
-
-        Self {
+    pub async fn new(
+        mosaic: PathBuf,
+        store: Arc<FsNoteStore>,
+        index: Arc<SqliteIndex>,
+        note_events: broadcast::Receiver<NoteEvent>,
+    ) -> Self {
+        let mut app = Self {
+            mosaic,
             store,
             index,
-            state,
+            state: AppState::default(),
             fuzzy_matcher: SkimMatcherV2::default(),
             editor_textarea: None,
+            note_events,
+        };
+
+        // Start on today's daily note. Creating it needs the writer lock;
+        // while the server or desktop app holds the mosaic, start read-only
+        // on the menu instead — browsing and search need no lock.
+        match app.todays_daily_note().await {
+            Ok(note) => {
+                app.state.current_note = Some(note);
+                app.state.mode = Mode::NoteView;
+            }
+            Err(e) if e.downcast_ref::<mosaic_engine::MosaicLocked>().is_some() => {
+                app.state.status_message = Some(
+                    "Read-only: tesela-server (or the desktop app) holds this mosaic, \
+                     so today's note wasn't created — edit there"
+                        .to_string(),
+                );
+            }
+            Err(e) => app.state.error_message = Some(format!("{e:#}")),
         }
+        app
+    }
+
+    /// Today's daily note, created through the engine on first open (a
+    /// plain `FsNoteStore::daily_note` would write the file directly).
+    async fn todays_daily_note(&self) -> Result<Note> {
+        let today = chrono::Utc::now().date_naive();
+        let config = DailyNoteConfig::default();
+        let path = self
+            .mosaic
+            .join("notes")
+            .join(daily::daily_note_filename(today, &config));
+        if !path.exists() {
+            let slug = daily::daily_note_title(today, &config);
+            let content = daily::daily_note_content(today, &config);
+            mosaic_engine::save_note(&self.mosaic, &slug, &content).await?;
+        }
+        Ok(self.store.daily_note(Some(today), &config).await?)
+    }
+
+    async fn refresh_listing(&mut self) {
+        match self
+            .store
+            .list(self.state.listing.filter_tag.as_deref(), 100, 0)
+            .await
+        {
+            Ok(notes) => {
+                self.state.listing.notes = notes;
+                let max = self.state.listing.notes.len().saturating_sub(1);
+                self.state.listing.selected = self.state.listing.selected.min(max);
+            }
+            Err(e) => self.state.error_message = Some(e.to_string()),
+        }
+    }
+
+    /// Apply an on-disk change made by any writer. The open note is only
+    /// replaced outside inline editing and the outliner, so a remote edit
+    /// never clobbers a buffer mid-edit.
+    async fn on_note_event(&mut self, event: NoteEvent) {
+        let current = self.state.current_note.as_ref().map(|n| n.id.clone());
+        let editing = matches!(self.state.mode, Mode::Editing | Mode::Outliner);
+        match event {
+            NoteEvent::Created(note) | NoteEvent::Updated(note) => {
+                if current.as_ref() == Some(&note.id) && !editing {
+                    self.state.current_note = Some(note);
+                }
+            }
+            NoteEvent::Deleted(id) => {
+                if current.as_ref() == Some(&id) && !editing {
+                    self.state.current_note = None;
+                    if matches!(self.state.mode, Mode::NoteView | Mode::GraphView) {
+                        self.state.mode = Mode::Listing;
+                    }
+                    self.state.status_message = Some(format!("{id} was deleted elsewhere"));
+                }
+            }
+        }
+        self.refresh_listing().await;
+    }
+
+    async fn create_note(&self, title: &str) -> Result<Note> {
+        let slug = mosaic_engine::create_note(&self.mosaic, title).await?;
+        self.store
+            .get(&NoteId::new(&slug))
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("note '{}' not found after create", title))
     }
 
     pub async fn run<B: ratatui::backend::Backend>(
@@ -88,6 +170,15 @@ impl App {
                         None => continue,
                     }
                 }
+                note_event = self.note_events.recv() => {
+                    match note_event {
+                        Ok(ev) => self.on_note_event(ev).await,
+                        // Missed some events: a full refresh covers them.
+                        Err(broadcast::error::RecvError::Lagged(_)) => self.refresh_listing().await,
+                        Err(broadcast::error::RecvError::Closed) => {}
+                    }
+                    continue;
+                }
                 _ = tokio::time::sleep(Duration::from_millis(TICK_INTERVAL_MS)) => Event::Tick,
             };
 
@@ -132,6 +223,7 @@ impl App {
             Mode::Editing => {
                 crate::view::editing::render(f, chunks[0], &self.state, &self.editor_textarea)
             }
+            Mode::Outliner => crate::view::outline::render(f, chunks[0], &self.state),
         }
 
         // Status bar
@@ -147,16 +239,31 @@ impl App {
         if self.state.tag_picker.active {
             crate::view::tag_picker::render(f, f.area(), &self.state);
         }
+        if self.state.property_picker.active {
+            crate::view::property_picker::render(f, f.area(), &self.state);
+        }
     }
 
     /// Spawn an external editor, suspending the TUI while it runs.
     /// Uses stdout directly to avoid generic Backend: Write constraint.
+    ///
+    /// Mirrors `tesela edit`: the mosaic is locked and the engine opened
+    /// BEFORE the editor launches, so a running server fails up front
+    /// instead of after the user has typed; the edited file is then
+    /// recorded through the engine so the change syncs.
     async fn spawn_editor<B: ratatui::backend::Backend>(
         &mut self,
         terminal: &mut Terminal<B>,
         path: &std::path::Path,
         note_id: &NoteId,
     ) -> Result<()> {
+        let (_lock, engine) = match mosaic_engine::open_locked_engine(&self.mosaic).await {
+            Ok(opened) => opened,
+            Err(e) => {
+                self.state.error_message = Some(format!("{e:#}"));
+                return Ok(());
+            }
+        };
         let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vim".to_string());
 
         // Suspend TUI — restore normal terminal state
@@ -171,17 +278,25 @@ impl App {
         execute!(std::io::stdout(), EnterAlternateScreen)?;
         terminal.clear()?;
 
+        // The editor wrote the file outside the engine; record it as a
+        // NoteUpsert so it isn't reverted by the next materialize.
+        match std::fs::read_to_string(path) {
+            Ok(edited) => {
+                if let Err(e) =
+                    mosaic_engine::record_content(&engine, note_id.as_str(), &edited).await
+                {
+                    self.state.error_message = Some(format!("{e:#}"));
+                }
+            }
+            Err(e) => self.state.error_message = Some(e.to_string()),
+        }
+        drop(engine);
+
         // Reload note from disk and update index
         if let Ok(Some(note)) = self.store.get(note_id).await {
             let _ = self.index.upsert_note(&note).await;
             self.state.current_note = Some(note);
-            if let Ok(notes) = self
-                .store
-                .list(self.state.listing.filter_tag.as_deref(), 100, 0)
-                .await
-            {
-                self.state.listing.notes = notes;
-            }
+            self.refresh_listing().await;
         }
 
         Ok(())
@@ -199,17 +314,8 @@ impl App {
             }
 
             Action::RefreshList => {
-                match self
-                    .store
-                    .list(self.state.listing.filter_tag.as_deref(), 100, 0)
-                    .await
-                {
-                    Ok(notes) => {
-                        self.state.listing.notes = notes;
-                        self.state.listing.selected = 0;
-                    }
-                    Err(e) => self.state.error_message = Some(e.to_string()),
-                }
+                self.state.listing.selected = 0;
+                self.refresh_listing().await;
             }
 
             Action::SelectNext => match self.state.mode {
@@ -240,47 +346,35 @@ impl App {
 
             Action::EditNote(id) => {
                 if let Ok(Some(note)) = self.store.get(&id).await {
-                    let mosaic = self.store.mosaic_root().await.to_path_buf();
-                    let path = mosaic.join(&note.path);
+                    let path = self.mosaic.join(&note.path);
                     self.state.pending_editor = Some((path, id));
                     self.state.mode = Mode::NoteView;
                 }
             }
 
-            Action::OpenDailyNote => {
-                let today = chrono::Utc::now().date_naive();
-                let config = DailyNoteConfig::default();
-                match self.store.daily_note(Some(today), &config).await {
-                    Ok(note) => {
-                        let mosaic = self.store.mosaic_root().await.to_path_buf();
-                        let path = mosaic.join(&note.path);
-                        let id = note.id.clone();
-                        self.state.current_note = Some(note);
-                        self.state.pending_editor = Some((path, id));
-                        self.state.mode = Mode::NoteView;
-                    }
-                    Err(e) => self.state.error_message = Some(e.to_string()),
+            Action::OpenDailyNote => match self.todays_daily_note().await {
+                Ok(note) => {
+                    let path = self.mosaic.join(&note.path);
+                    let id = note.id.clone();
+                    self.state.current_note = Some(note);
+                    self.state.pending_editor = Some((path, id));
+                    self.state.mode = Mode::NoteView;
                 }
-            }
+                Err(e) => self.state.error_message = Some(format!("{e:#}")),
+            },
 
             Action::DeleteNote(id) => {
                 self.state.confirm_delete = None;
-                match self.store.delete(&id).await {
+                match mosaic_engine::delete_note(&self.mosaic, id.as_str()).await {
                     Ok(()) => {
                         let _ = self.index.remove_note(&id).await;
                         self.state.current_note = None;
                         self.state.status_message = Some("Note deleted".to_string());
-                        if let Ok(notes) = self
-                            .store
-                            .list(self.state.listing.filter_tag.as_deref(), 100, 0)
-                            .await
-                        {
-                            self.state.listing.notes = notes;
-                            self.state.listing.selected = 0;
-                        }
+                        self.state.listing.selected = 0;
+                        self.refresh_listing().await;
                         self.state.mode = Mode::Listing;
                     }
-                    Err(e) => self.state.error_message = Some(e.to_string()),
+                    Err(e) => self.state.error_message = Some(format!("{e:#}")),
                 }
             }
 
@@ -321,11 +415,10 @@ impl App {
                 }
             }
 
-            Action::CreateNote { title } => match self.store.create(&title, "- ", &[]).await {
+            Action::CreateNote { title } => match self.create_note(&title).await {
                 Ok(note) => {
                     let _ = self.index.upsert_note(&note).await;
-                    let mosaic = self.store.mosaic_root().await.to_path_buf();
-                    let path = mosaic.join(&note.path);
+                    let path = self.mosaic.join(&note.path);
                     let id = note.id.clone();
                     self.state.listing.notes.insert(0, note.clone());
                     self.state.current_note = Some(note);
@@ -333,7 +426,7 @@ impl App {
                     self.state.pending_editor = Some((path, id));
                     self.state.mode = Mode::NoteView;
                 }
-                Err(e) => self.state.error_message = Some(e.to_string()),
+                Err(e) => self.state.error_message = Some(format!("{e:#}")),
             },
 
             Action::NewNoteInput(s) => {
@@ -436,17 +529,8 @@ impl App {
                         self.state.listing.filter_tag = Some(tag);
                     }
                     // Refresh the list with the new filter
-                    match self
-                        .store
-                        .list(self.state.listing.filter_tag.as_deref(), 100, 0)
-                        .await
-                    {
-                        Ok(notes) => {
-                            self.state.listing.notes = notes;
-                            self.state.listing.selected = 0;
-                        }
-                        Err(e) => self.state.error_message = Some(e.to_string()),
-                    }
+                    self.state.listing.selected = 0;
+                    self.refresh_listing().await;
                 }
             }
 
@@ -539,28 +623,190 @@ impl App {
                             &note.metadata.custom,
                         );
                         let new_content = format!("{}\n{}", frontmatter, edited_body);
+                        let id = note.id.clone();
 
-                        let mut updated_note = note.clone();
-                        updated_note.content = new_content;
-                        updated_note.body = edited_body;
-                        updated_note.modified_at = chrono::Utc::now();
+                        match mosaic_engine::save_note(&self.mosaic, id.as_str(), &new_content)
+                            .await
+                        {
+                            Ok(()) => {
+                                if let Ok(Some(saved)) = self.store.get(&id).await {
+                                    let _ = self.index.upsert_note(&saved).await;
+                                    self.state.current_note = Some(saved);
+                                }
+                                self.state.status_message = Some("Note saved".to_string());
+                            }
+                            Err(e) => {
+                                // Stay in the editor so the edit isn't lost.
+                                self.state.error_message = Some(format!("{e:#}"));
+                                return Ok(false);
+                            }
+                        }
+                    }
+                }
+                self.editor_textarea = None;
+                self.state.mode = Mode::NoteView;
+            }
 
-                        if let Err(e) = self.store.update(&updated_note).await {
-                            self.state.error_message = Some(e.to_string());
-                        } else {
-                            let _ = self.index.upsert_note(&updated_note).await;
-                            self.state.current_note = Some(updated_note);
+            Action::EnterOutliner => {
+                if let Some(note) = &self.state.current_note {
+                    let defs = self.index.get_all_property_defs().await.unwrap_or_default();
+                    self.state.outline = OutlineState::open(&note.content, defs);
+                    self.state.graph_view_active = false;
+                    self.state.mode = Mode::Outliner;
+                }
+            }
+
+            Action::ExitOutliner { save } => {
+                let content = self.state.outline.content();
+                if let (true, true, Some(content), Some(note)) = (
+                    save,
+                    self.state.outline.dirty,
+                    content,
+                    &self.state.current_note,
+                ) {
+                    let id = note.id.clone();
+                    match mosaic_engine::save_note(&self.mosaic, id.as_str(), &content).await {
+                        Ok(()) => {
+                            if let Ok(Some(saved)) = self.store.get(&id).await {
+                                let _ = self.index.upsert_note(&saved).await;
+                                self.state.current_note = Some(saved);
+                            }
                             self.state.status_message = Some("Note saved".to_string());
                         }
+                        Err(e) => {
+                            // Stay in the outliner so the edits aren't lost.
+                            self.state.error_message = Some(format!("{e:#}"));
+                            return Ok(false);
+                        }
                     }
                 }
-                self.editor_textarea = None;
+                self.state.outline = OutlineState::default();
                 self.state.mode = Mode::NoteView;
             }
 
+            Action::OutlineNext => self.state.outline.cursor_next(),
+            Action::OutlinePrev => self.state.outline.cursor_prev(),
+            Action::OutlineToggleFold => self.state.outline.toggle_fold(),
+
+            Action::OutlineIndent => {
+                self.state.outline.indent();
+            }
+
+            Action::OutlineOutdent => {
+                self.state.outline.outdent();
+            }
+
+            Action::OutlineMoveUp => {
+                self.state.outline.move_up();
+            }
+
+            Action::OutlineMoveDown => {
+                self.state.outline.move_down();
+            }
+
+            Action::OutlineToggleStatus => {
+                self.state.outline.toggle_status();
+            }
+
+            Action::TogglePropertyPicker => {
+                if self.state.property_picker.active {
+                    self.state.property_picker.deactivate();
+                } else if !self.state.outline.blocks().is_empty() {
+                    let mut names: Vec<String> = self
+                        .state
+                        .outline
+                        .property_defs
+                        .iter()
+                        .map(|def| def.name.clone())
+                        .collect();
+                    names.sort();
+                    names.dedup();
+                    self.state.property_picker.activate(names);
+                }
+            }
+
+            Action::PropertyPickerQuery(q) => {
+                self.state.property_picker.query = q;
+                self.state.property_picker.filter();
+            }
+
+            Action::PropertyPickerNext => {
+                let max = self.state.property_picker.filtered.len().saturating_sub(1);
+                self.state.property_picker.selected =
+                    (self.state.property_picker.selected + 1).min(max);
+            }
+
+            Action::PropertyPickerPrev => {
+                self.state.property_picker.selected =
+                    self.state.property_picker.selected.saturating_sub(1);
+            }
+
+            Action::PropertyPickerSelect => {
+                let selection = self.state.property_picker.selection();
+                match self.state.property_picker.stage {
+                    PropertyPickerStage::Name => {
+                        if let Some(name) = selection {
+                            let name = name.to_lowercase();
+                            let values = self.state.outline.choices(&name);
+                            self.state.property_picker.choose_property(name, values);
+                        }
+                    }
+                    PropertyPickerStage::Value => {
+                        if let Some(name) = self.state.property_picker.property.clone() {
+                            // An empty value removes the property.
+                            let value = selection.unwrap_or_default();
+                            self.state.outline.set_property(&name, &value);
+                        }
+                        self.state.property_picker.deactivate();
+                    }
+                }
+            }
+
             Action::ShowMessage(msg) => self.state.status_message = Some(msg),
             Action::ShowError(err) => self.state.error_message = Some(err),
         }
         Ok(false)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn open_app(mosaic: &std::path::Path) -> App {
+        std::fs::create_dir_all(mosaic.join("notes")).unwrap();
+        std::fs::create_dir_all(mosaic.join(".tesela")).unwrap();
+        let config = tesela_core::config::Config::default();
+        let store = Arc::new(FsNoteStore::new(mosaic.to_path_buf(), config.storage));
+        let index = Arc::new(
+            SqliteIndex::open(&mosaic.join(".tesela").join("tesela.db"))
+                .await
+                .unwrap(),
+        );
+        let (_tx, note_events) = broadcast::channel(1);
+        App::new(mosaic.to_path_buf(), store, index, note_events).await
+    }
+
+    #[tokio::test]
+    async fn starts_on_todays_note_when_the_mosaic_is_free() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = open_app(tmp.path()).await;
+        assert_eq!(app.state.mode, Mode::NoteView);
+        assert!(app.state.current_note.is_some());
+        assert!(app.state.error_message.is_none());
+    }
+
+    #[tokio::test]
+    async fn starts_read_only_while_another_process_holds_the_mosaic() {
+        let tmp = tempfile::tempdir().unwrap();
+        let _held = mosaic_engine::acquire_mosaic_lock(tmp.path()).unwrap();
+        let app = open_app(tmp.path()).await;
+        assert_eq!(app.state.mode, Mode::MainMenu);
+        assert!(app.state.current_note.is_none());
+        assert!(app.state.error_message.is_none());
+        let status = app.state.status_message.as_deref().unwrap_or_default();
+        assert!(status.starts_with("Read-only"), "{status}");
+        let notes = std::fs::read_dir(tmp.path().join("notes")).unwrap().count();
+        assert_eq!(notes, 0, "nothing was written under the lock");
+    }
+}
@@ -31,11 +31,21 @@ fn handle_key(state: &AppState, key: &KeyEvent) -> Vec<Action> {
         return handle_tag_picker(state, key);
     }
 
+    // Property picker captures all input when active
+    if state.property_picker.active {
+        return handle_property_picker(state, key);
+    }
+
     // Editing mode captures all input
     if state.mode == Mode::Editing {
         return handle_editing(key);
     }
 
+    // The outliner owns its keys (Tab, Ctrl+C discard) before the globals
+    if state.mode == Mode::Outliner {
+        return handle_outliner(key);
+    }
+
     // Global shortcuts (work in any mode)
     match (key.modifiers, key.code) {
         (KeyModifiers::CONTROL, KeyCode::Char('c')) => {
@@ -58,6 +68,7 @@ fn handle_key(state: &AppState, key: &KeyEvent) -> Vec<Action> {
         Mode::NoteView | Mode::GraphView => handle_note_view(state, key),
         Mode::NewNote => handle_new_note(state, key),
         Mode::Editing => handle_editing(key), // already handled above, but needed for exhaustiveness
+        Mode::Outliner => handle_outliner(key), // likewise
     }
 }
 
@@ -183,6 +194,7 @@ fn handle_note_view_keys(state: &AppState, key: &KeyEvent) -> Vec<Action> {
             }
         }
         KeyCode::Char('i') => vec![Action::EnterEditMode],
+        KeyCode::Char('o') => vec![Action::EnterOutliner],
         KeyCode::Char('g') => vec![Action::ToggleGraphView],
         KeyCode::Char(']') => vec![Action::OpenNextNote],
         KeyCode::Char('[') => vec![Action::OpenPrevNote],
@@ -209,6 +221,51 @@ fn handle_editing(key: &KeyEvent) -> Vec<Action> {
     }
 }
 
+fn handle_outliner(key: &KeyEvent) -> Vec<Action> {
+    match (key.modifiers, key.code) {
+        (_, KeyCode::Esc) => vec![Action::ExitOutliner { save: true }],
+        (KeyModifiers::CONTROL, KeyCode::Char('c')) => vec![Action::ExitOutliner { save: false }],
+        (KeyModifiers::ALT, KeyCode::Up) | (_, KeyCode::Char('K')) => vec![Action::OutlineMoveUp],
+        (KeyModifiers::ALT, KeyCode::Down) | (_, KeyCode::Char('J')) => {
+            vec![Action::OutlineMoveDown]
+        }
+        (_, KeyCode::Char('j')) | (_, KeyCode::Down) => vec![Action::OutlineNext],
+        (_, KeyCode::Char('k')) | (_, KeyCode::Up) => vec![Action::OutlinePrev],
+        (_, KeyCode::Tab) => vec![Action::OutlineIndent],
+        (_, KeyCode::BackTab) => vec![Action::OutlineOutdent],
+        (_, KeyCode::Char(' ')) | (_, KeyCode::Char('z')) => vec![Action::OutlineToggleFold],
+        (_, KeyCode::Char('x')) => vec![Action::OutlineToggleStatus],
+        (_, KeyCode::Char('p')) => vec![Action::TogglePropertyPicker],
+        (_, KeyCode::Char('?')) => vec![Action::ToggleHelp],
+        _ => vec![],
+    }
+}
+
+fn handle_property_picker(state: &AppState, key: &KeyEvent) -> Vec<Action> {
+    match (key.modifiers, key.code) {
+        (KeyModifiers::CONTROL, KeyCode::Char('j')) | (_, KeyCode::Down) => {
+            vec![Action::PropertyPickerNext]
+        }
+        (KeyModifiers::CONTROL, KeyCode::Char('k')) | (_, KeyCode::Up) => {
+            vec![Action::PropertyPickerPrev]
+        }
+        (_, KeyCode::Esc) => vec![Action::TogglePropertyPicker],
+        (_, KeyCode::Enter) => vec![Action::PropertyPickerSelect],
+        (_, KeyCode::Backspace) => {
+            let mut q = state.property_picker.query.clone();
+            q.pop();
+            vec![Action::PropertyPickerQuery(q)]
+        }
+        (_, KeyCode::Char(c)) => {
+            vec![Action::PropertyPickerQuery(format!(
+                "{}{}",
+                state.property_picker.query, c
+            ))]
+        }
+        _ => vec![],
+    }
+}
+
 fn handle_new_note(state: &AppState, key: &KeyEvent) -> Vec<Action> {
     match key.code {
         KeyCode::Enter => {
@@ -614,6 +671,80 @@ mod tests {
         assert!(!actions.iter().any(|a| matches!(a, Action::Quit)));
     }
 
+    #[test]
+    fn test_note_view_enters_outliner() {
+        let state = AppState {
+            mode: Mode::NoteView,
+            ..AppState::default()
+        };
+        let actions = handle(&state, &key(KeyCode::Char('o')));
+        assert!(actions.iter().any(|a| matches!(a, Action::EnterOutliner)));
+    }
+
+    #[test]
+    fn test_outliner_structural_keys() {
+        let state = AppState {
+            mode: Mode::Outliner,
+            ..AppState::default()
+        };
+        let cases = [
+            (key(KeyCode::Tab), "indent"),
+            (key(KeyCode::BackTab), "outdent"),
+            (key(KeyCode::Char('J')), "down"),
+            (key(KeyCode::Char('K')), "up"),
+            (key(KeyCode::Char(' ')), "fold"),
+            (key(KeyCode::Char('x')), "status"),
+        ];
+        for (event, what) in cases {
+            let actions = handle(&state, &event);
+            let ok = match what {
+                "indent" => matches!(actions[..], [Action::OutlineIndent]),
+                "outdent" => matches!(actions[..], [Action::OutlineOutdent]),
+                "down" => matches!(actions[..], [Action::OutlineMoveDown]),
+                "up" => matches!(actions[..], [Action::OutlineMoveUp]),
+                "fold" => matches!(actions[..], [Action::OutlineToggleFold]),
+                _ => matches!(actions[..], [Action::OutlineToggleStatus]),
+            };
+            assert!(ok, "{what}: {actions:?}");
+        }
+    }
+
+    #[test]
+    fn test_outliner_esc_saves_and_ctrl_c_discards() {
+        let state = AppState {
+            mode: Mode::Outliner,
+            ..AppState::default()
+        };
+        let actions = handle(&state, &key(KeyCode::Esc));
+        assert!(matches!(actions[..], [Action::ExitOutliner { save: true }]));
+        let actions = handle(&state, &ctrl(KeyCode::Char('c')));
+        assert!(matches!(
+            actions[..],
+            [Action::ExitOutliner { save: false }]
+        ));
+        assert!(!actions.iter().any(|a| matches!(a, Action::Quit)));
+    }
+
+    #[test]
+    fn test_property_picker_captures_input_when_active() {
+        let mut state = AppState {
+            mode: Mode::Outliner,
+            ..AppState::default()
+        };
+        state.property_picker.active = true;
+        let actions = handle(&state, &key(KeyCode::Char('x')));
+        assert!(actions
+            .iter()
+            .any(|a| matches!(a, Action::PropertyPickerQuery(q) if q == "x")));
+        assert!(!actions
+            .iter()
+            .any(|a| matches!(a, Action::OutlineToggleStatus)));
+        let actions = handle(&state, &key(KeyCode::Enter));
+        assert!(actions
+            .iter()
+            .any(|a| matches!(a, Action::PropertyPickerSelect)));
+    }
+
     #[test]
     fn test_fuzzy_ctrl_jk_navigate_results() {
         let mut state = AppState::default();
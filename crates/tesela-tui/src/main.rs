@@ -2,6 +2,7 @@ mod action;
 mod app;
 mod event;
 mod handler;
+mod mosaic_engine;
 mod state;
 mod theme;
 mod view;
@@ -31,14 +32,16 @@ async fn main() -> Result<()> {
     let config = Config::default();
     let db_path = mosaic.join(".tesela").join("tesela.db");
 
-    let store = Arc::new(FsNoteStore::new(mosaic, config.storage));
+    let store = Arc::new(FsNoteStore::new(mosaic.clone(), config.storage));
     let index = Arc::new(SqliteIndex::open(&db_path).await?);
 
-    // Wire up the Indexer so file changes appear in search results immediately
+    // Wire up the Indexer so file changes appear in search results immediately,
+    // and forward its change events so the app refreshes live.
+    let (note_event_tx, note_events) = tokio::sync::broadcast::channel(256);
     let store_dyn: Arc<dyn NoteStore> = Arc::clone(&store) as Arc<dyn NoteStore>;
     let index_dyn: Arc<dyn SearchIndex> = Arc::clone(&index) as Arc<dyn SearchIndex>;
     let graph_dyn: Arc<dyn LinkGraph> = Arc::clone(&index) as Arc<dyn LinkGraph>;
-    let indexer = Indexer::new(store_dyn, index_dyn, graph_dyn);
+    let indexer = Indexer::new(store_dyn, index_dyn, graph_dyn).with_notify_tx(note_event_tx);
     indexer.initial_index().await?;
     let indexer_handle = indexer.start().await?;
 
@@ -50,7 +53,7 @@ async fn main() -> Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     // Run app (new() loads today's daily note)
-    let app = app::App::new(store, index).await;
+    let app = app::App::new(mosaic, store, index, note_events).await;
     let result = app.run(&mut terminal).await;
 
     // Restore terminal
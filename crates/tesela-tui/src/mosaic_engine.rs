@@ -0,0 +1,200 @@
+//! Engine-direct writes for the TUI's create / edit / delete actions.
+//!
+//! Mirrors `tesela-mcp/src/mosaic_engine.rs` and the CLI's
+//! `open_locked_engine` pattern (`crates/tesela-cli/src/mosaic_notes.rs`): a
+//! raw `FsNoteStore` write never syncs and gets reverted by the engine's next
+//! materialize (engine-only-writes rule, 2026-06-09). Every TUI write locks
+//! the mosaic, records a `NoteUpsert` / `NoteDelete` through the Loro engine
+//! — which persists the snapshot and materializes `<slug>.md` — and releases
+//! the lock, so the TUI coexists with the CLI and MCP but refuses to write
+//! while `tesela-server` (or the desktop app) holds the mosaic. `tesela-tui`
+//! is its own `[[bin]]` with no shared lib target, so the small locking +
+//! hydrate helpers are duplicated here; keep this in sync with the MCP and
+//! CLI copies if either changes.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::sync::Arc;
+use tesela_core::stable_uuid_from_slug;
+use tesela_sync::{DeviceId, Hlc, LoroEngine, OpPayload, SyncEngine};
+
+/// Why a write was refused while `tesela-server` (or the desktop app)
+/// holds the mosaic. Attached as error context so callers can tell it
+/// apart from a failed write (`anyhow::Error::downcast_ref`).
+#[derive(Debug)]
+pub struct MosaicLocked;
+
+impl std::fmt::Display for MosaicLocked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(
+            "mosaic is locked — tesela-server (or the desktop app) is running on it; \
+             edit there instead, or stop it to write from the TUI",
+        )
+    }
+}
+
+/// Read the mosaic's existing device id (no write); falls back to a random
+/// id if absent/malformed. Mirrors `tesela-cli::backfill_task::load_device_id`.
+fn load_device_id(mosaic: &Path) -> DeviceId {
+    let path = mosaic.join(".tesela").join("device_id.hex");
+    if let Ok(bytes) = std::fs::read(&path) {
+        let s = String::from_utf8_lossy(&bytes);
+        let s = s.trim();
+        if s.len() == 32 {
+            let mut arr = [0u8; 16];
+            let mut ok = true;
+            for i in 0..16 {
+                match u8::from_str_radix(&s[i * 2..i * 2 + 2], 16) {
+                    Ok(b) => arr[i] = b,
+                    Err(_) => {
+                        ok = false;
+                        break;
+                    }
+                }
+            }
+            if ok {
+                return DeviceId::from_bytes(arr);
+            }
+        }
+    }
+    DeviceId::new_random()
+}
+
+/// Exclusive, non-blocking flock on `<mosaic>/.tesela/server.lock` — the
+/// SAME lock the server holds. Mirrors
+/// `tesela-cli::backfill_task::acquire_mosaic_lock`.
+pub(crate) fn acquire_mosaic_lock(mosaic: &Path) -> Result<std::fs::File> {
+    use std::os::unix::io::AsRawFd;
+    let tesela_dir = mosaic.join(".tesela");
+    std::fs::create_dir_all(&tesela_dir)?;
+    let lock_path = tesela_dir.join("server.lock");
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .read(true)
+        .write(true)
+        .truncate(false)
+        .open(&lock_path)?;
+    let rc = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+    if rc != 0 {
+        anyhow::bail!("lock held (EWOULDBLOCK)");
+    }
+    Ok(file)
+}
+
+/// Parse `content`, stamp persistent block ids onto any unstamped bullets,
+/// and return the canonical serialized form. Mirrors
+/// `tesela-cli::mosaic_notes::stamp_block_ids`.
+fn stamp_block_ids(content: &str) -> String {
+    let tree = tesela_core::note_tree::parse_note(content);
+    if !tree.stamped_any {
+        return content.to_string();
+    }
+    tesela_core::note_tree::serialize_note(&tree)
+}
+
+/// Lock the mosaic (refuse while `tesela-server`/the desktop holds it) and
+/// open the Loro engine over its snapshots + materialized notes dir.
+/// Mirrors `tesela-cli::mosaic_notes::open_locked_engine`. The returned
+/// `File` guard must stay alive for the duration of the write; dropping it
+/// releases the flock.
+pub async fn open_locked_engine(mosaic: &Path) -> Result<(std::fs::File, LoroEngine)> {
+    let lock = acquire_mosaic_lock(mosaic).context(MosaicLocked)?;
+    let device = load_device_id(mosaic);
+    let snapshot_dir = mosaic.join(".tesela").join("loro");
+    let notes_dir = mosaic.join("notes");
+    let hlc = Arc::new(Hlc::new(device));
+    let engine = LoroEngine::with_dirs(device, hlc, snapshot_dir, Some(notes_dir))
+        .await
+        .map_err(|e| anyhow::anyhow!("open loro engine: {e}"))?;
+    Ok((lock, engine))
+}
+
+/// Record `content` as the note's new full content: a `NoteUpsert` (the
+/// per-bid reconcile every editor save uses) with block ids stamped,
+/// materialized to `<slug>.md` before returning.
+pub async fn record_content(engine: &LoroEngine, slug: &str, content: &str) -> Result<()> {
+    let stamped = stamp_block_ids(content);
+    tesela_sync::hydrate_note(engine, stable_uuid_from_slug(slug), slug, &stamped)
+        .await
+        .map_err(|e| anyhow::anyhow!("record note {slug}: {e}"))?;
+    Ok(())
+}
+
+/// Lock, record, unlock — for saves that don't already hold the engine.
+pub async fn save_note(mosaic: &Path, slug: &str, content: &str) -> Result<()> {
+    let (_lock, engine) = open_locked_engine(mosaic).await?;
+    record_content(&engine, slug, content).await
+}
+
+/// Create a note through the engine and return its slug. Mirrors
+/// `tesela-cli::cmd_new`'s write path.
+pub async fn create_note(mosaic: &Path, title: &str) -> Result<String> {
+    use tesela_core::storage::markdown::{generate_frontmatter, sanitize_filename};
+
+    let slug = sanitize_filename(title);
+    if mosaic.join("notes").join(format!("{slug}.md")).exists() {
+        anyhow::bail!("Note '{}' already exists", title);
+    }
+    let frontmatter = generate_frontmatter(title, &[], chrono::Utc::now(), &Default::default());
+    save_note(mosaic, &slug, &format!("{}\n- \n", frontmatter)).await?;
+    Ok(slug)
+}
+
+/// Delete a note through the engine: a `NoteDelete` for its doc, which
+/// removes `<slug>.md` and propagates the deletion to peers.
+pub async fn delete_note(mosaic: &Path, slug: &str) -> Result<()> {
+    let (_lock, engine) = open_locked_engine(mosaic).await?;
+    let doc_id = engine
+        .resolve_note_doc_id(slug)
+        .await
+        .map_err(|e| anyhow::anyhow!("resolve note {slug}: {e}"))?;
+    engine
+        .record_local(OpPayload::NoteDelete {
+            note_id: doc_id,
+            display_alias: Some(slug.to_string()),
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("delete note {slug}: {e}"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn create_save_and_delete_materialize_through_the_engine() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mosaic = tmp.path();
+        std::fs::create_dir_all(mosaic.join("notes")).unwrap();
+
+        let slug = create_note(mosaic, "Groceries").await.unwrap();
+        let path = mosaic.join("notes").join(format!("{slug}.md"));
+        assert!(path.exists(), "create materializes the note file");
+        assert!(create_note(mosaic, "Groceries").await.is_err());
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        save_note(
+            mosaic,
+            &slug,
+            &format!("{}- milk\n", content.trim_end_matches("- \n")),
+        )
+        .await
+        .unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(saved.contains("- milk"), "save reaches disk: {saved}");
+        assert!(saved.contains("bid:"), "saved blocks are stamped: {saved}");
+
+        delete_note(mosaic, &slug).await.unwrap();
+        assert!(!path.exists(), "delete removes the materialized file");
+    }
+
+    #[tokio::test]
+    async fn writes_refuse_while_the_mosaic_is_locked() {
+        let tmp = tempfile::tempdir().unwrap();
+        let _held = acquire_mosaic_lock(tmp.path()).unwrap();
+        let err = create_note(tmp.path(), "Blocked").await.unwrap_err();
+        assert!(format!("{err:#}").contains("mosaic is locked"));
+        assert!(err.downcast_ref::<MosaicLocked>().is_some());
+    }
+}
@@ -1,5 +1,6 @@
 pub mod listing;
 pub mod mode;
+pub mod outline;
 pub mod search;
 
 use std::path::PathBuf;
@@ -88,6 +89,77 @@ impl TagPickerState {
     }
 }
 
+/// Which half of the property picker is showing: first the property
+/// name, then a value for it.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PropertyPickerStage {
+    #[default]
+    Name,
+    Value,
+}
+
+/// Property picker overlay for the outliner. Names come from the
+/// mosaic's property definitions; values from the chosen definition's
+/// `values`, or free text when it has none. The typed query is always
+/// accepted as-is, so an unlisted name or value can still be set.
+#[derive(Debug, Clone, Default)]
+pub struct PropertyPickerState {
+    pub active: bool,
+    pub stage: PropertyPickerStage,
+    pub query: String,
+    pub options: Vec<String>,
+    pub filtered: Vec<String>,
+    pub selected: usize,
+    /// Property chosen in the `Name` stage.
+    pub property: Option<String>,
+}
+
+impl PropertyPickerState {
+    pub fn activate(&mut self, names: Vec<String>) {
+        self.active = true;
+        self.stage = PropertyPickerStage::Name;
+        self.property = None;
+        self.set_options(names);
+    }
+
+    /// Move to the `Value` stage for `property`.
+    pub fn choose_property(&mut self, property: String, values: Vec<String>) {
+        self.stage = PropertyPickerStage::Value;
+        self.property = Some(property);
+        self.set_options(values);
+    }
+
+    pub fn deactivate(&mut self) {
+        *self = Self::default();
+    }
+
+    fn set_options(&mut self, options: Vec<String>) {
+        self.query = String::new();
+        self.options = options;
+        self.selected = 0;
+        self.filter();
+    }
+
+    pub fn filter(&mut self) {
+        let q = self.query.to_lowercase();
+        self.filtered = self
+            .options
+            .iter()
+            .filter(|o| o.to_lowercase().contains(&q))
+            .cloned()
+            .collect();
+        self.selected = self.selected.min(self.filtered.len().saturating_sub(1));
+    }
+
+    /// The highlighted option, falling back to the typed query.
+    pub fn selection(&self) -> Option<String> {
+        self.filtered
+            .get(self.selected)
+            .cloned()
+            .or_else(|| Some(self.query.trim().to_string()).filter(|q| !q.is_empty()))
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct AppState {
     pub mode: mode::Mode,
@@ -114,4 +186,8 @@ pub struct AppState {
     pub help_active: bool,
     /// Armed delete confirmation — first D press sets this, second D executes
     pub confirm_delete: Option<NoteId>,
+    /// Block-level outliner over the current note (`Mode::Outliner`)
+    pub outline: outline::OutlineState,
+    /// Property picker overlay (outliner only)
+    pub property_picker: PropertyPickerState,
 }
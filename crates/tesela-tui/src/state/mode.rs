@@ -8,4 +8,5 @@ pub enum Mode {
     GraphView,
     NewNote,
     Editing,
+    Outliner,
 }
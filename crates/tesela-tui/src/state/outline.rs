@@ -0,0 +1,375 @@
+use std::collections::HashSet;
+
+use tesela_core::lifecycle::property_kv;
+use tesela_core::note_tree::{self, NoteTree};
+use tesela_core::types::PropertyDef;
+use uuid::Uuid;
+
+/// Status cycle used when the mosaic has no `status` property page with
+/// `values` (same fallback the web slash menu uses).
+const DEFAULT_STATUS_CHOICES: [&str; 3] = ["todo", "doing", "done"];
+
+/// Block-level outliner over the open note. Operates on the
+/// `note_tree::parse_note` model so every block keeps its
+/// `<!-- bid:... -->` id through indent/move/property edits; the app
+/// serializes with `note_tree::serialize_note` and records the result
+/// through the engine on save.
+#[derive(Debug, Clone, Default)]
+pub struct OutlineState {
+    pub tree: Option<NoteTree>,
+    /// Index into `tree.blocks` of the block under the cursor.
+    pub cursor: usize,
+    /// Collapsed blocks. View-only — folding is never written to the note.
+    pub folded: HashSet<Uuid>,
+    /// Unsaved structural or property edits.
+    pub dirty: bool,
+    /// Property definitions for the picker, loaded on entry.
+    pub property_defs: Vec<PropertyDef>,
+}
+
+impl OutlineState {
+    pub fn open(content: &str, property_defs: Vec<PropertyDef>) -> Self {
+        Self {
+            tree: Some(note_tree::parse_note(content)),
+            cursor: 0,
+            folded: HashSet::new(),
+            dirty: false,
+            property_defs,
+        }
+    }
+
+    pub fn blocks(&self) -> &[note_tree::FlatBlock] {
+        self.tree
+            .as_ref()
+            .map(|t| t.blocks.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Serialized note content (frontmatter preserved verbatim).
+    pub fn content(&self) -> Option<String> {
+        self.tree.as_ref().map(note_tree::serialize_note)
+    }
+
+    /// End (exclusive) of the subtree rooted at `i`.
+    fn subtree_end(&self, i: usize) -> usize {
+        let blocks = self.blocks();
+        let indent = blocks[i].indent;
+        (i + 1..blocks.len())
+            .find(|&j| blocks[j].indent <= indent)
+            .unwrap_or(blocks.len())
+    }
+
+    pub fn has_children(&self, i: usize) -> bool {
+        let blocks = self.blocks();
+        blocks
+            .get(i + 1)
+            .is_some_and(|next| next.indent > blocks[i].indent)
+    }
+
+    /// Indices of blocks not hidden under a folded ancestor.
+    pub fn visible(&self) -> Vec<usize> {
+        let blocks = self.blocks();
+        let mut out = Vec::with_capacity(blocks.len());
+        let mut i = 0;
+        while i < blocks.len() {
+            out.push(i);
+            i = if self.folded.contains(&blocks[i].id) {
+                self.subtree_end(i)
+            } else {
+                i + 1
+            };
+        }
+        out
+    }
+
+    pub fn cursor_next(&mut self) {
+        let visible = self.visible();
+        if let Some(&next) = visible.iter().find(|&&i| i > self.cursor) {
+            self.cursor = next;
+        }
+    }
+
+    pub fn cursor_prev(&mut self) {
+        let visible = self.visible();
+        if let Some(&prev) = visible.iter().rev().find(|&&i| i < self.cursor) {
+            self.cursor = prev;
+        }
+    }
+
+    pub fn toggle_fold(&mut self) {
+        if !self.has_children(self.cursor) {
+            return;
+        }
+        let id = self.blocks()[self.cursor].id;
+        if !self.folded.remove(&id) {
+            self.folded.insert(id);
+        }
+    }
+
+    /// Nest the block (with its subtree) under its previous sibling.
+    pub fn indent(&mut self) -> bool {
+        let i = self.cursor;
+        let blocks = self.blocks();
+        if i == 0 || i >= blocks.len() || blocks[i - 1].indent < blocks[i].indent {
+            return false;
+        }
+        self.shift_subtree(i, 1);
+        true
+    }
+
+    /// Lift the block (with its subtree) one level.
+    pub fn outdent(&mut self) -> bool {
+        let i = self.cursor;
+        if self.blocks().get(i).is_none_or(|b| b.indent == 0) {
+            return false;
+        }
+        self.shift_subtree(i, -1);
+        true
+    }
+
+    fn shift_subtree(&mut self, i: usize, delta: i16) {
+        let end = self.subtree_end(i);
+        if let Some(tree) = self.tree.as_mut() {
+            for block in &mut tree.blocks[i..end] {
+                block.indent = (block.indent as i16 + delta).max(0) as u16;
+            }
+        }
+        self.finish_edit();
+    }
+
+    /// Swap the block's subtree with its previous sibling's.
+    pub fn move_up(&mut self) -> bool {
+        let i = self.cursor;
+        let Some(indent) = self.blocks().get(i).map(|b| b.indent) else {
+            return false;
+        };
+        let Some(prev) = (0..i).rev().find(|&k| self.blocks()[k].indent <= indent) else {
+            return false;
+        };
+        if self.blocks()[prev].indent != indent {
+            return false;
+        }
+        let end = self.subtree_end(i);
+        if let Some(tree) = self.tree.as_mut() {
+            tree.blocks[prev..end].rotate_left(i - prev);
+        }
+        self.cursor = prev;
+        self.finish_edit();
+        true
+    }
+
+    /// Swap the block's subtree with its next sibling's.
+    pub fn move_down(&mut self) -> bool {
+        let i = self.cursor;
+        let Some(indent) = self.blocks().get(i).map(|b| b.indent) else {
+            return false;
+        };
+        let next = self.subtree_end(i);
+        if self.blocks().get(next).is_none_or(|b| b.indent != indent) {
+            return false;
+        }
+        let next_end = self.subtree_end(next);
+        if let Some(tree) = self.tree.as_mut() {
+            tree.blocks[i..next_end].rotate_left(next - i);
+        }
+        self.cursor = i + (next_end - next);
+        self.finish_edit();
+        true
+    }
+
+    /// Current value of an in-text `key:: value` property on the cursor
+    /// block.
+    pub fn property(&self, key: &str) -> Option<String> {
+        let block = self.blocks().get(self.cursor)?;
+        block
+            .text
+            .lines()
+            .skip(1)
+            .filter_map(property_kv)
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    /// Set (or, with an empty value, remove) a property on the cursor
+    /// block. Written as a `key:: value` continuation line — the form
+    /// `parse_note` folds into the block text.
+    pub fn set_property(&mut self, key: &str, value: &str) -> bool {
+        let cursor = self.cursor;
+        let Some(block) = self
+            .tree
+            .as_mut()
+            .and_then(|tree| tree.blocks.get_mut(cursor))
+        else {
+            return false;
+        };
+        block.text = with_property(&block.text, key, value);
+        self.dirty = true;
+        true
+    }
+
+    /// Allowed `values` of a property, if its definition lists any.
+    pub fn choices(&self, key: &str) -> Vec<String> {
+        self.property_defs
+            .iter()
+            .find(|def| def.name.eq_ignore_ascii_case(key))
+            .and_then(|def| def.values.clone())
+            .unwrap_or_default()
+    }
+
+    /// Advance the cursor block's `status::` through the status choices,
+    /// wrapping from the last back to the first.
+    pub fn toggle_status(&mut self) -> bool {
+        let mut choices = self.choices("status");
+        if choices.is_empty() {
+            choices = DEFAULT_STATUS_CHOICES.map(String::from).to_vec();
+        }
+        let next = match self.property("status") {
+            Some(current) => choices
+                .iter()
+                .position(|c| c.eq_ignore_ascii_case(&current))
+                .map(|p| choices[(p + 1) % choices.len()].clone())
+                .unwrap_or_else(|| choices[0].clone()),
+            None => choices[0].clone(),
+        };
+        self.set_property("status", &next)
+    }
+
+    /// Re-derive `parent` from indents after a structural edit so the tree
+    /// stays self-consistent with what `parse_note` would produce.
+    fn finish_edit(&mut self) {
+        if let Some(tree) = self.tree.as_mut() {
+            let mut stack: Vec<(u16, Uuid)> = Vec::new();
+            for block in &mut tree.blocks {
+                while stack
+                    .last()
+                    .is_some_and(|&(indent, _)| indent >= block.indent)
+                {
+                    stack.pop();
+                }
+                block.parent = stack.last().map(|&(_, id)| id);
+                stack.push((block.indent, block.id));
+            }
+        }
+        self.dirty = true;
+    }
+}
+
+/// `text` with the `key:: value` continuation line replaced, appended, or
+/// (for an empty value) removed. The first line is the block's prose and
+/// is never treated as a property line.
+fn with_property(text: &str, key: &str, value: &str) -> String {
+    let mut lines = text.lines();
+    let mut out = vec![lines.next().unwrap_or("").to_string()];
+    let mut replaced = false;
+    for line in lines {
+        if property_kv(line).is_some_and(|(k, _)| k.eq_ignore_ascii_case(key)) {
+            if !replaced && !value.is_empty() {
+                out.push(format!("{key}:: {value}"));
+            }
+            replaced = true;
+        } else {
+            out.push(line.to_string());
+        }
+    }
+    if !replaced && !value.is_empty() {
+        out.push(format!("{key}:: {value}"));
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = concat!(
+        "---\ntitle: \"T\"\n---\n",
+        "- A <!-- bid:00000000-0000-7000-8000-00000000000a -->\n",
+        "  - A1 <!-- bid:00000000-0000-7000-8000-0000000000a1 -->\n",
+        "- B <!-- bid:00000000-0000-7000-8000-00000000000b -->\n",
+        "- C <!-- bid:00000000-0000-7000-8000-00000000000c -->\n",
+    );
+
+    fn texts(o: &OutlineState) -> Vec<(u16, String)> {
+        o.blocks()
+            .iter()
+            .map(|b| (b.indent, b.text.lines().next().unwrap_or("").to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn move_down_carries_the_subtree_and_keeps_ids() {
+        let mut o = OutlineState::open(NOTE, Vec::new());
+        let ids: HashSet<Uuid> = o.blocks().iter().map(|b| b.id).collect();
+        assert!(o.move_down());
+        assert_eq!(
+            texts(&o),
+            vec![
+                (0, "B".into()),
+                (0, "A".into()),
+                (1, "A1".into()),
+                (0, "C".into())
+            ]
+        );
+        assert_eq!(o.cursor, 1);
+        let content = o.content().unwrap();
+        let reparsed = note_tree::parse_note(&content);
+        assert!(!reparsed.stamped_any, "no ids were re-minted: {content}");
+        assert_eq!(
+            reparsed.blocks.iter().map(|b| b.id).collect::<HashSet<_>>(),
+            ids
+        );
+        assert!(content.starts_with("---\ntitle: \"T\"\n---\n"));
+    }
+
+    #[test]
+    fn move_up_stops_at_first_sibling() {
+        let mut o = OutlineState::open(NOTE, Vec::new());
+        o.cursor = 1; // A1 has no previous sibling
+        assert!(!o.move_up());
+        o.cursor = 3;
+        assert!(o.move_up());
+        assert_eq!(texts(&o)[2], (0, "C".into()));
+        assert_eq!(o.cursor, 2);
+    }
+
+    #[test]
+    fn indent_requires_a_previous_sibling_and_sets_parent() {
+        let mut o = OutlineState::open(NOTE, Vec::new());
+        assert!(!o.indent(), "first block cannot indent");
+        o.cursor = 2;
+        assert!(o.indent());
+        assert_eq!(o.blocks()[2].indent, 1);
+        assert_eq!(o.blocks()[2].parent, Some(o.blocks()[0].id));
+        assert!(o.outdent());
+        assert_eq!(o.blocks()[2].parent, None);
+        assert!(!o.outdent());
+    }
+
+    #[test]
+    fn fold_hides_descendants_from_navigation() {
+        let mut o = OutlineState::open(NOTE, Vec::new());
+        o.toggle_fold();
+        assert_eq!(o.visible(), vec![0, 2, 3]);
+        o.cursor_next();
+        assert_eq!(o.cursor, 2);
+        assert!(!o.dirty, "folding is view-only");
+    }
+
+    #[test]
+    fn status_cycles_and_properties_round_trip() {
+        let mut o = OutlineState::open(NOTE, Vec::new());
+        o.cursor = 2;
+        o.toggle_status();
+        assert_eq!(o.property("status").as_deref(), Some("todo"));
+        o.toggle_status();
+        o.toggle_status();
+        o.toggle_status();
+        assert_eq!(o.property("status").as_deref(), Some("todo"));
+
+        o.set_property("priority", "high");
+        let reparsed = note_tree::parse_note(&o.content().unwrap());
+        assert_eq!(reparsed.blocks[2].text, "B\nstatus:: todo\npriority:: high");
+        o.set_property("status", "");
+        assert_eq!(o.property("status"), None);
+    }
+}
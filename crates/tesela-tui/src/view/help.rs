@@ -10,7 +10,7 @@ use crate::theme::icons;
 use crate::theme::DEFAULT as T;
 
 pub fn render(f: &mut Frame, area: Rect) {
-    let dialog = centered_rect(60, 22, area);
+    let dialog = centered_rect(60, 31, area);
     f.render_widget(Clear, dialog);
 
     let key_style = Style::default().fg(T.accent).add_modifier(Modifier::BOLD);
@@ -60,6 +60,34 @@ pub fn render(f: &mut Frame, area: Rect) {
             desc_style,
         ),
         key_desc("    /           ", "Search", key_style, desc_style),
+        Line::from(""),
+        Line::from(Span::styled("  Outliner (o)", section_style)),
+        key_desc(
+            "    Tab / S-Tab ",
+            "Indent / Outdent block",
+            key_style,
+            desc_style,
+        ),
+        key_desc(
+            "    J / K       ",
+            "Move block down / up",
+            key_style,
+            desc_style,
+        ),
+        key_desc("    Space / z   ", "Fold / Unfold", key_style, desc_style),
+        key_desc(
+            "    x           ",
+            "Cycle task status",
+            key_style,
+            desc_style,
+        ),
+        key_desc("    p           ", "Set property", key_style, desc_style),
+        key_desc(
+            "    Esc / ^C    ",
+            "Save / Discard and exit",
+            key_style,
+            desc_style,
+        ),
     ];
 
     let block = Block::default()
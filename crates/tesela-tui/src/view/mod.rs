@@ -5,6 +5,8 @@ pub mod listing;
 pub mod main_menu;
 pub mod new_note;
 pub mod note_preview;
+pub mod outline;
+pub mod property_picker;
 pub mod search;
 pub mod status_bar;
 pub mod tag_picker;
@@ -0,0 +1,86 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph},
+    Frame,
+};
+
+use crate::state::AppState;
+use crate::theme::DEFAULT as T;
+
+pub fn render(f: &mut Frame, area: Rect, state: &AppState) {
+    let outline = &state.outline;
+    let title = state
+        .current_note
+        .as_ref()
+        .map(|n| n.title.as_str())
+        .unwrap_or("Untitled");
+    let modified = if outline.dirty { " [+]" } else { "" };
+
+    let blocks = outline.blocks();
+    let mut lines: Vec<Line> = Vec::new();
+    let mut cursor_line = 0;
+    for i in outline.visible() {
+        let block = &blocks[i];
+        let is_cursor = i == outline.cursor;
+        if is_cursor {
+            cursor_line = lines.len();
+        }
+        let indent = "  ".repeat(block.indent as usize);
+        let bullet = if outline.folded.contains(&block.id) {
+            "▸ "
+        } else {
+            "• "
+        };
+        let mut text_lines = block.text.lines();
+        let first = text_lines.next().unwrap_or("");
+        let base = if is_cursor {
+            Style::default()
+                .fg(T.selection_fg)
+                .bg(T.selection_bg)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(T.text)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(indent.clone(), base),
+            Span::styled(bullet, base.fg(T.tree)),
+            Span::styled(first.to_string(), base),
+        ]));
+        // Continuation and `key:: value` lines sit under the bullet, dimmed.
+        for rest in text_lines {
+            lines.push(Line::from(Span::styled(
+                format!("{indent}  {}", rest.trim_start()),
+                Style::default().fg(T.text_dim),
+            )));
+        }
+    }
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "(no blocks)",
+            Style::default().fg(T.text_dim),
+        )));
+    }
+
+    // Keep the cursor block on screen.
+    let height = area.height.saturating_sub(2) as usize;
+    let scroll = cursor_line.saturating_sub(height.saturating_sub(1));
+
+    let block_widget = Block::default()
+        .title(format!(
+            " {} Outline: {}{} ",
+            crate::theme::icons::NOTE,
+            title,
+            modified
+        ))
+        .title_style(Style::default().fg(T.accent).add_modifier(Modifier::BOLD))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(T.accent));
+
+    let para = Paragraph::new(lines)
+        .block(block_widget)
+        .scroll((scroll as u16, 0));
+    f.render_widget(para, area);
+}
@@ -0,0 +1,85 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::state::{AppState, PropertyPickerStage};
+use crate::theme::DEFAULT as T;
+
+pub fn render(f: &mut Frame, area: Rect, state: &AppState) {
+    let picker = &state.property_picker;
+    let dialog = centered_rect(50, 18, area);
+    f.render_widget(Clear, dialog);
+
+    let title = match (picker.stage, &picker.property) {
+        (PropertyPickerStage::Value, Some(name)) => format!(" {name}:: "),
+        _ => " Set Property ".to_string(),
+    };
+    let outer = Block::default()
+        .title(title)
+        .title_style(Style::default().fg(T.accent).add_modifier(Modifier::BOLD))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(T.accent));
+    f.render_widget(outer, dialog);
+
+    let inner_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(1), Constraint::Min(1)])
+        .split(dialog);
+
+    // Query input line
+    let input_line = Line::from(vec![
+        Span::styled("> ", Style::default().fg(T.accent)),
+        Span::styled(picker.query.as_str(), Style::default().fg(T.text)),
+        Span::styled("█", Style::default().fg(T.accent)),
+    ]);
+    f.render_widget(Paragraph::new(input_line), inner_chunks[0]);
+
+    let items: Vec<ListItem> = if picker.filtered.is_empty() {
+        // Free text: Enter takes the query as typed (empty clears a value).
+        let hint = match picker.stage {
+            PropertyPickerStage::Name => "type a property name",
+            PropertyPickerStage::Value => "type a value (empty removes it)",
+        };
+        vec![ListItem::new(Span::styled(
+            hint,
+            Style::default()
+                .fg(T.text_dim)
+                .add_modifier(Modifier::ITALIC),
+        ))]
+    } else {
+        picker
+            .filtered
+            .iter()
+            .map(|o| ListItem::new(Span::styled(o.as_str(), Style::default().fg(T.text))))
+            .collect()
+    };
+
+    let list = List::new(items)
+        .highlight_style(
+            Style::default()
+                .fg(T.selection_fg)
+                .bg(T.selection_bg)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+
+    let mut list_state = ListState::default();
+    if !picker.filtered.is_empty() {
+        list_state.select(Some(picker.selected));
+    }
+
+    f.render_stateful_widget(list, inner_chunks[1], &mut list_state);
+}
+
+fn centered_rect(percent_x: u16, height: u16, area: Rect) -> Rect {
+    let width = (area.width * percent_x / 100).max(30).min(area.width);
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    Rect::new(x, y, width, height.min(area.height))
+}
@@ -8,6 +8,8 @@ pub fn render(f: &mut Frame, area: Rect, state: &AppState) {
         "?: close help  Esc: close help".to_string()
     } else if state.fuzzy.active {
         "↑↓/^j/^k: navigate  Enter: open  Esc: close".to_string()
+    } else if state.property_picker.active {
+        "↑↓/^j/^k: navigate  Enter: select  type to filter or enter a value  Esc: close".to_string()
     } else if state.tag_picker.active {
         "↑↓/^j/^k: navigate  Enter: select  type to filter  Esc: close".to_string()
     } else if state.confirm_delete.is_some() {
@@ -30,10 +32,14 @@ pub fn render(f: &mut Frame, area: Rect, state: &AppState) {
                     .to_string()
             }
             Mode::NoteView => {
-                "i: edit  e: $EDITOR  g: graph  D: delete  [/]: prev/next  j/k: scroll  Esc: back"
+                "i: edit  o: outline  e: $EDITOR  g: graph  D: delete  [/]: prev/next  j/k: scroll  Esc: back"
                     .to_string()
             }
             Mode::Editing => "Esc: save & exit  Ctrl+C: discard".to_string(),
+            Mode::Outliner => {
+                "j/k: move  Tab/S-Tab: indent  J/K: reorder  Space: fold  x: status  p: property  Esc: save  ^C: discard"
+                    .to_string()
+            }
             Mode::GraphView => "g: toggle  e: edit  j/k: scroll  Esc: back".to_string(),
             Mode::NewNote => "type title  Enter: create  Esc: cancel".to_string(),
         }
@@ -9,7 +9,7 @@ import type { Visibility } from "$lib/types/Visibility";
 // (`crates/tesela-core/src/property.rs`) — the ONE type list, spelled per
 // this language's convention (`multi-select` here vs Rust's `multiselect`;
 // `valueTypeBucket` in `query-language.ts` maps both spellings onto the same
-// semantic buckets). `datetime`/`node` exist for parity/typed-query use but
+// semantic buckets). `formula`/`rollup` exist for parity/typed-query use but
 // aren't yet offered in the Property-page type picker (`PropertyTypeConfig
 // .svelte`'s `ALL_TYPES`) — that's a separate product decision.
 export type PropertyType =
@@ -24,7 +24,9 @@ export type PropertyType =
   | "email"
   | "phone"
   | "object"
-  | "node";
+  | "node"
+  | "formula"
+  | "rollup";
 
 export const PROPERTY_TYPE_LABELS: Record<PropertyType, string> = {
   text: "Text",
@@ -39,6 +41,8 @@ export const PROPERTY_TYPE_LABELS: Record<PropertyType, string> = {
   phone: "Phone",
   object: "Object",
   node: "Node",
+  formula: "Formula",
+  rollup: "Rollup",
 };
 
 const PROPERTY_TYPE_VALUES = new Set<PropertyType>(Object.keys(PROPERTY_TYPE_LABELS) as PropertyType[]);
@@ -49,6 +53,12 @@ export function parsePropertyType(raw: unknown): PropertyType {
     : "text";
 }
 
+/** `formula` / `rollup` values are computed by the server index at query
+ * time and never stored — show them, but don't offer to edit them. */
+export function isComputedType(type: PropertyType | undefined): boolean {
+  return type === "formula" || type === "rollup";
+}
+
 /**
  * Phase 10.6 — chip-display config. Lives on the Property page so every
  * surface that pins this property as a chip (per-tag `display_chips`) gets
@@ -92,7 +92,7 @@ export type Token =
 export type Spanned = { tok: Token; start: number; end: number };
 
 function isWordChar(c: string): boolean {
-  return /[A-Za-z0-9_-]/.test(c);
+  return /[A-Za-z0-9_.-]/.test(c);
 }
 
 export function tokenize(input: string): Spanned[] {
@@ -525,6 +525,19 @@ function parsePredicate(p: ParserState): BoolExpr | null {
     return null;
   }
 
+  // Dotted traversal (`project.status:x`) and `has-backlink-from:` need
+  // the relation index only the Rust side loads; here the key falls back
+  // to a literal property lookup, so flag it for the author.
+  if (isRelationKey(key)) {
+    recordDrop(
+      p,
+      keySpanned.start,
+      keySpanned.end,
+      p.input.slice(keySpanned.start, keySpanned.end),
+      `unsupported predicate '${key}': relation traversal is only evaluated by the server`,
+    );
+  }
+
   // Legacy `tag-in:a,b,c` shape — equivalent to `tag IN (a, b, c)`.
   if (key.endsWith("-in") && peek(p)?.t === "colon") {
     bump(p); // ':'
@@ -674,6 +687,10 @@ function parsePredicate(p: ParserState): BoolExpr | null {
   return null;
 }
 
+function isRelationKey(key: string): boolean {
+  return key === "has-backlink-from" || key.includes(".");
+}
+
 function consumeInfixOp(p: ParserState): QueryOp | null {
   const t = peek(p);
   let op: QueryOp | null = null;
@@ -988,13 +1005,16 @@ function applyOp(actual: string, op: QueryOp, expected: string): boolean {
  * Map a `value_type` string onto the four comparison buckets. Both the
  * server `ValueType` vocabulary (`multiselect`, `node`, …) and the web
  * `PropertyType` vocabulary (`multi-select`, `email`, `phone`, `object`)
- * collapse here — only number / date-like / checkbox differ from the
- * default string bucket. Mirror of `query.rs:compare_typed`'s match arms.
+ * collapse here — only number / date-like / checkbox / computed differ from
+ * the default string bucket. Mirror of `query.rs:compare_typed`'s match arms.
  */
-function valueTypeBucket(vt: string): "number" | "date" | "checkbox" | "string" {
+function valueTypeBucket(vt: string): "number" | "date" | "checkbox" | "computed" | "string" {
   switch (vt.toLowerCase()) {
     case "number":
       return "number";
+    case "formula":
+    case "rollup":
+      return "computed";
     case "date":
     case "datetime":
       return "date";
@@ -1028,6 +1048,9 @@ function compareTyped(a: string, b: string, vt: string): number {
       const bl = asciiLower(b);
       return al < bl ? -1 : al > bl ? 1 : 0;
     }
+    case "computed":
+      // formula / rollup — a number or a date, so the untyped heuristic.
+      return compare(a, b);
     default: {
       // string bucket — NO numeric promotion (a select "10" stays text).
       const al = asciiLower(a);
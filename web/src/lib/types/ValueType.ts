@@ -10,4 +10,4 @@
  * Unknown strings degrade to `Text` (coerce-and-keep: validation is a view,
  * never a gate).
  */
-export type ValueType = "text" | "number" | "date" | "datetime" | "checkbox" | "url" | "select" | "multiselect" | "node" | "email" | "phone" | "object";
+export type ValueType = "text" | "number" | "date" | "datetime" | "checkbox" | "url" | "select" | "multiselect" | "node" | "email" | "phone" | "object" | "formula" | "rollup";
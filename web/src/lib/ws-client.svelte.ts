@@ -16,6 +16,8 @@ import {
 
 export type DeadlineApproachingEvent = {
   event: "deadline_approaching";
+  /** Stable id for `POST /notifications/{id}/ack` and `/snooze`. */
+  notification_id: string;
   block_id: string;
   title: string;
   note_id: string;
@@ -24,6 +26,7 @@ export type DeadlineApproachingEvent = {
 };
 export type ScheduledFiresEvent = {
   event: "scheduled_fires";
+  notification_id: string;
   block_id: string;
   title: string;
   note_id: string;
@@ -45,6 +48,15 @@ export type ViewsChangedEvent = {
   views: ViewRecord[];
 };
 
+/** A reminder was acknowledged or snoozed on some client; dismiss any
+ *  toast still showing for it. `snoozed_until` is set only for a snooze. */
+export type NotificationStateChangedEvent = {
+  event: "notification_state_changed";
+  notification_id: string;
+  status: "fired" | "acknowledged" | "snoozed";
+  snoozed_until: string | null;
+};
+
 type WsEvent =
   | { event: "note_created"; note: Note }
   | { event: "note_updated"; note: Note }
@@ -52,7 +64,8 @@ type WsEvent =
   | DeadlineApproachingEvent
   | ScheduledFiresEvent
   | RecurringRolledEvent
-  | ViewsChangedEvent;
+  | ViewsChangedEvent
+  | NotificationStateChangedEvent;
 
 // Same-origin path; vite dev server proxies `/ws` → tesela-server's WS at
 // 127.0.0.1:7474. Computed at runtime so LAN clients (phones, etc.) connect
@@ -83,6 +83,11 @@ test("all conformance cases pass through the real parser + matcher", () => {
       !c.expectDiagnostics || q.diagnostics.length > 0,
       `${c.name}: expected parser diagnostics`,
     );
+    assert.ok(
+      !c.clientDiagnostics ||
+        q.diagnostics.some((d) => d.startsWith("unsupported predicate")),
+      `${c.name}: expected an unsupported-predicate diagnostic`,
+    );
     const got = c.nodeContext
       ? blockMatchesWithContext(toParsedBlock(c.block), q, types, c.nodeContext).matched
       : blockMatches(toParsedBlock(c.block), q, types);